@@ -62,8 +62,10 @@ export const sidebar = [
                     { text: "Will Message", link: "/en/RobustMQ-MQTT/WillMessage" },
                     { text: "Exclusive Subscription", link: "/en/RobustMQ-MQTT/ExclusiveSubscription" },
                     { text: "Delayed Publishing", link: "/en/RobustMQ-MQTT/DelayMessage" },
+                    { text: "Scheduled Publishing", link: "/en/RobustMQ-MQTT/ScheduledPublish" },
                     { text: "Auto Subscription", link: "/en/RobustMQ-MQTT/AutoSubscription" },
                     { text: "Topic Rewrite", link: "/en/RobustMQ-MQTT/TopicRewrite" },
+                    { text: "Rule Engine", link: "/en/RobustMQ-MQTT/RuleEngine" },
                     { text: "Wildcard Subscription", link: "/en/RobustMQ-MQTT/WildcardSubscription" },
                     { text: "Session Persistence", link: "/en/RobustMQ-MQTT/SessionPersistence" },
                     { text: "System Alarm", link: "/en/RobustMQ-MQTT/SystemAlarm" },
@@ -87,6 +89,8 @@ export const sidebar = [
                     { text: "Local File", link: "/en/RobustMQ-MQTT/Bridge/LocalFile" },
                     { text: "Kafka", link: "/en/RobustMQ-MQTT/Bridge/Kafka" },
                     { text: "Pulsar", link: "/en/RobustMQ-MQTT/Bridge/Pulsar" },
+                    { text: "MQTT", link: "/en/RobustMQ-MQTT/Bridge/MQTT" },
+                    { text: "Webhook", link: "/en/RobustMQ-MQTT/Bridge/Webhook" },
                     { text: "GreptimeDB", link: "/en/RobustMQ-MQTT/Bridge/GreptimeDB" },
                 ]
             },
@@ -144,6 +148,7 @@ export const sidebar = [
       { text: "Overview", link: "/en/Api/COMMON" },
       { text: "Cluster API", link: "/en/Api/CLUSTER" },
       { text: "MQTT API", link: "/en/Api/MQTT" },
+      { text: "Journal API", link: "/en/Api/JOURNAL" },
     ],
   },
   {
@@ -154,6 +159,7 @@ export const sidebar = [
       { text: "Infrastructure Metrics", link: "/en/Observability/Infrastructure-Metrics" },
       { text: "MQTT Specific Metrics", link: "/en/Observability/MQTT-Specific-Metrics" },
       { text: "Grafana Configuration Guide", link: "/en/Observability/Grafana-Configuration-Guide" },
+      { text: "Distributed Tracing", link: "/en/Observability/Distributed-Tracing" },
     ],
   },
   {
@@ -62,8 +62,10 @@ export const sidebar = [
                     { text: "遗嘱消息", link: "/zh/RobustMQ-MQTT/WillMessage" },
                     { text: "排他订阅", link: "/zh/RobustMQ-MQTT/ExclusiveSubscription" },
                     { text: "延迟发布", link: "/zh/RobustMQ-MQTT/DelayMessage" },
+                    { text: "定时发布", link: "/zh/RobustMQ-MQTT/ScheduledPublish" },
                     { text: "自动订阅", link: "/zh/RobustMQ-MQTT/AutoSubscription" },
                     { text: "主题重写", link: "/zh/RobustMQ-MQTT/TopicRewrite" },
+                    { text: "规则引擎", link: "/zh/RobustMQ-MQTT/RuleEngine" },
                     { text: "通配符订阅", link: "/zh/RobustMQ-MQTT/WildcardSubscription" },
                     { text: "会话持久化", link: "/zh/RobustMQ-MQTT/SessionPersistence" },
                     { text: "系统告警", link: "/zh/RobustMQ-MQTT/SystemAlarm.md" },
@@ -87,6 +89,8 @@ export const sidebar = [
                     { text: "本地文件", link: "/zh/RobustMQ-MQTT/Bridge/LocalFile" },
                     { text: "Kafka", link: "/zh/RobustMQ-MQTT/Bridge/Kafka" },
                     { text: "Pulsar", link: "/zh/RobustMQ-MQTT/Bridge/Pulsar" },
+                    { text: "MQTT", link: "/zh/RobustMQ-MQTT/Bridge/MQTT" },
+                    { text: "Webhook", link: "/zh/RobustMQ-MQTT/Bridge/Webhook" },
                     { text: "GreptimeDB", link: "/zh/RobustMQ-MQTT/Bridge/GreptimeDB" },
                 ]
             },
@@ -155,6 +159,7 @@ export const sidebar = [
       { text: "概览", link: "/zh/Api/COMMON" },
       { text: "Cluster API", link: "/zh/Api/CLUSTER" },
       { text: "MQTT API", link: "/zh/Api/MQTT" },
+      { text: "Journal API", link: "/zh/Api/JOURNAL" },
     ],
   },
   {
@@ -165,6 +170,7 @@ export const sidebar = [
       { text: "基础设施指标", link: "/zh/Observability/基础设施指标" },
       { text: "MQTT 专用指标", link: "/zh/Observability/MQTT专用指标" },
       { text: "Grafana 配置指南", link: "/zh/Observability/Grafana配置指南" },
+      { text: "分布式追踪", link: "/zh/Observability/分布式追踪" },
     ],
   },
   {
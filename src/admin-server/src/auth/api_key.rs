@@ -0,0 +1,141 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{
+    middleware::AdminIdentity,
+    role::AdminRole,
+    storage::{hash_api_key_secret, AdminApiKey, AdminStorage},
+};
+use crate::{
+    request::admin::{ApiKeyListReq, CreateApiKeyReq, DeleteApiKeyReq},
+    response::{
+        admin::{ApiKeyListRow, CreateApiKeyResp},
+        PageReplyData,
+    },
+    state::HttpState,
+    tool::query::{apply_filters, apply_pagination, apply_sorting, build_query_params, Queryable},
+};
+use axum::{extract::State, Extension, Json};
+use common_base::{
+    http_response::{error_response, success_response},
+    tools::{now_second, unique_id},
+};
+use std::{str::FromStr, sync::Arc};
+
+pub async fn api_key_list(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<ApiKeyListReq>,
+) -> String {
+    let options = build_query_params(
+        params.page,
+        params.limit,
+        params.sort_field,
+        params.sort_by,
+        params.filter_field,
+        params.filter_values,
+        params.exact_match,
+    );
+
+    let storage = AdminStorage::new(state.client_pool.clone());
+    let data = match storage.list_api_key().await {
+        Ok(data) => data,
+        Err(e) => {
+            return error_response(e.to_string());
+        }
+    };
+
+    let keys = data
+        .into_iter()
+        .map(|key| ApiKeyListRow {
+            id: key.id,
+            name: key.name,
+            role: key.role.to_string(),
+            create_by: key.create_by,
+            create_time: key.create_time,
+        })
+        .collect();
+
+    let filtered = apply_filters(keys, &options);
+    let sorted = apply_sorting(filtered, &options);
+    let pagination = apply_pagination(sorted, &options);
+
+    success_response(PageReplyData {
+        data: pagination.0,
+        total_count: pagination.1,
+    })
+}
+
+impl Queryable for ApiKeyListRow {
+    fn get_field_str(&self, field: &str) -> Option<String> {
+        match field {
+            "id" => Some(self.id.clone()),
+            "name" => Some(self.name.clone()),
+            "role" => Some(self.role.clone()),
+            "create_by" => Some(self.create_by.clone()),
+            _ => None,
+        }
+    }
+}
+
+pub async fn api_key_create(
+    State(state): State<Arc<HttpState>>,
+    Extension(identity): Extension<AdminIdentity>,
+    Json(params): Json<CreateApiKeyReq>,
+) -> String {
+    if params.name.is_empty() {
+        return error_response("API key name cannot be empty".to_string());
+    }
+
+    let role = match AdminRole::from_str(&params.role) {
+        Ok(role) => role,
+        Err(e) => return error_response(e.to_string()),
+    };
+
+    let id = unique_id();
+    let secret = format!("{}{}", unique_id(), unique_id());
+    let api_key = AdminApiKey {
+        id: id.clone(),
+        name: params.name,
+        secret_hash: hash_api_key_secret(&secret),
+        role,
+        create_by: identity.username,
+        create_time: now_second(),
+    };
+
+    let storage = AdminStorage::new(state.client_pool.clone());
+    match storage.save_api_key(&api_key).await {
+        Ok(_) => success_response(CreateApiKeyResp {
+            api_key: format!("{id}.{secret}"),
+            id,
+        }),
+        Err(e) => error_response(e.to_string()),
+    }
+}
+
+pub async fn api_key_delete(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<DeleteApiKeyReq>,
+) -> String {
+    let storage = AdminStorage::new(state.client_pool.clone());
+    match storage.get_api_key(&params.id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return error_response(format!("API key {} does not exist", params.id)),
+        Err(e) => return error_response(e.to_string()),
+    }
+
+    match storage.delete_api_key(&params.id).await {
+        Ok(_) => success_response("success"),
+        Err(e) => error_response(e.to_string()),
+    }
+}
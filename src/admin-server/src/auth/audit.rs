@@ -0,0 +1,229 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    request::admin::AuditLogListReq,
+    response::PageReplyData,
+    state::HttpState,
+    tool::query::{apply_filters, apply_pagination, apply_sorting, build_query_params, Queryable},
+};
+use axum::{extract::State, http::StatusCode, Json};
+use broker_core::{
+    engine::{engine_prefix_list_by_broker, engine_save_by_broker},
+    rocksdb::RocksDBEngine,
+};
+use common_base::{
+    error::common::CommonError,
+    http_response::{error_response, success_response},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+
+// Fields of a request body that are never written to the audit log.
+const SENSITIVE_FIELDS: [&str; 4] = ["password", "secret", "token", "api_key"];
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AuditLog {
+    pub id: String,
+    pub username: String,
+    pub role: String,
+    pub method: String,
+    pub path: String,
+    pub body: String,
+    pub client_ip: String,
+    pub status_code: u16,
+    // Handlers report errors with a 200 status and a non-zero code in the body
+    #[serde(default)]
+    pub success: bool,
+    pub duration_ms: u64,
+    // milliseconds
+    pub create_time: u64,
+}
+
+impl Queryable for AuditLog {
+    fn get_field_str(&self, field: &str) -> Option<String> {
+        match field {
+            "username" => Some(self.username.clone()),
+            "role" => Some(self.role.clone()),
+            "path" => Some(self.path.clone()),
+            "client_ip" => Some(self.client_ip.clone()),
+            "status_code" => Some(self.status_code.to_string()),
+            "success" => Some(self.success.to_string()),
+            "create_time" => Some(format!("{:020}", self.create_time)),
+            _ => None,
+        }
+    }
+}
+
+fn audit_log_prefix_key() -> String {
+    "/admin/audit-log/".to_string()
+}
+
+fn audit_log_key(log: &AuditLog) -> String {
+    format!(
+        "{}{:020}/{}",
+        audit_log_prefix_key(),
+        log.create_time,
+        log.id
+    )
+}
+
+/// The audit log is kept in the local rocksdb of the broker that served the call.
+pub struct AuditLogStorage {
+    rocksdb_engine_handler: Arc<RocksDBEngine>,
+}
+
+impl AuditLogStorage {
+    pub fn new(rocksdb_engine_handler: Arc<RocksDBEngine>) -> Self {
+        AuditLogStorage {
+            rocksdb_engine_handler,
+        }
+    }
+
+    pub fn save(&self, log: AuditLog) -> Result<(), CommonError> {
+        let key = audit_log_key(&log);
+        engine_save_by_broker(self.rocksdb_engine_handler.clone(), key, log)
+    }
+
+    pub fn list(&self) -> Result<Vec<AuditLog>, CommonError> {
+        let mut results = Vec::new();
+        for raw in engine_prefix_list_by_broker(
+            self.rocksdb_engine_handler.clone(),
+            audit_log_prefix_key(),
+        )? {
+            if let Ok(data) = serde_json::from_str::<AuditLog>(&raw.data) {
+                results.push(data);
+            }
+        }
+        Ok(results)
+    }
+}
+
+/// Mask the sensitive fields of a json request body, bodies that are not json are dropped.
+pub fn redact_body(body: &[u8]) -> String {
+    if body.is_empty() {
+        return String::new();
+    }
+    let Ok(mut value) = serde_json::from_slice::<Value>(body) else {
+        return format!("<{} bytes>", body.len());
+    };
+    redact_value(&mut value);
+    value.to_string()
+}
+
+// A call succeeded when the status is 2xx and the body, if it is an admin
+// response, carries code 0.
+pub fn response_succeeded(status: StatusCode, body: &[u8]) -> bool {
+    if !status.is_success() {
+        return false;
+    }
+    match serde_json::from_slice::<Value>(body) {
+        Ok(value) => value.get("code").and_then(Value::as_u64).unwrap_or(0) == 0,
+        Err(_) => true,
+    }
+}
+
+fn redact_value(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                let key = key.to_lowercase();
+                if SENSITIVE_FIELDS.iter().any(|name| key.contains(name)) {
+                    *field = Value::String("******".to_string());
+                } else {
+                    redact_value(field);
+                }
+            }
+        }
+        Value::Array(list) => list.iter_mut().for_each(redact_value),
+        _ => {}
+    }
+}
+
+pub async fn audit_log_list(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<AuditLogListReq>,
+) -> String {
+    let options = build_query_params(
+        params.page,
+        params.limit,
+        params.sort_field,
+        params.sort_by,
+        params.filter_field,
+        params.filter_values,
+        params.exact_match,
+    );
+
+    let storage = AuditLogStorage::new(state.rocksdb_engine_handler.clone());
+    let mut logs = match storage.list() {
+        Ok(data) => data,
+        Err(e) => {
+            return error_response(e.to_string());
+        }
+    };
+
+    logs.retain(|log| {
+        params
+            .start_time
+            .is_none_or(|start| log.create_time >= start)
+            && params.end_time.is_none_or(|end| log.create_time <= end)
+    });
+    // newest first unless the caller asks for another order
+    logs.reverse();
+
+    let filtered = apply_filters(logs, &options);
+    let sorted = apply_sorting(filtered, &options);
+    let pagination = apply_pagination(sorted, &options);
+
+    success_response(PageReplyData {
+        data: pagination.0,
+        total_count: pagination.1,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redact_body_test() {
+        let body = br#"{"username":"u1","password":"p1","config":{"api_key":"k","port":1}}"#;
+        let value: Value = serde_json::from_str(&redact_body(body)).unwrap();
+        assert_eq!(value["username"], "u1");
+        assert_eq!(value["password"], "******");
+        assert_eq!(value["config"]["api_key"], "******");
+        assert_eq!(value["config"]["port"], 1);
+
+        assert_eq!(redact_body(b""), "");
+        assert_eq!(redact_body(b"not json"), "<8 bytes>");
+    }
+
+    #[test]
+    fn response_succeeded_test() {
+        assert!(response_succeeded(
+            StatusCode::OK,
+            success_response("success").as_bytes()
+        ));
+        assert!(!response_succeeded(
+            StatusCode::OK,
+            error_response("rule not found".to_string()).as_bytes()
+        ));
+        assert!(!response_succeeded(
+            StatusCode::FORBIDDEN,
+            success_response("success").as_bytes()
+        ));
+        assert!(response_succeeded(StatusCode::OK, b""));
+    }
+}
@@ -0,0 +1,322 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{
+    audit::{redact_body, response_succeeded, AuditLog, AuditLogStorage},
+    role::{is_mutating_path, required_role, AdminRole},
+    storage::{parse_api_key, AdminStorage},
+};
+use crate::{path::API_PREFIX, server::extract_client_ip, state::HttpState};
+use axum::{
+    body::{to_bytes, Body, HttpBody},
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use common_base::{
+    http_response::error_response,
+    tools::{now_mills, unique_id},
+};
+use common_config::broker::broker_config;
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::Instant,
+};
+use tracing::warn;
+
+pub const AUTHORIZATION_HEADER: &str = "authorization";
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+// Request bodies larger than this are rejected on audited paths.
+const MAX_AUDIT_BODY_SIZE: usize = 4 * 1024 * 1024;
+
+// Responses larger than this are not read to find out whether the call succeeded.
+const MAX_AUDIT_RESPONSE_SIZE: usize = 1024 * 1024;
+
+/// The caller of the admin API, added to the extensions of every request that
+/// passed the auth middleware.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AdminIdentity {
+    pub username: String,
+    pub role: AdminRole,
+}
+
+impl AdminIdentity {
+    // Used for every call when auth is disabled, and for public paths.
+    fn anonymous(role: AdminRole) -> Self {
+        AdminIdentity {
+            username: "anonymous".to_string(),
+            role,
+        }
+    }
+}
+
+enum AuthOutcome {
+    Allowed(AdminIdentity),
+    Rejected(StatusCode, String, Option<AdminIdentity>),
+}
+
+/// Authenticates the caller, checks the role required by the path and writes
+/// mutating calls into the audit log.
+pub async fn auth_middleware(
+    State(state): State<Arc<HttpState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let start = Instant::now();
+    let path = request
+        .uri()
+        .path()
+        .strip_prefix(API_PREFIX)
+        .unwrap_or(request.uri().path())
+        .to_string();
+
+    let outcome = authorize(&state, &path, request.headers()).await;
+
+    if !is_mutating_path(&path) {
+        return match outcome {
+            AuthOutcome::Allowed(identity) => {
+                let mut request = request;
+                request.extensions_mut().insert(identity);
+                next.run(request).await
+            }
+            AuthOutcome::Rejected(status, message, _) => {
+                (status, error_response(message)).into_response()
+            }
+        };
+    }
+
+    let method = request.method().to_string();
+    let client_ip = audit_client_ip(
+        request.headers(),
+        addr,
+        &broker_config().admin_server.trusted_proxies,
+    );
+    let (parts, body) = request.into_parts();
+    let body = match to_bytes(body, MAX_AUDIT_BODY_SIZE).await {
+        Ok(body) => body,
+        Err(e) => {
+            return (StatusCode::PAYLOAD_TOO_LARGE, error_response(e.to_string())).into_response();
+        }
+    };
+
+    let (identity, response) = match outcome {
+        AuthOutcome::Allowed(identity) => {
+            let mut request = Request::from_parts(parts, Body::from(body.clone()));
+            request.extensions_mut().insert(identity.clone());
+            (Some(identity), next.run(request).await)
+        }
+        AuthOutcome::Rejected(status, message, identity) => {
+            (identity, (status, error_response(message)).into_response())
+        }
+    };
+
+    let (response, success) = response_outcome(response).await;
+
+    let log = AuditLog {
+        id: unique_id(),
+        username: identity
+            .as_ref()
+            .map(|identity| identity.username.clone())
+            .unwrap_or_default(),
+        role: identity
+            .as_ref()
+            .map(|identity| identity.role.to_string())
+            .unwrap_or_default(),
+        method,
+        path,
+        body: redact_body(&body),
+        client_ip,
+        status_code: response.status().as_u16(),
+        success,
+        duration_ms: start.elapsed().as_millis() as u64,
+        create_time: now_mills() as u64,
+    };
+    let storage = AuditLogStorage::new(state.rocksdb_engine_handler.clone());
+    if let Err(e) = storage.save(log) {
+        warn!("Failed to save admin audit log, error message: {}", e);
+    }
+
+    response
+}
+
+// Handlers report errors in the body, so small bodies of a known size are
+// buffered to read the outcome. Streams such as server-sent events are passed
+// through untouched and judged by the status alone.
+async fn response_outcome(response: Response) -> (Response, bool) {
+    let is_stream = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("text/event-stream"));
+    let buffered = !is_stream
+        && response
+            .body()
+            .size_hint()
+            .exact()
+            .is_some_and(|size| size <= MAX_AUDIT_RESPONSE_SIZE as u64);
+    if !buffered {
+        let success = response.status().is_success();
+        return (response, success);
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let body = match to_bytes(body, MAX_AUDIT_RESPONSE_SIZE).await {
+        Ok(body) => body,
+        Err(e) => {
+            parts.status = StatusCode::INTERNAL_SERVER_ERROR;
+            parts.headers.remove(header::CONTENT_LENGTH);
+            error_response(e.to_string()).into()
+        }
+    };
+    let success = response_succeeded(parts.status, &body);
+    (Response::from_parts(parts, Body::from(body)), success)
+}
+
+// Forwarding headers can be set by any caller, so they are only believed when
+// the connection comes from one of the configured proxies.
+fn audit_client_ip(headers: &HeaderMap, addr: SocketAddr, trusted_proxies: &[String]) -> String {
+    let trusted = trusted_proxies
+        .iter()
+        .filter_map(|proxy| proxy.parse::<IpAddr>().ok())
+        .any(|proxy| proxy == addr.ip());
+    if trusted {
+        extract_client_ip(headers, addr)
+    } else {
+        addr.ip().to_string()
+    }
+}
+
+async fn authorize(state: &Arc<HttpState>, path: &str, headers: &HeaderMap) -> AuthOutcome {
+    let required = required_role(path);
+    if !broker_config().admin_server.auth_enable {
+        return AuthOutcome::Allowed(AdminIdentity::anonymous(AdminRole::Admin));
+    }
+
+    let Some(required) = required else {
+        return AuthOutcome::Allowed(AdminIdentity::anonymous(AdminRole::Viewer));
+    };
+
+    let identity = match authenticate(state, headers).await {
+        Ok(identity) => identity,
+        Err(message) => return AuthOutcome::Rejected(StatusCode::UNAUTHORIZED, message, None),
+    };
+
+    if identity.role < required {
+        return AuthOutcome::Rejected(
+            StatusCode::FORBIDDEN,
+            format!(
+                "Permission denied, {path} requires role {required}, user {} has role {}",
+                identity.username, identity.role
+            ),
+            Some(identity),
+        );
+    }
+
+    AuthOutcome::Allowed(identity)
+}
+
+async fn authenticate(
+    state: &Arc<HttpState>,
+    headers: &HeaderMap,
+) -> Result<AdminIdentity, String> {
+    if let Some(token) = bearer_token(headers) {
+        return match state.session_manager.get(token) {
+            Some(session) => Ok(AdminIdentity {
+                username: session.username,
+                role: session.role,
+            }),
+            None => Err("Session token is invalid or expired, please login again".to_string()),
+        };
+    }
+
+    if let Some(api_key) = headers.get(API_KEY_HEADER).and_then(|h| h.to_str().ok()) {
+        let Some((id, secret)) = parse_api_key(api_key) else {
+            return Err("Malformed API key".to_string());
+        };
+        let storage = AdminStorage::new(state.client_pool.clone());
+        return match storage.get_api_key(id).await {
+            Ok(Some(key)) if key.verify_secret(secret) => Ok(AdminIdentity {
+                username: format!("api-key:{}", key.name),
+                role: key.role,
+            }),
+            Ok(_) => Err("API key is invalid".to_string()),
+            Err(e) => Err(format!("Failed to verify API key, error message: {e}")),
+        };
+    }
+
+    Err(format!(
+        "Authentication required, provide a session token in the {AUTHORIZATION_HEADER} header or an API key in the {API_KEY_HEADER} header"
+    ))
+}
+
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(AUTHORIZATION_HEADER)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| token.trim())
+        .filter(|token| !token.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::response::sse::{Event, Sse};
+    use common_base::http_response::success_response;
+    use futures::stream;
+    use std::convert::Infallible;
+
+    #[test]
+    fn audit_client_ip_test() {
+        let addr: SocketAddr = "10.0.0.1:8080".parse().unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", "1.2.3.4".parse().unwrap());
+
+        assert_eq!(audit_client_ip(&headers, addr, &[]), "10.0.0.1");
+        assert_eq!(
+            audit_client_ip(&headers, addr, &["10.0.0.2".to_string()]),
+            "10.0.0.1"
+        );
+        assert_eq!(
+            audit_client_ip(&headers, addr, &["10.0.0.1".to_string()]),
+            "1.2.3.4"
+        );
+    }
+
+    #[tokio::test]
+    async fn response_outcome_test() {
+        let (response, success) =
+            response_outcome(success_response("success").into_response()).await;
+        assert!(success);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(body, success_response("success").as_bytes());
+
+        let (_, success) =
+            response_outcome(error_response("failed".to_string()).into_response()).await;
+        assert!(!success);
+
+        // A stream that never ends must be handed back without being read
+        let events = stream::pending::<Result<Event, Infallible>>();
+        let (response, success) = response_outcome(Sse::new(events).into_response()).await;
+        assert!(success);
+        assert_eq!(
+            response.headers().get(header::CONTENT_TYPE).unwrap(),
+            "text/event-stream"
+        );
+    }
+}
@@ -0,0 +1,51 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+pub mod api_key;
+pub mod audit;
+pub mod middleware;
+pub mod role;
+pub mod session;
+pub mod storage;
+pub mod user;
+
+use common_base::{error::common::CommonError, tools::now_second};
+use common_config::broker::broker_config;
+use grpc_clients::pool::ClientPool;
+use role::AdminRole;
+use std::sync::Arc;
+use storage::{AdminStorage, AdminUser};
+use tracing::warn;
+
+/// Create the configured default admin user when no admin user exists yet.
+pub async fn init_default_admin_user(client_pool: Arc<ClientPool>) -> Result<(), CommonError> {
+    let config = &broker_config().admin_server;
+    let storage = AdminStorage::new(client_pool);
+    if !storage.list_user().await?.is_empty() {
+        return Ok(());
+    }
+
+    let user = AdminUser::new(
+        &config.default_username,
+        &config.default_password,
+        AdminRole::Admin,
+        now_second(),
+    )?;
+    storage.save_user(&user).await?;
+    warn!(
+        "Created the default admin user {}, change its password as soon as possible",
+        user.username
+    );
+    Ok(())
+}
@@ -0,0 +1,186 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::path::*;
+use common_base::error::common::CommonError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Roles of the admin API, each role includes the permissions of the roles below it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AdminRole {
+    // Read only access
+    Viewer,
+    // Day to day operations: connectors, listeners, schemas, topic rewrite ...
+    Operator,
+    // Security sensitive changes: cluster config, users, acl, blacklist, admin accounts
+    Admin,
+}
+
+impl fmt::Display for AdminRole {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            AdminRole::Viewer => "viewer",
+            AdminRole::Operator => "operator",
+            AdminRole::Admin => "admin",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for AdminRole {
+    type Err = CommonError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "viewer" => Ok(AdminRole::Viewer),
+            "operator" => Ok(AdminRole::Operator),
+            "admin" => Ok(AdminRole::Admin),
+            _ => Err(CommonError::CommonError(format!(
+                "Unsupported admin role {s}, available roles are viewer, operator and admin"
+            ))),
+        }
+    }
+}
+
+// Paths that can be called without credentials.
+const PUBLIC_PATHS: [&str; 2] = [STATUS_PATH, AUTH_LOGIN_PATH];
+
+// Paths that need the admin role even though they are not under /admin.
+const ADMIN_PATHS: [&str; 10] = [
+    CLUSTER_CONFIG_SET_PATH,
+    MQTT_USER_CREATE_PATH,
+    MQTT_USER_DELETE_PATH,
+    MQTT_ACL_CREATE_PATH,
+    MQTT_ACL_DELETE_PATH,
+    MQTT_BLACKLIST_CREATE_PATH,
+    MQTT_BLACKLIST_DELETE_PATH,
+    MQTT_LISTENER_CREATE_PATH,
+    MQTT_LISTENER_DELETE_PATH,
+    JOURNAL_SHARD_DELETE_PATH,
+];
+
+// The last path segments of read only endpoints.
+const READ_SEGMENTS: [&str; 7] = [
+    "list", "get", "detail", "overview", "metrics", "history", "trace",
+];
+
+/// The role required to call a path of the admin API, `None` if the path is public.
+///
+/// The path is the one matched by the router, without the `/api` prefix.
+pub fn required_role(path: &str) -> Option<AdminRole> {
+    if PUBLIC_PATHS.contains(&path) {
+        return None;
+    }
+
+    if path == AUTH_LOGOUT_PATH {
+        return Some(AdminRole::Viewer);
+    }
+
+    if ADMIN_PATHS.contains(&path) || path.starts_with("/admin/") {
+        return Some(AdminRole::Admin);
+    }
+
+    let last_segment = path.rsplit('/').next().unwrap_or_default();
+    if READ_SEGMENTS.contains(&last_segment) {
+        return Some(AdminRole::Viewer);
+    }
+
+    Some(AdminRole::Operator)
+}
+
+/// Whether calls to the path change the state of the cluster and go into the audit log.
+pub fn is_mutating_path(path: &str) -> bool {
+    match required_role(path) {
+        Some(role) => role > AdminRole::Viewer,
+        None => path == AUTH_LOGIN_PATH,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn admin_role_test() {
+        assert!(AdminRole::Admin > AdminRole::Operator);
+        assert!(AdminRole::Operator > AdminRole::Viewer);
+        assert_eq!(AdminRole::from_str("Admin").unwrap(), AdminRole::Admin);
+        assert!(AdminRole::from_str("root").is_err());
+        assert_eq!(AdminRole::Operator.to_string(), "operator");
+    }
+
+    #[test]
+    fn required_role_test() {
+        assert_eq!(required_role(STATUS_PATH), None);
+        assert_eq!(required_role(AUTH_LOGIN_PATH), None);
+        assert_eq!(required_role(AUTH_LOGOUT_PATH), Some(AdminRole::Viewer));
+
+        assert_eq!(required_role(MQTT_ACL_LIST_PATH), Some(AdminRole::Viewer));
+        assert_eq!(required_role(MQTT_OVERVIEW_PATH), Some(AdminRole::Viewer));
+        assert_eq!(
+            required_role(MQTT_OVERVIEW_METRICS_PATH),
+            Some(AdminRole::Viewer)
+        );
+        assert_eq!(
+            required_role(CLUSTER_CONFIG_GET_PATH),
+            Some(AdminRole::Viewer)
+        );
+
+        assert_eq!(
+            required_role(MQTT_CONNECTOR_CREATE_PATH),
+            Some(AdminRole::Operator)
+        );
+        assert_eq!(
+            required_role(MQTT_LISTENER_STOP_PATH),
+            Some(AdminRole::Operator)
+        );
+
+        assert_eq!(required_role(MQTT_ACL_DELETE_PATH), Some(AdminRole::Admin));
+        assert_eq!(
+            required_role(JOURNAL_SHARD_DELETE_PATH),
+            Some(AdminRole::Admin)
+        );
+        assert_eq!(
+            required_role(JOURNAL_SEGMENT_SEAL_PATH),
+            Some(AdminRole::Operator)
+        );
+        assert_eq!(
+            required_role(JOURNAL_GROUP_OFFSET_LIST_PATH),
+            Some(AdminRole::Viewer)
+        );
+        assert_eq!(
+            required_role(CLUSTER_CONFIG_SET_PATH),
+            Some(AdminRole::Admin)
+        );
+        assert_eq!(
+            required_role(ADMIN_AUDIT_LOG_LIST_PATH),
+            Some(AdminRole::Admin)
+        );
+
+        assert_eq!(
+            required_role(MQTT_SCHEDULED_PUBLISH_HISTORY_PATH),
+            Some(AdminRole::Viewer)
+        );
+        assert_eq!(required_role(MQTT_TRACE_PATH), Some(AdminRole::Viewer));
+
+        assert!(is_mutating_path(MQTT_ACL_DELETE_PATH));
+        assert!(is_mutating_path(AUTH_LOGIN_PATH));
+        assert!(!is_mutating_path(MQTT_ACL_LIST_PATH));
+        assert!(!is_mutating_path(MQTT_TRACE_PATH));
+        assert!(!is_mutating_path(STATUS_PATH));
+    }
+}
@@ -0,0 +1,110 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::role::AdminRole;
+use common_base::tools::{now_second, unique_id};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AdminSession {
+    pub token: String,
+    pub username: String,
+    pub role: AdminRole,
+    pub create_time: u64,
+    pub expire_time: u64,
+}
+
+/// Login sessions of the admin API.
+///
+/// Sessions only live in the memory of the node that issued them, a restart
+/// or a request to another node needs a new login. API keys are the way to go
+/// for scripts and the CLI.
+pub struct SessionManager {
+    // (token, AdminSession)
+    sessions: DashMap<String, AdminSession>,
+    expire_sec: u64,
+}
+
+impl SessionManager {
+    pub fn new(expire_sec: u64) -> Self {
+        SessionManager {
+            sessions: DashMap::with_capacity(8),
+            expire_sec,
+        }
+    }
+
+    pub fn create(&self, username: &str, role: AdminRole) -> AdminSession {
+        self.remove_expired();
+        let now = now_second();
+        let session = AdminSession {
+            token: format!("{}{}", unique_id(), unique_id()),
+            username: username.to_string(),
+            role,
+            create_time: now,
+            expire_time: now + self.expire_sec,
+        };
+        self.sessions.insert(session.token.clone(), session.clone());
+        session
+    }
+
+    pub fn get(&self, token: &str) -> Option<AdminSession> {
+        let session = self.sessions.get(token)?.clone();
+        if session.expire_time <= now_second() {
+            self.sessions.remove(token);
+            return None;
+        }
+        Some(session)
+    }
+
+    pub fn remove(&self, token: &str) {
+        self.sessions.remove(token);
+    }
+
+    pub fn remove_by_user(&self, username: &str) {
+        self.sessions
+            .retain(|_, session| session.username != username);
+    }
+
+    fn remove_expired(&self) {
+        let now = now_second();
+        self.sessions.retain(|_, session| session.expire_time > now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_manager_test() {
+        let manager = SessionManager::new(3600);
+        let session = manager.create("u1", AdminRole::Operator);
+        assert_eq!(manager.get(&session.token).unwrap(), session);
+        assert!(manager.get("unknown").is_none());
+
+        manager.remove(&session.token);
+        assert!(manager.get(&session.token).is_none());
+
+        let s1 = manager.create("u1", AdminRole::Operator);
+        let s2 = manager.create("u2", AdminRole::Viewer);
+        manager.remove_by_user("u1");
+        assert!(manager.get(&s1.token).is_none());
+        assert!(manager.get(&s2.token).is_some());
+
+        let expired = SessionManager::new(0);
+        let session = expired.create("u1", AdminRole::Admin);
+        assert!(expired.get(&session.token).is_none());
+    }
+}
@@ -0,0 +1,227 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::role::AdminRole;
+use common_base::error::common::CommonError;
+use common_config::broker::broker_config;
+use grpc_clients::meta::kv::call::{
+    placement_delete, placement_get, placement_get_prefix, placement_set,
+};
+use grpc_clients::pool::ClientPool;
+use protocol::meta::meta_service_kv::{DeleteRequest, GetPrefixRequest, GetRequest, SetRequest};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AdminUser {
+    pub username: String,
+    // bcrypt hash of the password
+    pub password: String,
+    pub role: AdminRole,
+    pub create_time: u64,
+}
+
+impl AdminUser {
+    pub fn new(
+        username: &str,
+        password: &str,
+        role: AdminRole,
+        create_time: u64,
+    ) -> Result<Self, CommonError> {
+        Ok(AdminUser {
+            username: username.to_string(),
+            password: hash_password(password)?,
+            role,
+            create_time,
+        })
+    }
+
+    pub fn verify_password(&self, password: &str) -> bool {
+        bcrypt::verify(password, &self.password).unwrap_or(false)
+    }
+}
+
+/// An API key is handed out once as `{id}.{secret}`, only the sha256 of the
+/// secret is stored.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AdminApiKey {
+    pub id: String,
+    pub name: String,
+    pub secret_hash: String,
+    pub role: AdminRole,
+    pub create_by: String,
+    pub create_time: u64,
+}
+
+impl AdminApiKey {
+    pub fn verify_secret(&self, secret: &str) -> bool {
+        hash_api_key_secret(secret) == self.secret_hash
+    }
+}
+
+pub fn hash_password(password: &str) -> Result<String, CommonError> {
+    bcrypt::hash(password, bcrypt::DEFAULT_COST)
+        .map_err(|e| CommonError::CommonError(e.to_string()))
+}
+
+pub fn hash_api_key_secret(secret: &str) -> String {
+    hex::encode(Sha256::digest(secret.as_bytes()))
+}
+
+/// Split an API key into its id and secret.
+pub fn parse_api_key(api_key: &str) -> Option<(&str, &str)> {
+    let (id, secret) = api_key.split_once('.')?;
+    if id.is_empty() || secret.is_empty() {
+        return None;
+    }
+    Some((id, secret))
+}
+
+fn admin_user_prefix_key() -> String {
+    "/admin/user/".to_string()
+}
+
+fn admin_user_key(username: &str) -> String {
+    format!("{}{}", admin_user_prefix_key(), username)
+}
+
+fn admin_api_key_prefix_key() -> String {
+    "/admin/api-key/".to_string()
+}
+
+fn admin_api_key_key(id: &str) -> String {
+    format!("{}{}", admin_api_key_prefix_key(), id)
+}
+
+/// Admin users and API keys, kept in the meta service so that every broker
+/// of the cluster shares them.
+pub struct AdminStorage {
+    client_pool: Arc<ClientPool>,
+}
+
+impl AdminStorage {
+    pub fn new(client_pool: Arc<ClientPool>) -> Self {
+        AdminStorage { client_pool }
+    }
+
+    pub async fn list_user(&self) -> Result<Vec<AdminUser>, CommonError> {
+        self.get_prefix(admin_user_prefix_key()).await
+    }
+
+    pub async fn get_user(&self, username: &str) -> Result<Option<AdminUser>, CommonError> {
+        self.get(admin_user_key(username)).await
+    }
+
+    pub async fn save_user(&self, user: &AdminUser) -> Result<(), CommonError> {
+        self.set(admin_user_key(&user.username), user).await
+    }
+
+    pub async fn delete_user(&self, username: &str) -> Result<(), CommonError> {
+        self.delete(admin_user_key(username)).await
+    }
+
+    pub async fn list_api_key(&self) -> Result<Vec<AdminApiKey>, CommonError> {
+        self.get_prefix(admin_api_key_prefix_key()).await
+    }
+
+    pub async fn get_api_key(&self, id: &str) -> Result<Option<AdminApiKey>, CommonError> {
+        self.get(admin_api_key_key(id)).await
+    }
+
+    pub async fn save_api_key(&self, api_key: &AdminApiKey) -> Result<(), CommonError> {
+        self.set(admin_api_key_key(&api_key.id), api_key).await
+    }
+
+    pub async fn delete_api_key(&self, id: &str) -> Result<(), CommonError> {
+        self.delete(admin_api_key_key(id)).await
+    }
+
+    async fn get<T: DeserializeOwned>(&self, key: String) -> Result<Option<T>, CommonError> {
+        let config = broker_config();
+        let reply = placement_get(
+            &self.client_pool,
+            &config.get_meta_service_addr(),
+            GetRequest { key },
+        )
+        .await?;
+        if reply.value.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str::<T>(&reply.value)?))
+    }
+
+    async fn get_prefix<T: DeserializeOwned>(&self, prefix: String) -> Result<Vec<T>, CommonError> {
+        let config = broker_config();
+        let reply = placement_get_prefix(
+            &self.client_pool,
+            &config.get_meta_service_addr(),
+            GetPrefixRequest { prefix },
+        )
+        .await?;
+        let mut results = Vec::with_capacity(reply.values.len());
+        for value in reply.values {
+            results.push(serde_json::from_str::<T>(&value)?);
+        }
+        Ok(results)
+    }
+
+    async fn set<T: Serialize>(&self, key: String, value: &T) -> Result<(), CommonError> {
+        let config = broker_config();
+        placement_set(
+            &self.client_pool,
+            &config.get_meta_service_addr(),
+            SetRequest {
+                key,
+                value: serde_json::to_string(value)?,
+            },
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn delete(&self, key: String) -> Result<(), CommonError> {
+        let config = broker_config();
+        placement_delete(
+            &self.client_pool,
+            &config.get_meta_service_addr(),
+            DeleteRequest { key },
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn api_key_test() {
+        assert_eq!(parse_api_key("abc.def"), Some(("abc", "def")));
+        assert_eq!(parse_api_key("abc."), None);
+        assert_eq!(parse_api_key("abcdef"), None);
+
+        let api_key = AdminApiKey {
+            id: "abc".to_string(),
+            name: "ci".to_string(),
+            secret_hash: hash_api_key_secret("def"),
+            role: AdminRole::Operator,
+            create_by: "admin".to_string(),
+            create_time: 0,
+        };
+        assert!(api_key.verify_secret("def"));
+        assert!(!api_key.verify_secret("deg"));
+    }
+}
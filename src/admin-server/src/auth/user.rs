@@ -0,0 +1,234 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{
+    middleware::bearer_token,
+    role::AdminRole,
+    storage::{hash_password, AdminStorage, AdminUser},
+};
+use crate::{
+    request::admin::{
+        AdminUserListReq, CreateAdminUserReq, DeleteAdminUserReq, LoginReq, UpdateAdminUserReq,
+    },
+    response::{
+        admin::{AdminUserListRow, LoginResp},
+        PageReplyData,
+    },
+    state::HttpState,
+    tool::query::{apply_filters, apply_pagination, apply_sorting, build_query_params, Queryable},
+};
+use axum::{extract::State, http::HeaderMap, Json};
+use common_base::{
+    http_response::{error_response, success_response},
+    tools::now_second,
+};
+use std::{str::FromStr, sync::Arc};
+
+pub async fn login(State(state): State<Arc<HttpState>>, Json(params): Json<LoginReq>) -> String {
+    let storage = AdminStorage::new(state.client_pool.clone());
+    let user = match storage.get_user(&params.username).await {
+        Ok(Some(user)) if user.verify_password(&params.password) => user,
+        Ok(_) => return error_response("Invalid username or password".to_string()),
+        Err(e) => return error_response(e.to_string()),
+    };
+
+    let session = state.session_manager.create(&user.username, user.role);
+    success_response(LoginResp {
+        token: session.token,
+        username: session.username,
+        role: session.role.to_string(),
+        expire_time: session.expire_time,
+    })
+}
+
+pub async fn logout(State(state): State<Arc<HttpState>>, headers: HeaderMap) -> String {
+    if let Some(token) = bearer_token(&headers) {
+        state.session_manager.remove(token);
+    }
+    success_response("success")
+}
+
+pub async fn admin_user_list(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<AdminUserListReq>,
+) -> String {
+    let options = build_query_params(
+        params.page,
+        params.limit,
+        params.sort_field,
+        params.sort_by,
+        params.filter_field,
+        params.filter_values,
+        params.exact_match,
+    );
+
+    let storage = AdminStorage::new(state.client_pool.clone());
+    let data = match storage.list_user().await {
+        Ok(data) => data,
+        Err(e) => {
+            return error_response(e.to_string());
+        }
+    };
+
+    let mut users = Vec::new();
+    for user in data {
+        if let Some(username) = &params.username {
+            if !user.username.contains(username) {
+                continue;
+            }
+        }
+        users.push(AdminUserListRow {
+            username: user.username,
+            role: user.role.to_string(),
+            create_time: user.create_time,
+        });
+    }
+
+    let filtered = apply_filters(users, &options);
+    let sorted = apply_sorting(filtered, &options);
+    let pagination = apply_pagination(sorted, &options);
+
+    success_response(PageReplyData {
+        data: pagination.0,
+        total_count: pagination.1,
+    })
+}
+
+impl Queryable for AdminUserListRow {
+    fn get_field_str(&self, field: &str) -> Option<String> {
+        match field {
+            "username" => Some(self.username.clone()),
+            "role" => Some(self.role.clone()),
+            _ => None,
+        }
+    }
+}
+
+pub async fn admin_user_create(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<CreateAdminUserReq>,
+) -> String {
+    if params.username.is_empty() || params.password.is_empty() {
+        return error_response("Username and password cannot be empty".to_string());
+    }
+
+    let role = match AdminRole::from_str(&params.role) {
+        Ok(role) => role,
+        Err(e) => return error_response(e.to_string()),
+    };
+
+    let storage = AdminStorage::new(state.client_pool.clone());
+    match storage.get_user(&params.username).await {
+        Ok(Some(_)) => {
+            return error_response(format!("Admin user {} already exists", params.username));
+        }
+        Ok(None) => {}
+        Err(e) => return error_response(e.to_string()),
+    }
+
+    let user = match AdminUser::new(&params.username, &params.password, role, now_second()) {
+        Ok(user) => user,
+        Err(e) => return error_response(e.to_string()),
+    };
+
+    match storage.save_user(&user).await {
+        Ok(_) => success_response("success"),
+        Err(e) => error_response(e.to_string()),
+    }
+}
+
+pub async fn admin_user_update(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<UpdateAdminUserReq>,
+) -> String {
+    let storage = AdminStorage::new(state.client_pool.clone());
+    let mut user = match storage.get_user(&params.username).await {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            return error_response(format!("Admin user {} does not exist", params.username));
+        }
+        Err(e) => return error_response(e.to_string()),
+    };
+
+    if let Some(role) = &params.role {
+        let role = match AdminRole::from_str(role) {
+            Ok(role) => role,
+            Err(e) => return error_response(e.to_string()),
+        };
+        if user.role == AdminRole::Admin && role != AdminRole::Admin {
+            if let Err(e) = check_not_last_admin(&storage, &user.username).await {
+                return error_response(e);
+            }
+        }
+        user.role = role;
+    }
+
+    if let Some(password) = &params.password {
+        if password.is_empty() {
+            return error_response("Password cannot be empty".to_string());
+        }
+        user.password = match hash_password(password) {
+            Ok(hash) => hash,
+            Err(e) => return error_response(e.to_string()),
+        };
+    }
+
+    if let Err(e) = storage.save_user(&user).await {
+        return error_response(e.to_string());
+    }
+
+    // force a new login so that the change applies at once
+    state.session_manager.remove_by_user(&user.username);
+    success_response("success")
+}
+
+pub async fn admin_user_delete(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<DeleteAdminUserReq>,
+) -> String {
+    let storage = AdminStorage::new(state.client_pool.clone());
+    let user = match storage.get_user(&params.username).await {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            return error_response(format!("Admin user {} does not exist", params.username));
+        }
+        Err(e) => return error_response(e.to_string()),
+    };
+
+    if user.role == AdminRole::Admin {
+        if let Err(e) = check_not_last_admin(&storage, &user.username).await {
+            return error_response(e);
+        }
+    }
+
+    if let Err(e) = storage.delete_user(&user.username).await {
+        return error_response(e.to_string());
+    }
+    state.session_manager.remove_by_user(&user.username);
+    success_response("success")
+}
+
+// At least one admin must be left, otherwise nobody can manage the admin users anymore.
+async fn check_not_last_admin(storage: &AdminStorage, username: &str) -> Result<(), String> {
+    let users = storage.list_user().await.map_err(|e| e.to_string())?;
+    let has_other_admin = users
+        .iter()
+        .any(|user| user.role == AdminRole::Admin && user.username != username);
+    if !has_other_admin {
+        return Err(format!(
+            "Admin user {username} is the last user with the admin role"
+        ));
+    }
+    Ok(())
+}
@@ -12,9 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{path::*, response::PageReplyData};
+use crate::{
+    auth::middleware::{API_KEY_HEADER, AUTHORIZATION_HEADER},
+    path::*,
+    response::PageReplyData,
+};
 use common_base::http_response::AdminServerResponse;
-use reqwest::Client;
+use reqwest::{Client, RequestBuilder};
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 use thiserror::Error;
@@ -32,24 +36,35 @@ pub enum HttpClientError {
     InvalidUrl(String),
 }
 
+/// Environment variable holding the API key used by [`AdminHttpClient::new`]
+pub const API_KEY_ENV: &str = "ROBUSTMQ_API_KEY";
+
 /// HTTP client for RobustMQ Admin Server API
 #[derive(Clone)]
 pub struct AdminHttpClient {
     client: Client,
     base_url: String,
+    // (header name, header value)
+    auth_header: Option<(String, String)>,
 }
 
 impl AdminHttpClient {
-    /// Create a new HTTP client instance
+    /// Create a new HTTP client instance, authenticated with the API key in
+    /// `ROBUSTMQ_API_KEY` if it is set
     pub fn new(base_url: impl Into<String>) -> Self {
         let client = Client::builder()
             .timeout(Duration::from_secs(30))
             .build()
             .expect("Failed to create HTTP client");
 
-        Self {
+        let http_client = Self {
             client,
             base_url: base_url.into(),
+            auth_header: None,
+        };
+        match std::env::var(API_KEY_ENV) {
+            Ok(api_key) if !api_key.is_empty() => http_client.with_api_key(api_key),
+            _ => http_client,
         }
     }
 
@@ -63,6 +78,29 @@ pub fn with_timeout(base_url: impl Into<String>, timeout: Duration) -> Self {
         Self {
             client,
             base_url: base_url.into(),
+            auth_header: None,
+        }
+    }
+
+    /// Authenticate requests with an API key
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.auth_header = Some((API_KEY_HEADER.to_string(), api_key.into()));
+        self
+    }
+
+    /// Authenticate requests with the session token returned by login
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.auth_header = Some((
+            AUTHORIZATION_HEADER.to_string(),
+            format!("Bearer {}", token.into()),
+        ));
+        self
+    }
+
+    fn with_auth(&self, builder: RequestBuilder) -> RequestBuilder {
+        match &self.auth_header {
+            Some((name, value)) => builder.header(name, value),
+            None => builder,
         }
     }
 
@@ -74,8 +112,7 @@ pub async fn post<T, R>(&self, endpoint: &str, request: &T) -> Result<R, HttpCli
     {
         let url = self.build_url(endpoint)?;
         let response = self
-            .client
-            .post(&url)
+            .with_auth(self.client.post(&url))
             .header("Content-Type", "application/json")
             .json(request)
             .send()
@@ -118,8 +155,7 @@ pub async fn post_raw<T>(&self, endpoint: &str, request: &T) -> Result<String, H
         let url = self.build_url(endpoint)?;
 
         let response = self
-            .client
-            .post(&url)
+            .with_auth(self.client.post(&url))
             .header("Content-Type", "application/json")
             .json(request)
             .send()
@@ -142,7 +178,7 @@ pub async fn post_raw<T>(&self, endpoint: &str, request: &T) -> Result<String, H
     pub async fn get(&self, endpoint: &str) -> Result<String, HttpClientError> {
         let url = self.build_url(endpoint)?;
 
-        let response = self.client.get(&url).send().await?;
+        let response = self.with_auth(self.client.get(&url)).send().await?;
 
         let status = response.status();
         let response_text = response.text().await?;
@@ -157,6 +193,62 @@ pub async fn get(&self, endpoint: &str) -> Result<String, HttpClientError> {
         Ok(response_text)
     }
 
+    /// Make a POST request that answers with server-sent events, `on_event` is
+    /// called with the event name and data of every event until the stream ends
+    pub async fn post_event_stream<T, F>(
+        &self,
+        endpoint: &str,
+        request: &T,
+        timeout: Duration,
+        mut on_event: F,
+    ) -> Result<(), HttpClientError>
+    where
+        T: Serialize,
+        F: FnMut(&str, &str),
+    {
+        let url = self.build_url(endpoint)?;
+
+        let mut response = self
+            .with_auth(self.client.post(&url))
+            .header("Content-Type", "application/json")
+            .timeout(timeout)
+            .json(request)
+            .send()
+            .await?;
+
+        let status = response.status();
+        let is_event_stream = response
+            .headers()
+            .get("content-type")
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("text/event-stream"));
+
+        if !status.is_success() || !is_event_stream {
+            let response_text = response.text().await?;
+            let message = match serde_json::from_str::<AdminServerResponse<String>>(&response_text)
+            {
+                Ok(api_response) => api_response.data,
+                Err(_) => response_text,
+            };
+            return Err(HttpClientError::ServerError {
+                code: status.as_u16() as u64,
+                message,
+            });
+        }
+
+        let mut buffer = String::new();
+        while let Some(chunk) = response.chunk().await? {
+            buffer.push_str(&String::from_utf8_lossy(&chunk));
+            while let Some(end) = buffer.find("\n\n") {
+                let raw: String = buffer.drain(..end + 2).collect();
+                if let Some((event, data)) = parse_sse_event(&raw) {
+                    on_event(&event, &data);
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Build full URL from endpoint
     fn build_url(&self, endpoint: &str) -> Result<String, HttpClientError> {
         let endpoint = if endpoint.starts_with('/') {
@@ -179,6 +271,24 @@ fn build_url(&self, endpoint: &str) -> Result<String, HttpClientError> {
     }
 }
 
+// Returns the event name and data of one server-sent event, events without
+// data such as keep-alive comments are skipped.
+fn parse_sse_event(raw: &str) -> Option<(String, String)> {
+    let mut event = "message".to_string();
+    let mut data = Vec::new();
+    for line in raw.lines() {
+        if let Some(value) = line.strip_prefix("event:") {
+            event = value.trim().to_string();
+        } else if let Some(value) = line.strip_prefix("data:") {
+            data.push(value.strip_prefix(' ').unwrap_or(value));
+        }
+    }
+    if data.is_empty() {
+        return None;
+    }
+    Some((event, data.join("\n")))
+}
+
 /// Convenience methods for common admin server operations
 impl AdminHttpClient {
     /// Get service version information
@@ -218,6 +328,24 @@ pub async fn get_client_list<T, R>(
         self.post(&api_path(MQTT_CLIENT_LIST_PATH), request).await
     }
 
+    /// Get the connection state, inflight and queued messages of a client
+    pub async fn get_client_detail<T, R>(&self, request: &T) -> Result<R, HttpClientError>
+    where
+        T: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        self.post(&api_path(MQTT_CLIENT_DETAIL_PATH), request).await
+    }
+
+    /// Disconnect a client
+    pub async fn kick_client<T, R>(&self, request: &T) -> Result<R, HttpClientError>
+    where
+        T: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        self.post(&api_path(MQTT_CLIENT_KICK_PATH), request).await
+    }
+
     /// Get session list
     pub async fn get_session_list<T, R>(
         &self,
@@ -230,6 +358,15 @@ pub async fn get_session_list<T, R>(
         self.post(&api_path(MQTT_SESSION_LIST_PATH), request).await
     }
 
+    /// Clear the session of a client, disconnecting it if needed
+    pub async fn clear_session<T, R>(&self, request: &T) -> Result<R, HttpClientError>
+    where
+        T: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        self.post(&api_path(MQTT_SESSION_CLEAR_PATH), request).await
+    }
+
     /// Get topic list
     pub async fn get_topic_list<T, R>(
         &self,
@@ -374,6 +511,54 @@ pub async fn delete_connector<T>(&self, request: &T) -> Result<String, HttpClien
             .await
     }
 
+    /// Get listener list
+    pub async fn get_listener_list<T, R>(
+        &self,
+        request: &T,
+    ) -> Result<PageReplyData<R>, HttpClientError>
+    where
+        T: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        self.post(&api_path(MQTT_LISTENER_LIST_PATH), request).await
+    }
+
+    /// Create listener
+    pub async fn create_listener<T>(&self, request: &T) -> Result<String, HttpClientError>
+    where
+        T: Serialize,
+    {
+        self.post_raw(&api_path(MQTT_LISTENER_CREATE_PATH), request)
+            .await
+    }
+
+    /// Delete listener
+    pub async fn delete_listener<T>(&self, request: &T) -> Result<String, HttpClientError>
+    where
+        T: Serialize,
+    {
+        self.post_raw(&api_path(MQTT_LISTENER_DELETE_PATH), request)
+            .await
+    }
+
+    /// Start listener
+    pub async fn start_listener<T>(&self, request: &T) -> Result<String, HttpClientError>
+    where
+        T: Serialize,
+    {
+        self.post_raw(&api_path(MQTT_LISTENER_START_PATH), request)
+            .await
+    }
+
+    /// Stop listener
+    pub async fn stop_listener<T>(&self, request: &T) -> Result<String, HttpClientError>
+    where
+        T: Serialize,
+    {
+        self.post_raw(&api_path(MQTT_LISTENER_STOP_PATH), request)
+            .await
+    }
+
     /// Get schema list
     pub async fn get_schema_list<T, R>(
         &self,
@@ -466,6 +651,43 @@ pub async fn get_cluster_config<T>(&self, request: &T) -> Result<String, HttpCli
             .await
     }
 
+    /// Start draining a broker
+    pub async fn drain_node<T>(&self, request: &T) -> Result<String, HttpClientError>
+    where
+        T: Serialize,
+    {
+        self.post_raw(&api_path(CLUSTER_NODE_DRAIN_PATH), request)
+            .await
+    }
+
+    /// Cancel the drain of a broker
+    pub async fn cancel_drain_node<T>(&self, request: &T) -> Result<String, HttpClientError>
+    where
+        T: Serialize,
+    {
+        self.post_raw(&api_path(CLUSTER_NODE_DRAIN_CANCEL_PATH), request)
+            .await
+    }
+
+    /// Get the drain status of the brokers
+    pub async fn get_drain_node_list<T, R>(&self, request: &T) -> Result<R, HttpClientError>
+    where
+        T: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        self.post(&api_path(CLUSTER_NODE_DRAIN_LIST_PATH), request)
+            .await
+    }
+
+    /// Spread the connections evenly over the brokers
+    pub async fn rebalance_cluster<T, R>(&self, request: &T) -> Result<R, HttpClientError>
+    where
+        T: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        self.post(&api_path(CLUSTER_REBALANCE_PATH), request).await
+    }
+
     /// Get flapping detection list
     pub async fn get_flapping_detect_list<T, R>(
         &self,
@@ -562,6 +784,372 @@ pub async fn get_subscribe_detail<T>(&self, request: &T) -> Result<String, HttpC
         self.post_raw(&api_path(MQTT_SUBSCRIBE_DETAIL_PATH), request)
             .await
     }
+
+    /// Subscribe on behalf of a client
+    pub async fn create_subscribe<T>(&self, request: &T) -> Result<String, HttpClientError>
+    where
+        T: Serialize,
+    {
+        self.post_raw(&api_path(MQTT_SUBSCRIBE_CREATE_PATH), request)
+            .await
+    }
+
+    /// Unsubscribe on behalf of a client
+    pub async fn delete_subscribe<T>(&self, request: &T) -> Result<String, HttpClientError>
+    where
+        T: Serialize,
+    {
+        self.post_raw(&api_path(MQTT_SUBSCRIBE_DELETE_PATH), request)
+            .await
+    }
+
+    /// Publish a message to a topic
+    pub async fn publish_message<T>(&self, request: &T) -> Result<String, HttpClientError>
+    where
+        T: Serialize,
+    {
+        self.post_raw(&api_path(MQTT_MESSAGE_PUBLISH_PATH), request)
+            .await
+    }
+
+    /// Get retained message list
+    pub async fn get_retain_message_list<T, R>(
+        &self,
+        request: &T,
+    ) -> Result<PageReplyData<R>, HttpClientError>
+    where
+        T: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        self.post(&api_path(MQTT_RETAIN_MESSAGE_LIST_PATH), request)
+            .await
+    }
+
+    /// Delete the retained message of a topic
+    pub async fn delete_retain_message<T>(&self, request: &T) -> Result<String, HttpClientError>
+    where
+        T: Serialize,
+    {
+        self.post_raw(&api_path(MQTT_RETAIN_MESSAGE_DELETE_PATH), request)
+            .await
+    }
+
+    /// Get the pending delay messages
+    pub async fn get_delay_message_list<T, R>(
+        &self,
+        request: &T,
+    ) -> Result<PageReplyData<R>, HttpClientError>
+    where
+        T: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        self.post(&api_path(MQTT_DELAY_MESSAGE_LIST_PATH), request)
+            .await
+    }
+
+    /// Get the detail of a delay message
+    pub async fn get_delay_message_detail<T, R>(&self, request: &T) -> Result<R, HttpClientError>
+    where
+        T: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        self.post(&api_path(MQTT_DELAY_MESSAGE_DETAIL_PATH), request)
+            .await
+    }
+
+    /// Cancel delay messages by id or by target topic
+    pub async fn cancel_delay_message<T, R>(&self, request: &T) -> Result<R, HttpClientError>
+    where
+        T: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        self.post(&api_path(MQTT_DELAY_MESSAGE_CANCEL_PATH), request)
+            .await
+    }
+
+    /// Get the scheduled publishes
+    pub async fn get_scheduled_publish_list<T, R>(
+        &self,
+        request: &T,
+    ) -> Result<PageReplyData<R>, HttpClientError>
+    where
+        T: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        self.post(&api_path(MQTT_SCHEDULED_PUBLISH_LIST_PATH), request)
+            .await
+    }
+
+    /// Create a scheduled publish
+    pub async fn create_scheduled_publish<T>(&self, request: &T) -> Result<String, HttpClientError>
+    where
+        T: Serialize,
+    {
+        self.post_raw(&api_path(MQTT_SCHEDULED_PUBLISH_CREATE_PATH), request)
+            .await
+    }
+
+    /// Delete a scheduled publish
+    pub async fn delete_scheduled_publish<T>(&self, request: &T) -> Result<String, HttpClientError>
+    where
+        T: Serialize,
+    {
+        self.post_raw(&api_path(MQTT_SCHEDULED_PUBLISH_DELETE_PATH), request)
+            .await
+    }
+
+    /// Get the recorded firings of a scheduled publish
+    pub async fn get_scheduled_publish_history<T, R>(
+        &self,
+        request: &T,
+    ) -> Result<PageReplyData<R>, HttpClientError>
+    where
+        T: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        self.post(&api_path(MQTT_SCHEDULED_PUBLISH_HISTORY_PATH), request)
+            .await
+    }
+
+    /// Get the per-topic detail metrics
+    pub async fn get_detail_metrics_topic_list<T, R>(
+        &self,
+        request: &T,
+    ) -> Result<PageReplyData<R>, HttpClientError>
+    where
+        T: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        self.post(&api_path(MQTT_DETAIL_METRICS_TOPIC_LIST_PATH), request)
+            .await
+    }
+
+    /// Get the per-client detail metrics
+    pub async fn get_detail_metrics_client_list<T, R>(
+        &self,
+        request: &T,
+    ) -> Result<PageReplyData<R>, HttpClientError>
+    where
+        T: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        self.post(&api_path(MQTT_DETAIL_METRICS_CLIENT_LIST_PATH), request)
+            .await
+    }
+
+    /// Get the detail metrics config
+    pub async fn get_detail_metrics_config<T, R>(&self, request: &T) -> Result<R, HttpClientError>
+    where
+        T: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        self.post(&api_path(MQTT_DETAIL_METRICS_CONFIG_GET_PATH), request)
+            .await
+    }
+
+    /// Update the detail metrics config, fields left out keep their value
+    pub async fn set_detail_metrics_config<T>(&self, request: &T) -> Result<String, HttpClientError>
+    where
+        T: Serialize,
+    {
+        self.post_raw(&api_path(MQTT_DETAIL_METRICS_CONFIG_SET_PATH), request)
+            .await
+    }
+
+    /// Trace the packets of a client or a topic, `on_event` is called with
+    /// every event until the trace is over
+    pub async fn trace<T, F>(
+        &self,
+        request: &T,
+        timeout: Duration,
+        on_event: F,
+    ) -> Result<(), HttpClientError>
+    where
+        T: Serialize,
+        F: FnMut(&str, &str),
+    {
+        self.post_event_stream(&api_path(MQTT_TRACE_PATH), request, timeout, on_event)
+            .await
+    }
+
+    /// Get journal namespace list
+    pub async fn get_journal_namespace_list<T, R>(
+        &self,
+        request: &T,
+    ) -> Result<PageReplyData<R>, HttpClientError>
+    where
+        T: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        self.post(&api_path(JOURNAL_NAMESPACE_LIST_PATH), request)
+            .await
+    }
+
+    /// Get journal shard list
+    pub async fn get_journal_shard_list<T, R>(
+        &self,
+        request: &T,
+    ) -> Result<PageReplyData<R>, HttpClientError>
+    where
+        T: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        self.post(&api_path(JOURNAL_SHARD_LIST_PATH), request).await
+    }
+
+    /// Delete journal shard
+    pub async fn delete_journal_shard<T>(&self, request: &T) -> Result<String, HttpClientError>
+    where
+        T: Serialize,
+    {
+        self.post_raw(&api_path(JOURNAL_SHARD_DELETE_PATH), request)
+            .await
+    }
+
+    /// Get the segment list of a journal shard
+    pub async fn get_journal_segment_list<T, R>(
+        &self,
+        request: &T,
+    ) -> Result<PageReplyData<R>, HttpClientError>
+    where
+        T: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        self.post(&api_path(JOURNAL_SEGMENT_LIST_PATH), request)
+            .await
+    }
+
+    /// Get journal segment detail
+    pub async fn get_journal_segment_detail<T, R>(&self, request: &T) -> Result<R, HttpClientError>
+    where
+        T: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        self.post(&api_path(JOURNAL_SEGMENT_DETAIL_PATH), request)
+            .await
+    }
+
+    /// Seal up a journal segment
+    pub async fn seal_journal_segment<T, R>(&self, request: &T) -> Result<R, HttpClientError>
+    where
+        T: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        self.post(&api_path(JOURNAL_SEGMENT_SEAL_PATH), request)
+            .await
+    }
+
+    /// Get the committed offsets of a journal consumer group
+    pub async fn get_journal_group_offset_list<T, R>(
+        &self,
+        request: &T,
+    ) -> Result<PageReplyData<R>, HttpClientError>
+    where
+        T: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        self.post(&api_path(JOURNAL_GROUP_OFFSET_LIST_PATH), request)
+            .await
+    }
+
+    /// Login and get a session token
+    pub async fn login<T, R>(&self, request: &T) -> Result<R, HttpClientError>
+    where
+        T: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        self.post(&api_path(AUTH_LOGIN_PATH), request).await
+    }
+
+    /// Invalidate the current session token
+    pub async fn logout(&self) -> Result<String, HttpClientError> {
+        let empty_request = serde_json::json!({});
+        self.post_raw(&api_path(AUTH_LOGOUT_PATH), &empty_request)
+            .await
+    }
+
+    /// Get admin user list
+    pub async fn get_admin_user_list<T, R>(
+        &self,
+        request: &T,
+    ) -> Result<PageReplyData<R>, HttpClientError>
+    where
+        T: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        self.post(&api_path(ADMIN_USER_LIST_PATH), request).await
+    }
+
+    /// Create admin user
+    pub async fn create_admin_user<T>(&self, request: &T) -> Result<String, HttpClientError>
+    where
+        T: Serialize,
+    {
+        self.post_raw(&api_path(ADMIN_USER_CREATE_PATH), request)
+            .await
+    }
+
+    /// Update the password or role of an admin user
+    pub async fn update_admin_user<T>(&self, request: &T) -> Result<String, HttpClientError>
+    where
+        T: Serialize,
+    {
+        self.post_raw(&api_path(ADMIN_USER_UPDATE_PATH), request)
+            .await
+    }
+
+    /// Delete admin user
+    pub async fn delete_admin_user<T>(&self, request: &T) -> Result<String, HttpClientError>
+    where
+        T: Serialize,
+    {
+        self.post_raw(&api_path(ADMIN_USER_DELETE_PATH), request)
+            .await
+    }
+
+    /// Get API key list
+    pub async fn get_api_key_list<T, R>(
+        &self,
+        request: &T,
+    ) -> Result<PageReplyData<R>, HttpClientError>
+    where
+        T: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        self.post(&api_path(ADMIN_API_KEY_LIST_PATH), request).await
+    }
+
+    /// Create API key
+    pub async fn create_api_key<T, R>(&self, request: &T) -> Result<R, HttpClientError>
+    where
+        T: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        self.post(&api_path(ADMIN_API_KEY_CREATE_PATH), request)
+            .await
+    }
+
+    /// Delete API key
+    pub async fn delete_api_key<T>(&self, request: &T) -> Result<String, HttpClientError>
+    where
+        T: Serialize,
+    {
+        self.post_raw(&api_path(ADMIN_API_KEY_DELETE_PATH), request)
+            .await
+    }
+
+    /// Get audit log list
+    pub async fn get_audit_log_list<T, R>(
+        &self,
+        request: &T,
+    ) -> Result<PageReplyData<R>, HttpClientError>
+    where
+        T: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        self.post(&api_path(ADMIN_AUDIT_LOG_LIST_PATH), request)
+            .await
+    }
 }
 
 #[cfg(test)]
@@ -601,6 +1189,19 @@ fn test_invalid_url() {
         assert!(matches!(result, Err(HttpClientError::InvalidUrl(_))));
     }
 
+    #[test]
+    fn test_parse_sse_event() {
+        assert_eq!(
+            parse_sse_event("data: {\"a\":1}\n\n"),
+            Some(("message".to_string(), "{\"a\":1}".to_string()))
+        );
+        assert_eq!(
+            parse_sse_event("event: end\ndata: trace finished\n\n"),
+            Some(("end".to_string(), "trace finished".to_string()))
+        );
+        assert_eq!(parse_sse_event(":\n\n"), None);
+    }
+
     #[tokio::test]
     async fn test_client_creation() {
         let client = AdminHttpClient::new("http://localhost:8080");
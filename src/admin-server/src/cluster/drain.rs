@@ -0,0 +1,258 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    request::cluster::{ClusterRebalanceReq, NodeDrainCancelReq, NodeDrainListReq, NodeDrainReq},
+    response::cluster::{ClusterRebalanceResp, NodeDrainStatusRow, RebalanceMoveRow},
+    state::HttpState,
+};
+use axum::{extract::State, Json};
+use common_base::{
+    error::common::CommonError,
+    http_response::{error_response, success_response},
+};
+use grpc_clients::mqtt::admin::call::{
+    broker_mqtt_drain_node, broker_mqtt_drain_node_status, broker_mqtt_rebalance_connections,
+};
+use metadata_struct::{mqtt::node_extend::NodeExtend, placement::node::BrokerNode};
+use protocol::broker::broker_mqtt_admin::{
+    DrainNodeRequest, DrainNodeStatusReply, DrainNodeStatusRequest, RebalanceConnectionsRequest,
+};
+use std::sync::Arc;
+
+pub async fn node_drain(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<NodeDrainReq>,
+) -> String {
+    let node = match broker_node(&state, params.broker_id) {
+        Ok(node) => node,
+        Err(e) => return error_response(e.to_string()),
+    };
+
+    let request = DrainNodeRequest {
+        cancel: false,
+        disconnect_rate: params.disconnect_rate.unwrap_or_default(),
+    };
+    match broker_mqtt_drain_node(&state.client_pool, &[node.node_inner_addr], request).await {
+        Ok(_) => success_response("success"),
+        Err(e) => error_response(e.to_string()),
+    }
+}
+
+pub async fn node_drain_cancel(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<NodeDrainCancelReq>,
+) -> String {
+    let node = match broker_node(&state, params.broker_id) {
+        Ok(node) => node,
+        Err(e) => return error_response(e.to_string()),
+    };
+
+    let request = DrainNodeRequest {
+        cancel: true,
+        disconnect_rate: 0,
+    };
+    match broker_mqtt_drain_node(&state.client_pool, &[node.node_inner_addr], request).await {
+        Ok(_) => success_response("success"),
+        Err(e) => error_response(e.to_string()),
+    }
+}
+
+pub async fn node_drain_list(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<NodeDrainListReq>,
+) -> String {
+    let nodes = match params.broker_id {
+        Some(broker_id) => match broker_node(&state, broker_id) {
+            Ok(node) => vec![node],
+            Err(e) => return error_response(e.to_string()),
+        },
+        None => broker_nodes(&state),
+    };
+
+    let mut results = Vec::new();
+    for node in nodes {
+        match node_drain_status(&state, &node).await {
+            Ok(reply) => results.push(NodeDrainStatusRow {
+                broker_id: reply.broker_id,
+                draining: reply.draining,
+                start_time: reply.start_time,
+                disconnect_rate: reply.disconnect_rate,
+                connection_count: reply.connection_count,
+                disconnected_count: reply.disconnected_count,
+                share_leader_count: reply.share_leader_count,
+                connector_count: reply.connector_count,
+                drained: reply.drained,
+            }),
+            Err(e) => return error_response(e.to_string()),
+        }
+    }
+    success_response(results)
+}
+
+pub async fn cluster_rebalance(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<ClusterRebalanceReq>,
+) -> String {
+    // Draining brokers neither give nor take connections.
+    let mut loads = Vec::new();
+    for node in broker_nodes(&state) {
+        let reply = match node_drain_status(&state, &node).await {
+            Ok(reply) => reply,
+            Err(e) => return error_response(e.to_string()),
+        };
+        if reply.draining {
+            continue;
+        }
+        let mqtt_addr = NodeExtend::decode(&node.extend)
+            .map(|extend| extend.mqtt.mqtt_addr)
+            .unwrap_or_default();
+        loads.push(BrokerLoad {
+            broker_id: node.node_id,
+            connection_count: reply.connection_count,
+            mqtt_addr,
+        });
+    }
+
+    let plan = plan_rebalance(&loads);
+    if !params.dry_run {
+        for row in plan.moves.iter() {
+            let Some(addr) = state
+                .broker_cache
+                .node_lists
+                .get(&row.broker_id)
+                .map(|node| node.node_inner_addr.clone())
+            else {
+                continue;
+            };
+            let request = RebalanceConnectionsRequest {
+                count: row.move_count,
+                disconnect_rate: params.disconnect_rate.unwrap_or_default(),
+                server_references: row.server_references.clone(),
+            };
+            if let Err(e) =
+                broker_mqtt_rebalance_connections(&state.client_pool, &[addr], request).await
+            {
+                return error_response(e.to_string());
+            }
+        }
+    }
+    success_response(plan)
+}
+
+struct BrokerLoad {
+    broker_id: u64,
+    connection_count: u64,
+    mqtt_addr: String,
+}
+
+// Brokers above the average connection count move their excess to the brokers below it.
+fn plan_rebalance(loads: &[BrokerLoad]) -> ClusterRebalanceResp {
+    if loads.is_empty() {
+        return ClusterRebalanceResp {
+            target_connection_count: 0,
+            moves: Vec::new(),
+        };
+    }
+
+    let total: u64 = loads.iter().map(|load| load.connection_count).sum();
+    let target = total.div_ceil(loads.len() as u64);
+
+    let server_references: Vec<String> = loads
+        .iter()
+        .filter(|load| load.connection_count < target && !load.mqtt_addr.is_empty())
+        .map(|load| load.mqtt_addr.clone())
+        .collect();
+
+    let mut moves: Vec<RebalanceMoveRow> = loads
+        .iter()
+        .filter(|load| load.connection_count > target)
+        .map(|load| RebalanceMoveRow {
+            broker_id: load.broker_id,
+            connection_count: load.connection_count,
+            move_count: load.connection_count - target,
+            server_references: server_references.clone(),
+        })
+        .collect();
+    moves.sort_by_key(|row| row.broker_id);
+
+    ClusterRebalanceResp {
+        target_connection_count: target,
+        moves,
+    }
+}
+
+fn broker_nodes(state: &Arc<HttpState>) -> Vec<BrokerNode> {
+    let mut nodes = state.broker_cache.node_list();
+    nodes.sort_by_key(|node| node.node_id);
+    nodes
+}
+
+fn broker_node(state: &Arc<HttpState>, broker_id: u64) -> Result<BrokerNode, CommonError> {
+    state
+        .broker_cache
+        .node_list()
+        .into_iter()
+        .find(|node| node.node_id == broker_id)
+        .ok_or_else(|| CommonError::CommonError(format!("Broker {broker_id} does not exist")))
+}
+
+async fn node_drain_status(
+    state: &Arc<HttpState>,
+    node: &BrokerNode,
+) -> Result<DrainNodeStatusReply, CommonError> {
+    broker_mqtt_drain_node_status(
+        &state.client_pool,
+        &[node.node_inner_addr.clone()],
+        DrainNodeStatusRequest {},
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{plan_rebalance, BrokerLoad};
+
+    fn load(broker_id: u64, connection_count: u64) -> BrokerLoad {
+        BrokerLoad {
+            broker_id,
+            connection_count,
+            mqtt_addr: format!("127.0.0.{broker_id}:1883"),
+        }
+    }
+
+    #[test]
+    fn plan_rebalance_test() {
+        let plan = plan_rebalance(&[]);
+        assert!(plan.moves.is_empty());
+
+        // already balanced
+        let plan = plan_rebalance(&[load(1, 100), load(2, 101)]);
+        assert_eq!(plan.target_connection_count, 101);
+        assert!(plan.moves.is_empty());
+
+        // a broker added after scale-out
+        let plan = plan_rebalance(&[load(1, 300), load(2, 300), load(3, 0)]);
+        assert_eq!(plan.target_connection_count, 200);
+        assert_eq!(plan.moves.len(), 2);
+        assert_eq!(plan.moves[0].broker_id, 1);
+        assert_eq!(plan.moves[0].move_count, 100);
+        assert_eq!(plan.moves[1].broker_id, 2);
+        assert_eq!(plan.moves[1].move_count, 100);
+        assert_eq!(
+            plan.moves[0].server_references,
+            vec!["127.0.0.3:1883".to_string()]
+        );
+    }
+}
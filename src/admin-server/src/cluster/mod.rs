@@ -14,6 +14,8 @@
 
 use std::sync::Arc;
 
+pub mod drain;
+
 use crate::{
     request::cluster::{ClusterConfigGetReq, ClusterConfigSetReq},
     state::HttpState,
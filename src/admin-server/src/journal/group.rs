@@ -0,0 +1,96 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::{
+    request::journal::GroupOffsetListReq,
+    response::{journal::GroupOffsetListRow, PageReplyData},
+    state::HttpState,
+    tool::query::{apply_filters, apply_pagination, apply_sorting, build_query_params, Queryable},
+};
+use axum::{extract::State, Json};
+use common_base::http_response::{error_response, success_response};
+use common_config::broker::broker_config;
+use grpc_clients::meta::inner::call::get_offset_data;
+use protocol::meta::meta_service_inner::GetOffsetDataRequest;
+use std::sync::Arc;
+
+pub async fn group_offset_list(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<GroupOffsetListReq>,
+) -> String {
+    if params.group_name.is_empty() {
+        return error_response("Group name cannot be empty".to_string());
+    }
+
+    let options = build_query_params(
+        params.page,
+        params.limit,
+        params.sort_field,
+        params.sort_by,
+        params.filter_field,
+        params.filter_values,
+        params.exact_match,
+    );
+
+    let request = GetOffsetDataRequest {
+        cluster_name: state.broker_cache.cluster_name.clone(),
+        group: params.group_name.clone(),
+    };
+    let reply = match get_offset_data(
+        &state.client_pool,
+        &broker_config().get_meta_service_addr(),
+        request,
+    )
+    .await
+    {
+        Ok(reply) => reply,
+        Err(e) => return error_response(e.to_string()),
+    };
+
+    let offsets: Vec<GroupOffsetListRow> = reply
+        .offsets
+        .into_iter()
+        .filter(|raw| {
+            params
+                .namespace
+                .as_ref()
+                .is_none_or(|namespace| raw.namespace == *namespace)
+        })
+        .map(|raw| GroupOffsetListRow {
+            group_name: params.group_name.clone(),
+            namespace: raw.namespace,
+            shard_name: raw.shard_name,
+            offset: raw.offset,
+        })
+        .collect();
+
+    let filtered = apply_filters(offsets, &options);
+    let sorted = apply_sorting(filtered, &options);
+    let pagination = apply_pagination(sorted, &options);
+
+    success_response(PageReplyData {
+        data: pagination.0,
+        total_count: pagination.1,
+    })
+}
+
+impl Queryable for GroupOffsetListRow {
+    fn get_field_str(&self, field: &str) -> Option<String> {
+        match field {
+            "namespace" => Some(self.namespace.clone()),
+            "shard_name" => Some(self.shard_name.clone()),
+            "offset" => Some(self.offset.to_string()),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,331 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::{
+    journal::shard::list_journal_shards,
+    request::journal::{SealSegmentReq, SegmentDetailReq, SegmentListReq},
+    response::{
+        journal::{SealSegmentResp, SegmentDetailResp, SegmentFileResp, SegmentListRow},
+        PageReplyData,
+    },
+    state::HttpState,
+    tool::query::{apply_filters, apply_pagination, apply_sorting, build_query_params, Queryable},
+};
+use axum::{extract::State, Json};
+use common_base::{
+    error::common::CommonError,
+    http_response::{error_response, success_response},
+};
+use common_config::broker::broker_config;
+use grpc_clients::{
+    journal::admin::call::{journal_admin_get_segment_file, journal_admin_seal_up_segment},
+    meta::journal::call::{list_segment, list_segment_meta},
+};
+use metadata_struct::journal::{segment::JournalSegment, segment_meta::JournalSegmentMetadata};
+use protocol::{
+    journal::journal_segment_admin::{GetSegmentFileRequest, SealUpSegmentRequest},
+    meta::meta_service_journal::{ListSegmentMetaRequest, ListSegmentRequest},
+};
+use std::sync::Arc;
+
+pub async fn segment_list(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<SegmentListReq>,
+) -> String {
+    let options = build_query_params(
+        params.page,
+        params.limit,
+        params.sort_field,
+        params.sort_by,
+        params.filter_field,
+        params.filter_values,
+        params.exact_match,
+    );
+
+    let rows = match list_segment_rows(&state, &params.namespace, &params.shard_name, -1).await {
+        Ok(rows) => rows,
+        Err(e) => return error_response(e.to_string()),
+    };
+
+    let filtered = apply_filters(rows, &options);
+    let sorted = apply_sorting(filtered, &options);
+    let pagination = apply_pagination(sorted, &options);
+
+    success_response(PageReplyData {
+        data: pagination.0,
+        total_count: pagination.1,
+    })
+}
+
+impl Queryable for SegmentListRow {
+    fn get_field_str(&self, field: &str) -> Option<String> {
+        match field {
+            "segment_no" => Some(self.segment_no.to_string()),
+            "status" => Some(self.status.clone()),
+            "leader" => Some(self.leader.to_string()),
+            _ => None,
+        }
+    }
+}
+
+pub async fn segment_detail(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<SegmentDetailReq>,
+) -> String {
+    let rows = match list_segment_rows(
+        &state,
+        &params.namespace,
+        &params.shard_name,
+        params.segment_no as i32,
+    )
+    .await
+    {
+        Ok(rows) => rows,
+        Err(e) => return error_response(e.to_string()),
+    };
+
+    let Some(segment) = rows.into_iter().next() else {
+        return error_response(format!(
+            "Segment {} of shard {}/{} does not exist",
+            params.segment_no, params.namespace, params.shard_name
+        ));
+    };
+
+    // The segment file metadata only lives on the leader of the segment.
+    let file = match node_inner_addr(&state, segment.leader) {
+        Ok(addr) => {
+            let request = GetSegmentFileRequest {
+                namespace: params.namespace.clone(),
+                shard_name: params.shard_name.clone(),
+                segment_no: params.segment_no,
+            };
+            journal_admin_get_segment_file(&state.client_pool, &[addr], request)
+                .await
+                .map(|reply| SegmentFileResp {
+                    start_offset: reply.start_offset,
+                    end_offset: reply.end_offset,
+                    start_timestamp: reply.start_timestamp,
+                    end_timestamp: reply.end_timestamp,
+                })
+        }
+        Err(e) => Err(e),
+    };
+
+    let (file, file_error) = match file {
+        Ok(file) => (Some(file), None),
+        Err(e) => (None, Some(e.to_string())),
+    };
+    success_response(SegmentDetailResp {
+        segment,
+        file,
+        file_error,
+    })
+}
+
+pub async fn segment_seal(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<SealSegmentReq>,
+) -> String {
+    match seal_segment(&state, &params).await {
+        Ok(data) => success_response(data),
+        Err(e) => error_response(e.to_string()),
+    }
+}
+
+async fn seal_segment(
+    state: &Arc<HttpState>,
+    params: &SealSegmentReq,
+) -> Result<SealSegmentResp, CommonError> {
+    let segment_no = match params.segment_no {
+        Some(segment_no) => segment_no,
+        None => {
+            let shards = list_journal_shards(state, &params.namespace, &params.shard_name).await?;
+            let Some(shard) = shards.first() else {
+                return Err(CommonError::CommonError(format!(
+                    "Shard {}/{} does not exist",
+                    params.namespace, params.shard_name
+                )));
+            };
+            shard.active_segment_seq
+        }
+    };
+
+    let segments = list_journal_segments(
+        state,
+        &params.namespace,
+        &params.shard_name,
+        segment_no as i32,
+    )
+    .await?;
+    let Some(segment) = segments.first() else {
+        return Err(CommonError::CommonError(format!(
+            "Segment {} of shard {}/{} does not exist",
+            segment_no, params.namespace, params.shard_name
+        )));
+    };
+
+    // Sealing moves the writes of the shard, it has to run on the leader.
+    let addr = node_inner_addr(state, segment.leader)?;
+    let request = SealUpSegmentRequest {
+        namespace: params.namespace.clone(),
+        shard_name: params.shard_name.clone(),
+        segment_no,
+    };
+    let reply = journal_admin_seal_up_segment(&state.client_pool, &[addr], request).await?;
+
+    Ok(SealSegmentResp {
+        namespace: params.namespace.clone(),
+        shard_name: params.shard_name.clone(),
+        segment_no,
+        end_offset: reply.end_offset,
+        next_segment_no: reply.next_segment_no,
+    })
+}
+
+async fn list_segment_rows(
+    state: &Arc<HttpState>,
+    namespace: &str,
+    shard_name: &str,
+    segment_no: i32,
+) -> Result<Vec<SegmentListRow>, CommonError> {
+    if namespace.is_empty() || shard_name.is_empty() {
+        return Err(CommonError::CommonError(
+            "Namespace and shard name cannot be empty".to_string(),
+        ));
+    }
+
+    let segments = list_journal_segments(state, namespace, shard_name, segment_no).await?;
+
+    let request = ListSegmentMetaRequest {
+        cluster_name: state.broker_cache.cluster_name.clone(),
+        namespace: namespace.to_string(),
+        shard_name: shard_name.to_string(),
+        segment_no,
+    };
+    let reply = list_segment_meta(
+        &state.client_pool,
+        &broker_config().get_meta_service_addr(),
+        request,
+    )
+    .await?;
+    let metas = serde_json::from_slice::<Vec<JournalSegmentMetadata>>(&reply.segments)?;
+
+    Ok(build_segment_rows(&segments, &metas))
+}
+
+async fn list_journal_segments(
+    state: &Arc<HttpState>,
+    namespace: &str,
+    shard_name: &str,
+    segment_no: i32,
+) -> Result<Vec<JournalSegment>, CommonError> {
+    let request = ListSegmentRequest {
+        cluster_name: state.broker_cache.cluster_name.clone(),
+        namespace: namespace.to_string(),
+        shard_name: shard_name.to_string(),
+        segment_no,
+    };
+    let reply = list_segment(
+        &state.client_pool,
+        &broker_config().get_meta_service_addr(),
+        request,
+    )
+    .await?;
+    Ok(serde_json::from_slice::<Vec<JournalSegment>>(
+        &reply.segments,
+    )?)
+}
+
+fn node_inner_addr(state: &Arc<HttpState>, node_id: u64) -> Result<String, CommonError> {
+    state
+        .broker_cache
+        .node_list()
+        .into_iter()
+        .find(|node| node.node_id == node_id)
+        .map(|node| node.node_inner_addr)
+        .ok_or_else(|| {
+            CommonError::CommonError(format!("Segment leader {node_id} is not available"))
+        })
+}
+
+fn build_segment_rows(
+    segments: &[JournalSegment],
+    metas: &[JournalSegmentMetadata],
+) -> Vec<SegmentListRow> {
+    let mut rows: Vec<SegmentListRow> = segments
+        .iter()
+        .map(|segment| {
+            let meta = metas
+                .iter()
+                .find(|meta| meta.segment_seq == segment.segment_seq);
+            SegmentListRow {
+                namespace: segment.namespace.clone(),
+                shard_name: segment.shard_name.clone(),
+                segment_no: segment.segment_seq,
+                status: segment.status.to_string(),
+                leader: segment.leader,
+                leader_epoch: segment.leader_epoch,
+                replicas: segment.replicas.iter().map(|rep| rep.node_id).collect(),
+                isr: segment.isr.clone(),
+                start_offset: meta.map(|meta| meta.start_offset).unwrap_or(-1),
+                end_offset: meta.map(|meta| meta.end_offset).unwrap_or(-1),
+                start_timestamp: meta.map(|meta| meta.start_timestamp).unwrap_or(-1),
+                end_timestamp: meta.map(|meta| meta.end_timestamp).unwrap_or(-1),
+            }
+        })
+        .collect();
+    rows.sort_by_key(|row| row.segment_no);
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use metadata_struct::journal::segment::{Replica, SegmentStatus};
+
+    #[test]
+    fn build_segment_rows_test() {
+        let segments = vec![
+            JournalSegment {
+                segment_seq: 1,
+                leader: 2,
+                status: SegmentStatus::Write,
+                replicas: vec![Replica {
+                    node_id: 2,
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+            JournalSegment {
+                segment_seq: 0,
+                status: SegmentStatus::SealUp,
+                ..Default::default()
+            },
+        ];
+        let metas = vec![JournalSegmentMetadata {
+            segment_seq: 0,
+            start_offset: 0,
+            end_offset: 99,
+            ..Default::default()
+        }];
+
+        let rows = build_segment_rows(&segments, &metas);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].segment_no, 0);
+        assert_eq!(rows[0].end_offset, 99);
+        assert_eq!(rows[1].segment_no, 1);
+        assert_eq!(rows[1].status, SegmentStatus::Write.to_string());
+        assert_eq!(rows[1].replicas, vec![2]);
+        assert_eq!(rows[1].start_offset, -1);
+    }
+}
@@ -0,0 +1,227 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::{
+    request::journal::{DeleteShardReq, NamespaceListReq, ShardListReq},
+    response::{
+        journal::{NamespaceListRow, ShardListRow},
+        PageReplyData,
+    },
+    state::HttpState,
+    tool::query::{apply_filters, apply_pagination, apply_sorting, build_query_params, Queryable},
+};
+use axum::{extract::State, Json};
+use common_base::{
+    error::common::CommonError,
+    http_response::{error_response, success_response},
+};
+use common_config::broker::broker_config;
+use grpc_clients::meta::journal::call::{delete_shard, list_shard};
+use metadata_struct::journal::shard::JournalShard;
+use protocol::meta::meta_service_journal::{DeleteShardRequest, ListShardRequest};
+use std::{collections::BTreeMap, sync::Arc};
+
+pub async fn namespace_list(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<NamespaceListReq>,
+) -> String {
+    let options = build_query_params(
+        params.page,
+        params.limit,
+        params.sort_field,
+        params.sort_by,
+        params.filter_field,
+        params.filter_values,
+        params.exact_match,
+    );
+
+    let shards = match list_journal_shards(&state, "", "").await {
+        Ok(shards) => shards,
+        Err(e) => return error_response(e.to_string()),
+    };
+
+    let namespaces = build_namespace_rows(&shards);
+    let filtered = apply_filters(namespaces, &options);
+    let sorted = apply_sorting(filtered, &options);
+    let pagination = apply_pagination(sorted, &options);
+
+    success_response(PageReplyData {
+        data: pagination.0,
+        total_count: pagination.1,
+    })
+}
+
+impl Queryable for NamespaceListRow {
+    fn get_field_str(&self, field: &str) -> Option<String> {
+        match field {
+            "namespace" => Some(self.namespace.clone()),
+            "shard_num" => Some(self.shard_num.to_string()),
+            _ => None,
+        }
+    }
+}
+
+pub async fn shard_list(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<ShardListReq>,
+) -> String {
+    let options = build_query_params(
+        params.page,
+        params.limit,
+        params.sort_field,
+        params.sort_by,
+        params.filter_field,
+        params.filter_values,
+        params.exact_match,
+    );
+
+    // The meta service only looks a shard up by name inside a namespace.
+    let namespace = params.namespace.unwrap_or_default();
+    let shard_name = params.shard_name.unwrap_or_default();
+    let lookup_name = if namespace.is_empty() {
+        ""
+    } else {
+        &shard_name
+    };
+    let shards = match list_journal_shards(&state, &namespace, lookup_name).await {
+        Ok(shards) => shards,
+        Err(e) => return error_response(e.to_string()),
+    };
+
+    let rows: Vec<ShardListRow> = shards
+        .iter()
+        .filter(|shard| shard_name.is_empty() || shard.shard_name == shard_name)
+        .map(build_shard_row)
+        .collect();
+    let filtered = apply_filters(rows, &options);
+    let sorted = apply_sorting(filtered, &options);
+    let pagination = apply_pagination(sorted, &options);
+
+    success_response(PageReplyData {
+        data: pagination.0,
+        total_count: pagination.1,
+    })
+}
+
+impl Queryable for ShardListRow {
+    fn get_field_str(&self, field: &str) -> Option<String> {
+        match field {
+            "namespace" => Some(self.namespace.clone()),
+            "shard_name" => Some(self.shard_name.clone()),
+            "status" => Some(self.status.clone()),
+            "create_time" => Some(self.create_time.to_string()),
+            _ => None,
+        }
+    }
+}
+
+pub async fn shard_delete(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<DeleteShardReq>,
+) -> String {
+    if params.namespace.is_empty() || params.shard_name.is_empty() {
+        return error_response("Namespace and shard name cannot be empty".to_string());
+    }
+
+    // The meta service marks the shard as being deleted and the journal
+    // servers remove its segments in the background.
+    let request = DeleteShardRequest {
+        cluster_name: state.broker_cache.cluster_name.clone(),
+        namespace: params.namespace,
+        shard_name: params.shard_name,
+    };
+    match delete_shard(
+        &state.client_pool,
+        &broker_config().get_meta_service_addr(),
+        request,
+    )
+    .await
+    {
+        Ok(_) => success_response("success"),
+        Err(e) => error_response(e.to_string()),
+    }
+}
+
+/// List the shards known to the meta service, an empty namespace and shard
+/// name list all the shards of the cluster.
+pub(crate) async fn list_journal_shards(
+    state: &Arc<HttpState>,
+    namespace: &str,
+    shard_name: &str,
+) -> Result<Vec<JournalShard>, CommonError> {
+    let request = ListShardRequest {
+        cluster_name: state.broker_cache.cluster_name.clone(),
+        namespace: namespace.to_string(),
+        shard_name: shard_name.to_string(),
+    };
+    let reply = list_shard(
+        &state.client_pool,
+        &broker_config().get_meta_service_addr(),
+        request,
+    )
+    .await?;
+    Ok(serde_json::from_slice::<Vec<JournalShard>>(&reply.shards)?)
+}
+
+fn build_namespace_rows(shards: &[JournalShard]) -> Vec<NamespaceListRow> {
+    let mut namespaces: BTreeMap<String, usize> = BTreeMap::new();
+    for shard in shards {
+        *namespaces.entry(shard.namespace.clone()).or_default() += 1;
+    }
+
+    namespaces
+        .into_iter()
+        .map(|(namespace, shard_num)| NamespaceListRow {
+            namespace,
+            shard_num,
+        })
+        .collect()
+}
+
+fn build_shard_row(shard: &JournalShard) -> ShardListRow {
+    ShardListRow {
+        namespace: shard.namespace.clone(),
+        shard_name: shard.shard_name.clone(),
+        status: format!("{:?}", shard.status),
+        start_segment_seq: shard.start_segment_seq,
+        active_segment_seq: shard.active_segment_seq,
+        last_segment_seq: shard.last_segment_seq,
+        replica_num: shard.config.replica_num,
+        max_segment_size: shard.config.max_segment_size,
+        create_time: shard.create_time,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shard(namespace: &str, shard_name: &str) -> JournalShard {
+        JournalShard {
+            namespace: namespace.to_string(),
+            shard_name: shard_name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn build_namespace_rows_test() {
+        let shards = vec![shard("ns2", "s1"), shard("ns1", "s1"), shard("ns2", "s2")];
+        let rows = build_namespace_rows(&shards);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].namespace, "ns1");
+        assert_eq!(rows[0].shard_num, 1);
+        assert_eq!(rows[1].namespace, "ns2");
+        assert_eq!(rows[1].shard_num, 2);
+    }
+}
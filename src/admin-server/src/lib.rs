@@ -13,6 +13,7 @@
 // limitations under the License.
 
 #![allow(clippy::result_large_err)]
+pub mod auth;
 pub mod client;
 pub mod cluster;
 pub mod journal;
@@ -13,15 +13,31 @@
 // limitations under the License.
 
 use crate::{
-    request::mqtt::ClientListReq,
-    response::{mqtt::ClientListRow, PageReplyData},
+    request::mqtt::{ClientDetailReq, ClientListReq, KickClientReq},
+    response::{
+        mqtt::{
+            ClientDetailResp, ClientInflightMessage, ClientListRow, ClientQueuedSubscription,
+            KickClientResp,
+        },
+        PageReplyData,
+    },
     state::HttpState,
     tool::query::{apply_filters, apply_pagination, apply_sorting, build_query_params, Queryable},
 };
 use axum::{extract::State, Json};
-use common_base::{http_response::success_response, utils::time_util::timestamp_to_local_datetime};
+use common_base::{
+    error::common::CommonError,
+    http_response::{error_response, success_response},
+    utils::time_util::timestamp_to_local_datetime,
+};
+use grpc_clients::mqtt::admin::call::{broker_mqtt_inspect_client, broker_mqtt_kick_client};
+use mqtt_broker::storage::session::SessionStorage;
+use protocol::broker::broker_mqtt_admin::{InspectClientRequest, KickClientRequest};
 use std::sync::Arc;
 
+// DISCONNECT reason code sent to a kicked client when none is given.
+const DEFAULT_KICK_REASON_CODE: u8 = 0x98;
+
 pub async fn client_list(
     State(state): State<Arc<HttpState>>,
     Json(params): Json<ClientListReq>,
@@ -92,3 +108,118 @@ fn get_field_str(&self, field: &str) -> Option<String> {
         }
     }
 }
+
+pub async fn client_kick(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<KickClientReq>,
+) -> String {
+    let (broker_id, addr) = match client_broker_addr(&state, &params.client_id).await {
+        Ok(data) => data,
+        Err(e) => return error_response(e.to_string()),
+    };
+
+    let request = KickClientRequest {
+        client_id: params.client_id.clone(),
+        reason_code: params.reason_code.unwrap_or(DEFAULT_KICK_REASON_CODE) as u32,
+        reason_string: params.reason_string.unwrap_or_default(),
+    };
+    match broker_mqtt_kick_client(&state.client_pool, &[addr], request).await {
+        Ok(reply) => success_response(KickClientResp {
+            client_id: params.client_id,
+            connect_id: reply.connect_id,
+            broker_id,
+        }),
+        Err(e) => error_response(e.to_string()),
+    }
+}
+
+pub async fn client_detail(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<ClientDetailReq>,
+) -> String {
+    let (_, addr) = match client_broker_addr(&state, &params.client_id).await {
+        Ok(data) => data,
+        Err(e) => return error_response(e.to_string()),
+    };
+
+    let request = InspectClientRequest {
+        client_id: params.client_id,
+    };
+    let reply = match broker_mqtt_inspect_client(&state.client_pool, &[addr], request).await {
+        Ok(reply) => reply,
+        Err(e) => return error_response(e.to_string()),
+    };
+
+    success_response(ClientDetailResp {
+        client_id: reply.client_id,
+        connected: reply.connected,
+        connect_id: reply.connect_id,
+        broker_id: reply.broker_id,
+        source_addr: reply.source_addr,
+        protocol: reply.protocol,
+        keep_alive: reply.keep_alive,
+        last_heartbeat_time: reply.last_heartbeat_time,
+        keep_alive_deadline: reply.keep_alive_deadline,
+        receive_maximum: reply.receive_maximum,
+        inflight_messages: reply
+            .inflight_messages
+            .into_iter()
+            .map(|message| ClientInflightMessage {
+                pkid: message.pkid,
+                direction: message.direction,
+                create_time: message.create_time,
+            })
+            .collect(),
+        queued_subscriptions: reply
+            .queued_subscriptions
+            .into_iter()
+            .map(|queued| ClientQueuedSubscription {
+                sub_path: queued.sub_path,
+                topic_name: queued.topic_name,
+                committed_offset: queued.committed_offset,
+                pending_messages: queued.pending_messages,
+                push_success_record_num: queued.push_success_record_num,
+                push_error_record_num: queued.push_error_record_num,
+                last_push_time: queued.last_push_time,
+            })
+            .collect(),
+    })
+}
+
+/// Resolve the broker that owns the session of a client, returning its id and
+/// inner grpc address. Operations on the client are forwarded to that broker.
+pub(crate) async fn client_broker_addr(
+    state: &Arc<HttpState>,
+    client_id: &str,
+) -> Result<(u64, String), CommonError> {
+    if client_id.is_empty() {
+        return Err(CommonError::CommonError(
+            "Client ID cannot be empty".to_string(),
+        ));
+    }
+
+    let session_storage = SessionStorage::new(state.client_pool.clone());
+    let Some(session) = session_storage.get_session(client_id.to_string()).await? else {
+        return Err(CommonError::CommonError(format!(
+            "Session of client {client_id} does not exist"
+        )));
+    };
+
+    let Some(broker_id) = session.broker_id else {
+        return Err(CommonError::CommonError(format!(
+            "Session of client {client_id} is not bound to any broker"
+        )));
+    };
+
+    state
+        .broker_cache
+        .node_list()
+        .into_iter()
+        .find(|node| node.node_id == broker_id)
+        .map(|node| (broker_id, node.node_inner_addr))
+        .ok_or_else(|| {
+            CommonError::CommonError(format!(
+                "Broker {broker_id} holding client {client_id} is not available"
+            ))
+        })
+}
@@ -20,7 +20,7 @@
 };
 use axum::{extract::State, Json};
 use common_base::{
-    error::ResultCommonError,
+    error::{common::CommonError, ResultCommonError},
     http_response::{error_response, success_response},
     tools::now_second,
     utils::time_util::timestamp_to_local_datetime,
@@ -29,13 +29,15 @@
     config_greptimedb::GreptimeDBConnectorConfig,
     config_kafka::KafkaConnectorConfig,
     config_local_file::LocalFileConnectorConfig,
+    config_mqtt::{MqttBridgeConnectorConfig, MqttBridgeDirection},
     config_postgres::PostgresConnectorConfig,
     config_pulsar::PulsarConnectorConfig,
-    connector::MQTTConnector,
+    config_webhook::WebhookConnectorConfig,
+    connector::{ConnectorDeliveryPolicy, MQTTConnector},
     connector_type::{connector_type_for_string, ConnectorType},
     status::MQTTStatus,
 };
-use mqtt_broker::storage::connector::ConnectorStorage;
+use mqtt_broker::{storage::connector::ConnectorStorage, subscribe::common::sub_path_validator};
 use std::sync::Arc;
 
 pub async fn connector_list(
@@ -52,19 +54,30 @@ pub async fn connector_list(
         params.exact_match,
     );
 
+    let connector_manager = &state.mqtt_context.connector_manager;
     let mut connectors = Vec::new();
-    for connector in state.mqtt_context.connector_manager.get_all_connector() {
+    for connector in connector_manager.get_all_connector() {
+        // Metrics only exist on the broker running the connector
+        let metrics = connector_manager.get_connector_metrics(&connector.connector_name);
         connectors.push(ConnectorListRow {
             connector_name: connector.connector_name.clone(),
             connector_type: connector.connector_type.to_string(),
             config: connector.config.clone(),
             topic_id: connector.topic_id.clone(),
+            topic_filters: connector.topic_filters.clone(),
             status: connector.status.to_string(),
             broker_id: if let Some(id) = connector.broker_id {
                 id.to_string()
             } else {
                 "-".to_string()
             },
+            lag: metrics.lag(),
+            committed_offsets: metrics.committed_offsets,
+            success_total: metrics.success_total,
+            failure_total: metrics.failure_total,
+            dead_letter_total: metrics.dead_letter_total,
+            discard_total: metrics.discard_total,
+            last_error: metrics.last_error.unwrap_or_else(|| "-".to_string()),
             create_time: timestamp_to_local_datetime(connector.create_time as i64),
             update_time: timestamp_to_local_datetime(connector.update_time as i64),
         });
@@ -124,6 +137,9 @@ async fn connector_create_inner(
 ) -> ResultCommonError {
     let connector_type = connector_type_for_string(params.connector_type.clone())?;
     connector_config_validator(&connector_type, &params.config)?;
+    connector_topic_validator(&connector_type, &params)?;
+
+    let delivery_policy = connector_delivery_policy(&connector_type, &params)?;
 
     let storage = ConnectorStorage::new(state.client_pool.clone());
     let connector = MQTTConnector {
@@ -132,8 +148,10 @@ async fn connector_create_inner(
         connector_type,
         config: params.config.clone(),
         topic_id: params.topic_id.clone(),
+        topic_filters: params.topic_filters.clone(),
         status: MQTTStatus::Idle,
         broker_id: None,
+        delivery_policy,
         create_time: now_second(),
         update_time: now_second(),
     };
@@ -158,6 +176,62 @@ fn connector_config_validator(connector_type: &ConnectorType, config: &str) -> R
         ConnectorType::Postgres => {
             let _postgres_config: PostgresConnectorConfig = serde_json::from_str(config)?;
         }
+        ConnectorType::Mqtt => {
+            let mqtt_config: MqttBridgeConnectorConfig = serde_json::from_str(config)?;
+            mqtt_config.validate()?;
+        }
+        ConnectorType::Webhook => {
+            let webhook_config: WebhookConnectorConfig = serde_json::from_str(config)?;
+            webhook_config.validate()?;
+        }
+    }
+    Ok(())
+}
+
+// The retry settings of a webhook config predate the delivery policy, they are
+// moved into it. Setting both would leave one of them silently ignored.
+fn connector_delivery_policy(
+    connector_type: &ConnectorType,
+    params: &CreateConnectorReq,
+) -> Result<ConnectorDeliveryPolicy, CommonError> {
+    if *connector_type != ConnectorType::Webhook {
+        return Ok(params.delivery_policy.clone().unwrap_or_default());
+    }
+
+    let webhook_config: WebhookConnectorConfig = serde_json::from_str(&params.config)?;
+    if !webhook_config.has_retry_settings() {
+        return Ok(params.delivery_policy.clone().unwrap_or_default());
+    }
+    if params.delivery_policy.is_some() {
+        return Err(CommonError::CommonError(
+            "max_retries, retry_min_interval_ms and retry_max_interval_ms of the webhook config cannot be combined with delivery_policy, set them in delivery_policy only".to_string(),
+        ));
+    }
+    let mut policy = ConnectorDeliveryPolicy::default();
+    webhook_config.apply_retry_settings(&mut policy);
+    Ok(policy)
+}
+
+fn connector_topic_validator(
+    connector_type: &ConnectorType,
+    params: &CreateConnectorReq,
+) -> ResultCommonError {
+    for filter in params.topic_filters.iter() {
+        sub_path_validator(filter).map_err(|e| CommonError::CommonError(e.to_string()))?;
+    }
+
+    // An MQTT ingress connector writes to local topics instead of reading one
+    if *connector_type == ConnectorType::Mqtt {
+        let mqtt_config: MqttBridgeConnectorConfig = serde_json::from_str(&params.config)?;
+        if mqtt_config.direction == MqttBridgeDirection::Ingress {
+            return Ok(());
+        }
+    }
+
+    if params.topic_id.is_empty() && params.topic_filters.is_empty() {
+        return Err(CommonError::CommonError(
+            "Either topic_id or topic_filters must be set".to_string(),
+        ));
     }
     Ok(())
 }
@@ -0,0 +1,258 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    request::mqtt::{DelayMessageCancelReq, DelayMessageDetailReq, DelayMessageListReq},
+    response::{
+        mqtt::{DelayMessageCancelResp, DelayMessageDetailResp, DelayMessageListRow},
+        PageReplyData,
+    },
+    state::HttpState,
+    tool::query::{apply_filters, apply_pagination, apply_sorting, build_query_params, Queryable},
+};
+use axum::{extract::State, Json};
+use common_base::{
+    error::common::CommonError,
+    http_response::{error_response, success_response},
+};
+use delay_message::{DelayMessageEntry, DelayMessageStatus, DELAY_MESSAGE_LIST_MAX_NUM};
+use metadata_struct::{adapter::record::Record, mqtt::message::MqttMessage};
+use mqtt_broker::handler::cache::MQTTCacheManager;
+use std::sync::Arc;
+
+// Delay message payloads are cut to this size in the detail.
+const MAX_DETAIL_PAYLOAD_SIZE: usize = 1024;
+
+pub async fn delay_message_list(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<DelayMessageListReq>,
+) -> String {
+    let options = build_query_params(
+        params.page,
+        params.limit,
+        params.sort_field,
+        params.sort_by,
+        params.filter_field,
+        params.filter_values,
+        params.exact_match,
+    );
+
+    let cache_manager = &state.mqtt_context.cache_manager;
+    let topic_id = match &params.topic_name {
+        Some(topic_name) => match topic_id_by_name(cache_manager, topic_name) {
+            Ok(topic_id) => Some(topic_id),
+            Err(e) => return error_response(e.to_string()),
+        },
+        None => None,
+    };
+
+    let messages = match state
+        .mqtt_context
+        .delay_message_manager
+        .list(topic_id.as_deref(), DELAY_MESSAGE_LIST_MAX_NUM)
+        .await
+    {
+        Ok(messages) => messages,
+        Err(e) => return error_response(e.to_string()),
+    };
+
+    let rows = messages
+        .into_iter()
+        .map(|info| DelayMessageListRow {
+            topic_name: topic_name_by_id(cache_manager, &info.target_shard_name),
+            id: info.id,
+            delay_timestamp: info.delay_timestamp,
+            create_time: info.create_time,
+        })
+        .collect();
+
+    let filtered = apply_filters(rows, &options);
+    let sorted = apply_sorting(filtered, &options);
+    let pagination = apply_pagination(sorted, &options);
+
+    success_response(PageReplyData {
+        data: pagination.0,
+        total_count: pagination.1,
+    })
+}
+
+impl Queryable for DelayMessageListRow {
+    fn get_field_str(&self, field: &str) -> Option<String> {
+        match field {
+            "id" => Some(self.id.clone()),
+            "topic_name" => Some(self.topic_name.clone()),
+            "delay_timestamp" => Some(self.delay_timestamp.to_string()),
+            "create_time" => Some(self.create_time.to_string()),
+            _ => None,
+        }
+    }
+}
+
+pub async fn delay_message_detail(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<DelayMessageDetailReq>,
+) -> String {
+    let delay_message_manager = &state.mqtt_context.delay_message_manager;
+    let entry = match delay_message_manager.get(&params.id).await {
+        Ok(Some(entry)) => entry,
+        Ok(None) => {
+            return error_response(format!("Delay message {} does not exist", params.id));
+        }
+        Err(e) => return error_response(e.to_string()),
+    };
+
+    let payload = match delay_message_manager.get_payload(&entry.info).await {
+        Ok(payload) => payload,
+        Err(e) => return error_response(e.to_string()),
+    };
+
+    match build_delay_message_detail(&state.mqtt_context.cache_manager, entry, payload) {
+        Ok(detail) => success_response(detail),
+        Err(e) => error_response(e.to_string()),
+    }
+}
+
+pub async fn delay_message_cancel(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<DelayMessageCancelReq>,
+) -> String {
+    let delay_message_manager = &state.mqtt_context.delay_message_manager;
+    let result = match (&params.id, &params.topic_name) {
+        (Some(id), None) => delay_message_manager
+            .cancel(id)
+            .await
+            .map(|cancelled| cancelled as u64),
+        (None, Some(topic_name)) => {
+            match topic_id_by_name(&state.mqtt_context.cache_manager, topic_name) {
+                Ok(topic_id) => delay_message_manager.cancel_by_topic(&topic_id).await,
+                Err(e) => Err(e),
+            }
+        }
+        _ => {
+            return error_response("Exactly one of id and topic_name must be set".to_string());
+        }
+    };
+
+    match result {
+        Ok(cancelled_num) => success_response(DelayMessageCancelResp { cancelled_num }),
+        Err(e) => error_response(e.to_string()),
+    }
+}
+
+fn build_delay_message_detail(
+    cache_manager: &Arc<MQTTCacheManager>,
+    entry: DelayMessageEntry,
+    payload: Option<Record>,
+) -> Result<DelayMessageDetailResp, CommonError> {
+    let message = payload.map(MqttMessage::decode_record).transpose()?;
+    let status = match entry.status {
+        DelayMessageStatus::Pending => "pending",
+        DelayMessageStatus::Delivered => "delivered",
+        DelayMessageStatus::Cancelled => "cancelled",
+    };
+
+    Ok(DelayMessageDetailResp {
+        topic_name: topic_name_by_id(cache_manager, &entry.info.target_shard_name),
+        id: entry.info.id,
+        status: status.to_string(),
+        delay_timestamp: entry.info.delay_timestamp,
+        create_time: entry.info.create_time,
+        client_id: message.as_ref().map(|message| message.client_id.clone()),
+        qos: message.as_ref().map(|message| message.qos.into()),
+        payload: message.as_ref().map(|message| {
+            let end = message.payload.len().min(MAX_DETAIL_PAYLOAD_SIZE);
+            String::from_utf8_lossy(&message.payload[..end]).to_string()
+        }),
+        payload_size: message.as_ref().map(|message| message.payload.len()),
+    })
+}
+
+// Delay messages are stored against the topic id, the topic must be known
+// to this broker to look them up by name.
+fn topic_id_by_name(
+    cache_manager: &Arc<MQTTCacheManager>,
+    topic_name: &str,
+) -> Result<String, CommonError> {
+    match cache_manager.get_topic_by_name(topic_name) {
+        Some(topic) => Ok(topic.topic_id),
+        None => Err(CommonError::CommonError(format!(
+            "Topic {topic_name} does not exist"
+        ))),
+    }
+}
+
+fn topic_name_by_id(cache_manager: &Arc<MQTTCacheManager>, topic_id: &str) -> String {
+    cache_manager
+        .topic_name_by_id(topic_id)
+        .unwrap_or_else(|| topic_id.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use metadata_struct::{delay_info::DelayMessageInfo, mqtt::topic::MQTTTopic};
+    use mqtt_broker::common::tool::test_build_mqtt_cache_manager;
+
+    #[test]
+    fn build_delay_message_detail_test() {
+        let cache_manager = test_build_mqtt_cache_manager();
+        let topic = MQTTTopic::new(
+            "t1".to_string(),
+            "cluster".to_string(),
+            "sensor/1".to_string(),
+        );
+        cache_manager.add_topic(&topic.topic_name, &topic);
+        assert_eq!(
+            topic_id_by_name(&cache_manager, "sensor/1").unwrap(),
+            "t1".to_string()
+        );
+        assert!(topic_id_by_name(&cache_manager, "sensor/2").is_err());
+
+        let entry = DelayMessageEntry {
+            info: DelayMessageInfo {
+                id: "m1".to_string(),
+                delay_shard_name: "s1".to_string(),
+                target_shard_name: "t1".to_string(),
+                offset: 0,
+                delay_timestamp: 100,
+                create_time: 10,
+            },
+            status: DelayMessageStatus::Pending,
+        };
+        let message = MqttMessage {
+            client_id: "c1".to_string(),
+            payload: "hello".into(),
+            ..Default::default()
+        };
+        let detail = build_delay_message_detail(
+            &cache_manager,
+            entry.clone(),
+            Some(Record::build_byte(message.encode())),
+        )
+        .unwrap();
+        assert_eq!(detail.topic_name, "sensor/1");
+        assert_eq!(detail.status, "pending");
+        assert_eq!(detail.client_id, Some("c1".to_string()));
+        assert_eq!(detail.payload, Some("hello".to_string()));
+        assert_eq!(detail.payload_size, Some(5));
+
+        let mut entry = entry;
+        entry.status = DelayMessageStatus::Delivered;
+        entry.info.target_shard_name = "t2".to_string();
+        let detail = build_delay_message_detail(&cache_manager, entry, None).unwrap();
+        assert_eq!(detail.topic_name, "t2");
+        assert_eq!(detail.status, "delivered");
+        assert!(detail.payload.is_none());
+    }
+}
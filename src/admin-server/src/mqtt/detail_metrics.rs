@@ -0,0 +1,250 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    request::mqtt::{DetailMetricsConfigGetReq, DetailMetricsConfigSetReq, DetailMetricsListReq},
+    response::{mqtt::DetailMetricsListRow, PageReplyData},
+    state::HttpState,
+    tool::query::{apply_filters, apply_pagination, apply_sorting, build_query_params, Queryable},
+};
+use axum::{extract::State, Json};
+use common_base::{
+    http_response::{error_response, success_response},
+    utils::time_util::timestamp_to_local_datetime,
+};
+use common_config::config::{DetailMetricsEviction, MqttDetailMetrics};
+use mqtt_broker::{
+    common::detail_metrics::DetailMetricsSnapshot,
+    handler::{
+        dynamic_config::{save_cluster_dynamic_config, ClusterDynamicConfig},
+        error::MqttBrokerError,
+    },
+};
+use std::sync::Arc;
+
+pub async fn detail_metrics_topic_list(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<DetailMetricsListReq>,
+) -> String {
+    let data_list = state
+        .mqtt_context
+        .cache_manager
+        .detail_metrics
+        .list_topics();
+    detail_metrics_list(data_list, params)
+}
+
+pub async fn detail_metrics_client_list(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<DetailMetricsListReq>,
+) -> String {
+    let data_list = state
+        .mqtt_context
+        .cache_manager
+        .detail_metrics
+        .list_clients();
+    detail_metrics_list(data_list, params)
+}
+
+fn detail_metrics_list(
+    data_list: Vec<DetailMetricsSnapshot>,
+    params: DetailMetricsListReq,
+) -> String {
+    let options = build_query_params(
+        params.page,
+        params.limit,
+        params.sort_field,
+        params.sort_by,
+        params.filter_field,
+        params.filter_values,
+        params.exact_match,
+    );
+
+    let results = data_list
+        .into_iter()
+        .map(|entry| DetailMetricsListRow {
+            name: entry.name,
+            messages_in: entry.messages_in,
+            messages_out: entry.messages_out,
+            bytes_in: entry.bytes_in,
+            bytes_out: entry.bytes_out,
+            dropped: entry.dropped,
+            inflight: entry.inflight,
+            avg_latency_ms: entry.avg_latency_ms,
+            max_latency_ms: entry.max_latency_ms,
+            last_active: timestamp_to_local_datetime(entry.last_active as i64),
+        })
+        .collect();
+
+    let filtered = apply_filters(results, &options);
+    let sorted = apply_sorting(filtered, &options);
+    let pagination = apply_pagination(sorted, &options);
+
+    success_response(PageReplyData {
+        data: pagination.0,
+        total_count: pagination.1,
+    })
+}
+
+impl Queryable for DetailMetricsListRow {
+    fn get_field_str(&self, field: &str) -> Option<String> {
+        // Sorting compares strings, numbers are padded so they sort by value
+        match field {
+            "name" => Some(self.name.clone()),
+            "messages_in" => Some(format!("{:020}", self.messages_in)),
+            "messages_out" => Some(format!("{:020}", self.messages_out)),
+            "bytes_in" => Some(format!("{:020}", self.bytes_in)),
+            "bytes_out" => Some(format!("{:020}", self.bytes_out)),
+            "dropped" => Some(format!("{:020}", self.dropped)),
+            "inflight" => Some(format!("{:020}", self.inflight.max(0))),
+            "avg_latency_ms" => Some(format!("{:020}", self.avg_latency_ms)),
+            "max_latency_ms" => Some(format!("{:020}", self.max_latency_ms)),
+            "last_active" => Some(self.last_active.clone()),
+            _ => None,
+        }
+    }
+}
+
+pub async fn detail_metrics_config_get(
+    State(state): State<Arc<HttpState>>,
+    Json(_params): Json<DetailMetricsConfigGetReq>,
+) -> String {
+    success_response(state.mqtt_context.cache_manager.detail_metrics.get_config())
+}
+
+pub async fn detail_metrics_config_set(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<DetailMetricsConfigSetReq>,
+) -> String {
+    if let Err(e) = detail_metrics_config_set_inner(&state, params).await {
+        return error_response(e.to_string());
+    }
+    success_response("success")
+}
+
+async fn detail_metrics_config_set_inner(
+    state: &Arc<HttpState>,
+    params: DetailMetricsConfigSetReq,
+) -> Result<(), MqttBrokerError> {
+    let cache_manager = &state.mqtt_context.cache_manager;
+    let config = merge_detail_metrics_config(cache_manager.detail_metrics.get_config(), params)?;
+
+    save_cluster_dynamic_config(
+        &state.client_pool,
+        ClusterDynamicConfig::MqttDetailMetrics,
+        config.encode(),
+    )
+    .await?;
+
+    cache_manager.update_detail_metrics_config(config);
+    Ok(())
+}
+
+fn merge_detail_metrics_config(
+    mut config: MqttDetailMetrics,
+    params: DetailMetricsConfigSetReq,
+) -> Result<MqttDetailMetrics, MqttBrokerError> {
+    if let Some(enable) = params.enable {
+        config.enable = enable;
+    }
+    if let Some(topic_filters) = params.topic_filters {
+        config.topic_filters = topic_filters;
+    }
+    if let Some(client_id_filters) = params.client_id_filters {
+        config.client_id_filters = client_id_filters;
+    }
+    if let Some(max_topics) = params.max_topics {
+        config.max_topics = max_topics;
+    }
+    if let Some(max_clients) = params.max_clients {
+        config.max_clients = max_clients;
+    }
+    if let Some(eviction) = params.eviction {
+        config.eviction = match eviction.as_str() {
+            "lru" => DetailMetricsEviction::Lru,
+            "top_n" => DetailMetricsEviction::TopN,
+            _ => {
+                return Err(MqttBrokerError::InvalidDetailMetricsConfig(format!(
+                    "unknown eviction {}, must be lru or top_n",
+                    eviction
+                )));
+            }
+        };
+    }
+
+    if config
+        .topic_filters
+        .iter()
+        .chain(config.client_id_filters.iter())
+        .any(|filter| filter.trim().is_empty())
+    {
+        return Err(MqttBrokerError::InvalidDetailMetricsConfig(
+            "filters must not be empty".to_string(),
+        ));
+    }
+    if config.max_topics == 0 || config.max_clients == 0 {
+        return Err(MqttBrokerError::InvalidDetailMetricsConfig(
+            "max_topics and max_clients must be greater than 0".to_string(),
+        ));
+    }
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_detail_metrics_config_test() {
+        let current = MqttDetailMetrics {
+            enable: false,
+            topic_filters: vec!["sensor/#".to_string()],
+            client_id_filters: Vec::new(),
+            max_topics: 1000,
+            max_clients: 1000,
+            eviction: DetailMetricsEviction::Lru,
+        };
+
+        let params = DetailMetricsConfigSetReq {
+            enable: Some(true),
+            max_topics: Some(50),
+            eviction: Some("top_n".to_string()),
+            ..Default::default()
+        };
+        let config = merge_detail_metrics_config(current.clone(), params).unwrap();
+        assert!(config.enable);
+        assert_eq!(config.topic_filters, vec!["sensor/#".to_string()]);
+        assert_eq!(config.max_topics, 50);
+        assert_eq!(config.max_clients, 1000);
+        assert_eq!(config.eviction, DetailMetricsEviction::TopN);
+
+        let params = DetailMetricsConfigSetReq {
+            eviction: Some("fifo".to_string()),
+            ..Default::default()
+        };
+        assert!(merge_detail_metrics_config(current.clone(), params).is_err());
+
+        let params = DetailMetricsConfigSetReq {
+            max_clients: Some(0),
+            ..Default::default()
+        };
+        assert!(merge_detail_metrics_config(current.clone(), params).is_err());
+
+        let params = DetailMetricsConfigSetReq {
+            client_id_filters: Some(vec!["".to_string()]),
+            ..Default::default()
+        };
+        assert!(merge_detail_metrics_config(current, params).is_err());
+    }
+}
@@ -0,0 +1,194 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    request::mqtt::{CreateListenerReq, ListenerListReq, ListenerOperateReq},
+    response::{mqtt::ListenerListRow, PageReplyData},
+    state::HttpState,
+    tool::query::{apply_filters, apply_pagination, apply_sorting, build_query_params, Queryable},
+};
+use axum::{extract::State, Json};
+use common_base::http_response::{error_response, success_response};
+use common_config::config::MqttListener;
+use std::{net::SocketAddr, sync::Arc};
+
+const LISTENER_PROTOCOLS: [&str; 5] = ["tcp", "tls", "websocket", "websockets", "quic"];
+const LISTENER_AUTHENTICATIONS: [&str; 3] = ["password", "x509", "anonymous"];
+
+pub async fn listener_list(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<ListenerListReq>,
+) -> String {
+    let options = build_query_params(
+        params.page,
+        params.limit,
+        params.sort_field,
+        params.sort_by,
+        params.filter_field,
+        params.filter_values,
+        params.exact_match,
+    );
+
+    let mut listeners = Vec::new();
+    for status in state.mqtt_context.listener_manager.list_listeners() {
+        let listener = status.listener;
+        listeners.push(ListenerListRow {
+            tls: listener.is_tls(),
+            mtls: listener.tls_ca.is_some(),
+            name: listener.name,
+            protocol: listener.protocol,
+            bind_addr: listener.bind_addr,
+            authentication: listener.authentication,
+            max_connections: listener.max_connections,
+            mountpoint: listener.mountpoint,
+            websocket_path: listener.websocket_path,
+            proxy_protocol: listener.proxy_protocol,
+            running: status.running,
+            connection_num: status.connection_num,
+        });
+    }
+
+    let filtered = apply_filters(listeners, &options);
+    let sorted = apply_sorting(filtered, &options);
+    let pagination = apply_pagination(sorted, &options);
+
+    success_response(PageReplyData {
+        data: pagination.0,
+        total_count: pagination.1,
+    })
+}
+
+impl Queryable for ListenerListRow {
+    fn get_field_str(&self, field: &str) -> Option<String> {
+        match field {
+            "name" => Some(self.name.clone()),
+            "protocol" => Some(self.protocol.clone()),
+            "bind_addr" => Some(self.bind_addr.clone()),
+            "running" => Some(self.running.to_string()),
+            _ => None,
+        }
+    }
+}
+
+pub async fn listener_create(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<CreateListenerReq>,
+) -> String {
+    if let Err(e) = listener_validator(&params) {
+        return error_response(e);
+    }
+
+    let start = params.start;
+    let listener = MqttListener {
+        name: params.name.clone(),
+        protocol: params.protocol,
+        bind_addr: params.bind_addr,
+        tls_cert: params.tls_cert,
+        tls_key: params.tls_key,
+        tls_ca: params.tls_ca,
+        authentication: params.authentication,
+        max_connections: params.max_connections,
+        mountpoint: params.mountpoint,
+        websocket_path: params.websocket_path,
+        proxy_protocol: params.proxy_protocol,
+    };
+
+    let listener_manager = &state.mqtt_context.listener_manager;
+    if let Err(e) = listener_manager.add_listener(listener).await {
+        return error_response(e.to_string());
+    }
+
+    if start {
+        if let Err(e) = listener_manager.start_listener(&params.name).await {
+            return error_response(e.to_string());
+        }
+    }
+    success_response("success")
+}
+
+pub async fn listener_delete(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<ListenerOperateReq>,
+) -> String {
+    if let Err(e) = state
+        .mqtt_context
+        .listener_manager
+        .delete_listener(&params.name)
+        .await
+    {
+        return error_response(e.to_string());
+    }
+    success_response("success")
+}
+
+pub async fn listener_start(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<ListenerOperateReq>,
+) -> String {
+    if let Err(e) = state
+        .mqtt_context
+        .listener_manager
+        .start_listener(&params.name)
+        .await
+    {
+        return error_response(e.to_string());
+    }
+    success_response("success")
+}
+
+pub async fn listener_stop(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<ListenerOperateReq>,
+) -> String {
+    if let Err(e) = state
+        .mqtt_context
+        .listener_manager
+        .stop_listener(&params.name)
+        .await
+    {
+        return error_response(e.to_string());
+    }
+    success_response("success")
+}
+
+fn listener_validator(params: &CreateListenerReq) -> Result<(), String> {
+    if params.name.is_empty() {
+        return Err("Listener name cannot be empty".to_string());
+    }
+
+    if !LISTENER_PROTOCOLS.contains(&params.protocol.as_str()) {
+        return Err(format!(
+            "Unsupported listener protocol {}, optional values: {:?}",
+            params.protocol, LISTENER_PROTOCOLS
+        ));
+    }
+
+    if params.bind_addr.parse::<SocketAddr>().is_err() {
+        return Err(format!("Invalid bind address {}", params.bind_addr));
+    }
+
+    for authentication in params.authentication.iter() {
+        if !LISTENER_AUTHENTICATIONS.contains(&authentication.as_str()) {
+            return Err(format!(
+                "Unsupported authentication {}, optional values: {:?}",
+                authentication, LISTENER_AUTHENTICATIONS
+            ));
+        }
+    }
+
+    if params.tls_cert.is_some() != params.tls_key.is_some() {
+        return Err("tls_cert and tls_key must be set together".to_string());
+    }
+    Ok(())
+}
@@ -0,0 +1,261 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    auth::middleware::AdminIdentity,
+    request::mqtt::{DeleteRetainMessageReq, PublishMessageReq, RetainMessageListReq},
+    response::{mqtt::RetainMessageListRow, PageReplyData},
+    state::HttpState,
+    tool::query::{apply_filters, apply_pagination, apply_sorting, build_query_params, Queryable},
+};
+use axum::{extract::State, Extension, Json};
+use common_base::{
+    error::common::CommonError,
+    http_response::{error_response, success_response},
+};
+use metadata_struct::mqtt::{message::MqttMessage, topic::MQTTTopic};
+use mqtt_broker::{
+    handler::{message::publish_message_to_topic, topic::topic_name_validator},
+    storage::topic::TopicStorage,
+};
+use protocol::mqtt::common::{qos, Publish, PublishProperties};
+use std::sync::Arc;
+
+// Retained message payloads are cut to this size in the list.
+const MAX_LIST_PAYLOAD_SIZE: usize = 1024;
+
+pub async fn message_publish(
+    State(state): State<Arc<HttpState>>,
+    Extension(identity): Extension<AdminIdentity>,
+    Json(params): Json<PublishMessageReq>,
+) -> String {
+    let (publish, publish_properties) = match build_publish(&params) {
+        Ok(data) => data,
+        Err(e) => return error_response(e.to_string()),
+    };
+
+    let client_id = format!("admin:{}", identity.username);
+    if let Err(e) = publish_message_to_topic(
+        &state.mqtt_context.cache_manager,
+        &state.client_pool,
+        &state.mqtt_context.message_storage_adapter,
+        &client_id,
+        &publish,
+        &publish_properties,
+    )
+    .await
+    {
+        return error_response(e.to_string());
+    }
+
+    success_response("success")
+}
+
+pub async fn retain_message_list(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<RetainMessageListReq>,
+) -> String {
+    let options = build_query_params(
+        params.page,
+        params.limit,
+        params.sort_field,
+        params.sort_by,
+        params.filter_field,
+        params.filter_values,
+        params.exact_match,
+    );
+
+    // Retained messages are read from the meta service, the local topic cache
+    // is not guaranteed to hold the latest retained message of every topic.
+    let topic_storage = TopicStorage::new(state.client_pool.clone());
+    let topics = match topic_storage.all().await {
+        Ok(topics) => topics,
+        Err(e) => return error_response(e.to_string()),
+    };
+
+    let mut messages = Vec::new();
+    for entry in topics.iter() {
+        let topic = entry.value();
+        if let Some(topic_name) = &params.topic_name {
+            if topic.topic_name != *topic_name {
+                continue;
+            }
+        }
+
+        match build_retain_message_row(topic) {
+            Ok(Some(row)) => messages.push(row),
+            Ok(None) => {}
+            Err(e) => return error_response(e.to_string()),
+        }
+    }
+
+    let filtered = apply_filters(messages, &options);
+    let sorted = apply_sorting(filtered, &options);
+    let pagination = apply_pagination(sorted, &options);
+
+    success_response(PageReplyData {
+        data: pagination.0,
+        total_count: pagination.1,
+    })
+}
+
+impl Queryable for RetainMessageListRow {
+    fn get_field_str(&self, field: &str) -> Option<String> {
+        match field {
+            "topic_name" => Some(self.topic_name.clone()),
+            "client_id" => Some(self.client_id.clone()),
+            "create_time" => Some(self.create_time.to_string()),
+            _ => None,
+        }
+    }
+}
+
+pub async fn retain_message_delete(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<DeleteRetainMessageReq>,
+) -> String {
+    let topic_storage = TopicStorage::new(state.client_pool.clone());
+    if let Err(e) = topic_storage
+        .delete_retain_message(params.topic_name.clone())
+        .await
+    {
+        return error_response(e.to_string());
+    }
+
+    state
+        .mqtt_context
+        .cache_manager
+        .update_topic_retain_message(&params.topic_name, Some(Vec::new()));
+
+    success_response("success")
+}
+
+fn build_publish(
+    params: &PublishMessageReq,
+) -> Result<(Publish, Option<PublishProperties>), CommonError> {
+    topic_name_validator(&params.topic).map_err(|e| CommonError::CommonError(e.to_string()))?;
+    if params.topic.contains('+') || params.topic.contains('#') {
+        return Err(CommonError::CommonError(format!(
+            "Topic name {} must not contain wildcards",
+            params.topic
+        )));
+    }
+
+    let Some(qos) = qos(params.qos) else {
+        return Err(CommonError::CommonError(format!(
+            "Invalid QoS {}, must be 0, 1 or 2",
+            params.qos
+        )));
+    };
+
+    let publish = Publish {
+        qos,
+        retain: params.retain,
+        topic: params.topic.clone().into(),
+        payload: params.payload.clone().into(),
+        ..Default::default()
+    };
+
+    let properties = PublishProperties {
+        payload_format_indicator: params.payload_format_indicator,
+        message_expiry_interval: params.message_expiry_interval,
+        topic_alias: None,
+        response_topic: params.response_topic.clone(),
+        correlation_data: params.correlation_data.clone().map(|data| data.into()),
+        user_properties: params.user_properties.clone(),
+        subscription_identifiers: Vec::new(),
+        content_type: params.content_type.clone(),
+    };
+
+    Ok((publish, Some(properties)))
+}
+
+fn build_retain_message_row(
+    topic: &MQTTTopic,
+) -> Result<Option<RetainMessageListRow>, CommonError> {
+    let Some(retain_message) = &topic.retain_message else {
+        return Ok(None);
+    };
+    if retain_message.is_empty() {
+        return Ok(None);
+    }
+
+    let message = serde_json::from_slice::<MqttMessage>(retain_message)?;
+    let end = message.payload.len().min(MAX_LIST_PAYLOAD_SIZE);
+    Ok(Some(RetainMessageListRow {
+        topic_name: topic.topic_name.clone(),
+        client_id: message.client_id.clone(),
+        qos: message.qos.into(),
+        payload: String::from_utf8_lossy(&message.payload[..end]).to_string(),
+        payload_size: message.payload.len(),
+        content_type: message.content_type.clone(),
+        expired_at: topic.retain_message_expired_at.unwrap_or_default(),
+        create_time: message.create_time,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_publish_test() {
+        let mut params = PublishMessageReq {
+            topic: "sensor/1".to_string(),
+            payload: "hello".to_string(),
+            qos: 1,
+            retain: true,
+            content_type: Some("text/plain".to_string()),
+            ..Default::default()
+        };
+        let (publish, properties) = build_publish(&params).unwrap();
+        assert!(publish.retain);
+        assert_eq!(u8::from(publish.qos), 1);
+        assert_eq!(
+            properties.unwrap().content_type,
+            Some("text/plain".to_string())
+        );
+
+        params.qos = 3;
+        assert!(build_publish(&params).is_err());
+
+        params.qos = 0;
+        params.topic = "sensor/+".to_string();
+        assert!(build_publish(&params).is_err());
+    }
+
+    #[test]
+    fn build_retain_message_row_test() {
+        let mut topic = MQTTTopic::new(
+            "t1".to_string(),
+            "cluster".to_string(),
+            "sensor/1".to_string(),
+        );
+        assert!(build_retain_message_row(&topic).unwrap().is_none());
+
+        topic.retain_message = Some(Vec::new());
+        assert!(build_retain_message_row(&topic).unwrap().is_none());
+
+        let message = MqttMessage {
+            client_id: "c1".to_string(),
+            payload: "hello".into(),
+            ..Default::default()
+        };
+        topic.retain_message = Some(message.encode());
+        let row = build_retain_message_row(&topic).unwrap().unwrap();
+        assert_eq!(row.client_id, "c1");
+        assert_eq!(row.payload, "hello");
+        assert_eq!(row.payload_size, 5);
+    }
+}
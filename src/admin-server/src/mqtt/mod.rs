@@ -17,10 +17,17 @@
 pub mod blacklist;
 pub mod client;
 pub mod connector;
+pub mod delay_message;
+pub mod detail_metrics;
+pub mod listener;
+pub mod message;
 pub mod overview;
+pub mod rule;
+pub mod scheduled_publish;
 pub mod schema;
 pub mod session;
 pub mod subscribe;
 pub mod system;
 pub mod topic;
+pub mod trace;
 pub mod user;
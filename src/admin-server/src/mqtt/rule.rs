@@ -0,0 +1,186 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    request::mqtt::{CreateRuleReq, DeleteRuleReq, RuleListReq},
+    response::{mqtt::RuleListRow, PageReplyData},
+    state::HttpState,
+    tool::query::{apply_filters, apply_pagination, apply_sorting, build_query_params, Queryable},
+};
+use axum::{extract::State, Json};
+use common_base::{
+    http_response::{error_response, success_response},
+    tools::now_second,
+    utils::time_util::timestamp_to_local_datetime,
+};
+use metadata_struct::mqtt::rule::{MqttRule, MqttRuleAction, MqttRuleChange};
+use mqtt_broker::handler::{
+    dynamic_config::{save_cluster_dynamic_config, ClusterDynamicConfig},
+    error::MqttBrokerError,
+};
+use mqtt_broker::storage::rule::RuleStorage;
+use rule_engine::{error::RuleEngineError, sql::parse_rule_sql};
+use std::sync::Arc;
+
+pub async fn rule_list(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<RuleListReq>,
+) -> String {
+    let options = build_query_params(
+        params.page,
+        params.limit,
+        params.sort_field,
+        params.sort_by,
+        params.filter_field,
+        params.filter_values,
+        params.exact_match,
+    );
+
+    let mut rules = Vec::new();
+    for rule in state.mqtt_context.cache_manager.rule_engine.list_rules() {
+        rules.push(RuleListRow {
+            rule_name: rule.rule_name.clone(),
+            sql: rule.sql.clone(),
+            actions: serde_json::to_string(&rule.actions).unwrap_or_default(),
+            enable: rule.enable,
+            description: rule.description.clone(),
+            create_time: timestamp_to_local_datetime(rule.create_time as i64),
+        });
+    }
+
+    let filtered = apply_filters(rules, &options);
+    let sorted = apply_sorting(filtered, &options);
+    let pagination = apply_pagination(sorted, &options);
+
+    success_response(PageReplyData {
+        data: pagination.0,
+        total_count: pagination.1,
+    })
+}
+
+impl Queryable for RuleListRow {
+    fn get_field_str(&self, field: &str) -> Option<String> {
+        match field {
+            "rule_name" => Some(self.rule_name.clone()),
+            "sql" => Some(self.sql.clone()),
+            "enable" => Some(self.enable.to_string()),
+            _ => None,
+        }
+    }
+}
+
+pub async fn rule_create(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<CreateRuleReq>,
+) -> String {
+    if let Err(e) = rule_create_inner(&state, params).await {
+        return error_response(e.to_string());
+    }
+    success_response("success")
+}
+
+pub async fn rule_delete(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<DeleteRuleReq>,
+) -> String {
+    if let Err(e) = rule_delete_inner(&state, params).await {
+        return error_response(e.to_string());
+    }
+    success_response("success")
+}
+
+async fn rule_create_inner(
+    state: &Arc<HttpState>,
+    params: CreateRuleReq,
+) -> Result<(), MqttBrokerError> {
+    parse_rule_sql(&params.sql)?;
+    for action in params.actions.iter() {
+        if let MqttRuleAction::Connector { connector_name } = action {
+            if state
+                .mqtt_context
+                .connector_manager
+                .get_connector(connector_name)
+                .is_none()
+            {
+                return Err(MqttBrokerError::ConnectorNotFound(connector_name.clone()));
+            }
+        }
+    }
+
+    let rule_engine = &state.mqtt_context.cache_manager.rule_engine;
+    if rule_engine.get_rule(&params.rule_name).is_some() {
+        return Err(RuleEngineError::RuleAlreadyExist(params.rule_name).into());
+    }
+
+    save_rule(
+        state,
+        MqttRule {
+            rule_name: params.rule_name,
+            sql: params.sql,
+            actions: params.actions,
+            enable: params.enable,
+            description: params.description,
+            create_time: now_second(),
+        },
+    )
+    .await
+}
+
+async fn rule_delete_inner(
+    state: &Arc<HttpState>,
+    params: DeleteRuleReq,
+) -> Result<(), MqttBrokerError> {
+    let rule_engine = &state.mqtt_context.cache_manager.rule_engine;
+    if rule_engine.get_rule(&params.rule_name).is_none() {
+        return Err(MqttBrokerError::RuleNotFound(params.rule_name));
+    }
+
+    let rule_storage = RuleStorage::new(state.client_pool.clone());
+    rule_storage.delete_rule(&params.rule_name).await?;
+    rule_engine.remove_rule(&params.rule_name);
+    notify_rule_change(
+        state,
+        MqttRuleChange::Delete {
+            rule_name: params.rule_name,
+        },
+    )
+    .await
+}
+
+// Every rule is stored under its own key, so concurrent changes to different
+// rules do not overwrite each other.
+async fn save_rule(state: &Arc<HttpState>, rule: MqttRule) -> Result<(), MqttBrokerError> {
+    let rule_storage = RuleStorage::new(state.client_pool.clone());
+    rule_storage.save_rule(&rule).await?;
+    state
+        .mqtt_context
+        .cache_manager
+        .rule_engine
+        .set_rule(rule.clone())?;
+    notify_rule_change(state, MqttRuleChange::Set { rule }).await
+}
+
+// The meta service pushes the change to all brokers through the cluster
+// dynamic config.
+async fn notify_rule_change(
+    state: &Arc<HttpState>,
+    change: MqttRuleChange,
+) -> Result<(), MqttBrokerError> {
+    save_cluster_dynamic_config(
+        &state.client_pool,
+        ClusterDynamicConfig::MqttRuleEngine,
+        change.encode(),
+    )
+    .await
+}
@@ -0,0 +1,333 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    request::mqtt::{
+        CreateScheduledPublishReq, DeleteScheduledPublishReq, ScheduledPublishHistoryReq,
+        ScheduledPublishListReq,
+    },
+    response::{
+        mqtt::{ScheduledPublishHistoryRow, ScheduledPublishListRow},
+        PageReplyData,
+    },
+    state::HttpState,
+    tool::query::{apply_filters, apply_pagination, apply_sorting, build_query_params, Queryable},
+};
+use axum::{extract::State, Json};
+use common_base::{
+    http_response::{error_response, success_response},
+    tools::now_second,
+};
+use metadata_struct::mqtt::scheduled_publish::{MqttPublishSchedule, MqttScheduledPublish};
+use mqtt_broker::{
+    handler::{
+        dynamic_config::{save_cluster_dynamic_config, ClusterDynamicConfig},
+        error::MqttBrokerError,
+        scheduled_publish::{next_fire_time, schedule_next_fire, validate_scheduled_publish},
+    },
+    storage::scheduled_publish::ScheduledPublishStorage,
+};
+use protocol::mqtt::common::qos;
+use std::sync::Arc;
+
+const DEFAULT_TIMEZONE: &str = "UTC";
+
+pub async fn scheduled_publish_list(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<ScheduledPublishListReq>,
+) -> String {
+    let options = build_query_params(
+        params.page,
+        params.limit,
+        params.sort_field,
+        params.sort_by,
+        params.filter_field,
+        params.filter_values,
+        params.exact_match,
+    );
+
+    let now = now_second();
+    let schedules = state
+        .mqtt_context
+        .cache_manager
+        .list_scheduled_publishes()
+        .iter()
+        .map(|schedule| build_scheduled_publish_row(schedule, now))
+        .collect();
+
+    let filtered = apply_filters(schedules, &options);
+    let sorted = apply_sorting(filtered, &options);
+    let pagination = apply_pagination(sorted, &options);
+
+    success_response(PageReplyData {
+        data: pagination.0,
+        total_count: pagination.1,
+    })
+}
+
+impl Queryable for ScheduledPublishListRow {
+    fn get_field_str(&self, field: &str) -> Option<String> {
+        match field {
+            "name" => Some(self.name.clone()),
+            "topic_name" => Some(self.topic_name.clone()),
+            "schedule" => Some(self.schedule.clone()),
+            "create_time" => Some(self.create_time.to_string()),
+            _ => None,
+        }
+    }
+}
+
+pub async fn scheduled_publish_create(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<CreateScheduledPublishReq>,
+) -> String {
+    if let Err(e) = scheduled_publish_create_inner(&state, params).await {
+        return error_response(e.to_string());
+    }
+    success_response("success")
+}
+
+pub async fn scheduled_publish_delete(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<DeleteScheduledPublishReq>,
+) -> String {
+    if let Err(e) = scheduled_publish_delete_inner(&state, params).await {
+        return error_response(e.to_string());
+    }
+    success_response("success")
+}
+
+pub async fn scheduled_publish_history(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<ScheduledPublishHistoryReq>,
+) -> String {
+    let options = build_query_params(
+        params.page,
+        params.limit,
+        params.sort_field,
+        params.sort_by,
+        params.filter_field,
+        params.filter_values,
+        params.exact_match,
+    );
+
+    let storage = ScheduledPublishStorage::new(state.mqtt_context.message_storage_adapter.clone());
+    let fires = match storage.list_fires(&params.name).await {
+        Ok(fires) => fires,
+        Err(e) => return error_response(e.to_string()),
+    };
+
+    // Newest firing first
+    let rows = fires
+        .into_iter()
+        .rev()
+        .map(|fire| ScheduledPublishHistoryRow {
+            fire_time: fire.fire_time,
+            publish_time: fire.publish_time,
+            success: fire.success,
+            error: fire.error,
+        })
+        .collect();
+
+    let filtered = apply_filters(rows, &options);
+    let sorted = apply_sorting(filtered, &options);
+    let pagination = apply_pagination(sorted, &options);
+
+    success_response(PageReplyData {
+        data: pagination.0,
+        total_count: pagination.1,
+    })
+}
+
+impl Queryable for ScheduledPublishHistoryRow {
+    fn get_field_str(&self, field: &str) -> Option<String> {
+        match field {
+            "fire_time" => Some(self.fire_time.to_string()),
+            "success" => Some(self.success.to_string()),
+            _ => None,
+        }
+    }
+}
+
+async fn scheduled_publish_create_inner(
+    state: &Arc<HttpState>,
+    params: CreateScheduledPublishReq,
+) -> Result<(), MqttBrokerError> {
+    let schedule = build_scheduled_publish(params, now_second())?;
+    validate_scheduled_publish(&schedule)?;
+
+    let cache_manager = &state.mqtt_context.cache_manager;
+    if cache_manager
+        .get_scheduled_publish(&schedule.name)
+        .is_some()
+    {
+        return Err(MqttBrokerError::ScheduledPublishAlreadyExist(schedule.name));
+    }
+
+    let mut schedules = cache_manager.list_scheduled_publishes();
+    schedules.push(schedule.clone());
+    save_scheduled_publishes(state, schedules).await?;
+
+    schedule_next_fire(
+        &state.mqtt_context.delay_message_manager,
+        &schedule,
+        schedule.create_time,
+    )
+    .await?;
+    Ok(())
+}
+
+async fn scheduled_publish_delete_inner(
+    state: &Arc<HttpState>,
+    params: DeleteScheduledPublishReq,
+) -> Result<(), MqttBrokerError> {
+    let cache_manager = &state.mqtt_context.cache_manager;
+    if cache_manager.get_scheduled_publish(&params.name).is_none() {
+        return Err(MqttBrokerError::ScheduledPublishNotFound(params.name));
+    }
+
+    // The queued firing of the schedule is dropped once it is due
+    let schedules = cache_manager
+        .list_scheduled_publishes()
+        .into_iter()
+        .filter(|schedule| schedule.name != params.name)
+        .collect();
+    save_scheduled_publishes(state, schedules).await
+}
+
+// Schedules are stored as one cluster dynamic config, the meta service pushes
+// every change to all brokers.
+async fn save_scheduled_publishes(
+    state: &Arc<HttpState>,
+    schedules: Vec<MqttScheduledPublish>,
+) -> Result<(), MqttBrokerError> {
+    save_cluster_dynamic_config(
+        &state.client_pool,
+        ClusterDynamicConfig::MqttScheduledPublish,
+        serde_json::to_vec(&schedules)?,
+    )
+    .await?;
+
+    state
+        .mqtt_context
+        .cache_manager
+        .set_scheduled_publishes(schedules);
+    Ok(())
+}
+
+fn build_scheduled_publish(
+    params: CreateScheduledPublishReq,
+    create_time: u64,
+) -> Result<MqttScheduledPublish, MqttBrokerError> {
+    let Some(qos) = qos(params.qos) else {
+        return Err(MqttBrokerError::InvalidScheduledPublish(format!(
+            "invalid QoS {}, must be 0, 1 or 2",
+            params.qos
+        )));
+    };
+
+    let schedule = match (params.cron, params.interval_sec) {
+        (Some(expression), None) => MqttPublishSchedule::Cron { expression },
+        (None, Some(seconds)) => MqttPublishSchedule::Interval { seconds },
+        _ => {
+            return Err(MqttBrokerError::InvalidScheduledPublish(
+                "exactly one of cron and interval_sec must be set".to_string(),
+            ));
+        }
+    };
+
+    Ok(MqttScheduledPublish {
+        name: params.name,
+        topic_name: params.topic_name,
+        payload: params.payload,
+        qos,
+        retain: params.retain,
+        schedule,
+        timezone: params
+            .timezone
+            .unwrap_or_else(|| DEFAULT_TIMEZONE.to_string()),
+        end_time: params.end_time,
+        create_time,
+    })
+}
+
+fn build_scheduled_publish_row(
+    schedule: &MqttScheduledPublish,
+    now: u64,
+) -> ScheduledPublishListRow {
+    let schedule_str = match &schedule.schedule {
+        MqttPublishSchedule::Cron { expression } => format!("cron: {expression}"),
+        MqttPublishSchedule::Interval { seconds } => format!("interval: {seconds}s"),
+    };
+
+    ScheduledPublishListRow {
+        name: schedule.name.clone(),
+        topic_name: schedule.topic_name.clone(),
+        qos: schedule.qos.into(),
+        retain: schedule.retain,
+        schedule: schedule_str,
+        timezone: schedule.timezone.clone(),
+        end_time: schedule.end_time,
+        next_fire_time: next_fire_time(schedule, now).ok().flatten(),
+        create_time: schedule.create_time,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_scheduled_publish_test() {
+        let mut params = CreateScheduledPublishReq {
+            name: "s1".to_string(),
+            topic_name: "device/cmd".to_string(),
+            payload: "reboot".to_string(),
+            qos: 1,
+            interval_sec: Some(60),
+            end_time: Some(1500),
+            ..Default::default()
+        };
+        let schedule = build_scheduled_publish(params.clone(), 1000).unwrap();
+        assert_eq!(
+            schedule.schedule,
+            MqttPublishSchedule::Interval { seconds: 60 }
+        );
+        assert_eq!(schedule.timezone, "UTC");
+
+        let row = build_scheduled_publish_row(&schedule, 1000);
+        assert_eq!(row.schedule, "interval: 60s");
+        assert_eq!(row.qos, 1);
+        assert_eq!(row.next_fire_time, Some(1060));
+        assert_eq!(
+            build_scheduled_publish_row(&schedule, 1500).next_fire_time,
+            None
+        );
+
+        params.cron = Some("0 * * * *".to_string());
+        assert!(build_scheduled_publish(params.clone(), 1000).is_err());
+
+        params.interval_sec = None;
+        params.timezone = Some("Asia/Shanghai".to_string());
+        let schedule = build_scheduled_publish(params.clone(), 1000).unwrap();
+        assert_eq!(
+            build_scheduled_publish_row(&schedule, 1000).schedule,
+            "cron: 0 * * * *"
+        );
+        assert_eq!(schedule.timezone, "Asia/Shanghai");
+
+        params.qos = 3;
+        assert!(build_scheduled_publish(params, 1000).is_err());
+    }
+}
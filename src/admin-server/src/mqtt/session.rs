@@ -12,14 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use super::client::client_broker_addr;
 use crate::{
-    request::mqtt::SessionListReq,
-    response::{mqtt::SessionListRow, PageReplyData},
+    request::mqtt::{ClearSessionReq, SessionListReq},
+    response::{
+        mqtt::{ClearSessionResp, SessionListRow},
+        PageReplyData,
+    },
     state::HttpState,
     tool::query::{apply_filters, apply_pagination, apply_sorting, build_query_params, Queryable},
 };
 use axum::{extract::State, Json};
-use common_base::http_response::success_response;
+use common_base::http_response::{error_response, success_response};
+use grpc_clients::mqtt::admin::call::broker_mqtt_clear_session;
+use protocol::broker::broker_mqtt_admin::ClearSessionRequest;
 use std::sync::Arc;
 
 pub async fn session_list(
@@ -89,3 +95,24 @@ fn get_field_str(&self, field: &str) -> Option<String> {
         }
     }
 }
+
+pub async fn session_clear(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<ClearSessionReq>,
+) -> String {
+    let (_, addr) = match client_broker_addr(&state, &params.client_id).await {
+        Ok(data) => data,
+        Err(e) => return error_response(e.to_string()),
+    };
+
+    let request = ClearSessionRequest {
+        client_id: params.client_id.clone(),
+    };
+    match broker_mqtt_clear_session(&state.client_pool, &[addr], request).await {
+        Ok(reply) => success_response(ClearSessionResp {
+            client_id: params.client_id,
+            was_connected: reply.was_connected,
+        }),
+        Err(e) => error_response(e.to_string()),
+    }
+}
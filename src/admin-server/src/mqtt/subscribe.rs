@@ -12,10 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use super::client::client_broker_addr;
 use crate::{
     request::mqtt::{
-        AutoSubscribeListReq, CreateAutoSubscribeReq, DeleteAutoSubscribeReq, SubscribeDetailReq,
-        SubscribeListReq,
+        AutoSubscribeListReq, CreateAutoSubscribeReq, CreateSubscribeReq, DeleteAutoSubscribeReq,
+        DeleteSubscribeReq, SubscribeDetailReq, SubscribeListReq,
     },
     response::{
         mqtt::{AutoSubscribeListRow, SlowSubscribeListRow, SubscribeListRow},
@@ -29,10 +30,14 @@
     http_response::{error_response, success_response},
     utils::time_util::timestamp_to_local_datetime,
 };
+use grpc_clients::mqtt::admin::call::{
+    broker_mqtt_subscribe_for_client, broker_mqtt_unsubscribe_for_client,
+};
 use metadata_struct::mqtt::{
     auto_subscribe_rule::MqttAutoSubscribeRule, subscribe_data::is_mqtt_share_subscribe,
 };
 use mqtt_broker::storage::{auto_subscribe::AutoSubscribeStorage, local::LocalStorage};
+use protocol::broker::broker_mqtt_admin::{SubscribeForClientRequest, UnsubscribeForClientRequest};
 use protocol::mqtt::common::{qos, retain_forward_rule};
 use std::sync::Arc;
 
@@ -136,6 +141,56 @@ fn get_field_str(&self, field: &str) -> Option<String> {
     }
 }
 
+pub async fn subscribe_create(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<CreateSubscribeReq>,
+) -> String {
+    if qos(params.qos as u8).is_none() {
+        return error_response("Inconsistent QoS format".to_string());
+    }
+
+    if retain_forward_rule(params.retained_handling as u8).is_none() {
+        return error_response("Inconsistent RetainHandling format".to_string());
+    }
+
+    let (_, addr) = match client_broker_addr(&state, &params.client_id).await {
+        Ok(data) => data,
+        Err(e) => return error_response(e.to_string()),
+    };
+
+    let request = SubscribeForClientRequest {
+        client_id: params.client_id,
+        path: params.path,
+        qos: params.qos,
+        no_local: params.no_local,
+        retain_as_published: params.retain_as_published,
+        retained_handling: params.retained_handling,
+    };
+    match broker_mqtt_subscribe_for_client(&state.client_pool, &[addr], request).await {
+        Ok(_) => success_response("success"),
+        Err(e) => error_response(e.to_string()),
+    }
+}
+
+pub async fn subscribe_delete(
+    State(state): State<Arc<HttpState>>,
+    Json(params): Json<DeleteSubscribeReq>,
+) -> String {
+    let (_, addr) = match client_broker_addr(&state, &params.client_id).await {
+        Ok(data) => data,
+        Err(e) => return error_response(e.to_string()),
+    };
+
+    let request = UnsubscribeForClientRequest {
+        client_id: params.client_id,
+        path: params.path,
+    };
+    match broker_mqtt_unsubscribe_for_client(&state.client_pool, &[addr], request).await {
+        Ok(_) => success_response("success"),
+        Err(e) => error_response(e.to_string()),
+    }
+}
+
 pub async fn auto_subscribe_create(
     State(state): State<Arc<HttpState>>,
     Json(params): Json<CreateAutoSubscribeReq>,
@@ -0,0 +1,122 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{request::mqtt::TraceReq, state::HttpState};
+use async_stream::stream;
+use axum::{
+    extract::State,
+    response::{
+        sse::{Event, KeepAlive},
+        IntoResponse, Response, Sse,
+    },
+    Json,
+};
+use common_base::http_response::error_response;
+use mqtt_broker::common::packet_trace::{PacketTraceManager, TraceTarget, MAX_TRACE_DURATION_SEC};
+use std::{sync::Arc, time::Duration};
+use tokio::{sync::broadcast::error::RecvError, time::Instant};
+
+pub const TRACE_EVENT_LAGGED: &str = "lagged";
+pub const TRACE_EVENT_END: &str = "end";
+
+// Stops the trace when the stream ends or the caller goes away.
+struct TraceGuard {
+    manager: Arc<PacketTraceManager>,
+    trace_id: String,
+}
+
+impl Drop for TraceGuard {
+    fn drop(&mut self) {
+        self.manager.stop_trace(&self.trace_id);
+    }
+}
+
+/// Streams the packets of a client or a topic handled by this broker as
+/// server-sent events until the requested duration is over.
+pub async fn trace(State(state): State<Arc<HttpState>>, Json(params): Json<TraceReq>) -> Response {
+    let target = match build_trace_target(&params) {
+        Ok(target) => target,
+        Err(e) => return error_response(e).into_response(),
+    };
+
+    let manager = state.mqtt_context.cache_manager.packet_trace.clone();
+    let (trace_id, mut receiver) = match manager.start_trace(target, params.duration_sec) {
+        Ok(data) => data,
+        Err(e) => return error_response(e.to_string()).into_response(),
+    };
+
+    let duration_sec = params.duration_sec.clamp(1, MAX_TRACE_DURATION_SEC);
+    let deadline = Instant::now() + Duration::from_secs(duration_sec);
+    let guard = TraceGuard { manager, trace_id };
+
+    let stream = stream! {
+        let _guard = guard;
+        loop {
+            let res = tokio::select! {
+                _ = tokio::time::sleep_until(deadline) => break,
+                res = receiver.recv() => res,
+            };
+
+            match res {
+                Ok(event) => yield Event::default().json_data(event),
+                Err(RecvError::Lagged(num)) => {
+                    yield Ok(Event::default().event(TRACE_EVENT_LAGGED).data(num.to_string()))
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+        yield Ok(Event::default().event(TRACE_EVENT_END).data("trace finished"));
+    };
+
+    Sse::new(stream)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+fn build_trace_target(params: &TraceReq) -> Result<TraceTarget, String> {
+    match (&params.client_id, &params.topic) {
+        (Some(client_id), None) if !client_id.is_empty() => {
+            Ok(TraceTarget::ClientId(client_id.clone()))
+        }
+        (None, Some(topic)) if !topic.is_empty() => Ok(TraceTarget::Topic(topic.clone())),
+        _ => Err("Exactly one of client_id and topic must be set".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_trace_target_test() {
+        let mut params = TraceReq {
+            client_id: Some("c1".to_string()),
+            topic: None,
+            duration_sec: 10,
+        };
+        assert_eq!(
+            build_trace_target(&params).unwrap(),
+            TraceTarget::ClientId("c1".to_string())
+        );
+
+        params.topic = Some("sensor/#".to_string());
+        assert!(build_trace_target(&params).is_err());
+
+        params.client_id = None;
+        assert_eq!(
+            build_trace_target(&params).unwrap(),
+            TraceTarget::Topic("sensor/#".to_string())
+        );
+    }
+}
@@ -21,9 +21,27 @@
 // Common API paths
 pub const STATUS_PATH: &str = "/status";
 
+// Auth API paths
+pub const AUTH_LOGIN_PATH: &str = "/auth/login";
+pub const AUTH_LOGOUT_PATH: &str = "/auth/logout";
+
+// Admin API paths
+pub const ADMIN_USER_LIST_PATH: &str = "/admin/user/list";
+pub const ADMIN_USER_CREATE_PATH: &str = "/admin/user/create";
+pub const ADMIN_USER_UPDATE_PATH: &str = "/admin/user/update";
+pub const ADMIN_USER_DELETE_PATH: &str = "/admin/user/delete";
+pub const ADMIN_API_KEY_LIST_PATH: &str = "/admin/api-key/list";
+pub const ADMIN_API_KEY_CREATE_PATH: &str = "/admin/api-key/create";
+pub const ADMIN_API_KEY_DELETE_PATH: &str = "/admin/api-key/delete";
+pub const ADMIN_AUDIT_LOG_LIST_PATH: &str = "/admin/audit-log/list";
+
 // Cluster API paths
 pub const CLUSTER_CONFIG_SET_PATH: &str = "/cluster/config/set";
 pub const CLUSTER_CONFIG_GET_PATH: &str = "/cluster/config/get";
+pub const CLUSTER_NODE_DRAIN_PATH: &str = "/cluster/node/drain";
+pub const CLUSTER_NODE_DRAIN_CANCEL_PATH: &str = "/cluster/node/drain/cancel";
+pub const CLUSTER_NODE_DRAIN_LIST_PATH: &str = "/cluster/node/drain/list";
+pub const CLUSTER_REBALANCE_PATH: &str = "/cluster/rebalance";
 
 // MQTT Overview API paths
 pub const MQTT_OVERVIEW_PATH: &str = "/mqtt/overview";
@@ -31,13 +49,35 @@
 
 // MQTT Client API paths
 pub const MQTT_CLIENT_LIST_PATH: &str = "/mqtt/client/list";
+pub const MQTT_CLIENT_DETAIL_PATH: &str = "/mqtt/client/detail";
+pub const MQTT_CLIENT_KICK_PATH: &str = "/mqtt/client/kick";
 
 // MQTT Session API paths
 pub const MQTT_SESSION_LIST_PATH: &str = "/mqtt/session/list";
+pub const MQTT_SESSION_CLEAR_PATH: &str = "/mqtt/session/clear";
 
 // MQTT Topic API paths
 pub const MQTT_TOPIC_LIST_PATH: &str = "/mqtt/topic/list";
 
+// MQTT Message API paths
+pub const MQTT_MESSAGE_PUBLISH_PATH: &str = "/mqtt/message/publish";
+pub const MQTT_RETAIN_MESSAGE_LIST_PATH: &str = "/mqtt/retain-message/list";
+pub const MQTT_RETAIN_MESSAGE_DELETE_PATH: &str = "/mqtt/retain-message/delete";
+
+// MQTT Delay Message API paths
+pub const MQTT_DELAY_MESSAGE_LIST_PATH: &str = "/mqtt/delay-message/list";
+pub const MQTT_DELAY_MESSAGE_DETAIL_PATH: &str = "/mqtt/delay-message/detail";
+pub const MQTT_DELAY_MESSAGE_CANCEL_PATH: &str = "/mqtt/delay-message/cancel";
+
+// MQTT Scheduled Publish API paths
+pub const MQTT_SCHEDULED_PUBLISH_LIST_PATH: &str = "/mqtt/scheduled-publish/list";
+pub const MQTT_SCHEDULED_PUBLISH_CREATE_PATH: &str = "/mqtt/scheduled-publish/create";
+pub const MQTT_SCHEDULED_PUBLISH_DELETE_PATH: &str = "/mqtt/scheduled-publish/delete";
+pub const MQTT_SCHEDULED_PUBLISH_HISTORY_PATH: &str = "/mqtt/scheduled-publish/history";
+
+// MQTT Trace API paths
+pub const MQTT_TRACE_PATH: &str = "/mqtt/trace";
+
 // MQTT Topic Rewrite API paths
 pub const MQTT_TOPIC_REWRITE_LIST_PATH: &str = "/mqtt/topic-rewrite/list";
 pub const MQTT_TOPIC_REWRITE_CREATE_PATH: &str = "/mqtt/topic-rewrite/create";
@@ -46,6 +86,8 @@
 // MQTT Subscribe API paths
 pub const MQTT_SUBSCRIBE_LIST_PATH: &str = "/mqtt/subscribe/list";
 pub const MQTT_SUBSCRIBE_DETAIL_PATH: &str = "/mqtt/subscribe/detail";
+pub const MQTT_SUBSCRIBE_CREATE_PATH: &str = "/mqtt/subscribe/create";
+pub const MQTT_SUBSCRIBE_DELETE_PATH: &str = "/mqtt/subscribe/delete";
 
 // MQTT Auto Subscribe API paths
 pub const MQTT_AUTO_SUBSCRIBE_LIST_PATH: &str = "/mqtt/auto-subscribe/list";
@@ -78,6 +120,11 @@
 pub const MQTT_CONNECTOR_CREATE_PATH: &str = "/mqtt/connector/create";
 pub const MQTT_CONNECTOR_DELETE_PATH: &str = "/mqtt/connector/delete";
 
+// MQTT Rule Engine API paths
+pub const MQTT_RULE_LIST_PATH: &str = "/mqtt/rule/list";
+pub const MQTT_RULE_CREATE_PATH: &str = "/mqtt/rule/create";
+pub const MQTT_RULE_DELETE_PATH: &str = "/mqtt/rule/delete";
+
 // MQTT Schema API paths
 pub const MQTT_SCHEMA_LIST_PATH: &str = "/mqtt/schema/list";
 pub const MQTT_SCHEMA_CREATE_PATH: &str = "/mqtt/schema/create";
@@ -86,10 +133,38 @@
 pub const MQTT_SCHEMA_BIND_CREATE_PATH: &str = "/mqtt/schema-bind/create";
 pub const MQTT_SCHEMA_BIND_DELETE_PATH: &str = "/mqtt/schema-bind/delete";
 
+// MQTT Listener API paths
+pub const MQTT_LISTENER_LIST_PATH: &str = "/mqtt/listener/list";
+pub const MQTT_LISTENER_CREATE_PATH: &str = "/mqtt/listener/create";
+pub const MQTT_LISTENER_DELETE_PATH: &str = "/mqtt/listener/delete";
+pub const MQTT_LISTENER_START_PATH: &str = "/mqtt/listener/start";
+pub const MQTT_LISTENER_STOP_PATH: &str = "/mqtt/listener/stop";
+
 // MQTT System API paths
 pub const MQTT_SYSTEM_ALARM_LIST_PATH: &str = "/mqtt/system-alarm/list";
 pub const MQTT_BAN_LOG_LIST_PATH: &str = "/mqtt/ban-log/list";
 
+// MQTT Detail Metrics API paths
+pub const MQTT_DETAIL_METRICS_TOPIC_LIST_PATH: &str = "/mqtt/detail-metrics/topic/list";
+pub const MQTT_DETAIL_METRICS_CLIENT_LIST_PATH: &str = "/mqtt/detail-metrics/client/list";
+pub const MQTT_DETAIL_METRICS_CONFIG_GET_PATH: &str = "/mqtt/detail-metrics/config/get";
+pub const MQTT_DETAIL_METRICS_CONFIG_SET_PATH: &str = "/mqtt/detail-metrics/config/set";
+
+// Journal Namespace API paths
+pub const JOURNAL_NAMESPACE_LIST_PATH: &str = "/journal/namespace/list";
+
+// Journal Shard API paths
+pub const JOURNAL_SHARD_LIST_PATH: &str = "/journal/shard/list";
+pub const JOURNAL_SHARD_DELETE_PATH: &str = "/journal/shard/delete";
+
+// Journal Segment API paths
+pub const JOURNAL_SEGMENT_LIST_PATH: &str = "/journal/segment/list";
+pub const JOURNAL_SEGMENT_DETAIL_PATH: &str = "/journal/segment/detail";
+pub const JOURNAL_SEGMENT_SEAL_PATH: &str = "/journal/segment/seal";
+
+// Journal Group API paths
+pub const JOURNAL_GROUP_OFFSET_LIST_PATH: &str = "/journal/group/offset/list";
+
 // Utility functions for building API paths with prefix
 pub const API_PREFIX: &str = "/api";
 
@@ -22,3 +22,30 @@ pub struct ClusterConfigSetReq {
     pub config_type: String,
     pub config: String,
 }
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NodeDrainReq {
+    pub broker_id: u64,
+    // connections disconnected per second, the broker default when not set
+    pub disconnect_rate: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NodeDrainCancelReq {
+    pub broker_id: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct NodeDrainListReq {
+    // every broker when not set
+    pub broker_id: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ClusterRebalanceReq {
+    // connections disconnected per second on every broker, the broker default when not set
+    pub disconnect_rate: Option<u32>,
+    // only compute the plan, no connection is moved
+    #[serde(default)]
+    pub dry_run: bool,
+}
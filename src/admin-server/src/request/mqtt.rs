@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use metadata_struct::mqtt::bridge::connector::ConnectorDeliveryPolicy;
+use metadata_struct::mqtt::rule::MqttRuleAction;
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize)]
@@ -204,7 +206,12 @@ pub struct CreateConnectorReq {
     pub connector_name: String,
     pub connector_type: String,
     pub config: String,
+    #[serde(default)]
     pub topic_id: String,
+    #[serde(default)]
+    pub topic_filters: Vec<String>,
+    #[serde(default)]
+    pub delivery_policy: Option<ConnectorDeliveryPolicy>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -212,6 +219,31 @@ pub struct DeleteConnectorReq {
     pub connector_name: String,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RuleListReq {
+    pub limit: Option<u32>,
+    pub page: Option<u32>,
+    pub sort_field: Option<String>,
+    pub sort_by: Option<String>,
+    pub filter_field: Option<String>,
+    pub filter_values: Option<Vec<String>>,
+    pub exact_match: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CreateRuleReq {
+    pub rule_name: String,
+    pub sql: String,
+    pub actions: Vec<MqttRuleAction>,
+    pub enable: bool,
+    pub description: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DeleteRuleReq {
+    pub rule_name: String,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SchemaListReq {
     pub limit: Option<u32>,
@@ -274,6 +306,183 @@ pub struct ClientListReq {
     pub exact_match: Option<String>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct KickClientReq {
+    pub client_id: String,
+    // MQTT 5 DISCONNECT reason code, defaults to 0x98 (Administrative action)
+    pub reason_code: Option<u8>,
+    pub reason_string: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ClientDetailReq {
+    pub client_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ClearSessionReq {
+    pub client_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CreateSubscribeReq {
+    pub client_id: String,
+    pub path: String,
+    pub qos: u32,
+    #[serde(default)]
+    pub no_local: bool,
+    #[serde(default)]
+    pub retain_as_published: bool,
+    #[serde(default)]
+    pub retained_handling: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DeleteSubscribeReq {
+    pub client_id: String,
+    pub path: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct PublishMessageReq {
+    pub topic: String,
+    pub payload: String,
+    #[serde(default)]
+    pub qos: u8,
+    #[serde(default)]
+    pub retain: bool,
+    pub payload_format_indicator: Option<u8>,
+    // seconds
+    pub message_expiry_interval: Option<u32>,
+    pub content_type: Option<String>,
+    pub response_topic: Option<String>,
+    pub correlation_data: Option<String>,
+    #[serde(default)]
+    pub user_properties: Vec<(String, String)>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RetainMessageListReq {
+    pub topic_name: Option<String>,
+    pub limit: Option<u32>,
+    pub page: Option<u32>,
+    pub sort_field: Option<String>,
+    pub sort_by: Option<String>,
+    pub filter_field: Option<String>,
+    pub filter_values: Option<Vec<String>>,
+    pub exact_match: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DeleteRetainMessageReq {
+    pub topic_name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DelayMessageListReq {
+    pub topic_name: Option<String>,
+    pub limit: Option<u32>,
+    pub page: Option<u32>,
+    pub sort_field: Option<String>,
+    pub sort_by: Option<String>,
+    pub filter_field: Option<String>,
+    pub filter_values: Option<Vec<String>>,
+    pub exact_match: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DelayMessageDetailReq {
+    pub id: String,
+}
+
+// Cancel one message by id, or every pending message of a topic
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct DelayMessageCancelReq {
+    pub id: Option<String>,
+    pub topic_name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ScheduledPublishListReq {
+    pub limit: Option<u32>,
+    pub page: Option<u32>,
+    pub sort_field: Option<String>,
+    pub sort_by: Option<String>,
+    pub filter_field: Option<String>,
+    pub filter_values: Option<Vec<String>>,
+    pub exact_match: Option<String>,
+}
+
+// Exactly one of cron and interval_sec must be set
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct CreateScheduledPublishReq {
+    pub name: String,
+    pub topic_name: String,
+    pub payload: String,
+    #[serde(default)]
+    pub qos: u8,
+    #[serde(default)]
+    pub retain: bool,
+    pub cron: Option<String>,
+    pub interval_sec: Option<u64>,
+    // IANA name or UTC offset, defaults to UTC
+    pub timezone: Option<String>,
+    // seconds
+    pub end_time: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DeleteScheduledPublishReq {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ScheduledPublishHistoryReq {
+    pub name: String,
+    pub limit: Option<u32>,
+    pub page: Option<u32>,
+    pub sort_field: Option<String>,
+    pub sort_by: Option<String>,
+    pub filter_field: Option<String>,
+    pub filter_values: Option<Vec<String>>,
+    pub exact_match: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TraceReq {
+    pub client_id: Option<String>,
+    // topic filter, wildcards are allowed
+    pub topic: Option<String>,
+    // seconds, at most 600
+    pub duration_sec: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct DetailMetricsListReq {
+    pub limit: Option<u32>,
+    pub page: Option<u32>,
+    pub sort_field: Option<String>,
+    pub sort_by: Option<String>,
+    pub filter_field: Option<String>,
+    pub filter_values: Option<Vec<String>>,
+    pub exact_match: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct DetailMetricsConfigGetReq {}
+
+// Fields left out keep their current value
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct DetailMetricsConfigSetReq {
+    pub enable: Option<bool>,
+    pub topic_filters: Option<Vec<String>>,
+    pub client_id_filters: Option<Vec<String>>,
+    pub max_topics: Option<usize>,
+    pub max_clients: Option<usize>,
+    // lru or top_n
+    pub eviction: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SystemAlarmListReq {
     pub limit: Option<u32>,
@@ -284,3 +493,38 @@ pub struct SystemAlarmListReq {
     pub filter_values: Option<Vec<String>>,
     pub exact_match: Option<String>,
 }
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ListenerListReq {
+    pub limit: Option<u32>,
+    pub page: Option<u32>,
+    pub sort_field: Option<String>,
+    pub sort_by: Option<String>,
+    pub filter_field: Option<String>,
+    pub filter_values: Option<Vec<String>>,
+    pub exact_match: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct CreateListenerReq {
+    pub name: String,
+    pub protocol: String,
+    pub bind_addr: String,
+    pub tls_cert: Option<String>,
+    pub tls_key: Option<String>,
+    pub tls_ca: Option<String>,
+    #[serde(default)]
+    pub authentication: Vec<String>,
+    pub max_connections: Option<u64>,
+    pub mountpoint: Option<String>,
+    pub websocket_path: Option<String>,
+    #[serde(default)]
+    pub proxy_protocol: bool,
+    #[serde(default)]
+    pub start: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ListenerOperateReq {
+    pub name: String,
+}
@@ -0,0 +1,46 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct LoginResp {
+    pub token: String,
+    pub username: String,
+    pub role: String,
+    pub expire_time: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct AdminUserListRow {
+    pub username: String,
+    pub role: String,
+    pub create_time: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ApiKeyListRow {
+    pub id: String,
+    pub name: String,
+    pub role: String,
+    pub create_by: String,
+    pub create_time: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct CreateApiKeyResp {
+    pub id: String,
+    // Only returned once, keep it safe
+    pub api_key: String,
+}
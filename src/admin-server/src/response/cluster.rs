@@ -0,0 +1,43 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct NodeDrainStatusRow {
+    pub broker_id: u64,
+    pub draining: bool,
+    pub start_time: u64,
+    pub disconnect_rate: u32,
+    pub connection_count: u64,
+    pub disconnected_count: u64,
+    pub share_leader_count: u64,
+    pub connector_count: u64,
+    pub drained: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct RebalanceMoveRow {
+    pub broker_id: u64,
+    pub connection_count: u64,
+    pub move_count: u64,
+    pub server_references: Vec<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct ClusterRebalanceResp {
+    // connections each broker keeps once the moves are done
+    pub target_connection_count: u64,
+    pub moves: Vec<RebalanceMoveRow>,
+}
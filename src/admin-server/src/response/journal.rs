@@ -11,3 +11,73 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct NamespaceListRow {
+    pub namespace: String,
+    pub shard_num: usize,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ShardListRow {
+    pub namespace: String,
+    pub shard_name: String,
+    pub status: String,
+    pub start_segment_seq: u32,
+    pub active_segment_seq: u32,
+    pub last_segment_seq: u32,
+    pub replica_num: u32,
+    pub max_segment_size: u32,
+    pub create_time: u128,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SegmentListRow {
+    pub namespace: String,
+    pub shard_name: String,
+    pub segment_no: u32,
+    pub status: String,
+    pub leader: u64,
+    pub leader_epoch: u32,
+    pub replicas: Vec<u64>,
+    pub isr: Vec<u64>,
+    pub start_offset: i64,
+    pub end_offset: i64,
+    pub start_timestamp: i64,
+    pub end_timestamp: i64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SegmentFileResp {
+    pub start_offset: i64,
+    pub end_offset: i64,
+    pub start_timestamp: i64,
+    pub end_timestamp: i64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SegmentDetailResp {
+    pub segment: SegmentListRow,
+    // Segment file metadata kept by the leader, None when the leader cannot be reached
+    pub file: Option<SegmentFileResp>,
+    pub file_error: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SealSegmentResp {
+    pub namespace: String,
+    pub shard_name: String,
+    pub segment_no: u32,
+    pub end_offset: i64,
+    pub next_segment_no: u32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GroupOffsetListRow {
+    pub group_name: String,
+    pub namespace: String,
+    pub shard_name: String,
+    pub offset: u64,
+}
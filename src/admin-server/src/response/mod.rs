@@ -14,6 +14,8 @@
 
 use serde::{Deserialize, Serialize};
 
+pub mod admin;
+pub mod cluster;
 pub mod journal;
 pub mod meta;
 pub mod mqtt;
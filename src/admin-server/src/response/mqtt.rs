@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use metadata_struct::placement::node::BrokerNode;
 use serde::{Deserialize, Serialize};
@@ -26,6 +26,53 @@ pub struct ClientListRow {
     pub create_time: String,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+pub struct KickClientResp {
+    pub client_id: String,
+    pub connect_id: u64,
+    pub broker_id: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ClearSessionResp {
+    pub client_id: String,
+    pub was_connected: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ClientDetailResp {
+    pub client_id: String,
+    pub connected: bool,
+    pub connect_id: u64,
+    pub broker_id: u64,
+    pub source_addr: String,
+    pub protocol: String,
+    pub keep_alive: u32,
+    pub last_heartbeat_time: u64,
+    pub keep_alive_deadline: u64,
+    pub receive_maximum: u32,
+    pub inflight_messages: Vec<ClientInflightMessage>,
+    pub queued_subscriptions: Vec<ClientQueuedSubscription>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ClientInflightMessage {
+    pub pkid: u32,
+    pub direction: String,
+    pub create_time: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ClientQueuedSubscription {
+    pub sub_path: String,
+    pub topic_name: String,
+    pub committed_offset: u64,
+    pub pending_messages: u64,
+    pub push_success_record_num: u64,
+    pub push_error_record_num: u64,
+    pub last_push_time: u64,
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct TopicListRow {
     pub topic_id: String,
@@ -33,6 +80,65 @@ pub struct TopicListRow {
     pub is_contain_retain_message: bool,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RetainMessageListRow {
+    pub topic_name: String,
+    pub client_id: String,
+    pub qos: u8,
+    pub payload: String,
+    pub payload_size: usize,
+    pub content_type: Option<String>,
+    pub expired_at: u64,
+    pub create_time: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DelayMessageListRow {
+    pub id: String,
+    pub topic_name: String,
+    pub delay_timestamp: u64,
+    pub create_time: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DelayMessageDetailResp {
+    pub id: String,
+    pub topic_name: String,
+    pub status: String,
+    pub delay_timestamp: u64,
+    pub create_time: u64,
+    pub client_id: Option<String>,
+    pub qos: Option<u8>,
+    pub payload: Option<String>,
+    pub payload_size: Option<usize>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DelayMessageCancelResp {
+    pub cancelled_num: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ScheduledPublishListRow {
+    pub name: String,
+    pub topic_name: String,
+    pub qos: u8,
+    pub retain: bool,
+    pub schedule: String,
+    pub timezone: String,
+    pub end_time: Option<u64>,
+    pub next_fire_time: Option<u64>,
+    pub create_time: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ScheduledPublishHistoryRow {
+    pub fire_time: u64,
+    pub publish_time: u64,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct TopicRewriteListRow {
     pub source_topic: String,
@@ -71,8 +177,16 @@ pub struct ConnectorListRow {
     pub connector_type: String,
     pub config: String,
     pub topic_id: String,
+    pub topic_filters: Vec<String>,
     pub status: String,
     pub broker_id: String,
+    pub committed_offsets: BTreeMap<String, u64>,
+    pub lag: u64,
+    pub success_total: u64,
+    pub failure_total: u64,
+    pub dead_letter_total: u64,
+    pub discard_total: u64,
+    pub last_error: String,
     pub create_time: String,
     pub update_time: String,
 }
@@ -116,6 +230,16 @@ pub struct AutoSubscribeListRow {
     pub retained_handling: String,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RuleListRow {
+    pub rule_name: String,
+    pub sql: String,
+    pub actions: String,
+    pub enable: bool,
+    pub description: String,
+    pub create_time: String,
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct SlowSubscribeListRow {
     pub client_id: String,
@@ -194,3 +318,34 @@ pub struct OverViewMetricsResp {
     pub message_out_num: Vec<HashMap<String, u64>>,
     pub message_drop_num: Vec<HashMap<String, u64>>,
 }
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ListenerListRow {
+    pub name: String,
+    pub protocol: String,
+    pub bind_addr: String,
+    pub tls: bool,
+    pub mtls: bool,
+    pub authentication: Vec<String>,
+    pub max_connections: Option<u64>,
+    pub mountpoint: Option<String>,
+    pub websocket_path: Option<String>,
+    pub proxy_protocol: bool,
+    pub running: bool,
+    pub connection_num: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DetailMetricsListRow {
+    // topic name or client id
+    pub name: String,
+    pub messages_in: u64,
+    pub messages_out: u64,
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+    pub dropped: u64,
+    pub inflight: i64,
+    pub avg_latency_ms: u64,
+    pub max_latency_ms: u64,
+    pub last_active: String,
+}
@@ -13,24 +13,56 @@
 // limitations under the License.
 
 use crate::{
-    cluster::{cluster_config_get, cluster_config_set},
+    auth::{
+        api_key::{api_key_create, api_key_delete, api_key_list},
+        audit::audit_log_list,
+        init_default_admin_user,
+        middleware::auth_middleware,
+        user::{
+            admin_user_create, admin_user_delete, admin_user_list, admin_user_update, login, logout,
+        },
+    },
+    cluster::{
+        cluster_config_get, cluster_config_set,
+        drain::{cluster_rebalance, node_drain, node_drain_cancel, node_drain_list},
+    },
+    journal::{
+        group::group_offset_list,
+        segment::{segment_detail, segment_list, segment_seal},
+        shard::{namespace_list, shard_delete, shard_list},
+    },
     mqtt::{
         acl::{acl_create, acl_delete, acl_list},
         blacklist::{blacklist_create, blacklist_delete, blacklist_list},
-        client::client_list,
+        client::{client_detail, client_kick, client_list},
         connector::{connector_create, connector_delete, connector_list},
+        delay_message::{delay_message_cancel, delay_message_detail, delay_message_list},
+        detail_metrics::{
+            detail_metrics_client_list, detail_metrics_config_get, detail_metrics_config_set,
+            detail_metrics_topic_list,
+        },
+        listener::{
+            listener_create, listener_delete, listener_list, listener_start, listener_stop,
+        },
+        message::{message_publish, retain_message_delete, retain_message_list},
         overview::{overview, overview_metrics},
+        rule::{rule_create, rule_delete, rule_list},
+        scheduled_publish::{
+            scheduled_publish_create, scheduled_publish_delete, scheduled_publish_history,
+            scheduled_publish_list,
+        },
         schema::{
             schema_bind_create, schema_bind_delete, schema_bind_list, schema_create, schema_delete,
             schema_list,
         },
-        session::session_list,
+        session::{session_clear, session_list},
         subscribe::{
             auto_subscribe_create, auto_subscribe_delete, auto_subscribe_list, slow_subscribe_list,
-            subscribe_detail, subscribe_list,
+            subscribe_create, subscribe_delete, subscribe_detail, subscribe_list,
         },
         system::{ban_log_list, flapping_detect_list, system_alarm_list},
         topic::{topic_list, topic_rewrite_create, topic_rewrite_list},
+        trace::trace,
         user::{user_create, user_delete, user_list},
     },
     path::*,
@@ -47,13 +79,14 @@
     Router,
 };
 use common_base::version::version;
+use common_config::broker::broker_config;
 use common_metrics::http::record_http_request;
 use reqwest::StatusCode;
 use std::path::PathBuf;
 use std::{net::SocketAddr, sync::Arc, time::Instant};
 use tokio::fs;
 use tower_http::{cors::CorsLayer, services::ServeDir};
-use tracing::{info, warn};
+use tracing::{error, info, warn};
 
 pub struct AdminServer {}
 
@@ -69,9 +102,22 @@ pub fn new() -> Self {
 
     pub async fn start(&self, port: u32, state: Arc<HttpState>) {
         let ip = format!("0.0.0.0:{port}");
+        if broker_config().admin_server.auth_enable {
+            if let Err(e) = init_default_admin_user(state.client_pool.clone()).await {
+                error!(
+                    "Failed to create the default admin user, error message: {}",
+                    e
+                );
+            }
+        }
+
+        let api_route = self.api_route().route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            auth_middleware,
+        ));
         let route = Router::new()
             .merge(self.static_route())
-            .nest("/api", self.api_route())
+            .nest("/api", api_route)
             .with_state(state)
             .layer(middleware::from_fn(base_middleware))
             .layer(CorsLayer::permissive());
@@ -100,7 +146,9 @@ fn static_route(&self) -> Router<Arc<HttpState>> {
     fn api_route(&self) -> Router<Arc<HttpState>> {
         Router::new()
             .merge(self.common_route())
+            .merge(self.admin_route())
             .merge(self.mqtt_route())
+            .merge(self.journal_route())
             .merge(self.kafka_route())
     }
 
@@ -110,6 +158,29 @@ fn common_route(&self) -> Router<Arc<HttpState>> {
             // config
             .route(CLUSTER_CONFIG_SET_PATH, post(cluster_config_set))
             .route(CLUSTER_CONFIG_GET_PATH, post(cluster_config_get))
+            // drain
+            .route(CLUSTER_NODE_DRAIN_PATH, post(node_drain))
+            .route(CLUSTER_NODE_DRAIN_CANCEL_PATH, post(node_drain_cancel))
+            .route(CLUSTER_NODE_DRAIN_LIST_PATH, post(node_drain_list))
+            .route(CLUSTER_REBALANCE_PATH, post(cluster_rebalance))
+    }
+
+    fn admin_route(&self) -> Router<Arc<HttpState>> {
+        Router::new()
+            // auth
+            .route(AUTH_LOGIN_PATH, post(login))
+            .route(AUTH_LOGOUT_PATH, post(logout))
+            // admin user
+            .route(ADMIN_USER_LIST_PATH, post(admin_user_list))
+            .route(ADMIN_USER_CREATE_PATH, post(admin_user_create))
+            .route(ADMIN_USER_UPDATE_PATH, post(admin_user_update))
+            .route(ADMIN_USER_DELETE_PATH, post(admin_user_delete))
+            // api key
+            .route(ADMIN_API_KEY_LIST_PATH, post(api_key_list))
+            .route(ADMIN_API_KEY_CREATE_PATH, post(api_key_create))
+            .route(ADMIN_API_KEY_DELETE_PATH, post(api_key_delete))
+            // audit log
+            .route(ADMIN_AUDIT_LOG_LIST_PATH, post(audit_log_list))
     }
 
     fn mqtt_route(&self) -> Router<Arc<HttpState>> {
@@ -119,10 +190,40 @@ fn mqtt_route(&self) -> Router<Arc<HttpState>> {
             .route(MQTT_OVERVIEW_METRICS_PATH, post(overview_metrics))
             // client
             .route(MQTT_CLIENT_LIST_PATH, post(client_list))
+            .route(MQTT_CLIENT_DETAIL_PATH, post(client_detail))
+            .route(MQTT_CLIENT_KICK_PATH, post(client_kick))
             // session
             .route(MQTT_SESSION_LIST_PATH, post(session_list))
+            .route(MQTT_SESSION_CLEAR_PATH, post(session_clear))
             // topic
             .route(MQTT_TOPIC_LIST_PATH, post(topic_list))
+            // message
+            .route(MQTT_MESSAGE_PUBLISH_PATH, post(message_publish))
+            .route(MQTT_RETAIN_MESSAGE_LIST_PATH, post(retain_message_list))
+            .route(MQTT_RETAIN_MESSAGE_DELETE_PATH, post(retain_message_delete))
+            // delay-message
+            .route(MQTT_DELAY_MESSAGE_LIST_PATH, post(delay_message_list))
+            .route(MQTT_DELAY_MESSAGE_DETAIL_PATH, post(delay_message_detail))
+            .route(MQTT_DELAY_MESSAGE_CANCEL_PATH, post(delay_message_cancel))
+            // scheduled-publish
+            .route(
+                MQTT_SCHEDULED_PUBLISH_LIST_PATH,
+                post(scheduled_publish_list),
+            )
+            .route(
+                MQTT_SCHEDULED_PUBLISH_CREATE_PATH,
+                post(scheduled_publish_create),
+            )
+            .route(
+                MQTT_SCHEDULED_PUBLISH_DELETE_PATH,
+                post(scheduled_publish_delete),
+            )
+            .route(
+                MQTT_SCHEDULED_PUBLISH_HISTORY_PATH,
+                post(scheduled_publish_history),
+            )
+            // trace
+            .route(MQTT_TRACE_PATH, post(trace))
             // topic-rewrite
             .route(MQTT_TOPIC_REWRITE_LIST_PATH, post(topic_rewrite_list))
             .route(MQTT_TOPIC_REWRITE_CREATE_PATH, post(topic_rewrite_create))
@@ -130,6 +231,8 @@ fn mqtt_route(&self) -> Router<Arc<HttpState>> {
             // subscribe
             .route(MQTT_SUBSCRIBE_LIST_PATH, post(subscribe_list))
             .route(MQTT_SUBSCRIBE_DETAIL_PATH, post(subscribe_detail))
+            .route(MQTT_SUBSCRIBE_CREATE_PATH, post(subscribe_create))
+            .route(MQTT_SUBSCRIBE_DELETE_PATH, post(subscribe_delete))
             // auto subscribe
             .route(MQTT_AUTO_SUBSCRIBE_LIST_PATH, post(auto_subscribe_list))
             .route(MQTT_AUTO_SUBSCRIBE_CREATE_PATH, post(auto_subscribe_create))
@@ -154,6 +257,16 @@ fn mqtt_route(&self) -> Router<Arc<HttpState>> {
             .route(MQTT_CONNECTOR_LIST_PATH, post(connector_list))
             .route(MQTT_CONNECTOR_CREATE_PATH, post(connector_create))
             .route(MQTT_CONNECTOR_DELETE_PATH, post(connector_delete))
+            // rule engine
+            .route(MQTT_RULE_LIST_PATH, post(rule_list))
+            .route(MQTT_RULE_CREATE_PATH, post(rule_create))
+            .route(MQTT_RULE_DELETE_PATH, post(rule_delete))
+            // listener
+            .route(MQTT_LISTENER_LIST_PATH, post(listener_list))
+            .route(MQTT_LISTENER_CREATE_PATH, post(listener_create))
+            .route(MQTT_LISTENER_DELETE_PATH, post(listener_delete))
+            .route(MQTT_LISTENER_START_PATH, post(listener_start))
+            .route(MQTT_LISTENER_STOP_PATH, post(listener_stop))
             // schema
             .route(MQTT_SCHEMA_LIST_PATH, post(schema_list))
             .route(MQTT_SCHEMA_CREATE_PATH, post(schema_create))
@@ -164,6 +277,38 @@ fn mqtt_route(&self) -> Router<Arc<HttpState>> {
             // system alarm
             .route(MQTT_SYSTEM_ALARM_LIST_PATH, post(system_alarm_list))
             .route(MQTT_BAN_LOG_LIST_PATH, post(ban_log_list))
+            // detail metrics
+            .route(
+                MQTT_DETAIL_METRICS_TOPIC_LIST_PATH,
+                post(detail_metrics_topic_list),
+            )
+            .route(
+                MQTT_DETAIL_METRICS_CLIENT_LIST_PATH,
+                post(detail_metrics_client_list),
+            )
+            .route(
+                MQTT_DETAIL_METRICS_CONFIG_GET_PATH,
+                post(detail_metrics_config_get),
+            )
+            .route(
+                MQTT_DETAIL_METRICS_CONFIG_SET_PATH,
+                post(detail_metrics_config_set),
+            )
+    }
+
+    fn journal_route(&self) -> Router<Arc<HttpState>> {
+        Router::new()
+            // namespace
+            .route(JOURNAL_NAMESPACE_LIST_PATH, post(namespace_list))
+            // shard
+            .route(JOURNAL_SHARD_LIST_PATH, post(shard_list))
+            .route(JOURNAL_SHARD_DELETE_PATH, post(shard_delete))
+            // segment
+            .route(JOURNAL_SEGMENT_LIST_PATH, post(segment_list))
+            .route(JOURNAL_SEGMENT_DETAIL_PATH, post(segment_detail))
+            .route(JOURNAL_SEGMENT_SEAL_PATH, post(segment_seal))
+            // group
+            .route(JOURNAL_GROUP_OFFSET_LIST_PATH, post(group_offset_list))
     }
 
     fn kafka_route(&self) -> Router<Arc<HttpState>> {
@@ -347,7 +492,7 @@ fn format_size(size: Option<f64>) -> String {
     }
 }
 
-fn extract_client_ip(headers: &HeaderMap, socket_addr: SocketAddr) -> String {
+pub(crate) fn extract_client_ip(headers: &HeaderMap, socket_addr: SocketAddr) -> String {
     if let Some(forwarded_for) = headers.get("x-forwarded-for") {
         if let Ok(forwarded_str) = forwarded_for.to_str() {
             if let Some(first_ip) = forwarded_str.split(',').next() {
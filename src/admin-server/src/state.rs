@@ -14,15 +14,20 @@
 
 use std::sync::Arc;
 
+use crate::auth::session::SessionManager;
+
 use broker_core::{cache::BrokerCacheManager, rocksdb::RocksDBEngine};
+use delay_message::DelayMessageManager;
 use grpc_clients::pool::ClientPool;
 use mqtt_broker::{
     bridge::manager::ConnectorManager, common::metrics_cache::MetricsCacheManager,
-    handler::cache::MQTTCacheManager, subscribe::manager::SubscribeManager,
+    handler::cache::MQTTCacheManager, server::listener::ListenerManager,
+    subscribe::manager::SubscribeManager,
 };
 use network_server::common::connection_manager::ConnectionManager;
 use rate_limit::RateLimiterManager;
 use schema_register::schema::SchemaRegisterManager;
+use storage_adapter::storage::ArcStorageAdapter;
 
 #[derive(Clone)]
 pub struct HttpState {
@@ -32,6 +37,7 @@ pub struct HttpState {
     pub rocksdb_engine_handler: Arc<RocksDBEngine>,
     pub mqtt_context: MQTTContext,
     pub rate_limiter_manager: Arc<RateLimiterManager>,
+    pub session_manager: Arc<SessionManager>,
 }
 
 #[derive(Clone)]
@@ -41,4 +47,7 @@ pub struct MQTTContext {
     pub metrics_manager: Arc<MetricsCacheManager>,
     pub connector_manager: Arc<ConnectorManager>,
     pub schema_manager: Arc<SchemaRegisterManager>,
+    pub listener_manager: Arc<ListenerManager>,
+    pub message_storage_adapter: ArcStorageAdapter,
+    pub delay_message_manager: Arc<DelayMessageManager>,
 }
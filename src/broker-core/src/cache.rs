@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use common_base::{node_status::NodeStatus, tools::now_second};
+use common_base::{node_status::NodeStatus, tools::now_second, utils::crc::calc_crc32};
 use common_config::config::BrokerConfig;
 use dashmap::DashMap;
 use metadata_struct::placement::node::BrokerNode;
@@ -32,6 +32,9 @@ pub struct BrokerCacheManager {
 
     // (cluster_name, Status)
     pub status: DashMap<String, NodeStatus>,
+
+    // (cluster_name, draining)
+    pub draining: DashMap<String, bool>,
 }
 impl BrokerCacheManager {
     pub fn new(cluster_name: String) -> Self {
@@ -41,6 +44,7 @@ pub fn new(cluster_name: String) -> Self {
             node_lists: DashMap::with_capacity(2),
             cluster_info: DashMap::with_capacity(1),
             status: DashMap::with_capacity(2),
+            draining: DashMap::with_capacity(1),
         }
     }
 
@@ -60,6 +64,17 @@ pub fn node_list(&self) -> Vec<BrokerNode> {
             .collect()
     }
 
+    // Work that has to run once per key across the cluster runs on the owner
+    // of the key. Owners are picked by rendezvous hashing over the node list,
+    // so only the keys of a node that joins or leaves change hands.
+    pub fn is_owner(&self, node_id: u64, key: &str) -> bool {
+        self.node_lists
+            .iter()
+            .map(|entry| *entry.key())
+            .max_by_key(|id| (calc_crc32(format!("{id}/{key}").as_bytes()), *id))
+            .is_some_and(|owner| owner == node_id)
+    }
+
     // get start time
     pub fn get_start_time(&self) -> u64 {
         self.start_time
@@ -78,6 +93,19 @@ pub fn is_stop(&self) -> bool {
         }
     }
 
+    // drain
+    pub fn set_draining(&self, draining: bool) {
+        self.draining.insert(self.cluster_name.clone(), draining);
+    }
+
+    pub fn is_draining(&self) -> bool {
+        if let Some(draining) = self.draining.get(&self.cluster_name) {
+            *draining
+        } else {
+            false
+        }
+    }
+
     // cluster config
     pub fn set_cluster_config(&self, cluster: BrokerConfig) {
         self.cluster_info.insert(self.cluster_name.clone(), cluster);
@@ -127,4 +155,36 @@ async fn node_operations() {
         let nodes = cache_manager.node_list();
         assert!(nodes.is_empty());
     }
+
+    #[tokio::test]
+    async fn draining_operations() {
+        let cache_manager = BrokerCacheManager::new("test".to_string());
+        assert!(!cache_manager.is_draining());
+
+        cache_manager.set_draining(true);
+        assert!(cache_manager.is_draining());
+
+        cache_manager.set_draining(false);
+        assert!(!cache_manager.is_draining());
+    }
+
+    #[test]
+    fn is_owner() {
+        let cache_manager = BrokerCacheManager::new("test".to_string());
+        assert!(!cache_manager.is_owner(1, "topic"));
+
+        for node_id in 1..=3 {
+            cache_manager.add_node(BrokerNode {
+                node_id,
+                ..Default::default()
+            });
+        }
+
+        for key in ["topic-1", "topic-2", "topic-3", "topic-4"] {
+            let owners: Vec<u64> = (1..=3)
+                .filter(|node_id| cache_manager.is_owner(*node_id, key))
+                .collect();
+            assert_eq!(owners.len(), 1);
+        }
+    }
 }
@@ -78,6 +78,7 @@ pub async fn register_node(
                 websocket_addr: format!("{}:{}", local_ip, config.mqtt_server.websocket_port),
                 websockets_addr: format!("{}:{}", local_ip, config.mqtt_server.websockets_port),
                 quic_addr: format!("{}:{}", local_ip, config.mqtt_server.quic_port),
+                draining: cache_manager.is_draining(),
             },
         };
 
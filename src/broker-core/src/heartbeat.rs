@@ -55,12 +55,33 @@ pub async fn report_heartbeat(
         } else {
             debug!("heartbeat report success");
         }
+
+        // The heartbeat only carries the node id, so a drain state change is
+        // published by registering the node again with the new extend.
+        if drain_state_changed(cache_manager) {
+            if let Err(e) = register_node(client_pool, cache_manager).await {
+                error!("{}", e);
+            } else {
+                info!(
+                    "Node drain state reported to meta service, draining: {}",
+                    cache_manager.is_draining()
+                );
+            }
+        }
         Ok(())
     };
 
     loop_select(ac_fn, 3, &stop_send).await;
 }
 
+fn drain_state_changed(cache_manager: &Arc<BrokerCacheManager>) -> bool {
+    let config = broker_config();
+    if let Some(node) = cache_manager.node_lists.get(&config.broker_id) {
+        return node.is_draining() != cache_manager.is_draining();
+    }
+    false
+}
+
 #[derive(Deserialize, Serialize)]
 struct MetaServiceStatus {
     pub current_leader: u32,
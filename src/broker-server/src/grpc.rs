@@ -18,9 +18,12 @@
 use common_base::tools::now_mills;
 use common_config::broker::broker_config;
 use common_metrics::grpc::{extract_grpc_status_code, parse_grpc_path, record_grpc_request};
-use journal_server::server::grpc::admin::GrpcJournalServerAdminService;
+use journal_server::server::grpc::admin::{
+    GrpcJournalSegmentAdminService, GrpcJournalServerAdminService,
+};
 use journal_server::server::grpc::inner::GrpcJournalServerInnerService;
 use journal_server::JournalServerParams;
+use meta_service::server::service_consumer_group::GrpcConsumerGroupService;
 use meta_service::server::service_inner::GrpcPlacementService;
 use meta_service::server::service_journal::GrpcEngineService;
 use meta_service::server::service_kv::GrpcKvService;
@@ -28,11 +31,17 @@
 use meta_service::server::service_raft::GrpcOpenRaftServices;
 use meta_service::MetaServiceServerParams;
 use mqtt_broker::broker::MqttBrokerServerParams;
+use mqtt_broker::server::admin::GrpcAdminServices;
 use mqtt_broker::server::inner::GrpcInnerServices;
+use mqtt_broker::server::session::GrpcSessionServices;
+use protocol::broker::broker_mqtt_admin::mqtt_broker_admin_service_server::MqttBrokerAdminServiceServer;
 use protocol::broker::broker_mqtt_inner::mqtt_broker_inner_service_server::MqttBrokerInnerServiceServer;
+use protocol::broker::broker_mqtt_session::mqtt_broker_session_service_server::MqttBrokerSessionServiceServer;
 use protocol::cluster::cluster_status::cluster_service_server::ClusterServiceServer;
 use protocol::journal::journal_admin::journal_server_admin_service_server::JournalServerAdminServiceServer;
 use protocol::journal::journal_inner::journal_server_inner_service_server::JournalServerInnerServiceServer;
+use protocol::journal::journal_segment_admin::journal_segment_admin_service_server::JournalSegmentAdminServiceServer;
+use protocol::meta::meta_service_consumer_group::consumer_group_service_server::ConsumerGroupServiceServer;
 use protocol::meta::meta_service_inner::meta_service_service_server::MetaServiceServiceServer;
 use protocol::meta::meta_service_journal::engine_service_server::EngineServiceServer;
 use protocol::meta::meta_service_kv::kv_service_server::KvServiceServer;
@@ -90,14 +99,27 @@ pub async fn start_grpc_server(
             .add_service(
                 OpenRaftServiceServer::new(get_place_raft_handler(&place_params))
                     .max_decoding_message_size(grpc_max_decoding_message_size),
+            )
+            .add_service(
+                ConsumerGroupServiceServer::new(get_place_consumer_group_handler(&place_params))
+                    .max_decoding_message_size(grpc_max_decoding_message_size),
             );
     }
 
     if config.is_start_broker() {
-        route = route.add_service(
-            MqttBrokerInnerServiceServer::new(get_mqtt_inner_handler(&mqtt_params))
-                .max_decoding_message_size(grpc_max_decoding_message_size),
-        );
+        route = route
+            .add_service(
+                MqttBrokerInnerServiceServer::new(get_mqtt_inner_handler(&mqtt_params))
+                    .max_decoding_message_size(grpc_max_decoding_message_size),
+            )
+            .add_service(
+                MqttBrokerAdminServiceServer::new(get_mqtt_admin_handler(&mqtt_params))
+                    .max_decoding_message_size(grpc_max_decoding_message_size),
+            )
+            .add_service(
+                MqttBrokerSessionServiceServer::new(get_mqtt_session_handler(&mqtt_params))
+                    .max_decoding_message_size(grpc_max_decoding_message_size),
+            );
     }
 
     if config.is_start_journal() {
@@ -106,6 +128,12 @@ pub async fn start_grpc_server(
                 JournalServerAdminServiceServer::new(get_journal_admin_handler(&journal_params))
                     .max_decoding_message_size(grpc_max_decoding_message_size),
             )
+            .add_service(
+                JournalSegmentAdminServiceServer::new(get_journal_segment_admin_handler(
+                    &journal_params,
+                ))
+                .max_decoding_message_size(grpc_max_decoding_message_size),
+            )
             .add_service(
                 JournalServerInnerServiceServer::new(get_journal_inner_handler(&journal_params))
                     .max_decoding_message_size(grpc_max_decoding_message_size),
@@ -158,6 +186,12 @@ fn get_place_raft_handler(place_params: &MetaServiceServerParams) -> GrpcOpenRaf
     GrpcOpenRaftServices::new(place_params.storage_driver.raft_node.clone())
 }
 
+fn get_place_consumer_group_handler(
+    place_params: &MetaServiceServerParams,
+) -> GrpcConsumerGroupService {
+    GrpcConsumerGroupService::new(place_params.storage_driver.clone())
+}
+
 fn get_mqtt_inner_handler(mqtt_params: &MqttBrokerServerParams) -> GrpcInnerServices {
     GrpcInnerServices::new(
         mqtt_params.cache_manager.clone(),
@@ -169,10 +203,39 @@ fn get_mqtt_inner_handler(mqtt_params: &MqttBrokerServerParams) -> GrpcInnerServ
     )
 }
 
+fn get_mqtt_admin_handler(mqtt_params: &MqttBrokerServerParams) -> GrpcAdminServices {
+    GrpcAdminServices::new(
+        mqtt_params.cache_manager.clone(),
+        mqtt_params.subscribe_manager.clone(),
+        mqtt_params.connection_manager.clone(),
+        mqtt_params.client_pool.clone(),
+        mqtt_params.message_storage_adapter.clone(),
+        mqtt_params.connector_manager.clone(),
+    )
+}
+
+fn get_mqtt_session_handler(mqtt_params: &MqttBrokerServerParams) -> GrpcSessionServices {
+    GrpcSessionServices::new(
+        mqtt_params.cache_manager.clone(),
+        mqtt_params.subscribe_manager.clone(),
+        mqtt_params.connection_manager.clone(),
+    )
+}
+
 fn get_journal_admin_handler(params: &JournalServerParams) -> GrpcJournalServerAdminService {
     GrpcJournalServerAdminService::new(params.cache_manager.clone())
 }
 
+fn get_journal_segment_admin_handler(
+    params: &JournalServerParams,
+) -> GrpcJournalSegmentAdminService {
+    GrpcJournalSegmentAdminService::new(
+        params.cache_manager.clone(),
+        params.client_pool.clone(),
+        params.segment_file_manager.clone(),
+    )
+}
+
 fn get_journal_inner_handler(params: &JournalServerParams) -> GrpcJournalServerInnerService {
     GrpcJournalServerInnerService::new(
         params.cache_manager.clone(),
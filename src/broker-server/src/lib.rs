@@ -14,6 +14,7 @@
 
 use crate::grpc::start_grpc_server;
 use admin_server::{
+    auth::session::SessionManager,
     server::AdminServer,
     state::{HttpState, MQTTContext},
 };
@@ -23,6 +24,7 @@
     rocksdb::{column_family_list, storage_data_fold, RocksDBEngine},
 };
 use common_base::runtime::create_runtime;
+use common_base::telemetry::trace::{init_tracer_provider, stop_tracer_provider};
 use common_config::{broker::broker_config, config::BrokerConfig};
 use common_metrics::core::server::register_prometheus_export;
 use delay_message::DelayMessageManager;
@@ -50,6 +52,7 @@
     common::metrics_cache::MetricsCacheManager,
     handler::cache::MQTTCacheManager as MqttCacheManager,
     security::AuthDriver,
+    server::listener::ListenerManager,
     storage::message::build_message_storage_driver,
     subscribe::manager::SubscribeManager,
 };
@@ -160,14 +163,21 @@ pub fn start(&self) {
                 metrics_manager: self.mqtt_params.metrics_cache_manager.clone(),
                 connector_manager: self.mqtt_params.connector_manager.clone(),
                 schema_manager: self.mqtt_params.schema_manager.clone(),
+                listener_manager: self.mqtt_params.listener_manager.clone(),
+                message_storage_adapter: self.mqtt_params.message_storage_adapter.clone(),
+                delay_message_manager: self.mqtt_params.delay_message_manager.clone(),
             },
             rocksdb_engine_handler: self.rocksdb_engine_handler.clone(),
             broker_cache: broker_cache.clone(),
             rate_limiter_manager: self.rate_limiter_manager.clone(),
+            session_manager: Arc::new(SessionManager::new(
+                self.config.admin_server.session_expire_sec,
+            )),
         });
+        let admin_port = self.config.admin_server.port;
         server_runtime.spawn(async move {
             let admin_server = AdminServer::new();
-            admin_server.start(8080, state).await;
+            admin_server.start(admin_port, state).await;
         });
 
         // check grpc server ready
@@ -189,6 +199,19 @@ pub fn start(&self) {
             });
         }
 
+        // start telemetry, the otlp exporter needs a tokio runtime context
+        {
+            let _guard = server_runtime.enter();
+            let telemetry = &self.config.telemetry;
+            if let Err(e) = init_tracer_provider(
+                telemetry.enable,
+                &telemetry.exporter_type,
+                &telemetry.exporter_endpoint,
+            ) {
+                error!("Failed to initialize tracer provider, error message:{}", e);
+            }
+        }
+
         self.wait_for_grpc_ready(&grpc_ready);
 
         let mut place_stop_send = None;
@@ -313,6 +336,12 @@ fn build_mqtt_server(
         ));
         let metrics_cache_manager = Arc::new(MetricsCacheManager::new());
         let schema_manager = Arc::new(SchemaRegisterManager::new());
+        let listener_manager = Arc::new(ListenerManager::new(
+            cache_manager.clone(),
+            connection_manager.clone(),
+            client_pool.clone(),
+            broker_cache.clone(),
+        ));
 
         MqttBrokerServerParams {
             cache_manager,
@@ -327,6 +356,7 @@ fn build_mqtt_server(
             metrics_cache_manager,
             rocksdb_engine_handler,
             broker_cache,
+            listener_manager,
         }
     }
 
@@ -390,6 +420,10 @@ pub fn awaiting_stop(
                     error!("place stop signal, error message{}", e);
                 }
             }
+
+            if let Err(e) = stop_tracer_provider() {
+                error!("stop tracer provider, error message{}", e);
+            }
             sleep(Duration::from_secs(3));
         });
     }
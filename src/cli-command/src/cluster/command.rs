@@ -13,8 +13,16 @@
 // limitations under the License.
 
 use crate::mqtt::pub_sub::error_info;
-use admin_server::{client::AdminHttpClient, request::cluster::ClusterConfigSetReq};
+use admin_server::{
+    client::AdminHttpClient,
+    request::cluster::{
+        ClusterConfigSetReq, ClusterRebalanceReq, NodeDrainCancelReq, NodeDrainListReq,
+        NodeDrainReq,
+    },
+    response::cluster::{ClusterRebalanceResp, NodeDrainStatusRow},
+};
 use common_config::config::BrokerConfig;
+use prettytable::{row, Table};
 
 #[derive(Clone)]
 pub struct ClusterCliCommandParam {
@@ -26,6 +34,10 @@ pub struct ClusterCliCommandParam {
 pub enum ClusterActionType {
     GetConfig,
     SetConfig(ClusterConfigSetReq),
+    Drain(NodeDrainReq),
+    DrainCancel(NodeDrainCancelReq),
+    DrainStatus(NodeDrainListReq),
+    Rebalance(ClusterRebalanceReq),
 }
 
 pub struct ClusterCommand {}
@@ -48,6 +60,18 @@ pub async fn start(&self, params: ClusterCliCommandParam) {
             ClusterActionType::SetConfig(request) => {
                 self.set_cluster_config(params, request.clone()).await;
             }
+            ClusterActionType::Drain(request) => {
+                self.drain_node(params, request).await;
+            }
+            ClusterActionType::DrainCancel(request) => {
+                self.cancel_drain_node(params, request).await;
+            }
+            ClusterActionType::DrainStatus(request) => {
+                self.drain_status(params, request).await;
+            }
+            ClusterActionType::Rebalance(request) => {
+                self.rebalance(params, request).await;
+            }
         }
     }
 
@@ -104,4 +128,130 @@ async fn get_cluster_config(&self, params: ClusterCliCommandParam) {
             }
         }
     }
+
+    async fn drain_node(&self, params: ClusterCliCommandParam, cli_request: NodeDrainReq) {
+        // Create admin HTTP client
+        let admin_client = AdminHttpClient::new(format!("http://{}", params.server));
+
+        match admin_client.drain_node(&cli_request).await {
+            Ok(_) => {
+                println!(
+                    "Broker {} is draining, check the progress with `cluster drain-status`",
+                    cli_request.broker_id
+                );
+            }
+            Err(e) => {
+                println!("MQTT broker drain node exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn cancel_drain_node(
+        &self,
+        params: ClusterCliCommandParam,
+        cli_request: NodeDrainCancelReq,
+    ) {
+        // Create admin HTTP client
+        let admin_client = AdminHttpClient::new(format!("http://{}", params.server));
+
+        match admin_client.cancel_drain_node(&cli_request).await {
+            Ok(_) => {
+                println!(
+                    "Drain of broker {} cancelled, it takes new connections again",
+                    cli_request.broker_id
+                );
+            }
+            Err(e) => {
+                println!("MQTT broker cancel drain exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn drain_status(&self, params: ClusterCliCommandParam, cli_request: NodeDrainListReq) {
+        // Create admin HTTP client
+        let admin_client = AdminHttpClient::new(format!("http://{}", params.server));
+
+        match admin_client
+            .get_drain_node_list::<NodeDrainListReq, Vec<NodeDrainStatusRow>>(&cli_request)
+            .await
+        {
+            Ok(rows) => {
+                let mut table = Table::new();
+                table.add_row(row![
+                    "broker_id",
+                    "draining",
+                    "connections",
+                    "disconnected",
+                    "disconnect_rate",
+                    "share_leaders",
+                    "connectors",
+                    "drained"
+                ]);
+                for row in rows {
+                    table.add_row(row![
+                        row.broker_id,
+                        row.draining,
+                        row.connection_count,
+                        row.disconnected_count,
+                        row.disconnect_rate,
+                        row.share_leader_count,
+                        row.connector_count,
+                        row.drained
+                    ]);
+                }
+                table.printstd();
+            }
+            Err(e) => {
+                println!("MQTT broker drain status exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn rebalance(&self, params: ClusterCliCommandParam, cli_request: ClusterRebalanceReq) {
+        // Create admin HTTP client
+        let admin_client = AdminHttpClient::new(format!("http://{}", params.server));
+
+        match admin_client
+            .rebalance_cluster::<ClusterRebalanceReq, ClusterRebalanceResp>(&cli_request)
+            .await
+        {
+            Ok(resp) => {
+                if resp.moves.is_empty() {
+                    println!("Connections are already balanced, nothing to move");
+                    return;
+                }
+
+                println!(
+                    "Every broker is brought down to {} connections",
+                    resp.target_connection_count
+                );
+                let mut table = Table::new();
+                table.add_row(row![
+                    "broker_id",
+                    "connections",
+                    "move",
+                    "server_references"
+                ]);
+                for row in resp.moves {
+                    table.add_row(row![
+                        row.broker_id,
+                        row.connection_count,
+                        row.move_count,
+                        row.server_references.join(",")
+                    ]);
+                }
+                table.printstd();
+                if cli_request.dry_run {
+                    println!("Dry run, no connection was moved");
+                }
+            }
+            Err(e) => {
+                println!("MQTT broker rebalance exception");
+                error_info(e.to_string());
+            }
+        }
+    }
 }
@@ -13,16 +13,28 @@
 // limitations under the License.
 
 use crate::cluster::command::{ClusterActionType, ClusterCliCommandParam, ClusterCommand};
+use crate::journal::command::{JournalCliCommandParam, JournalCommand};
+use crate::journal::params::{
+    process_group_args, process_namespace_args, process_segment_args, process_shard_args,
+    GroupArgs, NamespaceArgs, SegmentArgs, ShardArgs,
+};
 use crate::mqtt::command::{MqttBrokerCommand, MqttCliCommandParam};
 use crate::mqtt::params::{
     process_acl_args, process_auto_subscribe_args, process_blacklist_args, process_connection_args,
-    process_connector_args, process_flapping_detect_args, process_publish_args,
-    process_schema_args, process_session_args, process_slow_sub_args, process_subscribe_args,
-    process_subscribes_args, process_system_alarm_args, process_topic_args,
-    process_topic_rewrite_args, process_user_args, AclArgs, AutoSubscribeRuleCommand,
-    BlacklistArgs, ClientsArgs, ClusterConfigActionType, ClusterConfigArgs, ConnectorArgs,
-    FlappingDetectArgs, PubSubArgs, SchemaArgs, SessionArgs, SlowSubscribeArgs, SubscribesArgs,
-    SystemAlarmArgs, TopicArgs, TopicRewriteArgs, UserArgs,
+    process_connector_args, process_delay_message_args, process_detail_metrics_args,
+    process_flapping_detect_args, process_message_args, process_publish_args,
+    process_retain_message_args, process_scheduled_publish_args, process_schema_args,
+    process_session_args, process_slow_sub_args, process_subscribe_args, process_subscribes_args,
+    process_system_alarm_args, process_topic_args, process_topic_rewrite_args, process_trace_args,
+    process_user_args, AclArgs, AutoSubscribeRuleCommand, BlacklistArgs, ClientsArgs,
+    ClusterConfigActionType, ClusterConfigArgs, ClusterDrainArgs, ClusterDrainCancelArgs,
+    ClusterDrainStatusArgs, ClusterRebalanceArgs, ConnectorArgs, DelayMessageArgs,
+    DetailMetricsArgs, FlappingDetectArgs, MessageArgs, PubSubArgs, RetainMessageArgs,
+    ScheduledPublishArgs, SchemaArgs, SessionArgs, SlowSubscribeArgs, SubscribesArgs,
+    SystemAlarmArgs, TopicArgs, TopicRewriteArgs, TraceArgs, UserArgs,
+};
+use admin_server::request::cluster::{
+    ClusterRebalanceReq, NodeDrainCancelReq, NodeDrainListReq, NodeDrainReq,
 };
 use clap::{arg, Parser, Subcommand};
 
@@ -86,10 +98,27 @@ pub enum MQTTAction {
     SlowSubscribe(SlowSubscribeArgs),
     // ---- system alarm ----
     SystemAlarm(SystemAlarmArgs),
+    // ---- packet trace ----
+    Trace(TraceArgs),
 
     // list topic
     Topic(TopicArgs),
 
+    // publish through the admin api
+    Message(MessageArgs),
+
+    // retained message
+    RetainMessage(RetainMessageArgs),
+
+    // delay message
+    DelayMessage(DelayMessageArgs),
+
+    // scheduled publish
+    ScheduledPublish(ScheduledPublishArgs),
+
+    // per-topic and per-client metrics
+    DetailMetrics(DetailMetricsArgs),
+
     // topic rewrite
     TopicRewrite(TopicRewriteArgs),
 
@@ -121,6 +150,14 @@ pub struct ClusterArgs {
 #[derive(Debug, Subcommand)]
 pub enum ClusterAction {
     Config(ClusterConfigArgs),
+    #[command(author = "RobustMQ", about = "action: drain a broker before it is stopped, its clients are moved to the other brokers", long_about = None)]
+    Drain(ClusterDrainArgs),
+    #[command(author = "RobustMQ", about = "action: cancel the drain of a broker", long_about = None)]
+    DrainCancel(ClusterDrainCancelArgs),
+    #[command(author = "RobustMQ", about = "action: show the drain progress of the brokers", long_about = None)]
+    DrainStatus(ClusterDrainStatusArgs),
+    #[command(author = "RobustMQ", about = "action: spread the connections evenly over the brokers", long_about = None)]
+    Rebalance(ClusterRebalanceArgs),
 }
 
 #[derive(clap::Args, Debug)]
@@ -130,8 +167,20 @@ pub struct JournalArgs {
     #[arg(short, long,default_value_t =String::from("127.0.0.1:8080"))]
     server: String,
 
-    #[arg(short, long,default_value_t =String::from("status"))]
-    action: String,
+    #[clap(subcommand)]
+    action: JournalAction,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum JournalAction {
+    // namespace
+    Namespace(NamespaceArgs),
+    // shard
+    Shard(ShardArgs),
+    // segment
+    Segment(SegmentArgs),
+    // consumer group
+    Group(GroupArgs),
 }
 
 #[derive(clap::Args, Debug)]
@@ -172,12 +221,24 @@ pub async fn handle_mqtt(args: MqttArgs, cmd: MqttBrokerCommand) {
             MQTTAction::FlappingDetect(args) => process_flapping_detect_args(args),
             // system alarm
             MQTTAction::SystemAlarm(args) => process_system_alarm_args(args),
+            // packet trace
+            MQTTAction::Trace(args) => process_trace_args(args),
             // Connections
             MQTTAction::Client(args) => process_connection_args(args),
             // connector
             MQTTAction::Connector(args) => process_connector_args(args),
             // list topic
             MQTTAction::Topic(args) => process_topic_args(args),
+            // message
+            MQTTAction::Message(args) => process_message_args(args),
+            // retained message
+            MQTTAction::RetainMessage(args) => process_retain_message_args(args),
+            // delay message
+            MQTTAction::DelayMessage(args) => process_delay_message_args(args),
+            // scheduled publish
+            MQTTAction::ScheduledPublish(args) => process_scheduled_publish_args(args),
+            // detail metrics
+            MQTTAction::DetailMetrics(args) => process_detail_metrics_args(args),
             // topic rewrite rule
             MQTTAction::TopicRewrite(args) => process_topic_rewrite_args(args),
             MQTTAction::SlowSubscribe(args) => process_slow_sub_args(args),
@@ -200,14 +261,38 @@ pub async fn handle_cluster(args: ClusterArgs, cmd: ClusterCommand) {
             ClusterAction::Config(config_args) => match config_args.action {
                 ClusterConfigActionType::Get => ClusterActionType::GetConfig,
             },
+            ClusterAction::Drain(args) => ClusterActionType::Drain(NodeDrainReq {
+                broker_id: args.broker_id,
+                disconnect_rate: args.disconnect_rate,
+            }),
+            ClusterAction::DrainCancel(args) => {
+                ClusterActionType::DrainCancel(NodeDrainCancelReq {
+                    broker_id: args.broker_id,
+                })
+            }
+            ClusterAction::DrainStatus(args) => ClusterActionType::DrainStatus(NodeDrainListReq {
+                broker_id: args.broker_id,
+            }),
+            ClusterAction::Rebalance(args) => ClusterActionType::Rebalance(ClusterRebalanceReq {
+                disconnect_rate: args.disconnect_rate,
+                dry_run: args.dry_run,
+            }),
         },
     };
     cmd.start(params).await;
 }
 
-// TODO: implement journal engine
-pub async fn handle_journal(args: JournalArgs) {
-    println!("{args:?}");
+pub async fn handle_journal(args: JournalArgs, cmd: JournalCommand) {
+    let params = JournalCliCommandParam {
+        server: args.server,
+        action: match args.action {
+            JournalAction::Namespace(args) => process_namespace_args(args),
+            JournalAction::Shard(args) => process_shard_args(args),
+            JournalAction::Segment(args) => process_segment_args(args),
+            JournalAction::Group(args) => process_group_args(args),
+        },
+    };
+    cmd.start(params).await;
 }
 
 pub async fn handle_status(args: StatusArgs) {
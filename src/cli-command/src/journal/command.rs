@@ -0,0 +1,378 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::mqtt::pub_sub::error_info;
+use admin_server::client::AdminHttpClient;
+use admin_server::request::journal::{
+    DeleteShardReq, GroupOffsetListReq, NamespaceListReq, SealSegmentReq, SegmentDetailReq,
+    SegmentListReq, ShardListReq,
+};
+use admin_server::response::journal::{
+    GroupOffsetListRow, NamespaceListRow, SealSegmentResp, SegmentDetailResp, SegmentListRow,
+    ShardListRow,
+};
+use prettytable::{row, Table};
+
+// Default pagination constants
+const DEFAULT_PAGE_SIZE: u32 = 10000;
+const DEFAULT_PAGE_NUM: u32 = 1;
+
+#[derive(Clone)]
+pub struct JournalCliCommandParam {
+    pub server: String,
+    pub action: JournalActionType,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JournalActionType {
+    // namespace
+    ListNamespace,
+
+    // shard
+    ListShard(Option<String>, Option<String>),
+    DeleteShard(DeleteShardReq),
+
+    // segment
+    ListSegment(String, String),
+    SegmentDetail(SegmentDetailReq),
+    SealSegment(SealSegmentReq),
+
+    // group
+    ListGroupOffset(String, Option<String>),
+}
+
+pub struct JournalCommand {}
+
+impl Default for JournalCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JournalCommand {
+    pub fn new() -> Self {
+        JournalCommand {}
+    }
+
+    pub async fn start(&self, params: JournalCliCommandParam) {
+        match params.action.clone() {
+            JournalActionType::ListNamespace => {
+                self.list_namespace(params).await;
+            }
+            JournalActionType::ListShard(namespace, shard_name) => {
+                self.list_shard(params, namespace, shard_name).await;
+            }
+            JournalActionType::DeleteShard(request) => {
+                self.delete_shard(params, request).await;
+            }
+            JournalActionType::ListSegment(namespace, shard_name) => {
+                self.list_segment(params, namespace, shard_name).await;
+            }
+            JournalActionType::SegmentDetail(request) => {
+                self.segment_detail(params, request).await;
+            }
+            JournalActionType::SealSegment(request) => {
+                self.seal_segment(params, request).await;
+            }
+            JournalActionType::ListGroupOffset(group_name, namespace) => {
+                self.list_group_offset(params, group_name, namespace).await;
+            }
+        }
+    }
+
+    async fn list_namespace(&self, params: JournalCliCommandParam) {
+        // Create admin HTTP client
+        let admin_client = AdminHttpClient::new(format!("http://{}", params.server));
+
+        let request = NamespaceListReq {
+            limit: Some(DEFAULT_PAGE_SIZE),
+            page: Some(DEFAULT_PAGE_NUM),
+            ..Default::default()
+        };
+
+        match admin_client
+            .get_journal_namespace_list::<NamespaceListReq, Vec<NamespaceListRow>>(&request)
+            .await
+        {
+            Ok(page_data) => {
+                println!("namespace list result:");
+                // format table
+                let mut table = Table::new();
+                table.set_titles(row!["namespace", "shard_num"]);
+                for namespace in page_data.data {
+                    table.add_row(row![namespace.namespace, namespace.shard_num]);
+                }
+                // output cmd
+                table.printstd()
+            }
+            Err(e) => {
+                println!("Journal engine list namespace exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn list_shard(
+        &self,
+        params: JournalCliCommandParam,
+        namespace: Option<String>,
+        shard_name: Option<String>,
+    ) {
+        // Create admin HTTP client
+        let admin_client = AdminHttpClient::new(format!("http://{}", params.server));
+
+        let request = ShardListReq {
+            namespace,
+            shard_name,
+            limit: Some(DEFAULT_PAGE_SIZE),
+            page: Some(DEFAULT_PAGE_NUM),
+            ..Default::default()
+        };
+
+        match admin_client
+            .get_journal_shard_list::<ShardListReq, Vec<ShardListRow>>(&request)
+            .await
+        {
+            Ok(page_data) => {
+                println!("shard list result:");
+                // format table
+                let mut table = Table::new();
+                table.set_titles(row![
+                    "namespace",
+                    "shard_name",
+                    "status",
+                    "start_segment",
+                    "active_segment",
+                    "last_segment",
+                    "replica_num",
+                    "max_segment_size",
+                ]);
+                for shard in page_data.data {
+                    table.add_row(row![
+                        shard.namespace,
+                        shard.shard_name,
+                        shard.status,
+                        shard.start_segment_seq,
+                        shard.active_segment_seq,
+                        shard.last_segment_seq,
+                        shard.replica_num,
+                        shard.max_segment_size
+                    ]);
+                }
+                // output cmd
+                table.printstd()
+            }
+            Err(e) => {
+                println!("Journal engine list shard exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn delete_shard(&self, params: JournalCliCommandParam, cli_request: DeleteShardReq) {
+        // Create admin HTTP client
+        let admin_client = AdminHttpClient::new(format!("http://{}", params.server));
+
+        match admin_client.delete_journal_shard(&cli_request).await {
+            Ok(_) => {
+                println!(
+                    "Shard {}/{} is being deleted, its segments are removed in the background.",
+                    cli_request.namespace, cli_request.shard_name
+                );
+            }
+            Err(e) => {
+                println!("Journal engine delete shard exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn list_segment(
+        &self,
+        params: JournalCliCommandParam,
+        namespace: String,
+        shard_name: String,
+    ) {
+        // Create admin HTTP client
+        let admin_client = AdminHttpClient::new(format!("http://{}", params.server));
+
+        let request = SegmentListReq {
+            namespace,
+            shard_name,
+            limit: Some(DEFAULT_PAGE_SIZE),
+            page: Some(DEFAULT_PAGE_NUM),
+            ..Default::default()
+        };
+
+        match admin_client
+            .get_journal_segment_list::<SegmentListReq, Vec<SegmentListRow>>(&request)
+            .await
+        {
+            Ok(page_data) => {
+                println!("segment list result:");
+                // format table
+                let mut table = Table::new();
+                table.set_titles(row![
+                    "segment_no",
+                    "status",
+                    "leader",
+                    "leader_epoch",
+                    "replicas",
+                    "isr",
+                    "start_offset",
+                    "end_offset",
+                    "start_timestamp",
+                    "end_timestamp",
+                ]);
+                for segment in page_data.data {
+                    table.add_row(row![
+                        segment.segment_no,
+                        segment.status,
+                        segment.leader,
+                        segment.leader_epoch,
+                        format!("{:?}", segment.replicas),
+                        format!("{:?}", segment.isr),
+                        segment.start_offset,
+                        segment.end_offset,
+                        segment.start_timestamp,
+                        segment.end_timestamp
+                    ]);
+                }
+                // output cmd
+                table.printstd()
+            }
+            Err(e) => {
+                println!("Journal engine list segment exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn segment_detail(&self, params: JournalCliCommandParam, cli_request: SegmentDetailReq) {
+        // Create admin HTTP client
+        let admin_client = AdminHttpClient::new(format!("http://{}", params.server));
+
+        match admin_client
+            .get_journal_segment_detail::<SegmentDetailReq, SegmentDetailResp>(&cli_request)
+            .await
+        {
+            Ok(detail) => {
+                let segment = detail.segment;
+                let mut table = Table::new();
+                table.set_titles(row!["field", "value"]);
+                table.add_row(row![
+                    "segment",
+                    format!(
+                        "{}/{}/{}",
+                        segment.namespace, segment.shard_name, segment.segment_no
+                    )
+                ]);
+                table.add_row(row!["status", segment.status]);
+                table.add_row(row!["leader", segment.leader]);
+                table.add_row(row!["leader_epoch", segment.leader_epoch]);
+                table.add_row(row!["replicas", format!("{:?}", segment.replicas)]);
+                table.add_row(row!["isr", format!("{:?}", segment.isr)]);
+                table.add_row(row!["meta_start_offset", segment.start_offset]);
+                table.add_row(row!["meta_end_offset", segment.end_offset]);
+                table.add_row(row!["meta_start_timestamp", segment.start_timestamp]);
+                table.add_row(row!["meta_end_timestamp", segment.end_timestamp]);
+                match detail.file {
+                    Some(file) => {
+                        table.add_row(row!["file_start_offset", file.start_offset]);
+                        table.add_row(row!["file_end_offset", file.end_offset]);
+                        table.add_row(row!["file_start_timestamp", file.start_timestamp]);
+                        table.add_row(row!["file_end_timestamp", file.end_timestamp]);
+                    }
+                    None => {
+                        table.add_row(row!["file", detail.file_error.unwrap_or_default()]);
+                    }
+                }
+                table.printstd();
+            }
+            Err(e) => {
+                println!("Journal engine segment detail exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn seal_segment(&self, params: JournalCliCommandParam, cli_request: SealSegmentReq) {
+        // Create admin HTTP client
+        let admin_client = AdminHttpClient::new(format!("http://{}", params.server));
+
+        match admin_client
+            .seal_journal_segment::<SealSegmentReq, SealSegmentResp>(&cli_request)
+            .await
+        {
+            Ok(resp) => {
+                println!(
+                    "Segment {}/{}/{} sealed up at offset {}, writes move to segment {}.",
+                    resp.namespace,
+                    resp.shard_name,
+                    resp.segment_no,
+                    resp.end_offset,
+                    resp.next_segment_no
+                );
+            }
+            Err(e) => {
+                println!("Journal engine seal segment exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn list_group_offset(
+        &self,
+        params: JournalCliCommandParam,
+        group_name: String,
+        namespace: Option<String>,
+    ) {
+        // Create admin HTTP client
+        let admin_client = AdminHttpClient::new(format!("http://{}", params.server));
+
+        let request = GroupOffsetListReq {
+            group_name,
+            namespace,
+            limit: Some(DEFAULT_PAGE_SIZE),
+            page: Some(DEFAULT_PAGE_NUM),
+            ..Default::default()
+        };
+
+        match admin_client
+            .get_journal_group_offset_list::<GroupOffsetListReq, Vec<GroupOffsetListRow>>(&request)
+            .await
+        {
+            Ok(page_data) => {
+                println!("group offset list result:");
+                // format table
+                let mut table = Table::new();
+                table.set_titles(row!["group_name", "namespace", "shard_name", "offset"]);
+                for offset in page_data.data {
+                    table.add_row(row![
+                        offset.group_name,
+                        offset.namespace,
+                        offset.shard_name,
+                        offset.offset
+                    ]);
+                }
+                // output cmd
+                table.printstd()
+            }
+            Err(e) => {
+                println!("Journal engine list group offset exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+}
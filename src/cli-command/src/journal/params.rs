@@ -0,0 +1,183 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::journal::command::JournalActionType;
+use admin_server::request::journal::{DeleteShardReq, SealSegmentReq, SegmentDetailReq};
+
+// ---- namespace ----
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "related operations of journal namespaces, such as listing", long_about = None)]
+#[command(next_line_help = true)]
+pub struct NamespaceArgs {
+    #[command(subcommand)]
+    pub action: NamespaceActionType,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum NamespaceActionType {
+    #[command(author = "RobustMQ", about = "action: list namespaces", long_about = None)]
+    List,
+}
+
+// ---- shard ----
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "related operations of journal shards, such as listing and deleting", long_about = None)]
+#[command(next_line_help = true)]
+pub struct ShardArgs {
+    #[command(subcommand)]
+    pub action: ShardActionType,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum ShardActionType {
+    #[command(author = "RobustMQ", about = "action: list shards", long_about = None)]
+    List(ListShardArgs),
+    #[command(author = "RobustMQ", about = "action: delete a shard and all its segments", long_about = None)]
+    Delete(DeleteShardArgs),
+}
+
+#[derive(clap::Args, Debug)]
+#[command(next_line_help = true)]
+pub struct ListShardArgs {
+    #[arg(short, long)]
+    pub namespace: Option<String>,
+    #[arg(short, long)]
+    pub shard_name: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(next_line_help = true)]
+pub struct DeleteShardArgs {
+    #[arg(short, long, required = true)]
+    pub namespace: String,
+    #[arg(short, long, required = true)]
+    pub shard_name: String,
+}
+
+// ---- segment ----
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "related operations of journal segments, such as listing, detail and sealing", long_about = None)]
+#[command(next_line_help = true)]
+pub struct SegmentArgs {
+    #[command(subcommand)]
+    pub action: SegmentActionType,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum SegmentActionType {
+    #[command(author = "RobustMQ", about = "action: list the segments of a shard", long_about = None)]
+    List(ListSegmentArgs),
+    #[command(author = "RobustMQ", about = "action: show the offsets and timestamps of a segment", long_about = None)]
+    Detail(SegmentDetailArgs),
+    #[command(author = "RobustMQ", about = "action: seal up a segment, the shard scrolls to the next segment", long_about = None)]
+    Seal(SealSegmentArgs),
+}
+
+#[derive(clap::Args, Debug)]
+#[command(next_line_help = true)]
+pub struct ListSegmentArgs {
+    #[arg(short, long, required = true)]
+    pub namespace: String,
+    #[arg(short, long, required = true)]
+    pub shard_name: String,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(next_line_help = true)]
+pub struct SegmentDetailArgs {
+    #[arg(short, long, required = true)]
+    pub namespace: String,
+    #[arg(short, long, required = true)]
+    pub shard_name: String,
+    #[arg(long, required = true)]
+    pub segment_no: u32,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(next_line_help = true)]
+pub struct SealSegmentArgs {
+    #[arg(short, long, required = true)]
+    pub namespace: String,
+    #[arg(short, long, required = true)]
+    pub shard_name: String,
+    // the active segment of the shard when not set
+    #[arg(long)]
+    pub segment_no: Option<u32>,
+}
+
+// ---- group ----
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "related operations of journal consumer groups, such as listing offsets", long_about = None)]
+#[command(next_line_help = true)]
+pub struct GroupArgs {
+    #[command(subcommand)]
+    pub action: GroupActionType,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum GroupActionType {
+    #[command(author = "RobustMQ", about = "action: list the committed offsets of a group", long_about = None)]
+    Offset(GroupOffsetArgs),
+}
+
+#[derive(clap::Args, Debug)]
+#[command(next_line_help = true)]
+pub struct GroupOffsetArgs {
+    #[arg(short, long, required = true)]
+    pub group_name: String,
+    #[arg(short, long)]
+    pub namespace: Option<String>,
+}
+
+pub fn process_namespace_args(args: NamespaceArgs) -> JournalActionType {
+    match args.action {
+        NamespaceActionType::List => JournalActionType::ListNamespace,
+    }
+}
+
+pub fn process_shard_args(args: ShardArgs) -> JournalActionType {
+    match args.action {
+        ShardActionType::List(arg) => JournalActionType::ListShard(arg.namespace, arg.shard_name),
+        ShardActionType::Delete(arg) => JournalActionType::DeleteShard(DeleteShardReq {
+            namespace: arg.namespace,
+            shard_name: arg.shard_name,
+        }),
+    }
+}
+
+pub fn process_segment_args(args: SegmentArgs) -> JournalActionType {
+    match args.action {
+        SegmentActionType::List(arg) => {
+            JournalActionType::ListSegment(arg.namespace, arg.shard_name)
+        }
+        SegmentActionType::Detail(arg) => JournalActionType::SegmentDetail(SegmentDetailReq {
+            namespace: arg.namespace,
+            shard_name: arg.shard_name,
+            segment_no: arg.segment_no,
+        }),
+        SegmentActionType::Seal(arg) => JournalActionType::SealSegment(SealSegmentReq {
+            namespace: arg.namespace,
+            shard_name: arg.shard_name,
+            segment_no: arg.segment_no,
+        }),
+    }
+}
+
+pub fn process_group_args(args: GroupArgs) -> JournalActionType {
+    match args.action {
+        GroupActionType::Offset(arg) => {
+            JournalActionType::ListGroupOffset(arg.group_name, arg.namespace)
+        }
+    }
+}
@@ -15,4 +15,5 @@
 #![allow(clippy::result_large_err)]
 pub mod cluster;
 pub mod handler;
+pub mod journal;
 pub mod mqtt;
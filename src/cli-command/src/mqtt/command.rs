@@ -15,14 +15,21 @@
 use crate::mqtt::pub_sub::{connect_server5, error_info};
 use crate::mqtt::pub_sub::{PublishArgsRequest, SubscribeArgsRequest};
 use admin_server::client::AdminHttpClient;
-use admin_server::response::mqtt::SessionListRow;
+use admin_server::response::mqtt::{
+    ClearSessionResp, ClientDetailResp, DelayMessageCancelResp, DelayMessageDetailResp,
+    DelayMessageListRow, DetailMetricsListRow, KickClientResp, RetainMessageListRow,
+    ScheduledPublishHistoryRow, ScheduledPublishListRow, SessionListRow,
+};
 use common_base::tools::unique_id;
 use paho_mqtt::{DisconnectOptionsBuilder, MessageBuilder, Properties, PropertyCode, ReasonCode};
 use prettytable::{row, Table};
+use std::time::Duration;
 
 // Default pagination constants
 const DEFAULT_PAGE_SIZE: u32 = 10000;
 const DEFAULT_PAGE_NUM: u32 = 1;
+// Extra time given to a trace request over the trace duration
+const TRACE_TIMEOUT_MARGIN_SEC: u64 = 30;
 use tokio::io::{self, AsyncBufReadExt, BufReader};
 use tokio::{select, signal};
 
@@ -36,9 +43,12 @@ pub struct MqttCliCommandParam {
 pub enum MqttActionType {
     // session
     ListSession,
+    ClearSession(admin_server::request::mqtt::ClearSessionReq),
 
     // subscribe
     ListSubscribe,
+    CreateSubscribe(admin_server::request::mqtt::CreateSubscribeReq),
+    DeleteSubscribe(admin_server::request::mqtt::DeleteSubscribeReq),
 
     // user admin
     ListUser,
@@ -57,6 +67,8 @@ pub enum MqttActionType {
 
     // client
     ListClient,
+    KickClient(admin_server::request::mqtt::KickClientReq),
+    InspectClient(admin_server::request::mqtt::ClientDetailReq),
 
     // #### observability ####
     // slow subscribe
@@ -79,6 +91,31 @@ pub enum MqttActionType {
     // Topic
     ListTopic,
 
+    // message
+    PublishMessage(admin_server::request::mqtt::PublishMessageReq),
+    ListRetainMessage(Option<String>),
+    DeleteRetainMessage(admin_server::request::mqtt::DeleteRetainMessageReq),
+
+    // delay message
+    ListDelayMessage(Option<String>),
+    DelayMessageDetail(admin_server::request::mqtt::DelayMessageDetailReq),
+    CancelDelayMessage(admin_server::request::mqtt::DelayMessageCancelReq),
+
+    // scheduled publish
+    ListScheduledPublish,
+    CreateScheduledPublish(admin_server::request::mqtt::CreateScheduledPublishReq),
+    DeleteScheduledPublish(admin_server::request::mqtt::DeleteScheduledPublishReq),
+    ScheduledPublishHistory(String),
+
+    // detail metrics
+    ListDetailMetricsTopic,
+    ListDetailMetricsClient,
+    GetDetailMetricsConfig,
+    SetDetailMetricsConfig(admin_server::request::mqtt::DetailMetricsConfigSetReq),
+
+    // packet trace
+    Trace(admin_server::request::mqtt::TraceReq),
+
     // flapping detect
     ListFlappingDetect,
 
@@ -121,17 +158,90 @@ pub async fn start(&self, params: MqttCliCommandParam) {
             MqttActionType::ListClient => {
                 self.list_clients(params.clone()).await;
             }
+            MqttActionType::KickClient(request) => {
+                self.kick_client(params_clone.clone(), request).await;
+            }
+            MqttActionType::InspectClient(request) => {
+                self.inspect_client(params_clone.clone(), request).await;
+            }
 
             // session
             MqttActionType::ListSession => {
                 self.list_session(params.clone()).await;
             }
+            MqttActionType::ClearSession(request) => {
+                self.clear_session(params_clone.clone(), request).await;
+            }
 
             // topic
             MqttActionType::ListTopic => {
                 self.list_topic(params.clone()).await;
             }
 
+            // message
+            MqttActionType::PublishMessage(request) => {
+                self.publish_message(params_clone.clone(), request).await;
+            }
+            MqttActionType::ListRetainMessage(topic_name) => {
+                self.list_retain_message(params_clone.clone(), topic_name)
+                    .await;
+            }
+            MqttActionType::DeleteRetainMessage(request) => {
+                self.delete_retain_message(params_clone.clone(), request)
+                    .await;
+            }
+
+            // delay message
+            MqttActionType::ListDelayMessage(topic_name) => {
+                self.list_delay_message(params_clone.clone(), topic_name)
+                    .await;
+            }
+            MqttActionType::DelayMessageDetail(request) => {
+                self.delay_message_detail(params_clone.clone(), request)
+                    .await;
+            }
+            MqttActionType::CancelDelayMessage(request) => {
+                self.cancel_delay_message(params_clone.clone(), request)
+                    .await;
+            }
+
+            // scheduled publish
+            MqttActionType::ListScheduledPublish => {
+                self.list_scheduled_publish(params_clone.clone()).await;
+            }
+            MqttActionType::CreateScheduledPublish(request) => {
+                self.create_scheduled_publish(params_clone.clone(), request)
+                    .await;
+            }
+            MqttActionType::DeleteScheduledPublish(request) => {
+                self.delete_scheduled_publish(params_clone.clone(), request)
+                    .await;
+            }
+            MqttActionType::ScheduledPublishHistory(name) => {
+                self.scheduled_publish_history(params_clone.clone(), name)
+                    .await;
+            }
+
+            // detail metrics
+            MqttActionType::ListDetailMetricsTopic => {
+                self.list_detail_metrics(params_clone.clone(), true).await;
+            }
+            MqttActionType::ListDetailMetricsClient => {
+                self.list_detail_metrics(params_clone.clone(), false).await;
+            }
+            MqttActionType::GetDetailMetricsConfig => {
+                self.get_detail_metrics_config(params_clone.clone()).await;
+            }
+            MqttActionType::SetDetailMetricsConfig(request) => {
+                self.set_detail_metrics_config(params_clone.clone(), request)
+                    .await;
+            }
+
+            // packet trace
+            MqttActionType::Trace(request) => {
+                self.trace(params_clone.clone(), request).await;
+            }
+
             // topic rewrite
             MqttActionType::ListTopicRewrite => {
                 self.list_topic_rewrite_rule(params.clone()).await;
@@ -151,6 +261,12 @@ pub async fn start(&self, params: MqttCliCommandParam) {
             MqttActionType::ListSubscribe => {
                 self.list_subscribe(params_clone.clone()).await;
             }
+            MqttActionType::CreateSubscribe(request) => {
+                self.create_subscribe(params_clone.clone(), request).await;
+            }
+            MqttActionType::DeleteSubscribe(request) => {
+                self.delete_subscribe(params_clone.clone(), request).await;
+            }
 
             //auto subscribe
             MqttActionType::ListAutoSubscribe => {
@@ -446,6 +562,184 @@ async fn list_session(&self, params: MqttCliCommandParam) {
         }
     }
 
+    async fn clear_session(
+        &self,
+        params: MqttCliCommandParam,
+        cli_request: admin_server::request::mqtt::ClearSessionReq,
+    ) {
+        // Create admin HTTP client
+        let admin_client = AdminHttpClient::new(format!("http://{}", params.server));
+
+        match admin_client
+            .clear_session::<admin_server::request::mqtt::ClearSessionReq, ClearSessionResp>(
+                &cli_request,
+            )
+            .await
+        {
+            Ok(resp) => {
+                if resp.was_connected {
+                    println!(
+                        "Session cleared, client {} was disconnected",
+                        resp.client_id
+                    );
+                } else {
+                    println!("Session cleared");
+                }
+            }
+            Err(e) => {
+                println!("MQTT broker clear session normal exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    // ------------ client admin ------------
+    async fn kick_client(
+        &self,
+        params: MqttCliCommandParam,
+        cli_request: admin_server::request::mqtt::KickClientReq,
+    ) {
+        // Create admin HTTP client
+        let admin_client = AdminHttpClient::new(format!("http://{}", params.server));
+
+        match admin_client
+            .kick_client::<admin_server::request::mqtt::KickClientReq, KickClientResp>(&cli_request)
+            .await
+        {
+            Ok(resp) => {
+                println!(
+                    "Client {} (connection {}) was disconnected from broker {}",
+                    resp.client_id, resp.connect_id, resp.broker_id
+                );
+            }
+            Err(e) => {
+                println!("MQTT broker kick client normal exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn inspect_client(
+        &self,
+        params: MqttCliCommandParam,
+        cli_request: admin_server::request::mqtt::ClientDetailReq,
+    ) {
+        // Create admin HTTP client
+        let admin_client = AdminHttpClient::new(format!("http://{}", params.server));
+
+        let detail = match admin_client
+            .get_client_detail::<admin_server::request::mqtt::ClientDetailReq, ClientDetailResp>(
+                &cli_request,
+            )
+            .await
+        {
+            Ok(detail) => detail,
+            Err(e) => {
+                println!("MQTT broker inspect client normal exception");
+                error_info(e.to_string());
+                return;
+            }
+        };
+
+        let mut table = Table::new();
+        table.set_titles(row![
+            "client_id",
+            "connected",
+            "connect_id",
+            "broker_id",
+            "source_addr",
+            "protocol",
+            "keep_alive",
+            "last_heartbeat_time",
+            "keep_alive_deadline",
+            "receive_maximum"
+        ]);
+        table.add_row(row![
+            detail.client_id,
+            detail.connected,
+            detail.connect_id,
+            detail.broker_id,
+            detail.source_addr,
+            detail.protocol,
+            detail.keep_alive,
+            detail.last_heartbeat_time,
+            detail.keep_alive_deadline,
+            detail.receive_maximum,
+        ]);
+        table.printstd();
+
+        println!("inflight messages:");
+        let mut table = Table::new();
+        table.set_titles(row!["pkid", "direction", "create_time"]);
+        for message in detail.inflight_messages {
+            table.add_row(row![message.pkid, message.direction, message.create_time]);
+        }
+        table.printstd();
+
+        println!("queued subscriptions:");
+        let mut table = Table::new();
+        table.set_titles(row![
+            "sub_path",
+            "topic_name",
+            "committed_offset",
+            "pending_messages",
+            "push_success_record_num",
+            "push_error_record_num",
+            "last_push_time"
+        ]);
+        for queued in detail.queued_subscriptions {
+            table.add_row(row![
+                queued.sub_path,
+                queued.topic_name,
+                queued.committed_offset,
+                queued.pending_messages,
+                queued.push_success_record_num,
+                queued.push_error_record_num,
+                queued.last_push_time,
+            ]);
+        }
+        table.printstd();
+    }
+
+    // ------------ subscribe admin ------------
+    async fn create_subscribe(
+        &self,
+        params: MqttCliCommandParam,
+        cli_request: admin_server::request::mqtt::CreateSubscribeReq,
+    ) {
+        // Create admin HTTP client
+        let admin_client = AdminHttpClient::new(format!("http://{}", params.server));
+
+        match admin_client.create_subscribe(&cli_request).await {
+            Ok(_) => {
+                println!("Created successfully!")
+            }
+            Err(e) => {
+                println!("MQTT broker create subscribe normal exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn delete_subscribe(
+        &self,
+        params: MqttCliCommandParam,
+        cli_request: admin_server::request::mqtt::DeleteSubscribeReq,
+    ) {
+        // Create admin HTTP client
+        let admin_client = AdminHttpClient::new(format!("http://{}", params.server));
+
+        match admin_client.delete_subscribe(&cli_request).await {
+            Ok(_) => {
+                println!("Deleted successfully!");
+            }
+            Err(e) => {
+                println!("MQTT broker delete subscribe normal exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
     // ------------ user admin ------------
     async fn create_user(
         &self,
@@ -862,6 +1156,491 @@ async fn list_slow_subscribe(&self, params: MqttCliCommandParam) {
         }
     }
 
+    async fn publish_message(
+        &self,
+        params: MqttCliCommandParam,
+        cli_request: admin_server::request::mqtt::PublishMessageReq,
+    ) {
+        // Create admin HTTP client
+        let admin_client = AdminHttpClient::new(format!("http://{}", params.server));
+
+        match admin_client.publish_message(&cli_request).await {
+            Ok(_) => {
+                println!("Published successfully!")
+            }
+            Err(e) => {
+                println!("MQTT broker publish message exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn list_retain_message(&self, params: MqttCliCommandParam, topic_name: Option<String>) {
+        // Create admin HTTP client
+        let admin_client = AdminHttpClient::new(format!("http://{}", params.server));
+
+        let request = admin_server::request::mqtt::RetainMessageListReq {
+            topic_name,
+            limit: Some(DEFAULT_PAGE_SIZE),
+            page: Some(DEFAULT_PAGE_NUM),
+            sort_field: None,
+            sort_by: None,
+            filter_field: None,
+            filter_values: None,
+            exact_match: None,
+        };
+
+        match admin_client
+            .get_retain_message_list::<admin_server::request::mqtt::RetainMessageListReq, Vec<RetainMessageListRow>>(
+                &request,
+            )
+            .await
+        {
+            Ok(page_data) => {
+                println!("retained message list result:");
+                // format table
+                let mut table = Table::new();
+                table.set_titles(row![
+                    "topic_name",
+                    "client_id",
+                    "qos",
+                    "payload",
+                    "payload_size",
+                    "expired_at",
+                ]);
+                for message in page_data.data {
+                    table.add_row(row![
+                        message.topic_name,
+                        message.client_id,
+                        message.qos,
+                        message.payload,
+                        message.payload_size,
+                        message.expired_at
+                    ]);
+                }
+                // output cmd
+                table.printstd()
+            }
+            Err(e) => {
+                println!("MQTT broker list retained message exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn delete_retain_message(
+        &self,
+        params: MqttCliCommandParam,
+        cli_request: admin_server::request::mqtt::DeleteRetainMessageReq,
+    ) {
+        // Create admin HTTP client
+        let admin_client = AdminHttpClient::new(format!("http://{}", params.server));
+
+        match admin_client.delete_retain_message(&cli_request).await {
+            Ok(_) => {
+                println!("Deleted successfully!")
+            }
+            Err(e) => {
+                println!("MQTT broker delete retained message exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn list_delay_message(&self, params: MqttCliCommandParam, topic_name: Option<String>) {
+        // Create admin HTTP client
+        let admin_client = AdminHttpClient::new(format!("http://{}", params.server));
+
+        let request = admin_server::request::mqtt::DelayMessageListReq {
+            topic_name,
+            limit: Some(DEFAULT_PAGE_SIZE),
+            page: Some(DEFAULT_PAGE_NUM),
+            sort_field: Some("delay_timestamp".to_string()),
+            sort_by: Some("asc".to_string()),
+            filter_field: None,
+            filter_values: None,
+            exact_match: None,
+        };
+
+        match admin_client
+            .get_delay_message_list::<admin_server::request::mqtt::DelayMessageListReq, Vec<DelayMessageListRow>>(
+                &request,
+            )
+            .await
+        {
+            Ok(page_data) => {
+                println!("delay message list result:");
+                // format table
+                let mut table = Table::new();
+                table.set_titles(row!["id", "topic_name", "delay_timestamp", "create_time"]);
+                for message in page_data.data {
+                    table.add_row(row![
+                        message.id,
+                        message.topic_name,
+                        message.delay_timestamp,
+                        message.create_time
+                    ]);
+                }
+                // output cmd
+                table.printstd()
+            }
+            Err(e) => {
+                println!("MQTT broker list delay message exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn delay_message_detail(
+        &self,
+        params: MqttCliCommandParam,
+        cli_request: admin_server::request::mqtt::DelayMessageDetailReq,
+    ) {
+        // Create admin HTTP client
+        let admin_client = AdminHttpClient::new(format!("http://{}", params.server));
+
+        match admin_client
+            .get_delay_message_detail::<admin_server::request::mqtt::DelayMessageDetailReq, DelayMessageDetailResp>(
+                &cli_request,
+            )
+            .await
+        {
+            Ok(detail) => {
+                println!("delay message detail:");
+                let mut table = Table::new();
+                table.set_titles(row!["field", "value"]);
+                table.add_row(row!["id", detail.id]);
+                table.add_row(row!["topic_name", detail.topic_name]);
+                table.add_row(row!["status", detail.status]);
+                table.add_row(row!["delay_timestamp", detail.delay_timestamp]);
+                table.add_row(row!["create_time", detail.create_time]);
+                table.add_row(row!["client_id", detail.client_id.unwrap_or_default()]);
+                table.add_row(row![
+                    "qos",
+                    detail.qos.map(|qos| qos.to_string()).unwrap_or_default()
+                ]);
+                table.add_row(row!["payload", detail.payload.unwrap_or_default()]);
+                table.add_row(row![
+                    "payload_size",
+                    detail
+                        .payload_size
+                        .map(|size| size.to_string())
+                        .unwrap_or_default()
+                ]);
+                table.printstd()
+            }
+            Err(e) => {
+                println!("MQTT broker get delay message detail exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn cancel_delay_message(
+        &self,
+        params: MqttCliCommandParam,
+        cli_request: admin_server::request::mqtt::DelayMessageCancelReq,
+    ) {
+        // Create admin HTTP client
+        let admin_client = AdminHttpClient::new(format!("http://{}", params.server));
+
+        match admin_client
+            .cancel_delay_message::<admin_server::request::mqtt::DelayMessageCancelReq, DelayMessageCancelResp>(
+                &cli_request,
+            )
+            .await
+        {
+            Ok(data) => {
+                println!("Cancelled {} delay message(s)", data.cancelled_num)
+            }
+            Err(e) => {
+                println!("MQTT broker cancel delay message exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn list_scheduled_publish(&self, params: MqttCliCommandParam) {
+        // Create admin HTTP client
+        let admin_client = AdminHttpClient::new(format!("http://{}", params.server));
+
+        let request = admin_server::request::mqtt::ScheduledPublishListReq {
+            limit: Some(DEFAULT_PAGE_SIZE),
+            page: Some(DEFAULT_PAGE_NUM),
+            sort_field: Some("name".to_string()),
+            sort_by: Some("asc".to_string()),
+            filter_field: None,
+            filter_values: None,
+            exact_match: None,
+        };
+
+        match admin_client
+            .get_scheduled_publish_list::<admin_server::request::mqtt::ScheduledPublishListReq, Vec<ScheduledPublishListRow>>(
+                &request,
+            )
+            .await
+        {
+            Ok(page_data) => {
+                println!("scheduled publish list result:");
+                // format table
+                let mut table = Table::new();
+                table.set_titles(row![
+                    "name",
+                    "topic_name",
+                    "qos",
+                    "retain",
+                    "schedule",
+                    "timezone",
+                    "end_time",
+                    "next_fire_time"
+                ]);
+                for schedule in page_data.data {
+                    table.add_row(row![
+                        schedule.name,
+                        schedule.topic_name,
+                        schedule.qos,
+                        schedule.retain,
+                        schedule.schedule,
+                        schedule.timezone,
+                        schedule
+                            .end_time
+                            .map(|time| time.to_string())
+                            .unwrap_or_default(),
+                        schedule
+                            .next_fire_time
+                            .map(|time| time.to_string())
+                            .unwrap_or_default()
+                    ]);
+                }
+                // output cmd
+                table.printstd()
+            }
+            Err(e) => {
+                println!("MQTT broker list scheduled publish exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn create_scheduled_publish(
+        &self,
+        params: MqttCliCommandParam,
+        cli_request: admin_server::request::mqtt::CreateScheduledPublishReq,
+    ) {
+        // Create admin HTTP client
+        let admin_client = AdminHttpClient::new(format!("http://{}", params.server));
+
+        match admin_client.create_scheduled_publish(&cli_request).await {
+            Ok(_) => {
+                println!("Created successfully!")
+            }
+            Err(e) => {
+                println!("MQTT broker create scheduled publish exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn delete_scheduled_publish(
+        &self,
+        params: MqttCliCommandParam,
+        cli_request: admin_server::request::mqtt::DeleteScheduledPublishReq,
+    ) {
+        // Create admin HTTP client
+        let admin_client = AdminHttpClient::new(format!("http://{}", params.server));
+
+        match admin_client.delete_scheduled_publish(&cli_request).await {
+            Ok(_) => {
+                println!("Deleted successfully!")
+            }
+            Err(e) => {
+                println!("MQTT broker delete scheduled publish exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn scheduled_publish_history(&self, params: MqttCliCommandParam, name: String) {
+        // Create admin HTTP client
+        let admin_client = AdminHttpClient::new(format!("http://{}", params.server));
+
+        let request = admin_server::request::mqtt::ScheduledPublishHistoryReq {
+            name,
+            limit: Some(DEFAULT_PAGE_SIZE),
+            page: Some(DEFAULT_PAGE_NUM),
+            sort_field: None,
+            sort_by: None,
+            filter_field: None,
+            filter_values: None,
+            exact_match: None,
+        };
+
+        match admin_client
+            .get_scheduled_publish_history::<admin_server::request::mqtt::ScheduledPublishHistoryReq, Vec<ScheduledPublishHistoryRow>>(
+                &request,
+            )
+            .await
+        {
+            Ok(page_data) => {
+                println!("scheduled publish history result:");
+                // format table
+                let mut table = Table::new();
+                table.set_titles(row!["fire_time", "publish_time", "success", "error"]);
+                for fire in page_data.data {
+                    table.add_row(row![
+                        fire.fire_time,
+                        fire.publish_time,
+                        fire.success,
+                        fire.error.unwrap_or_default()
+                    ]);
+                }
+                // output cmd
+                table.printstd()
+            }
+            Err(e) => {
+                println!("MQTT broker get scheduled publish history exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn list_detail_metrics(&self, params: MqttCliCommandParam, topic: bool) {
+        // Create admin HTTP client
+        let admin_client = AdminHttpClient::new(format!("http://{}", params.server));
+
+        let request = admin_server::request::mqtt::DetailMetricsListReq {
+            limit: Some(DEFAULT_PAGE_SIZE),
+            page: Some(DEFAULT_PAGE_NUM),
+            sort_field: Some("messages_in".to_string()),
+            sort_by: Some("desc".to_string()),
+            filter_field: None,
+            filter_values: None,
+            exact_match: None,
+        };
+
+        let result = if topic {
+            admin_client
+                .get_detail_metrics_topic_list::<admin_server::request::mqtt::DetailMetricsListReq, Vec<DetailMetricsListRow>>(
+                    &request,
+                )
+                .await
+        } else {
+            admin_client
+                .get_detail_metrics_client_list::<admin_server::request::mqtt::DetailMetricsListReq, Vec<DetailMetricsListRow>>(
+                    &request,
+                )
+                .await
+        };
+
+        match result {
+            Ok(page_data) => {
+                println!("detail metrics list result:");
+                // format table
+                let mut table = Table::new();
+                table.set_titles(row![
+                    if topic { "topic" } else { "client_id" },
+                    "messages_in",
+                    "messages_out",
+                    "bytes_in",
+                    "bytes_out",
+                    "dropped",
+                    "inflight",
+                    "avg_latency_ms",
+                    "max_latency_ms",
+                    "last_active"
+                ]);
+                for metrics in page_data.data {
+                    table.add_row(row![
+                        metrics.name,
+                        metrics.messages_in,
+                        metrics.messages_out,
+                        metrics.bytes_in,
+                        metrics.bytes_out,
+                        metrics.dropped,
+                        metrics.inflight,
+                        metrics.avg_latency_ms,
+                        metrics.max_latency_ms,
+                        metrics.last_active
+                    ]);
+                }
+                // output cmd
+                table.printstd()
+            }
+            Err(e) => {
+                println!("MQTT broker list detail metrics exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn get_detail_metrics_config(&self, params: MqttCliCommandParam) {
+        // Create admin HTTP client
+        let admin_client = AdminHttpClient::new(format!("http://{}", params.server));
+
+        let request = admin_server::request::mqtt::DetailMetricsConfigGetReq {};
+        match admin_client
+            .get_detail_metrics_config::<admin_server::request::mqtt::DetailMetricsConfigGetReq, serde_json::Value>(
+                &request,
+            )
+            .await
+        {
+            Ok(config) => {
+                println!("detail metrics config:");
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&config).unwrap_or_default()
+                );
+            }
+            Err(e) => {
+                println!("MQTT broker get detail metrics config exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn set_detail_metrics_config(
+        &self,
+        params: MqttCliCommandParam,
+        cli_request: admin_server::request::mqtt::DetailMetricsConfigSetReq,
+    ) {
+        // Create admin HTTP client
+        let admin_client = AdminHttpClient::new(format!("http://{}", params.server));
+
+        match admin_client.set_detail_metrics_config(&cli_request).await {
+            Ok(_) => {
+                println!("Updated successfully!")
+            }
+            Err(e) => {
+                println!("MQTT broker set detail metrics config exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn trace(
+        &self,
+        params: MqttCliCommandParam,
+        cli_request: admin_server::request::mqtt::TraceReq,
+    ) {
+        // Create admin HTTP client
+        let admin_client = AdminHttpClient::new(format!("http://{}", params.server));
+        let timeout = Duration::from_secs(cli_request.duration_sec + TRACE_TIMEOUT_MARGIN_SEC);
+
+        println!("Tracing for {} seconds...", cli_request.duration_sec);
+        let result = admin_client
+            .trace(&cli_request, timeout, |event, data| match event {
+                "lagged" => println!("... {data} events dropped, the trace is too busy"),
+                "end" => println!("{data}"),
+                _ => print_trace_event(data),
+            })
+            .await;
+
+        if let Err(e) = result {
+            println!("MQTT broker trace exception");
+            error_info(e.to_string());
+        }
+    }
+
     async fn list_topic(&self, params: MqttCliCommandParam) {
         // Create admin HTTP client
         let admin_client = AdminHttpClient::new(format!("http://{}", params.server));
@@ -1051,8 +1830,16 @@ async fn list_connectors(&self, params: MqttCliCommandParam) {
                     "connector type",
                     "connector config",
                     "topic id",
+                    "topic filters",
                     "status",
                     "broker id",
+                    "committed offsets",
+                    "lag",
+                    "success",
+                    "failure",
+                    "dead letter",
+                    "discard",
+                    "last error",
                     "create time",
                     "update time",
                 ]);
@@ -1063,8 +1850,21 @@ async fn list_connectors(&self, params: MqttCliCommandParam) {
                         connector.connector_type,
                         connector.config,
                         connector.topic_id,
+                        connector.topic_filters.join(","),
                         connector.status,
                         connector.broker_id,
+                        connector
+                            .committed_offsets
+                            .iter()
+                            .map(|(topic_id, offset)| format!("{topic_id}:{offset}"))
+                            .collect::<Vec<_>>()
+                            .join(","),
+                        connector.lag,
+                        connector.success_total,
+                        connector.failure_total,
+                        connector.dead_letter_total,
+                        connector.discard_total,
+                        connector.last_error,
                         connector.create_time,
                         connector.update_time
                     ]);
@@ -1457,3 +2257,37 @@ async fn list_auto_subscribe_rule(&self, params: MqttCliCommandParam) {
         }
     }
 }
+
+// Prints one packet trace event as a single line.
+fn print_trace_event(data: &str) {
+    let Ok(event) = serde_json::from_str::<serde_json::Value>(data) else {
+        println!("{data}");
+        return;
+    };
+
+    let field = |name: &str| match &event[name] {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(value) => value.clone(),
+        value => value.to_string(),
+    };
+    let mut line = format!(
+        "{} {} {} {} connect_id={}",
+        field("time"),
+        field("direction"),
+        field("client_id"),
+        field("packet_type"),
+        field("connect_id")
+    );
+    for name in ["topic", "qos", "retain", "pkid", "payload_size", "payload"] {
+        let value = field(name);
+        if !value.is_empty() {
+            line.push_str(&format!(" {name}={value}"));
+        }
+    }
+    if let Some(filters) = event["filters"].as_array() {
+        if !filters.is_empty() {
+            line.push_str(&format!(" filters={}", event["filters"]));
+        }
+    }
+    println!("{line}");
+}
@@ -20,6 +20,7 @@
 
 use common_base::enum_type::mqtt::acl::mqtt_acl_blacklist_type::MqttAclBlackListType;
 use core::option::Option::Some;
+use metadata_struct::mqtt::bridge::connector::ConnectorDeliveryPolicy;
 
 use crate::mqtt::command::MqttActionType;
 use crate::mqtt::pub_sub::{PublishArgsRequest, SubscribeArgsRequest};
@@ -38,6 +39,15 @@ pub struct SessionArgs {
 pub enum SessionActionType {
     #[command(author = "RobustMQ", about = "action: list sessions", long_about = None)]
     List,
+    #[command(author = "RobustMQ", about = "action: clear the session of a client", long_about = None)]
+    Clear(ClearSessionArgs),
+}
+
+#[derive(clap::Args, Debug)]
+#[command(next_line_help = true)]
+pub struct ClearSessionArgs {
+    #[arg(short, long, required = true)]
+    pub client_id: String,
 }
 
 // subscribe
@@ -54,6 +64,36 @@ pub struct SubscribesArgs {
 pub enum SubscribesActionType {
     #[command(author = "RobustMQ", about = "action: list subscriptions", long_about = None)]
     List,
+    #[command(author = "RobustMQ", about = "action: subscribe on behalf of a client", long_about = None)]
+    Add(AddSubscribeArgs),
+    #[command(author = "RobustMQ", about = "action: unsubscribe on behalf of a client", long_about = None)]
+    Remove(RemoveSubscribeArgs),
+}
+
+#[derive(clap::Args, Debug)]
+#[command(next_line_help = true)]
+pub struct AddSubscribeArgs {
+    #[arg(short, long, required = true)]
+    pub client_id: String,
+    #[arg(short, long, required = true)]
+    pub path: String,
+    #[arg(short, long, default_value_t = 0)]
+    pub qos: u32,
+    #[arg(long, default_value_t = false)]
+    pub no_local: bool,
+    #[arg(long, default_value_t = false)]
+    pub retain_as_published: bool,
+    #[arg(long, default_value_t = 0)]
+    pub retained_handling: u32,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(next_line_help = true)]
+pub struct RemoveSubscribeArgs {
+    #[arg(short, long, required = true)]
+    pub client_id: String,
+    #[arg(short, long, required = true)]
+    pub path: String,
 }
 
 // connection
@@ -70,6 +110,29 @@ pub struct ClientsArgs {
 pub enum ClientsActionType {
     #[command(author = "RobustMQ", about = "action: list clients", long_about = None)]
     List,
+    #[command(author = "RobustMQ", about = "action: disconnect a client", long_about = None)]
+    Kick(KickClientArgs),
+    #[command(author = "RobustMQ", about = "action: show keep-alive state, inflight and queued messages of a client", long_about = None)]
+    Inspect(InspectClientArgs),
+}
+
+#[derive(clap::Args, Debug)]
+#[command(next_line_help = true)]
+pub struct KickClientArgs {
+    #[arg(short, long, required = true)]
+    pub client_id: String,
+    // MQTT 5 DISCONNECT reason code, 0x98 (Administrative action) when not set
+    #[arg(long)]
+    pub reason_code: Option<u8>,
+    #[arg(long)]
+    pub reason_string: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(next_line_help = true)]
+pub struct InspectClientArgs {
+    #[arg(short, long, required = true)]
+    pub client_id: String,
 }
 
 // cluster config
@@ -88,6 +151,43 @@ pub enum ClusterConfigActionType {
     Get,
 }
 
+// cluster node drain
+#[derive(clap::Args, Debug)]
+#[command(next_line_help = true)]
+pub struct ClusterDrainArgs {
+    #[arg(short, long, required = true)]
+    pub broker_id: u64,
+    // connections disconnected per second, the broker default when not set
+    #[arg(long)]
+    pub disconnect_rate: Option<u32>,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(next_line_help = true)]
+pub struct ClusterDrainCancelArgs {
+    #[arg(short, long, required = true)]
+    pub broker_id: u64,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(next_line_help = true)]
+pub struct ClusterDrainStatusArgs {
+    // every broker when not set
+    #[arg(short, long)]
+    pub broker_id: Option<u64>,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(next_line_help = true)]
+pub struct ClusterRebalanceArgs {
+    // connections disconnected per second on every broker, the broker default when not set
+    #[arg(long)]
+    pub disconnect_rate: Option<u32>,
+    // only print the plan, no connection is moved
+    #[arg(long, default_value_t = false)]
+    pub dry_run: bool,
+}
+
 // user
 #[derive(clap::Args, Debug)]
 #[command(author = "RobustMQ", about = "related operations of mqtt users, such as listing, creating, and deleting", long_about = None
@@ -303,6 +403,264 @@ pub enum TopicActionType {
     List,
 }
 
+// message
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "related operations of messages, such as publishing through the admin api", long_about = None
+)]
+#[command(next_line_help = true)]
+pub struct MessageArgs {
+    #[command(subcommand)]
+    pub action: MessageActionType,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum MessageActionType {
+    #[command(author = "RobustMQ", about = "action: publish a message to a topic", long_about = None)]
+    Publish(PublishMessageArgs),
+}
+
+#[derive(clap::Args, Debug)]
+#[command(next_line_help = true)]
+pub struct PublishMessageArgs {
+    #[arg(short, long, required = true)]
+    pub topic: String,
+    #[arg(short, long, required = true)]
+    pub payload: String,
+    #[arg(short, long, default_value_t = 0)]
+    pub qos: u8,
+    #[arg(short, long, default_value_t = false)]
+    pub retain: bool,
+    #[arg(long)]
+    pub content_type: Option<String>,
+    // seconds
+    #[arg(long)]
+    pub message_expiry_interval: Option<u32>,
+    #[arg(long)]
+    pub response_topic: Option<String>,
+    #[arg(long)]
+    pub correlation_data: Option<String>,
+    // key=value, can be repeated
+    #[arg(long = "user-property", value_parser = parse_user_property)]
+    pub user_properties: Vec<(String, String)>,
+}
+
+fn parse_user_property(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("invalid user property {value}, expected key=value")),
+    }
+}
+
+// retained message
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "related operations of retained messages, such as listing and deleting", long_about = None
+)]
+#[command(next_line_help = true)]
+pub struct RetainMessageArgs {
+    #[command(subcommand)]
+    pub action: RetainMessageActionType,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum RetainMessageActionType {
+    #[command(author = "RobustMQ", about = "action: list retained messages", long_about = None)]
+    List(ListRetainMessageArgs),
+    #[command(author = "RobustMQ", about = "action: delete the retained message of a topic", long_about = None)]
+    Delete(DeleteRetainMessageArgs),
+}
+
+#[derive(clap::Args, Debug)]
+#[command(next_line_help = true)]
+pub struct ListRetainMessageArgs {
+    #[arg(short, long)]
+    pub topic_name: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(next_line_help = true)]
+pub struct DeleteRetainMessageArgs {
+    #[arg(short, long, required = true)]
+    pub topic_name: String,
+}
+
+// delay message
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "related operations of delay messages, such as listing, inspecting and cancelling", long_about = None
+)]
+#[command(next_line_help = true)]
+pub struct DelayMessageArgs {
+    #[command(subcommand)]
+    pub action: DelayMessageActionType,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum DelayMessageActionType {
+    #[command(author = "RobustMQ", about = "action: list pending delay messages", long_about = None)]
+    List(ListDelayMessageArgs),
+    #[command(author = "RobustMQ", about = "action: show the detail of a delay message", long_about = None)]
+    Detail(DelayMessageDetailArgs),
+    #[command(author = "RobustMQ", about = "action: cancel a delay message, or every pending delay message of a topic", long_about = None)]
+    Cancel(CancelDelayMessageArgs),
+}
+
+#[derive(clap::Args, Debug)]
+#[command(next_line_help = true)]
+pub struct ListDelayMessageArgs {
+    #[arg(short, long)]
+    pub topic_name: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(next_line_help = true)]
+pub struct DelayMessageDetailArgs {
+    #[arg(short, long, required = true)]
+    pub id: String,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(next_line_help = true)]
+pub struct CancelDelayMessageArgs {
+    #[arg(
+        short,
+        long,
+        conflicts_with = "topic_name",
+        required_unless_present = "topic_name"
+    )]
+    pub id: Option<String>,
+    #[arg(short, long)]
+    pub topic_name: Option<String>,
+}
+
+// scheduled publish
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "related operations of scheduled publishes, such as listing, creating, deleting and showing the firing history", long_about = None
+)]
+#[command(next_line_help = true)]
+pub struct ScheduledPublishArgs {
+    #[command(subcommand)]
+    pub action: ScheduledPublishActionType,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum ScheduledPublishActionType {
+    #[command(author = "RobustMQ", about = "action: list scheduled publishes", long_about = None)]
+    List,
+    #[command(author = "RobustMQ", about = "action: create a scheduled publish from a cron expression or a fixed interval", long_about = None)]
+    Create(CreateScheduledPublishArgs),
+    #[command(author = "RobustMQ", about = "action: delete a scheduled publish", long_about = None)]
+    Delete(DeleteScheduledPublishArgs),
+    #[command(author = "RobustMQ", about = "action: show the recorded firings of a scheduled publish", long_about = None)]
+    History(ScheduledPublishHistoryArgs),
+}
+
+#[derive(clap::Args, Debug)]
+#[command(next_line_help = true)]
+pub struct CreateScheduledPublishArgs {
+    #[arg(short, long, required = true)]
+    pub name: String,
+    #[arg(short, long, required = true)]
+    pub topic_name: String,
+    #[arg(short, long, required = true)]
+    pub payload: String,
+    #[arg(short, long, default_value_t = 0)]
+    pub qos: u8,
+    #[arg(short, long, default_value_t = false)]
+    pub retain: bool,
+    // five field cron expression, such as "*/5 * * * *"
+    #[arg(
+        short,
+        long,
+        conflicts_with = "interval_sec",
+        required_unless_present = "interval_sec"
+    )]
+    pub cron: Option<String>,
+    #[arg(short, long)]
+    pub interval_sec: Option<u64>,
+    // IANA name or UTC offset, defaults to UTC
+    #[arg(long)]
+    pub timezone: Option<String>,
+    // seconds
+    #[arg(long)]
+    pub end_time: Option<u64>,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(next_line_help = true)]
+pub struct DeleteScheduledPublishArgs {
+    #[arg(short, long, required = true)]
+    pub name: String,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(next_line_help = true)]
+pub struct ScheduledPublishHistoryArgs {
+    #[arg(short, long, required = true)]
+    pub name: String,
+}
+
+// detail metrics
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "related operations of per-topic and per-client metrics, such as listing and changing the config", long_about = None
+)]
+#[command(next_line_help = true)]
+pub struct DetailMetricsArgs {
+    #[command(subcommand)]
+    pub action: DetailMetricsActionType,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum DetailMetricsActionType {
+    #[command(author = "RobustMQ", about = "action: list the metrics of the tracked topics", long_about = None)]
+    TopicList,
+    #[command(author = "RobustMQ", about = "action: list the metrics of the tracked clients", long_about = None)]
+    ClientList,
+    #[command(author = "RobustMQ", about = "action: show the detail metrics config", long_about = None)]
+    GetConfig,
+    #[command(author = "RobustMQ", about = "action: change the detail metrics config, options left out keep their value", long_about = None)]
+    SetConfig(SetDetailMetricsConfigArgs),
+}
+
+#[derive(clap::Args, Debug)]
+#[command(next_line_help = true)]
+pub struct SetDetailMetricsConfigArgs {
+    #[arg(long)]
+    pub enable: Option<bool>,
+    // comma separated topic filters, wildcards are allowed
+    #[arg(long, value_delimiter = ',')]
+    pub topic_filters: Option<Vec<String>>,
+    // comma separated client ids, a trailing * matches a prefix
+    #[arg(long, value_delimiter = ',')]
+    pub client_id_filters: Option<Vec<String>>,
+    #[arg(long)]
+    pub max_topics: Option<usize>,
+    #[arg(long)]
+    pub max_clients: Option<usize>,
+    // lru or top_n
+    #[arg(long)]
+    pub eviction: Option<String>,
+}
+
+// trace
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "print the packets of a client or a topic handled by the broker for a limited time", long_about = None
+)]
+#[command(next_line_help = true)]
+pub struct TraceArgs {
+    #[arg(
+        short,
+        long,
+        conflicts_with = "topic",
+        required_unless_present = "topic"
+    )]
+    pub client_id: Option<String>,
+    // topic filter, wildcards are allowed
+    #[arg(short, long)]
+    pub topic: Option<String>,
+    // at most 600 seconds
+    #[arg(short, long, default_value_t = 60)]
+    pub duration_sec: u64,
+}
+
 // ---- system alarm ----
 #[derive(clap::Args, Debug)]
 #[command(author = "RobustMQ", about = "related operations of system alarm, such as setting and listing", long_about = None
@@ -389,8 +747,14 @@ pub struct CreateConnectorArgs {
     pub connector_type: String,
     #[arg(short, long, required = true)]
     pub config: String,
-    #[arg(short, long, required = true)]
-    pub topic_id: String,
+    #[arg(short, long)]
+    pub topic_id: Option<String>,
+    #[arg(long = "topic-filter")]
+    pub topic_filters: Vec<String>,
+    #[arg(long)]
+    pub max_retries: Option<u32>,
+    #[arg(long)]
+    pub dead_letter_topic: Option<String>,
 }
 
 #[derive(clap::Args, Debug)]
@@ -512,12 +876,33 @@ pub fn process_system_alarm_args(args: SystemAlarmArgs) -> MqttActionType {
 pub fn process_session_args(args: SessionArgs) -> MqttActionType {
     match args.action {
         SessionActionType::List => MqttActionType::ListSession,
+        SessionActionType::Clear(arg) => {
+            MqttActionType::ClearSession(admin_server::request::mqtt::ClearSessionReq {
+                client_id: arg.client_id,
+            })
+        }
     }
 }
 
 pub fn process_subscribes_args(args: SubscribesArgs) -> MqttActionType {
     match args.action {
         SubscribesActionType::List => MqttActionType::ListSubscribe,
+        SubscribesActionType::Add(arg) => {
+            MqttActionType::CreateSubscribe(admin_server::request::mqtt::CreateSubscribeReq {
+                client_id: arg.client_id,
+                path: arg.path,
+                qos: arg.qos,
+                no_local: arg.no_local,
+                retain_as_published: arg.retain_as_published,
+                retained_handling: arg.retained_handling,
+            })
+        }
+        SubscribesActionType::Remove(arg) => {
+            MqttActionType::DeleteSubscribe(admin_server::request::mqtt::DeleteSubscribeReq {
+                client_id: arg.client_id,
+                path: arg.path,
+            })
+        }
     }
 }
 
@@ -590,6 +975,18 @@ pub fn process_blacklist_args(
 pub fn process_connection_args(args: ClientsArgs) -> MqttActionType {
     match args.action {
         ClientsActionType::List => MqttActionType::ListClient,
+        ClientsActionType::Kick(arg) => {
+            MqttActionType::KickClient(admin_server::request::mqtt::KickClientReq {
+                client_id: arg.client_id,
+                reason_code: arg.reason_code,
+                reason_string: arg.reason_string,
+            })
+        }
+        ClientsActionType::Inspect(arg) => {
+            MqttActionType::InspectClient(admin_server::request::mqtt::ClientDetailReq {
+                client_id: arg.client_id,
+            })
+        }
     }
 }
 
@@ -599,6 +996,104 @@ pub fn process_topic_args(args: TopicArgs) -> MqttActionType {
     }
 }
 
+pub fn process_message_args(args: MessageArgs) -> MqttActionType {
+    match args.action {
+        MessageActionType::Publish(arg) => {
+            MqttActionType::PublishMessage(admin_server::request::mqtt::PublishMessageReq {
+                topic: arg.topic,
+                payload: arg.payload,
+                qos: arg.qos,
+                retain: arg.retain,
+                payload_format_indicator: None,
+                message_expiry_interval: arg.message_expiry_interval,
+                content_type: arg.content_type,
+                response_topic: arg.response_topic,
+                correlation_data: arg.correlation_data,
+                user_properties: arg.user_properties,
+            })
+        }
+    }
+}
+
+pub fn process_retain_message_args(args: RetainMessageArgs) -> MqttActionType {
+    match args.action {
+        RetainMessageActionType::List(arg) => MqttActionType::ListRetainMessage(arg.topic_name),
+        RetainMessageActionType::Delete(arg) => MqttActionType::DeleteRetainMessage(
+            admin_server::request::mqtt::DeleteRetainMessageReq {
+                topic_name: arg.topic_name,
+            },
+        ),
+    }
+}
+
+pub fn process_delay_message_args(args: DelayMessageArgs) -> MqttActionType {
+    match args.action {
+        DelayMessageActionType::List(arg) => MqttActionType::ListDelayMessage(arg.topic_name),
+        DelayMessageActionType::Detail(arg) => {
+            MqttActionType::DelayMessageDetail(admin_server::request::mqtt::DelayMessageDetailReq {
+                id: arg.id,
+            })
+        }
+        DelayMessageActionType::Cancel(arg) => {
+            MqttActionType::CancelDelayMessage(admin_server::request::mqtt::DelayMessageCancelReq {
+                id: arg.id,
+                topic_name: arg.topic_name,
+            })
+        }
+    }
+}
+
+pub fn process_scheduled_publish_args(args: ScheduledPublishArgs) -> MqttActionType {
+    match args.action {
+        ScheduledPublishActionType::List => MqttActionType::ListScheduledPublish,
+        ScheduledPublishActionType::Create(arg) => MqttActionType::CreateScheduledPublish(
+            admin_server::request::mqtt::CreateScheduledPublishReq {
+                name: arg.name,
+                topic_name: arg.topic_name,
+                payload: arg.payload,
+                qos: arg.qos,
+                retain: arg.retain,
+                cron: arg.cron,
+                interval_sec: arg.interval_sec,
+                timezone: arg.timezone,
+                end_time: arg.end_time,
+            },
+        ),
+        ScheduledPublishActionType::Delete(arg) => MqttActionType::DeleteScheduledPublish(
+            admin_server::request::mqtt::DeleteScheduledPublishReq { name: arg.name },
+        ),
+        ScheduledPublishActionType::History(arg) => {
+            MqttActionType::ScheduledPublishHistory(arg.name)
+        }
+    }
+}
+
+pub fn process_detail_metrics_args(args: DetailMetricsArgs) -> MqttActionType {
+    match args.action {
+        DetailMetricsActionType::TopicList => MqttActionType::ListDetailMetricsTopic,
+        DetailMetricsActionType::ClientList => MqttActionType::ListDetailMetricsClient,
+        DetailMetricsActionType::GetConfig => MqttActionType::GetDetailMetricsConfig,
+        DetailMetricsActionType::SetConfig(arg) => MqttActionType::SetDetailMetricsConfig(
+            admin_server::request::mqtt::DetailMetricsConfigSetReq {
+                enable: arg.enable,
+                topic_filters: arg.topic_filters,
+                client_id_filters: arg.client_id_filters,
+                max_topics: arg.max_topics,
+                max_clients: arg.max_clients,
+                eviction: arg.eviction,
+            },
+        ),
+    }
+}
+
+pub fn process_trace_args(args: TraceArgs) -> MqttActionType {
+    MqttActionType::Trace(admin_server::request::mqtt::TraceReq {
+        client_id: args.client_id,
+        topic: args.topic,
+        duration_sec: args.duration_sec,
+    })
+}
+
 pub fn process_connector_args(args: ConnectorArgs) -> MqttActionType {
     match args.action {
         ConnectorActionType::List(_) => MqttActionType::ListConnector,
@@ -607,7 +1102,9 @@ pub fn process_connector_args(args: ConnectorArgs) -> MqttActionType {
                 connector_name: arg.connector_name,
                 connector_type: arg.connector_type,
                 config: arg.config,
-                topic_id: arg.topic_id,
+                topic_id: arg.topic_id.unwrap_or_default(),
+                topic_filters: arg.topic_filters,
+                delivery_policy: build_delivery_policy(arg.max_retries, arg.dead_letter_topic),
             })
         }
         ConnectorActionType::Delete(arg) => {
@@ -618,6 +1115,22 @@ pub fn process_connector_args(args: ConnectorArgs) -> MqttActionType {
     }
 }
 
+fn build_delivery_policy(
+    max_retries: Option<u32>,
+    dead_letter_topic: Option<String>,
+) -> Option<ConnectorDeliveryPolicy> {
+    if max_retries.is_none() && dead_letter_topic.is_none() {
+        return None;
+    }
+
+    let mut policy = ConnectorDeliveryPolicy::default();
+    if let Some(max_retries) = max_retries {
+        policy.max_retries = max_retries;
+    }
+    policy.dead_letter_topic = dead_letter_topic;
+    Some(policy)
+}
+
 pub fn process_topic_rewrite_args(args: TopicRewriteArgs) -> MqttActionType {
     match args.action {
         TopicRewriteActionType::List => MqttActionType::ListTopicRewrite,
@@ -18,6 +18,7 @@
     handler::{
         handle_cluster, handle_journal, handle_mqtt, handle_status, RobustMQCli, RobustMQCliCommand,
     },
+    journal::command::JournalCommand,
     mqtt::command::MqttBrokerCommand,
 };
 use common_base::version::logo::banner_print;
@@ -29,7 +30,7 @@ async fn main() {
     match args.command {
         RobustMQCliCommand::Mqtt(args) => handle_mqtt(args, MqttBrokerCommand::new()).await,
         RobustMQCliCommand::Cluster(args) => handle_cluster(args, ClusterCommand::new()).await,
-        RobustMQCliCommand::Journal(args) => handle_journal(args).await,
+        RobustMQCliCommand::Journal(args) => handle_journal(args, JournalCommand::new()).await,
         RobustMQCliCommand::Status(args) => handle_status(args).await,
     }
 }
@@ -126,6 +126,9 @@ pub enum CommonError {
     #[error("Module {0} does not support this feature {1}")]
     NotSupportFeature(String, String),
 
+    #[error("Only part of the records were deleted from shard {0}: {1}")]
+    PartialDeletion(String, String),
+
     #[error("Cannot recognize Kafka protocol {0}")]
     NotSupportKafkaRequest(i16),
 
@@ -153,6 +156,9 @@ pub enum CommonError {
     #[error("{0} is an unavailable type of Connector.")]
     IneligibleConnectorType(String),
 
+    #[error("Invalid PROXY protocol header: {0}")]
+    InvalidProxyProtocolHeader(String),
+
     #[error("{0}")]
     OpenDALError(#[from] opendal::Error),
 }
@@ -12,67 +12,207 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::config::broker_mqtt::BrokerMqttConfig;
-use opentelemetry::{global, propagation::Extractor, trace::noop::NoopTracerProvider};
-use opentelemetry_otlp::{SpanExporter, WithExportConfig};
-use opentelemetry_sdk::{propagation::TraceContextPropagator, trace as sdktrace, Resource};
-use std::{collections::HashMap, sync::OnceLock};
-
-// Prepare to support three types
-// 1. For performance, turn it off
-// 2. For debugging, output to standard output
-// 3. For release, output to otlp
-#[derive(Debug)]
-pub enum TraceExporterProvider {
-    Noop(NoopTracerProvider),
-    Stdout(NoopTracerProvider),
-    Otlp(sdktrace::SdkTracerProvider),
-}
+use crate::error::common::CommonError;
+use opentelemetry::{
+    global,
+    propagation::{Extractor, Injector, TextMapPropagator},
+    trace::{Status, TraceContextExt, Tracer},
+    Context, KeyValue,
+};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{propagation::TraceContextPropagator, trace::SdkTracerProvider, Resource};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        OnceLock,
+    },
+};
+
+// W3C trace context keys, carried in MQTT 5 user properties and record headers
+pub const TRACEPARENT: &str = "traceparent";
+pub const TRACESTATE: &str = "tracestate";
+
+const TRACER_NAME: &str = "robustmq";
+
+// Supported exporters
+// 1. otlp, export to an OTLP collector over gRPC
+// 2. stdout, print spans to standard output for debugging
+pub const EXPORTER_TYPE_OTLP: &str = "otlp";
+pub const EXPORTER_TYPE_STDOUT: &str = "stdout";
 
-static GLOBAL_PROVIDER: OnceLock<TraceExporterProvider> = OnceLock::new();
+static TRACE_ENABLE: AtomicBool = AtomicBool::new(false);
+static GLOBAL_PROVIDER: OnceLock<SdkTracerProvider> = OnceLock::new();
 
-pub async fn init_tracer_provider(broker_config: &'static BrokerMqttConfig) {
-    if !broker_config.telemetry.enable {
-        global::set_tracer_provider(NoopTracerProvider::new());
-        return;
+pub fn init_tracer_provider(
+    enable: bool,
+    exporter_type: &str,
+    exporter_endpoint: &str,
+) -> Result<(), CommonError> {
+    if !enable {
+        return Ok(());
     }
-    match broker_config.telemetry.exporter_type.as_str() {
-        "otlp" => {
-            let exporter = SpanExporter::builder()
+
+    let builder = SdkTracerProvider::builder()
+        .with_resource(Resource::builder().with_service_name(TRACER_NAME).build());
+    let provider = match exporter_type {
+        EXPORTER_TYPE_OTLP => {
+            let exporter = opentelemetry_otlp::SpanExporter::builder()
                 .with_tonic()
-                .with_endpoint(broker_config.telemetry.exporter_endpoint.as_str())
+                .with_endpoint(exporter_endpoint)
                 .build()
-                .unwrap();
-            global::set_text_map_propagator(TraceContextPropagator::new());
-            let provider = sdktrace::SdkTracerProvider::builder()
-                .with_batch_exporter(exporter)
-                .with_resource(Resource::builder().with_service_name("robustmq").build())
-                .build();
-            GLOBAL_PROVIDER
-                .set(TraceExporterProvider::Otlp(provider.clone()))
-                .unwrap();
-            global::set_tracer_provider(provider.clone());
+                .map_err(|e| CommonError::CommonError(e.to_string()))?;
+            builder.with_batch_exporter(exporter).build()
         }
+        EXPORTER_TYPE_STDOUT => builder
+            .with_simple_exporter(opentelemetry_stdout::SpanExporter::default())
+            .build(),
         _ => {
-            global::set_tracer_provider(NoopTracerProvider::new());
+            return Err(CommonError::CommonError(format!(
+                "unsupported telemetry exporter type {exporter_type}, optional values are {EXPORTER_TYPE_OTLP}, {EXPORTER_TYPE_STDOUT}"
+            )));
         }
+    };
+
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    global::set_tracer_provider(provider.clone());
+    if GLOBAL_PROVIDER.set(provider).is_err() {
+        return Err(CommonError::CommonError(
+            "tracer provider has already been initialized".to_string(),
+        ));
     }
+    TRACE_ENABLE.store(true, Ordering::Relaxed);
+    Ok(())
 }
 
-pub async fn stop_tracer_provider() {
+pub fn stop_tracer_provider() -> Result<(), CommonError> {
+    TRACE_ENABLE.store(false, Ordering::Relaxed);
     if let Some(provider) = GLOBAL_PROVIDER.get() {
-        match provider {
-            TraceExporterProvider::Otlp(provider) => {
-                provider.shutdown().unwrap();
-            }
-            TraceExporterProvider::Noop(_provider) => {
-                // Ignore
-            }
-            TraceExporterProvider::Stdout(_provider) => {
-                // Ignore
-            }
+        provider
+            .shutdown()
+            .map_err(|e| CommonError::CommonError(e.to_string()))?;
+    }
+    Ok(())
+}
+
+pub fn is_trace_enable() -> bool {
+    TRACE_ENABLE.load(Ordering::Relaxed)
+}
+
+// A span on the message path. When tracing is disabled it holds nothing, so
+// creating, annotating and dropping it costs almost nothing on the hot path.
+// The span is ended when the value is dropped.
+#[derive(Default)]
+pub struct TraceSpan {
+    cx: Option<Context>,
+}
+
+impl TraceSpan {
+    // Start a root span, or a child of the remote span carried in the key/value pairs
+    pub fn from_pairs<'a, I>(name: &'static str, pairs: I) -> Self
+    where
+        I: IntoIterator<Item = (&'a str, &'a str)>,
+    {
+        if !is_trace_enable() {
+            return TraceSpan::default();
+        }
+        let parent = extract_context(pairs);
+        TraceSpan::start(name, &parent)
+    }
+
+    pub fn from_user_properties(name: &'static str, user_properties: &[(String, String)]) -> Self {
+        TraceSpan::from_pairs(
+            name,
+            user_properties
+                .iter()
+                .map(|(k, v)| (k.as_str(), v.as_str())),
+        )
+    }
+
+    pub fn child(&self, name: &'static str) -> Self {
+        match &self.cx {
+            Some(cx) => TraceSpan::start(name, cx),
+            None => TraceSpan::default(),
+        }
+    }
+
+    fn start(name: &'static str, parent: &Context) -> Self {
+        let tracer = global::tracer(TRACER_NAME);
+        let span = tracer.start_with_context(name, parent);
+        TraceSpan {
+            cx: Some(parent.with_span(span)),
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.cx.is_some()
+    }
+
+    pub fn set_attribute(&self, key: &'static str, value: impl Into<String>) {
+        if let Some(cx) = &self.cx {
+            cx.span().set_attribute(KeyValue::new(key, value.into()));
+        }
+    }
+
+    pub fn set_error(&self, error: impl Into<String>) {
+        if let Some(cx) = &self.cx {
+            cx.span().set_status(Status::error(error.into()));
+        }
+    }
+
+    // The W3C trace context of this span, ready to be written to headers
+    pub fn trace_context(&self) -> Vec<(String, String)> {
+        match &self.cx {
+            Some(cx) => inject_context(cx),
+            None => Vec::new(),
+        }
+    }
+
+    // Replace any upstream trace context in the user properties with this span's
+    pub fn inject_user_properties(&self, user_properties: &mut Vec<(String, String)>) {
+        let trace_context = self.trace_context();
+        if trace_context.is_empty() {
+            return;
+        }
+        user_properties.retain(|(k, _)| !is_trace_context_key(k));
+        user_properties.extend(trace_context);
+    }
+}
+
+impl Drop for TraceSpan {
+    fn drop(&mut self) {
+        if let Some(cx) = self.cx.take() {
+            cx.span().end();
+        }
+    }
+}
+
+pub fn is_trace_context_key(key: &str) -> bool {
+    key.eq_ignore_ascii_case(TRACEPARENT) || key.eq_ignore_ascii_case(TRACESTATE)
+}
+
+fn extract_context<'a, I>(pairs: I) -> Context
+where
+    I: IntoIterator<Item = (&'a str, &'a str)>,
+{
+    let mut carrier = CustomContext::new();
+    for (k, v) in pairs {
+        if is_trace_context_key(k) {
+            carrier.inner.insert(k.to_lowercase(), v.to_string());
         }
     }
+    if carrier.inner.is_empty() {
+        return Context::new();
+    }
+    TraceContextPropagator::new().extract(&carrier)
+}
+
+fn inject_context(cx: &Context) -> Vec<(String, String)> {
+    let mut carrier = CustomContext::new();
+    TraceContextPropagator::new().inject_context(cx, &mut carrier);
+    let mut results: Vec<(String, String)> = carrier.inner.into_iter().collect();
+    results.sort();
+    results
 }
 
 pub struct CustomContext {
@@ -80,12 +220,10 @@ pub struct CustomContext {
 }
 
 impl Extractor for CustomContext {
-    /// Get a value for a key from the MetadataMap.  If the value can't be converted to &str, returns None
     fn get(&self, key: &str) -> Option<&str> {
         self.inner.get(key).map(|metadata| metadata.as_str())
     }
 
-    /// Collect all the keys from the MetadataMap.
     fn keys(&self) -> Vec<&str> {
         self.inner
             .keys()
@@ -94,6 +232,12 @@ fn keys(&self) -> Vec<&str> {
     }
 }
 
+impl Injector for CustomContext {
+    fn set(&mut self, key: &str, value: String) {
+        self.inner.insert(key.to_string(), value);
+    }
+}
+
 impl CustomContext {
     pub fn new() -> Self {
         CustomContext {
@@ -110,31 +254,54 @@ fn default() -> Self {
 
 #[cfg(test)]
 mod tests {
-    use crate::config::broker_mqtt::broker_mqtt_conf;
-    use crate::config::broker_mqtt::init_broker_mqtt_conf_by_path;
-    use std::path::PathBuf;
+    use super::{
+        extract_context, init_tracer_provider, inject_context, is_trace_enable, TraceSpan,
+        TRACEPARENT,
+    };
+    use opentelemetry::trace::TraceContextExt;
 
-    use super::init_tracer_provider;
+    const PARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
 
     #[test]
-    fn telemetry_config_test() {
-        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-        path.push("../../../example/mqtt-cluster/mqtt-server/node-1.toml");
-        init_broker_mqtt_conf_by_path(path.to_str().unwrap());
-        let telemetry_config = &broker_mqtt_conf().telemetry;
-        assert!(!telemetry_config.enable);
+    fn trace_context_propagation_test() {
+        let cx = extract_context(vec![("TraceParent", PARENT), ("k1", "v1")]);
+        let span = cx.span();
+        let span_context = span.span_context();
+        assert!(span_context.is_valid());
+        assert!(span_context.is_remote());
         assert_eq!(
-            telemetry_config.exporter_endpoint,
-            "grpc://127.0.0.1:4317".to_string()
+            span_context.trace_id().to_string(),
+            "4bf92f3577b34da6a3ce929d0e0e4736"
         );
-        assert_eq!(telemetry_config.exporter_type, "otlp".to_string());
+
+        let headers = inject_context(&cx);
+        assert!(headers.contains(&(TRACEPARENT.to_string(), PARENT.to_string())));
+
+        let cx = extract_context(vec![("k1", "v1")]);
+        assert!(!cx.span().span_context().is_valid());
     }
 
-    #[tokio::test]
-    async fn telemetry_test_init() {
-        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-        path.push("../../../example/mqtt-cluster/mqtt-server/node-1.toml");
-        init_broker_mqtt_conf_by_path(path.to_str().unwrap());
-        init_tracer_provider(broker_mqtt_conf()).await
+    #[test]
+    fn disabled_trace_span_test() {
+        init_tracer_provider(false, "otlp", "").unwrap();
+        assert!(!is_trace_enable());
+
+        let span = TraceSpan::from_user_properties(
+            "mqtt.publish",
+            &[(TRACEPARENT.to_string(), PARENT.to_string())],
+        );
+        assert!(!span.is_recording());
+        assert!(!span.child("mqtt.storage.write").is_recording());
+
+        let mut user_properties = vec![(TRACEPARENT.to_string(), PARENT.to_string())];
+        span.inject_user_properties(&mut user_properties);
+        assert_eq!(user_properties.len(), 1);
+        assert!(span.trace_context().is_empty());
+    }
+
+    #[test]
+    fn unsupported_exporter_test() {
+        assert!(init_tracer_provider(true, "zipkin", "").is_err());
+        assert!(!is_trace_enable());
     }
 }
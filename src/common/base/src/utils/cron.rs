@@ -0,0 +1,307 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use chrono::{DateTime, Datelike, Duration, LocalResult, NaiveDate, TimeZone, Timelike};
+
+use crate::error::common::CommonError;
+
+use super::time_util::{parse_timezone, TimeZoneWrapper};
+
+const MONTH_NAMES: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+
+const WEEKDAY_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+// An expression that matches nothing, such as `0 0 30 2 *`, gives up after
+// this many days.
+const MAX_SEARCH_DAYS: u32 = 366 * 5;
+
+// Five field cron expression: minute, hour, day of month, month and day of
+// week. Fields take `*`, numbers, ranges, `/` steps and comma separated
+// lists, months and days of week also take their English short names. Day
+// of week runs from 0 to 7, both meaning Sunday. As in Vixie cron, a day
+// matches either day field when both of them are restricted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CronExpression {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    day_of_month_restricted: bool,
+    day_of_week_restricted: bool,
+}
+
+impl CronExpression {
+    pub fn parse(expression: &str) -> Result<Self, CommonError> {
+        let expression = expression.trim();
+        let expression = match expression {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            _ => expression,
+        };
+
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(invalid_expression(
+                expression,
+                "expected 5 fields: minute hour day-of-month month day-of-week",
+            ));
+        }
+
+        let mut days_of_week = parse_field(fields[4], 0, 7, &WEEKDAY_NAMES, 0)
+            .map_err(|e| invalid_expression(expression, &e))?;
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week | 1) & !(1 << 7);
+        }
+
+        Ok(CronExpression {
+            minutes: parse_field(fields[0], 0, 59, &[], 0)
+                .map_err(|e| invalid_expression(expression, &e))?,
+            hours: parse_field(fields[1], 0, 23, &[], 0)
+                .map_err(|e| invalid_expression(expression, &e))?,
+            days_of_month: parse_field(fields[2], 1, 31, &[], 0)
+                .map_err(|e| invalid_expression(expression, &e))?,
+            months: parse_field(fields[3], 1, 12, &MONTH_NAMES, 1)
+                .map_err(|e| invalid_expression(expression, &e))?,
+            days_of_week,
+            day_of_month_restricted: !fields[2].starts_with('*'),
+            day_of_week_restricted: !fields[4].starts_with('*'),
+        })
+    }
+
+    // The first matching minute strictly after `timestamp`, in seconds.
+    // `timezone` is an IANA name or a UTC offset, local times skipped by a
+    // daylight saving change never match.
+    pub fn next_after(&self, timestamp: u64, timezone: &str) -> Result<Option<u64>, CommonError> {
+        let timezone = parse_timezone(timezone).map_err(CommonError::CommonError)?;
+        Ok(match timezone {
+            TimeZoneWrapper::Tz(tz) => self.next_after_in(&tz, timestamp),
+            TimeZoneWrapper::FixedOffset(offset) => self.next_after_in(&offset, timestamp),
+        })
+    }
+
+    fn next_after_in<T: TimeZone>(&self, tz: &T, timestamp: u64) -> Option<u64> {
+        let after = DateTime::from_timestamp(i64::try_from(timestamp).ok()?, 0)?;
+        let local = after.with_timezone(tz).naive_local();
+        let start = local.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+
+        let mut date = start.date();
+        let mut first_minute = start.hour() * 60 + start.minute();
+        for _ in 0..MAX_SEARCH_DAYS {
+            if self.day_matches(date) {
+                for minute_of_day in first_minute..24 * 60 {
+                    let (hour, minute) = (minute_of_day / 60, minute_of_day % 60);
+                    if !contains(self.hours, hour) || !contains(self.minutes, minute) {
+                        continue;
+                    }
+
+                    let candidate =
+                        match tz.from_local_datetime(&date.and_hms_opt(hour, minute, 0)?) {
+                            LocalResult::Single(time) => time,
+                            LocalResult::Ambiguous(earliest, _) => earliest,
+                            LocalResult::None => continue,
+                        };
+                    let candidate = u64::try_from(candidate.timestamp()).ok()?;
+                    if candidate > timestamp {
+                        return Some(candidate);
+                    }
+                }
+            }
+            date = date.succ_opt()?;
+            first_minute = 0;
+        }
+        None
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        if !contains(self.months, date.month()) {
+            return false;
+        }
+
+        let day_of_month = contains(self.days_of_month, date.day());
+        let day_of_week = contains(self.days_of_week, date.weekday().num_days_from_sunday());
+        match (self.day_of_month_restricted, self.day_of_week_restricted) {
+            (true, true) => day_of_month || day_of_week,
+            (true, false) => day_of_month,
+            (false, true) => day_of_week,
+            (false, false) => true,
+        }
+    }
+}
+
+fn contains(mask: u64, value: u32) -> bool {
+    mask & (1 << value) != 0
+}
+
+fn parse_field(
+    field: &str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+    name_base: u32,
+) -> Result<u64, String> {
+    let mut mask = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step = step
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|step| *step > 0)
+                    .ok_or_else(|| format!("invalid step in '{part}'"))?;
+                (range, Some(step))
+            }
+            None => (part, None),
+        };
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (
+                parse_value(start, names, name_base)?,
+                parse_value(end, names, name_base)?,
+            )
+        } else {
+            let value = parse_value(range, names, name_base)?;
+            // `5/15` runs from 5 to the end of the range
+            (value, if step.is_some() { max } else { value })
+        };
+
+        if start < min || end > max || start > end {
+            return Err(format!("'{part}' is out of range {min}-{max}"));
+        }
+        for value in (start..=end).step_by(step.unwrap_or(1) as usize) {
+            mask |= 1 << value;
+        }
+    }
+    Ok(mask)
+}
+
+fn parse_value(value: &str, names: &[&str], name_base: u32) -> Result<u32, String> {
+    if let Ok(value) = value.parse::<u32>() {
+        return Ok(value);
+    }
+    names
+        .iter()
+        .position(|name| name.eq_ignore_ascii_case(value))
+        .map(|position| position as u32 + name_base)
+        .ok_or_else(|| format!("invalid value '{value}'"))
+}
+
+fn invalid_expression(expression: &str, reason: &str) -> CommonError {
+    CommonError::CommonError(format!("Invalid cron expression '{expression}': {reason}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CronExpression;
+    use chrono::{TimeZone, Utc};
+
+    fn timestamp(year: i32, month: u32, day: u32, hour: u32, minute: u32, second: u32) -> u64 {
+        Utc.with_ymd_and_hms(year, month, day, hour, minute, second)
+            .unwrap()
+            .timestamp() as u64
+    }
+
+    #[test]
+    pub fn parse_test() {
+        assert!(CronExpression::parse("*/15 * * * *").is_ok());
+        assert!(CronExpression::parse("0 9-17 * JAN-MAR mon-fri").is_ok());
+        assert!(CronExpression::parse("@daily").is_ok());
+        assert_eq!(
+            CronExpression::parse("0 0 * * 7").unwrap(),
+            CronExpression::parse("0 0 * * 0").unwrap()
+        );
+
+        assert!(CronExpression::parse("* * * *").is_err());
+        assert!(CronExpression::parse("60 * * * *").is_err());
+        assert!(CronExpression::parse("* 24 * * *").is_err());
+        assert!(CronExpression::parse("* * 0 * *").is_err());
+        assert!(CronExpression::parse("*/0 * * * *").is_err());
+        assert!(CronExpression::parse("5-1 * * * *").is_err());
+        assert!(CronExpression::parse("* * * FOO *").is_err());
+    }
+
+    #[test]
+    pub fn next_after_test() {
+        let cron = CronExpression::parse("*/15 * * * *").unwrap();
+        let now = timestamp(2025, 3, 1, 10, 7, 30);
+        assert_eq!(
+            cron.next_after(now, "UTC").unwrap(),
+            Some(timestamp(2025, 3, 1, 10, 15, 0))
+        );
+        let next = timestamp(2025, 3, 1, 10, 15, 0);
+        assert_eq!(
+            cron.next_after(next, "UTC").unwrap(),
+            Some(timestamp(2025, 3, 1, 10, 30, 0))
+        );
+
+        // 09:00 in Shanghai is 01:00 UTC
+        let cron = CronExpression::parse("0 9 * * *").unwrap();
+        assert_eq!(
+            cron.next_after(now, "Asia/Shanghai").unwrap(),
+            Some(timestamp(2025, 3, 2, 1, 0, 0))
+        );
+        assert_eq!(
+            cron.next_after(now, "UTC+8").unwrap(),
+            Some(timestamp(2025, 3, 2, 1, 0, 0))
+        );
+        assert!(cron.next_after(now, "Mars/Olympus").is_err());
+
+        // 2025-03-01 is a Saturday
+        let cron = CronExpression::parse("0 0 * * MON").unwrap();
+        assert_eq!(
+            cron.next_after(now, "UTC").unwrap(),
+            Some(timestamp(2025, 3, 3, 0, 0, 0))
+        );
+
+        // Either day field matches when both are restricted
+        let cron = CronExpression::parse("0 0 15 * MON").unwrap();
+        assert_eq!(
+            cron.next_after(timestamp(2025, 3, 4, 0, 0, 0), "UTC")
+                .unwrap(),
+            Some(timestamp(2025, 3, 10, 0, 0, 0))
+        );
+        assert_eq!(
+            cron.next_after(timestamp(2025, 3, 12, 0, 0, 0), "UTC")
+                .unwrap(),
+            Some(timestamp(2025, 3, 15, 0, 0, 0))
+        );
+
+        let cron = CronExpression::parse("0 0 29 2 *").unwrap();
+        assert_eq!(
+            cron.next_after(now, "UTC").unwrap(),
+            Some(timestamp(2028, 2, 29, 0, 0, 0))
+        );
+
+        let cron = CronExpression::parse("0 0 30 2 *").unwrap();
+        assert_eq!(cron.next_after(now, "UTC").unwrap(), None);
+    }
+
+    #[test]
+    pub fn daylight_saving_test() {
+        // 02:30 does not exist in New York on 2025-03-09
+        let cron = CronExpression::parse("30 2 * * *").unwrap();
+        let now = timestamp(2025, 3, 8, 12, 0, 0);
+        assert_eq!(
+            cron.next_after(now, "America/New_York").unwrap(),
+            Some(timestamp(2025, 3, 10, 6, 30, 0))
+        );
+    }
+}
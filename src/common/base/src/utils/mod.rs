@@ -13,6 +13,7 @@
 // limitations under the License.
 
 pub mod crc;
+pub mod cron;
 pub mod file_utils;
 pub mod time_util;
 pub mod topic_util;
@@ -45,12 +45,12 @@ pub fn timestamp_to_timezone_datetime(timestamp: i64, timezone: &str) -> Result<
     })
 }
 
-enum TimeZoneWrapper {
+pub(crate) enum TimeZoneWrapper {
     Tz(Tz),
     FixedOffset(FixedOffset),
 }
 
-fn parse_timezone(timezone: &str) -> Result<TimeZoneWrapper, String> {
+pub(crate) fn parse_timezone(timezone: &str) -> Result<TimeZoneWrapper, String> {
     if let Ok(tz) = timezone.parse::<Tz>() {
         return Ok(TimeZoneWrapper::Tz(tz));
     }
@@ -32,6 +32,19 @@ pub struct Prometheus {
     pub port: u32,
 }
 
+// Admin Server
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
+pub struct AdminServer {
+    pub port: u32,
+    pub auth_enable: bool,
+    pub session_expire_sec: u64,
+    pub default_username: String,
+    pub default_password: String,
+    // Reverse proxies whose X-Forwarded-For header is trusted for the audit log
+    #[serde(default)]
+    pub trusted_proxies: Vec<String>,
+}
+
 // Log
 #[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
 pub struct Log {
@@ -65,6 +78,25 @@ pub fn default_prometheus() -> Prometheus {
     }
 }
 
+pub fn default_telemetry() -> Telemetry {
+    Telemetry {
+        enable: false,
+        exporter_type: "otlp".to_string(),
+        exporter_endpoint: "http://127.0.0.1:4317".to_string(),
+    }
+}
+
+pub fn default_admin_server() -> AdminServer {
+    AdminServer {
+        port: 8080,
+        auth_enable: false,
+        session_expire_sec: 3600,
+        default_username: "admin".to_string(),
+        default_password: "robustmq".to_string(),
+        trusted_proxies: Vec::new(),
+    }
+}
+
 pub fn default_pprof() -> PProf {
     PProf {
         enable: false,
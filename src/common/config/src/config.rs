@@ -14,17 +14,21 @@
 
 use super::default::{
     default_broker_id, default_cluster_name, default_flapping_detect, default_grpc_port,
-    default_journal_runtime, default_journal_server, default_journal_storage, default_meta_addrs,
-    default_mqtt_auth_config, default_mqtt_auth_storage, default_mqtt_message_storage,
-    default_mqtt_offline_message, default_mqtt_protocol_config, default_mqtt_runtime,
-    default_mqtt_schema, default_mqtt_security, default_mqtt_server,
+    default_journal_record_compression, default_journal_runtime, default_journal_server,
+    default_journal_storage, default_meta_addrs, default_mqtt_auth_config,
+    default_mqtt_auth_storage, default_mqtt_detail_metrics, default_mqtt_message_storage,
+    default_mqtt_offline_message, default_mqtt_protocol_config, default_mqtt_proxy_protocol,
+    default_mqtt_runtime, default_mqtt_schema, default_mqtt_security, default_mqtt_server,
     default_mqtt_slow_subscribe_config, default_mqtt_system_monitor, default_network,
     default_place_runtime, default_rocksdb, default_roles, default_runtime,
 };
 use super::security::{AuthnConfig, AuthzConfig};
 use crate::common::Log;
 use crate::common::Prometheus;
-use crate::common::{default_log, default_pprof, default_prometheus};
+use crate::common::{
+    default_admin_server, default_log, default_pprof, default_prometheus, default_telemetry,
+    AdminServer, Telemetry,
+};
 use common_base::enum_type::delay_type::DelayType;
 use serde::{Deserialize, Serialize};
 use toml::Table;
@@ -53,6 +57,9 @@ pub struct BrokerConfig {
     #[serde(default = "default_log")]
     pub log: Log,
 
+    #[serde(default = "default_telemetry")]
+    pub telemetry: Telemetry,
+
     #[serde(default = "default_runtime")]
     pub runtime: Runtime,
 
@@ -62,6 +69,9 @@ pub struct BrokerConfig {
     #[serde(default = "default_pprof")]
     pub p_prof: PProf,
 
+    #[serde(default = "default_admin_server")]
+    pub admin_server: AdminServer,
+
     // meta
     #[serde(default = "default_place_runtime")]
     pub meta_runtime: MetaRuntime,
@@ -115,6 +125,9 @@ pub struct BrokerConfig {
 
     #[serde(default = "default_mqtt_system_monitor")]
     pub mqtt_system_monitor: MqttSystemMonitor,
+
+    #[serde(default = "default_mqtt_detail_metrics")]
+    pub mqtt_detail_metrics: MqttDetailMetrics,
 }
 
 impl BrokerConfig {
@@ -189,6 +202,94 @@ pub struct MqttServer {
     pub websocket_port: u32,
     pub websockets_port: u32,
     pub quic_port: u32,
+    #[serde(default = "default_mqtt_proxy_protocol")]
+    pub proxy_protocol: MqttProxyProtocol,
+    #[serde(default)]
+    pub listeners: Vec<MqttListener>,
+}
+
+impl MqttServer {
+    /// The listeners the broker should start. When no listener is configured
+    /// explicitly, one listener per legacy port is derived so that existing
+    /// configuration files keep working unchanged.
+    pub fn get_listeners(&self) -> Vec<MqttListener> {
+        if !self.listeners.is_empty() {
+            return self.listeners.clone();
+        }
+
+        let legacy = [
+            ("tcp", "tcp", self.tcp_port, self.proxy_protocol.tcp),
+            ("tls", "tls", self.tls_port, self.proxy_protocol.tls),
+            (
+                "websocket",
+                "websocket",
+                self.websocket_port,
+                self.proxy_protocol.websocket,
+            ),
+            (
+                "websockets",
+                "websockets",
+                self.websockets_port,
+                self.proxy_protocol.websockets,
+            ),
+            ("quic", "quic", self.quic_port, false),
+        ];
+
+        legacy
+            .into_iter()
+            .map(|(name, protocol, port, proxy_protocol)| MqttListener {
+                name: name.to_string(),
+                protocol: protocol.to_string(),
+                bind_addr: format!("0.0.0.0:{port}"),
+                proxy_protocol,
+                ..Default::default()
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+pub struct MqttListener {
+    pub name: String,
+    // tcp, tls, websocket, websockets, quic
+    pub protocol: String,
+    pub bind_addr: String,
+    // TLS material, falls back to runtime.tls_cert/runtime.tls_key when not set
+    #[serde(default)]
+    pub tls_cert: Option<String>,
+    #[serde(default)]
+    pub tls_key: Option<String>,
+    // CA used to verify client certificates, enables mTLS when set
+    #[serde(default)]
+    pub tls_ca: Option<String>,
+    // Ordered authentication chain: password, x509, anonymous.
+    // An empty chain uses the cluster-wide authentication.
+    #[serde(default)]
+    pub authentication: Vec<String>,
+    #[serde(default)]
+    pub max_connections: Option<u64>,
+    // Prefix transparently added to the topics of clients on this listener
+    #[serde(default)]
+    pub mountpoint: Option<String>,
+    #[serde(default)]
+    pub websocket_path: Option<String>,
+    #[serde(default)]
+    pub proxy_protocol: bool,
+}
+
+impl MqttListener {
+    pub fn is_tls(&self) -> bool {
+        self.protocol == "tls" || self.protocol == "websockets" || self.protocol == "quic"
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct MqttProxyProtocol {
+    pub tcp: bool,
+    pub tls: bool,
+    pub websocket: bool,
+    pub websockets: bool,
+    pub header_timeout_ms: u64,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, Default)]
@@ -219,6 +320,9 @@ pub struct MqttMessageStorage {
 
     pub mysql_addr: String,
 
+    #[serde(default)]
+    pub postgres_addr: String,
+
     pub rocksdb_data_path: String,
     pub rocksdb_max_open_files: Option<i32>,
 }
@@ -241,13 +345,74 @@ pub struct MqttSystemMonitor {
     pub os_memory_high_watermark: f32,
 }
 
+// Per-topic and per-client metrics, only for the allowlisted topics and clients
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+pub struct MqttDetailMetrics {
+    pub enable: bool,
+
+    // Topic filters, MQTT wildcards are allowed
+    #[serde(default)]
+    pub topic_filters: Vec<String>,
+
+    // Client ids, a trailing * matches a prefix
+    #[serde(default)]
+    pub client_id_filters: Vec<String>,
+
+    // Caps on the number of tracked topics and clients, which bound the
+    // cardinality of the exported series
+    pub max_topics: usize,
+
+    pub max_clients: usize,
+
+    // Which series makes room for a new one once a cap is reached
+    #[serde(default)]
+    pub eviction: DetailMetricsEviction,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DetailMetricsEviction {
+    // The least recently active one
+    #[default]
+    Lru,
+    // The one with the least traffic, so that the busiest ones stay
+    TopN,
+}
+
+impl MqttDetailMetrics {
+    pub fn encode(&self) -> Vec<u8> {
+        serde_json::to_vec(&self).unwrap()
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone, Default)]
 pub struct MqttOfflineMessage {
     pub enable: bool,
 
     pub expire_ms: u32,
 
+    // Bounds of the queue kept for each offline session, 0 means unlimited
     pub max_messages_num: u32,
+
+    #[serde(default)]
+    pub max_messages_bytes: u64,
+
+    // What happens when a message does not fit in a full queue
+    #[serde(default)]
+    pub overflow_policy: OfflineMessageOverflowPolicy,
+
+    // Do not queue QoS 0 messages for offline sessions
+    #[serde(default)]
+    pub exclude_qos0: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OfflineMessageOverflowPolicy {
+    #[default]
+    DropOldest,
+    DropNewest,
+    Disconnect,
 }
 
 impl MqttOfflineMessage {
@@ -359,9 +524,118 @@ pub struct JournalRuntime {
 pub struct JournalStorage {
     pub data_path: Vec<String>,
     pub rocksdb_max_open_files: i32,
+    #[serde(default = "default_journal_record_compression")]
+    pub record_compression: String,
 }
 
 #[derive(Serialize, Deserialize, Default, Clone, Debug)]
 pub struct JournalServer {
     pub tcp_port: u32,
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::default::default_mqtt_server;
+
+    use super::{BrokerConfig, DetailMetricsEviction, MqttServer};
+
+    #[test]
+    fn legacy_listeners_test() {
+        let server = default_mqtt_server();
+        let listeners = server.get_listeners();
+        assert_eq!(listeners.len(), 5);
+        assert_eq!(listeners[0].name, "tcp");
+        assert_eq!(listeners[0].bind_addr, "0.0.0.0:1883");
+        assert!(!listeners[0].is_tls());
+        assert_eq!(listeners[1].bind_addr, "0.0.0.0:1884");
+        assert!(listeners[1].is_tls());
+    }
+
+    #[test]
+    fn configured_listeners_test() {
+        let server: MqttServer = toml::from_str(
+            r#"
+            tcp_port = 1883
+            tls_port = 1884
+            websocket_port = 8083
+            websockets_port = 8084
+            quic_port = 9083
+
+            [[listeners]]
+            name = "internal"
+            protocol = "tcp"
+            bind_addr = "10.0.0.1:1883"
+            authentication = ["anonymous"]
+            mountpoint = "internal/"
+
+            [[listeners]]
+            name = "devices"
+            protocol = "tls"
+            bind_addr = "0.0.0.0:8883"
+            tls_ca = "./config/certs/ca.pem"
+            authentication = ["x509", "password"]
+            max_connections = 100000
+            "#,
+        )
+        .unwrap();
+
+        let listeners = server.get_listeners();
+        assert_eq!(listeners.len(), 2);
+        assert_eq!(listeners[0].mountpoint, Some("internal/".to_string()));
+        assert_eq!(listeners[1].authentication, vec!["x509", "password"]);
+        assert_eq!(listeners[1].max_connections, Some(100000));
+        assert!(listeners[1].tls_cert.is_none());
+    }
+
+    #[test]
+    fn telemetry_config_test() {
+        let config: BrokerConfig = toml::from_str(
+            r#"
+            cluster_name = 'test1'
+            broker_id = 1
+            "#,
+        )
+        .unwrap();
+        assert!(!config.telemetry.enable);
+        assert_eq!(config.telemetry.exporter_type, "otlp");
+
+        let config: BrokerConfig = toml::from_str(
+            r#"
+            cluster_name = 'test1'
+            broker_id = 1
+
+            [telemetry]
+            enable = true
+            exporter_type = "stdout"
+            exporter_endpoint = ""
+            "#,
+        )
+        .unwrap();
+        assert!(config.telemetry.enable);
+        assert_eq!(config.telemetry.exporter_type, "stdout");
+    }
+
+    #[test]
+    fn detail_metrics_config_test() {
+        let config: BrokerConfig = toml::from_str(
+            r#"
+            cluster_name = 'test1'
+            broker_id = 1
+
+            [mqtt_detail_metrics]
+            enable = true
+            topic_filters = ["sensor/+/temp"]
+            max_topics = 100
+            max_clients = 50
+            eviction = "top_n"
+            "#,
+        )
+        .unwrap();
+        let detail_metrics = config.mqtt_detail_metrics;
+        assert!(detail_metrics.enable);
+        assert_eq!(detail_metrics.topic_filters, vec!["sensor/+/temp"]);
+        assert!(detail_metrics.client_id_filters.is_empty());
+        assert_eq!(detail_metrics.max_clients, 50);
+        assert_eq!(detail_metrics.eviction, DetailMetricsEviction::TopN);
+    }
+}
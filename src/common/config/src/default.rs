@@ -14,10 +14,11 @@
 
 use super::security::{AuthnConfig, AuthzConfig};
 use crate::config::{
-    JournalRuntime, JournalServer, JournalStorage, MetaRuntime, MqttAuthConfig, MqttAuthStorage,
-    MqttFlappingDetect, MqttMessageStorage, MqttOfflineMessage, MqttProtocolConfig, MqttRuntime,
-    MqttSchema, MqttSecurity, MqttServer, MqttSlowSubscribeConfig, MqttSystemMonitor, Network,
-    Rocksdb, Runtime, SchemaFailedOperation, SchemaStrategy,
+    DetailMetricsEviction, JournalRuntime, JournalServer, JournalStorage, MetaRuntime,
+    MqttAuthConfig, MqttAuthStorage, MqttDetailMetrics, MqttFlappingDetect, MqttMessageStorage,
+    MqttOfflineMessage, MqttProtocolConfig, MqttProxyProtocol, MqttRuntime, MqttSchema,
+    MqttSecurity, MqttServer, MqttSlowSubscribeConfig, MqttSystemMonitor, Network,
+    OfflineMessageOverflowPolicy, Rocksdb, Runtime, SchemaFailedOperation, SchemaStrategy,
 };
 use common_base::enum_type::delay_type::DelayType;
 use common_base::runtime::get_runtime_worker_threads;
@@ -88,6 +89,18 @@ pub fn default_mqtt_server() -> MqttServer {
         websocket_port: 8083,
         websockets_port: 8084,
         quic_port: 9083,
+        proxy_protocol: default_mqtt_proxy_protocol(),
+        listeners: Vec::new(),
+    }
+}
+
+pub fn default_mqtt_proxy_protocol() -> MqttProxyProtocol {
+    MqttProxyProtocol {
+        tcp: false,
+        tls: false,
+        websocket: false,
+        websockets: false,
+        header_timeout_ms: 5000,
     }
 }
 
@@ -114,6 +127,7 @@ pub fn default_mqtt_message_storage() -> MqttMessageStorage {
         storage_type: "memory".to_string(),
         journal_addr: "".to_string(),
         mysql_addr: "".to_string(),
+        postgres_addr: "".to_string(),
         rocksdb_data_path: "".to_string(),
         rocksdb_max_open_files: None,
     }
@@ -132,6 +146,9 @@ pub fn default_mqtt_offline_message() -> MqttOfflineMessage {
         enable: true,
         expire_ms: 0,
         max_messages_num: 0,
+        max_messages_bytes: 0,
+        overflow_policy: OfflineMessageOverflowPolicy::DropOldest,
+        exclude_qos0: false,
     }
 }
 
@@ -192,6 +209,17 @@ pub fn default_mqtt_system_monitor() -> MqttSystemMonitor {
     }
 }
 
+pub fn default_mqtt_detail_metrics() -> MqttDetailMetrics {
+    MqttDetailMetrics {
+        enable: false,
+        topic_filters: Vec::new(),
+        client_id_filters: Vec::new(),
+        max_topics: 1000,
+        max_clients: 1000,
+        eviction: DetailMetricsEviction::Lru,
+    }
+}
+
 pub fn default_journal_server() -> JournalServer {
     JournalServer { tcp_port: 1778 }
 }
@@ -208,5 +236,10 @@ pub fn default_journal_storage() -> JournalStorage {
     JournalStorage {
         data_path: vec!["./data/journal/".to_string()],
         rocksdb_max_open_files: 10000,
+        record_compression: default_journal_record_compression(),
     }
 }
+
+pub fn default_journal_record_compression() -> String {
+    "zstd".to_string()
+}
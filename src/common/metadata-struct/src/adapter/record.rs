@@ -12,7 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use common_base::{tools::now_second, utils::crc::calc_crc32};
+use common_base::{
+    telemetry::trace::is_trace_context_key, tools::now_second, utils::crc::calc_crc32,
+};
 use pulsar::{producer, Error as PulsarError, SerializeMessage};
 use serde::{Deserialize, Serialize};
 
@@ -75,6 +77,28 @@ pub fn set_key(&mut self, key: String) {
         self.key = key;
     }
 
+    // The W3C trace context (traceparent/tracestate) carried in the headers
+    pub fn trace_context(&self) -> Vec<(&str, &str)> {
+        self.header
+            .iter()
+            .filter(|h| is_trace_context_key(&h.name))
+            .map(|h| (h.name.as_str(), h.value.as_str()))
+            .collect()
+    }
+
+    // Replace the trace context headers, other headers are kept
+    pub fn set_trace_context(&mut self, trace_context: Vec<(String, String)>) {
+        if trace_context.is_empty() {
+            return;
+        }
+        self.header.retain(|h| !is_trace_context_key(&h.name));
+        self.header.extend(
+            trace_context
+                .into_iter()
+                .map(|(name, value)| Header { name, value }),
+        );
+    }
+
     pub fn crc32_check(&self) -> bool {
         let crc_num = calc_crc32(&self.data);
         crc_num == self.crc_num
@@ -55,10 +55,30 @@ pub struct NetworkConnection {
     pub protocol: Option<RobustMQProtocol>,
     pub addr: SocketAddr,
     pub create_time: u64,
+    #[serde(default)]
+    pub proxy_addr: Option<SocketAddr>,
+    #[serde(default)]
+    pub proxy_tls: Option<ProxyTlsInfo>,
+    #[serde(default)]
+    pub listener: Option<String>,
+    #[serde(default)]
+    pub client_cert_verified: bool,
     #[serde(skip_serializing, skip_deserializing)]
     pub connection_stop_sx: Option<mpsc::Sender<bool>>,
 }
 
+/// TLS information terminated by an upstream load balancer and forwarded
+/// through the PROXY protocol v2 TLVs.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct ProxyTlsInfo {
+    pub version: Option<String>,
+    pub cipher: Option<String>,
+    pub sni: Option<String>,
+    pub alpn: Option<String>,
+    pub client_cn: Option<String>,
+    pub client_cert_verified: bool,
+}
+
 impl NetworkConnection {
     pub fn new(
         connection_type: NetworkConnectionType,
@@ -72,10 +92,42 @@ pub fn new(
             protocol: None,
             addr,
             create_time: now_second(),
+            proxy_addr: None,
+            proxy_tls: None,
+            listener: None,
+            client_cert_verified: false,
             connection_stop_sx,
         }
     }
 
+    pub fn set_listener(&mut self, listener: &str) {
+        self.listener = Some(listener.to_string());
+    }
+
+    /// Whether the client presented a certificate that was verified, either by
+    /// the broker's mTLS handshake or by the load balancer in front of it.
+    pub fn is_client_cert_verified(&self) -> bool {
+        if self.client_cert_verified {
+            return true;
+        }
+        if let Some(tls) = &self.proxy_tls {
+            return tls.client_cert_verified && tls.client_cn.is_some();
+        }
+        false
+    }
+
+    /// Replace the peer address with the real client address announced by the
+    /// PROXY protocol header, keeping the load balancer address for reference.
+    pub fn set_proxy_info(&mut self, source: SocketAddr, proxy_tls: Option<ProxyTlsInfo>) {
+        self.proxy_addr = Some(self.addr);
+        self.addr = source;
+        self.proxy_tls = proxy_tls;
+    }
+
+    pub fn is_proxied(&self) -> bool {
+        self.proxy_addr.is_some()
+    }
+
     pub fn connection_id(&self) -> u64 {
         self.connection_id
     }
@@ -16,8 +16,12 @@
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DelayMessageInfo {
+    #[serde(default)]
+    pub id: String,
     pub delay_shard_name: String,
     pub target_shard_name: String,
     pub offset: u64,
     pub delay_timestamp: u64,
+    #[serde(default)]
+    pub create_time: u64,
 }
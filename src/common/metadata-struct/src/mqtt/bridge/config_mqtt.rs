@@ -0,0 +1,217 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_base::error::common::CommonError;
+use serde::{Deserialize, Serialize};
+
+// User property carrying the cluster a bridged message came from. Bridges never
+// forward a message back out of the cluster named in it, which breaks forwarding loops.
+pub const MQTT_BRIDGE_ORIGIN_PROPERTY: &str = "robustmq-bridge-origin";
+
+const DEFAULT_KEEP_ALIVE_SECS: u64 = 60;
+const DEFAULT_MAX_QOS: u8 = 1;
+const DEFAULT_RECONNECT_MIN_INTERVAL_MS: u64 = 1000;
+const DEFAULT_RECONNECT_MAX_INTERVAL_MS: u64 = 60000;
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MqttBridgeDirection {
+    // Forward messages of the local topic to the remote broker
+    #[default]
+    Egress,
+    // Subscribe on the remote broker and republish the messages locally
+    Ingress,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
+pub struct MqttBridgeConnectorConfig {
+    // Remote broker address, host:port
+    pub server: String,
+    #[serde(default)]
+    pub direction: MqttBridgeDirection,
+    pub client_id: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub keep_alive_secs: Option<u64>,
+    pub enable_tls: Option<bool>,
+    pub ca_path: Option<String>,
+    pub client_cert_path: Option<String>,
+    pub client_key_path: Option<String>,
+    // Egress: prepended to the local topic name when publishing remotely
+    pub remote_topic_prefix: Option<String>,
+    // Ingress: topic filters subscribed on the remote broker
+    pub remote_topic_filters: Option<Vec<String>>,
+    // Ingress: prepended to the remote topic name when republishing locally
+    pub local_topic_prefix: Option<String>,
+    // Highest QoS used on the other side, messages with a lower QoS keep it
+    pub max_qos: Option<u8>,
+    pub reconnect_min_interval_ms: Option<u64>,
+    pub reconnect_max_interval_ms: Option<u64>,
+}
+
+impl MqttBridgeConnectorConfig {
+    pub fn validate(&self) -> Result<(), CommonError> {
+        self.host_port()?;
+
+        if self.max_qos.is_some_and(|qos| qos > 2) {
+            return Err(CommonError::InvalidParameterFormat(
+                "max_qos".to_string(),
+                format!("{:?}", self.max_qos),
+            ));
+        }
+
+        if self.direction == MqttBridgeDirection::Ingress
+            && self
+                .remote_topic_filters
+                .as_ref()
+                .is_none_or(|filters| filters.is_empty())
+        {
+            return Err(CommonError::ParameterCannotBeNull(
+                "remote_topic_filters".to_string(),
+            ));
+        }
+
+        if self.client_cert_path.is_some() != self.client_key_path.is_some() {
+            return Err(CommonError::CommonError(
+                "client_cert_path and client_key_path must be set together".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn host_port(&self) -> Result<(String, u16), CommonError> {
+        let invalid =
+            || CommonError::InvalidParameterFormat("server".to_string(), self.server.clone());
+        let (host, port) = self.server.rsplit_once(':').ok_or_else(invalid)?;
+        if host.is_empty() {
+            return Err(invalid());
+        }
+        let port = port.parse::<u16>().map_err(|_| invalid())?;
+        Ok((host.to_string(), port))
+    }
+
+    pub fn client_id(&self, connector_name: &str) -> String {
+        self.client_id
+            .clone()
+            .unwrap_or_else(|| format!("robustmq_bridge_{connector_name}"))
+    }
+
+    pub fn keep_alive_secs(&self) -> u64 {
+        self.keep_alive_secs.unwrap_or(DEFAULT_KEEP_ALIVE_SECS)
+    }
+
+    pub fn is_tls(&self) -> bool {
+        self.enable_tls.unwrap_or(self.ca_path.is_some())
+    }
+
+    pub fn remote_topic(&self, local_topic: &str) -> String {
+        format!(
+            "{}{}",
+            self.remote_topic_prefix.as_deref().unwrap_or_default(),
+            local_topic
+        )
+    }
+
+    pub fn local_topic(&self, remote_topic: &str) -> String {
+        format!(
+            "{}{}",
+            self.local_topic_prefix.as_deref().unwrap_or_default(),
+            remote_topic
+        )
+    }
+
+    pub fn map_qos(&self, qos: u8) -> u8 {
+        qos.min(self.max_qos.unwrap_or(DEFAULT_MAX_QOS))
+    }
+
+    // Exponential backoff between reconnect attempts, starting at the minimum interval
+    pub fn reconnect_interval_ms(&self, attempt: u32) -> u64 {
+        let min = self
+            .reconnect_min_interval_ms
+            .unwrap_or(DEFAULT_RECONNECT_MIN_INTERVAL_MS);
+        let max = self
+            .reconnect_max_interval_ms
+            .unwrap_or(DEFAULT_RECONNECT_MAX_INTERVAL_MS)
+            .max(min);
+        min.saturating_mul(1u64 << attempt.min(32)).min(max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(server: &str) -> MqttBridgeConnectorConfig {
+        MqttBridgeConnectorConfig {
+            server: server.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn validate_test() {
+        assert!(config("127.0.0.1:1883").validate().is_ok());
+        assert!(config("127.0.0.1").validate().is_err());
+        assert!(config(":1883").validate().is_err());
+        assert!(config("127.0.0.1:abc").validate().is_err());
+
+        let mut ingress = config("127.0.0.1:1883");
+        ingress.direction = MqttBridgeDirection::Ingress;
+        assert!(ingress.validate().is_err());
+        ingress.remote_topic_filters = Some(vec!["sensors/#".to_string()]);
+        assert!(ingress.validate().is_ok());
+
+        ingress.max_qos = Some(3);
+        assert!(ingress.validate().is_err());
+        ingress.max_qos = Some(2);
+        ingress.client_cert_path = Some("/tmp/cert.pem".to_string());
+        assert!(ingress.validate().is_err());
+    }
+
+    #[test]
+    fn topic_mapping_test() {
+        let mut conf = config("127.0.0.1:1883");
+        assert_eq!(conf.remote_topic("a/b"), "a/b");
+        assert_eq!(conf.local_topic("a/b"), "a/b");
+
+        conf.remote_topic_prefix = Some("edge1/".to_string());
+        conf.local_topic_prefix = Some("cloud/".to_string());
+        assert_eq!(conf.remote_topic("a/b"), "edge1/a/b");
+        assert_eq!(conf.local_topic("a/b"), "cloud/a/b");
+        assert_eq!(conf.client_id("c1"), "robustmq_bridge_c1");
+    }
+
+    #[test]
+    fn qos_and_backoff_test() {
+        let mut conf = config("127.0.0.1:1883");
+        assert_eq!(conf.map_qos(2), 1);
+        assert_eq!(conf.map_qos(0), 0);
+        conf.max_qos = Some(2);
+        assert_eq!(conf.map_qos(2), 2);
+
+        assert_eq!(conf.reconnect_interval_ms(0), 1000);
+        assert_eq!(conf.reconnect_interval_ms(3), 8000);
+        assert_eq!(conf.reconnect_interval_ms(100), 60000);
+    }
+
+    #[test]
+    fn deserialize_test() {
+        let conf: MqttBridgeConnectorConfig = serde_json::from_str(
+            r#"{"server": "cloud:1883", "direction": "ingress", "remote_topic_filters": ["a/#"]}"#,
+        )
+        .unwrap();
+        assert_eq!(conf.direction, MqttBridgeDirection::Ingress);
+        assert_eq!(conf.host_port().unwrap(), ("cloud".to_string(), 1883));
+    }
+}
@@ -0,0 +1,240 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::connector::ConnectorDeliveryPolicy;
+use common_base::error::common::CommonError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+pub const DEFAULT_WEBHOOK_SIGNATURE_HEADER: &str = "X-RobustMQ-Signature";
+
+const DEFAULT_BATCH_SIZE: u64 = 1;
+const DEFAULT_BATCH_INTERVAL_MS: u64 = 100;
+const DEFAULT_TIMEOUT_MS: u64 = 5000;
+// Timeouts and throttling are worth retrying, other 4xx responses will not change on resend
+const DEFAULT_RETRY_STATUS_CODES: [u16; 2] = [408, 429];
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum WebhookHttpMethod {
+    #[default]
+    Post,
+    Put,
+    Patch,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum WebhookStatusAction {
+    // The request was accepted, the batch is acknowledged
+    Success,
+    // Resend the batch after a backoff
+    Retry,
+    // The endpoint will not accept the batch however often it is sent
+    Reject,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
+pub struct WebhookConnectorConfig {
+    // Request URL, may contain ${topic}, ${clientid}, ${payload}, ${timestamp} and ${qos}
+    pub url: String,
+    #[serde(default)]
+    pub method: WebhookHttpMethod,
+    // Header values are templates as well
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    // Body of a single message, defaults to a JSON object with all message fields
+    pub body_template: Option<String>,
+    // Messages sent in one request, a batch is sent as a JSON array
+    pub batch_size: Option<u64>,
+    // Longest time a partial batch waits for more messages
+    pub batch_interval_ms: Option<u64>,
+    pub timeout_ms: Option<u64>,
+    // Retry settings of webhook connectors created before the connector delivery
+    // policy, they are moved into the policy when the connector is loaded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_retries: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_min_interval_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_max_interval_ms: Option<u64>,
+    // Non-2xx status codes that are retried, 5xx responses are always retried
+    pub retry_status_codes: Option<Vec<u16>>,
+    // When set, the body is signed with HMAC-SHA256 and the hex digest is sent in the signature header
+    pub hmac_secret: Option<String>,
+    pub signature_header: Option<String>,
+}
+
+impl WebhookConnectorConfig {
+    pub fn validate(&self) -> Result<(), CommonError> {
+        if self.url.is_empty() {
+            return Err(CommonError::ParameterCannotBeNull("url".to_string()));
+        }
+
+        if !self.url.starts_with("http://") && !self.url.starts_with("https://") {
+            return Err(CommonError::InvalidParameterFormat(
+                "url".to_string(),
+                self.url.clone(),
+            ));
+        }
+
+        if self.batch_size == Some(0) {
+            return Err(CommonError::InvalidParameterFormat(
+                "batch_size".to_string(),
+                "0".to_string(),
+            ));
+        }
+
+        if self
+            .hmac_secret
+            .as_ref()
+            .is_some_and(|secret| secret.is_empty())
+        {
+            return Err(CommonError::ParameterCannotBeNull(
+                "hmac_secret".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn has_retry_settings(&self) -> bool {
+        self.max_retries.is_some()
+            || self.retry_min_interval_ms.is_some()
+            || self.retry_max_interval_ms.is_some()
+    }
+
+    // The retry settings of the config take the place of the policy's
+    pub fn apply_retry_settings(&self, policy: &mut ConnectorDeliveryPolicy) {
+        if let Some(max_retries) = self.max_retries {
+            policy.max_retries = max_retries;
+        }
+        if let Some(interval) = self.retry_min_interval_ms {
+            policy.retry_min_interval_ms = interval;
+        }
+        if let Some(interval) = self.retry_max_interval_ms {
+            policy.retry_max_interval_ms = interval;
+        }
+    }
+
+    pub fn batch_size(&self) -> u64 {
+        self.batch_size.unwrap_or(DEFAULT_BATCH_SIZE)
+    }
+
+    pub fn batch_interval_ms(&self) -> u64 {
+        self.batch_interval_ms.unwrap_or(DEFAULT_BATCH_INTERVAL_MS)
+    }
+
+    pub fn timeout_ms(&self) -> u64 {
+        self.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS)
+    }
+
+    pub fn signature_header(&self) -> String {
+        self.signature_header
+            .clone()
+            .unwrap_or(DEFAULT_WEBHOOK_SIGNATURE_HEADER.to_string())
+    }
+
+    pub fn status_action(&self, status: u16) -> WebhookStatusAction {
+        if (200..300).contains(&status) {
+            return WebhookStatusAction::Success;
+        }
+
+        if status >= 500 {
+            return WebhookStatusAction::Retry;
+        }
+
+        let retryable = match &self.retry_status_codes {
+            Some(codes) => codes.contains(&status),
+            None => DEFAULT_RETRY_STATUS_CODES.contains(&status),
+        };
+        if retryable {
+            WebhookStatusAction::Retry
+        } else {
+            WebhookStatusAction::Reject
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(url: &str) -> WebhookConnectorConfig {
+        WebhookConnectorConfig {
+            url: url.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn validate_test() {
+        assert!(config("http://127.0.0.1:8080/hook").validate().is_ok());
+        assert!(config("https://example.com/${topic}").validate().is_ok());
+        assert!(config("").validate().is_err());
+        assert!(config("127.0.0.1:8080/hook").validate().is_err());
+
+        let mut conf = config("http://127.0.0.1:8080/hook");
+        conf.batch_size = Some(0);
+        assert!(conf.validate().is_err());
+        conf.batch_size = Some(10);
+        conf.hmac_secret = Some("".to_string());
+        assert!(conf.validate().is_err());
+    }
+
+    #[test]
+    fn status_action_test() {
+        let mut conf = config("http://127.0.0.1:8080/hook");
+        assert_eq!(conf.status_action(200), WebhookStatusAction::Success);
+        assert_eq!(conf.status_action(204), WebhookStatusAction::Success);
+        assert_eq!(conf.status_action(503), WebhookStatusAction::Retry);
+        assert_eq!(conf.status_action(429), WebhookStatusAction::Retry);
+        assert_eq!(conf.status_action(400), WebhookStatusAction::Reject);
+        assert_eq!(conf.status_action(404), WebhookStatusAction::Reject);
+
+        conf.retry_status_codes = Some(vec![404]);
+        assert_eq!(conf.status_action(404), WebhookStatusAction::Retry);
+        assert_eq!(conf.status_action(429), WebhookStatusAction::Reject);
+        assert_eq!(conf.status_action(500), WebhookStatusAction::Retry);
+    }
+
+    #[test]
+    fn deserialize_test() {
+        let conf: WebhookConnectorConfig = serde_json::from_str(
+            r#"{"url": "http://127.0.0.1/hook", "method": "PUT", "headers": {"X-Topic": "${topic}"}}"#,
+        )
+        .unwrap();
+        assert_eq!(conf.method, WebhookHttpMethod::Put);
+        assert_eq!(conf.headers.get("X-Topic").unwrap(), "${topic}");
+        assert_eq!(conf.batch_size(), 1);
+        assert_eq!(conf.signature_header(), DEFAULT_WEBHOOK_SIGNATURE_HEADER);
+        assert!(!conf.has_retry_settings());
+    }
+
+    #[test]
+    fn retry_settings_test() {
+        let conf: WebhookConnectorConfig = serde_json::from_str(
+            r#"{"url": "http://127.0.0.1/hook", "max_retries": 5, "retry_min_interval_ms": 200}"#,
+        )
+        .unwrap();
+        assert!(conf.has_retry_settings());
+
+        let mut policy = ConnectorDeliveryPolicy::default();
+        conf.apply_retry_settings(&mut policy);
+        assert_eq!(policy.max_retries, 5);
+        assert_eq!(policy.retry_min_interval_ms, 200);
+        assert_eq!(
+            policy.retry_max_interval_ms,
+            ConnectorDeliveryPolicy::default().retry_max_interval_ms
+        );
+    }
+}
@@ -22,14 +22,57 @@ pub struct MQTTConnector {
     pub connector_name: String,
     pub connector_type: ConnectorType,
     pub config: String,
+    // Topic the connector is bound to, may be empty when topic filters are set
     pub topic_id: String,
+    // MQTT topic filters, every topic matching one of them is read as well
+    #[serde(default)]
+    pub topic_filters: Vec<String>,
     pub status: MQTTStatus,
     pub broker_id: Option<u64>,
+    #[serde(default)]
+    pub delivery_policy: ConnectorDeliveryPolicy,
     pub create_time: u64,
     pub update_time: u64,
 }
 
+// How the connector runtime handles records the target system does not accept
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct ConnectorDeliveryPolicy {
+    // Retries of a failed batch before its records are sent one by one
+    pub max_retries: u32,
+    pub retry_min_interval_ms: u64,
+    pub retry_max_interval_ms: u64,
+    // Topic receiving records that keep failing, they are discarded when it is not set
+    pub dead_letter_topic: Option<String>,
+}
+
+impl Default for ConnectorDeliveryPolicy {
+    fn default() -> Self {
+        ConnectorDeliveryPolicy {
+            max_retries: 3,
+            retry_min_interval_ms: 1000,
+            retry_max_interval_ms: 30000,
+            dead_letter_topic: None,
+        }
+    }
+}
+
+impl ConnectorDeliveryPolicy {
+    // Exponential backoff between retries, starting at the minimum interval
+    pub fn retry_interval_ms(&self, attempt: u32) -> u64 {
+        let max = self.retry_max_interval_ms.max(self.retry_min_interval_ms);
+        self.retry_min_interval_ms
+            .saturating_mul(1u64 << attempt.min(32))
+            .min(max)
+    }
+}
+
 impl MQTTConnector {
+    pub fn has_source_topic(&self) -> bool {
+        !self.topic_id.is_empty() || !self.topic_filters.is_empty()
+    }
+
     pub fn encode(&self) -> Vec<u8> {
         serde_json::to_vec(&self).unwrap()
     }
@@ -38,3 +81,29 @@ pub fn decode(data: &[u8]) -> Self {
         serde_json::from_slice(data).unwrap()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delivery_policy_test() {
+        let policy = ConnectorDeliveryPolicy::default();
+        assert_eq!(policy.retry_interval_ms(0), 1000);
+        assert_eq!(policy.retry_interval_ms(3), 8000);
+        assert_eq!(policy.retry_interval_ms(100), 30000);
+
+        // Connectors saved before the policy existed decode with the defaults
+        let connector = MQTTConnector::decode(
+            br#"{"cluster_name":"c","connector_name":"n","connector_type":"Kafka","config":"{}","topic_id":"t","status":"Idle","broker_id":null,"create_time":0,"update_time":0}"#,
+        );
+        assert_eq!(connector.delivery_policy, policy);
+        assert!(connector.topic_filters.is_empty());
+        assert!(connector.has_source_topic());
+
+        let policy: ConnectorDeliveryPolicy =
+            serde_json::from_str(r#"{"dead_letter_topic": "dlq/orders"}"#).unwrap();
+        assert_eq!(policy.max_retries, 3);
+        assert_eq!(policy.dead_letter_topic, Some("dlq/orders".to_string()));
+    }
+}
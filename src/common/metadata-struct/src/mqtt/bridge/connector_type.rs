@@ -24,6 +24,8 @@ pub enum ConnectorType {
     GreptimeDB,
     Pulsar,
     Postgres,
+    Mqtt,
+    Webhook,
 }
 
 pub const CONNECTOR_TYPE_FILE: &str = "file";
@@ -31,6 +33,8 @@ pub enum ConnectorType {
 pub const CONNECTOR_TYPE_GREPTIMEDB: &str = "greptime";
 pub const CONNECTOR_TYPE_PULSAR: &str = "pulsar";
 pub const CONNECTOR_TYPE_POSTGRES: &str = "postgres";
+pub const CONNECTOR_TYPE_MQTT: &str = "mqtt";
+pub const CONNECTOR_TYPE_WEBHOOK: &str = "webhook";
 
 impl Display for ConnectorType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -59,5 +63,13 @@ pub fn connector_type_for_string(connector_type: String) -> Result<ConnectorType
         return Ok(ConnectorType::Postgres);
     }
 
+    if CONNECTOR_TYPE_MQTT == connector_type {
+        return Ok(ConnectorType::Mqtt);
+    }
+
+    if CONNECTOR_TYPE_WEBHOOK == connector_type {
+        return Ok(ConnectorType::Webhook);
+    }
+
     Err(CommonError::IneligibleConnectorType(connector_type))
 }
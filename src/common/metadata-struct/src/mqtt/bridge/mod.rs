@@ -15,8 +15,10 @@
 pub mod config_greptimedb;
 pub mod config_kafka;
 pub mod config_local_file;
+pub mod config_mqtt;
 pub mod config_postgres;
 pub mod config_pulsar;
+pub mod config_webhook;
 pub mod connector;
 pub mod connector_type;
 pub mod status;
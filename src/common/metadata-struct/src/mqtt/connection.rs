@@ -51,6 +51,9 @@ pub struct MQTTConnection {
     pub sender_qos_message: Arc<AtomicIsize>,
     // Time when the connection was created
     pub create_time: u64,
+    // Topic prefix of the listener the connection was accepted on
+    #[serde(default)]
+    pub mountpoint: Option<String>,
 }
 
 pub struct ConnectionConfig {
@@ -89,6 +92,10 @@ pub fn login_success(&mut self, user_name: String) {
         self.login_user = user_name;
     }
 
+    pub fn set_mountpoint(&mut self, mountpoint: Option<String>) {
+        self.mountpoint = mountpoint.filter(|mountpoint| !mountpoint.is_empty());
+    }
+
     pub fn is_response_problem_info(&self) -> bool {
         self.request_problem_info == 1
     }
@@ -14,6 +14,7 @@
 
 use bytes::Bytes;
 use common_base::error::common::CommonError;
+use common_base::telemetry::trace::is_trace_context_key;
 use common_base::tools::now_second;
 use protocol::mqtt::common::{Publish, PublishProperties, QoS};
 use serde::{Deserialize, Serialize};
@@ -110,7 +111,17 @@ pub fn build_record(
         let msg =
             MqttMessage::build_message(client_id, publish, publish_properties, expiry_interval);
         match serde_json::to_vec(&msg) {
-            Ok(data) => Some(Record::build_byte(data)),
+            Ok(data) => {
+                // Carry the trace context in the record header so connectors can continue the trace
+                let mut record = Record::build_byte(data);
+                record.set_trace_context(
+                    msg.user_properties
+                        .into_iter()
+                        .filter(|(k, _)| is_trace_context_key(k))
+                        .collect(),
+                );
+                Some(record)
+            }
 
             Err(e) => {
                 error!("Message encoding failed, error message :{}", e.to_string());
@@ -260,6 +271,37 @@ fn test_build_record() {
         assert_eq!(decoded.payload, publish.payload);
     }
 
+    #[test]
+    fn test_build_record_trace_context() {
+        let traceparent = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let publish = Publish {
+            dup: false,
+            qos: QoS::AtLeastOnce,
+            p_kid: 1,
+            retain: false,
+            topic: Bytes::from("test/topic"),
+            payload: Bytes::from("test message"),
+        };
+        let properties = PublishProperties {
+            user_properties: vec![
+                ("k1".to_string(), "v1".to_string()),
+                ("traceparent".to_string(), traceparent.to_string()),
+            ],
+            ..Default::default()
+        };
+
+        let mut record =
+            MqttMessage::build_record("test-client", &publish, &Some(properties), 0).unwrap();
+        assert_eq!(record.trace_context(), vec![("traceparent", traceparent)]);
+
+        record.set_trace_context(vec![("traceparent".to_string(), "new".to_string())]);
+        assert_eq!(record.header.len(), 1);
+        assert_eq!(record.trace_context(), vec![("traceparent", "new")]);
+
+        let record = MqttMessage::build_record("test-client", &publish, &None, 0).unwrap();
+        assert!(record.header.is_empty());
+    }
+
     #[test]
     fn test_encode_decode() {
         let msg = MqttMessage {
@@ -18,6 +18,8 @@
 pub mod lastwill;
 pub mod message;
 pub mod node_extend;
+pub mod rule;
+pub mod scheduled_publish;
 pub mod session;
 pub mod subscribe_data;
 pub mod topic;
@@ -27,10 +27,17 @@ pub struct MqttNodeExtend {
     pub websocket_addr: String,
     pub websockets_addr: String,
     pub quic_addr: String,
+    // Set while the broker is being drained, it takes no new connections or work.
+    #[serde(default)]
+    pub draining: bool,
 }
 
 impl NodeExtend {
     pub fn encode(&self) -> String {
         serde_json::to_string(&self).unwrap()
     }
+
+    pub fn decode(data: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(data)
+    }
 }
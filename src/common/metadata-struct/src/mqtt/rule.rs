@@ -0,0 +1,63 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use protocol::mqtt::common::QoS;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct MqttRule {
+    pub rule_name: String,
+    pub sql: String,
+    pub actions: Vec<MqttRuleAction>,
+    pub enable: bool,
+    pub description: String,
+    pub create_time: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MqttRuleAction {
+    // Publish the rule output as a new message on the topic
+    Republish {
+        topic: String,
+        qos: QoS,
+        retain: bool,
+    },
+    // Hand the rule output to an existing connector
+    Connector {
+        connector_name: String,
+    },
+    // Discard the original message instead of storing it
+    Drop,
+}
+
+impl MqttRule {
+    pub fn encode(&self) -> Vec<u8> {
+        serde_json::to_vec(&self).unwrap()
+    }
+}
+
+// A single rule change pushed to every broker. Each rule is stored under its
+// own key, so brokers apply changes one rule at a time.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MqttRuleChange {
+    Set { rule: MqttRule },
+    Delete { rule_name: String },
+}
+
+impl MqttRuleChange {
+    pub fn encode(&self) -> Vec<u8> {
+        serde_json::to_vec(&self).unwrap()
+    }
+}
@@ -0,0 +1,46 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use protocol::mqtt::common::QoS;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct MqttScheduledPublish {
+    pub name: String,
+    pub topic_name: String,
+    pub payload: String,
+    pub qos: QoS,
+    pub retain: bool,
+    pub schedule: MqttPublishSchedule,
+    // IANA name or UTC offset the cron expression is evaluated in
+    pub timezone: String,
+    // No firing is published after this time, in seconds
+    pub end_time: Option<u64>,
+    pub create_time: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MqttPublishSchedule {
+    // Five field cron expression
+    Cron { expression: String },
+    // Fixed interval counted from the create time
+    Interval { seconds: u64 },
+}
+
+impl MqttScheduledPublish {
+    pub fn encode(&self) -> Vec<u8> {
+        serde_json::to_vec(&self).unwrap()
+    }
+}
@@ -14,6 +14,8 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::mqtt::node_extend::NodeExtend;
+
 #[derive(Default, Clone, Debug, Serialize, Deserialize)]
 pub struct BrokerNode {
     pub cluster_name: String,
@@ -30,4 +32,37 @@ impl BrokerNode {
     pub fn encode(&self) -> Vec<u8> {
         serde_json::to_vec(&self).unwrap()
     }
+
+    // Only brokers carry the mqtt extend, other nodes are never draining.
+    pub fn is_draining(&self) -> bool {
+        NodeExtend::decode(&self.extend)
+            .map(|extend| extend.mqtt.draining)
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BrokerNode;
+    use crate::mqtt::node_extend::{MqttNodeExtend, NodeExtend};
+
+    #[test]
+    fn is_draining_test() {
+        let mut node = BrokerNode::default();
+        assert!(!node.is_draining());
+
+        let mut extend = NodeExtend {
+            mqtt: MqttNodeExtend::default(),
+        };
+        node.extend = extend.encode();
+        assert!(!node.is_draining());
+
+        extend.mqtt.draining = true;
+        node.extend = extend.encode();
+        assert!(node.is_draining());
+
+        // registered before the drain flag existed
+        node.extend = r#"{"mqtt":{"grpc_addr":"","mqtt_addr":"","mqtts_addr":"","websocket_addr":"","websockets_addr":"","quic_addr":""}}"#.to_string();
+        assert!(!node.is_draining());
+    }
 }
@@ -102,6 +102,14 @@ macro_rules! counter_metric_get {
     }};
 }
 
+#[macro_export]
+macro_rules! counter_metric_remove {
+    ($family:ident,$label:ident) => {
+        let family = $family.clone();
+        family.write().unwrap().remove(&$label);
+    };
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -118,6 +118,14 @@ macro_rules! gauge_metric_set {
     };
 }
 
+#[macro_export]
+macro_rules! gauge_metric_remove {
+    ($family:ident,$label:ident) => {
+        let family = $family.clone();
+        family.write().unwrap().remove(&$label);
+    };
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -171,6 +171,14 @@ macro_rules! histogram_metric_observe {
     }};
 }
 
+#[macro_export]
+macro_rules! histogram_metric_remove {
+    ($family:ident, $label:ident) => {
+        let family = $family.clone();
+        family.write().unwrap().remove(&$label);
+    };
+}
+
 /// Default bucket configuration for request duration metrics (exponential buckets)
 /// start=1.0, factor=2.0, length=10 generates buckets: [1, 2, 4, 8, 16, 32, 64, 128, 256, 512]
 pub const DEFAULT_REQUEST_DURATION_BUCKETS: BucketType = BucketType::ExponentialBuckets {
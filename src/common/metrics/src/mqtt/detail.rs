@@ -0,0 +1,245 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Per-topic and per-client series. They are only recorded for the topics and
+// clients the broker tracks, which keeps their cardinality bounded, and are
+// removed again when the broker stops tracking them.
+
+use crate::{
+    counter_metric_inc, counter_metric_inc_by, counter_metric_remove, gauge_metric_inc_by,
+    gauge_metric_remove, histogram_metric_observe, histogram_metric_remove,
+    register_counter_metric, register_gauge_metric,
+    register_histogram_metric_ms_with_default_buckets,
+};
+use prometheus_client::encoding::EncodeLabelSet;
+
+#[derive(Eq, Hash, Clone, EncodeLabelSet, Debug, PartialEq)]
+struct TopicLabel {
+    topic: String,
+}
+
+#[derive(Eq, Hash, Clone, EncodeLabelSet, Debug, PartialEq)]
+struct ClientLabel {
+    client_id: String,
+}
+
+register_counter_metric!(
+    MQTT_TOPIC_MESSAGES_IN,
+    "mqtt_topic_messages_in",
+    "Number of messages published to a tracked topic",
+    TopicLabel
+);
+
+register_counter_metric!(
+    MQTT_TOPIC_MESSAGES_OUT,
+    "mqtt_topic_messages_out",
+    "Number of messages of a tracked topic delivered to subscribers",
+    TopicLabel
+);
+
+register_counter_metric!(
+    MQTT_TOPIC_BYTES_IN,
+    "mqtt_topic_bytes_in",
+    "Payload bytes published to a tracked topic",
+    TopicLabel
+);
+
+register_counter_metric!(
+    MQTT_TOPIC_BYTES_OUT,
+    "mqtt_topic_bytes_out",
+    "Payload bytes of a tracked topic delivered to subscribers",
+    TopicLabel
+);
+
+register_counter_metric!(
+    MQTT_TOPIC_MESSAGES_DROPPED,
+    "mqtt_topic_messages_dropped",
+    "Number of messages of a tracked topic that were dropped",
+    TopicLabel
+);
+
+register_gauge_metric!(
+    MQTT_TOPIC_INFLIGHT,
+    "mqtt_topic_inflight",
+    "Number of QoS 1/2 messages of a tracked topic waiting for acknowledgement",
+    TopicLabel
+);
+
+register_histogram_metric_ms_with_default_buckets!(
+    MQTT_TOPIC_DELIVERY_LATENCY,
+    "mqtt_topic_delivery_latency_ms",
+    "Time to deliver a message of a tracked topic to a subscriber, acknowledgement included",
+    TopicLabel
+);
+
+register_counter_metric!(
+    MQTT_CLIENT_MESSAGES_IN,
+    "mqtt_client_messages_in",
+    "Number of messages published by a tracked client",
+    ClientLabel
+);
+
+register_counter_metric!(
+    MQTT_CLIENT_MESSAGES_OUT,
+    "mqtt_client_messages_out",
+    "Number of messages delivered to a tracked client",
+    ClientLabel
+);
+
+register_counter_metric!(
+    MQTT_CLIENT_BYTES_IN,
+    "mqtt_client_bytes_in",
+    "Payload bytes published by a tracked client",
+    ClientLabel
+);
+
+register_counter_metric!(
+    MQTT_CLIENT_BYTES_OUT,
+    "mqtt_client_bytes_out",
+    "Payload bytes delivered to a tracked client",
+    ClientLabel
+);
+
+register_counter_metric!(
+    MQTT_CLIENT_MESSAGES_DROPPED,
+    "mqtt_client_messages_dropped",
+    "Number of messages from or to a tracked client that were dropped",
+    ClientLabel
+);
+
+register_gauge_metric!(
+    MQTT_CLIENT_INFLIGHT,
+    "mqtt_client_inflight",
+    "Number of QoS 1/2 messages to a tracked client waiting for acknowledgement",
+    ClientLabel
+);
+
+register_histogram_metric_ms_with_default_buckets!(
+    MQTT_CLIENT_DELIVERY_LATENCY,
+    "mqtt_client_delivery_latency_ms",
+    "Time to deliver a message to a tracked client, acknowledgement included",
+    ClientLabel
+);
+
+pub fn record_topic_messages_in(topic: &str, bytes: u64) {
+    let label = TopicLabel {
+        topic: topic.to_string(),
+    };
+    counter_metric_inc!(MQTT_TOPIC_MESSAGES_IN, label);
+    counter_metric_inc_by!(MQTT_TOPIC_BYTES_IN, label, bytes);
+}
+
+pub fn record_topic_messages_out(topic: &str, bytes: u64, latency_ms: f64) {
+    let label = TopicLabel {
+        topic: topic.to_string(),
+    };
+    counter_metric_inc!(MQTT_TOPIC_MESSAGES_OUT, label);
+    counter_metric_inc_by!(MQTT_TOPIC_BYTES_OUT, label, bytes);
+    histogram_metric_observe!(MQTT_TOPIC_DELIVERY_LATENCY, latency_ms, label);
+}
+
+pub fn record_topic_messages_dropped(topic: &str) {
+    let label = TopicLabel {
+        topic: topic.to_string(),
+    };
+    counter_metric_inc!(MQTT_TOPIC_MESSAGES_DROPPED, label);
+}
+
+pub fn record_topic_inflight_add(topic: &str, value: i64) {
+    let label = TopicLabel {
+        topic: topic.to_string(),
+    };
+    gauge_metric_inc_by!(MQTT_TOPIC_INFLIGHT, label, value);
+}
+
+pub fn remove_topic_metrics(topic: &str) {
+    let label = TopicLabel {
+        topic: topic.to_string(),
+    };
+    counter_metric_remove!(MQTT_TOPIC_MESSAGES_IN, label);
+    counter_metric_remove!(MQTT_TOPIC_MESSAGES_OUT, label);
+    counter_metric_remove!(MQTT_TOPIC_BYTES_IN, label);
+    counter_metric_remove!(MQTT_TOPIC_BYTES_OUT, label);
+    counter_metric_remove!(MQTT_TOPIC_MESSAGES_DROPPED, label);
+    gauge_metric_remove!(MQTT_TOPIC_INFLIGHT, label);
+    histogram_metric_remove!(MQTT_TOPIC_DELIVERY_LATENCY, label);
+}
+
+pub fn record_client_messages_in(client_id: &str, bytes: u64) {
+    let label = ClientLabel {
+        client_id: client_id.to_string(),
+    };
+    counter_metric_inc!(MQTT_CLIENT_MESSAGES_IN, label);
+    counter_metric_inc_by!(MQTT_CLIENT_BYTES_IN, label, bytes);
+}
+
+pub fn record_client_messages_out(client_id: &str, bytes: u64, latency_ms: f64) {
+    let label = ClientLabel {
+        client_id: client_id.to_string(),
+    };
+    counter_metric_inc!(MQTT_CLIENT_MESSAGES_OUT, label);
+    counter_metric_inc_by!(MQTT_CLIENT_BYTES_OUT, label, bytes);
+    histogram_metric_observe!(MQTT_CLIENT_DELIVERY_LATENCY, latency_ms, label);
+}
+
+pub fn record_client_messages_dropped(client_id: &str) {
+    let label = ClientLabel {
+        client_id: client_id.to_string(),
+    };
+    counter_metric_inc!(MQTT_CLIENT_MESSAGES_DROPPED, label);
+}
+
+pub fn record_client_inflight_add(client_id: &str, value: i64) {
+    let label = ClientLabel {
+        client_id: client_id.to_string(),
+    };
+    gauge_metric_inc_by!(MQTT_CLIENT_INFLIGHT, label, value);
+}
+
+pub fn remove_client_metrics(client_id: &str) {
+    let label = ClientLabel {
+        client_id: client_id.to_string(),
+    };
+    counter_metric_remove!(MQTT_CLIENT_MESSAGES_IN, label);
+    counter_metric_remove!(MQTT_CLIENT_MESSAGES_OUT, label);
+    counter_metric_remove!(MQTT_CLIENT_BYTES_IN, label);
+    counter_metric_remove!(MQTT_CLIENT_BYTES_OUT, label);
+    counter_metric_remove!(MQTT_CLIENT_MESSAGES_DROPPED, label);
+    gauge_metric_remove!(MQTT_CLIENT_INFLIGHT, label);
+    histogram_metric_remove!(MQTT_CLIENT_DELIVERY_LATENCY, label);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::server::metrics_register_default;
+    use prometheus_client::encoding::text::encode;
+
+    #[test]
+    fn remove_topic_metrics_test() {
+        let topic = "detail/metrics/remove";
+        record_topic_messages_in(topic, 10);
+        record_topic_messages_out(topic, 10, 3.0);
+        record_topic_inflight_add(topic, 1);
+
+        let mut buffer = String::new();
+        encode(&mut buffer, &metrics_register_default()).unwrap();
+        assert!(buffer.contains(topic));
+
+        remove_topic_metrics(topic);
+        let mut buffer = String::new();
+        encode(&mut buffer, &metrics_register_default()).unwrap();
+        assert!(!buffer.contains(topic));
+    }
+}
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 pub mod auth;
+pub mod detail;
 pub mod event;
 pub mod packets;
 pub mod publish;
@@ -485,6 +485,10 @@ fn calc_mqtt_packet_test() {
             connection_id: 100,
             protocol: Some(RobustMQProtocol::MQTT3),
             create_time: now_second(),
+            proxy_addr: None,
+            proxy_tls: None,
+            listener: None,
+            client_cert_verified: false,
         };
         let ty = NetworkConnectionType::Tcp;
         record_mqtt_packet_received_metrics(&nc, &mp, &ty);
@@ -12,12 +12,25 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{counter_metric_inc, register_counter_metric};
+use crate::{
+    counter_metric_inc, counter_metric_inc_by, gauge_metric_remove, gauge_metric_set,
+    register_counter_metric, register_gauge_metric,
+};
 use prometheus_client::encoding::EncodeLabelSet;
 
 #[derive(Eq, Hash, Clone, EncodeLabelSet, Debug, PartialEq)]
 pub struct NetworkLabel {}
 
+#[derive(Eq, Hash, Clone, EncodeLabelSet, Debug, PartialEq)]
+pub struct ClientLabel {
+    pub client_id: String,
+}
+
+#[derive(Eq, Hash, Clone, EncodeLabelSet, Debug, PartialEq)]
+pub struct OfflineQueueDropLabel {
+    pub policy: String,
+}
+
 register_counter_metric!(
     MQTT_SESSION_CREATED,
     "mqtt_session_created",
@@ -32,6 +45,27 @@ pub struct NetworkLabel {}
     NetworkLabel
 );
 
+register_gauge_metric!(
+    MQTT_OFFLINE_QUEUE_MESSAGES,
+    "mqtt_offline_queue_messages",
+    "Number of messages queued for an offline session",
+    ClientLabel
+);
+
+register_gauge_metric!(
+    MQTT_OFFLINE_QUEUE_BYTES,
+    "mqtt_offline_queue_bytes",
+    "Number of bytes queued for an offline session",
+    ClientLabel
+);
+
+register_counter_metric!(
+    MQTT_OFFLINE_QUEUE_DROPPED,
+    "mqtt_offline_queue_dropped",
+    "Number of messages dropped because an offline queue overflowed",
+    OfflineQueueDropLabel
+);
+
 pub fn record_mqtt_session_created() {
     let label = NetworkLabel {};
     counter_metric_inc!(MQTT_SESSION_CREATED, label);
@@ -41,3 +75,26 @@ pub fn record_mqtt_session_deleted() {
     let label = NetworkLabel {};
     counter_metric_inc!(MQTT_SESSION_DELETED, label);
 }
+
+pub fn record_offline_queue_depth(client_id: &str, messages: u64, bytes: u64) {
+    let label = ClientLabel {
+        client_id: client_id.to_string(),
+    };
+    gauge_metric_set!(MQTT_OFFLINE_QUEUE_MESSAGES, label, messages as i64);
+    gauge_metric_set!(MQTT_OFFLINE_QUEUE_BYTES, label, bytes as i64);
+}
+
+pub fn remove_offline_queue_depth(client_id: &str) {
+    let label = ClientLabel {
+        client_id: client_id.to_string(),
+    };
+    gauge_metric_remove!(MQTT_OFFLINE_QUEUE_MESSAGES, label);
+    gauge_metric_remove!(MQTT_OFFLINE_QUEUE_BYTES, label);
+}
+
+pub fn record_offline_queue_dropped(policy: &str, num: u64) {
+    let label = OfflineQueueDropLabel {
+        policy: policy.to_string(),
+    };
+    counter_metric_inc_by!(MQTT_OFFLINE_QUEUE_DROPPED, label, num);
+}
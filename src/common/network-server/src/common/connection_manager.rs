@@ -44,6 +44,8 @@ pub struct ConnectionManager {
     >,
     pub websocket_write_list: DashMap<u64, SplitSink<WebSocket, Message>>,
     pub quic_write_list: DashMap<u64, QuicFramedWriteStream>,
+    // (listener_name, connection_num)
+    pub listener_connections: DashMap<String, u64>,
 }
 
 impl ConnectionManager {
@@ -53,23 +55,67 @@ pub fn new(lock_max_try_mut_times: i32, lock_try_mut_sleep_time_ms: u64) -> Conn
         let tcp_tls_write_list = DashMap::with_capacity(64);
         let websocket_write_list = DashMap::with_capacity(64);
         let quic_write_list = DashMap::with_capacity(64);
+        let listener_connections = DashMap::with_capacity(8);
         ConnectionManager {
             connections,
             tcp_write_list,
             tcp_tls_write_list,
             websocket_write_list,
             quic_write_list,
+            listener_connections,
             lock_max_try_mut_times,
             lock_try_mut_sleep_time_ms,
         }
     }
 
+    // A connection of a listener must hold a slot from
+    // `try_reserve_listener_connection`, which is given back when it is closed.
     pub fn add_connection(&self, connection: NetworkConnection) -> u64 {
         let connection_id = connection.connection_id();
         self.connections.insert(connection_id, connection);
         connection_id
     }
 
+    // Takes a connection slot of the listener before the handshake starts, so
+    // that concurrent handshakes cannot together go over `max_connections`.
+    pub fn try_reserve_listener_connection(
+        &self,
+        listener: &str,
+        max_connections: Option<u64>,
+    ) -> bool {
+        let mut num = self
+            .listener_connections
+            .entry(listener.to_string())
+            .or_insert(0);
+        if max_connections.is_some_and(|max| *num >= max) {
+            return false;
+        }
+        *num += 1;
+        true
+    }
+
+    // Gives back a slot whose connection failed before it was added.
+    pub fn release_listener_connection(&self, listener: &str) {
+        if let Some(mut num) = self.listener_connections.get_mut(listener) {
+            *num = num.saturating_sub(1);
+        }
+    }
+
+    pub fn get_listener_connection_num(&self, listener: &str) -> u64 {
+        if let Some(num) = self.listener_connections.get(listener) {
+            return *num;
+        }
+        0
+    }
+
+    pub async fn close_listener_connect(&self, listener: &str) {
+        for (connect_id, connection) in self.connections.clone() {
+            if connection.listener.as_deref() == Some(listener) {
+                self.close_connect(connect_id).await;
+            }
+        }
+    }
+
     pub fn list_connect(&self) -> DashMap<u64, NetworkConnection> {
         self.connections.clone()
     }
@@ -82,6 +128,9 @@ pub async fn close_all_connect(&self) {
 
     pub async fn close_connect(&self, connection_id: u64) {
         if let Some((_, connection)) = self.connections.remove(&connection_id) {
+            if let Some(listener) = &connection.listener {
+                self.release_listener_connection(listener);
+            }
             connection.stop_connection().await;
         }
 
@@ -472,3 +521,33 @@ pub fn set_mqtt_connect_protocol(&self, connect_id: u64, protocol: u8) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn listener_connection_reserve_test() {
+        let manager = ConnectionManager::new(3, 10);
+        assert!(manager.try_reserve_listener_connection("l1", Some(2)));
+        assert!(manager.try_reserve_listener_connection("l1", Some(2)));
+        // Slots held by handshakes that have not finished count towards the limit
+        assert!(!manager.try_reserve_listener_connection("l1", Some(2)));
+        assert!(manager.try_reserve_listener_connection("l2", None));
+
+        manager.release_listener_connection("l1");
+        assert_eq!(manager.get_listener_connection_num("l1"), 1);
+
+        let mut connection = NetworkConnection::new(
+            NetworkConnectionType::Tcp,
+            "127.0.0.1:1883".parse().unwrap(),
+            None,
+        );
+        connection.set_listener("l1");
+        let connection_id = manager.add_connection(connection);
+        assert_eq!(manager.get_listener_connection_num("l1"), 1);
+
+        manager.close_connect(connection_id).await;
+        assert_eq!(manager.get_listener_connection_num("l1"), 0);
+    }
+}
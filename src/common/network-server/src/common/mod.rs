@@ -17,6 +17,7 @@
 pub mod handler;
 pub mod metric;
 pub mod packet;
+pub mod proxy_protocol;
 pub mod response;
 pub mod tcp_acceptor;
 pub mod tls_acceptor;
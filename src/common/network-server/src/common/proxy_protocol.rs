@@ -0,0 +1,412 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! HAProxy PROXY protocol (v1 text and v2 binary) header parser.
+//!
+//! The header is sent by an L4 load balancer as the very first bytes of the
+//! TCP stream, before any TLS handshake or MQTT packet. Only the header bytes
+//! are consumed from the stream, so the remaining bytes can be handed to the
+//! TLS acceptor or the codec unchanged.
+
+use common_base::error::common::CommonError;
+use common_config::broker::broker_config;
+use metadata_struct::connection::{NetworkConnection, ProxyTlsInfo};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::time::timeout;
+
+const V1_PREFIX: &[u8] = b"PROXY ";
+const V1_MAX_LEN: usize = 107;
+const V2_SIGNATURE: [u8; 12] = [
+    0x0D, 0x0A, 0x0D, 0x0A, 0x00, 0x0D, 0x0A, 0x51, 0x55, 0x49, 0x54, 0x0A,
+];
+
+const V2_CMD_LOCAL: u8 = 0x00;
+const V2_CMD_PROXY: u8 = 0x01;
+
+const V2_FAMILY_UNSPEC: u8 = 0x00;
+const V2_FAMILY_INET: u8 = 0x10;
+const V2_FAMILY_INET6: u8 = 0x20;
+const V2_FAMILY_UNIX: u8 = 0x30;
+
+const PP2_TYPE_ALPN: u8 = 0x01;
+const PP2_TYPE_AUTHORITY: u8 = 0x02;
+const PP2_TYPE_SSL: u8 = 0x20;
+const PP2_SUBTYPE_SSL_VERSION: u8 = 0x21;
+const PP2_SUBTYPE_SSL_CN: u8 = 0x22;
+const PP2_SUBTYPE_SSL_CIPHER: u8 = 0x23;
+const PP2_CLIENT_SSL: u8 = 0x01;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ProxyHeader {
+    /// Real client address. `None` for `LOCAL`/`UNKNOWN` headers, in which case
+    /// the peer address of the socket should be kept.
+    pub source: Option<SocketAddr>,
+    pub destination: Option<SocketAddr>,
+    pub tls: Option<ProxyTlsInfo>,
+}
+
+/// Read the PROXY protocol header from `stream` using the configured timeout.
+pub async fn accept_proxy_header<S>(stream: &mut S) -> Result<ProxyHeader, CommonError>
+where
+    S: AsyncRead + Unpin,
+{
+    let conf = broker_config();
+    read_proxy_header(
+        stream,
+        Duration::from_millis(conf.mqtt_server.proxy_protocol.header_timeout_ms),
+    )
+    .await
+}
+
+/// Apply a parsed header to the connection. `LOCAL`/`UNKNOWN` headers carry no
+/// address, so the socket peer address is kept for them.
+pub fn apply_proxy_header(connection: &mut NetworkConnection, header: ProxyHeader) {
+    if let Some(source) = header.source {
+        connection.set_proxy_info(source, header.tls);
+    }
+}
+
+/// Read and parse a PROXY protocol header from the beginning of `stream`.
+pub async fn read_proxy_header<S>(
+    stream: &mut S,
+    header_timeout: Duration,
+) -> Result<ProxyHeader, CommonError>
+where
+    S: AsyncRead + Unpin,
+{
+    match timeout(header_timeout, read_header(stream)).await {
+        Ok(res) => res,
+        Err(_) => Err(CommonError::InvalidProxyProtocolHeader(format!(
+            "header was not received within {}ms",
+            header_timeout.as_millis()
+        ))),
+    }
+}
+
+async fn read_header<S>(stream: &mut S) -> Result<ProxyHeader, CommonError>
+where
+    S: AsyncRead + Unpin,
+{
+    // The shortest valid header ("PROXY UNKNOWN\r\n") is longer than the v2
+    // signature, so reading the signature length never over-reads.
+    let mut prefix = [0u8; 12];
+    stream.read_exact(&mut prefix).await?;
+
+    if prefix == V2_SIGNATURE {
+        let mut head = [0u8; 4];
+        stream.read_exact(&mut head).await?;
+        let len = u16::from_be_bytes([head[2], head[3]]) as usize;
+        let mut payload = vec![0u8; len];
+        stream.read_exact(&mut payload).await?;
+        return parse_v2(head[0], head[1], &payload);
+    }
+
+    if !prefix.starts_with(V1_PREFIX) {
+        return Err(CommonError::InvalidProxyProtocolHeader(
+            "missing PROXY protocol signature".to_string(),
+        ));
+    }
+
+    let mut line = prefix.to_vec();
+    while !line.ends_with(b"\r\n") {
+        if line.len() >= V1_MAX_LEN {
+            return Err(CommonError::InvalidProxyProtocolHeader(
+                "v1 header exceeds 107 bytes".to_string(),
+            ));
+        }
+        line.push(stream.read_u8().await?);
+    }
+    parse_v1(&line)
+}
+
+/// Parse a complete v1 header line, including the trailing CRLF.
+pub fn parse_v1(line: &[u8]) -> Result<ProxyHeader, CommonError> {
+    let line = std::str::from_utf8(line)
+        .map_err(|e| CommonError::InvalidProxyProtocolHeader(e.to_string()))?;
+    let line = line.strip_suffix("\r\n").ok_or_else(|| {
+        CommonError::InvalidProxyProtocolHeader("v1 header is not terminated by CRLF".to_string())
+    })?;
+
+    let parts: Vec<&str> = line.split(' ').collect();
+    if parts.len() < 2 || parts[0] != "PROXY" {
+        return Err(CommonError::InvalidProxyProtocolHeader(line.to_string()));
+    }
+
+    match parts[1] {
+        "UNKNOWN" => Ok(ProxyHeader::default()),
+        "TCP4" | "TCP6" => {
+            if parts.len() != 6 {
+                return Err(CommonError::InvalidProxyProtocolHeader(line.to_string()));
+            }
+            let src_ip: IpAddr = parts[2].parse()?;
+            let dst_ip: IpAddr = parts[3].parse()?;
+            let is_v4 = parts[1] == "TCP4";
+            if src_ip.is_ipv4() != is_v4 || dst_ip.is_ipv4() != is_v4 {
+                return Err(CommonError::InvalidProxyProtocolHeader(line.to_string()));
+            }
+            let src_port: u16 = parts[4].parse()?;
+            let dst_port: u16 = parts[5].parse()?;
+            Ok(ProxyHeader {
+                source: Some(SocketAddr::new(src_ip, src_port)),
+                destination: Some(SocketAddr::new(dst_ip, dst_port)),
+                tls: None,
+            })
+        }
+        _ => Err(CommonError::InvalidProxyProtocolHeader(line.to_string())),
+    }
+}
+
+/// Parse the v2 header fields that follow the 12-byte signature.
+pub fn parse_v2(ver_cmd: u8, family: u8, payload: &[u8]) -> Result<ProxyHeader, CommonError> {
+    if ver_cmd >> 4 != 2 {
+        return Err(CommonError::InvalidProxyProtocolHeader(format!(
+            "unsupported v2 version {}",
+            ver_cmd >> 4
+        )));
+    }
+
+    match ver_cmd & 0x0F {
+        V2_CMD_LOCAL => return Ok(ProxyHeader::default()),
+        V2_CMD_PROXY => {}
+        cmd => {
+            return Err(CommonError::InvalidProxyProtocolHeader(format!(
+                "unsupported v2 command {cmd}"
+            )))
+        }
+    }
+
+    let (source, destination, addr_len) = match family & 0xF0 {
+        V2_FAMILY_INET => {
+            check_len(payload, 12)?;
+            let src = Ipv4Addr::new(payload[0], payload[1], payload[2], payload[3]);
+            let dst = Ipv4Addr::new(payload[4], payload[5], payload[6], payload[7]);
+            let src_port = u16::from_be_bytes([payload[8], payload[9]]);
+            let dst_port = u16::from_be_bytes([payload[10], payload[11]]);
+            (
+                Some(SocketAddr::new(IpAddr::V4(src), src_port)),
+                Some(SocketAddr::new(IpAddr::V4(dst), dst_port)),
+                12,
+            )
+        }
+        V2_FAMILY_INET6 => {
+            check_len(payload, 36)?;
+            let mut src = [0u8; 16];
+            let mut dst = [0u8; 16];
+            src.copy_from_slice(&payload[0..16]);
+            dst.copy_from_slice(&payload[16..32]);
+            let src_port = u16::from_be_bytes([payload[32], payload[33]]);
+            let dst_port = u16::from_be_bytes([payload[34], payload[35]]);
+            (
+                Some(SocketAddr::new(IpAddr::V6(Ipv6Addr::from(src)), src_port)),
+                Some(SocketAddr::new(IpAddr::V6(Ipv6Addr::from(dst)), dst_port)),
+                36,
+            )
+        }
+        V2_FAMILY_UNIX => {
+            check_len(payload, 216)?;
+            (None, None, 216)
+        }
+        V2_FAMILY_UNSPEC => (None, None, 0),
+        fam => {
+            return Err(CommonError::InvalidProxyProtocolHeader(format!(
+                "unsupported v2 address family {fam}"
+            )))
+        }
+    };
+
+    Ok(ProxyHeader {
+        source,
+        destination,
+        tls: parse_tlvs(&payload[addr_len..])?,
+    })
+}
+
+fn parse_tlvs(mut data: &[u8]) -> Result<Option<ProxyTlsInfo>, CommonError> {
+    let mut sni = None;
+    let mut alpn = None;
+    let mut tls = None;
+    while !data.is_empty() {
+        let (tlv_type, value, rest) = next_tlv(data)?;
+        match tlv_type {
+            PP2_TYPE_ALPN => alpn = Some(String::from_utf8_lossy(value).to_string()),
+            PP2_TYPE_AUTHORITY => sni = Some(String::from_utf8_lossy(value).to_string()),
+            PP2_TYPE_SSL => tls = parse_ssl_tlv(value)?,
+            _ => {}
+        }
+        data = rest;
+    }
+
+    Ok(tls.map(|mut info| {
+        info.sni = sni;
+        info.alpn = alpn;
+        info
+    }))
+}
+
+fn parse_ssl_tlv(value: &[u8]) -> Result<Option<ProxyTlsInfo>, CommonError> {
+    check_len(value, 5)?;
+    let client = value[0];
+    let verify = u32::from_be_bytes([value[1], value[2], value[3], value[4]]);
+    if client & PP2_CLIENT_SSL == 0 {
+        return Ok(None);
+    }
+
+    let mut info = ProxyTlsInfo {
+        client_cert_verified: verify == 0,
+        ..Default::default()
+    };
+    let mut data = &value[5..];
+    while !data.is_empty() {
+        let (sub_type, sub_value, rest) = next_tlv(data)?;
+        let sub_value = String::from_utf8_lossy(sub_value).to_string();
+        match sub_type {
+            PP2_SUBTYPE_SSL_VERSION => info.version = Some(sub_value),
+            PP2_SUBTYPE_SSL_CN => info.client_cn = Some(sub_value),
+            PP2_SUBTYPE_SSL_CIPHER => info.cipher = Some(sub_value),
+            _ => {}
+        }
+        data = rest;
+    }
+    Ok(Some(info))
+}
+
+fn next_tlv(data: &[u8]) -> Result<(u8, &[u8], &[u8]), CommonError> {
+    check_len(data, 3)?;
+    let len = u16::from_be_bytes([data[1], data[2]]) as usize;
+    check_len(data, 3 + len)?;
+    Ok((data[0], &data[3..3 + len], &data[3 + len..]))
+}
+
+fn check_len(data: &[u8], len: usize) -> Result<(), CommonError> {
+    if data.len() < len {
+        return Err(CommonError::InvalidProxyProtocolHeader(format!(
+            "expected at least {len} bytes, got {}",
+            data.len()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v2_inet_payload(tlvs: &[u8]) -> Vec<u8> {
+        let mut payload = vec![192, 168, 1, 10, 10, 0, 0, 1];
+        payload.extend_from_slice(&51234u16.to_be_bytes());
+        payload.extend_from_slice(&1883u16.to_be_bytes());
+        payload.extend_from_slice(tlvs);
+        payload
+    }
+
+    fn tlv(tlv_type: u8, value: &[u8]) -> Vec<u8> {
+        let mut data = vec![tlv_type];
+        data.extend_from_slice(&(value.len() as u16).to_be_bytes());
+        data.extend_from_slice(value);
+        data
+    }
+
+    #[test]
+    fn parse_v1_tcp4_test() {
+        let header = parse_v1(b"PROXY TCP4 192.168.1.10 10.0.0.1 51234 1883\r\n").unwrap();
+        assert_eq!(
+            header.source.unwrap(),
+            "192.168.1.10:51234".parse::<SocketAddr>().unwrap()
+        );
+        assert_eq!(
+            header.destination.unwrap(),
+            "10.0.0.1:1883".parse::<SocketAddr>().unwrap()
+        );
+
+        let header = parse_v1(b"PROXY TCP6 ::1 ::2 51234 1883\r\n").unwrap();
+        assert_eq!(
+            header.source.unwrap(),
+            "[::1]:51234".parse::<SocketAddr>().unwrap()
+        );
+
+        let header = parse_v1(b"PROXY UNKNOWN\r\n").unwrap();
+        assert!(header.source.is_none());
+
+        assert!(parse_v1(b"PROXY TCP4 ::1 ::2 51234 1883\r\n").is_err());
+        assert!(parse_v1(b"PROXY TCP4 192.168.1.10 10.0.0.1 51234\r\n").is_err());
+        assert!(parse_v1(b"PROXY TCP4 192.168.1.10 10.0.0.1 51234 1883").is_err());
+    }
+
+    #[test]
+    fn parse_v2_test() {
+        let header = parse_v2(0x21, 0x11, &v2_inet_payload(&[])).unwrap();
+        assert_eq!(
+            header.source.unwrap(),
+            "192.168.1.10:51234".parse::<SocketAddr>().unwrap()
+        );
+        assert!(header.tls.is_none());
+
+        let header = parse_v2(0x20, 0x00, &[]).unwrap();
+        assert!(header.source.is_none());
+
+        assert!(parse_v2(0x11, 0x11, &v2_inet_payload(&[])).is_err());
+        assert!(parse_v2(0x21, 0x11, &[1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn parse_v2_tls_tlv_test() {
+        let mut ssl = vec![PP2_CLIENT_SSL, 0, 0, 0, 0];
+        ssl.extend(tlv(PP2_SUBTYPE_SSL_VERSION, b"TLSv1.3"));
+        ssl.extend(tlv(PP2_SUBTYPE_SSL_CN, b"device-001"));
+        ssl.extend(tlv(PP2_SUBTYPE_SSL_CIPHER, b"TLS_AES_128_GCM_SHA256"));
+
+        let mut tlvs = tlv(PP2_TYPE_AUTHORITY, b"mqtt.example.com");
+        tlvs.extend(tlv(PP2_TYPE_SSL, &ssl));
+
+        let header = parse_v2(0x21, 0x11, &v2_inet_payload(&tlvs)).unwrap();
+        let tls = header.tls.unwrap();
+        assert_eq!(tls.version.unwrap(), "TLSv1.3");
+        assert_eq!(tls.client_cn.unwrap(), "device-001");
+        assert_eq!(tls.cipher.unwrap(), "TLS_AES_128_GCM_SHA256");
+        assert_eq!(tls.sni.unwrap(), "mqtt.example.com");
+        assert!(tls.client_cert_verified);
+    }
+
+    #[tokio::test]
+    async fn read_proxy_header_test() {
+        let mut data = b"PROXY TCP4 192.168.1.10 10.0.0.1 51234 1883\r\n".to_vec();
+        data.extend_from_slice(&[0x10, 0x00]);
+        let mut reader = data.as_slice();
+        let header = read_proxy_header(&mut reader, Duration::from_secs(1))
+            .await
+            .unwrap();
+        assert_eq!(header.source.unwrap().port(), 51234);
+        assert_eq!(reader, &[0x10, 0x00]);
+
+        let mut data = V2_SIGNATURE.to_vec();
+        let payload = v2_inet_payload(&[]);
+        data.extend_from_slice(&[0x21, 0x11]);
+        data.extend_from_slice(&(payload.len() as u16).to_be_bytes());
+        data.extend_from_slice(&payload);
+        data.push(0x10);
+        let mut reader = data.as_slice();
+        let header = read_proxy_header(&mut reader, Duration::from_secs(1))
+            .await
+            .unwrap();
+        assert_eq!(header.source.unwrap().port(), 51234);
+        assert_eq!(reader, &[0x10]);
+
+        let mut reader: &[u8] = &[0x10, 0x0c, 0x00, 0x04, b'M', b'Q', b'T', b'T', 4, 2, 0, 60];
+        assert!(read_proxy_header(&mut reader, Duration::from_secs(1))
+            .await
+            .is_err());
+    }
+}
@@ -14,8 +14,10 @@
 
 use crate::common::channel::RequestChannel;
 use crate::common::connection_manager::ConnectionManager;
+use crate::common::proxy_protocol::{accept_proxy_header, apply_proxy_header};
 use crate::common::tool::read_packet;
 use broker_core::cache::BrokerCacheManager;
+use common_config::config::MqttListener;
 use common_metrics::mqtt::packets::record_received_error_metrics;
 use futures_util::StreamExt;
 use metadata_struct::connection::{NetworkConnection, NetworkConnectionType};
@@ -29,7 +31,7 @@
 use tokio::time::sleep;
 use tokio::{io, select};
 use tokio_util::codec::{FramedRead, FramedWrite};
-use tracing::{debug, error};
+use tracing::{debug, error, warn};
 
 /// The `acceptor_process` function is responsible for accepting incoming TCP connections
 /// in an asynchronous manner. It utilizes multiple threads to handle the incoming connections
@@ -54,6 +56,7 @@ pub async fn acceptor_process(
     request_channel: Arc<RequestChannel>,
     network_type: NetworkConnectionType,
     codec: RobustMQCodec,
+    listener_conf: MqttListener,
 ) {
     for index in 1..=accept_thread_num {
         let listener = listener_arc.clone();
@@ -63,6 +66,7 @@ pub async fn acceptor_process(
         let network_type = network_type.clone();
         let row_codec = codec.clone();
         let row_broker_cache = broker_cache.clone();
+        let listener_conf = listener_conf.clone();
         tokio::spawn(async move {
             debug!(
                 "{} Server acceptor thread {} start successfully.",
@@ -81,35 +85,69 @@ pub async fn acceptor_process(
 
                     val = listener.accept()=>{
                         match val{
-                            Ok((stream, addr)) => {
+                            Ok((mut stream, addr)) => {
                                 debug!("Accept {} connection:{:?}", network_type, addr);
 
-                                let (r_stream, w_stream) = io::split(stream);
-                                let read_frame_stream = FramedRead::new(r_stream, row_codec.clone());
-                                let write_frame_stream = FramedWrite::new(w_stream, row_codec.clone());
+                                // The slot is taken here and given back if the connection
+                                // fails before it is added to the connection manager.
+                                if !connection_manager.try_reserve_listener_connection(&listener_conf.name, listener_conf.max_connections) {
+                                    warn!("{} listener [{}] reached the maximum number of connections {:?}, connection {} rejected", network_type, listener_conf.name, listener_conf.max_connections, addr);
+                                    continue;
+                                }
+
+                                // The PROXY header is read in the connection task, so a client
+                                // that is slow to send it does not hold up the accept loop.
+                                let connection_manager = connection_manager.clone();
+                                let broker_cache = row_broker_cache.clone();
+                                let request_channel = request_channel.clone();
+                                let network_type = network_type.clone();
+                                let codec = row_codec.clone();
+                                let listener_conf = listener_conf.clone();
+                                tokio::spawn(async move {
+                                    let proxy_header = if listener_conf.proxy_protocol {
+                                        match accept_proxy_header(&mut stream).await {
+                                            Ok(header) => Some(header),
+                                            Err(e) => {
+                                                error!("{} connection {} failed to read PROXY protocol header with error message :{}", network_type, addr, e);
+                                                connection_manager.release_listener_connection(&listener_conf.name);
+                                                return;
+                                            }
+                                        }
+                                    } else {
+                                        None
+                                    };
 
-                                // if !tcp_establish_connection_check(&addr, &connection_manager, &mut write_frame_stream).await{
-                                //     continue;
-                                // }
+                                    let (r_stream, w_stream) = io::split(stream);
+                                    let read_frame_stream = FramedRead::new(r_stream, codec.clone());
+                                    let write_frame_stream = FramedWrite::new(w_stream, codec);
 
-                                let (connection_stop_sx, connection_stop_rx) = mpsc::channel::<bool>(1);
-                                let connection = NetworkConnection::new(
-                                    NetworkConnectionType::Tcp,
-                                    addr,
-                                    Some(connection_stop_sx.clone())
-                                );
+                                    // if !tcp_establish_connection_check(&addr, &connection_manager, &mut write_frame_stream).await{
+                                    //     continue;
+                                    // }
+
+                                    let (connection_stop_sx, connection_stop_rx) = mpsc::channel::<bool>(1);
+                                    let mut connection = NetworkConnection::new(
+                                        NetworkConnectionType::Tcp,
+                                        addr,
+                                        Some(connection_stop_sx.clone())
+                                    );
+                                    connection.set_listener(&listener_conf.name);
+                                    if let Some(header) = proxy_header {
+                                        apply_proxy_header(&mut connection, header);
+                                    }
 
-                                connection_manager.add_connection(connection.clone());
-                                connection_manager.add_tcp_write(connection.connection_id, write_frame_stream);
-                                read_frame_process(
-                                    row_broker_cache.clone(),
-                                    read_frame_stream,
-                                    connection.connection_id(),
-                                    connection_manager.clone(),
-                                    request_channel.clone(),
-                                    connection_stop_rx,
-                                    network_type.clone(),
-                                );
+                                    connection_manager.add_connection(connection.clone());
+                                    connection_manager.add_tcp_write(connection.connection_id, write_frame_stream);
+                                    read_frame_process(
+                                        broker_cache,
+                                        read_frame_stream,
+                                        connection.connection_id(),
+                                        connection_manager,
+                                        request_channel,
+                                        connection_stop_rx,
+                                        network_type,
+                                    );
+                                });
                             }
                             Err(e) => {
                                 error!("{} accept failed to create connection with error message :{:?}", network_type, e);
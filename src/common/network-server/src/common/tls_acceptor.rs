@@ -33,7 +33,9 @@
 // limitations under the License.
 use crate::common::channel::RequestChannel;
 use crate::common::connection_manager::ConnectionManager;
+use crate::common::proxy_protocol::{accept_proxy_header, apply_proxy_header};
 use crate::common::tool::read_packet;
+use common_config::config::MqttListener;
 use common_metrics::mqtt::packets::record_received_error_metrics;
 use futures_util::StreamExt;
 use metadata_struct::connection::{NetworkConnection, NetworkConnectionType};
@@ -49,10 +51,11 @@
 use tokio::sync::{broadcast, mpsc};
 use tokio::time::sleep;
 use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
-use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::rustls::server::WebPkiClientVerifier;
+use tokio_rustls::rustls::{RootCertStore, ServerConfig};
 use tokio_rustls::TlsAcceptor;
 use tokio_util::codec::{FramedRead, FramedWrite};
-use tracing::{debug, error};
+use tracing::{debug, error, warn};
 
 pub(crate) fn load_certs(path: &Path) -> io::Result<Vec<CertificateDer<'static>>> {
     certs(&mut BufReader::new(File::open(path)?)).collect()
@@ -74,8 +77,9 @@ pub async fn acceptor_tls_process(
     broker_cache: Arc<BrokerCacheManager>,
     request_channel: Arc<RequestChannel>,
     codec: RobustMQCodec,
+    listener_conf: MqttListener,
 ) -> ResultCommonError {
-    let tls_acceptor = create_tls_accept()?;
+    let tls_acceptor = create_tls_accept(&listener_conf)?;
 
     for index in 1..=accept_thread_num {
         let listener = listener_arc.clone();
@@ -86,6 +90,7 @@ pub async fn acceptor_tls_process(
         let network_type = network_type.clone();
         let row_codec = codec.clone();
         let row_broker_cache = broker_cache.clone();
+        let listener_conf = listener_conf.clone();
         tokio::spawn(async move {
             debug!(
                 "{} Server acceptor thread {} start successfully.",
@@ -103,34 +108,77 @@ pub async fn acceptor_tls_process(
                     }
                     val = listener.accept()=>{
                         match val{
-                            Ok((stream, addr)) => {
+                            Ok((mut stream, addr)) => {
                                 debug!("Accept {} tls connection:{:?}", network_type, addr);
-                                let stream = match raw_tls_acceptor.accept(stream).await{
-                                    Ok(da) => da,
-                                    Err(e) => {
-                                        error!("{} Accepter failed to read Stream with error message :{e:?}", network_type);
-                                        continue;
+
+                                // The slot is taken here and given back if the connection
+                                // fails before it is added to the connection manager.
+                                if !connection_manager.try_reserve_listener_connection(&listener_conf.name, listener_conf.max_connections) {
+                                    warn!("{} listener [{}] reached the maximum number of connections {:?}, connection {} rejected", network_type, listener_conf.name, listener_conf.max_connections, addr);
+                                    continue;
+                                }
+
+                                // The PROXY header and the TLS handshake run in the connection
+                                // task, so a client that is slow to send them does not hold up
+                                // the accept loop.
+                                let tls_acceptor = raw_tls_acceptor.clone();
+                                let connection_manager = connection_manager.clone();
+                                let broker_cache = row_broker_cache.clone();
+                                let request_channel = request_channel.clone();
+                                let network_type = network_type.clone();
+                                let codec = row_codec.clone();
+                                let listener_conf = listener_conf.clone();
+                                tokio::spawn(async move {
+                                    // The PROXY protocol header precedes the TLS ClientHello.
+                                    let proxy_header = if listener_conf.proxy_protocol {
+                                        match accept_proxy_header(&mut stream).await {
+                                            Ok(header) => Some(header),
+                                            Err(e) => {
+                                                error!("{} connection {} failed to read PROXY protocol header with error message :{}", network_type, addr, e);
+                                                connection_manager.release_listener_connection(&listener_conf.name);
+                                                return;
+                                            }
+                                        }
+                                    } else {
+                                        None
+                                    };
+                                    let stream = match tls_acceptor.accept(stream).await{
+                                        Ok(da) => da,
+                                        Err(e) => {
+                                            error!("{} Accepter failed to read Stream with error message :{e:?}", network_type);
+                                            connection_manager.release_listener_connection(&listener_conf.name);
+                                            return;
+                                        }
+                                    };
+
+                                    // A certificate is only present when the listener requires client
+                                    // certificates, in which case rustls has already verified it.
+                                    let client_cert_verified = stream.get_ref().1.peer_certificates().is_some();
+
+                                    let (r_stream, w_stream) = tokio::io::split(stream);
+                                    let read_frame_stream = FramedRead::new(r_stream, codec.clone());
+                                    let write_frame_stream = FramedWrite::new(w_stream, codec);
+
+                                    // if !tcp_tls_establish_connection_check(&addr,&connection_manager,&mut write_frame_stream).await{
+                                    //     continue;
+                                    // }
+
+                                    let (connection_stop_sx, connection_stop_rx) = mpsc::channel::<bool>(1);
+                                    let mut connection = NetworkConnection::new(
+                                        NetworkConnectionType::Tls,
+                                        addr,
+                                        Some(connection_stop_sx.clone())
+                                    );
+                                    connection.set_listener(&listener_conf.name);
+                                    if let Some(header) = proxy_header {
+                                        apply_proxy_header(&mut connection, header);
                                     }
-                                };
-
-                                let (r_stream, w_stream) = tokio::io::split(stream);
-                                let read_frame_stream = FramedRead::new(r_stream, row_codec.clone());
-                                let write_frame_stream = FramedWrite::new(w_stream, row_codec.clone());
-
-                                // if !tcp_tls_establish_connection_check(&addr,&connection_manager,&mut write_frame_stream).await{
-                                //     continue;
-                                // }
-
-                                let (connection_stop_sx, connection_stop_rx) = mpsc::channel::<bool>(1);
-                                let connection = NetworkConnection::new(
-                                    NetworkConnectionType::Tls,
-                                    addr,
-                                    Some(connection_stop_sx.clone())
-                                );
-                                connection_manager.add_connection(connection.clone());
-                                connection_manager.add_tcp_tls_write(connection.connection_id, write_frame_stream);
-
-                                read_tls_frame_process(row_broker_cache.clone(), read_frame_stream, connection, request_channel.clone(), connection_stop_rx, network_type.clone());
+                                    connection.client_cert_verified = client_cert_verified;
+                                    connection_manager.add_connection(connection.clone());
+                                    connection_manager.add_tcp_tls_write(connection.connection_id, write_frame_stream);
+
+                                    read_tls_frame_process(broker_cache, read_frame_stream, connection, request_channel, connection_stop_rx, network_type);
+                                });
                             }
                             Err(e) => {
                                 error!("{} accept failed to create connection with error message :{:?}", network_type, e);
@@ -201,13 +249,49 @@ pub(crate) fn read_tls_frame_process(
     });
 }
 
-#[allow(clippy::result_large_err)]
-fn create_tls_accept() -> Result<TlsAcceptor, CommonError> {
+/// Certificate and key of a listener, falling back to the broker-wide TLS material.
+pub fn listener_tls_files(listener: &MqttListener) -> (String, String) {
     let conf = broker_config();
-    let certs = load_certs(Path::new(&conf.runtime.tls_cert))?;
-    let key = load_key(Path::new(&conf.runtime.tls_key))?;
-    let config = ServerConfig::builder()
-        .with_no_client_auth()
-        .with_single_cert(certs, key)?;
+    let cert = listener
+        .tls_cert
+        .clone()
+        .unwrap_or_else(|| conf.runtime.tls_cert.clone());
+    let key = listener
+        .tls_key
+        .clone()
+        .unwrap_or_else(|| conf.runtime.tls_key.clone());
+    (cert, key)
+}
+
+#[allow(clippy::result_large_err)]
+fn create_tls_accept(listener: &MqttListener) -> Result<TlsAcceptor, CommonError> {
+    let config = build_tls_server_config(listener)?;
     Ok(TlsAcceptor::from(Arc::new(config)))
 }
+
+// Server certificate of the listener, plus client certificate verification when
+// the listener has a CA configured.
+#[allow(clippy::result_large_err)]
+pub(crate) fn build_tls_server_config(
+    listener: &MqttListener,
+) -> Result<ServerConfig, CommonError> {
+    let (cert_path, key_path) = listener_tls_files(listener);
+    let certs = load_certs(Path::new(&cert_path))?;
+    let key = load_key(Path::new(&key_path))?;
+    let builder = ServerConfig::builder();
+    let config = if let Some(ca_path) = &listener.tls_ca {
+        let mut roots = RootCertStore::empty();
+        for cert in load_certs(Path::new(ca_path))? {
+            roots.add(cert)?;
+        }
+        let verifier = WebPkiClientVerifier::builder(Arc::new(roots))
+            .build()
+            .map_err(|e| CommonError::CommonError(e.to_string()))?;
+        builder
+            .with_client_cert_verifier(verifier)
+            .with_single_cert(certs, key)?
+    } else {
+        builder.with_no_client_auth().with_single_cert(certs, key)?
+    };
+    Ok(config)
+}
@@ -14,6 +14,7 @@
 
 use crate::{command::ArcCommandAdapter, common::connection_manager::ConnectionManager};
 use broker_core::cache::BrokerCacheManager;
+use common_config::config::MqttListener;
 use grpc_clients::pool::ClientPool;
 use metadata_struct::connection::NetworkConnectionType;
 use std::sync::Arc;
@@ -36,4 +37,5 @@ pub struct ServerContext {
     pub proc_config: ProcessorConfig,
     pub broker_cache: Arc<BrokerCacheManager>,
     pub stop_sx: broadcast::Sender<bool>,
+    pub listener: MqttListener,
 }
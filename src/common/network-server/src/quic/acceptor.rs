@@ -17,6 +17,7 @@
 use crate::common::tool::read_packet;
 use crate::quic::stream::{QuicFramedReadStream, QuicFramedWriteStream};
 use broker_core::cache::BrokerCacheManager;
+use common_config::config::MqttListener;
 use common_metrics::mqtt::packets::record_received_error_metrics;
 use metadata_struct::connection::{NetworkConnection, NetworkConnectionType};
 use protocol::codec::{RobustMQCodec, RobustMQCodecWrapper};
@@ -26,7 +27,7 @@
 use tokio::select;
 use tokio::sync::broadcast;
 use tokio::sync::mpsc::{self, Receiver};
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 #[allow(clippy::too_many_arguments)]
 pub(crate) async fn acceptor_process(
@@ -38,6 +39,7 @@ pub(crate) async fn acceptor_process(
     network_type: NetworkConnectionType,
     codec: RobustMQCodec,
     stop_sx: broadcast::Sender<bool>,
+    listener_conf: MqttListener,
 ) {
     for index in 1..=accept_thread_num {
         let endpoint = endpoint_arc.clone();
@@ -47,6 +49,7 @@ pub(crate) async fn acceptor_process(
         let network_type = network_type.clone();
         let row_codec = codec.clone();
         let row_broker_cache = broker_cache.clone();
+        let listener_conf = listener_conf.clone();
         tokio::spawn(async move {
             debug!(
                 "{} Server acceptor thread {} start successfully.",
@@ -64,10 +67,19 @@ pub(crate) async fn acceptor_process(
                     }
                     val = endpoint.accept()=> {
                         if let Some(incoming) = val{
+                            // The slot is given back if the connection fails before it is added
+                            if !connection_manager.try_reserve_listener_connection(&listener_conf.name, listener_conf.max_connections) {
+                                warn!("{} listener [{}] reached the maximum number of connections {:?}, connection {} rejected", network_type, listener_conf.name, listener_conf.max_connections, incoming.remote_address());
+                                incoming.refuse();
+                                continue;
+                            }
                             match incoming.await {
                                 Ok(connection) => {
                                     info!("Accept {} connection:{:?}", network_type, connection.remote_address());
                                     let client_addr = connection.remote_address();
+                                    // Only present when the listener requires client certificates,
+                                    // in which case rustls has already verified it.
+                                    let client_cert_verified = connection.peer_identity().is_some();
                                     match connection.accept_bi().await {
                                         Ok((w_stream, r_stream)) => {
                                             let codec_write = QuicFramedWriteStream::new(w_stream, row_codec.clone());
@@ -78,11 +90,13 @@ pub(crate) async fn acceptor_process(
                                             // }
 
                                             let (connection_stop_sx, connection_stop_rx) = mpsc::channel::<bool>(1);
-                                            let connection = NetworkConnection::new(
+                                            let mut connection = NetworkConnection::new(
                                                 NetworkConnectionType::QUIC,
                                                 client_addr,
                                                 Some(connection_stop_sx.clone())
                                             );
+                                            connection.set_listener(&listener_conf.name);
+                                            connection.client_cert_verified = client_cert_verified;
 
                                             connection_manager.add_connection(connection.clone());
                                             connection_manager.add_mqtt_quic_write(connection.connection_id, codec_write);
@@ -98,6 +112,7 @@ pub(crate) async fn acceptor_process(
                                             );
                                         },
                                         Err(e) => {
+                                            connection_manager.release_listener_connection(&listener_conf.name);
                                             if let ConnectionError::ApplicationClosed(data) = e.clone(){
                                                 if data.error_code.into_inner() == 0 {
                                                     continue;
@@ -108,9 +123,14 @@ pub(crate) async fn acceptor_process(
                                     }
                                 },
                                 Err(e) => {
+                                    connection_manager.release_listener_connection(&listener_conf.name);
                                     error!("{} accept failed to wait connection with error message :{:?}", network_type, e);
                                 }
                             }
+                        } else {
+                            // the endpoint has been closed
+                            debug!("{} Server acceptor thread {} stopped successfully.", network_type, index);
+                            break;
                         }
                     }
                 };
@@ -15,34 +15,38 @@
 use crate::common::channel::RequestChannel;
 use crate::common::handler::handler_process;
 use crate::common::response::{response_process, ResponseProcessContext};
-use crate::common::tls_acceptor::{load_certs, load_key};
+use crate::common::tls_acceptor::build_tls_server_config;
 use crate::context::ServerContext;
 use crate::quic::acceptor::acceptor_process;
 use common_base::error::common::CommonError;
 use common_base::error::ResultCommonError;
-use common_config::broker::broker_config;
 use metadata_struct::connection::NetworkConnectionType;
 use protocol::codec::RobustMQCodec;
-use quinn::{Endpoint, ServerConfig};
-use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
-use std::path::Path;
-use std::sync::Arc;
+use quinn::crypto::rustls::QuicServerConfig;
+use quinn::{Endpoint, ServerConfig, VarInt};
+use std::net::SocketAddr;
+use std::sync::{Arc, OnceLock};
 use tracing::info;
 
 pub struct QuicServer {
     context: ServerContext,
+    endpoint: OnceLock<Arc<Endpoint>>,
 }
 
 impl QuicServer {
     pub fn new(context: ServerContext) -> Self {
-        QuicServer { context }
+        QuicServer {
+            context,
+            endpoint: OnceLock::new(),
+        }
     }
 
-    pub async fn start(&self, port: u32) -> ResultCommonError {
+    pub async fn start(&self) -> ResultCommonError {
         let config = self.build_config()?;
-        let addr = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(0, 0, 0, 0), port as u16));
+        let addr: SocketAddr = self.context.listener.bind_addr.parse()?;
         let server = Endpoint::server(config, addr)?;
         let arc_quic_endpoint = Arc::new(server);
+        let _ = self.endpoint.set(arc_quic_endpoint.clone());
         let network_type = NetworkConnectionType::QUIC;
         let request_channel = Arc::new(RequestChannel::new(self.context.proc_config.channel_size));
         let request_recv_channel = request_channel.create_request_channel(&network_type);
@@ -57,6 +61,7 @@ pub async fn start(&self, port: u32) -> ResultCommonError {
             network_type.clone(),
             codec.clone(),
             self.context.stop_sx.clone(),
+            self.context.listener.clone(),
         )
         .await;
 
@@ -82,18 +87,30 @@ pub async fn start(&self, port: u32) -> ResultCommonError {
         })
         .await;
 
-        info!("MQTT Quic Server started successfully, addr: {}", addr);
+        info!(
+            "MQTT Quic Server [{}] started successfully, addr: {}",
+            self.context.listener.name, addr
+        );
         Ok(())
     }
 
-    pub async fn stop(&self) {}
+    pub async fn stop(&self) {
+        // Closing the endpoint refuses new connections and closes the open ones,
+        // the acceptor threads exit once the endpoint stops accepting.
+        if let Some(endpoint) = self.endpoint.get() {
+            endpoint.close(VarInt::from_u32(0), b"server stopped");
+            endpoint.wait_idle().await;
+        }
+        info!("MQTT Quic Server [{}] stopped.", self.context.listener.name);
+    }
 
+    // Same TLS settings as the TLS listener, including client certificate
+    // verification when `tls_ca` is set.
     #[allow(clippy::result_large_err)]
     fn build_config(&self) -> Result<ServerConfig, CommonError> {
-        let conf = broker_config();
-        let certs = load_certs(Path::new(&conf.runtime.tls_cert))?;
-        let key = load_key(Path::new(&conf.runtime.tls_key))?;
-        let config = ServerConfig::with_single_cert(certs, key)?;
-        Ok(config)
+        let tls_config = build_tls_server_config(&self.context.listener)?;
+        let crypto = QuicServerConfig::try_from(tls_config)
+            .map_err(|e| CommonError::CommonError(e.to_string()))?;
+        Ok(ServerConfig::with_crypto(Arc::new(crypto)))
     }
 }
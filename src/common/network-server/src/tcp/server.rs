@@ -26,6 +26,7 @@
 };
 use broker_core::cache::BrokerCacheManager;
 use common_base::error::ResultCommonError;
+use common_config::config::MqttListener;
 use common_metrics::network::record_broker_thread_num;
 use grpc_clients::pool::ClientPool;
 use metadata_struct::connection::NetworkConnectionType;
@@ -49,6 +50,7 @@ pub struct TcpServer {
     acceptor_stop_send: broadcast::Sender<bool>,
     broker_cache: Arc<BrokerCacheManager>,
     stop_sx: broadcast::Sender<bool>,
+    listener: MqttListener,
 }
 
 impl TcpServer {
@@ -69,11 +71,12 @@ pub fn new(context: ServerContext) -> Self {
             request_channel,
             acceptor_stop_send,
             broker_cache: context.broker_cache.clone(),
+            listener: context.listener,
         }
     }
 
-    pub async fn start(&self, tls: bool, port: u32) -> ResultCommonError {
-        let listener = TcpListener::bind(format!("0.0.0.0:{port}")).await?;
+    pub async fn start(&self) -> ResultCommonError {
+        let listener = TcpListener::bind(&self.listener.bind_addr).await?;
         let arc_listener = Arc::new(listener);
         let request_recv_channel = self
             .request_channel
@@ -82,7 +85,7 @@ pub async fn start(&self, tls: bool, port: u32) -> ResultCommonError {
             .request_channel
             .create_response_channel(&self.network_type);
         let codec = RobustMQCodec::new();
-        if tls {
+        if self.network_type == NetworkConnectionType::Tls {
             acceptor_tls_process(
                 self.proc_config.accept_thread_num,
                 arc_listener.clone(),
@@ -92,6 +95,7 @@ pub async fn start(&self, tls: bool, port: u32) -> ResultCommonError {
                 self.broker_cache.clone(),
                 self.request_channel.clone(),
                 codec,
+                self.listener.clone(),
             )
             .await?;
         } else {
@@ -104,6 +108,7 @@ pub async fn start(&self, tls: bool, port: u32) -> ResultCommonError {
                 self.request_channel.clone(),
                 self.network_type.clone(),
                 codec,
+                self.listener.clone(),
             )
             .await;
         }
@@ -132,8 +137,8 @@ pub async fn start(&self, tls: bool, port: u32) -> ResultCommonError {
 
         self.record_pre_server_metrics();
         info!(
-            "MQTT {} Server started successfully, listening port: {port}",
-            self.network_type
+            "MQTT {} Server [{}] started successfully, listening addr: {}",
+            self.network_type, self.listener.name, self.listener.bind_addr
         );
         Ok(())
     }
@@ -0,0 +1,46 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::proxy_protocol::{accept_proxy_header, ProxyHeader};
+use axum::middleware::AddExtension;
+use axum::Extension;
+use axum_server::accept::Accept;
+use futures::future::BoxFuture;
+use std::io;
+use tokio::net::TcpStream;
+use tower::Layer;
+
+/// Acceptor that consumes the PROXY protocol header before the HTTP upgrade
+/// (and before the TLS handshake when wrapped by `RustlsAcceptor`). The parsed
+/// header is attached to every request of the connection as an extension.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ProxyProtocolAcceptor;
+
+impl<S> Accept<TcpStream, S> for ProxyProtocolAcceptor
+where
+    S: Send + 'static,
+{
+    type Stream = TcpStream;
+    type Service = AddExtension<S, ProxyHeader>;
+    type Future = BoxFuture<'static, io::Result<(Self::Stream, Self::Service)>>;
+
+    fn accept(&self, mut stream: TcpStream, service: S) -> Self::Future {
+        Box::pin(async move {
+            let header = accept_proxy_header(&mut stream)
+                .await
+                .map_err(|e| io::Error::other(e.to_string()))?;
+            Ok((stream, Extension(header).layer(service)))
+        })
+    }
+}
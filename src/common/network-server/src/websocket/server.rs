@@ -14,20 +14,26 @@
 
 use crate::command::ArcCommandAdapter;
 use crate::common::connection_manager::ConnectionManager;
+use crate::common::proxy_protocol::{apply_proxy_header, ProxyHeader};
+use crate::common::tls_acceptor::listener_tls_files;
+use crate::websocket::proxy::ProxyProtocolAcceptor;
 use axum::extract::ws::{Message, WebSocket};
 use axum::extract::{ConnectInfo, State, WebSocketUpgrade};
-use axum::response::Response;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
 use axum::routing::get;
-use axum::Router;
+use axum::{Extension, Router};
 use axum_extra::headers::UserAgent;
 use axum_extra::TypedHeader;
-use axum_server::tls_rustls::RustlsConfig;
+use axum_server::tls_rustls::{RustlsAcceptor, RustlsConfig};
+use axum_server::Handle;
 use bytes::{BufMut, BytesMut};
 use common_base::error::common::CommonError;
 use common_base::error::ResultCommonError;
 use common_base::tools::now_mills;
-use common_config::broker::broker_config;
+use common_config::config::MqttListener;
 use common_metrics::network::record_ws_request_duration;
+use futures::future::BoxFuture;
 use futures_util::stream::StreamExt;
 use kafka_protocol::messages::ResponseHeader;
 use metadata_struct::connection::{NetworkConnection, NetworkConnectionType};
@@ -49,8 +55,7 @@
 
 #[derive(Clone)]
 pub struct WebSocketServerState {
-    pub ws_port: u32,
-    pub wss_port: u32,
+    pub listener: MqttListener,
     pub command: ArcCommandAdapter,
     pub connection_manager: Arc<ConnectionManager>,
     pub stop_sx: broadcast::Sender<bool>,
@@ -58,15 +63,13 @@ pub struct WebSocketServerState {
 
 impl WebSocketServerState {
     pub fn new(
-        ws_port: u32,
-        wss_port: u32,
+        listener: MqttListener,
         command: ArcCommandAdapter,
         connection_manager: Arc<ConnectionManager>,
         stop_sx: broadcast::Sender<bool>,
     ) -> Self {
         Self {
-            ws_port,
-            wss_port,
+            listener,
             command,
             connection_manager,
             stop_sx,
@@ -76,45 +79,91 @@ pub fn new(
 #[derive(Clone)]
 pub struct WebSocketServer {
     state: WebSocketServerState,
+    handle: Handle,
 }
 
 impl WebSocketServer {
     pub fn new(state: WebSocketServerState) -> Self {
-        WebSocketServer { state }
+        WebSocketServer {
+            state,
+            handle: Handle::new(),
+        }
     }
 
-    pub async fn start_ws(&self) -> ResultCommonError {
-        let ip: SocketAddr = format!("0.0.0.0:{}", self.state.ws_port).parse()?;
+    /// Binds the listener address and serves it in the background. Bind and TLS
+    /// configuration errors are returned, so a listener that fails to start is
+    /// never reported as running.
+    pub async fn start(&self) -> ResultCommonError {
+        let listener = &self.state.listener;
+        let ip: SocketAddr = listener.bind_addr.parse()?;
         let app = routes_v1(self.state.clone());
+        let make_service = app.into_make_service_with_connect_info::<SocketAddr>();
 
-        info!("Broker WebSocket Server start success. addr:{}", ip);
-        axum_server::bind(ip)
-            .serve(app.into_make_service_with_connect_info::<SocketAddr>())
-            .await?;
-        Ok(())
-    }
+        let tls_config = if listener.is_tls() {
+            let (cert_path, key_path) = listener_tls_files(listener);
+            Some(
+                RustlsConfig::from_pem_file(PathBuf::from(cert_path), PathBuf::from(key_path))
+                    .await?,
+            )
+        } else {
+            None
+        };
+        let tcp_listener = std::net::TcpListener::bind(ip)?;
+        let server = axum_server::from_tcp(tcp_listener).handle(self.handle.clone());
 
-    pub async fn start_wss(&self) -> ResultCommonError {
-        let ip: SocketAddr = format!("0.0.0.0:{}", self.state.wss_port).parse()?;
-        let app = routes_v1(self.state.clone());
+        let serve: BoxFuture<'static, std::io::Result<()>> =
+            match (tls_config, listener.proxy_protocol) {
+                (Some(tls_config), true) => Box::pin(
+                    server
+                        .acceptor(RustlsAcceptor::new(tls_config).acceptor(ProxyProtocolAcceptor))
+                        .serve(make_service),
+                ),
+                (Some(tls_config), false) => Box::pin(
+                    server
+                        .acceptor(RustlsAcceptor::new(tls_config))
+                        .serve(make_service),
+                ),
+                (None, true) => {
+                    Box::pin(server.acceptor(ProxyProtocolAcceptor).serve(make_service))
+                }
+                (None, false) => Box::pin(server.serve(make_service)),
+            };
 
-        let config = broker_config();
-        let tls_config = RustlsConfig::from_pem_file(
-            PathBuf::from(config.runtime.tls_cert.clone()),
-            PathBuf::from(config.runtime.tls_key.clone()),
-        )
-        .await?;
+        let name = listener.name.clone();
+        tokio::spawn(async move {
+            if let Err(e) = serve.await {
+                error!(
+                    "Broker WebSocket Server [{}] stopped with error: {}",
+                    name, e
+                );
+            }
+        });
 
-        info!("Broker WebSocket TLS Server start success. addr:{}", ip);
-        axum_server::bind_rustls(ip, tls_config)
-            .serve(app.into_make_service_with_connect_info::<SocketAddr>())
-            .await?;
+        info!(
+            "Broker WebSocket{} Server [{}] start success. addr:{}",
+            if listener.is_tls() { " TLS" } else { "" },
+            listener.name,
+            ip
+        );
         Ok(())
     }
+
+    pub fn stop(&self) {
+        self.handle.shutdown();
+        info!(
+            "Broker WebSocket Server [{}] stopped.",
+            self.state.listener.name
+        );
+    }
 }
 
 fn routes_v1(state: WebSocketServerState) -> Router {
-    let mqtt_ws = Router::new().route(ROUTE_ROOT, get(ws_handler));
+    let path = state
+        .listener
+        .websocket_path
+        .clone()
+        .unwrap_or_else(|| ROUTE_ROOT.to_string());
+    let mqtt_ws = Router::new().route(&path, get(ws_handler));
     let app = Router::new().merge(mqtt_ws);
     app.with_state(state)
 }
@@ -124,6 +173,7 @@ async fn ws_handler(
     State(state): State<WebSocketServerState>,
     user_agent: Option<TypedHeader<UserAgent>>,
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    proxy_header: Option<Extension<ProxyHeader>>,
 ) -> Response {
     let user_agent = if let Some(TypedHeader(user_agent)) = user_agent {
         user_agent.to_string()
@@ -131,13 +181,37 @@ async fn ws_handler(
         String::from("Unknown Source")
     };
 
-    info!("websocket `{user_agent}` at {addr} connected.");
+    // The slot is given back if the upgrade fails
+    if !state
+        .connection_manager
+        .try_reserve_listener_connection(&state.listener.name, state.listener.max_connections)
+    {
+        warn!(
+            "Listener [{}] reached max connections {:?}, reject websocket connection from {}",
+            state.listener.name, state.listener.max_connections, addr
+        );
+        return StatusCode::SERVICE_UNAVAILABLE.into_response();
+    }
+
+    let proxy_header = proxy_header.map(|Extension(header)| header);
+    info!("websocket `{user_agent}` at {addr} connected, proxy header: {proxy_header:?}.");
     let codec = RobustMQCodec::new();
+    let connection_manager = state.connection_manager.clone();
+    let listener_name = state.listener.name.clone();
     ws.protocols(["mqtt", "mqttv3.1"])
+        .on_failed_upgrade(move |e| {
+            warn!(
+                "websocket connection from {} failed to upgrade, error message: {}",
+                addr, e
+            );
+            connection_manager.release_listener_connection(&listener_name);
+        })
         .on_upgrade(move |socket| {
             handle_socket(
                 socket,
                 addr,
+                proxy_header,
+                state.listener.name.clone(),
                 state.command,
                 codec,
                 state.connection_manager.clone(),
@@ -149,13 +223,20 @@ async fn ws_handler(
 async fn handle_socket(
     socket: WebSocket,
     addr: SocketAddr,
+    proxy_header: Option<ProxyHeader>,
+    listener_name: String,
     command: ArcCommandAdapter,
     mut codec: RobustMQCodec,
     connection_manager: Arc<ConnectionManager>,
     stop_sx: broadcast::Sender<bool>,
 ) {
     let (sender, mut receiver) = socket.split();
-    let tcp_connection = NetworkConnection::new(NetworkConnectionType::WebSocket, addr, None);
+    let mut tcp_connection = NetworkConnection::new(NetworkConnectionType::WebSocket, addr, None);
+    tcp_connection.set_listener(&listener_name);
+    if let Some(header) = proxy_header {
+        apply_proxy_header(&mut tcp_connection, header);
+    }
+    let addr = tcp_connection.addr;
     connection_manager.add_websocket_write(tcp_connection.connection_id, sender);
     connection_manager.add_connection(tcp_connection.clone());
     let mut stop_rx = stop_sx.subscribe();
@@ -150,6 +150,34 @@ pub fn read_prefix(
         Ok(result)
     }
 
+    // Search data by prefix, starting from start_key and returning at most limit entries
+    pub fn read_prefix_from(
+        &self,
+        cf: Arc<BoundColumnFamily<'_>>,
+        search_key: &str,
+        start_key: &str,
+        limit: usize,
+    ) -> Result<Vec<(String, Vec<u8>)>, CommonError> {
+        let mut iter = self.db.raw_iterator_cf(&cf);
+        iter.seek(start_key);
+
+        let mut result = Vec::new();
+        while iter.valid() && result.len() < limit {
+            if let Some(key) = iter.key() {
+                if let Some(val) = iter.value() {
+                    let key = String::from_utf8(key.to_vec())?;
+                    if !key.starts_with(search_key) {
+                        break;
+                    }
+                    result.push((key, val.to_vec()));
+                }
+            }
+
+            iter.next();
+        }
+        Ok(result)
+    }
+
     // Search data by prefix
     pub fn read_list_by_model(
         &self,
@@ -12,81 +12,142 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{sync::Arc, time::Duration};
+use std::{sync::atomic::Ordering, sync::Arc, time::Duration};
 
-use common_base::error::common::CommonError;
-use metadata_struct::adapter::{read_config::ReadConfig, record::Record};
+use common_base::{error::common::CommonError, tools::now_second};
+use metadata_struct::adapter::record::Record;
 use storage_adapter::storage::{ArcStorageAdapter, ShardInfo};
-use tokio::{select, sync::broadcast, time::sleep};
-use tracing::{debug, info};
+use tokio::{select, time::interval};
+use tracing::{debug, error, info, warn};
 
-use crate::{persist::recover_delay_queue, pop::pop_delay_queue, DelayMessageManager};
+use crate::{
+    persist::{
+        bucket_of, delete_index_window, read_delay_bucket, save_delay_checkpoint, window_of,
+        DelayBucket, DELAY_BUCKET_SECONDS, DELAY_MESSAGE_WINDOW_SHARD_NAME,
+    },
+    pop::pop_delay_queue,
+    DelayMessageManager,
+};
 
 const DELAY_MESSAGE_SHARD_NAME_PREFIX: &str = "$delay-message-shard-";
 
-pub(crate) fn start_recover_delay_queue(
-    delay_message_manager: &Arc<DelayMessageManager>,
-    message_storage_adapter: &ArcStorageAdapter,
-    namespace: &str,
-    shard_num: u64,
-) {
-    let read_config = ReadConfig {
-        max_record_num: 100,
-        max_size: 1024 * 1024 * 1024,
-    };
+// Buckets due within this window are loaded into the timing wheel
+const DELAY_MESSAGE_PRELOAD_SECONDS: u64 = 2 * DELAY_BUCKET_SECONDS;
 
+// Bounds the catch-up work of a single tick after a long downtime
+const MAX_LOAD_BUCKET_PER_TICK: u64 = 10;
+
+pub(crate) fn start_delay_message_pop(delay_message_manager: &Arc<DelayMessageManager>) {
     let new_delay_message_manager = delay_message_manager.clone();
-    let new_message_storage_adapter = message_storage_adapter.clone();
-    let new_namespace = namespace.to_owned();
+    let mut recv = delay_message_manager.stop_send.subscribe();
+
     tokio::spawn(async move {
-        recover_delay_queue(
-            &new_message_storage_adapter,
-            &new_delay_message_manager,
-            &new_namespace,
-            read_config,
-            shard_num,
-        )
-        .await;
+        let mut ticker = interval(Duration::from_secs(1));
+        loop {
+            select! {
+                val = recv.recv() =>{
+                    if let Ok(flag) = val {
+                        if flag {
+                            debug!("{}","Delay message pop thread exited successfully");
+                            break;
+                        }
+                    }
+                }
+                _ = ticker.tick() => {
+                    let now = now_second();
+                    load_delay_buckets(&new_delay_message_manager, now).await;
+                    pop_delay_queue(&new_delay_message_manager, now);
+                    advance_delay_checkpoint(&new_delay_message_manager, now).await;
+                }
+            }
+        }
     });
 }
 
-pub(crate) fn start_delay_message_pop(
+// The loaded mark moves before the bucket is read, a message written to the
+// bucket meanwhile is then scheduled by its sender, or by both and deduplicated.
+pub(crate) async fn load_delay_buckets(delay_message_manager: &Arc<DelayMessageManager>, now: u64) {
+    let target = bucket_of(now + DELAY_MESSAGE_PRELOAD_SECONDS);
+    for _ in 0..MAX_LOAD_BUCKET_PER_TICK {
+        let bucket = delay_message_manager.loaded_until.load(Ordering::SeqCst);
+        if bucket > target {
+            break;
+        }
+        delay_message_manager
+            .loaded_until
+            .store(bucket + 1, Ordering::SeqCst);
+
+        match load_delay_bucket(delay_message_manager, bucket).await {
+            Ok(data) => delay_message_manager.load_bucket(bucket, data),
+            Err(e) => {
+                error!(
+                    "Failed to load delay message bucket {}, error message: {:?}",
+                    bucket, e
+                );
+                delay_message_manager
+                    .loaded_until
+                    .store(bucket, Ordering::SeqCst);
+                break;
+            }
+        }
+    }
+}
+
+// A bucket whose window was never created holds no messages
+async fn load_delay_bucket(
     delay_message_manager: &Arc<DelayMessageManager>,
-    message_storage_adapter: &ArcStorageAdapter,
-    namespace: &str,
-    shard_num: u64,
+    bucket: u64,
+) -> Result<DelayBucket, CommonError> {
+    if !delay_message_manager.has_window(window_of(bucket)).await? {
+        return Ok(DelayBucket::default());
+    }
+    read_delay_bucket(
+        &delay_message_manager.message_storage_adapter,
+        &delay_message_manager.namespace,
+        bucket,
+    )
+    .await
+}
+
+// Every bucket before the current one has been handed to the sender once the
+// wheel has advanced to `now`, so the index windows it leaves behind can be
+// dropped.
+pub(crate) async fn advance_delay_checkpoint(
+    delay_message_manager: &Arc<DelayMessageManager>,
+    now: u64,
 ) {
-    for shard_no in 0..shard_num {
-        let new_delay_message_manager = delay_message_manager.clone();
-        let new_message_storage_adapter = message_storage_adapter.clone();
-        let new_namespace = namespace.to_owned();
-
-        let (stop_send, _) = broadcast::channel(2);
-        delay_message_manager.add_delay_queue_pop_thread(shard_no, stop_send.clone());
-
-        tokio::spawn(async move {
-            loop {
-                let mut recv = stop_send.subscribe();
-                select! {
-                    val = recv.recv() =>{
-                        if let Ok(flag) = val {
-                            if flag {
-                                debug!("{}","Heartbeat reporting thread exited successfully");
-                                break;
-                            }
-                        }
-                    }
-                    _ =  pop_delay_queue(
-                        &new_namespace,
-                        &new_message_storage_adapter,
-                        &new_delay_message_manager,
-                        shard_no,
-                    ) => {
-                        sleep(Duration::from_millis(100)).await;
-                    }
-                }
-            }
-        });
+    let checkpoint = bucket_of(now).min(delay_message_manager.loaded_until.load(Ordering::SeqCst));
+    if checkpoint <= delay_message_manager.checkpoint.load(Ordering::SeqCst) {
+        return;
+    }
+
+    if let Err(e) = save_delay_checkpoint(
+        &delay_message_manager.message_storage_adapter,
+        &delay_message_manager.namespace,
+        checkpoint,
+    )
+    .await
+    {
+        error!(
+            "Failed to save delay message checkpoint {}, error message: {:?}",
+            checkpoint, e
+        );
+        return;
+    }
+
+    for window in delay_message_manager.release_buckets(checkpoint) {
+        if let Err(e) = delete_index_window(
+            &delay_message_manager.message_storage_adapter,
+            &delay_message_manager.namespace,
+            window,
+        )
+        .await
+        {
+            warn!(
+                "Failed to drop delivered delay message index window {}, error message: {:?}",
+                window, e
+            );
+        }
     }
 }
 
@@ -108,8 +169,11 @@ pub(crate) async fn init_delay_message_shard(
     namespace: &str,
     shard_num: u64,
 ) -> Result<(), CommonError> {
-    for i in 0..shard_num {
-        let shard_name = get_delay_message_shard_name(i);
+    let shard_names = (0..shard_num)
+        .map(get_delay_message_shard_name)
+        .chain([DELAY_MESSAGE_WINDOW_SHARD_NAME.to_owned()]);
+
+    for shard_name in shard_names {
         let results = message_storage_adapter
             .list_shard(namespace.to_owned(), shard_name.clone())
             .await?;
@@ -139,8 +203,8 @@ mod test {
     use storage_adapter::storage::build_memory_storage_driver;
 
     use crate::{
-        get_delay_message_shard_name, init_delay_message_shard, persist_delay_message,
-        pop::read_offset_data,
+        get_delay_message_shard_name, init_delay_message_shard,
+        persist::DELAY_MESSAGE_WINDOW_SHARD_NAME, persist_delay_message, pop::read_offset_data,
     };
 
     #[tokio::test]
@@ -171,12 +235,18 @@ pub async fn init_delay_message_shard_test() {
 
         let shard_name = get_delay_message_shard_name(shard_num - 1);
         let res = message_storage_adapter
-            .list_shard(namespace, shard_name.clone())
+            .list_shard(namespace.clone(), shard_name.clone())
             .await;
         assert!(res.is_ok());
         let res = res.unwrap();
         assert_eq!(res.len(), 1);
         assert_eq!(res.first().unwrap().shard_name, shard_name);
+
+        let res = message_storage_adapter
+            .list_shard(namespace, DELAY_MESSAGE_WINDOW_SHARD_NAME.to_string())
+            .await
+            .unwrap();
+        assert_eq!(res.len(), 1);
     }
 
     #[tokio::test]
@@ -12,29 +12,43 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use common_base::{error::common::CommonError, tools::now_second};
-use common_metrics::mqtt::statistics::{
-    record_mqtt_delay_queue_remaining_capacity_set, record_mqtt_delay_queue_total_capacity_set,
-    record_mqtt_delay_queue_used_capacity_set,
+use common_base::{
+    error::common::CommonError,
+    tools::{now_second, unique_id},
 };
-use dashmap::DashMap;
+use common_metrics::mqtt::statistics::record_mqtt_delay_queue_used_capacity_set;
 use delay::{
     get_delay_message_shard_name, init_delay_message_shard, persist_delay_message,
-    start_delay_message_pop, start_recover_delay_queue,
+    start_delay_message_pop,
 };
 use metadata_struct::{adapter::record::Record, delay_info::DelayMessageInfo};
-use persist::persist_delay_info;
+use persist::{
+    bucket_of, build_delay_id, create_index_window, import_legacy_delay_info,
+    load_delay_checkpoint, load_index_windows, persist_delay_cancel, persist_delay_info,
+    read_delay_info, save_delay_checkpoint, scan_delay_info, window_of, window_of_id, DelayBucket,
+    DelayScanCursor,
+};
+use pop::read_offset_data;
+use serde::{Deserialize, Serialize};
 use std::{
-    sync::{atomic::AtomicU64, Arc},
-    time::Duration,
+    collections::{BTreeMap, BTreeSet, HashSet},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
 };
 use storage_adapter::storage::ArcStorageAdapter;
-use tokio::{sync::broadcast, time::Instant};
-use tokio_util::time::DelayQueue;
+use tokio::sync::broadcast;
+use tracing::{debug, info};
+use wheel::TimingWheel;
 
 pub mod delay;
 pub mod persist;
 pub mod pop;
+pub mod wheel;
+
+// Most index entries a single list call returns
+pub const DELAY_MESSAGE_LIST_MAX_NUM: usize = 10000;
 
 pub async fn start_delay_message_manager(
     delay_message_manager: &Arc<DelayMessageManager>,
@@ -42,33 +56,71 @@ pub async fn start_delay_message_manager(
     namespace: &str,
     shard_num: u64,
 ) -> Result<(), CommonError> {
-    delay_message_manager.start().await;
     init_delay_message_shard(message_storage_adapter, namespace, shard_num).await?;
+    import_legacy_delay_info(message_storage_adapter, namespace).await?;
 
-    start_recover_delay_queue(
-        delay_message_manager,
-        message_storage_adapter,
-        namespace,
-        shard_num,
-    );
-
-    start_delay_message_pop(
-        delay_message_manager,
-        message_storage_adapter,
-        namespace,
-        shard_num,
+    let checkpoint = match load_delay_checkpoint(message_storage_adapter, namespace).await? {
+        Some(checkpoint) => checkpoint,
+        None => {
+            let checkpoint = bucket_of(now_second());
+            save_delay_checkpoint(message_storage_adapter, namespace, checkpoint).await?;
+            checkpoint
+        }
+    };
+    delay_message_manager.start(checkpoint);
+    delay_message_manager.refresh_windows().await?;
+    info!(
+        "Delay message manager started from bucket {}, namespace: {}",
+        checkpoint, namespace
     );
 
+    start_delay_message_pop(delay_message_manager);
     Ok(())
 }
 
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DelayMessageStatus {
+    Pending,
+    Delivered,
+    Cancelled,
+}
+
+#[derive(Clone, Debug)]
+pub struct DelayMessageEntry {
+    pub info: DelayMessageInfo,
+    pub status: DelayMessageStatus,
+}
+
+// Scheduled messages are kept in the index shards and grouped into buckets by
+// delivery time. Only the buckets about to be delivered are loaded into the
+// timing wheel, everything before the checkpoint bucket has been delivered.
 pub struct DelayMessageManager {
     namespace: String,
     shard_num: u64,
     message_storage_adapter: ArcStorageAdapter,
     incr_no: AtomicU64,
-    delay_queue_list: DashMap<u64, DelayQueue<DelayMessageInfo>>,
-    delay_queue_pop_thread: DashMap<u64, broadcast::Sender<bool>>,
+    wheel: Mutex<DelayWheel>,
+    // Index windows that exist and are not fully delivered, refreshed from
+    // the window records. Only these are ever read.
+    windows: Mutex<BTreeSet<u64>>,
+    windows_offset: AtomicU64,
+    loaded_until: AtomicU64,
+    checkpoint: AtomicU64,
+    stop_send: broadcast::Sender<bool>,
+}
+
+struct DelayWheel {
+    wheel: TimingWheel<DelayMessageInfo>,
+    buckets: BTreeMap<u64, LoadedBucket>,
+}
+
+// Ids are kept until the bucket is checkpointed, so a message that is read
+// from the index twice is still only delivered once.
+#[derive(Default)]
+struct LoadedBucket {
+    scheduled: HashSet<String>,
+    fired: HashSet<String>,
+    cancelled: HashSet<String>,
 }
 
 impl DelayMessageManager {
@@ -77,19 +129,21 @@ pub fn new(
         shard_num: u64,
         message_storage_adapter: ArcStorageAdapter,
     ) -> Self {
+        let (stop_send, _) = broadcast::channel(2);
         DelayMessageManager {
             namespace,
             shard_num,
             message_storage_adapter,
             incr_no: AtomicU64::new(0),
-            delay_queue_list: DashMap::with_capacity(2),
-            delay_queue_pop_thread: DashMap::with_capacity(2),
-        }
-    }
-
-    pub async fn start(&self) {
-        for shard_no in 0..self.shard_num {
-            self.delay_queue_list.insert(shard_no, DelayQueue::new());
+            wheel: Mutex::new(DelayWheel {
+                wheel: TimingWheel::new(now_second()),
+                buckets: BTreeMap::new(),
+            }),
+            windows: Mutex::new(BTreeSet::new()),
+            windows_offset: AtomicU64::new(0),
+            loaded_until: AtomicU64::new(0),
+            checkpoint: AtomicU64::new(0),
+            stop_send,
         }
     }
 
@@ -98,69 +152,314 @@ pub async fn send(
         target_topic: &str,
         delay_timestamp: u64,
         data: Record,
-    ) -> Result<(), CommonError> {
+    ) -> Result<String, CommonError> {
         let shard_no = self.get_target_shard_no();
-        let namespace = self.namespace.clone();
         let delay_shard_name = get_delay_message_shard_name(shard_no);
 
         // Persist DelayMessage
         let offset = persist_delay_message(
             &self.message_storage_adapter,
-            &namespace,
+            &self.namespace,
             &delay_shard_name,
             data,
         )
         .await?;
 
-        // DelayInfo into delay queue
+        // persist DelayInfo
+        let now = now_second();
+        let delay_timestamp = now + delay_timestamp;
         let delay_info = DelayMessageInfo {
-            delay_shard_name: delay_shard_name.clone(),
+            id: build_delay_id(delay_timestamp, &unique_id()),
+            delay_shard_name,
             target_shard_name: target_topic.to_string(),
             offset,
-            delay_timestamp: now_second() + delay_timestamp,
+            delay_timestamp,
+            create_time: now,
         };
-        self.send_to_delay_queue(shard_no, &delay_info);
+        self.create_window(window_of(bucket_of(delay_timestamp)))
+            .await?;
+        persist_delay_info(&self.message_storage_adapter, &self.namespace, &delay_info).await?;
 
-        // persist DelayInfo
-        persist_delay_info(&self.message_storage_adapter, &self.namespace, delay_info).await?;
-        Ok(())
+        // The bucket may already be loaded, the index entry was written
+        // first so the loader sees it otherwise
+        if bucket_of(delay_info.delay_timestamp) < self.loaded_until.load(Ordering::SeqCst) {
+            self.schedule(delay_info.clone());
+        }
+        Ok(delay_info.id)
     }
 
-    pub async fn stop(&self) -> Result<(), CommonError> {
-        for shard_no in 0..self.shard_num {
-            if let Some(stop_send) = self.delay_queue_pop_thread.get(&shard_no) {
-                stop_send.send(true)?;
-            }
+    // The index entries of a message are dropped with its window some time
+    // after delivery, the message no longer exists then.
+    pub async fn get(&self, id: &str) -> Result<Option<DelayMessageEntry>, CommonError> {
+        let Some(window) = window_of_id(id) else {
+            return Ok(None);
+        };
+        if !self.has_window(window).await? {
+            return Ok(None);
         }
-        Ok(())
+        let Some((info, cancelled)) =
+            read_delay_info(&self.message_storage_adapter, &self.namespace, id).await?
+        else {
+            return Ok(None);
+        };
+
+        let status = if cancelled {
+            DelayMessageStatus::Cancelled
+        } else if self.is_delivered(&info) {
+            DelayMessageStatus::Delivered
+        } else {
+            DelayMessageStatus::Pending
+        };
+        Ok(Some(DelayMessageEntry { info, status }))
     }
 
-    pub fn send_to_delay_queue(&self, shard_no: u64, delay_info: &DelayMessageInfo) {
-        if let Some(mut delay_queue) = self.delay_queue_list.get_mut(&shard_no) {
-            delay_queue.insert_at(
-                delay_info.clone(),
-                Instant::now() + Duration::from_secs(delay_info.delay_timestamp - now_second()),
-            );
-            record_mqtt_delay_queue_total_capacity_set(shard_no, delay_queue.capacity() as i64);
-            record_mqtt_delay_queue_used_capacity_set(shard_no, delay_queue.len() as i64);
-            record_mqtt_delay_queue_remaining_capacity_set(
-                shard_no,
-                (delay_queue.capacity() - delay_queue.len()) as i64,
+    // The payload is removed once the message is delivered or cancelled.
+    pub async fn get_payload(
+        &self,
+        delay_info: &DelayMessageInfo,
+    ) -> Result<Option<Record>, CommonError> {
+        read_offset_data(
+            &self.message_storage_adapter,
+            &self.namespace,
+            &delay_info.delay_shard_name,
+            delay_info.offset,
+        )
+        .await
+    }
+
+    // Pending messages, optionally only those of one target topic.
+    pub async fn list(
+        &self,
+        target_topic: Option<&str>,
+        max_num: usize,
+    ) -> Result<Vec<DelayMessageInfo>, CommonError> {
+        let (messages, _) = self.list_page(target_topic, None, max_num).await?;
+        Ok(messages)
+    }
+
+    // A page of pending messages and the cursor of the next page, None once
+    // the index has been read to the end.
+    pub async fn list_page(
+        &self,
+        target_topic: Option<&str>,
+        cursor: Option<DelayScanCursor>,
+        max_num: usize,
+    ) -> Result<(Vec<DelayMessageInfo>, Option<DelayScanCursor>), CommonError> {
+        self.refresh_windows().await?;
+        let checkpoint = self.checkpoint.load(Ordering::SeqCst);
+        let windows: Vec<u64> = self.windows.lock().unwrap().iter().copied().collect();
+        let (mut messages, next) = scan_delay_info(
+            &self.message_storage_adapter,
+            &self.namespace,
+            &windows,
+            target_topic,
+            checkpoint,
+            cursor,
+            max_num,
+        )
+        .await?;
+        messages.retain(|delay_info| !self.is_delivered(delay_info));
+        Ok((messages, next))
+    }
+
+    // Returns false when the message does not exist or is no longer pending.
+    pub async fn cancel(&self, id: &str) -> Result<bool, CommonError> {
+        let Some(entry) = self.get(id).await? else {
+            return Ok(false);
+        };
+        if entry.status != DelayMessageStatus::Pending {
+            return Ok(false);
+        }
+
+        persist_delay_cancel(&self.message_storage_adapter, &self.namespace, &entry.info).await?;
+
+        if bucket_of(entry.info.delay_timestamp) < self.loaded_until.load(Ordering::SeqCst)
+            && !self.unschedule(&entry.info)
+        {
+            return Ok(false);
+        }
+
+        if let Err(e) = self
+            .message_storage_adapter
+            .delete_by_offsets(
+                self.namespace.clone(),
+                entry.info.delay_shard_name.clone(),
+                vec![entry.info.offset],
+            )
+            .await
+        {
+            debug!(
+                "Failed to delete the payload of cancelled delay message {}, error message: {:?}",
+                id, e
             );
         }
+        Ok(true)
+    }
+
+    pub async fn cancel_by_topic(&self, target_topic: &str) -> Result<u64, CommonError> {
+        let mut total_num = 0;
+        let mut cursor = None;
+        loop {
+            let (messages, next) = self
+                .list_page(Some(target_topic), cursor, DELAY_MESSAGE_LIST_MAX_NUM)
+                .await?;
+
+            for delay_info in messages {
+                if self.cancel(&delay_info.id).await? {
+                    total_num += 1;
+                }
+            }
+
+            if next.is_none() {
+                break;
+            }
+            cursor = next;
+        }
+        Ok(total_num)
+    }
+
+    pub async fn stop(&self) -> Result<(), CommonError> {
+        let _ = self.stop_send.send(true);
+        Ok(())
     }
 
     pub fn get_shard_num(&self) -> u64 {
         self.shard_num
     }
 
-    fn add_delay_queue_pop_thread(&self, shard_no: u64, stop_send: broadcast::Sender<bool>) {
-        self.delay_queue_pop_thread.insert(shard_no, stop_send);
+    fn start(&self, checkpoint: u64) {
+        self.checkpoint.store(checkpoint, Ordering::SeqCst);
+        self.loaded_until.store(checkpoint, Ordering::SeqCst);
+    }
+
+    async fn create_window(&self, window: u64) -> Result<(), CommonError> {
+        if self.has_window(window).await? {
+            return Ok(());
+        }
+        create_index_window(&self.message_storage_adapter, &self.namespace, window).await?;
+        self.windows.lock().unwrap().insert(window);
+        Ok(())
+    }
+
+    // Windows created by other brokers are only known after a refresh, so a
+    // window that is not known yet is looked up once more.
+    pub(crate) async fn has_window(&self, window: u64) -> Result<bool, CommonError> {
+        if self.windows.lock().unwrap().contains(&window) {
+            return Ok(true);
+        }
+        self.refresh_windows().await?;
+        Ok(self.windows.lock().unwrap().contains(&window))
+    }
+
+    // Reads the window records written since the last refresh
+    async fn refresh_windows(&self) -> Result<(), CommonError> {
+        let offset = self.windows_offset.load(Ordering::SeqCst);
+        let (windows, next_offset) =
+            load_index_windows(&self.message_storage_adapter, &self.namespace, offset).await?;
+        let first_window = window_of(self.checkpoint.load(Ordering::SeqCst));
+        self.windows
+            .lock()
+            .unwrap()
+            .extend(windows.into_iter().filter(|window| *window >= first_window));
+        self.windows_offset.fetch_max(next_offset, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn is_delivered(&self, delay_info: &DelayMessageInfo) -> bool {
+        let bucket = bucket_of(delay_info.delay_timestamp);
+        if bucket < self.checkpoint.load(Ordering::SeqCst) {
+            return true;
+        }
+        let wheel = self.wheel.lock().unwrap();
+        wheel
+            .buckets
+            .get(&bucket)
+            .is_some_and(|loaded| loaded.fired.contains(&delay_info.id))
+    }
+
+    fn schedule(&self, delay_info: DelayMessageInfo) {
+        let mut wheel = self.wheel.lock().unwrap();
+        wheel.schedule(delay_info);
+        record_mqtt_delay_queue_used_capacity_set(0, wheel.wheel.len() as i64);
+    }
+
+    fn unschedule(&self, delay_info: &DelayMessageInfo) -> bool {
+        let mut wheel = self.wheel.lock().unwrap();
+        let bucket = wheel
+            .buckets
+            .entry(bucket_of(delay_info.delay_timestamp))
+            .or_default();
+        bucket.cancelled.insert(delay_info.id.clone());
+        if bucket.fired.contains(&delay_info.id) {
+            return false;
+        }
+
+        wheel.wheel.remove(|item| item.id == delay_info.id);
+        record_mqtt_delay_queue_used_capacity_set(0, wheel.wheel.len() as i64);
+        true
+    }
+
+    pub(crate) fn load_bucket(&self, bucket: u64, data: DelayBucket) {
+        let mut wheel = self.wheel.lock().unwrap();
+        let loaded = wheel.buckets.entry(bucket).or_default();
+        loaded.cancelled.extend(data.cancelled);
+        for delay_info in data.messages {
+            wheel.schedule(delay_info);
+        }
+        record_mqtt_delay_queue_used_capacity_set(0, wheel.wheel.len() as i64);
+    }
+
+    pub(crate) fn pop_expired(&self, now: u64) -> Vec<DelayMessageInfo> {
+        let mut wheel = self.wheel.lock().unwrap();
+        let expired = wheel.wheel.advance(now);
+        let mut results = Vec::with_capacity(expired.len());
+        for delay_info in expired {
+            let bucket = wheel
+                .buckets
+                .entry(bucket_of(delay_info.delay_timestamp))
+                .or_default();
+            if bucket.cancelled.contains(&delay_info.id) {
+                continue;
+            }
+            bucket.fired.insert(delay_info.id.clone());
+            results.push(delay_info);
+        }
+        record_mqtt_delay_queue_used_capacity_set(0, wheel.wheel.len() as i64);
+        results
+    }
+
+    // Forget every bucket before `checkpoint` and return the index windows
+    // that only held buckets before it.
+    pub(crate) fn release_buckets(&self, checkpoint: u64) -> Vec<u64> {
+        self.checkpoint.store(checkpoint, Ordering::SeqCst);
+        {
+            let mut wheel = self.wheel.lock().unwrap();
+            let remaining = wheel.buckets.split_off(&checkpoint);
+            wheel.buckets = remaining;
+        }
+        let mut windows = self.windows.lock().unwrap();
+        let remaining = windows.split_off(&window_of(checkpoint));
+        std::mem::replace(&mut *windows, remaining)
+            .into_iter()
+            .collect()
     }
 
     fn get_target_shard_no(&self) -> u64 {
-        self.incr_no
-            .fetch_add(1, std::sync::atomic::Ordering::Relaxed)
-            % self.shard_num
+        self.incr_no.fetch_add(1, Ordering::Relaxed) % self.shard_num
+    }
+}
+
+impl DelayWheel {
+    fn schedule(&mut self, delay_info: DelayMessageInfo) {
+        let bucket = self
+            .buckets
+            .entry(bucket_of(delay_info.delay_timestamp))
+            .or_default();
+        if bucket.cancelled.contains(&delay_info.id)
+            || !bucket.scheduled.insert(delay_info.id.clone())
+        {
+            return;
+        }
+        self.wheel.insert(delay_info.delay_timestamp, delay_info);
     }
 }
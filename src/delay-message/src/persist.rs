@@ -17,69 +17,413 @@
     adapter::{read_config::ReadConfig, record::Record},
     delay_info::DelayMessageInfo,
 };
-use std::{sync::Arc, time::Duration};
-use storage_adapter::storage::ArcStorageAdapter;
-use tokio::time::sleep;
-use tracing::{error, info};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use storage_adapter::storage::{ArcStorageAdapter, ShardInfo};
+use tracing::{error, info, warn};
 
-use crate::DelayMessageManager;
+// Every scheduled message has an index entry, tagged with the bucket of its
+// delivery time and its target topic. The entries of an hour of buckets share
+// one index shard, which is dropped as a whole once the checkpoint has moved
+// past it, so the index never holds delivered entries for long and works on
+// adapters that cannot delete single records.
+const DELAY_MESSAGE_INDEX_SHARD_PREFIX: &str = "$delay-message-index-";
 
+// One record per index window that has been created
+pub const DELAY_MESSAGE_WINDOW_SHARD_NAME: &str = "$delay-message-windows";
+
+// Index shard written by earlier versions, imported once on startup
 const DELAY_QUEUE_INFO_SHARD_NAME: &str = "$delay-queue-info-shard";
 
-pub async fn persist_delay_info(
+const DELAY_MESSAGE_CHECKPOINT_GROUP_PREFIX: &str = "$delay-message-checkpoint-";
+
+const DELAY_MESSAGE_CHECKPOINT_KEY: &str = "$delay-message-checkpoint";
+
+const DELAY_MESSAGE_CANCEL_KEY_PREFIX: &str = "cancel/";
+
+const DELAY_MESSAGE_CANCEL_TAG: &str = "cancel";
+
+pub const DELAY_BUCKET_SECONDS: u64 = 60;
+
+pub const DELAY_WINDOW_BUCKETS: u64 = 60;
+
+const INDEX_READ_RECORD_NUM: u64 = 1000;
+
+#[derive(Default, Debug)]
+pub struct DelayBucket {
+    pub messages: Vec<DelayMessageInfo>,
+    pub cancelled: Vec<String>,
+}
+
+// Position of a paginated index scan, the next entry to read
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DelayScanCursor {
+    pub window: u64,
+    pub offset: u64,
+}
+
+pub fn bucket_of(timestamp: u64) -> u64 {
+    timestamp / DELAY_BUCKET_SECONDS
+}
+
+pub fn window_of(bucket: u64) -> u64 {
+    bucket / DELAY_WINDOW_BUCKETS
+}
+
+// Ids start with the index window of the message, so it can be found without
+// a scan.
+pub fn build_delay_id(delay_timestamp: u64, unique: &str) -> String {
+    format!("{}-{}", window_of(bucket_of(delay_timestamp)), unique)
+}
+
+pub fn window_of_id(id: &str) -> Option<u64> {
+    id.split_once('-')?.0.parse().ok()
+}
+
+pub fn index_shard_name(window: u64) -> String {
+    format!("{DELAY_MESSAGE_INDEX_SHARD_PREFIX}{window}")
+}
+
+// Creates the index shard of a window and records the window. Creating a
+// window twice, e.g. from two brokers, only records it twice.
+pub async fn create_index_window(
     message_storage_adapter: &ArcStorageAdapter,
     namespace: &str,
-    delay_info: DelayMessageInfo,
+    window: u64,
 ) -> Result<(), CommonError> {
-    let data = Record::build_byte(serde_json::to_vec(&delay_info)?);
+    let shard_name = index_shard_name(window);
+    let shards = message_storage_adapter
+        .list_shard(namespace.to_owned(), shard_name.clone())
+        .await?;
+    if !shards
+        .iter()
+        .any(|shard| shard.namespace == namespace && shard.shard_name == shard_name)
+    {
+        message_storage_adapter
+            .create_shard(ShardInfo {
+                namespace: namespace.to_owned(),
+                shard_name,
+                replica_num: 1,
+            })
+            .await?;
+    }
+
+    let mut record = Record::build_byte(serde_json::to_vec(&window)?);
+    record.set_key(window.to_string());
     message_storage_adapter
         .write(
             namespace.to_owned(),
-            DELAY_QUEUE_INFO_SHARD_NAME.to_string(),
-            data,
+            DELAY_MESSAGE_WINDOW_SHARD_NAME.to_owned(),
+            record,
         )
         .await?;
     Ok(())
 }
 
-pub async fn recover_delay_queue(
+// The windows recorded from `offset` on, including those already dropped,
+// and the offset to continue from.
+pub async fn load_index_windows(
     message_storage_adapter: &ArcStorageAdapter,
-    delay_message_manager: &Arc<DelayMessageManager>,
     namespace: &str,
-    read_config: ReadConfig,
-    shard_num: u64,
-) {
-    let mut offset = 0;
-    let mut total_num = 0;
+    mut offset: u64,
+) -> Result<(BTreeSet<u64>, u64), CommonError> {
+    let read_config = ReadConfig {
+        max_record_num: INDEX_READ_RECORD_NUM,
+        max_size: 1024 * 1024 * 1024,
+    };
+
+    let mut windows = BTreeSet::new();
     loop {
-        let data = match message_storage_adapter
+        let data = message_storage_adapter
             .read_by_offset(
                 namespace.to_owned(),
-                DELAY_QUEUE_INFO_SHARD_NAME.to_owned(),
+                DELAY_MESSAGE_WINDOW_SHARD_NAME.to_owned(),
                 offset,
                 read_config.clone(),
             )
-            .await
+            .await?;
+
+        let Some(last_offset) = data.last().and_then(|record| record.offset) else {
+            break;
+        };
+        offset = last_offset + 1;
+        for record in data {
+            match serde_json::from_slice::<u64>(&record.data) {
+                Ok(window) => {
+                    windows.insert(window);
+                }
+                Err(e) => {
+                    error!(
+                        "Failed to parse delay message index window, error message: {:?}",
+                        e
+                    );
+                }
+            }
+        }
+    }
+    Ok((windows, offset))
+}
+
+// Every broker drops the windows its checkpoint moves past, a window that is
+// already gone counts as dropped.
+pub async fn delete_index_window(
+    message_storage_adapter: &ArcStorageAdapter,
+    namespace: &str,
+    window: u64,
+) -> Result<(), CommonError> {
+    let shard_name = index_shard_name(window);
+    if let Err(e) = message_storage_adapter
+        .delete_shard(namespace.to_owned(), shard_name.clone())
+        .await
+    {
+        let shards = message_storage_adapter
+            .list_shard(namespace.to_owned(), shard_name.clone())
+            .await?;
+        if shards
+            .iter()
+            .any(|shard| shard.namespace == namespace && shard.shard_name == shard_name)
         {
-            Ok(data) => data,
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+pub async fn persist_delay_info(
+    message_storage_adapter: &ArcStorageAdapter,
+    namespace: &str,
+    delay_info: &DelayMessageInfo,
+) -> Result<u64, CommonError> {
+    let record = build_index_record(delay_info.id.clone(), delay_info, None)?;
+    write_index_record(message_storage_adapter, namespace, delay_info, record).await
+}
+
+// Cancelling writes a tombstone next to the index entry, so the bucket drops
+// the message when it is loaded.
+pub async fn persist_delay_cancel(
+    message_storage_adapter: &ArcStorageAdapter,
+    namespace: &str,
+    delay_info: &DelayMessageInfo,
+) -> Result<u64, CommonError> {
+    let record = build_index_record(
+        cancel_key(&delay_info.id),
+        delay_info,
+        Some(DELAY_MESSAGE_CANCEL_TAG),
+    )?;
+    write_index_record(message_storage_adapter, namespace, delay_info, record).await
+}
+
+pub async fn read_delay_bucket(
+    message_storage_adapter: &ArcStorageAdapter,
+    namespace: &str,
+    bucket: u64,
+) -> Result<DelayBucket, CommonError> {
+    let mut result = DelayBucket::default();
+    for record in read_index_by_tag(
+        message_storage_adapter,
+        namespace,
+        window_of(bucket),
+        &bucket_tag(bucket),
+    )
+    .await?
+    {
+        let delay_info = match serde_json::from_slice::<DelayMessageInfo>(&record.data) {
+            Ok(delay_info) => delay_info,
             Err(e) => {
-                error!("Reading the shard {} failed with error * while building the deferred message index {:?}", DELAY_QUEUE_INFO_SHARD_NAME, e);
-                sleep(Duration::from_secs(1)).await;
+                error!(
+                    "Failed to parse delay message index entry in bucket {}, error message: {:?}",
+                    bucket, e
+                );
                 continue;
             }
         };
+        if record.key.starts_with(DELAY_MESSAGE_CANCEL_KEY_PREFIX) {
+            result.cancelled.push(delay_info.id);
+        } else {
+            result.messages.push(delay_info);
+        }
+    }
+    Ok(result)
+}
+
+// Returns the index entry of a message and whether it has been cancelled.
+pub async fn read_delay_info(
+    message_storage_adapter: &ArcStorageAdapter,
+    namespace: &str,
+    id: &str,
+) -> Result<Option<(DelayMessageInfo, bool)>, CommonError> {
+    let Some(window) = window_of_id(id) else {
+        return Ok(None);
+    };
+    let Some(record) = read_index_by_key(message_storage_adapter, namespace, window, id).await?
+    else {
+        return Ok(None);
+    };
+    let delay_info = serde_json::from_slice::<DelayMessageInfo>(&record.data)?;
+    let cancelled = read_index_by_key(message_storage_adapter, namespace, window, &cancel_key(id))
+        .await?
+        .is_some();
+    Ok(Some((delay_info, cancelled)))
+}
+
+// Scan the given windows for messages that have not been cancelled and are
+// due in `checkpoint` or a later bucket, optionally only those of one target
+// topic. Returns at most `max_num` messages and the cursor to continue from,
+// or None once every window has been read.
+pub async fn scan_delay_info(
+    message_storage_adapter: &ArcStorageAdapter,
+    namespace: &str,
+    windows: &[u64],
+    target_topic: Option<&str>,
+    checkpoint: u64,
+    cursor: Option<DelayScanCursor>,
+    max_num: usize,
+) -> Result<(Vec<DelayMessageInfo>, Option<DelayScanCursor>), CommonError> {
+    let read_config = ReadConfig {
+        max_record_num: INDEX_READ_RECORD_NUM,
+        max_size: 1024 * 1024 * 1024,
+    };
+
+    let cursor = cursor.unwrap_or_default();
+    let mut messages = Vec::new();
+    let mut seen = HashSet::new();
+    for &window in windows.iter().filter(|window| **window >= cursor.window) {
+        let mut offset = if window == cursor.window {
+            cursor.offset
+        } else {
+            0
+        };
+
+        // Tombstones come after the entries they cancel, so they are read
+        // up front rather than in the pages of the scan
+        let cancelled: HashSet<String> = read_index_by_tag(
+            message_storage_adapter,
+            namespace,
+            window,
+            DELAY_MESSAGE_CANCEL_TAG,
+        )
+        .await?
+        .into_iter()
+        .filter_map(|record| serde_json::from_slice::<DelayMessageInfo>(&record.data).ok())
+        .map(|delay_info| delay_info.id)
+        .collect();
+
+        loop {
+            let data = if let Some(topic) = target_topic {
+                message_storage_adapter
+                    .read_by_tag(
+                        namespace.to_owned(),
+                        index_shard_name(window),
+                        offset,
+                        topic_tag(topic),
+                        read_config.clone(),
+                    )
+                    .await?
+            } else {
+                message_storage_adapter
+                    .read_by_offset(
+                        namespace.to_owned(),
+                        index_shard_name(window),
+                        offset,
+                        read_config.clone(),
+                    )
+                    .await?
+            };
+
+            let Some(last_offset) = data.last().and_then(|record| record.offset) else {
+                break;
+            };
+            for record in data {
+                let Some(record_offset) = record.offset else {
+                    continue;
+                };
+                if record.key.starts_with(DELAY_MESSAGE_CANCEL_KEY_PREFIX) {
+                    continue;
+                }
+                let Ok(delay_info) = serde_json::from_slice::<DelayMessageInfo>(&record.data)
+                else {
+                    continue;
+                };
+                if bucket_of(delay_info.delay_timestamp) < checkpoint
+                    || cancelled.contains(&delay_info.id)
+                    || !seen.insert(delay_info.id.clone())
+                {
+                    continue;
+                }
+
+                messages.push(delay_info);
+                if messages.len() >= max_num {
+                    let next = DelayScanCursor {
+                        window,
+                        offset: record_offset + 1,
+                    };
+                    return Ok((messages, Some(next)));
+                }
+            }
+            offset = last_offset + 1;
+        }
+    }
+    Ok((messages, None))
+}
+
+pub async fn load_delay_checkpoint(
+    message_storage_adapter: &ArcStorageAdapter,
+    namespace: &str,
+) -> Result<Option<u64>, CommonError> {
+    let offsets = message_storage_adapter
+        .get_offset_by_group(checkpoint_group(namespace))
+        .await?;
+    Ok(offsets.iter().map(|offset| offset.offset).max())
+}
+
+pub async fn save_delay_checkpoint(
+    message_storage_adapter: &ArcStorageAdapter,
+    namespace: &str,
+    bucket: u64,
+) -> Result<(), CommonError> {
+    let mut offset = HashMap::new();
+    offset.insert(DELAY_MESSAGE_CHECKPOINT_KEY.to_owned(), bucket);
+    message_storage_adapter
+        .commit_offset(checkpoint_group(namespace), namespace.to_owned(), offset)
+        .await
+}
+
+// Move the pending entries of the old full-scan index into the bucket index
+// and truncate it. Entries keep a stable id, so importing again after a
+// failed truncate does not schedule a message twice.
+pub async fn import_legacy_delay_info(
+    message_storage_adapter: &ArcStorageAdapter,
+    namespace: &str,
+) -> Result<u64, CommonError> {
+    let read_config = ReadConfig {
+        max_record_num: INDEX_READ_RECORD_NUM,
+        max_size: 1024 * 1024 * 1024,
+    };
+
+    let mut offset = 0;
+    let mut total_num = 0;
+    let mut windows = HashSet::new();
+    loop {
+        let data = message_storage_adapter
+            .read_by_offset(
+                namespace.to_owned(),
+                DELAY_QUEUE_INFO_SHARD_NAME.to_owned(),
+                offset,
+                read_config.clone(),
+            )
+            .await?;
 
         if data.is_empty() {
             break;
         }
 
         for record in data {
-            offset = record.offset.unwrap();
+            let record_offset = record.offset.unwrap_or(offset);
+            offset = record_offset + 1;
 
-            let delay_info = match serde_json::from_slice::<DelayMessageInfo>(&record.data) {
+            let mut delay_info = match serde_json::from_slice::<DelayMessageInfo>(&record.data) {
                 Ok(delay_info) => delay_info,
                 Err(e) => {
-                    error!("While building the deferred message index, parsing the message failed with error message :{:?}", e);
+                    error!("While importing the legacy delay message index, parsing the message failed with error message :{:?}", e);
                     continue;
                 }
             };
@@ -88,136 +432,408 @@ pub async fn recover_delay_queue(
                 continue;
             }
 
-            let shard_no = offset % shard_num;
-            delay_message_manager.send_to_delay_queue(shard_no, &delay_info);
-
+            delay_info.id = build_delay_id(
+                delay_info.delay_timestamp,
+                &format!("legacy-{record_offset}"),
+            );
+            let window = window_of(bucket_of(delay_info.delay_timestamp));
+            if windows.insert(window) {
+                create_index_window(message_storage_adapter, namespace, window).await?;
+            }
+            persist_delay_info(message_storage_adapter, namespace, &delay_info).await?;
             total_num += 1;
         }
+    }
 
-        offset += 1;
+    if offset > 0 {
+        if let Err(e) = message_storage_adapter
+            .delete_before_offset(
+                namespace.to_owned(),
+                DELAY_QUEUE_INFO_SHARD_NAME.to_owned(),
+                offset,
+            )
+            .await
+        {
+            warn!(
+                "Failed to truncate the legacy delay message index, error message: {:?}",
+                e
+            );
+        }
+        info!(
+            "Imported {} pending delay messages from the legacy index",
+            total_num
+        );
     }
-    info!("Delay queue index was successfully constructed from the persistent store. Number of data items: {}", total_num);
+    Ok(total_num)
 }
 
-#[cfg(test)]
-mod test {
-    use std::{sync::Arc, time::Duration};
+async fn write_index_record(
+    message_storage_adapter: &ArcStorageAdapter,
+    namespace: &str,
+    delay_info: &DelayMessageInfo,
+    record: Record,
+) -> Result<u64, CommonError> {
+    message_storage_adapter
+        .write(
+            namespace.to_owned(),
+            index_shard_name(window_of(bucket_of(delay_info.delay_timestamp))),
+            record,
+        )
+        .await
+}
+
+fn build_index_record(
+    key: String,
+    delay_info: &DelayMessageInfo,
+    extra_tag: Option<&str>,
+) -> Result<Record, CommonError> {
+    let mut record = Record::build_byte(serde_json::to_vec(delay_info)?);
+    record.set_key(key);
+    let mut tags = vec![
+        bucket_tag(bucket_of(delay_info.delay_timestamp)),
+        topic_tag(&delay_info.target_shard_name),
+    ];
+    tags.extend(extra_tag.map(str::to_owned));
+    record.set_tags(tags);
+    Ok(record)
+}
 
-    use common_base::tools::unique_id;
-    use metadata_struct::{
-        adapter::{read_config::ReadConfig, record::Record},
-        delay_info::DelayMessageInfo,
+async fn read_index_by_tag(
+    message_storage_adapter: &ArcStorageAdapter,
+    namespace: &str,
+    window: u64,
+    tag: &str,
+) -> Result<Vec<Record>, CommonError> {
+    let read_config = ReadConfig {
+        max_record_num: INDEX_READ_RECORD_NUM,
+        max_size: 1024 * 1024 * 1024,
     };
+
+    let mut results = Vec::new();
+    let mut offset = 0;
+    loop {
+        let data = message_storage_adapter
+            .read_by_tag(
+                namespace.to_owned(),
+                index_shard_name(window),
+                offset,
+                tag.to_owned(),
+                read_config.clone(),
+            )
+            .await?;
+
+        let Some(last_offset) = data.last().and_then(|record| record.offset) else {
+            break;
+        };
+        offset = last_offset + 1;
+        results.extend(data);
+    }
+    Ok(results)
+}
+
+async fn read_index_by_key(
+    message_storage_adapter: &ArcStorageAdapter,
+    namespace: &str,
+    window: u64,
+    key: &str,
+) -> Result<Option<Record>, CommonError> {
+    let read_config = ReadConfig {
+        max_record_num: 1,
+        max_size: 1024 * 1024 * 1024,
+    };
+    let results = message_storage_adapter
+        .read_by_key(
+            namespace.to_owned(),
+            index_shard_name(window),
+            0,
+            key.to_owned(),
+            read_config,
+        )
+        .await?;
+    Ok(results.into_iter().next())
+}
+
+fn bucket_tag(bucket: u64) -> String {
+    format!("bucket-{bucket}")
+}
+
+fn topic_tag(topic: &str) -> String {
+    format!("topic-{topic}")
+}
+
+fn cancel_key(id: &str) -> String {
+    format!("{DELAY_MESSAGE_CANCEL_KEY_PREFIX}{id}")
+}
+
+fn checkpoint_group(namespace: &str) -> String {
+    format!("{DELAY_MESSAGE_CHECKPOINT_GROUP_PREFIX}{namespace}")
+}
+
+#[cfg(test)]
+mod test {
+    use common_base::tools::{now_second, unique_id};
+    use metadata_struct::{adapter::record::Record, delay_info::DelayMessageInfo};
     use storage_adapter::storage::build_memory_storage_driver;
-    use tokio::time::sleep;
 
-    use crate::{
-        persist::{persist_delay_info, recover_delay_queue, DELAY_QUEUE_INFO_SHARD_NAME},
-        pop::read_offset_data,
-        start_delay_message_pop, DelayMessageManager,
+    use crate::persist::{
+        bucket_of, build_delay_id, create_index_window, delete_index_window,
+        import_legacy_delay_info, load_delay_checkpoint, load_index_windows, persist_delay_cancel,
+        persist_delay_info, read_delay_bucket, read_delay_info, save_delay_checkpoint,
+        scan_delay_info, window_of, window_of_id, DelayScanCursor, DELAY_BUCKET_SECONDS,
+        DELAY_QUEUE_INFO_SHARD_NAME, DELAY_WINDOW_BUCKETS,
     };
 
+    fn build_delay_info(name: &str, target: &str, delay_timestamp: u64) -> DelayMessageInfo {
+        DelayMessageInfo {
+            id: build_delay_id(delay_timestamp, name),
+            delay_shard_name: "s1".to_owned(),
+            target_shard_name: target.to_owned(),
+            offset: 0,
+            delay_timestamp,
+            create_time: now_second(),
+        }
+    }
+
+    #[test]
+    pub fn delay_id_test() {
+        let delay_timestamp = 7 * DELAY_WINDOW_BUCKETS * DELAY_BUCKET_SECONDS + 5;
+        let id = build_delay_id(delay_timestamp, &unique_id());
+        assert_eq!(window_of_id(&id), Some(7));
+        assert_eq!(window_of_id("legacy"), None);
+        assert_eq!(window_of_id("abc-1"), None);
+    }
+
     #[tokio::test]
-    pub async fn persist_delay_info_test() {
+    pub async fn read_delay_bucket_test() {
         let message_storage_adapter = build_memory_storage_driver();
         let namespace = unique_id();
+        let bucket = (window_of(bucket_of(now_second())) + 1) * DELAY_WINDOW_BUCKETS;
+        let start = bucket * DELAY_BUCKET_SECONDS;
 
-        let target_shard_name = unique_id();
-        let delay_shard_name = unique_id();
-        for i in 0..10 {
-            let delay_info = DelayMessageInfo {
-                delay_shard_name: delay_shard_name.to_owned(),
-                target_shard_name: target_shard_name.to_owned(),
-                offset: i,
-                delay_timestamp: 5,
-            };
-
-            let res = persist_delay_info(&message_storage_adapter, &namespace, delay_info).await;
+        for i in 0..5 {
+            let delay_info = build_delay_info(&format!("m{i}"), "t1", start + i);
+            let res = persist_delay_info(&message_storage_adapter, &namespace, &delay_info).await;
             assert!(res.is_ok());
         }
+        let other = build_delay_info("other", "t1", start + DELAY_BUCKET_SECONDS);
+        persist_delay_info(&message_storage_adapter, &namespace, &other)
+            .await
+            .unwrap();
+        let cancelled = build_delay_info("m2", "t1", start + 2);
+        persist_delay_cancel(&message_storage_adapter, &namespace, &cancelled)
+            .await
+            .unwrap();
 
-        for i in 0..10 {
-            let res = read_offset_data(
-                &message_storage_adapter,
-                &namespace,
-                DELAY_QUEUE_INFO_SHARD_NAME,
-                i,
-            )
-            .await;
-            assert!(res.is_ok());
-            let raw = res.unwrap().unwrap();
-            assert_eq!(raw.offset.unwrap(), i);
-
-            let d = serde_json::from_slice::<DelayMessageInfo>(&raw.data).unwrap();
-            assert_eq!(d.target_shard_name, target_shard_name);
-            assert_eq!(d.delay_shard_name, delay_shard_name);
-            assert_eq!(d.offset, i);
-            assert_eq!(d.delay_timestamp, 5);
-        }
+        let res = read_delay_bucket(&message_storage_adapter, &namespace, bucket)
+            .await
+            .unwrap();
+        assert_eq!(res.messages.len(), 5);
+        assert_eq!(res.cancelled, vec![cancelled.id]);
+
+        let res = read_delay_bucket(&message_storage_adapter, &namespace, bucket + 1)
+            .await
+            .unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(res.messages[0].id, other.id);
     }
 
     #[tokio::test]
-    pub async fn build_delay_queue_test() {
-        let namespace = unique_id();
-        let shard_num = 1;
+    pub async fn read_and_scan_delay_info_test() {
         let message_storage_adapter = build_memory_storage_driver();
-        let delay_message_manager = Arc::new(DelayMessageManager::new(
-            namespace.clone(),
-            shard_num,
-            message_storage_adapter.clone(),
-        ));
-        delay_message_manager.start().await;
-
-        let target_topic = unique_id();
-        for i in 0..10 {
-            let data = Record::build_str(format!("data{i}"));
-            let res: Result<(), common_base::error::common::CommonError> =
-                delay_message_manager.send(&target_topic, i + 1, data).await;
+        let namespace = unique_id();
+        let window = window_of(bucket_of(now_second())) + 1;
+        let start = window * DELAY_WINDOW_BUCKETS * DELAY_BUCKET_SECONDS;
 
-            assert!(res.is_ok());
+        // Two windows, the second one starts with m4
+        let mut messages = Vec::new();
+        for i in 0..6 {
+            let target = if i % 2 == 0 { "t1" } else { "t2" };
+            let delay_timestamp = if i < 4 {
+                start + i
+            } else {
+                start + DELAY_WINDOW_BUCKETS * DELAY_BUCKET_SECONDS + i
+            };
+            let delay_info = build_delay_info(&format!("m{i}"), target, delay_timestamp);
+            persist_delay_info(&message_storage_adapter, &namespace, &delay_info)
+                .await
+                .unwrap();
+            messages.push(delay_info);
         }
+        persist_delay_cancel(&message_storage_adapter, &namespace, &messages[0])
+            .await
+            .unwrap();
+
+        let (delay_info, cancelled) =
+            read_delay_info(&message_storage_adapter, &namespace, &messages[0].id)
+                .await
+                .unwrap()
+                .unwrap();
+        assert_eq!(delay_info.target_shard_name, "t1");
+        assert!(cancelled);
+
+        let (_, cancelled) = read_delay_info(&message_storage_adapter, &namespace, &messages[1].id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!cancelled);
 
-        let new_delay_message_manager = Arc::new(DelayMessageManager::new(
-            namespace.clone(),
-            shard_num,
-            message_storage_adapter.clone(),
-        ));
-        new_delay_message_manager.start().await;
+        assert!(
+            read_delay_info(&message_storage_adapter, &namespace, "none")
+                .await
+                .unwrap()
+                .is_none()
+        );
 
-        start_delay_message_pop(
-            &new_delay_message_manager,
+        let windows = [window, window + 1];
+        let checkpoint = window * DELAY_WINDOW_BUCKETS;
+        let (res, next) = scan_delay_info(
             &message_storage_adapter,
             &namespace,
-            shard_num,
-        );
+            &windows,
+            Some("t1"),
+            checkpoint,
+            None,
+            100,
+        )
+        .await
+        .unwrap();
+        let ids: Vec<String> = res.into_iter().map(|delay_info| delay_info.id).collect();
+        assert_eq!(ids, vec![messages[2].id.clone(), messages[4].id.clone()]);
+        assert!(next.is_none());
 
-        // build delay queue
-        let read_config = ReadConfig {
-            max_record_num: 100,
-            max_size: 1024 * 1024 * 1024,
-        };
+        // Pages continue from the cursor, the tombstone of m0 takes no room
+        let mut ids = Vec::new();
+        let mut cursor = None;
+        loop {
+            let (res, next) = scan_delay_info(
+                &message_storage_adapter,
+                &namespace,
+                &windows,
+                None,
+                checkpoint,
+                cursor,
+                2,
+            )
+            .await
+            .unwrap();
+            ids.extend(res.into_iter().map(|delay_info| delay_info.id));
+            if next.is_none() {
+                break;
+            }
+            cursor = next;
+        }
+        let expect: Vec<String> = messages[1..].iter().map(|m| m.id.clone()).collect();
+        assert_eq!(ids, expect);
 
-        recover_delay_queue(
+        // Entries before the checkpoint have been delivered
+        let (res, _) = scan_delay_info(
             &message_storage_adapter,
-            &new_delay_message_manager,
             &namespace,
-            read_config,
-            shard_num,
+            &windows,
+            None,
+            checkpoint + 1,
+            Some(DelayScanCursor::default()),
+            100,
         )
-        .await;
+        .await
+        .unwrap();
+        assert_eq!(res.len(), 2);
+    }
 
-        sleep(Duration::from_secs(15)).await;
+    #[tokio::test]
+    pub async fn index_window_test() {
+        let message_storage_adapter = build_memory_storage_driver();
+        let namespace = unique_id();
 
-        for i in 0..10 {
-            let res =
-                read_offset_data(&message_storage_adapter, &namespace, &target_topic, i).await;
-            assert!(res.is_ok());
-            println!("i:{i},res:{res:?}");
-            let raw = res.unwrap().unwrap();
-            assert_eq!(raw.offset.unwrap(), i);
+        create_index_window(&message_storage_adapter, &namespace, 10)
+            .await
+            .unwrap();
+        create_index_window(&message_storage_adapter, &namespace, 12)
+            .await
+            .unwrap();
+        let (windows, offset) = load_index_windows(&message_storage_adapter, &namespace, 0)
+            .await
+            .unwrap();
+        assert_eq!(windows.into_iter().collect::<Vec<u64>>(), vec![10, 12]);
+
+        create_index_window(&message_storage_adapter, &namespace, 11)
+            .await
+            .unwrap();
+        let (windows, _) = load_index_windows(&message_storage_adapter, &namespace, offset)
+            .await
+            .unwrap();
+        assert_eq!(windows.into_iter().collect::<Vec<u64>>(), vec![11]);
+
+        delete_index_window(&message_storage_adapter, &namespace, 10)
+            .await
+            .unwrap();
+        delete_index_window(&message_storage_adapter, &namespace, 10)
+            .await
+            .unwrap();
+    }
 
-            let d: String = serde_json::from_slice(&raw.data).unwrap();
-            assert_eq!(d, format!("data{i}"));
+    #[tokio::test]
+    pub async fn delay_checkpoint_test() {
+        let message_storage_adapter = build_memory_storage_driver();
+        let namespace = unique_id();
+
+        let res = load_delay_checkpoint(&message_storage_adapter, &namespace)
+            .await
+            .unwrap();
+        assert!(res.is_none());
+
+        save_delay_checkpoint(&message_storage_adapter, &namespace, 10)
+            .await
+            .unwrap();
+        save_delay_checkpoint(&message_storage_adapter, &namespace, 12)
+            .await
+            .unwrap();
+        let res = load_delay_checkpoint(&message_storage_adapter, &namespace)
+            .await
+            .unwrap();
+        assert_eq!(res, Some(12));
+    }
+
+    #[tokio::test]
+    pub async fn import_legacy_delay_info_test() {
+        let message_storage_adapter = build_memory_storage_driver();
+        let namespace = unique_id();
+        let now = now_second();
+
+        for delay_timestamp in [now - 10, now + 100, now + 200] {
+            let mut delay_info = build_delay_info("", "t1", delay_timestamp);
+            delay_info.id = String::new();
+            let data = Record::build_byte(serde_json::to_vec(&delay_info).unwrap());
+            message_storage_adapter
+                .write(
+                    namespace.clone(),
+                    DELAY_QUEUE_INFO_SHARD_NAME.to_owned(),
+                    data,
+                )
+                .await
+                .unwrap();
         }
+
+        let res = import_legacy_delay_info(&message_storage_adapter, &namespace)
+            .await
+            .unwrap();
+        assert_eq!(res, 2);
+
+        let id = build_delay_id(now + 100, "legacy-1");
+        let (delay_info, _) = read_delay_info(&message_storage_adapter, &namespace, &id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(delay_info.delay_timestamp, now + 100);
+
+        let (windows, _) = load_index_windows(&message_storage_adapter, &namespace, 0)
+            .await
+            .unwrap();
+        assert!(windows.contains(&window_of(bucket_of(now + 100))));
+
+        let res = import_legacy_delay_info(&message_storage_adapter, &namespace)
+            .await
+            .unwrap();
+        assert_eq!(res, 0);
     }
 }
@@ -16,34 +16,25 @@
 
 use crate::DelayMessageManager;
 use common_base::error::common::CommonError;
-use futures::StreamExt;
 use metadata_struct::{
     adapter::{read_config::ReadConfig, record::Record},
     delay_info::DelayMessageInfo,
 };
 use storage_adapter::storage::ArcStorageAdapter;
-use tracing::{error, info};
+use tracing::{debug, error, info};
 
-pub async fn pop_delay_queue(
-    namespace: &str,
-    message_storage_adapter: &ArcStorageAdapter,
-    delay_message_manager: &Arc<DelayMessageManager>,
-    shard_no: u64,
-) {
-    if let Some(mut delay_queue) = delay_message_manager.delay_queue_list.get_mut(&shard_no) {
-        while let Some(expired) = delay_queue.next().await {
-            let delay_message = expired.into_inner();
-            let raw_message_storage_adapter = message_storage_adapter.clone();
-            let raw_namespace = namespace.to_owned();
-            tokio::spawn(async move {
-                send_delay_message_to_shard(
-                    &raw_message_storage_adapter,
-                    &raw_namespace,
-                    delay_message,
-                )
-                .await;
-            });
-        }
+pub fn pop_delay_queue(delay_message_manager: &Arc<DelayMessageManager>, now: u64) {
+    for delay_message in delay_message_manager.pop_expired(now) {
+        let raw_message_storage_adapter = delay_message_manager.message_storage_adapter.clone();
+        let raw_namespace = delay_message_manager.namespace.clone();
+        tokio::spawn(async move {
+            send_delay_message_to_shard(
+                &raw_message_storage_adapter,
+                &raw_namespace,
+                delay_message,
+            )
+            .await;
+        });
     }
 }
 
@@ -92,6 +83,22 @@ async fn send_delay_message_to_shard(
         {
             Ok(id) => {
                 info!("Delay message: message was written to {:?} successfully, offset: {:?}, delay info: {:?}",delay_message.target_shard_name,id, delay_message);
+
+                // A missing payload also keeps a bucket that is loaded again
+                // after a restart from delivering the message twice
+                if let Err(e) = message_storage_adapter
+                    .delete_by_offsets(
+                        namespace.to_owned(),
+                        delay_message.delay_shard_name.to_owned(),
+                        vec![delay_message.offset],
+                    )
+                    .await
+                {
+                    debug!(
+                        "Failed to delete delivered delay message payload, err: {:?}",
+                        e
+                    );
+                }
                 break;
             }
             Err(e) => {
@@ -134,16 +141,36 @@ pub(crate) async fn read_offset_data(
 mod test {
     use std::{sync::Arc, time::Duration};
 
-    use common_base::tools::unique_id;
+    use common_base::tools::{now_second, unique_id};
     use metadata_struct::{adapter::record::Record, delay_info::DelayMessageInfo};
-    use storage_adapter::storage::build_memory_storage_driver;
+    use storage_adapter::storage::{build_memory_storage_driver, ArcStorageAdapter};
     use tokio::time::sleep;
 
     use crate::{
         pop::{read_offset_data, send_delay_message_to_shard},
-        start_delay_message_pop, DelayMessageManager,
+        start_delay_message_manager, DelayMessageManager, DelayMessageStatus,
     };
 
+    async fn build_started_manager(
+        message_storage_adapter: &ArcStorageAdapter,
+        namespace: &str,
+    ) -> Arc<DelayMessageManager> {
+        let delay_message_manager = Arc::new(DelayMessageManager::new(
+            namespace.to_owned(),
+            2,
+            message_storage_adapter.clone(),
+        ));
+        start_delay_message_manager(
+            &delay_message_manager,
+            message_storage_adapter,
+            namespace,
+            2,
+        )
+        .await
+        .unwrap();
+        delay_message_manager
+    }
+
     #[tokio::test]
     pub async fn read_offset_data_test() {
         let message_storage_adapter = build_memory_storage_driver();
@@ -184,10 +211,12 @@ pub async fn send_delay_message_to_shard_test() {
         let target_shard_name = unique_id();
         for i in 0..100 {
             let delay_message: DelayMessageInfo = DelayMessageInfo {
+                id: unique_id(),
                 delay_shard_name: shard_name.to_owned(),
                 target_shard_name: target_shard_name.to_owned(),
                 offset: i,
                 delay_timestamp: 5,
+                create_time: 0,
             };
             send_delay_message_to_shard(&message_storage_adapter, &namespace, delay_message).await;
         }
@@ -201,48 +230,166 @@ pub async fn send_delay_message_to_shard_test() {
 
             let d: String = serde_json::from_slice(&raw.data).unwrap();
             assert_eq!(d, format!("data{i}"));
+
+            let res = read_offset_data(&message_storage_adapter, &namespace, &shard_name, i).await;
+            assert!(res.unwrap().is_none());
         }
     }
 
     #[tokio::test]
     pub async fn pop_delay_queue_test() {
         let namespace = unique_id();
-        let shard_num = 1;
         let message_storage_adapter = build_memory_storage_driver();
-        let delay_message_manager = Arc::new(DelayMessageManager::new(
-            namespace.clone(),
-            shard_num,
-            message_storage_adapter.clone(),
-        ));
-        delay_message_manager.start().await;
-
-        start_delay_message_pop(
-            &delay_message_manager,
-            &message_storage_adapter,
-            &namespace,
-            shard_num,
-        );
+        let delay_message_manager =
+            build_started_manager(&message_storage_adapter, &namespace).await;
 
         let target_topic = unique_id();
+        let mut ids = Vec::new();
         for i in 0..10 {
             let data = Record::build_str(format!("data{i}"));
-            let res = delay_message_manager.send(&target_topic, i + 1, data).await;
-
-            assert!(res.is_ok());
+            let res = delay_message_manager
+                .send(&target_topic, i % 3 + 1, data)
+                .await;
+            ids.push(res.unwrap());
         }
 
-        sleep(Duration::from_secs(15)).await;
+        let pending = delay_message_manager
+            .list(Some(&target_topic), 100)
+            .await
+            .unwrap();
+        assert_eq!(pending.len(), 10);
+
+        sleep(Duration::from_secs(6)).await;
 
+        let mut data = Vec::new();
         for i in 0..10 {
             let res =
                 read_offset_data(&message_storage_adapter, &namespace, &target_topic, i).await;
-            assert!(res.is_ok());
             let raw = res.unwrap().unwrap();
-            assert_eq!(raw.offset.unwrap(), i);
             let d: String = serde_json::from_slice(&raw.data).unwrap();
-            println!("i:{i},res:{d:?}")
+            data.push(d);
+        }
+        data.sort();
+        let mut expect: Vec<String> = (0..10).map(|i| format!("data{i}")).collect();
+        expect.sort();
+        assert_eq!(data, expect);
+
+        // The entries are gone once the checkpoint leaves their window
+        for id in ids {
+            if let Some(entry) = delay_message_manager.get(&id).await.unwrap() {
+                assert_eq!(entry.status, DelayMessageStatus::Delivered);
+            }
+        }
+        let pending = delay_message_manager
+            .list(Some(&target_topic), 100)
+            .await
+            .unwrap();
+        assert!(pending.is_empty());
+        delay_message_manager.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    pub async fn cancel_delay_message_test() {
+        let namespace = unique_id();
+        let message_storage_adapter = build_memory_storage_driver();
+        let delay_message_manager =
+            build_started_manager(&message_storage_adapter, &namespace).await;
+
+        let target_topic = unique_id();
+        let other_topic = unique_id();
+        let first = delay_message_manager
+            .send(&target_topic, 2, Record::build_str("first".to_string()))
+            .await
+            .unwrap();
+        for i in 0..5 {
+            delay_message_manager
+                .send(&other_topic, 2 + i, Record::build_str(format!("other{i}")))
+                .await
+                .unwrap();
+        }
+        let kept = delay_message_manager
+            .send(&target_topic, 2, Record::build_str("kept".to_string()))
+            .await
+            .unwrap();
+
+        assert!(delay_message_manager.cancel(&first).await.unwrap());
+        assert!(!delay_message_manager.cancel(&first).await.unwrap());
+        assert!(!delay_message_manager.cancel("not-exist").await.unwrap());
+
+        let entry = delay_message_manager.get(&first).await.unwrap().unwrap();
+        assert_eq!(entry.status, DelayMessageStatus::Cancelled);
+        assert!(delay_message_manager
+            .get_payload(&entry.info)
+            .await
+            .unwrap()
+            .is_none());
+
+        let entry = delay_message_manager.get(&kept).await.unwrap().unwrap();
+        assert_eq!(entry.status, DelayMessageStatus::Pending);
+        assert!(delay_message_manager
+            .get_payload(&entry.info)
+            .await
+            .unwrap()
+            .is_some());
+
+        assert_eq!(
+            delay_message_manager
+                .cancel_by_topic(&other_topic)
+                .await
+                .unwrap(),
+            5
+        );
+
+        sleep(Duration::from_secs(9)).await;
+
+        let res = read_offset_data(&message_storage_adapter, &namespace, &target_topic, 0).await;
+        let d: String = serde_json::from_slice(&res.unwrap().unwrap().data).unwrap();
+        assert_eq!(d, "kept".to_string());
+        let res = read_offset_data(&message_storage_adapter, &namespace, &target_topic, 1).await;
+        assert!(res.unwrap().is_none());
+        let res = read_offset_data(&message_storage_adapter, &namespace, &other_topic, 0).await;
+        assert!(res.unwrap().is_none());
+        delay_message_manager.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    pub async fn recover_after_restart_test() {
+        let namespace = unique_id();
+        let message_storage_adapter = build_memory_storage_driver();
+        let delay_message_manager =
+            build_started_manager(&message_storage_adapter, &namespace).await;
 
-            // assert_eq!(d, format!("data{}", i));
+        let target_topic = unique_id();
+        for i in 0..10 {
+            let data = Record::build_str(format!("data{i}"));
+            let res = delay_message_manager
+                .send(&target_topic, i % 3 + 2, data)
+                .await;
+            assert!(res.is_ok());
         }
+        let far = delay_message_manager
+            .send(&target_topic, 3600, Record::build_str("far".to_string()))
+            .await
+            .unwrap();
+        delay_message_manager.stop().await.unwrap();
+        drop(delay_message_manager);
+
+        let new_delay_message_manager =
+            build_started_manager(&message_storage_adapter, &namespace).await;
+
+        sleep(Duration::from_secs(6)).await;
+
+        for i in 0..10 {
+            let res =
+                read_offset_data(&message_storage_adapter, &namespace, &target_topic, i).await;
+            assert!(res.unwrap().is_some());
+        }
+        let res = read_offset_data(&message_storage_adapter, &namespace, &target_topic, 10).await;
+        assert!(res.unwrap().is_none());
+
+        let entry = new_delay_message_manager.get(&far).await.unwrap().unwrap();
+        assert_eq!(entry.status, DelayMessageStatus::Pending);
+        assert!(entry.info.delay_timestamp >= now_second() + 3500);
+        new_delay_message_manager.stop().await.unwrap();
     }
 }
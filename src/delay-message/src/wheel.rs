@@ -0,0 +1,202 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::mem;
+
+const WHEEL_SLOTS: u64 = 60;
+
+const WHEEL_LEVELS: usize = 3;
+
+// Hierarchical timing wheel with one second ticks. A slot of level `l` spans
+// WHEEL_SLOTS^l seconds, and entries move down a level whenever the wheel
+// reaches the start of their slot. Deadlines past the span of the top level
+// stay on the top level and are placed again every time their slot comes up.
+pub struct TimingWheel<T> {
+    current: u64,
+    levels: Vec<Vec<Vec<(u64, T)>>>,
+    ready: Vec<T>,
+    len: usize,
+}
+
+impl<T> TimingWheel<T> {
+    pub fn new(now: u64) -> Self {
+        let levels = (0..WHEEL_LEVELS)
+            .map(|_| (0..WHEEL_SLOTS).map(|_| Vec::new()).collect())
+            .collect();
+        TimingWheel {
+            current: now,
+            levels,
+            ready: Vec::new(),
+            len: 0,
+        }
+    }
+
+    // Entries whose deadline has already passed are returned by the next call
+    // of `advance`.
+    pub fn insert(&mut self, deadline: u64, item: T) {
+        self.len += 1;
+        self.place(deadline, item);
+    }
+
+    // Move the wheel to `now` and return every entry that is due.
+    pub fn advance(&mut self, now: u64) -> Vec<T> {
+        while self.current < now {
+            self.current += 1;
+            self.tick();
+        }
+        let expired = mem::take(&mut self.ready);
+        self.len -= expired.len();
+        expired
+    }
+
+    pub fn remove<F>(&mut self, mut predicate: F) -> usize
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let before = self.len;
+        self.ready.retain(|item| !predicate(item));
+        let mut remaining = self.ready.len();
+        for level in self.levels.iter_mut() {
+            for slot in level.iter_mut() {
+                slot.retain(|(_, item)| !predicate(item));
+                remaining += slot.len();
+            }
+        }
+        self.len = remaining;
+        before - remaining
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn current(&self) -> u64 {
+        self.current
+    }
+
+    fn tick(&mut self) {
+        for level in (1..WHEEL_LEVELS).rev() {
+            let span = slot_span(level);
+            if self.current.is_multiple_of(span) {
+                self.cascade(level, (self.current / span) % WHEEL_SLOTS);
+            }
+        }
+        self.cascade(0, self.current % WHEEL_SLOTS);
+    }
+
+    fn cascade(&mut self, level: usize, slot: u64) {
+        let entries = mem::take(&mut self.levels[level][slot as usize]);
+        for (deadline, item) in entries {
+            self.place(deadline, item);
+        }
+    }
+
+    fn place(&mut self, deadline: u64, item: T) {
+        if deadline <= self.current {
+            self.ready.push(item);
+            return;
+        }
+
+        let delta = deadline - self.current;
+        let mut level = 0;
+        while level + 1 < WHEEL_LEVELS && delta >= slot_span(level + 1) {
+            level += 1;
+        }
+        let slot = (deadline / slot_span(level)) % WHEEL_SLOTS;
+        self.levels[level][slot as usize].push((deadline, item));
+    }
+}
+
+fn slot_span(level: usize) -> u64 {
+    WHEEL_SLOTS.pow(level as u32)
+}
+
+#[cfg(test)]
+mod test {
+    use super::TimingWheel;
+
+    #[test]
+    pub fn fire_on_deadline_test() {
+        let mut wheel = TimingWheel::new(1000);
+        wheel.insert(1001, "a");
+        wheel.insert(1005, "b");
+        wheel.insert(1005, "c");
+        assert_eq!(wheel.len(), 3);
+
+        assert!(wheel.advance(1000).is_empty());
+        assert_eq!(wheel.advance(1001), vec!["a"]);
+        assert!(wheel.advance(1004).is_empty());
+
+        let mut fired = wheel.advance(1005);
+        fired.sort();
+        assert_eq!(fired, vec!["b", "c"]);
+        assert!(wheel.is_empty());
+    }
+
+    #[test]
+    pub fn cascade_levels_test() {
+        let start = 7_199;
+        let mut wheel = TimingWheel::new(start);
+        let deadlines = [
+            start + 59,
+            start + 60,
+            start + 61,
+            start + 3_601,
+            start + 300_000,
+        ];
+        for deadline in deadlines {
+            wheel.insert(deadline, deadline);
+        }
+
+        let mut fired = Vec::new();
+        for now in start..=start + 300_000 {
+            for deadline in wheel.advance(now) {
+                assert_eq!(deadline, now);
+                fired.push(deadline);
+            }
+        }
+        assert_eq!(fired, deadlines.to_vec());
+        assert!(wheel.is_empty());
+    }
+
+    #[test]
+    pub fn overdue_and_jump_test() {
+        let mut wheel = TimingWheel::new(100);
+        wheel.insert(50, 1);
+        wheel.insert(130, 2);
+        wheel.insert(250, 3);
+        assert_eq!(wheel.advance(100), vec![1]);
+
+        let mut fired = wheel.advance(1000);
+        fired.sort();
+        assert_eq!(fired, vec![2, 3]);
+        assert_eq!(wheel.current(), 1000);
+    }
+
+    #[test]
+    pub fn remove_test() {
+        let mut wheel = TimingWheel::new(0);
+        for i in 1..=10 {
+            wheel.insert(i * 30, i);
+        }
+        assert_eq!(wheel.remove(|item| item % 2 == 0), 5);
+        assert_eq!(wheel.len(), 5);
+        assert_eq!(wheel.advance(300), vec![1, 3, 5, 7, 9]);
+        assert!(wheel.is_empty());
+    }
+}
@@ -16,6 +16,9 @@
 use protocol::journal::journal_admin::{
     ListSegmentReply, ListSegmentRequest, ListShardReply, ListShardRequest,
 };
+use protocol::journal::journal_segment_admin::{
+    GetSegmentFileReply, GetSegmentFileRequest, SealUpSegmentReply, SealUpSegmentRequest,
+};
 
 use crate::pool::ClientPool;
 
@@ -44,3 +47,17 @@ pub async fn $fn_name(
     ListSegmentReply,
     ListSegment
 );
+
+generate_journal_admin_service_call!(
+    journal_admin_get_segment_file,
+    GetSegmentFileRequest,
+    GetSegmentFileReply,
+    GetSegmentFile
+);
+
+generate_journal_admin_service_call!(
+    journal_admin_seal_up_segment,
+    SealUpSegmentRequest,
+    SealUpSegmentReply,
+    SealUpSegment
+);
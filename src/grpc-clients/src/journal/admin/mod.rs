@@ -18,6 +18,10 @@
 use protocol::journal::journal_admin::{
     ListSegmentReply, ListSegmentRequest, ListShardReply, ListShardRequest,
 };
+use protocol::journal::journal_segment_admin::journal_segment_admin_service_client::JournalSegmentAdminServiceClient;
+use protocol::journal::journal_segment_admin::{
+    GetSegmentFileReply, GetSegmentFileRequest, SealUpSegmentReply, SealUpSegmentRequest,
+};
 use tonic::transport::Channel;
 
 use crate::macros::impl_retriable_request;
@@ -76,3 +80,53 @@ async fn check(&self, conn: Self::Connection) -> Result<Self::Connection, Self::
     journal_admin_services_client,
     list_segment
 );
+
+#[derive(Clone)]
+pub struct JournalSegmentAdminServiceManager {
+    pub addr: String,
+}
+
+impl JournalSegmentAdminServiceManager {
+    pub fn new(addr: String) -> Self {
+        Self { addr }
+    }
+}
+
+#[tonic::async_trait]
+impl Manager for JournalSegmentAdminServiceManager {
+    type Connection = JournalSegmentAdminServiceClient<Channel>;
+    type Error = CommonError;
+
+    async fn connect(&self) -> Result<Self::Connection, Self::Error> {
+        match JournalSegmentAdminServiceClient::connect(format!("http://{}", self.addr.clone()))
+            .await
+        {
+            Ok(client) => Ok(client),
+            Err(err) => Err(CommonError::CommonError(format!(
+                "{},{}",
+                err,
+                self.addr.clone()
+            ))),
+        }
+    }
+
+    async fn check(&self, conn: Self::Connection) -> Result<Self::Connection, Self::Error> {
+        Ok(conn)
+    }
+}
+
+impl_retriable_request!(
+    GetSegmentFileRequest,
+    JournalSegmentAdminServiceClient<Channel>,
+    GetSegmentFileReply,
+    journal_segment_admin_services_client,
+    get_segment_file
+);
+
+impl_retriable_request!(
+    SealUpSegmentRequest,
+    JournalSegmentAdminServiceClient<Channel>,
+    SealUpSegmentReply,
+    journal_segment_admin_services_client,
+    seal_up_segment
+);
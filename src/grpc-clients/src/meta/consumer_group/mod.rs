@@ -0,0 +1,85 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_base::error::common::CommonError;
+use mobc::Manager;
+use protocol::meta::meta_service_consumer_group::consumer_group_service_client::ConsumerGroupServiceClient;
+use protocol::meta::meta_service_consumer_group::{
+    CommitConsumerGroupOffsetReply, CommitConsumerGroupOffsetRequest, ConsumerGroupHeartbeatReply,
+    ConsumerGroupHeartbeatRequest, LeaveConsumerGroupReply, LeaveConsumerGroupRequest,
+};
+use tonic::transport::Channel;
+
+use crate::macros::impl_retriable_request;
+
+pub mod call;
+
+#[derive(Clone)]
+pub struct ConsumerGroupServiceManager {
+    pub addr: String,
+}
+
+impl ConsumerGroupServiceManager {
+    pub fn new(addr: String) -> Self {
+        Self { addr }
+    }
+}
+
+#[tonic::async_trait]
+impl Manager for ConsumerGroupServiceManager {
+    type Connection = ConsumerGroupServiceClient<Channel>;
+    type Error = CommonError;
+
+    async fn connect(&self) -> Result<Self::Connection, Self::Error> {
+        match ConsumerGroupServiceClient::connect(format!("http://{}", self.addr.clone())).await {
+            Ok(client) => Ok(client),
+            Err(err) => Err(CommonError::CommonError(format!(
+                "{},{}",
+                err,
+                self.addr.clone()
+            ))),
+        }
+    }
+
+    async fn check(&self, conn: Self::Connection) -> Result<Self::Connection, Self::Error> {
+        Ok(conn)
+    }
+}
+
+impl_retriable_request!(
+    ConsumerGroupHeartbeatRequest,
+    ConsumerGroupServiceClient<Channel>,
+    ConsumerGroupHeartbeatReply,
+    meta_service_consumer_group_services_client,
+    heartbeat,
+    true
+);
+
+impl_retriable_request!(
+    LeaveConsumerGroupRequest,
+    ConsumerGroupServiceClient<Channel>,
+    LeaveConsumerGroupReply,
+    meta_service_consumer_group_services_client,
+    leave_group,
+    true
+);
+
+impl_retriable_request!(
+    CommitConsumerGroupOffsetRequest,
+    ConsumerGroupServiceClient<Channel>,
+    CommitConsumerGroupOffsetReply,
+    meta_service_consumer_group_services_client,
+    commit_offset,
+    true
+);
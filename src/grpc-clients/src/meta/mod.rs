@@ -77,6 +77,7 @@ pub enum MetaServiceInterface {
     ChangeMembership,
 }
 
+pub mod consumer_group;
 #[allow(clippy::module_inception)]
 pub mod inner;
 pub mod journal;
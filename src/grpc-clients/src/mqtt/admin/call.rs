@@ -0,0 +1,92 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_base::error::common::CommonError;
+use protocol::broker::broker_mqtt_admin::{
+    ClearSessionReply, ClearSessionRequest, DrainNodeReply, DrainNodeRequest, DrainNodeStatusReply,
+    DrainNodeStatusRequest, InspectClientReply, InspectClientRequest, KickClientReply,
+    KickClientRequest, RebalanceConnectionsReply, RebalanceConnectionsRequest,
+    SubscribeForClientReply, SubscribeForClientRequest, UnsubscribeForClientReply,
+    UnsubscribeForClientRequest,
+};
+
+use crate::pool::ClientPool;
+
+macro_rules! generate_mqtt_admin_service_call {
+    ($fn_name:ident, $req_ty:ty, $rep_ty:ty, $variant:ident) => {
+        pub async fn $fn_name(
+            client_pool: &ClientPool,
+            addrs: &[impl AsRef<str>],
+            request: $req_ty,
+        ) -> Result<$rep_ty, CommonError> {
+            $crate::utils::retry_call(client_pool, addrs, request).await
+        }
+    };
+}
+
+generate_mqtt_admin_service_call!(
+    broker_mqtt_kick_client,
+    KickClientRequest,
+    KickClientReply,
+    KickClient
+);
+
+generate_mqtt_admin_service_call!(
+    broker_mqtt_clear_session,
+    ClearSessionRequest,
+    ClearSessionReply,
+    ClearSession
+);
+
+generate_mqtt_admin_service_call!(
+    broker_mqtt_inspect_client,
+    InspectClientRequest,
+    InspectClientReply,
+    InspectClient
+);
+
+generate_mqtt_admin_service_call!(
+    broker_mqtt_subscribe_for_client,
+    SubscribeForClientRequest,
+    SubscribeForClientReply,
+    SubscribeForClient
+);
+
+generate_mqtt_admin_service_call!(
+    broker_mqtt_unsubscribe_for_client,
+    UnsubscribeForClientRequest,
+    UnsubscribeForClientReply,
+    UnsubscribeForClient
+);
+
+generate_mqtt_admin_service_call!(
+    broker_mqtt_drain_node,
+    DrainNodeRequest,
+    DrainNodeReply,
+    DrainNode
+);
+
+generate_mqtt_admin_service_call!(
+    broker_mqtt_drain_node_status,
+    DrainNodeStatusRequest,
+    DrainNodeStatusReply,
+    DrainNodeStatus
+);
+
+generate_mqtt_admin_service_call!(
+    broker_mqtt_rebalance_connections,
+    RebalanceConnectionsRequest,
+    RebalanceConnectionsReply,
+    RebalanceConnections
+);
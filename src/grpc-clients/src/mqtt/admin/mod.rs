@@ -0,0 +1,125 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_base::error::common::CommonError;
+use mobc::Manager;
+use protocol::broker::broker_mqtt_admin::mqtt_broker_admin_service_client::MqttBrokerAdminServiceClient;
+use protocol::broker::broker_mqtt_admin::{
+    ClearSessionReply, ClearSessionRequest, DrainNodeReply, DrainNodeRequest, DrainNodeStatusReply,
+    DrainNodeStatusRequest, InspectClientReply, InspectClientRequest, KickClientReply,
+    KickClientRequest, RebalanceConnectionsReply, RebalanceConnectionsRequest,
+    SubscribeForClientReply, SubscribeForClientRequest, UnsubscribeForClientReply,
+    UnsubscribeForClientRequest,
+};
+use tonic::transport::Channel;
+
+use crate::macros::impl_retriable_request;
+
+pub mod call;
+
+#[derive(Clone)]
+pub struct MqttBrokerAdminServiceManager {
+    pub addr: String,
+}
+
+impl MqttBrokerAdminServiceManager {
+    pub fn new(addr: String) -> Self {
+        Self { addr }
+    }
+}
+
+#[tonic::async_trait]
+impl Manager for MqttBrokerAdminServiceManager {
+    type Connection = MqttBrokerAdminServiceClient<Channel>;
+    type Error = CommonError;
+
+    async fn connect(&self) -> Result<Self::Connection, Self::Error> {
+        match MqttBrokerAdminServiceClient::connect(format!("http://{}", self.addr.clone())).await {
+            Ok(client) => Ok(client),
+            Err(err) => Err(CommonError::CommonError(format!(
+                "{},{}",
+                err,
+                self.addr.clone()
+            ))),
+        }
+    }
+
+    async fn check(&self, conn: Self::Connection) -> Result<Self::Connection, Self::Error> {
+        Ok(conn)
+    }
+}
+
+impl_retriable_request!(
+    KickClientRequest,
+    MqttBrokerAdminServiceClient<Channel>,
+    KickClientReply,
+    mqtt_broker_admin_services_client,
+    kick_client
+);
+
+impl_retriable_request!(
+    ClearSessionRequest,
+    MqttBrokerAdminServiceClient<Channel>,
+    ClearSessionReply,
+    mqtt_broker_admin_services_client,
+    clear_session
+);
+
+impl_retriable_request!(
+    InspectClientRequest,
+    MqttBrokerAdminServiceClient<Channel>,
+    InspectClientReply,
+    mqtt_broker_admin_services_client,
+    inspect_client
+);
+
+impl_retriable_request!(
+    SubscribeForClientRequest,
+    MqttBrokerAdminServiceClient<Channel>,
+    SubscribeForClientReply,
+    mqtt_broker_admin_services_client,
+    subscribe_for_client
+);
+
+impl_retriable_request!(
+    UnsubscribeForClientRequest,
+    MqttBrokerAdminServiceClient<Channel>,
+    UnsubscribeForClientReply,
+    mqtt_broker_admin_services_client,
+    unsubscribe_for_client
+);
+
+impl_retriable_request!(
+    DrainNodeRequest,
+    MqttBrokerAdminServiceClient<Channel>,
+    DrainNodeReply,
+    mqtt_broker_admin_services_client,
+    drain_node
+);
+
+impl_retriable_request!(
+    DrainNodeStatusRequest,
+    MqttBrokerAdminServiceClient<Channel>,
+    DrainNodeStatusReply,
+    mqtt_broker_admin_services_client,
+    drain_node_status
+);
+
+impl_retriable_request!(
+    RebalanceConnectionsRequest,
+    MqttBrokerAdminServiceClient<Channel>,
+    RebalanceConnectionsReply,
+    mqtt_broker_admin_services_client,
+    rebalance_connections
+);
@@ -17,6 +17,7 @@
     DeleteSessionReply, DeleteSessionRequest, SendLastWillMessageReply, SendLastWillMessageRequest,
     UpdateMqttCacheReply, UpdateMqttCacheRequest,
 };
+use protocol::broker::broker_mqtt_session::{TakeoverSessionReply, TakeoverSessionRequest};
 
 use crate::pool::ClientPool;
 
@@ -52,3 +53,10 @@ pub async fn $fn_name(
     SendLastWillMessageReply,
     SendLastWillMessage
 );
+
+generate_mqtt_inner_service_call!(
+    broker_mqtt_takeover_session,
+    TakeoverSessionRequest,
+    TakeoverSessionReply,
+    TakeoverSession
+);
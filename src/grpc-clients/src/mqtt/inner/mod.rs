@@ -19,6 +19,8 @@
     DeleteSessionReply, DeleteSessionRequest, SendLastWillMessageReply, SendLastWillMessageRequest,
     UpdateMqttCacheReply, UpdateMqttCacheRequest,
 };
+use protocol::broker::broker_mqtt_session::mqtt_broker_session_service_client::MqttBrokerSessionServiceClient;
+use protocol::broker::broker_mqtt_session::{TakeoverSessionReply, TakeoverSessionRequest};
 use tonic::transport::Channel;
 
 use crate::macros::impl_retriable_request;
@@ -83,3 +85,44 @@ async fn check(&self, conn: Self::Connection) -> Result<Self::Connection, Self::
     mqtt_broker_mqtt_services_client,
     send_last_will_message
 );
+
+#[derive(Clone)]
+pub struct MqttBrokerSessionServiceManager {
+    pub addr: String,
+}
+
+impl MqttBrokerSessionServiceManager {
+    pub fn new(addr: String) -> Self {
+        Self { addr }
+    }
+}
+
+#[tonic::async_trait]
+impl Manager for MqttBrokerSessionServiceManager {
+    type Connection = MqttBrokerSessionServiceClient<Channel>;
+    type Error = CommonError;
+
+    async fn connect(&self) -> Result<Self::Connection, Self::Error> {
+        match MqttBrokerSessionServiceClient::connect(format!("http://{}", self.addr.clone())).await
+        {
+            Ok(client) => Ok(client),
+            Err(err) => Err(CommonError::CommonError(format!(
+                "{},{}",
+                err,
+                self.addr.clone()
+            ))),
+        }
+    }
+
+    async fn check(&self, conn: Self::Connection) -> Result<Self::Connection, Self::Error> {
+        Ok(conn)
+    }
+}
+
+impl_retriable_request!(
+    TakeoverSessionRequest,
+    MqttBrokerSessionServiceClient<Channel>,
+    TakeoverSessionReply,
+    mqtt_broker_session_services_client,
+    takeover_session
+);
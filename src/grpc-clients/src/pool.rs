@@ -14,14 +14,16 @@
 
 use std::time::Duration;
 
-use crate::journal::admin::JournalAdminServiceManager;
+use crate::journal::admin::{JournalAdminServiceManager, JournalSegmentAdminServiceManager};
 use crate::journal::inner::JournalInnerServiceManager;
+use crate::meta::consumer_group::ConsumerGroupServiceManager;
 use crate::meta::inner::PlacementServiceManager;
 use crate::meta::journal::JournalServiceManager;
 use crate::meta::kv::KvServiceManager;
 use crate::meta::mqtt::MqttServiceManager;
 use crate::meta::openraft::OpenRaftServiceManager;
-use crate::mqtt::inner::MqttBrokerPlacementServiceManager;
+use crate::mqtt::admin::MqttBrokerAdminServiceManager;
+use crate::mqtt::inner::{MqttBrokerPlacementServiceManager, MqttBrokerSessionServiceManager};
 use common_base::error::common::CommonError;
 use dashmap::mapref::one::Ref;
 use dashmap::DashMap;
@@ -37,14 +39,18 @@ pub struct ClientPool {
     meta_service_kv_service_pools: DashMap<String, Pool<KvServiceManager>>,
     meta_service_mqtt_service_pools: DashMap<String, Pool<MqttServiceManager>>,
     meta_service_openraft_service_pools: DashMap<String, Pool<OpenRaftServiceManager>>,
+    meta_service_consumer_group_service_pools: DashMap<String, Pool<ConsumerGroupServiceManager>>,
     // modules: meta service service: leader cache
     meta_service_leader_addr_caches: DashMap<String, String>,
 
     // modules: mqtt broker
     mqtt_broker_placement_service_pools: DashMap<String, Pool<MqttBrokerPlacementServiceManager>>,
+    mqtt_broker_admin_service_pools: DashMap<String, Pool<MqttBrokerAdminServiceManager>>,
+    mqtt_broker_session_service_pools: DashMap<String, Pool<MqttBrokerSessionServiceManager>>,
 
     // modules: journal engine
     journal_admin_service_pools: DashMap<String, Pool<JournalAdminServiceManager>>,
+    journal_segment_admin_service_pools: DashMap<String, Pool<JournalSegmentAdminServiceManager>>,
     journal_inner_service_pools: DashMap<String, Pool<JournalInnerServiceManager>>,
 }
 
@@ -58,11 +64,15 @@ pub fn new(max_open_connection: u64) -> Self {
             meta_service_kv_service_pools: DashMap::with_capacity(2),
             meta_service_mqtt_service_pools: DashMap::with_capacity(2),
             meta_service_openraft_service_pools: DashMap::with_capacity(2),
+            meta_service_consumer_group_service_pools: DashMap::with_capacity(2),
             meta_service_leader_addr_caches: DashMap::with_capacity(2),
             // modules: mqtt_broker
             mqtt_broker_placement_service_pools: DashMap::with_capacity(2),
+            mqtt_broker_admin_service_pools: DashMap::with_capacity(2),
+            mqtt_broker_session_service_pools: DashMap::with_capacity(2),
             // modules: journal_engine
             journal_admin_service_pools: DashMap::with_capacity(2),
+            journal_segment_admin_service_pools: DashMap::with_capacity(2),
             journal_inner_service_pools: DashMap::with_capacity(2),
         }
     }
@@ -245,6 +255,46 @@ pub async fn meta_service_openraft_services_client(
         ))
     }
 
+    pub async fn meta_service_consumer_group_services_client(
+        &self,
+        addr: &str,
+    ) -> Result<Connection<ConsumerGroupServiceManager>, CommonError> {
+        if !self
+            .meta_service_consumer_group_service_pools
+            .contains_key(addr)
+        {
+            let manager = ConsumerGroupServiceManager::new(addr.to_owned());
+            let pool = Pool::builder()
+                .max_open(self.max_open_connection)
+                .build(manager);
+            self.meta_service_consumer_group_service_pools
+                .insert(addr.to_owned(), pool);
+        }
+
+        if let Some(pool) = self.meta_service_consumer_group_service_pools.get(addr) {
+            match pool.get_timeout(Duration::from_secs(3)).await {
+                Ok(conn) => {
+                    return Ok(conn);
+                }
+                Err(e) => {
+                    return Err(CommonError::NoAvailableGrpcConnection(
+                        "ConsumerGroupServices".to_string(),
+                        format!(
+                            "get meta service consumer group service client failed, err: {}, state: {:?}",
+                            e,
+                            pool.state().await
+                        ),
+                    ));
+                }
+            };
+        }
+
+        Err(CommonError::NoAvailableGrpcConnection(
+            "ConsumerGroupServices".to_string(),
+            "connection pool is not initialized".to_string(),
+        ))
+    }
+
     // ----------modules: mqtt broker -------------
     pub async fn mqtt_broker_mqtt_services_client(
         &self,
@@ -282,6 +332,78 @@ pub async fn mqtt_broker_mqtt_services_client(
         ))
     }
 
+    pub async fn mqtt_broker_admin_services_client(
+        &self,
+        addr: &str,
+    ) -> Result<Connection<MqttBrokerAdminServiceManager>, CommonError> {
+        if !self.mqtt_broker_admin_service_pools.contains_key(addr) {
+            let manager = MqttBrokerAdminServiceManager::new(addr.to_owned());
+            let pool = Pool::builder()
+                .max_open(self.max_open_connection)
+                .build(manager);
+            self.mqtt_broker_admin_service_pools
+                .insert(addr.to_owned(), pool);
+        }
+
+        if let Some(pool) = self.mqtt_broker_admin_service_pools.get(addr) {
+            match pool.get_timeout(Duration::from_secs(3)).await {
+                Ok(conn) => {
+                    return Ok(conn);
+                }
+                Err(e) => {
+                    return Err(CommonError::NoAvailableGrpcConnection(
+                        "MQTTBrokerAdminServices".to_string(),
+                        format!(
+                            "get mqtt broker admin service client failed, err: {}, state: {:?}",
+                            e,
+                            pool.state().await
+                        ),
+                    ));
+                }
+            };
+        }
+        Err(CommonError::NoAvailableGrpcConnection(
+            "MQTTBrokerAdminServices".to_string(),
+            "connection pool is not initialized".to_string(),
+        ))
+    }
+
+    pub async fn mqtt_broker_session_services_client(
+        &self,
+        addr: &str,
+    ) -> Result<Connection<MqttBrokerSessionServiceManager>, CommonError> {
+        if !self.mqtt_broker_session_service_pools.contains_key(addr) {
+            let manager = MqttBrokerSessionServiceManager::new(addr.to_owned());
+            let pool = Pool::builder()
+                .max_open(self.max_open_connection)
+                .build(manager);
+            self.mqtt_broker_session_service_pools
+                .insert(addr.to_owned(), pool);
+        }
+
+        if let Some(pool) = self.mqtt_broker_session_service_pools.get(addr) {
+            match pool.get_timeout(Duration::from_secs(3)).await {
+                Ok(conn) => {
+                    return Ok(conn);
+                }
+                Err(e) => {
+                    return Err(CommonError::NoAvailableGrpcConnection(
+                        "MQTTBrokerSessionServices".to_string(),
+                        format!(
+                            "get mqtt broker session service client failed, err: {}, state: {:?}",
+                            e,
+                            pool.state().await
+                        ),
+                    ));
+                }
+            };
+        }
+        Err(CommonError::NoAvailableGrpcConnection(
+            "MQTTBrokerSessionServices".to_string(),
+            "connection pool is not initialized".to_string(),
+        ))
+    }
+
     // ----------modules: journal engine -------------
     pub async fn journal_inner_services_client(
         &self,
@@ -357,6 +479,43 @@ pub async fn journal_admin_services_client(
         ))
     }
 
+    pub async fn journal_segment_admin_services_client(
+        &self,
+        addr: &str,
+    ) -> Result<Connection<JournalSegmentAdminServiceManager>, CommonError> {
+        if !self.journal_segment_admin_service_pools.contains_key(addr) {
+            let manager = JournalSegmentAdminServiceManager::new(addr.to_owned());
+            let pool = Pool::builder()
+                .max_open(self.max_open_connection)
+                .build(manager);
+            self.journal_segment_admin_service_pools
+                .insert(addr.to_owned(), pool);
+        }
+
+        if let Some(pool) = self.journal_segment_admin_service_pools.get(addr) {
+            match pool.get_timeout(Duration::from_secs(3)).await {
+                Ok(conn) => {
+                    return Ok(conn);
+                }
+                Err(e) => {
+                    return Err(CommonError::NoAvailableGrpcConnection(
+                        "JournalEngineSegmentAdmin".to_string(),
+                        format!(
+                            "get journal engine segment admin service client failed, err: {}, state: {:?}",
+                            e,
+                            pool.state().await
+                        ),
+                    ));
+                }
+            };
+        }
+
+        Err(CommonError::NoAvailableGrpcConnection(
+            "JournalEngineSegmentAdmin".to_string(),
+            "connection pool is not initialized".to_string(),
+        ))
+    }
+
     // other
     pub fn get_leader_addr(&self, addr: &str) -> Option<Ref<'_, String, String>> {
         self.meta_service_leader_addr_caches.get(addr)
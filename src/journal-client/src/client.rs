@@ -18,10 +18,10 @@
 use common_base::utils::crc::calc_crc32;
 use metadata_struct::adapter::read_config::ReadConfig;
 use metadata_struct::adapter::record::Record;
-use metadata_struct::journal::shard::JournalShard;
+use metadata_struct::journal::shard::{shard_name_iden, JournalShard};
 use protocol::journal::journal_engine::{
-    CreateShardReqBody, DeleteShardReqBody, GetClusterMetadataNode, GetShardMetadataRespShard,
-    ListShardReqBody,
+    ClientSegmentMetadata, CreateShardReqBody, DeleteShardReqBody, GetClusterMetadataNode,
+    GetShardMetadataRespShard, ListShardReqBody,
 };
 use tokio::sync::broadcast::{self, Sender};
 
@@ -33,7 +33,7 @@
     fetch_offset_by_timestamp, AsyncReader, ReadShardByOffset,
 };
 use crate::async_writer::{AsyncWriter, SenderMessage, SenderMessageResp};
-use crate::cache::get_active_segment;
+use crate::cache::{get_active_segment, get_metadata_by_shard};
 use crate::service::{create_shard, delete_shard, list_shard};
 
 #[derive(Default, Clone)]
@@ -294,6 +294,32 @@ pub async fn read_by_tag(
         Ok(results)
     }
 
+    /// Metadata of the segment of the shard that currently takes the writes.
+    pub async fn active_segment(
+        &self,
+        namespace: &str,
+        shard_name: &str,
+    ) -> Result<ClientSegmentMetadata, JournalClientError> {
+        let active_segment = get_active_segment(
+            &self.metadata_cache,
+            &self.connection_manager,
+            namespace,
+            shard_name,
+        )
+        .await;
+
+        get_metadata_by_shard(
+            &self.metadata_cache,
+            &self.connection_manager,
+            namespace,
+            shard_name,
+        )
+        .await
+        .into_iter()
+        .find(|meta| meta.segment_no == active_segment)
+        .ok_or_else(|| JournalClientError::NotActiveSegment(shard_name_iden(namespace, shard_name)))
+    }
+
     pub fn metadata(&self) -> (Vec<GetShardMetadataRespShard>, Vec<GetClusterMetadataNode>) {
         self.metadata_cache.all_metadata()
     }
@@ -0,0 +1,502 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+
+use common_base::tools::{now_mills, unique_id};
+use grpc_clients::journal::admin::call::journal_admin_get_segment_file;
+use grpc_clients::meta::consumer_group::call::{
+    commit_consumer_group_offset, consumer_group_heartbeat, leave_consumer_group,
+};
+use grpc_clients::meta::inner::call::{get_offset_data, node_list};
+use grpc_clients::pool::ClientPool;
+use metadata_struct::adapter::read_config::ReadConfig;
+use metadata_struct::adapter::record::Record;
+use metadata_struct::journal::segment::segment_name;
+use metadata_struct::placement::node::BrokerNode;
+use protocol::journal::journal_segment_admin::GetSegmentFileRequest;
+use protocol::meta::meta_service_consumer_group::{
+    CommitConsumerGroupOffsetRequest, ConsumerGroupHeartbeatReply, ConsumerGroupHeartbeatRequest,
+    LeaveConsumerGroupRequest,
+};
+use protocol::meta::meta_service_inner::{GetOffsetDataRequest, NodeListRequest};
+use tokio::sync::broadcast;
+use tokio::time::sleep;
+use tracing::{info, warn};
+
+use crate::client::JournalClient;
+use crate::error::JournalClientError;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CommitMode {
+    // Positions are committed by `poll` every `interval_ms` and when the consumer closes
+    // or loses a shard in a rebalance.
+    Auto { interval_ms: u64 },
+    // Positions are only committed by `commit` and `commit_offsets`.
+    Manual,
+}
+
+/// Where a shard without a committed offset starts to be consumed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum StartPosition {
+    Earliest,
+    Latest,
+    Timestamp(u64),
+}
+
+#[derive(Clone)]
+pub struct ConsumerConfig {
+    pub cluster_name: String,
+    pub group_name: String,
+    pub namespace: String,
+    pub shards: Vec<String>,
+    pub commit_mode: CommitMode,
+    pub start_position: StartPosition,
+    // A member whose heartbeats have not reached meta-service for this long leaves the group.
+    pub session_timeout_ms: u64,
+    pub heartbeat_interval_ms: u64,
+    pub read_config: ReadConfig,
+}
+
+impl ConsumerConfig {
+    pub fn new(cluster_name: &str, group_name: &str, namespace: &str, shards: Vec<String>) -> Self {
+        ConsumerConfig {
+            cluster_name: cluster_name.to_string(),
+            group_name: group_name.to_string(),
+            namespace: namespace.to_string(),
+            shards,
+            commit_mode: CommitMode::Auto { interval_ms: 5000 },
+            start_position: StartPosition::Earliest,
+            session_timeout_ms: 10000,
+            heartbeat_interval_ms: 3000,
+            read_config: ReadConfig::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ConsumerRecord {
+    pub namespace: String,
+    pub shard_name: String,
+    pub record: Record,
+}
+
+/// A member of a consumer group.
+///
+/// Members join the group through meta-service and keep the membership alive
+/// with heartbeats. Meta-service assigns the shards of the group to its members
+/// and moves the group to a new generation every time the members change.
+/// Offsets are committed with the generation of the member, so a member that
+/// has not yet seen a rebalance cannot overwrite the offset committed by the new
+/// owner of a shard. Newly assigned shards resume from the committed offsets.
+/// Delivery is at least once: records consumed after the last commit of a
+/// revoked shard are read again by its new owner.
+pub struct Consumer {
+    client: JournalClient,
+    client_pool: Arc<ClientPool>,
+    meta_addrs: Vec<String>,
+    config: ConsumerConfig,
+    member_id: String,
+    // Generation of the group the current assignment belongs to, 0 before joining.
+    generation: u64,
+    // (shard_name, next offset to read)
+    positions: BTreeMap<String, u64>,
+    last_commit_time: u128,
+    last_refresh_time: u128,
+    stop_sx: broadcast::Sender<bool>,
+}
+
+impl Consumer {
+    pub async fn new(
+        client: JournalClient,
+        client_pool: Arc<ClientPool>,
+        meta_addrs: Vec<String>,
+        config: ConsumerConfig,
+    ) -> Result<Self, JournalClientError> {
+        if config.group_name.is_empty() {
+            return Err(JournalClientError::ConsumerGroupNotEmpty);
+        }
+
+        let (stop_sx, _) = broadcast::channel(2);
+        let mut consumer = Consumer {
+            client,
+            client_pool,
+            meta_addrs,
+            config,
+            member_id: unique_id(),
+            generation: 0,
+            positions: BTreeMap::new(),
+            last_commit_time: now_mills(),
+            last_refresh_time: 0,
+            stop_sx,
+        };
+
+        consumer.refresh_assignment().await?;
+        consumer.start_heartbeat_thread();
+        Ok(consumer)
+    }
+
+    pub fn member_id(&self) -> &str {
+        &self.member_id
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// The shards currently assigned to this member.
+    pub fn assignment(&self) -> Vec<String> {
+        self.positions.keys().cloned().collect()
+    }
+
+    pub fn position(&self, shard_name: &str) -> Option<u64> {
+        self.positions.get(shard_name).copied()
+    }
+
+    /// Reads the next records of every assigned shard.
+    pub async fn poll(&mut self) -> Result<Vec<ConsumerRecord>, JournalClientError> {
+        let now = now_mills();
+        if now - self.last_refresh_time >= self.config.heartbeat_interval_ms as u128 {
+            self.refresh_assignment().await?;
+        }
+
+        let mut results = Vec::new();
+        let shards: Vec<(String, u64)> = self
+            .positions
+            .iter()
+            .map(|(shard, offset)| (shard.clone(), *offset))
+            .collect();
+        for (shard_name, offset) in shards {
+            let records = self
+                .client
+                .read_by_offset(
+                    &self.config.namespace,
+                    &shard_name,
+                    offset,
+                    &self.config.read_config,
+                )
+                .await?;
+
+            for record in records {
+                let next = record.offset.map(|offset| offset + 1).unwrap_or(offset);
+                if let Some(position) = self.positions.get_mut(&shard_name) {
+                    *position = (*position).max(next);
+                }
+                results.push(ConsumerRecord {
+                    namespace: self.config.namespace.clone(),
+                    shard_name: shard_name.clone(),
+                    record,
+                });
+            }
+        }
+
+        if let CommitMode::Auto { interval_ms } = self.config.commit_mode {
+            if now_mills() - self.last_commit_time >= interval_ms as u128 {
+                self.commit().await?;
+            }
+        }
+        Ok(results)
+    }
+
+    /// Commits the current position of every assigned shard.
+    pub async fn commit(&mut self) -> Result<(), JournalClientError> {
+        let offsets: HashMap<String, u64> = self
+            .positions
+            .iter()
+            .map(|(shard, offset)| (shard.clone(), *offset))
+            .collect();
+        self.commit_offsets(offsets).await
+    }
+
+    /// Commits the given offsets, an offset is the next one to be read from the shard.
+    ///
+    /// Meta-service rejects the commit when the group has moved to a new generation
+    /// or a shard is no longer assigned to this member.
+    pub async fn commit_offsets(
+        &mut self,
+        offsets: HashMap<String, u64>,
+    ) -> Result<(), JournalClientError> {
+        self.last_commit_time = now_mills();
+        if offsets.is_empty() {
+            return Ok(());
+        }
+
+        let request = CommitConsumerGroupOffsetRequest {
+            cluster_name: self.config.cluster_name.clone(),
+            group_name: self.config.group_name.clone(),
+            member_id: self.member_id.clone(),
+            generation: self.generation,
+            namespace: self.config.namespace.clone(),
+            offsets,
+        };
+        if let Err(e) =
+            commit_consumer_group_offset(&self.client_pool, &self.meta_addrs, request).await
+        {
+            // the group may have been rebalanced, pick up the new assignment on the next poll
+            self.last_refresh_time = 0;
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    pub fn seek(&mut self, shard_name: &str, offset: u64) -> Result<(), JournalClientError> {
+        if let Some(position) = self.positions.get_mut(shard_name) {
+            *position = offset;
+            return Ok(());
+        }
+        Err(JournalClientError::ShardNotAssigned(shard_name.to_string()))
+    }
+
+    /// Moves every assigned shard to the first record written at or after `timestamp`.
+    pub async fn seek_to_timestamp(&mut self, timestamp: u64) -> Result<(), JournalClientError> {
+        let shards: Vec<String> = self.positions.keys().cloned().collect();
+        for shard_name in shards {
+            let offset = self.offset_by_timestamp(&shard_name, timestamp).await?;
+            self.positions.insert(shard_name, offset);
+        }
+        Ok(())
+    }
+
+    /// Leaves the group, meta-service assigns the shards to the remaining members.
+    pub async fn close(&mut self) -> Result<(), JournalClientError> {
+        let committed = if matches!(self.config.commit_mode, CommitMode::Auto { .. }) {
+            self.commit().await
+        } else {
+            Ok(())
+        };
+        let _ = self.stop_sx.send(true);
+        leave_consumer_group(
+            &self.client_pool,
+            &self.meta_addrs,
+            LeaveConsumerGroupRequest {
+                cluster_name: self.config.cluster_name.clone(),
+                group_name: self.config.group_name.clone(),
+                member_id: self.member_id.clone(),
+            },
+        )
+        .await?;
+        self.positions.clear();
+        committed
+    }
+
+    async fn refresh_assignment(&mut self) -> Result<(), JournalClientError> {
+        self.last_refresh_time = now_mills();
+        let reply = self.heartbeat().await?;
+        if reply.generation == self.generation {
+            return Ok(());
+        }
+
+        // Revoked shards can't be committed any more, the group has moved to a new
+        // generation. Their new owners resume from the last commit. When generations
+        // were missed a kept shard may have been owned by another member meanwhile,
+        // so its position is reloaded as well.
+        let assigned = reply.shards;
+        if reply.generation != self.generation + 1 {
+            self.positions.clear();
+        }
+        self.positions
+            .retain(|shard_name, _| assigned.contains(shard_name));
+
+        let committed = self.committed_offsets().await?;
+        for shard_name in assigned.iter() {
+            if self.positions.contains_key(shard_name) {
+                continue;
+            }
+            let offset = if let Some(offset) = committed.get(shard_name) {
+                *offset
+            } else {
+                self.start_offset(shard_name).await?
+            };
+            self.positions.insert(shard_name.clone(), offset);
+        }
+
+        info!(
+            "Consumer group {} rebalanced to generation {}, member {} owns shards {:?}",
+            self.config.group_name, reply.generation, self.member_id, assigned
+        );
+        self.generation = reply.generation;
+        Ok(())
+    }
+
+    async fn committed_offsets(&self) -> Result<HashMap<String, u64>, JournalClientError> {
+        let request = GetOffsetDataRequest {
+            cluster_name: self.config.cluster_name.clone(),
+            group: self.config.group_name.clone(),
+        };
+        let reply = get_offset_data(&self.client_pool, &self.meta_addrs, request).await?;
+        Ok(reply
+            .offsets
+            .into_iter()
+            .filter(|raw| raw.namespace == self.config.namespace)
+            .map(|raw| (raw.shard_name, raw.offset))
+            .collect())
+    }
+
+    async fn start_offset(&self, shard_name: &str) -> Result<u64, JournalClientError> {
+        match self.config.start_position {
+            StartPosition::Earliest => Ok(0),
+            StartPosition::Latest => self.end_offset(shard_name).await,
+            StartPosition::Timestamp(timestamp) => {
+                self.offset_by_timestamp(shard_name, timestamp).await
+            }
+        }
+    }
+
+    async fn offset_by_timestamp(
+        &self,
+        shard_name: &str,
+        timestamp: u64,
+    ) -> Result<u64, JournalClientError> {
+        let (_, offset) = self
+            .client
+            .get_offset_by_timestamp(&self.config.namespace, shard_name, timestamp)
+            .await?;
+        Ok(offset)
+    }
+
+    // The next offset to be written to the shard. Only the leader of the active segment
+    // knows it, the segment metadata in meta-service is updated when a segment is sealed.
+    async fn end_offset(&self, shard_name: &str) -> Result<u64, JournalClientError> {
+        let segment = self
+            .client
+            .active_segment(&self.config.namespace, shard_name)
+            .await?;
+
+        let reply = node_list(
+            &self.client_pool,
+            &self.meta_addrs,
+            NodeListRequest {
+                cluster_name: self.config.cluster_name.clone(),
+            },
+        )
+        .await?;
+        let mut leader_addr = None;
+        for raw in reply.nodes {
+            let node = serde_json::from_slice::<BrokerNode>(&raw)?;
+            if node.node_id == segment.leader {
+                leader_addr = Some(node.node_inner_addr);
+                break;
+            }
+        }
+        let Some(leader_addr) = leader_addr else {
+            return Err(JournalClientError::NotLeader(segment_name(
+                &self.config.namespace,
+                shard_name,
+                segment.segment_no,
+            )));
+        };
+
+        let file = journal_admin_get_segment_file(
+            &self.client_pool,
+            &[leader_addr],
+            GetSegmentFileRequest {
+                namespace: self.config.namespace.clone(),
+                shard_name: shard_name.to_string(),
+                segment_no: segment.segment_no,
+            },
+        )
+        .await?;
+        Ok(next_offset(
+            file.end_offset,
+            file.start_offset.max(segment.start_offset),
+        ))
+    }
+
+    async fn heartbeat(&self) -> Result<ConsumerGroupHeartbeatReply, JournalClientError> {
+        let request = heartbeat_request(&self.config, &self.member_id);
+        Ok(consumer_group_heartbeat(&self.client_pool, &self.meta_addrs, request).await?)
+    }
+
+    fn start_heartbeat_thread(&self) {
+        let client_pool = self.client_pool.clone();
+        let meta_addrs = self.meta_addrs.clone();
+        let group_name = self.config.group_name.clone();
+        let member_id = self.member_id.clone();
+        let request = heartbeat_request(&self.config, &self.member_id);
+        let interval = Duration::from_millis(self.config.heartbeat_interval_ms);
+        let mut stop_rx = self.stop_sx.subscribe();
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    val = stop_rx.recv() => {
+                        if let Ok(flag) = val {
+                            if flag {
+                                break;
+                            }
+                        }
+                    }
+                    _ = sleep(interval) => {
+                        if let Err(e) =
+                            consumer_group_heartbeat(&client_pool, &meta_addrs, request.clone()).await
+                        {
+                            warn!(
+                                "Consumer group {} member {} failed to send heartbeat, error message: {}",
+                                group_name, member_id, e
+                            );
+                        }
+                    }
+                }
+            }
+        });
+    }
+}
+
+impl Drop for Consumer {
+    fn drop(&mut self) {
+        let _ = self.stop_sx.send(true);
+    }
+}
+
+fn heartbeat_request(config: &ConsumerConfig, member_id: &str) -> ConsumerGroupHeartbeatRequest {
+    ConsumerGroupHeartbeatRequest {
+        cluster_name: config.cluster_name.clone(),
+        group_name: config.group_name.clone(),
+        member_id: member_id.to_string(),
+        namespace: config.namespace.clone(),
+        shards: config.shards.clone(),
+        session_timeout_ms: config.session_timeout_ms,
+    }
+}
+
+// The offset after `end_offset`, or the start of the segment when nothing has been
+// written to it yet. Both are -1 when unknown.
+fn next_offset(end_offset: i64, start_offset: i64) -> u64 {
+    if end_offset >= 0 {
+        return end_offset as u64 + 1;
+    }
+    start_offset.max(0) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_offset_test() {
+        assert_eq!(next_offset(99, 0), 100);
+        assert_eq!(next_offset(-1, 200), 200);
+        assert_eq!(next_offset(-1, -1), 0);
+    }
+
+    #[test]
+    fn heartbeat_request_test() {
+        let config = ConsumerConfig::new("c1", "g1", "ns", vec!["s1".to_string()]);
+        let request = heartbeat_request(&config, "m1");
+        assert_eq!(request.member_id, "m1");
+        assert_eq!(request.shards, vec!["s1".to_string()]);
+        assert_eq!(request.session_timeout_ms, config.session_timeout_ms);
+    }
+}
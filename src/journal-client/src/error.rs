@@ -84,6 +84,24 @@ pub enum JournalClientError {
     #[error("Sending a request packet, receiving a request returns a timeout")]
     SendPacketTimeout,
 
+    #[error("Producer has been closed")]
+    ProducerClosed,
+
+    #[error("Producer shards cannot be empty")]
+    ProducerShardsNotEmpty,
+
+    #[error("Producer failed to send batch, error message: {0}")]
+    ProducerSendFailed(String),
+
+    #[error("Consumer group name cannot be empty")]
+    ConsumerGroupNotEmpty,
+
+    #[error("Shard {0} is not assigned to this consumer")]
+    ShardNotAssigned(String),
+
     #[error("{0}")]
     SerdeJsonError(#[from] serde_json::Error),
+
+    #[error("{0}")]
+    CommonError(#[from] common_base::error::common::CommonError),
 }
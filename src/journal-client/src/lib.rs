@@ -19,7 +19,9 @@
 pub mod client;
 mod connection;
 mod consts;
+pub mod consumer;
 mod error;
 pub mod option;
+pub mod producer;
 mod service;
 pub mod tool;
@@ -0,0 +1,694 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use common_base::tools::unique_id;
+use common_base::utils::crc::calc_crc32;
+use protocol::journal::producer_batch::{
+    CompressionType, ProducerBatch, ProducerBatchRecord, PRODUCER_BATCH_TAG,
+};
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::{sleep, sleep_until, timeout, Instant};
+use tracing::warn;
+
+use crate::async_writer::SenderMessageResp;
+use crate::client::{JournalClient, JournalClientWriteData};
+use crate::error::JournalClientError;
+
+#[derive(Clone, Debug)]
+pub struct ProducerConfig {
+    // A batch is sent as soon as its records reach this size in bytes.
+    pub batch_size_bytes: usize,
+    pub batch_max_records: usize,
+    // How long a batch that is not full waits for more records before it is sent.
+    pub linger_ms: u64,
+    pub compression: CompressionType,
+    // Attach a producer id and sequence numbers so that the server drops retried batches.
+    pub idempotence: bool,
+    pub max_retries: u32,
+    pub retry_backoff_ms: u64,
+    pub request_timeout_ms: u64,
+    pub queue_size: usize,
+}
+
+impl Default for ProducerConfig {
+    fn default() -> Self {
+        ProducerConfig {
+            batch_size_bytes: 16 * 1024,
+            batch_max_records: 1000,
+            linger_ms: 5,
+            compression: CompressionType::None,
+            idempotence: true,
+            max_retries: 3,
+            retry_backoff_ms: 100,
+            request_timeout_ms: 30000,
+            queue_size: 1000,
+        }
+    }
+}
+
+/// Picks the shard of the records that don't name one explicitly.
+pub trait Partitioner: Send + Sync {
+    /// Returns the index of the shard in `[0, shard_num)`.
+    fn partition(&self, key: &str, shard_num: usize) -> usize;
+
+    /// Called when the batch of the shard at `shard_index` has been closed.
+    fn on_new_batch(&self, _shard_index: usize, _shard_num: usize) {}
+}
+
+/// Records with the same key always land in the same shard, records without a
+/// key are spread round robin.
+#[derive(Default)]
+pub struct KeyHashPartitioner {
+    round_robin: RoundRobinPartitioner,
+}
+
+impl Partitioner for KeyHashPartitioner {
+    fn partition(&self, key: &str, shard_num: usize) -> usize {
+        if key.is_empty() {
+            return self.round_robin.partition(key, shard_num);
+        }
+        calc_crc32(key.as_bytes()) as usize % shard_num
+    }
+}
+
+#[derive(Default)]
+pub struct RoundRobinPartitioner {
+    counter: AtomicUsize,
+}
+
+impl Partitioner for RoundRobinPartitioner {
+    fn partition(&self, _key: &str, shard_num: usize) -> usize {
+        self.counter.fetch_add(1, Ordering::Relaxed) % shard_num
+    }
+}
+
+/// Records with a key are hashed, records without a key stick to one shard
+/// until its batch is closed, which makes larger batches than round robin.
+#[derive(Default)]
+pub struct StickyPartitioner {
+    current: AtomicUsize,
+}
+
+impl Partitioner for StickyPartitioner {
+    fn partition(&self, key: &str, shard_num: usize) -> usize {
+        if !key.is_empty() {
+            return calc_crc32(key.as_bytes()) as usize % shard_num;
+        }
+        self.current.load(Ordering::Relaxed) % shard_num
+    }
+
+    fn on_new_batch(&self, shard_index: usize, shard_num: usize) {
+        let current = self.current.load(Ordering::Relaxed);
+        if current % shard_num == shard_index {
+            let _ = self.current.compare_exchange(
+                current,
+                current.wrapping_add(1),
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            );
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ProducerRecord {
+    pub key: String,
+    pub content: Vec<u8>,
+    pub tags: Vec<String>,
+    // Write to this shard instead of asking the partitioner.
+    pub shard: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordMetadata {
+    pub namespace: String,
+    pub shard_name: String,
+    pub offset: u64,
+}
+
+type DeliveryResult = Result<RecordMetadata, JournalClientError>;
+
+/// Resolves once the record has been acknowledged by the server.
+pub struct DeliveryFuture {
+    receiver: oneshot::Receiver<DeliveryResult>,
+}
+
+impl Future for DeliveryFuture {
+    type Output = DeliveryResult;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match Pin::new(&mut self.receiver).poll(cx) {
+            Poll::Ready(Ok(result)) => Poll::Ready(result),
+            Poll::Ready(Err(_)) => Poll::Ready(Err(JournalClientError::ProducerClosed)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+type WriteFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<SenderMessageResp, JournalClientError>> + Send + 'a>>;
+
+// The write path of the accumulator, kept behind a trait so that batching and
+// retries can be exercised without a running journal engine.
+trait BatchWriter: Send + Sync {
+    fn write(
+        &self,
+        namespace: String,
+        shard_name: String,
+        data: JournalClientWriteData,
+    ) -> WriteFuture<'_>;
+}
+
+impl BatchWriter for JournalClient {
+    fn write(
+        &self,
+        namespace: String,
+        shard_name: String,
+        data: JournalClientWriteData,
+    ) -> WriteFuture<'_> {
+        Box::pin(JournalClient::write(self, namespace, shard_name, data))
+    }
+}
+
+struct PendingRecord {
+    record: ProducerBatchRecord,
+    delivery_sx: oneshot::Sender<DeliveryResult>,
+}
+
+enum AccumulatorCommand {
+    Record(PendingRecord),
+    Flush(oneshot::Sender<()>),
+}
+
+/// Batches records per shard and writes them to the journal engine.
+///
+/// Every shard has its own accumulator task, batches of one shard are sent one
+/// after another so that their sequence numbers reach the server in order.
+pub struct Producer {
+    namespace: String,
+    shards: Vec<String>,
+    partitioner: Arc<dyn Partitioner>,
+    senders: Vec<mpsc::Sender<AccumulatorCommand>>,
+    closed: AtomicBool,
+}
+
+impl Producer {
+    pub fn new(
+        client: JournalClient,
+        namespace: &str,
+        shards: Vec<String>,
+        config: ProducerConfig,
+    ) -> Result<Self, JournalClientError> {
+        Producer::with_partitioner(
+            client,
+            namespace,
+            shards,
+            config,
+            Arc::new(KeyHashPartitioner::default()),
+        )
+    }
+
+    pub fn with_partitioner(
+        client: JournalClient,
+        namespace: &str,
+        shards: Vec<String>,
+        config: ProducerConfig,
+        partitioner: Arc<dyn Partitioner>,
+    ) -> Result<Self, JournalClientError> {
+        if shards.is_empty() {
+            return Err(JournalClientError::ProducerShardsNotEmpty);
+        }
+
+        let client: Arc<dyn BatchWriter> = Arc::new(client);
+        let mut senders = Vec::with_capacity(shards.len());
+        for (index, shard_name) in shards.iter().enumerate() {
+            let (sx, rx) = mpsc::channel(config.queue_size.max(1));
+            let accumulator = ShardAccumulator {
+                client: client.clone(),
+                namespace: namespace.to_string(),
+                shard_name: shard_name.clone(),
+                shard_index: index,
+                shard_num: shards.len(),
+                config: config.clone(),
+                partitioner: partitioner.clone(),
+                producer_id: new_producer_id(&config),
+                next_sequence: 0,
+                records: Vec::new(),
+                batch_size: 0,
+            };
+            tokio::spawn(accumulator.run(rx));
+            senders.push(sx);
+        }
+
+        Ok(Producer {
+            namespace: namespace.to_string(),
+            shards,
+            partitioner,
+            senders,
+            closed: AtomicBool::new(false),
+        })
+    }
+
+    pub async fn send(&self, record: ProducerRecord) -> Result<DeliveryFuture, JournalClientError> {
+        if self.closed.load(Ordering::Relaxed) {
+            return Err(JournalClientError::ProducerClosed);
+        }
+
+        let index = if let Some(shard_name) = &record.shard {
+            self.shards
+                .iter()
+                .position(|shard| shard == shard_name)
+                .ok_or_else(|| {
+                    JournalClientError::NotShardMetadata(format!(
+                        "{}/{}",
+                        self.namespace, shard_name
+                    ))
+                })?
+        } else {
+            self.partitioner.partition(&record.key, self.shards.len()) % self.shards.len()
+        };
+
+        let (delivery_sx, receiver) = oneshot::channel();
+        let pending = PendingRecord {
+            record: ProducerBatchRecord {
+                key: record.key,
+                value: record.content,
+                tags: record.tags,
+            },
+            delivery_sx,
+        };
+        self.senders[index]
+            .send(AccumulatorCommand::Record(pending))
+            .await
+            .map_err(|_| JournalClientError::ProducerClosed)?;
+        Ok(DeliveryFuture { receiver })
+    }
+
+    /// Same as [`Producer::send`], `callback` is called with the result of the delivery.
+    pub async fn send_with_callback<F>(
+        &self,
+        record: ProducerRecord,
+        callback: F,
+    ) -> Result<(), JournalClientError>
+    where
+        F: FnOnce(DeliveryResult) + Send + 'static,
+    {
+        let future = self.send(record).await?;
+        tokio::spawn(async move {
+            callback(future.await);
+        });
+        Ok(())
+    }
+
+    /// Sends every buffered record without waiting for the linger time and waits
+    /// until they are acknowledged.
+    pub async fn flush(&self) -> Result<(), JournalClientError> {
+        let mut waits = Vec::with_capacity(self.senders.len());
+        for sender in self.senders.iter() {
+            let (sx, rx) = oneshot::channel();
+            sender
+                .send(AccumulatorCommand::Flush(sx))
+                .await
+                .map_err(|_| JournalClientError::ProducerClosed)?;
+            waits.push(rx);
+        }
+        for rx in waits {
+            rx.await.map_err(|_| JournalClientError::ProducerClosed)?;
+        }
+        Ok(())
+    }
+
+    pub async fn close(&self) -> Result<(), JournalClientError> {
+        if self.closed.swap(true, Ordering::Relaxed) {
+            return Ok(());
+        }
+        self.flush().await
+    }
+}
+
+struct ShardAccumulator {
+    client: Arc<dyn BatchWriter>,
+    namespace: String,
+    shard_name: String,
+    shard_index: usize,
+    shard_num: usize,
+    config: ProducerConfig,
+    partitioner: Arc<dyn Partitioner>,
+    producer_id: String,
+    next_sequence: u64,
+    records: Vec<PendingRecord>,
+    batch_size: usize,
+}
+
+impl ShardAccumulator {
+    async fn run(mut self, mut rx: mpsc::Receiver<AccumulatorCommand>) {
+        let linger = Duration::from_millis(self.config.linger_ms);
+        let mut deadline = Instant::now() + linger;
+        loop {
+            let command = if self.records.is_empty() {
+                rx.recv().await
+            } else {
+                tokio::select! {
+                    command = rx.recv() => command,
+                    _ = sleep_until(deadline) => {
+                        self.send_batch().await;
+                        continue;
+                    }
+                }
+            };
+
+            match command {
+                Some(AccumulatorCommand::Record(pending)) => {
+                    if self.records.is_empty() {
+                        deadline = Instant::now() + linger;
+                    }
+                    self.batch_size += pending.record.size();
+                    self.records.push(pending);
+                    if self.batch_size >= self.config.batch_size_bytes
+                        || self.records.len() >= self.config.batch_max_records
+                    {
+                        self.send_batch().await;
+                    }
+                }
+                Some(AccumulatorCommand::Flush(sx)) => {
+                    self.send_batch().await;
+                    let _ = sx.send(());
+                }
+                None => {
+                    self.send_batch().await;
+                    break;
+                }
+            }
+        }
+    }
+
+    async fn send_batch(&mut self) {
+        if self.records.is_empty() {
+            return;
+        }
+        self.partitioner
+            .on_new_batch(self.shard_index, self.shard_num);
+
+        let pendings = std::mem::take(&mut self.records);
+        self.batch_size = 0;
+        let batch = ProducerBatch {
+            producer_id: self.producer_id.clone(),
+            base_sequence: self.next_sequence,
+            compression: self.config.compression,
+            records: pendings
+                .iter()
+                .map(|pending| pending.record.clone())
+                .collect(),
+        };
+
+        match self.write_with_retry(&batch).await {
+            Ok(base_offset) => {
+                if batch.is_idempotent() {
+                    self.next_sequence = batch.last_sequence() + 1;
+                }
+                for (i, pending) in pendings.into_iter().enumerate() {
+                    let _ = pending.delivery_sx.send(Ok(RecordMetadata {
+                        namespace: self.namespace.clone(),
+                        shard_name: self.shard_name.clone(),
+                        offset: base_offset + i as u64,
+                    }));
+                }
+            }
+            Err(e) => {
+                // The server may or may not have written the batch, so the sequence state
+                // is unknown. Start over with a new producer id rather than risk a gap or
+                // a wrongly deduplicated batch.
+                self.producer_id = new_producer_id(&self.config);
+                self.next_sequence = 0;
+                for pending in pendings {
+                    let _ = pending
+                        .delivery_sx
+                        .send(Err(JournalClientError::ProducerSendFailed(e.clone())));
+                }
+            }
+        }
+    }
+
+    async fn write_with_retry(&self, batch: &ProducerBatch) -> Result<u64, String> {
+        let content = batch.encode().map_err(|e| e.to_string())?;
+        let data = JournalClientWriteData {
+            key: "".to_string(),
+            content,
+            tags: vec![PRODUCER_BATCH_TAG.to_string()],
+        };
+
+        let mut times = 0;
+        loop {
+            let err = match timeout(
+                Duration::from_millis(self.config.request_timeout_ms),
+                self.client.write(
+                    self.namespace.clone(),
+                    self.shard_name.clone(),
+                    data.clone(),
+                ),
+            )
+            .await
+            {
+                Ok(Ok(resp)) => match resp.error {
+                    None => return Ok(resp.offset),
+                    Some(e) => e,
+                },
+                Ok(Err(e)) => e.to_string(),
+                Err(e) => e.to_string(),
+            };
+
+            if times >= self.config.max_retries {
+                return Err(err);
+            }
+            times += 1;
+            warn!(
+                "Producer failed to write batch to shard {}/{}, retry {}, error message: {}",
+                self.namespace, self.shard_name, times, err
+            );
+            sleep(Duration::from_millis(self.config.retry_backoff_ms)).await;
+        }
+    }
+}
+
+fn new_producer_id(config: &ProducerConfig) -> String {
+    if config.idempotence {
+        unique_id()
+    } else {
+        "".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    // Fails the first `failures` writes, then acknowledges batches at increasing offsets.
+    #[derive(Default)]
+    struct MockWriter {
+        failures: AtomicUsize,
+        batches: Mutex<Vec<ProducerBatch>>,
+    }
+
+    impl BatchWriter for MockWriter {
+        fn write(
+            &self,
+            _namespace: String,
+            _shard_name: String,
+            data: JournalClientWriteData,
+        ) -> WriteFuture<'_> {
+            Box::pin(async move {
+                if self
+                    .failures
+                    .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1))
+                    .is_ok()
+                {
+                    return Ok(SenderMessageResp {
+                        offset: 0,
+                        error: Some("mock error".to_string()),
+                    });
+                }
+                let batch = ProducerBatch::decode(&data.content).unwrap();
+                let mut batches = self.batches.lock().unwrap();
+                let offset = batches.iter().map(|b| b.records.len() as u64).sum();
+                batches.push(batch);
+                Ok(SenderMessageResp::new(offset))
+            })
+        }
+    }
+
+    fn accumulator(writer: Arc<MockWriter>, config: ProducerConfig) -> ShardAccumulator {
+        ShardAccumulator {
+            client: writer,
+            namespace: "ns".to_string(),
+            shard_name: "s0".to_string(),
+            shard_index: 0,
+            shard_num: 1,
+            producer_id: new_producer_id(&config),
+            config,
+            partitioner: Arc::new(RoundRobinPartitioner::default()),
+            next_sequence: 0,
+            records: Vec::new(),
+            batch_size: 0,
+        }
+    }
+
+    fn config() -> ProducerConfig {
+        ProducerConfig {
+            linger_ms: 10,
+            retry_backoff_ms: 1,
+            ..Default::default()
+        }
+    }
+
+    async fn send(
+        sx: &mpsc::Sender<AccumulatorCommand>,
+        value: &str,
+    ) -> oneshot::Receiver<DeliveryResult> {
+        let (delivery_sx, receiver) = oneshot::channel();
+        let pending = PendingRecord {
+            record: ProducerBatchRecord {
+                key: "".to_string(),
+                value: value.as_bytes().to_vec(),
+                tags: Vec::new(),
+            },
+            delivery_sx,
+        };
+        sx.send(AccumulatorCommand::Record(pending)).await.unwrap();
+        receiver
+    }
+
+    #[test]
+    fn key_hash_partitioner_test() {
+        let partitioner = KeyHashPartitioner::default();
+        let shard = partitioner.partition("k1", 8);
+        for _ in 0..10 {
+            assert_eq!(partitioner.partition("k1", 8), shard);
+        }
+        assert_eq!(partitioner.partition("", 3), 0);
+        assert_eq!(partitioner.partition("", 3), 1);
+        assert_eq!(partitioner.partition("", 3), 2);
+        assert_eq!(partitioner.partition("", 3), 0);
+    }
+
+    #[test]
+    fn sticky_partitioner_test() {
+        let partitioner = StickyPartitioner::default();
+        assert_eq!(partitioner.partition("", 3), 0);
+        assert_eq!(partitioner.partition("", 3), 0);
+
+        // Closing the batch of another shard doesn't move the sticky shard.
+        partitioner.on_new_batch(2, 3);
+        assert_eq!(partitioner.partition("", 3), 0);
+
+        partitioner.on_new_batch(0, 3);
+        assert_eq!(partitioner.partition("", 3), 1);
+        assert_eq!(
+            partitioner.partition("k1", 3),
+            calc_crc32("k1".as_bytes()) as usize % 3
+        );
+    }
+
+    #[tokio::test]
+    async fn accumulator_full_batch_test() {
+        let writer = Arc::new(MockWriter::default());
+        let config = ProducerConfig {
+            batch_max_records: 2,
+            linger_ms: 60000,
+            ..config()
+        };
+        let (sx, rx) = mpsc::channel(10);
+        tokio::spawn(accumulator(writer.clone(), config).run(rx));
+
+        let first = send(&sx, "a").await;
+        let second = send(&sx, "b").await;
+        let third = send(&sx, "c").await;
+        let fourth = send(&sx, "d").await;
+
+        assert_eq!(first.await.unwrap().unwrap().offset, 0);
+        assert_eq!(second.await.unwrap().unwrap().offset, 1);
+        assert_eq!(third.await.unwrap().unwrap().offset, 2);
+        assert_eq!(fourth.await.unwrap().unwrap().offset, 3);
+
+        let batches = writer.batches.lock().unwrap();
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].base_sequence, 0);
+        assert_eq!(batches[1].base_sequence, 2);
+        assert_eq!(batches[0].producer_id, batches[1].producer_id);
+    }
+
+    #[tokio::test]
+    async fn accumulator_linger_and_flush_test() {
+        let writer = Arc::new(MockWriter::default());
+        let (sx, rx) = mpsc::channel(10);
+        tokio::spawn(accumulator(writer.clone(), config()).run(rx));
+
+        // A batch that never fills up is sent once the linger time has passed.
+        let first = send(&sx, "a").await;
+        let result = timeout(Duration::from_secs(5), first).await.unwrap();
+        assert_eq!(result.unwrap().unwrap().offset, 0);
+
+        let second = send(&sx, "b").await;
+        let (flush_sx, flush_rx) = oneshot::channel();
+        sx.send(AccumulatorCommand::Flush(flush_sx)).await.unwrap();
+        flush_rx.await.unwrap();
+        assert_eq!(second.await.unwrap().unwrap().offset, 1);
+        assert_eq!(writer.batches.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn accumulator_retry_test() {
+        let writer = Arc::new(MockWriter::default());
+        writer.failures.store(2, Ordering::Relaxed);
+        let (sx, rx) = mpsc::channel(10);
+        tokio::spawn(accumulator(writer.clone(), config()).run(rx));
+
+        let first = send(&sx, "a").await;
+        assert_eq!(first.await.unwrap().unwrap().offset, 0);
+        assert_eq!(writer.batches.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn accumulator_retry_exhausted_test() {
+        let writer = Arc::new(MockWriter::default());
+        let config = ProducerConfig {
+            max_retries: 1,
+            ..config()
+        };
+        writer.failures.store(2, Ordering::Relaxed);
+        let (sx, rx) = mpsc::channel(10);
+        tokio::spawn(accumulator(writer.clone(), config).run(rx));
+
+        let first = send(&sx, "a").await;
+        assert!(matches!(
+            first.await.unwrap(),
+            Err(JournalClientError::ProducerSendFailed(_))
+        ));
+
+        // After a failed batch the producer starts over with a new id and sequence 0.
+        let second = send(&sx, "b").await;
+        assert_eq!(second.await.unwrap().unwrap().offset, 0);
+        let batches = writer.batches.lock().unwrap();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].base_sequence, 0);
+    }
+}
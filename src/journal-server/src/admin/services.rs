@@ -13,13 +13,30 @@
 // limitations under the License.
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::core::cache::CacheManager;
 use crate::core::error::JournalServerError;
+use crate::core::segment_meta::{update_end_and_start_offset, update_meta_end_timestamp};
+use crate::core::segment_status::{pre_sealup_segment, sealup_segment};
+use crate::segment::manager::SegmentFileManager;
+use crate::segment::write::stop_write_thread;
 use crate::segment::SegmentIdentity;
+use common_config::broker::broker_config;
+use grpc_clients::meta::journal::call::create_next_segment;
+use grpc_clients::pool::ClientPool;
+use metadata_struct::journal::segment::SegmentStatus;
 use protocol::journal::journal_admin::{
     ListSegmentReply, ListSegmentRequest, ListShardReply, ListShardRequest,
 };
+use protocol::journal::journal_segment_admin::{
+    GetSegmentFileReply, GetSegmentFileRequest, SealUpSegmentReply, SealUpSegmentRequest,
+};
+use protocol::meta::meta_service_journal::CreateNextSegmentRequest;
+use tokio::time::sleep;
+
+// How long a manual seal up waits for the next segment to reach the local cache.
+const NEXT_SEGMENT_WAIT_MS: u64 = 3000;
 
 /// List shards based on the request parameters
 pub async fn list_shard_by_req(
@@ -76,3 +93,109 @@ pub async fn list_segment_by_req(
 
     Ok(ListSegmentReply { segments })
 }
+
+/// Get the local segment file metadata of a segment led by this node
+pub async fn get_segment_file_by_req(
+    segment_file_manager: &Arc<SegmentFileManager>,
+    request: &GetSegmentFileRequest,
+) -> Result<GetSegmentFileReply, JournalServerError> {
+    let segment_iden =
+        SegmentIdentity::new(&request.namespace, &request.shard_name, request.segment_no);
+
+    let Some(file) = segment_file_manager.get_segment_file(&segment_iden) else {
+        return Err(JournalServerError::SegmentFileMetaNotExists(
+            segment_iden.name(),
+        ));
+    };
+
+    Ok(GetSegmentFileReply {
+        namespace: file.namespace,
+        shard_name: file.shard_name,
+        segment_no: file.segment_no,
+        start_offset: file.start_offset,
+        end_offset: file.end_offset,
+        start_timestamp: file.start_timestamp,
+        end_timestamp: file.end_timestamp,
+    })
+}
+
+/// Seal up a segment in the Write state before it is full, the writes of the
+/// shard move to the next segment
+pub async fn seal_up_segment_by_req(
+    cache_manager: &Arc<CacheManager>,
+    client_pool: &Arc<ClientPool>,
+    segment_file_manager: &Arc<SegmentFileManager>,
+    request: &SealUpSegmentRequest,
+) -> Result<SealUpSegmentReply, JournalServerError> {
+    let conf = broker_config();
+    let segment_iden =
+        SegmentIdentity::new(&request.namespace, &request.shard_name, request.segment_no);
+
+    let Some(segment) = cache_manager.get_segment(&segment_iden) else {
+        return Err(JournalServerError::SegmentNotExist(segment_iden.name()));
+    };
+
+    if segment.leader != conf.broker_id {
+        return Err(JournalServerError::NotLeader(segment_iden.name()));
+    }
+
+    if segment.status != SegmentStatus::Write {
+        return Err(JournalServerError::SegmentNotSealable(
+            segment_iden.name(),
+            segment.status.to_string(),
+        ));
+    }
+
+    // the next segment has to be known locally before the writes can move to it
+    let next_segment_iden = SegmentIdentity::new(
+        &request.namespace,
+        &request.shard_name,
+        request.segment_no + 1,
+    );
+    if cache_manager.get_segment(&next_segment_iden).is_none() {
+        let request = CreateNextSegmentRequest {
+            cluster_name: conf.cluster_name.clone(),
+            namespace: segment_iden.namespace.clone(),
+            shard_name: segment_iden.shard_name.clone(),
+        };
+        create_next_segment(client_pool, &conf.get_meta_service_addr(), request).await?;
+        wait_segment_in_cache(cache_manager, &next_segment_iden).await?;
+    }
+
+    pre_sealup_segment(cache_manager, client_pool, &segment_iden).await?;
+
+    // the end offset is only final once the pending writes are flushed
+    if let Some(write) = cache_manager.get_segment_write_thread(&segment_iden) {
+        stop_write_thread(&write).await?;
+    }
+
+    let Some(end_offset) = segment_file_manager.get_end_offset(&segment_iden) else {
+        return Err(JournalServerError::SegmentFileMetaNotExists(
+            segment_iden.name(),
+        ));
+    };
+    update_end_and_start_offset(client_pool, &segment_iden, end_offset).await?;
+
+    sealup_segment(cache_manager, client_pool, &segment_iden).await?;
+    update_meta_end_timestamp(client_pool, &segment_iden, segment_file_manager).await?;
+
+    Ok(SealUpSegmentReply {
+        end_offset,
+        next_segment_no: next_segment_iden.segment_seq,
+    })
+}
+
+async fn wait_segment_in_cache(
+    cache_manager: &Arc<CacheManager>,
+    segment_iden: &SegmentIdentity,
+) -> Result<(), JournalServerError> {
+    let mut waited = 0;
+    while waited < NEXT_SEGMENT_WAIT_MS {
+        if cache_manager.get_segment(segment_iden).is_some() {
+            return Ok(());
+        }
+        sleep(Duration::from_millis(100)).await;
+        waited += 100;
+    }
+    Err(JournalServerError::NextSegmentNotReady(segment_iden.name()))
+}
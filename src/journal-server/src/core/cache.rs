@@ -31,6 +31,7 @@
 use tracing::{error, info};
 
 use super::cluster_config::JournalEngineClusterConfig;
+use super::producer::ProducerStateManager;
 use crate::index::build::IndexBuildThreadData;
 use crate::segment::write::SegmentWrite;
 use crate::segment::SegmentIdentity;
@@ -62,6 +63,9 @@ pub struct CacheManager {
 
     // (segment_name, SegmentWrite)
     segment_writes: DashMap<String, SegmentWrite>,
+
+    // idempotent producer sequences
+    pub producer_state: ProducerStateManager,
 }
 
 impl Default for CacheManager {
@@ -88,6 +92,7 @@ pub fn new() -> Self {
             leader_segments,
             segment_index_build_thread,
             segment_writes: segment_write,
+            producer_state: ProducerStateManager::new(),
             start_time: now_second(),
         }
     }
@@ -154,6 +159,7 @@ pub fn get_shard(&self, namespace: &str, shard_name: &str) -> Option<JournalShar
 
     pub fn delete_shard(&self, namespace: &str, shard_name: &str) {
         self.shards.remove(&shard_name_iden(namespace, shard_name));
+        self.producer_state.remove_shard(namespace, shard_name);
     }
 
     pub fn get_shards(&self) -> Vec<JournalShard> {
@@ -79,6 +79,14 @@ pub enum JournalServerError {
     #[error("Current node is not the Leader of Segment {0}")]
     NotLeader(String),
 
+    #[error(
+        "Segment {0} is currently in state {1}, only a segment in the Write state can be sealed up"
+    )]
+    SegmentNotSealable(String, String),
+
+    #[error("Next segment of Segment {0} is not ready yet, retry later")]
+    NextSegmentNotReady(String),
+
     #[error("Segment file {0} does not exist, maybe it hasn't been initialized yet.")]
     SegmentFileNotExists(String),
 
@@ -102,6 +110,15 @@ pub enum JournalServerError {
 
     #[error("Segment Offset is at the end and can no longer be written.")]
     SegmentOffsetAtTheEnd,
+
+    #[error("Invalid record batch, {0}")]
+    InvalidRecordBatch(String),
+
+    #[error("Producer {0} has a batch in flight, retry later")]
+    ProducerBatchInFlight(String),
+
+    #[error("Producer {0} sequence out of order, expected {1}, received {2}")]
+    ProducerSequenceOutOfOrder(String, u64, u64),
 }
 
 pub fn get_journal_server_code(e: &JournalServerError) -> String {
@@ -127,6 +144,8 @@ pub fn get_journal_server_code(e: &JournalServerError) -> String {
         JournalServerError::NotFoundConnectionInCache(_) => "NotFoundConnectionInCache".to_string(),
         JournalServerError::SegmentStatusError(_, _) => "SegmentStatusError".to_string(),
         JournalServerError::NotLeader(_) => "NotLeader".to_string(),
+        JournalServerError::SegmentNotSealable(_, _) => "SegmentNotSealable".to_string(),
+        JournalServerError::NextSegmentNotReady(_) => "NextSegmentNotReady".to_string(),
         JournalServerError::SegmentFileNotExists(_) => "SegmentFileNotExists".to_string(),
         JournalServerError::SegmentDataDirectoryNotFound(_, _) => {
             "SegmentDataDirectoryNotFound".to_string()
@@ -143,6 +162,11 @@ pub fn get_journal_server_code(e: &JournalServerError) -> String {
             "NotAvailableOffsetByTimestamp".to_string()
         }
         JournalServerError::SegmentOffsetAtTheEnd => "SegmentOffsetAtTheEnd".to_string(),
+        JournalServerError::InvalidRecordBatch(_) => "InvalidRecordBatch".to_string(),
+        JournalServerError::ProducerBatchInFlight(_) => "ProducerBatchInFlight".to_string(),
+        JournalServerError::ProducerSequenceOutOfOrder(_, _, _) => {
+            "ProducerSequenceOutOfOrder".to_string()
+        }
     }
 }
 #[cfg(test)]
@@ -18,6 +18,7 @@
 pub mod error;
 pub mod log;
 pub mod notification;
+pub mod producer;
 pub mod segment;
 pub mod segment_meta;
 pub mod segment_status;
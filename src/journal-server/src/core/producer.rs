@@ -0,0 +1,242 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use common_base::tools::now_second;
+use dashmap::DashMap;
+use tokio::sync::broadcast;
+
+use super::cache::CacheManager;
+use super::error::JournalServerError;
+use super::tool::loop_select;
+
+// Number of recent batches whose offsets are kept per producer, so that a
+// retried batch can be answered with the offsets of the original write.
+const PRODUCER_BATCH_WINDOW: usize = 5;
+
+// A client starts a new producer id after a failed batch, so the state of
+// producers that stopped writing is dropped after this long.
+const PRODUCER_STATE_IDLE_SECONDS: u64 = 3600;
+
+const PRODUCER_STATE_EVICT_INTERVAL_SECONDS: u64 = 60;
+
+#[derive(Clone, Debug, PartialEq)]
+struct ProducerBatchOffset {
+    base_sequence: u64,
+    last_sequence: u64,
+    base_offset: u64,
+}
+
+#[derive(Default, Debug)]
+struct ProducerSequenceState {
+    last_sequence: Option<u64>,
+    pending_sequence: Option<u64>,
+    batches: VecDeque<ProducerBatchOffset>,
+    last_active_time: u64,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SequenceCheck {
+    // The batch is new and has been reserved, commit or abort it after the write.
+    Accept,
+    // The batch was already written, the value is the offset of its first record.
+    Duplicate(u64),
+}
+
+/// Tracks the sequence numbers of idempotent producers per shard.
+///
+/// The state only lives in memory: after a restart, or once a producer has
+/// been idle for long enough to be evicted, the first batch of every producer
+/// is accepted whatever its sequence is.
+#[derive(Default)]
+pub struct ProducerStateManager {
+    // (namespace/shard_name/producer_id, ProducerSequenceState)
+    states: DashMap<String, ProducerSequenceState>,
+}
+
+impl ProducerStateManager {
+    pub fn new() -> Self {
+        ProducerStateManager {
+            states: DashMap::with_capacity(8),
+        }
+    }
+
+    pub fn check_and_reserve(
+        &self,
+        key: &str,
+        base_sequence: u64,
+        last_sequence: u64,
+    ) -> Result<SequenceCheck, JournalServerError> {
+        let mut state = self.states.entry(key.to_string()).or_default();
+        state.last_active_time = now_second();
+
+        if state.pending_sequence.is_some() {
+            return Err(JournalServerError::ProducerBatchInFlight(key.to_string()));
+        }
+
+        if let Some(last) = state.last_sequence {
+            if base_sequence <= last {
+                return match state
+                    .batches
+                    .iter()
+                    .find(|batch| batch.base_sequence == base_sequence)
+                {
+                    Some(batch) => Ok(SequenceCheck::Duplicate(batch.base_offset)),
+                    None => Err(JournalServerError::ProducerSequenceOutOfOrder(
+                        key.to_string(),
+                        last + 1,
+                        base_sequence,
+                    )),
+                };
+            }
+
+            if base_sequence != last + 1 {
+                return Err(JournalServerError::ProducerSequenceOutOfOrder(
+                    key.to_string(),
+                    last + 1,
+                    base_sequence,
+                ));
+            }
+        }
+
+        state.pending_sequence = Some(last_sequence);
+        Ok(SequenceCheck::Accept)
+    }
+
+    pub fn commit(&self, key: &str, base_sequence: u64, last_sequence: u64, base_offset: u64) {
+        if let Some(mut state) = self.states.get_mut(key) {
+            state.pending_sequence = None;
+            state.last_sequence = Some(last_sequence);
+            state.last_active_time = now_second();
+            state.batches.push_back(ProducerBatchOffset {
+                base_sequence,
+                last_sequence,
+                base_offset,
+            });
+            while state.batches.len() > PRODUCER_BATCH_WINDOW {
+                state.batches.pop_front();
+            }
+        }
+    }
+
+    pub fn abort(&self, key: &str) {
+        if let Some(mut state) = self.states.get_mut(key) {
+            state.pending_sequence = None;
+        }
+    }
+
+    pub fn remove_shard(&self, namespace: &str, shard_name: &str) {
+        let prefix = format!("{namespace}/{shard_name}/");
+        self.states.retain(|key, _| !key.starts_with(&prefix));
+    }
+
+    // Drops the producers that have not written for `idle_seconds`. A batch
+    // in flight keeps its producer.
+    pub fn evict_idle(&self, now: u64, idle_seconds: u64) {
+        self.states.retain(|_, state| {
+            state.pending_sequence.is_some()
+                || now.saturating_sub(state.last_active_time) < idle_seconds
+        });
+    }
+}
+
+pub async fn start_producer_state_evict_thread(
+    cache_manager: Arc<CacheManager>,
+    stop_send: broadcast::Sender<bool>,
+) {
+    let ac_fn = async || -> Result<(), JournalServerError> {
+        cache_manager
+            .producer_state
+            .evict_idle(now_second(), PRODUCER_STATE_IDLE_SECONDS);
+        Ok(())
+    };
+    loop_select(ac_fn, PRODUCER_STATE_EVICT_INTERVAL_SECONDS, &stop_send).await;
+}
+
+pub fn producer_state_key(namespace: &str, shard_name: &str, producer_id: &str) -> String {
+    format!("{namespace}/{shard_name}/{producer_id}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn producer_sequence_test() {
+        let manager = ProducerStateManager::new();
+        let key = producer_state_key("n1", "s1", "p1");
+
+        // unknown producer, any sequence is accepted
+        assert_eq!(
+            manager.check_and_reserve(&key, 10, 19).unwrap(),
+            SequenceCheck::Accept
+        );
+
+        // in flight
+        assert!(manager.check_and_reserve(&key, 10, 19).is_err());
+        manager.commit(&key, 10, 19, 100);
+
+        // retry of the committed batch
+        assert_eq!(
+            manager.check_and_reserve(&key, 10, 19).unwrap(),
+            SequenceCheck::Duplicate(100)
+        );
+
+        // gap
+        assert!(manager.check_and_reserve(&key, 30, 39).is_err());
+
+        // next batch, aborted then retried
+        assert_eq!(
+            manager.check_and_reserve(&key, 20, 29).unwrap(),
+            SequenceCheck::Accept
+        );
+        manager.abort(&key);
+        assert_eq!(
+            manager.check_and_reserve(&key, 20, 29).unwrap(),
+            SequenceCheck::Accept
+        );
+        manager.commit(&key, 20, 29, 110);
+
+        manager.remove_shard("n1", "s1");
+        assert_eq!(
+            manager.check_and_reserve(&key, 0, 0).unwrap(),
+            SequenceCheck::Accept
+        );
+    }
+
+    #[test]
+    fn evict_idle_test() {
+        let manager = ProducerStateManager::new();
+        let idle = producer_state_key("n1", "s1", "idle");
+        let in_flight = producer_state_key("n1", "s1", "in-flight");
+
+        manager.check_and_reserve(&idle, 0, 9).unwrap();
+        manager.commit(&idle, 0, 9, 100);
+        manager.check_and_reserve(&in_flight, 0, 9).unwrap();
+
+        manager.evict_idle(now_second(), 3600);
+        assert_eq!(manager.states.len(), 2);
+
+        manager.evict_idle(now_second() + 3600, 3600);
+        assert_eq!(manager.states.len(), 1);
+
+        // An evicted producer starts over
+        assert_eq!(
+            manager.check_and_reserve(&idle, 50, 59).unwrap(),
+            SequenceCheck::Accept
+        );
+    }
+}
@@ -230,7 +230,8 @@ async fn save_record_index(
             position: read_data.position,
         };
 
-        if read_data.position == 0 {
+        // every record of the first batch is at position 0, only the first one is the start offset
+        if read_data.position == 0 && offset_index.get_start_offset(segment_iden)? < 0 {
             offset_index.save_start_offset(segment_iden, record.offset as u64)?;
         }
 
@@ -18,6 +18,7 @@
 use common_config::broker::broker_config;
 use common_config::config::BrokerConfig;
 use core::cache::{load_metadata_cache, CacheManager};
+use core::producer::start_producer_state_evict_thread;
 use grpc_clients::pool::ClientPool;
 use rocksdb_engine::RocksDBEngine;
 use segment::manager::{
@@ -116,6 +117,12 @@ fn start_daemon_thread(&self) {
         tokio::spawn(async move {
             segment_scroll.trigger_segment_scroll().await;
         });
+
+        let cache_manager = self.cache_manager.clone();
+        let inner_stop = self.inner_stop.clone();
+        tokio::spawn(async move {
+            start_producer_state_evict_thread(cache_manager, inner_stop).await;
+        });
     }
 
     async fn waiting_stop(&self) {
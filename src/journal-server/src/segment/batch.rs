@@ -0,0 +1,198 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use bytes::{Buf, BufMut, BytesMut};
+use common_base::utils::crc::calc_crc32;
+use prost::Message;
+use protocol::journal::journal_record::JournalRecord;
+use protocol::journal::producer_batch::{compress, decompress, CompressionType};
+
+use crate::core::error::JournalServerError;
+
+/// Records written before batches were introduced start with their offset. A batch
+/// starts with this marker instead, no record can have this offset.
+pub const RECORD_BATCH_MARKER: u64 = u64::MAX;
+
+/// [base_offset: u64][record_count: u32][first_timestamp: u64][last_timestamp: u64][compression: u8][crc: u32]
+pub const RECORD_BATCH_HEADER_LEN: u32 = 8 + 4 + 8 + 8 + 1 + 4;
+
+/// The header of a record batch, the crc covers the (compressed) body.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordBatchHeader {
+    pub base_offset: u64,
+    pub record_count: u32,
+    pub first_timestamp: u64,
+    pub last_timestamp: u64,
+    pub compression: CompressionType,
+    pub crc: u32,
+}
+
+impl RecordBatchHeader {
+    pub fn last_offset(&self) -> u64 {
+        self.base_offset + (self.record_count as u64).saturating_sub(1)
+    }
+
+    pub fn encode(&self, buf: &mut BytesMut) {
+        buf.put_u64(self.base_offset);
+        buf.put_u32(self.record_count);
+        buf.put_u64(self.first_timestamp);
+        buf.put_u64(self.last_timestamp);
+        buf.put_u8(compression_to_u8(self.compression));
+        buf.put_u32(self.crc);
+    }
+
+    pub fn decode(mut buf: &[u8]) -> Result<Self, JournalServerError> {
+        if buf.len() < RECORD_BATCH_HEADER_LEN as usize {
+            return Err(JournalServerError::InvalidRecordBatch(format!(
+                "header needs {} bytes, got {}",
+                RECORD_BATCH_HEADER_LEN,
+                buf.len()
+            )));
+        }
+        Ok(RecordBatchHeader {
+            base_offset: buf.get_u64(),
+            record_count: buf.get_u32(),
+            first_timestamp: buf.get_u64(),
+            last_timestamp: buf.get_u64(),
+            compression: compression_from_u8(buf.get_u8())?,
+            crc: buf.get_u32(),
+        })
+    }
+}
+
+/// Encode records into a batch frame, without the leading marker and length.
+///
+/// The body is the compressed concatenation of `[len: u32][record: bytes]`.
+pub fn encode_record_batch(
+    records: &[JournalRecord],
+    compression: CompressionType,
+) -> Result<Vec<u8>, JournalServerError> {
+    let mut raw = BytesMut::new();
+    for record in records {
+        let data = JournalRecord::encode_to_vec(record);
+        raw.put_u32(data.len() as u32);
+        raw.put_slice(&data);
+    }
+    let body = compress(compression, &raw)?;
+
+    let header = RecordBatchHeader {
+        base_offset: records.first().map(|r| r.offset as u64).unwrap_or_default(),
+        record_count: records.len() as u32,
+        first_timestamp: records.first().map(|r| r.create_time).unwrap_or_default(),
+        last_timestamp: records.last().map(|r| r.create_time).unwrap_or_default(),
+        compression,
+        crc: calc_crc32(&body),
+    };
+
+    let mut buf = BytesMut::with_capacity(RECORD_BATCH_HEADER_LEN as usize + body.len());
+    header.encode(&mut buf);
+    buf.put_slice(&body);
+    Ok(buf.to_vec())
+}
+
+/// Check the crc of the body and decode the records of a batch.
+pub fn decode_record_batch_body(
+    header: &RecordBatchHeader,
+    body: &[u8],
+) -> Result<Vec<JournalRecord>, JournalServerError> {
+    if calc_crc32(body) != header.crc {
+        return Err(JournalServerError::InvalidRecordBatch(format!(
+            "crc mismatch for batch with base offset {}",
+            header.base_offset
+        )));
+    }
+
+    let raw = decompress(header.compression, body)?;
+    let mut buf = raw.as_slice();
+    let mut records = Vec::with_capacity(header.record_count as usize);
+    while buf.remaining() >= 4 {
+        let len = buf.get_u32() as usize;
+        if buf.remaining() < len {
+            return Err(JournalServerError::InvalidRecordBatch(format!(
+                "record truncated in batch with base offset {}",
+                header.base_offset
+            )));
+        }
+        records.push(JournalRecord::decode(&buf[..len])?);
+        buf.advance(len);
+    }
+
+    if records.len() != header.record_count as usize {
+        return Err(JournalServerError::InvalidRecordBatch(format!(
+            "expected {} records, got {}",
+            header.record_count,
+            records.len()
+        )));
+    }
+    Ok(records)
+}
+
+fn compression_to_u8(compression: CompressionType) -> u8 {
+    match compression {
+        CompressionType::None => 0,
+        CompressionType::Lz4 => 1,
+        CompressionType::Zstd => 2,
+        CompressionType::Snappy => 3,
+    }
+}
+
+fn compression_from_u8(value: u8) -> Result<CompressionType, JournalServerError> {
+    match value {
+        0 => Ok(CompressionType::None),
+        1 => Ok(CompressionType::Lz4),
+        2 => Ok(CompressionType::Zstd),
+        3 => Ok(CompressionType::Snappy),
+        _ => Err(JournalServerError::InvalidRecordBatch(format!(
+            "unknown compression type {value}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_batch_test() {
+        let records: Vec<JournalRecord> = (0..20)
+            .map(|i| JournalRecord {
+                content: format!("{{\"sensor\":\"s1\",\"value\":{i}}}").into_bytes(),
+                create_time: 100 + i,
+                key: format!("k{i}"),
+                offset: 1000 + i as i64,
+                ..Default::default()
+            })
+            .collect();
+
+        for compression in [
+            CompressionType::None,
+            CompressionType::Lz4,
+            CompressionType::Zstd,
+        ] {
+            let data = encode_record_batch(&records, compression).unwrap();
+            let header = RecordBatchHeader::decode(&data).unwrap();
+            assert_eq!(header.base_offset, 1000);
+            assert_eq!(header.last_offset(), 1019);
+            assert_eq!(header.first_timestamp, 100);
+            assert_eq!(header.last_timestamp, 119);
+
+            let body = &data[RECORD_BATCH_HEADER_LEN as usize..];
+            assert_eq!(decode_record_batch_body(&header, body).unwrap(), records);
+
+            let mut corrupted = body.to_vec();
+            corrupted[0] ^= 0xff;
+            assert!(decode_record_batch_body(&header, &corrupted).is_err());
+        }
+    }
+}
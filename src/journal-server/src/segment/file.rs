@@ -12,22 +12,30 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashSet;
 use std::fs::remove_file;
 use std::io::ErrorKind;
 use std::path::Path;
+use std::str::FromStr;
 use std::sync::Arc;
 
-use bytes::BytesMut;
 use common_base::tools::{file_exists, try_create_fold};
 use common_config::broker::broker_config;
 use prost::Message;
 use protocol::journal::journal_record::JournalRecord;
+use protocol::journal::producer_batch::CompressionType;
 use tokio::fs::{self, File, OpenOptions};
-use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt, BufReader};
+use tracing::warn;
 
+use super::batch::{
+    decode_record_batch_body, encode_record_batch, RecordBatchHeader, RECORD_BATCH_HEADER_LEN,
+    RECORD_BATCH_MARKER,
+};
 use super::SegmentIdentity;
 use crate::core::cache::CacheManager;
 use crate::core::error::JournalServerError;
+use crate::index::IndexData;
 
 /// The record read from the segment file
 #[derive(Debug, Clone)]
@@ -57,13 +65,26 @@ pub async fn open_segment_write(
         ));
     };
 
+    let compression = match CompressionType::from_str(&conf.journal_storage.record_compression) {
+        Ok(compression) => compression,
+        Err(e) => {
+            warn!(
+                "{}, segment {} is written uncompressed",
+                e,
+                segment_iden.name()
+            );
+            CompressionType::None
+        }
+    };
+
     Ok((
         SegmentFile::new(
             segment_iden.namespace.to_string(),
             segment_iden.shard_name.to_string(),
             segment_iden.segment_seq,
             fold,
-        ),
+        )
+        .with_compression(compression),
         segment.config.max_segment_size,
     ))
 }
@@ -75,6 +96,7 @@ pub struct SegmentFile {
     pub shard_name: String,
     pub segment_no: u32,
     pub data_fold: String,
+    pub compression: CompressionType,
 }
 
 impl SegmentFile {
@@ -85,9 +107,16 @@ pub fn new(namespace: String, shard_name: String, segment_no: u32, data_fold: St
             shard_name,
             segment_no,
             data_fold,
+            compression: CompressionType::None,
         }
     }
 
+    /// set the compression of the record batches written to the segment file
+    pub fn with_compression(mut self, compression: CompressionType) -> Self {
+        self.compression = compression;
+        self
+    }
+
     /// try create a segment file under the data folder
     pub async fn try_create(&self) -> Result<(), JournalServerError> {
         try_create_fold(&self.data_fold)?;
@@ -109,18 +138,26 @@ pub async fn delete(&self) -> Result<(), JournalServerError> {
         Ok(remove_file(segment_file)?)
     }
 
-    /// append a list of records to the segment file
+    /// append a list of records to the segment file as one record batch
+    ///
+    /// The batch is stored in the following format:
+    ///
+    ///     [marker: u64][len: u32][header: bytes][body: bytes]
+    ///
+    /// See [`RecordBatchHeader`] for the header layout, `len` covers the header and the body.
     pub async fn write(&self, records: &[JournalRecord]) -> Result<(), JournalServerError> {
+        if records.is_empty() {
+            return Ok(());
+        }
+
         let segment_file = data_file_segment(&self.data_fold, self.segment_no);
         let file = OpenOptions::new().append(true).open(segment_file).await?;
         let mut writer = tokio::io::BufWriter::new(file);
 
-        for record in records {
-            let data = JournalRecord::encode_to_vec(record);
-            writer.write_u64(record.offset as u64).await?;
-            writer.write_u32(data.len() as u32).await?;
-            writer.write_all(data.as_ref()).await?;
-        }
+        let data = encode_record_batch(records, self.compression)?;
+        writer.write_u64(RECORD_BATCH_MARKER).await?;
+        writer.write_u32(data.len() as u32).await?;
+        writer.write_all(data.as_ref()).await?;
         writer.flush().await?;
         Ok(())
     }
@@ -139,7 +176,8 @@ pub async fn size(&self) -> Result<u64, JournalServerError> {
     ///     2. the total size of the records is less than or equal to `max_size`
     ///     3. the number of records is less than or equal to `max_record`
     ///
-    /// The records are stored in the segment file in the following format:
+    /// The segment file holds record batches (see [`SegmentFile::write`]) and, for segments
+    /// written by older versions, single records in the following format:
     ///
     ///     [offset: u64][len: u32][data: bytes]
     ///
@@ -147,7 +185,8 @@ pub async fn size(&self) -> Result<u64, JournalServerError> {
     ///
     /// # Return
     ///
-    /// A list of records and their byte positions in the segment file, in the order in which they are stored in the segment file.
+    /// A list of records and the byte positions of their batch (or of the record itself) in the segment file,
+    /// in the order in which they are stored in the segment file.
     ///
     pub async fn read_by_offset(
         &self,
@@ -166,51 +205,40 @@ pub async fn read_by_offset(
 
         let mut results = Vec::new();
         let mut already_size = 0;
-        loop {
-            if already_size > max_size {
-                break;
-            }
-
-            // read offset
+        'frames: loop {
             let position = reader.stream_position().await?;
-
-            let record_offset = match reader.read_u64().await {
-                Ok(offset) => offset,
-                Err(e) => {
-                    if e.kind() == ErrorKind::UnexpectedEof {
-                        break;
-                    }
-                    return Err(e.into());
-                }
+            let frame = match read_frame(&mut reader, start_offset).await? {
+                Some(frame) => frame,
+                None => break,
             };
 
-            // read len
-            let len = reader.read_u32().await?;
-
-            if record_offset < start_offset {
-                reader.seek(std::io::SeekFrom::Current(len as i64)).await?;
-                continue;
-            }
+            for record in frame {
+                if (record.offset as u64) < start_offset {
+                    continue;
+                }
 
-            // read body
-            let mut buf = BytesMut::with_capacity(len as usize);
-            reader.read_buf(&mut buf).await?;
+                // checked per record, a batch can hold far more than `max_size`
+                if already_size > max_size {
+                    break 'frames;
+                }
 
-            already_size += buf.len() as u64;
-            let record = JournalRecord::decode(buf)?;
-            results.push(ReadData { position, record });
+                // the size of the encoded record, key and headers included
+                already_size += record.encoded_len() as u64;
+                results.push(ReadData { position, record });
 
-            if results.len() >= max_record as usize {
-                break;
+                if results.len() >= max_record as usize {
+                    break 'frames;
+                }
             }
         }
 
         Ok(results)
     }
 
-    /// read a list of records by their byte positions in the segment file
+    /// read the records stored at the given byte positions in the segment file
     ///
-    /// See [`read_by_offset`] for more details.
+    /// A position points either at a single record or at a record batch, in which case every
+    /// record of the batch is returned. See [`SegmentFile::read_by_offset`] for more details.
     pub async fn read_by_positions(
         &self,
         positions: Vec<u64>,
@@ -224,34 +252,41 @@ pub async fn read_by_positions(
         for position in positions {
             reader.seek(std::io::SeekFrom::Start(position)).await?;
 
-            // read offset
-            let _ = match reader.read_u64().await {
-                Ok(offset) => offset,
-                Err(e) => {
-                    if e.kind() == ErrorKind::UnexpectedEof {
-                        break;
-                    }
-                    return Err(e.into());
-                }
+            let frame = match read_frame(&mut reader, 0).await? {
+                Some(frame) => frame,
+                None => break,
             };
 
-            // read len
-            let len = reader.read_u32().await?;
-
-            if len == 0 {
-                continue;
+            for record in frame {
+                results.push(ReadData { position, record });
             }
+        }
 
-            // read body
-            let mut buf = BytesMut::with_capacity(len as usize);
-            reader.read_buf(&mut buf).await?;
-
-            let record = JournalRecord::decode(buf)?;
+        Ok(results)
+    }
 
-            results.push(ReadData { position, record });
+    /// read the records referenced by key or tag index entries
+    ///
+    /// Several entries may point at the same batch, each batch is read once and only the
+    /// records whose offsets are referenced are returned.
+    pub async fn read_by_index(
+        &self,
+        index_data_list: &[IndexData],
+    ) -> Result<Vec<ReadData>, JournalServerError> {
+        let mut positions = Vec::new();
+        let mut offsets = HashSet::new();
+        for index_data in index_data_list {
+            if !positions.contains(&index_data.position) {
+                positions.push(index_data.position);
+            }
+            offsets.insert(index_data.offset);
         }
 
-        Ok(results)
+        let results = self.read_by_positions(positions).await?;
+        Ok(results
+            .into_iter()
+            .filter(|read_data| offsets.contains(&(read_data.record.offset as u64)))
+            .collect())
     }
 
     pub fn exists(&self) -> bool {
@@ -260,6 +295,58 @@ pub fn exists(&self) -> bool {
     }
 }
 
+/// read the frame at the current position of the reader, either a record batch or a single
+/// record written by older versions
+///
+/// Batches whose records are all below `start_offset` are skipped without being decoded.
+/// Returns `None` at the end of the file.
+async fn read_frame(
+    reader: &mut BufReader<File>,
+    start_offset: u64,
+) -> Result<Option<Vec<JournalRecord>>, JournalServerError> {
+    let marker = match reader.read_u64().await {
+        Ok(marker) => marker,
+        Err(e) => {
+            if e.kind() == ErrorKind::UnexpectedEof {
+                return Ok(None);
+            }
+            return Err(e.into());
+        }
+    };
+
+    let len = reader.read_u32().await?;
+
+    if marker != RECORD_BATCH_MARKER {
+        // a single record, the marker is its offset
+        if len == 0 {
+            return Ok(Some(Vec::new()));
+        }
+        if marker < start_offset {
+            reader.seek(std::io::SeekFrom::Current(len as i64)).await?;
+            return Ok(Some(Vec::new()));
+        }
+        let mut buf = vec![0u8; len as usize];
+        reader.read_exact(&mut buf).await?;
+        return Ok(Some(vec![JournalRecord::decode(buf.as_slice())?]));
+    }
+
+    let mut header_buf = [0u8; RECORD_BATCH_HEADER_LEN as usize];
+    reader.read_exact(&mut header_buf).await?;
+    let header = RecordBatchHeader::decode(&header_buf)?;
+    let body_len = len.saturating_sub(RECORD_BATCH_HEADER_LEN);
+
+    if header.last_offset() < start_offset {
+        reader
+            .seek(std::io::SeekFrom::Current(body_len as i64))
+            .await?;
+        return Ok(Some(Vec::new()));
+    }
+
+    let mut body = vec![0u8; body_len as usize];
+    reader.read_exact(&mut body).await?;
+    Ok(Some(decode_record_batch_body(&header, &body)?))
+}
+
 pub fn data_fold_shard(namespace: &str, shard_name: &str, data_fold: &str) -> String {
     let file_name = format!("{namespace}/{shard_name}");
     format!("{data_fold}/{file_name}")
@@ -278,9 +365,15 @@ mod tests {
     use metadata_struct::journal::segment::{JournalSegment, Replica, SegmentConfig};
     use protocol::journal::journal_record::JournalRecord;
 
+    use prost::Message;
+    use protocol::journal::producer_batch::CompressionType;
+    use tokio::fs::OpenOptions;
+    use tokio::io::AsyncWriteExt;
+
     use super::{data_file_segment, data_fold_shard, open_segment_write, SegmentFile};
     use crate::core::cache::CacheManager;
     use crate::core::test::{test_build_data_fold, test_build_segment};
+    use crate::index::IndexData;
     use crate::segment::SegmentIdentity;
 
     #[tokio::test]
@@ -393,6 +486,48 @@ async fn segment_read_offset_test() {
         assert_eq!(res.len(), 5);
     }
 
+    #[tokio::test]
+    async fn segment_read_max_size_test() {
+        let data_fold = test_build_data_fold();
+        let segment_iden = test_build_segment();
+
+        let segment = SegmentFile::new(
+            segment_iden.namespace.to_string(),
+            segment_iden.shard_name.to_string(),
+            segment_iden.segment_seq,
+            data_fold.first().unwrap().to_string(),
+        );
+        segment.try_create().await.unwrap();
+
+        // small payloads behind large keys, all in one batch
+        let records: Vec<JournalRecord> = (0..10)
+            .map(|i| JournalRecord {
+                content: vec![b'a'; 10],
+                create_time: now_second(),
+                key: format!("{i}").repeat(1000),
+                namespace: "n1".to_string(),
+                shard_name: "s1".to_string(),
+                offset: 1000 + i,
+                segment: 1,
+                ..Default::default()
+            })
+            .collect();
+        segment.write(&records).await.unwrap();
+
+        let record_size = records[0].encoded_len() as u64;
+        let res = segment
+            .read_by_offset(0, 0, record_size + 1, 1000)
+            .await
+            .unwrap();
+        assert_eq!(res.len(), 2);
+        assert_eq!(res[1].record.offset, 1001);
+
+        // the first record is returned even if it alone is larger than max_size
+        let res = segment.read_by_offset(0, 1005, 1, 1000).await.unwrap();
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0].record.offset, 1005);
+    }
+
     #[tokio::test]
     async fn segment_read_position_test() {
         let data_fold = test_build_data_fold();
@@ -427,16 +562,102 @@ async fn segment_read_position_test() {
             }
         }
 
+        // every write is a batch of one record
+        let positions: Vec<u64> = segment
+            .read_by_offset(0, 0, 20000, 1000)
+            .await
+            .unwrap()
+            .iter()
+            .map(|read_data| read_data.position)
+            .collect();
+        assert_eq!(positions.len(), 10);
+
         let res = segment.read_by_positions(vec![0]).await.unwrap();
         assert_eq!(res.len(), 1);
 
-        let res = segment.read_by_positions(vec![45]).await.unwrap();
+        let res = segment.read_by_positions(vec![positions[1]]).await.unwrap();
         assert_eq!(res.len(), 1);
+        assert_eq!(res[0].record.offset, 1001);
 
-        let res = segment.read_by_positions(vec![0, 45, 90]).await.unwrap();
+        let res = segment
+            .read_by_positions(positions[0..3].to_vec())
+            .await
+            .unwrap();
         assert_eq!(res.len(), 3);
 
         let size = segment.size().await.unwrap();
         assert!(size > 0);
     }
+
+    #[tokio::test]
+    async fn segment_read_batch_and_legacy_test() {
+        let data_fold = test_build_data_fold();
+        let segment_iden = test_build_segment();
+
+        let segment = SegmentFile::new(
+            segment_iden.namespace.to_string(),
+            segment_iden.shard_name.to_string(),
+            segment_iden.segment_seq,
+            data_fold.first().unwrap().to_string(),
+        )
+        .with_compression(CompressionType::Zstd);
+        segment.try_create().await.unwrap();
+
+        let build_record = |i: i64| JournalRecord {
+            content: format!("{{\"temperature\":{i}}}").into_bytes(),
+            create_time: now_second(),
+            key: format!("k{i}"),
+            tags: vec![format!("t{}", i % 2)],
+            offset: i,
+            ..Default::default()
+        };
+
+        // records written by older versions, one frame per record
+        let file = OpenOptions::new()
+            .append(true)
+            .open(data_file_segment(&segment.data_fold, segment.segment_no))
+            .await
+            .unwrap();
+        let mut writer = tokio::io::BufWriter::new(file);
+        for i in 0..5 {
+            let data = JournalRecord::encode_to_vec(&build_record(i));
+            writer.write_u64(i as u64).await.unwrap();
+            writer.write_u32(data.len() as u32).await.unwrap();
+            writer.write_all(&data).await.unwrap();
+        }
+        writer.flush().await.unwrap();
+
+        // followed by two compressed batches
+        let records: Vec<JournalRecord> = (5..15).map(build_record).collect();
+        segment.write(&records[0..5]).await.unwrap();
+        segment.write(&records[5..10]).await.unwrap();
+
+        let res = segment.read_by_offset(0, 0, 20000, 1000).await.unwrap();
+        assert_eq!(res.len(), 15);
+        for (i, read_data) in res.iter().enumerate() {
+            assert_eq!(read_data.record.offset, i as i64);
+        }
+
+        // the records of a batch share the position of the batch
+        let res = segment.read_by_offset(0, 7, 20000, 1000).await.unwrap();
+        assert_eq!(res.len(), 8);
+        assert_eq!(res[0].position, res[2].position);
+        assert_ne!(res[2].position, res[3].position);
+
+        let index_data: Vec<IndexData> = res
+            .iter()
+            .filter(|read_data| read_data.record.offset % 2 == 0)
+            .map(|read_data| IndexData {
+                offset: read_data.record.offset as u64,
+                timestamp: read_data.record.create_time,
+                position: read_data.position,
+            })
+            .collect();
+        let res = segment.read_by_index(&index_data).await.unwrap();
+        let offsets: Vec<i64> = res
+            .iter()
+            .map(|read_data| read_data.record.offset)
+            .collect();
+        assert_eq!(offsets, vec![8, 10, 12, 14]);
+    }
 }
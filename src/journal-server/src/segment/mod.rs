@@ -14,6 +14,7 @@
 
 use metadata_struct::journal::segment::{segment_name, JournalSegment};
 
+pub mod batch;
 pub mod file;
 pub mod manager;
 pub mod read;
@@ -177,7 +177,7 @@ async fn read_by_offset(
 
 /// handle read requests by key
 ///
-/// Use index (if there's any) to find all start byte positions of the records with the given key
+/// Use index (if there's any) to find the byte positions of the records (or their batches) with the given key
 async fn read_by_key(
     rocksdb_engine_handler: &Arc<RocksDBEngine>,
     segment_file: &SegmentFile,
@@ -195,9 +195,7 @@ async fn read_by_key(
         )
         .await?;
 
-    let positions = index_data_list.iter().map(|raw| raw.position).collect();
-
-    segment_file.read_by_positions(positions).await
+    segment_file.read_by_index(&index_data_list).await
 }
 
 /// handle read requests by tag
@@ -219,8 +217,7 @@ async fn read_by_tag(
             read_options.max_record,
         )
         .await?;
-    let positions = index_data_list.iter().map(|raw| raw.position).collect();
-    segment_file.read_by_positions(positions).await
+    segment_file.read_by_index(&index_data_list).await
 }
 
 #[cfg(test)]
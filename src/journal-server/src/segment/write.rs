@@ -14,6 +14,7 @@
 
 use crate::core::cache::CacheManager;
 use crate::core::error::{get_journal_server_code, JournalServerError};
+use crate::core::producer::{producer_state_key, SequenceCheck};
 use crate::core::segment_meta::{update_meta_end_timestamp, update_meta_start_timestamp};
 use crate::core::segment_status::sealup_segment;
 use crate::index::build::try_trigger_build_index;
@@ -23,8 +24,12 @@
 use common_base::tools::now_second;
 use grpc_clients::pool::ClientPool;
 use metadata_struct::journal::segment::SegmentStatus;
-use protocol::journal::journal_engine::{WriteReqBody, WriteRespMessage, WriteRespMessageStatus};
+use protocol::journal::journal_engine::{
+    JournalEngineError, WriteReqBody, WriteReqMessages, WriteReqSegmentMessages, WriteRespMessage,
+    WriteRespMessageStatus,
+};
 use protocol::journal::journal_record::JournalRecord;
+use protocol::journal::producer_batch::{is_producer_batch, ProducerBatch};
 use rocksdb_engine::RocksDBEngine;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -80,7 +85,38 @@ pub async fn write_data_req(
         );
 
         let mut record_list = Vec::new();
+        let mut resp_message_status = Vec::new();
+        let mut reserved_batches = Vec::new();
         for message in shard_data.messages.iter() {
+            if is_producer_batch(&message.tags) {
+                match expand_producer_batch(cache_manager, &shard_data, message) {
+                    Ok(ProducerBatchExpand::Records(records, reserved)) => {
+                        record_list.extend(records);
+                        if let Some(reserved) = reserved {
+                            reserved_batches.push(reserved);
+                        }
+                    }
+                    Ok(ProducerBatchExpand::Duplicate(offset)) => {
+                        resp_message_status.push(WriteRespMessageStatus {
+                            pkid: message.pkid,
+                            offset,
+                            ..Default::default()
+                        });
+                    }
+                    Err(e) => {
+                        resp_message_status.push(WriteRespMessageStatus {
+                            pkid: message.pkid,
+                            error: Some(JournalEngineError {
+                                code: get_journal_server_code(&e),
+                                error: e.to_string(),
+                            }),
+                            ..Default::default()
+                        });
+                    }
+                }
+                continue;
+            }
+
             // todo data validator
             let record = JournalRecord {
                 content: message.value.clone(),
@@ -97,6 +133,12 @@ pub async fn write_data_req(
             record_list.push(record);
         }
 
+        if record_list.is_empty() {
+            resp_message.messages = resp_message_status;
+            results.push(resp_message);
+            continue;
+        }
+
         let resp = match write_data(
             cache_manager,
             rocksdb_engine_handler,
@@ -108,6 +150,8 @@ pub async fn write_data_req(
         {
             Ok(resp) => resp,
             Err(e) => {
+                abort_producer_batches(cache_manager, &reserved_batches);
+
                 // if this write filled up the segment, we need to seal up the segment and update end timestamp
                 if get_journal_server_code(&e) == *"SegmentOffsetAtTheEnd" {
                     sealup_segment(cache_manager, client_pool, &segment_iden).await?;
@@ -120,15 +164,7 @@ pub async fn write_data_req(
                         &segment_iden,
                     )
                     .await?;
-
-                    // Stop the segment writer thread while waiting for messages to be written to the channel to clear
-                    loop {
-                        if write.data_sender.capacity() == write.data_sender.max_capacity() {
-                            write.stop_sender.send(true)?;
-                            break;
-                        }
-                        sleep(Duration::from_millis(10)).await;
-                    }
+                    stop_write_thread(&write).await?;
                 }
 
                 return Err(e);
@@ -136,10 +172,23 @@ pub async fn write_data_req(
         };
 
         if let Some(e) = resp.error {
+            abort_producer_batches(cache_manager, &reserved_batches);
             return Err(e);
         }
 
-        let mut resp_message_status = Vec::new();
+        for reserved in reserved_batches.iter() {
+            if let Some(offset) = resp.offsets.get(&reserved.pkid) {
+                cache_manager.producer_state.commit(
+                    &reserved.key,
+                    reserved.base_sequence,
+                    reserved.last_sequence,
+                    *offset,
+                );
+            } else {
+                cache_manager.producer_state.abort(&reserved.key);
+            }
+        }
+
         for (pkid, offset) in resp.offsets {
             let status = WriteRespMessageStatus {
                 pkid,
@@ -176,6 +225,83 @@ pub async fn write_data_req(
     Ok(results)
 }
 
+struct ReservedProducerBatch {
+    key: String,
+    pkid: u64,
+    base_sequence: u64,
+    last_sequence: u64,
+}
+
+enum ProducerBatchExpand {
+    Records(Vec<JournalRecord>, Option<ReservedProducerBatch>),
+    Duplicate(u64),
+}
+
+/// decode a producer batch into records, checking the producer sequence first
+///
+/// All the records of the batch share the pkid of the batch message, so the offset
+/// returned for it is the offset of the first record.
+fn expand_producer_batch(
+    cache_manager: &Arc<CacheManager>,
+    shard_data: &WriteReqSegmentMessages,
+    message: &WriteReqMessages,
+) -> Result<ProducerBatchExpand, JournalServerError> {
+    let batch = ProducerBatch::decode(&message.value)?;
+    if batch.records.is_empty() {
+        return Err(JournalServerError::RequestBodyNotEmpty(
+            "producer batch".to_string(),
+        ));
+    }
+
+    let reserved = if batch.is_idempotent() {
+        let key = producer_state_key(
+            &shard_data.namespace,
+            &shard_data.shard_name,
+            &batch.producer_id,
+        );
+        match cache_manager.producer_state.check_and_reserve(
+            &key,
+            batch.base_sequence,
+            batch.last_sequence(),
+        )? {
+            SequenceCheck::Duplicate(offset) => return Ok(ProducerBatchExpand::Duplicate(offset)),
+            SequenceCheck::Accept => Some(ReservedProducerBatch {
+                key,
+                pkid: message.pkid,
+                base_sequence: batch.base_sequence,
+                last_sequence: batch.last_sequence(),
+            }),
+        }
+    } else {
+        None
+    };
+
+    let create_time = now_second();
+    let records = batch
+        .records
+        .into_iter()
+        .map(|record| JournalRecord {
+            content: record.value,
+            create_time,
+            key: record.key,
+            namespace: shard_data.namespace.clone(),
+            shard_name: shard_data.shard_name.clone(),
+            segment: shard_data.segment,
+            tags: record.tags,
+            pkid: message.pkid,
+            producer_id: batch.producer_id.clone(),
+            offset: -1,
+        })
+        .collect();
+    Ok(ProducerBatchExpand::Records(records, reserved))
+}
+
+fn abort_producer_batches(cache_manager: &Arc<CacheManager>, reserved: &[ReservedProducerBatch]) {
+    for batch in reserved {
+        cache_manager.producer_state.abort(&batch.key);
+    }
+}
+
 /// get the write handle for the segment identified by `segment_iden`, write data and return the response
 pub(crate) async fn write_data(
     cache_manager: &Arc<CacheManager>,
@@ -232,6 +358,17 @@ async fn get_write(
     Ok(write)
 }
 
+/// stop the segment write thread once the messages already sent to its channel have been written
+pub(crate) async fn stop_write_thread(write: &SegmentWrite) -> Result<(), JournalServerError> {
+    loop {
+        if write.data_sender.capacity() == write.data_sender.max_capacity() {
+            write.stop_sender.send(true)?;
+            return Ok(());
+        }
+        sleep(Duration::from_millis(10)).await;
+    }
+}
+
 /// create a segment write thread which is responsible for writing data to the segment file identified by `segment_iden`
 ///
 /// Return a `SegmentWrite` handle which can be used to send data to the write thread
@@ -412,7 +549,8 @@ async fn batch_write0(
         record.offset = offset as i64;
         records.push(record.clone());
 
-        offsets.insert(record.pkid, offset);
+        // records expanded from a producer batch share the pkid, keep the first offset
+        offsets.entry(record.pkid).or_insert(offset);
         local_segment_end_offset = offset;
     }
 
@@ -12,12 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::admin::services::{list_segment_by_req, list_shard_by_req};
+use crate::admin::services::{
+    get_segment_file_by_req, list_segment_by_req, list_shard_by_req, seal_up_segment_by_req,
+};
 use crate::core::cache::CacheManager;
+use crate::segment::manager::SegmentFileManager;
+use grpc_clients::pool::ClientPool;
 use protocol::journal::journal_admin::journal_server_admin_service_server::JournalServerAdminService;
 use protocol::journal::journal_admin::{
     ListSegmentReply, ListSegmentRequest, ListShardReply, ListShardRequest,
 };
+use protocol::journal::journal_segment_admin::journal_segment_admin_service_server::JournalSegmentAdminService;
+use protocol::journal::journal_segment_admin::{
+    GetSegmentFileReply, GetSegmentFileRequest, SealUpSegmentReply, SealUpSegmentRequest,
+};
 use std::sync::Arc;
 use tonic::{Request, Response, Status};
 
@@ -55,3 +63,53 @@ async fn list_segment(
             .map(Response::new)
     }
 }
+
+pub struct GrpcJournalSegmentAdminService {
+    cache_manager: Arc<CacheManager>,
+    client_pool: Arc<ClientPool>,
+    segment_file_manager: Arc<SegmentFileManager>,
+}
+
+impl GrpcJournalSegmentAdminService {
+    pub fn new(
+        cache_manager: Arc<CacheManager>,
+        client_pool: Arc<ClientPool>,
+        segment_file_manager: Arc<SegmentFileManager>,
+    ) -> Self {
+        GrpcJournalSegmentAdminService {
+            cache_manager,
+            client_pool,
+            segment_file_manager,
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl JournalSegmentAdminService for GrpcJournalSegmentAdminService {
+    async fn get_segment_file(
+        &self,
+        request: Request<GetSegmentFileRequest>,
+    ) -> Result<Response<GetSegmentFileReply>, Status> {
+        let request = request.into_inner();
+        get_segment_file_by_req(&self.segment_file_manager, &request)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))
+            .map(Response::new)
+    }
+
+    async fn seal_up_segment(
+        &self,
+        request: Request<SealUpSegmentRequest>,
+    ) -> Result<Response<SealUpSegmentReply>, Status> {
+        let request = request.into_inner();
+        seal_up_segment_by_req(
+            &self.cache_manager,
+            &self.client_pool,
+            &self.segment_file_manager,
+            &request,
+        )
+        .await
+        .map_err(|e| Status::internal(e.to_string()))
+        .map(Response::new)
+    }
+}
@@ -0,0 +1,81 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use metadata_struct::{adapter::record::Record, mqtt::message::MqttMessage};
+
+/// Messages stored with an expire time of zero never expire.
+pub const NEVER_EXPIRE: u64 = 0;
+
+/// Whether a message with the given absolute expire time, in seconds, has
+/// expired at `now`.
+pub fn is_expired(expire_at: u64, now: u64) -> bool {
+    expire_at != NEVER_EXPIRE && expire_at < now
+}
+
+/// The Message Expiry Interval to forward to a subscriber: the lifetime the
+/// message has left, rather than the interval it was published with.
+pub fn remaining_expiry_interval(expire_at: u64, now: u64) -> Option<u32> {
+    if expire_at == NEVER_EXPIRE {
+        return None;
+    }
+    let remaining = expire_at.saturating_sub(now).max(1);
+    Some(remaining.min(u32::MAX as u64) as u32)
+}
+
+/// Expire time of a record holding an MQTT message, `None` if the record is
+/// not an MQTT message or never expires.
+pub fn mqtt_record_expire_at(record: &Record) -> Option<u64> {
+    let message = MqttMessage::decode_record(record.clone()).ok()?;
+    if message.expiry_interval == NEVER_EXPIRE {
+        return None;
+    }
+    Some(message.expiry_interval)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_expired_test() {
+        assert!(is_expired(90, 100));
+        assert!(!is_expired(100, 100));
+        assert!(!is_expired(110, 100));
+        assert!(!is_expired(NEVER_EXPIRE, 100));
+    }
+
+    #[test]
+    fn remaining_expiry_interval_test() {
+        assert_eq!(remaining_expiry_interval(130, 100), Some(30));
+        assert_eq!(remaining_expiry_interval(100, 100), Some(1));
+        assert_eq!(remaining_expiry_interval(NEVER_EXPIRE, 100), None);
+    }
+
+    #[test]
+    fn mqtt_record_expire_at_test() {
+        let message = MqttMessage {
+            expiry_interval: 120,
+            ..Default::default()
+        };
+        let record = Record::build_byte(message.encode());
+        assert_eq!(mqtt_record_expire_at(&record), Some(120));
+
+        let message = MqttMessage::default();
+        let record = Record::build_byte(message.encode());
+        assert_eq!(mqtt_record_expire_at(&record), None);
+
+        let record = Record::build_str("not a message".to_string());
+        assert_eq!(mqtt_record_expire_at(&record), None);
+    }
+}
@@ -11,3 +11,394 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
+
+use common_base::{
+    error::common::CommonError,
+    tools::{loop_select, now_second},
+};
+use expire::is_expired;
+use metadata_struct::adapter::{read_config::ReadConfig, record::Record};
+use storage_adapter::storage::{ArcStorageAdapter, DeletionSupport};
+use tokio::sync::broadcast;
+use tracing::{debug, error, info};
+
+pub mod expire;
+
+/// How often every shard is scanned for expired records.
+const MESSAGE_EXPIRE_INTERVAL_SEC: u64 = 60;
+
+/// Number of records read per request while scanning a shard.
+const MESSAGE_EXPIRE_SCAN_BATCH: u64 = 1000;
+
+/// Number of new records read per shard and per scan at most, so the cost of a
+/// scan does not grow with the amount of data the shard keeps.
+const MESSAGE_EXPIRE_SCAN_MAX_RECORDS: u64 = 10 * MESSAGE_EXPIRE_SCAN_BATCH;
+
+/// Number of records with a lifetime left remembered per shard at most. Once
+/// reached, scans stop reading new records until some of them expire.
+const MESSAGE_EXPIRE_MAX_PENDING: usize = 100_000;
+
+/// Extracts the absolute expire time, in seconds, of a stored record. `None`
+/// means the record never expires.
+pub type ExpireAtFn = fn(&Record) -> Option<u64>;
+
+/// What the scans of one shard left for the next scan.
+#[derive(Default, Debug, Clone)]
+struct ShardExpireState {
+    // Every record below this offset has been read by an earlier scan
+    scan_offset: u64,
+    // (expire_at, offset) of the records below `scan_offset` that have a
+    // lifetime left, so they are deleted without being read again
+    pending: BTreeSet<(u64, u64)>,
+    // Expired records the adapter still stores. Only kept for adapters that
+    // drop whole segments, as a segment goes once all of its offsets were
+    // asked for in the same call.
+    expired: BTreeSet<u64>,
+}
+
+/// Removes expired records from the shards of a storage adapter, so that
+/// shards nobody reads from do not grow forever.
+pub struct MessageExpireManager {
+    namespace: String,
+    storage_adapter: ArcStorageAdapter,
+    expire_at: ExpireAtFn,
+    // (shard_name, ShardExpireState)
+    shard_states: Mutex<HashMap<String, ShardExpireState>>,
+}
+
+impl MessageExpireManager {
+    pub fn new(
+        namespace: String,
+        storage_adapter: ArcStorageAdapter,
+        expire_at: ExpireAtFn,
+    ) -> Self {
+        MessageExpireManager {
+            namespace,
+            storage_adapter,
+            expire_at,
+            shard_states: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Delete the records of the shard that have expired at `now` and return
+    /// how many newly expired records were found. Every record is read once:
+    /// a scan reads the records written since the previous scan, and deletes
+    /// the records read earlier from the expire times it remembered.
+    pub async fn expire_shard(&self, shard_name: &str, now: u64) -> Result<u64, CommonError> {
+        let mut state = self
+            .shard_states
+            .lock()
+            .unwrap()
+            .remove(shard_name)
+            .unwrap_or_default();
+        let result = self.scan_shard(shard_name, now, &mut state).await;
+        self.shard_states
+            .lock()
+            .unwrap()
+            .insert(shard_name.to_owned(), state);
+        result
+    }
+
+    /// Forget the scan state of the shards that are no longer expired.
+    pub fn retain_shards(&self, shard_names: &HashSet<String>) {
+        self.shard_states
+            .lock()
+            .unwrap()
+            .retain(|shard_name, _| shard_names.contains(shard_name));
+    }
+
+    async fn scan_shard(
+        &self,
+        shard_name: &str,
+        now: u64,
+        state: &mut ShardExpireState,
+    ) -> Result<u64, CommonError> {
+        // Records read by earlier scans whose lifetime is over
+        let mut due = Vec::new();
+        while let Some(&(expire_at, offset)) = state.pending.first() {
+            if !is_expired(expire_at, now) {
+                break;
+            }
+            state.pending.pop_first();
+            due.push((expire_at, offset));
+        }
+        let offsets = due.iter().map(|(_, offset)| *offset).collect();
+        if let Err(e) = self.delete_expired(shard_name, state, offsets).await {
+            state.pending.extend(due);
+            return Err(e);
+        }
+        let mut expired_num = due.len() as u64;
+
+        // New records since the last scan
+        let mut read_num = 0;
+        while read_num < MESSAGE_EXPIRE_SCAN_MAX_RECORDS
+            && state.pending.len() < MESSAGE_EXPIRE_MAX_PENDING
+        {
+            let read_config = ReadConfig {
+                max_record_num: MESSAGE_EXPIRE_SCAN_BATCH,
+                max_size: u64::MAX,
+            };
+            let records = self
+                .storage_adapter
+                .read_by_offset(
+                    self.namespace.clone(),
+                    shard_name.to_owned(),
+                    state.scan_offset,
+                    read_config,
+                )
+                .await?;
+
+            let Some(next_offset) = records.iter().filter_map(|record| record.offset).max() else {
+                break;
+            };
+
+            let mut expired = Vec::new();
+            for record in records.iter() {
+                let Some(record_offset) = record.offset else {
+                    continue;
+                };
+                match (self.expire_at)(record) {
+                    Some(expire_at) if is_expired(expire_at, now) => expired.push(record_offset),
+                    Some(expire_at) => {
+                        state.pending.insert((expire_at, record_offset));
+                    }
+                    None => {}
+                }
+            }
+
+            expired_num += expired.len() as u64;
+            self.delete_expired(shard_name, state, expired).await?;
+
+            read_num += records.len() as u64;
+            state.scan_offset = next_offset + 1;
+        }
+
+        if !state.expired.is_empty() {
+            self.delete_expired_segments(shard_name, state).await?;
+        }
+        Ok(expired_num)
+    }
+
+    async fn delete_expired(
+        &self,
+        shard_name: &str,
+        state: &mut ShardExpireState,
+        offsets: Vec<u64>,
+    ) -> Result<(), CommonError> {
+        if offsets.is_empty() {
+            return Ok(());
+        }
+        if self.storage_adapter.deletion_support() == DeletionSupport::Segment {
+            state.expired.extend(offsets);
+            return Ok(());
+        }
+        self.storage_adapter
+            .delete_by_offsets(self.namespace.clone(), shard_name.to_owned(), offsets)
+            .await
+    }
+
+    async fn delete_expired_segments(
+        &self,
+        shard_name: &str,
+        state: &mut ShardExpireState,
+    ) -> Result<(), CommonError> {
+        match self
+            .storage_adapter
+            .delete_by_offsets(
+                self.namespace.clone(),
+                shard_name.to_owned(),
+                state.expired.iter().copied().collect(),
+            )
+            .await
+        {
+            Ok(()) => {
+                state.expired.clear();
+                Ok(())
+            }
+            // Segments that still hold live records stay, forget the offsets
+            // of the segments that went away
+            Err(CommonError::PartialDeletion(_, _)) => {
+                let read_config = ReadConfig {
+                    max_record_num: 1,
+                    max_size: u64::MAX,
+                };
+                let records = self
+                    .storage_adapter
+                    .read_by_offset(
+                        self.namespace.clone(),
+                        shard_name.to_owned(),
+                        0,
+                        read_config,
+                    )
+                    .await?;
+                match records.first().and_then(|record| record.offset) {
+                    Some(first_offset) => {
+                        state.expired = state.expired.split_off(&first_offset);
+                    }
+                    None => state.expired.clear(),
+                }
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Periodically expire the records of the shards returned by `shard_list`.
+/// Adapters that cannot delete records are left alone.
+pub fn start_message_expire_thread<F>(
+    message_expire_manager: Arc<MessageExpireManager>,
+    shard_list: F,
+    stop_send: broadcast::Sender<bool>,
+) where
+    F: Fn() -> Vec<String> + Send + Sync + 'static,
+{
+    if message_expire_manager.storage_adapter.deletion_support() == DeletionSupport::None {
+        info!("Message storage does not support deleting records, message expire thread is not started");
+        return;
+    }
+
+    info!("Message expire thread start successfully");
+    tokio::spawn(async move {
+        let expire_func = async || -> Result<(), CommonError> {
+            let now = now_second();
+            let shard_names = shard_list();
+            for shard_name in shard_names.iter() {
+                match message_expire_manager.expire_shard(shard_name, now).await {
+                    Ok(0) => {}
+                    Ok(num) => {
+                        debug!("{} messages of shard {} expired", num, shard_name);
+                    }
+                    Err(e) => {
+                        error!(
+                            "Failed to delete expired messages from shard {}, error message: {}",
+                            shard_name, e
+                        );
+                    }
+                }
+            }
+            message_expire_manager.retain_shards(&shard_names.into_iter().collect());
+            Ok(())
+        };
+        loop_select(expire_func, MESSAGE_EXPIRE_INTERVAL_SEC, &stop_send).await;
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expire::mqtt_record_expire_at;
+    use metadata_struct::mqtt::message::MqttMessage;
+    use storage_adapter::storage::build_memory_storage_driver;
+
+    fn build_record(expiry_interval: u64) -> Record {
+        let message = MqttMessage {
+            expiry_interval,
+            ..Default::default()
+        };
+        Record::build_byte(message.encode())
+    }
+
+    #[tokio::test]
+    async fn expire_shard_test() {
+        let storage_adapter = build_memory_storage_driver();
+        let namespace = "expire".to_string();
+        let shard_name = "topic-1".to_string();
+        let now = now_second();
+
+        let records = vec![
+            build_record(now - 10),
+            build_record(now + 100),
+            build_record(0),
+            build_record(now - 1),
+        ];
+        storage_adapter
+            .batch_write(namespace.clone(), shard_name.clone(), records)
+            .await
+            .unwrap();
+
+        let manager = MessageExpireManager::new(
+            namespace.clone(),
+            storage_adapter.clone(),
+            mqtt_record_expire_at,
+        );
+        assert_eq!(manager.expire_shard(&shard_name, now).await.unwrap(), 2);
+
+        let read_config = ReadConfig {
+            max_record_num: 10,
+            max_size: u64::MAX,
+        };
+        let remaining = storage_adapter
+            .read_by_offset(namespace.clone(), shard_name.clone(), 0, read_config)
+            .await
+            .unwrap();
+        let offsets: Vec<u64> = remaining.iter().filter_map(|r| r.offset).collect();
+        assert_eq!(offsets, vec![1, 2]);
+
+        // nothing left to expire, the live record is remembered and not read again
+        assert_eq!(manager.expire_shard(&shard_name, now).await.unwrap(), 0);
+        {
+            let states = manager.shard_states.lock().unwrap();
+            let state = states.get(&shard_name).unwrap();
+            assert_eq!(state.scan_offset, 4);
+            let pending: Vec<u64> = state.pending.iter().map(|(_, offset)| *offset).collect();
+            assert_eq!(pending, vec![1]);
+        }
+        // the message with a lifetime expires once it is over
+        assert_eq!(
+            manager.expire_shard(&shard_name, now + 200).await.unwrap(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn scan_max_records_test() {
+        let storage_adapter = build_memory_storage_driver();
+        let namespace = "expire".to_string();
+        let shard_name = "topic-1".to_string();
+        let records = (0..MESSAGE_EXPIRE_SCAN_MAX_RECORDS + 5)
+            .map(|_| build_record(0))
+            .collect();
+        storage_adapter
+            .batch_write(namespace.clone(), shard_name.clone(), records)
+            .await
+            .unwrap();
+
+        let manager = MessageExpireManager::new(namespace, storage_adapter, mqtt_record_expire_at);
+        let scan_offset = |manager: &MessageExpireManager| {
+            manager.shard_states.lock().unwrap()[&shard_name].scan_offset
+        };
+
+        manager
+            .expire_shard(&shard_name, now_second())
+            .await
+            .unwrap();
+        assert_eq!(scan_offset(&manager), MESSAGE_EXPIRE_SCAN_MAX_RECORDS);
+        manager
+            .expire_shard(&shard_name, now_second())
+            .await
+            .unwrap();
+        assert_eq!(scan_offset(&manager), MESSAGE_EXPIRE_SCAN_MAX_RECORDS + 5);
+    }
+
+    #[tokio::test]
+    async fn retain_shards_test() {
+        let manager = MessageExpireManager::new(
+            "expire".to_string(),
+            build_memory_storage_driver(),
+            mqtt_record_expire_at,
+        );
+        manager.expire_shard("topic-1", now_second()).await.unwrap();
+        manager.expire_shard("topic-2", now_second()).await.unwrap();
+
+        manager.retain_shards(&["topic-2".to_string()].into_iter().collect());
+        let states = manager.shard_states.lock().unwrap();
+        assert!(!states.contains_key("topic-1"));
+        assert!(states.contains_key("topic-2"));
+    }
+}
@@ -144,6 +144,28 @@ async fn start_stop_connector_thread(
             continue;
         }
 
+        if let Some(broker_id) = connector.broker_id {
+            if let Some(node) = cache_manager.get_broker_node(&connector.cluster_name, broker_id) {
+                if node.is_draining() {
+                    info!(
+                        "Broker {} is draining, Connector {} is rescheduled to another Broker.",
+                        broker_id, connector.connector_name
+                    );
+
+                    update_connector_status_to_idle(
+                        raft_machine_apply,
+                        call_manager,
+                        client_pool,
+                        cache_manager,
+                        &connector.cluster_name,
+                        &connector.connector_name,
+                    )
+                    .await?;
+                    continue;
+                }
+            }
+        }
+
         if connector.status == MQTTStatus::Running {
             continue;
         }
@@ -172,38 +194,95 @@ async fn calc_connector_broker(
     cache_manager: &Arc<CacheManager>,
     cluster_name: &str,
 ) -> Result<u64, MetaServiceError> {
-    let mut connector_broker_id_nums = HashMap::new();
+    let mut connector_broker_id_nums: HashMap<u64, u64> = HashMap::new();
     for connector in cache_manager.get_all_connector() {
+        if connector.cluster_name != cluster_name {
+            continue;
+        }
         if let Some(broker_id) = connector.broker_id {
-            if let Some(num) = connector_broker_id_nums.get(&broker_id) {
-                connector_broker_id_nums.insert(broker_id, *num + 1);
-            } else {
-                connector_broker_id_nums.insert(broker_id, 1);
-            }
+            *connector_broker_id_nums.entry(broker_id).or_insert(0) += 1;
         }
     }
 
-    let mut all_broker_id_nums = HashMap::new();
-    for broker_id in cache_manager.get_broker_node_id_by_cluster(cluster_name) {
-        if let Some(num) = connector_broker_id_nums.get(&broker_id) {
-            all_broker_id_nums.insert(broker_id, *num);
-        } else {
-            all_broker_id_nums.insert(broker_id, 0);
-        }
-    }
+    // Pick the available broker running the fewest connectors.
+    cache_manager
+        .get_available_broker_node_id_by_cluster(cluster_name)
+        .into_iter()
+        .min_by_key(|broker_id| {
+            (
+                connector_broker_id_nums
+                    .get(broker_id)
+                    .copied()
+                    .unwrap_or(0),
+                *broker_id,
+            )
+        })
+        .ok_or(MetaServiceError::NoAvailableBrokerNode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::calc_connector_broker;
+    use crate::core::cache::CacheManager;
+    use broker_core::rocksdb::column_family_list;
+    use common_base::tools::unique_id;
+    use common_base::utils::file_utils::test_temp_dir;
+    use common_config::broker::{default_broker_config, init_broker_conf_by_config};
+    use metadata_struct::mqtt::bridge::connector::MQTTConnector;
+    use metadata_struct::mqtt::node_extend::{MqttNodeExtend, NodeExtend};
+    use metadata_struct::placement::node::BrokerNode;
+    use rocksdb_engine::RocksDBEngine;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn calc_connector_broker_test() {
+        let config = default_broker_config();
+        init_broker_conf_by_config(config.clone());
+        let cluster_name = unique_id();
+        let rocksdb_engine_handler = Arc::new(RocksDBEngine::new(
+            &test_temp_dir(),
+            config.rocksdb.max_open_files,
+            column_family_list(),
+        ));
+        let cache_manager = Arc::new(CacheManager::new(rocksdb_engine_handler));
+
+        assert!(calc_connector_broker(&cache_manager, &cluster_name)
+            .await
+            .is_err());
 
-    let mut broker_id_num = 0;
-    let mut broker_id = -1;
-    for (id, num) in all_broker_id_nums {
-        if broker_id_num > num {
-            broker_id_num = num;
-            broker_id = id as i64
+        for node_id in 1..=3 {
+            let extend = NodeExtend {
+                mqtt: MqttNodeExtend {
+                    draining: node_id == 1,
+                    ..Default::default()
+                },
+            };
+            cache_manager.add_broker_node(BrokerNode {
+                cluster_name: cluster_name.clone(),
+                node_id,
+                extend: extend.encode(),
+                ..Default::default()
+            });
         }
-    }
 
-    if broker_id == -1 {
-        return Err(MetaServiceError::NoAvailableBrokerNode);
-    }
+        // broker 1 is draining and is never picked
+        let broker_id = calc_connector_broker(&cache_manager, &cluster_name)
+            .await
+            .unwrap();
+        assert_eq!(broker_id, 2);
 
-    Ok(broker_id as u64)
+        cache_manager.add_connector(
+            &cluster_name,
+            &MQTTConnector {
+                cluster_name: cluster_name.clone(),
+                connector_name: "c1".to_string(),
+                broker_id: Some(2),
+                ..Default::default()
+            },
+        );
+        let broker_id = calc_connector_broker(&cache_manager, &cluster_name)
+            .await
+            .unwrap();
+        assert_eq!(broker_id, 3);
+    }
 }
@@ -173,6 +173,18 @@ pub fn get_broker_node_id_by_cluster(&self, cluster_name: &str) -> Vec<u64> {
         Vec::new()
     }
 
+    // Brokers that may take new work, draining brokers are left out.
+    pub fn get_available_broker_node_id_by_cluster(&self, cluster_name: &str) -> Vec<u64> {
+        if let Some(data) = self.node_list.get(cluster_name) {
+            return data
+                .iter()
+                .filter(|row| !row.is_draining())
+                .map(|row| row.node_id)
+                .collect();
+        }
+        Vec::new()
+    }
+
     pub fn get_broker_node_by_cluster(&self, cluster_name: &str) -> Vec<BrokerNode> {
         if let Some(data) = self.node_list.get(cluster_name) {
             return data.iter().map(|row| row.clone()).collect();
@@ -0,0 +1,270 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::error::MetaServiceError;
+use crate::storage::placement::consumer_group::{ConsumerGroupMember, ConsumerGroupState};
+
+/// Heartbeat of a consumer group member as written to the raft log. The
+/// heartbeat time is taken from the clock of the leader that received the
+/// request, so liveness never compares the clocks of two hosts.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ConsumerGroupHeartbeatData {
+    pub cluster_name: String,
+    pub group_name: String,
+    pub member_id: String,
+    pub namespace: String,
+    pub shards: Vec<String>,
+    pub session_timeout_ms: u64,
+    pub heartbeat_time: u128,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConsumerGroupAssignment {
+    pub generation: u64,
+    pub shards: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ConsumerGroupCommitResult {
+    pub error: Option<String>,
+}
+
+/// Records the heartbeat of a member. Members whose session expired are removed,
+/// and when the members or the shards of the group change the generation is
+/// increased and the shards are assigned again.
+pub fn apply_heartbeat(
+    state: Option<ConsumerGroupState>,
+    data: &ConsumerGroupHeartbeatData,
+) -> ConsumerGroupState {
+    let mut state = state.unwrap_or_else(|| ConsumerGroupState {
+        cluster_name: data.cluster_name.clone(),
+        group_name: data.group_name.clone(),
+        ..Default::default()
+    });
+    let mut changed = false;
+
+    let mut shards = data.shards.clone();
+    shards.sort();
+    shards.dedup();
+    if state.namespace != data.namespace || state.shards != shards {
+        state.namespace = data.namespace.clone();
+        state.shards = shards;
+        changed = true;
+    }
+
+    let member_num = state.members.len();
+    state.members.retain(|member| {
+        member.member_id == data.member_id
+            || data.heartbeat_time.saturating_sub(member.heartbeat_time)
+                <= member.session_timeout_ms as u128
+    });
+    if state.members.len() != member_num {
+        changed = true;
+    }
+
+    if let Some(member) = state
+        .members
+        .iter_mut()
+        .find(|member| member.member_id == data.member_id)
+    {
+        member.heartbeat_time = data.heartbeat_time;
+        member.session_timeout_ms = data.session_timeout_ms;
+    } else {
+        state.members.push(ConsumerGroupMember {
+            member_id: data.member_id.clone(),
+            heartbeat_time: data.heartbeat_time,
+            session_timeout_ms: data.session_timeout_ms,
+        });
+        changed = true;
+    }
+
+    if changed {
+        rebalance(&mut state);
+    }
+    state
+}
+
+/// Removes a member from the group, returns false if it was not a member.
+pub fn apply_leave(state: &mut ConsumerGroupState, member_id: &str) -> bool {
+    let member_num = state.members.len();
+    state.members.retain(|member| member.member_id != member_id);
+    if state.members.len() == member_num {
+        return false;
+    }
+    rebalance(state);
+    true
+}
+
+/// A commit is accepted only from a member of the current generation and only
+/// for the shards assigned to it, so a member that has not yet seen a rebalance
+/// cannot overwrite the offsets committed by the new owner of a shard.
+pub fn check_commit(
+    state: Option<&ConsumerGroupState>,
+    group_name: &str,
+    member_id: &str,
+    generation: u64,
+    namespace: &str,
+    shards: &[String],
+) -> Result<(), MetaServiceError> {
+    let Some(state) = state else {
+        return Err(MetaServiceError::ConsumerGroupDoesNotExist(
+            group_name.to_string(),
+        ));
+    };
+
+    if state.generation != generation {
+        return Err(MetaServiceError::ConsumerGroupStaleGeneration(
+            group_name.to_string(),
+            generation,
+            state.generation,
+        ));
+    }
+
+    let Some(assigned) = state.assignment.get(member_id) else {
+        return Err(MetaServiceError::ConsumerGroupMemberDoesNotExist(
+            member_id.to_string(),
+            group_name.to_string(),
+        ));
+    };
+
+    for shard in shards {
+        if state.namespace != namespace || !assigned.contains(shard) {
+            return Err(MetaServiceError::ShardNotAssignedToMember(
+                format!("{namespace}/{shard}"),
+                member_id.to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn rebalance(state: &mut ConsumerGroupState) {
+    state.generation += 1;
+    state.members.sort_by(|a, b| a.member_id.cmp(&b.member_id));
+    let member_ids: Vec<String> = state
+        .members
+        .iter()
+        .map(|member| member.member_id.clone())
+        .collect();
+    state.assignment = assign_shards(&member_ids, &state.shards);
+}
+
+/// Range assignment: sorted shards are split into contiguous ranges over the
+/// sorted members, the first members get one more shard when it doesn't divide.
+pub fn assign_shards(members: &[String], shards: &[String]) -> HashMap<String, Vec<String>> {
+    let mut members = members.to_vec();
+    members.sort();
+    members.dedup();
+    let mut shards = shards.to_vec();
+    shards.sort();
+    shards.dedup();
+
+    let mut results = HashMap::new();
+    if members.is_empty() {
+        return results;
+    }
+
+    let per_member = shards.len() / members.len();
+    let extra = shards.len() % members.len();
+    let mut start = 0;
+    for (i, member) in members.iter().enumerate() {
+        let len = per_member + if i < extra { 1 } else { 0 };
+        results.insert(member.clone(), shards[start..start + len].to_vec());
+        start += len;
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn heartbeat(member_id: &str, heartbeat_time: u128) -> ConsumerGroupHeartbeatData {
+        ConsumerGroupHeartbeatData {
+            cluster_name: "c1".to_string(),
+            group_name: "g1".to_string(),
+            member_id: member_id.to_string(),
+            namespace: "ns".to_string(),
+            shards: (0..5).map(|i| format!("s{i}")).collect(),
+            session_timeout_ms: 1000,
+            heartbeat_time,
+        }
+    }
+
+    #[test]
+    fn assign_shards_test() {
+        let shards: Vec<String> = (0..5).map(|i| format!("s{i}")).collect();
+        let members = vec!["m2".to_string(), "m1".to_string()];
+
+        let assignment = assign_shards(&members, &shards);
+        assert_eq!(assignment.get("m1").unwrap(), &vec!["s0", "s1", "s2"]);
+        assert_eq!(assignment.get("m2").unwrap(), &vec!["s3", "s4"]);
+
+        let members: Vec<String> = (0..7).map(|i| format!("m{i}")).collect();
+        let assignment = assign_shards(&members, &shards);
+        let total: usize = assignment.values().map(|s| s.len()).sum();
+        assert_eq!(total, 5);
+        assert!(assignment.get("m6").unwrap().is_empty());
+
+        assert!(assign_shards(&[], &shards).is_empty());
+    }
+
+    #[test]
+    fn heartbeat_rebalance_test() {
+        let state = apply_heartbeat(None, &heartbeat("m1", 0));
+        assert_eq!(state.generation, 1);
+        assert_eq!(state.assignment.get("m1").unwrap().len(), 5);
+
+        // a heartbeat of a known member doesn't change the generation
+        let state = apply_heartbeat(Some(state), &heartbeat("m1", 500));
+        assert_eq!(state.generation, 1);
+
+        let state = apply_heartbeat(Some(state), &heartbeat("m2", 600));
+        assert_eq!(state.generation, 2);
+        assert_eq!(state.assignment.get("m1").unwrap().len(), 3);
+        assert_eq!(state.assignment.get("m2").unwrap().len(), 2);
+
+        // m1 has not sent a heartbeat for longer than its session timeout
+        let state = apply_heartbeat(Some(state), &heartbeat("m2", 1600));
+        assert_eq!(state.generation, 3);
+        assert_eq!(state.members.len(), 1);
+        assert!(!state.assignment.contains_key("m1"));
+
+        let mut state = apply_heartbeat(Some(state), &heartbeat("m1", 1700));
+        assert_eq!(state.generation, 4);
+        assert!(apply_leave(&mut state, "m1"));
+        assert_eq!(state.generation, 5);
+        assert!(!apply_leave(&mut state, "m1"));
+        assert_eq!(state.generation, 5);
+    }
+
+    #[test]
+    fn check_commit_test() {
+        let state = apply_heartbeat(None, &heartbeat("m1", 0));
+        let state = apply_heartbeat(Some(state), &heartbeat("m2", 0));
+        let m1_shards = state.assignment.get("m1").unwrap().clone();
+        let m2_shards = state.assignment.get("m2").unwrap().clone();
+
+        assert!(check_commit(Some(&state), "g1", "m1", 2, "ns", &m1_shards).is_ok());
+        assert!(check_commit(Some(&state), "g1", "m1", 1, "ns", &m1_shards).is_err());
+        assert!(check_commit(Some(&state), "g1", "m1", 2, "ns", &m2_shards).is_err());
+        assert!(check_commit(Some(&state), "g1", "m1", 2, "other", &m1_shards).is_err());
+        assert!(check_commit(Some(&state), "g1", "m3", 2, "ns", &m1_shards).is_err());
+        assert!(check_commit(None, "g1", "m1", 2, "ns", &m1_shards).is_err());
+    }
+}
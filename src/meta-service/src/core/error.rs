@@ -155,4 +155,19 @@ pub enum MetaServiceError {
 
     #[error("Schema [{0}] already exist")]
     SchemaAlreadyExist(String),
+
+    #[error("Consumer group {0} does not exist")]
+    ConsumerGroupDoesNotExist(String),
+
+    #[error("Member {0} is not in consumer group {1}")]
+    ConsumerGroupMemberDoesNotExist(String, String),
+
+    #[error("Consumer group {0} has moved on from generation {1} to {2}")]
+    ConsumerGroupStaleGeneration(String, u64, u64),
+
+    #[error("Shard {0} is not assigned to member {1}")]
+    ShardNotAssignedToMember(String, String),
+
+    #[error("Offset commit was rejected: {0}")]
+    ConsumerGroupCommitRejected(String),
 }
@@ -16,6 +16,7 @@
 pub mod cache_journal;
 pub mod cache_mqtt;
 pub mod cluster;
+pub mod consumer_group;
 pub mod controller;
 pub mod error;
 pub mod heartbeat;
@@ -16,6 +16,9 @@
 use metadata_struct::placement::node::BrokerNode;
 use metadata_struct::schema::{SchemaData, SchemaResourceBind};
 use prost::Message as _;
+use protocol::meta::meta_service_consumer_group::{
+    CommitConsumerGroupOffsetRequest, LeaveConsumerGroupRequest,
+};
 use protocol::meta::meta_service_inner::{
     BindSchemaRequest, CreateSchemaRequest, DeleteIdempotentDataRequest,
     DeleteResourceConfigRequest, DeleteSchemaRequest, SaveOffsetDataRequest,
@@ -25,9 +28,14 @@
 use std::sync::Arc;
 
 use crate::core::cache::CacheManager;
+use crate::core::consumer_group::{
+    apply_heartbeat, apply_leave, check_commit, ConsumerGroupAssignment, ConsumerGroupCommitResult,
+    ConsumerGroupHeartbeatData,
+};
 use crate::core::error::MetaServiceError;
 use crate::storage::placement::cluster::ClusterStorage;
 use crate::storage::placement::config::ResourceConfigStorage;
+use crate::storage::placement::consumer_group::ConsumerGroupStorage;
 use crate::storage::placement::idempotent::IdempotentStorage;
 use crate::storage::placement::node::NodeStorage;
 use crate::storage::placement::offset::OffsetStorage;
@@ -162,6 +170,70 @@ pub fn delete_schema_bind(&self, value: Vec<u8>) -> Result<(), MetaServiceError>
     pub fn delete_offset_data(&self, _: Vec<u8>) -> Result<(), MetaServiceError> {
         Ok(())
     }
+
+    // ConsumerGroup
+    pub fn consumer_group_heartbeat(&self, value: Vec<u8>) -> Result<Vec<u8>, MetaServiceError> {
+        let data = serde_json::from_slice::<ConsumerGroupHeartbeatData>(&value)?;
+        let storage = ConsumerGroupStorage::new(self.rocksdb_engine_handler.clone());
+        let state = apply_heartbeat(storage.get(&data.cluster_name, &data.group_name)?, &data);
+        storage.save(&state)?;
+
+        let assignment = ConsumerGroupAssignment {
+            generation: state.generation,
+            shards: state
+                .assignment
+                .get(&data.member_id)
+                .cloned()
+                .unwrap_or_default(),
+        };
+        Ok(serde_json::to_vec(&assignment)?)
+    }
+
+    pub fn consumer_group_leave(&self, value: Vec<u8>) -> Result<(), MetaServiceError> {
+        let req = LeaveConsumerGroupRequest::decode(value.as_ref())?;
+        let storage = ConsumerGroupStorage::new(self.rocksdb_engine_handler.clone());
+        if let Some(mut state) = storage.get(&req.cluster_name, &req.group_name)? {
+            if apply_leave(&mut state, &req.member_id) {
+                storage.save(&state)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn consumer_group_commit_offset(
+        &self,
+        value: Vec<u8>,
+    ) -> Result<Vec<u8>, MetaServiceError> {
+        let req = CommitConsumerGroupOffsetRequest::decode(value.as_ref())?;
+        let storage = ConsumerGroupStorage::new(self.rocksdb_engine_handler.clone());
+        let state = storage.get(&req.cluster_name, &req.group_name)?;
+
+        let shards: Vec<String> = req.offsets.keys().cloned().collect();
+        let error = match check_commit(
+            state.as_ref(),
+            &req.group_name,
+            &req.member_id,
+            req.generation,
+            &req.namespace,
+            &shards,
+        ) {
+            Ok(()) => {
+                let offset_storage = OffsetStorage::new(self.rocksdb_engine_handler.clone());
+                for (shard_name, offset) in req.offsets.iter() {
+                    offset_storage.save(
+                        &req.cluster_name,
+                        &req.group_name,
+                        &req.namespace,
+                        shard_name,
+                        *offset,
+                    )?;
+                }
+                None
+            }
+            Err(e) => Some(e.to_string()),
+        };
+        Ok(serde_json::to_vec(&ConsumerGroupCommitResult { error })?)
+    }
 }
 
 #[cfg(test)]
@@ -57,6 +57,9 @@ pub enum StorageDataType {
     IdempotentDataDelete,
     OffsetSet,
     OffsetDelete,
+    ConsumerGroupHeartbeat,
+    ConsumerGroupLeave,
+    ConsumerGroupCommitOffset,
 
     // Journal
     JournalSetShard,
@@ -110,6 +113,9 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             StorageDataType::IdempotentDataDelete => write!(f, "IdempotentDataDelete"),
             StorageDataType::OffsetSet => write!(f, "OffsetSet"),
             StorageDataType::OffsetDelete => write!(f, "OffsetDelete"),
+            StorageDataType::ConsumerGroupHeartbeat => write!(f, "ConsumerGroupHeartbeat"),
+            StorageDataType::ConsumerGroupLeave => write!(f, "ConsumerGroupLeave"),
+            StorageDataType::ConsumerGroupCommitOffset => write!(f, "ConsumerGroupCommitOffset"),
 
             StorageDataType::JournalSetShard => write!(f, "JournalSetShard"),
             StorageDataType::JournalDeleteShard => write!(f, "JournalDeleteShard"),
@@ -124,6 +124,19 @@ pub async fn route(
                 self.route_cluster.delete_offset_data(storage_data.value)?;
                 Ok(None)
             }
+            StorageDataType::ConsumerGroupHeartbeat => Ok(Some(
+                self.route_cluster
+                    .consumer_group_heartbeat(storage_data.value)?,
+            )),
+            StorageDataType::ConsumerGroupLeave => {
+                self.route_cluster
+                    .consumer_group_leave(storage_data.value)?;
+                Ok(None)
+            }
+            StorageDataType::ConsumerGroupCommitOffset => Ok(Some(
+                self.route_cluster
+                    .consumer_group_commit_offset(storage_data.value)?,
+            )),
             StorageDataType::SchemaSet => {
                 self.route_cluster.set_schema(storage_data.value)?;
                 Ok(None)
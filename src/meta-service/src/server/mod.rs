@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod service_consumer_group;
 pub mod service_inner;
 pub mod service_journal;
 pub mod service_kv;
@@ -0,0 +1,77 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use crate::server::services::consumer_group::{
+    commit_consumer_group_offset_by_req, consumer_group_heartbeat_by_req,
+    leave_consumer_group_by_req,
+};
+use protocol::meta::meta_service_consumer_group::consumer_group_service_server::ConsumerGroupService;
+use protocol::meta::meta_service_consumer_group::{
+    CommitConsumerGroupOffsetReply, CommitConsumerGroupOffsetRequest, ConsumerGroupHeartbeatReply,
+    ConsumerGroupHeartbeatRequest, LeaveConsumerGroupReply, LeaveConsumerGroupRequest,
+};
+use tonic::{Request, Response, Status};
+
+use crate::raft::route::apply::StorageDriver;
+
+pub struct GrpcConsumerGroupService {
+    raft_machine_apply: Arc<StorageDriver>,
+}
+
+impl GrpcConsumerGroupService {
+    pub fn new(raft_machine_apply: Arc<StorageDriver>) -> Self {
+        GrpcConsumerGroupService { raft_machine_apply }
+    }
+}
+
+#[tonic::async_trait]
+impl ConsumerGroupService for GrpcConsumerGroupService {
+    async fn heartbeat(
+        &self,
+        request: Request<ConsumerGroupHeartbeatRequest>,
+    ) -> Result<Response<ConsumerGroupHeartbeatReply>, Status> {
+        let req = request.into_inner();
+
+        consumer_group_heartbeat_by_req(&self.raft_machine_apply, &req)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))
+            .map(Response::new)
+    }
+
+    async fn leave_group(
+        &self,
+        request: Request<LeaveConsumerGroupRequest>,
+    ) -> Result<Response<LeaveConsumerGroupReply>, Status> {
+        let req = request.into_inner();
+
+        leave_consumer_group_by_req(&self.raft_machine_apply, &req)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))
+            .map(Response::new)
+    }
+
+    async fn commit_offset(
+        &self,
+        request: Request<CommitConsumerGroupOffsetRequest>,
+    ) -> Result<Response<CommitConsumerGroupOffsetReply>, Status> {
+        let req = request.into_inner();
+
+        commit_consumer_group_offset_by_req(&self.raft_machine_apply, &req)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))
+            .map(Response::new)
+    }
+}
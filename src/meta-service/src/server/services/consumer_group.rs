@@ -0,0 +1,111 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::core::consumer_group::{
+    ConsumerGroupAssignment, ConsumerGroupCommitResult, ConsumerGroupHeartbeatData,
+};
+use crate::core::error::MetaServiceError;
+use crate::raft::route::apply::StorageDriver;
+use crate::raft::route::data::{StorageData, StorageDataType};
+use common_base::tools::now_mills;
+use prost::Message;
+use protocol::meta::meta_service_consumer_group::{
+    CommitConsumerGroupOffsetReply, CommitConsumerGroupOffsetRequest, ConsumerGroupHeartbeatReply,
+    ConsumerGroupHeartbeatRequest, LeaveConsumerGroupReply, LeaveConsumerGroupRequest,
+};
+use std::sync::Arc;
+
+pub async fn consumer_group_heartbeat_by_req(
+    raft_machine_apply: &Arc<StorageDriver>,
+    req: &ConsumerGroupHeartbeatRequest,
+) -> Result<ConsumerGroupHeartbeatReply, MetaServiceError> {
+    if req.group_name.is_empty() || req.member_id.is_empty() {
+        return Err(MetaServiceError::RequestParamsNotEmpty(
+            "group_name or member_id".to_string(),
+        ));
+    }
+
+    let data = ConsumerGroupHeartbeatData {
+        cluster_name: req.cluster_name.clone(),
+        group_name: req.group_name.clone(),
+        member_id: req.member_id.clone(),
+        namespace: req.namespace.clone(),
+        shards: req.shards.clone(),
+        session_timeout_ms: req.session_timeout_ms,
+        heartbeat_time: now_mills(),
+    };
+    let data = StorageData::new(
+        StorageDataType::ConsumerGroupHeartbeat,
+        serde_json::to_vec(&data)?,
+    );
+    let value = client_write_value(raft_machine_apply, data).await?;
+    let assignment = serde_json::from_slice::<ConsumerGroupAssignment>(&value)?;
+    Ok(ConsumerGroupHeartbeatReply {
+        generation: assignment.generation,
+        shards: assignment.shards,
+    })
+}
+
+pub async fn leave_consumer_group_by_req(
+    raft_machine_apply: &Arc<StorageDriver>,
+    req: &LeaveConsumerGroupRequest,
+) -> Result<LeaveConsumerGroupReply, MetaServiceError> {
+    if req.group_name.is_empty() || req.member_id.is_empty() {
+        return Err(MetaServiceError::RequestParamsNotEmpty(
+            "group_name or member_id".to_string(),
+        ));
+    }
+
+    let data = StorageData::new(
+        StorageDataType::ConsumerGroupLeave,
+        LeaveConsumerGroupRequest::encode_to_vec(req),
+    );
+    raft_machine_apply.client_write(data).await?;
+    Ok(LeaveConsumerGroupReply::default())
+}
+
+pub async fn commit_consumer_group_offset_by_req(
+    raft_machine_apply: &Arc<StorageDriver>,
+    req: &CommitConsumerGroupOffsetRequest,
+) -> Result<CommitConsumerGroupOffsetReply, MetaServiceError> {
+    if req.group_name.is_empty() || req.member_id.is_empty() {
+        return Err(MetaServiceError::RequestParamsNotEmpty(
+            "group_name or member_id".to_string(),
+        ));
+    }
+
+    let data = StorageData::new(
+        StorageDataType::ConsumerGroupCommitOffset,
+        CommitConsumerGroupOffsetRequest::encode_to_vec(req),
+    );
+    let value = client_write_value(raft_machine_apply, data).await?;
+    let result = serde_json::from_slice::<ConsumerGroupCommitResult>(&value)?;
+    if let Some(e) = result.error {
+        return Err(MetaServiceError::ConsumerGroupCommitRejected(e));
+    }
+    Ok(CommitConsumerGroupOffsetReply::default())
+}
+
+// The value returned by the state machine when it applied the log entry.
+async fn client_write_value(
+    raft_machine_apply: &Arc<StorageDriver>,
+    data: StorageData,
+) -> Result<Vec<u8>, MetaServiceError> {
+    if let Some(resp) = raft_machine_apply.client_write(data).await? {
+        if let Some(value) = resp.data.value {
+            return Ok(value);
+        }
+    }
+    Err(MetaServiceError::ExecutionResultIsEmpty)
+}
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod consumer_group;
 pub mod inner;
 pub mod journal;
 pub mod kv;
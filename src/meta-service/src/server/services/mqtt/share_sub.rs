@@ -45,15 +45,22 @@ pub fn get_leader_node(
     ) -> Result<u64, CommonError> {
         let mut broker_ids = self
             .cache_manager
-            .get_broker_node_id_by_cluster(cluster_name);
+            .get_available_broker_node_id_by_cluster(cluster_name);
 
         broker_ids.sort();
 
-        let node_sub_info = self.read_node_sub_info(cluster_name)?;
+        let mut node_sub_info = self.read_node_sub_info(cluster_name)?;
 
         for (broker_id, group_list) in node_sub_info.clone() {
-            if group_list.contains(group_name) && broker_ids.contains(&broker_id) {
-                return Ok(broker_id);
+            if group_list.contains(group_name) {
+                if broker_ids.contains(&broker_id) {
+                    return Ok(broker_id);
+                }
+
+                // The leader is draining or gone, move the group to another broker.
+                self.remove_group_by_node(cluster_name, group_name)?;
+                node_sub_info = self.read_node_sub_info(cluster_name)?;
+                break;
             }
         }
 
@@ -86,7 +93,6 @@ pub fn get_leader_node(
         Ok(target_broker_id)
     }
 
-    #[allow(dead_code)]
     pub fn remove_group_by_node(
         &self,
         cluster_name: &str,
@@ -226,6 +232,7 @@ mod tests {
     use common_base::tools::{now_second, unique_id};
     use common_base::utils::file_utils::test_temp_dir;
     use common_config::broker::{default_broker_config, init_broker_conf_by_config};
+    use metadata_struct::mqtt::node_extend::{MqttNodeExtend, NodeExtend};
     use metadata_struct::placement::node::BrokerNode;
     use rocksdb_engine::RocksDBEngine;
     use std::sync::Arc;
@@ -366,4 +373,53 @@ fn get_leader_node_test() {
             .unwrap();
         assert_eq!(node, 1);
     }
+
+    #[test]
+    fn get_leader_node_draining_test() {
+        let config = default_broker_config();
+        init_broker_conf_by_config(config.clone());
+        let cluster_name = unique_id();
+        let rocksdb_engine_handler = Arc::new(RocksDBEngine::new(
+            &test_temp_dir(),
+            config.rocksdb.max_open_files,
+            column_family_list(),
+        ));
+        let cluster_cache = Arc::new(CacheManager::new(rocksdb_engine_handler.clone()));
+        for node_id in 1..=2 {
+            cluster_cache.add_broker_node(BrokerNode {
+                cluster_name: cluster_name.clone(),
+                node_id,
+                ..Default::default()
+            });
+        }
+
+        let share_sub = ShareSubLeader::new(cluster_cache.clone(), rocksdb_engine_handler);
+        let group_name = "group1".to_string();
+        let node = share_sub
+            .get_leader_node(&cluster_name, &group_name)
+            .unwrap();
+        assert_eq!(node, 1);
+
+        let extend = NodeExtend {
+            mqtt: MqttNodeExtend {
+                draining: true,
+                ..Default::default()
+            },
+        };
+        cluster_cache.add_broker_node(BrokerNode {
+            cluster_name: cluster_name.clone(),
+            node_id: 1,
+            extend: extend.encode(),
+            ..Default::default()
+        });
+
+        let node = share_sub
+            .get_leader_node(&cluster_name, &group_name)
+            .unwrap();
+        assert_eq!(node, 2);
+
+        let result = share_sub.read_node_sub_info(&cluster_name).unwrap();
+        assert!(!result.get(&1).unwrap().contains(&group_name));
+        assert!(result.get(&2).unwrap().contains(&group_name));
+    }
 }
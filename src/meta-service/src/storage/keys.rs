@@ -51,6 +51,10 @@ pub fn key_offset_by_group(cluster_name: &str, group: &str) -> String {
     prefix_key(format!("/offset/{cluster_name}/{group}"))
 }
 
+pub fn key_consumer_group(cluster_name: &str, group: &str) -> String {
+    prefix_key(format!("/consumer_group/{cluster_name}/{group}"))
+}
+
 /** ===========Journal========== */
 pub fn key_shard(cluster_name: &str, namespace: &str, shard_name: &str) -> String {
     prefix_key(format!(
@@ -0,0 +1,117 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use common_base::error::common::CommonError;
+use serde::{Deserialize, Serialize};
+
+use crate::storage::engine_meta::{
+    engine_delete_by_cluster, engine_get_by_cluster, engine_save_by_meta,
+};
+use crate::storage::keys::key_consumer_group;
+use rocksdb_engine::RocksDBEngine;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConsumerGroupMember {
+    pub member_id: String,
+    // Time of the last heartbeat, taken from the clock of the raft leader
+    pub heartbeat_time: u128,
+    pub session_timeout_ms: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConsumerGroupState {
+    pub cluster_name: String,
+    pub group_name: String,
+    pub namespace: String,
+    pub shards: Vec<String>,
+    // Increased every time the members or the shards of the group change
+    pub generation: u64,
+    pub members: Vec<ConsumerGroupMember>,
+    // member id => assigned shards
+    pub assignment: HashMap<String, Vec<String>>,
+}
+
+pub struct ConsumerGroupStorage {
+    rocksdb_engine_handler: Arc<RocksDBEngine>,
+}
+
+impl ConsumerGroupStorage {
+    pub fn new(rocksdb_engine_handler: Arc<RocksDBEngine>) -> Self {
+        ConsumerGroupStorage {
+            rocksdb_engine_handler,
+        }
+    }
+
+    pub fn save(&self, state: &ConsumerGroupState) -> Result<(), CommonError> {
+        let key = key_consumer_group(&state.cluster_name, &state.group_name);
+        engine_save_by_meta(self.rocksdb_engine_handler.clone(), key, state)
+    }
+
+    pub fn get(
+        &self,
+        cluster_name: &str,
+        group_name: &str,
+    ) -> Result<Option<ConsumerGroupState>, CommonError> {
+        let key = key_consumer_group(cluster_name, group_name);
+        if let Some(data) = engine_get_by_cluster(self.rocksdb_engine_handler.clone(), key)? {
+            return Ok(Some(serde_json::from_str::<ConsumerGroupState>(
+                &data.data,
+            )?));
+        }
+        Ok(None)
+    }
+
+    #[allow(dead_code)]
+    pub fn delete(&self, cluster_name: &str, group_name: &str) -> Result<(), CommonError> {
+        let key = key_consumer_group(cluster_name, group_name);
+        engine_delete_by_cluster(self.rocksdb_engine_handler.clone(), key)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::storage::placement::consumer_group::{ConsumerGroupState, ConsumerGroupStorage};
+    use broker_core::rocksdb::column_family_list;
+    use rocksdb_engine::RocksDBEngine;
+    use std::sync::Arc;
+    use tempfile::tempdir;
+
+    #[test]
+    fn consumer_group_storage_test() {
+        let rocksdb_engine = Arc::new(RocksDBEngine::new(
+            tempdir().unwrap().path().to_str().unwrap(),
+            100,
+            column_family_list(),
+        ));
+        let storage = ConsumerGroupStorage::new(rocksdb_engine);
+
+        let state = ConsumerGroupState {
+            cluster_name: "cluster1".to_string(),
+            group_name: "group1".to_string(),
+            namespace: "namespace1".to_string(),
+            shards: vec!["shard1".to_string()],
+            generation: 3,
+            ..Default::default()
+        };
+        storage.save(&state).unwrap();
+        assert_eq!(storage.get("cluster1", "group1").unwrap(), Some(state));
+        assert!(storage.get("cluster1", "group2").unwrap().is_none());
+
+        storage.delete("cluster1", "group1").unwrap();
+        assert!(storage.get("cluster1", "group1").unwrap().is_none());
+    }
+}
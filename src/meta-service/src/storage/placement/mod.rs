@@ -14,6 +14,7 @@
 
 pub mod cluster;
 pub mod config;
+pub mod consumer_group;
 pub mod idempotent;
 pub mod kv;
 pub mod node;
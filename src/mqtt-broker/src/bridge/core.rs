@@ -13,28 +13,35 @@
 // limitations under the License.
 
 use crate::common::types::ResultMqttBrokerError;
-use axum::async_trait;
+use crate::handler::cache::MQTTCacheManager;
+use crate::handler::error::MqttBrokerError;
 
 use common_base::{error::ResultCommonError, tools::loop_select};
 use common_config::broker::broker_config;
+use grpc_clients::pool::ClientPool;
 use metadata_struct::mqtt::bridge::{
-    config_local_file::LocalFileConnectorConfig, config_postgres::PostgresConnectorConfig,
-    config_pulsar::PulsarConnectorConfig, connector::MQTTConnector, connector_type::ConnectorType,
+    config_mqtt::{MqttBridgeConnectorConfig, MqttBridgeDirection},
+    config_webhook::WebhookConnectorConfig,
+    connector::{ConnectorDeliveryPolicy, MQTTConnector},
+    connector_type::ConnectorType,
     status::MQTTStatus,
 };
+use serde::de::DeserializeOwned;
 use std::{sync::Arc, time::Duration};
 use storage_adapter::storage::ArcStorageAdapter;
 use tokio::{sync::broadcast, time::sleep};
 use tracing::{error, info};
 
 use super::{
-    file::FileBridgePlugin, manager::ConnectorManager, postgres::PostgresBridgePlugin,
-    pulsar::PulsarBridgePlugin,
+    file::FileBridgePlugin, greptimedb::GreptimeDBBridgePlugin, kafka::KafkaBridgePlugin,
+    manager::ConnectorManager, mqtt::MqttBridgePlugin, postgres::PostgresBridgePlugin,
+    pulsar::PulsarBridgePlugin, runtime::ConnectorRuntime, webhook::WebhookBridgePlugin,
 };
 
 #[derive(Clone)]
 pub struct BridgePluginReadConfig {
     pub topic_id: String,
+    pub topic_filters: Vec<String>,
     pub record_num: u64,
 }
 
@@ -44,18 +51,21 @@ pub struct BridgePluginThread {
     pub stop_send: broadcast::Sender<bool>,
 }
 
-#[async_trait]
-pub trait BridgePlugin {
-    async fn exec(&self, config: BridgePluginReadConfig) -> ResultMqttBrokerError;
-}
-
 pub async fn start_connector_thread(
     message_storage: ArcStorageAdapter,
     connector_manager: Arc<ConnectorManager>,
+    cache_manager: Arc<MQTTCacheManager>,
+    client_pool: Arc<ClientPool>,
     stop_send: broadcast::Sender<bool>,
 ) {
     let ac_fn = async || -> ResultCommonError {
-        check_connector(&message_storage, &connector_manager).await;
+        check_connector(
+            &message_storage,
+            &connector_manager,
+            &cache_manager,
+            &client_pool,
+        )
+        .await;
         sleep(Duration::from_secs(1)).await;
         Ok(())
     };
@@ -67,6 +77,8 @@ pub async fn start_connector_thread(
 async fn check_connector(
     message_storage: &ArcStorageAdapter,
     connector_manager: &Arc<ConnectorManager>,
+    cache_manager: &Arc<MQTTCacheManager>,
+    client_pool: &Arc<ClientPool>,
 ) {
     let config = broker_config();
 
@@ -97,6 +109,8 @@ async fn check_connector(
 
         start_thread(
             connector_manager.clone(),
+            cache_manager.clone(),
+            client_pool.clone(),
             message_storage.clone(),
             raw.clone(),
             thread,
@@ -123,132 +137,139 @@ async fn check_connector(
                     raw.connector_name, e
                 );
             }
-            if let Some(mut connector) = connector_manager.get_connector(&raw.connector_name) {
-                connector.status = MQTTStatus::Idle;
-            }
+            connector_manager.update_connector_status(&raw.connector_name, MQTTStatus::Idle);
         }
     }
 }
 
 fn start_thread(
     connector_manager: Arc<ConnectorManager>,
+    cache_manager: Arc<MQTTCacheManager>,
+    client_pool: Arc<ClientPool>,
     message_storage: ArcStorageAdapter,
     connector: MQTTConnector,
     thread: BridgePluginThread,
 ) {
     tokio::spawn(async move {
-        match connector.connector_type {
-            ConnectorType::LocalFile => {
-                let local_file_config = match serde_json::from_str::<LocalFileConnectorConfig>(
-                    &connector.config,
-                ) {
-                    Ok(config) => config,
-                    Err(e) => {
-                        error!("Failed to parse LocalFileConnectorConfig file with error message :{}, configuration contents: {}", e, connector.config);
-                        return;
-                    }
-                };
-
-                let bridge = FileBridgePlugin::new(
-                    connector_manager.clone(),
-                    message_storage.clone(),
-                    connector.connector_name.clone(),
-                    local_file_config,
-                    thread.stop_send.clone(),
-                );
-
-                connector_manager.add_connector_thread(&connector.connector_name, thread);
-
-                if let Err(e) = bridge
-                    .exec(BridgePluginReadConfig {
-                        topic_id: connector.topic_id,
-                        record_num: 100,
-                    })
-                    .await
-                {
-                    connector_manager.remove_connector_thread(&connector.connector_name);
-                    error!(
-                        "Failed to start FileBridgePlugin with error message: {:?}",
-                        e
-                    );
+        let runtime = ConnectorRuntime::new(
+            connector_manager.clone(),
+            cache_manager.clone(),
+            client_pool.clone(),
+            message_storage.clone(),
+            connector.connector_name.clone(),
+            delivery_policy(&connector),
+            thread.stop_send.clone(),
+        );
+        let read_config = BridgePluginReadConfig {
+            topic_id: connector.topic_id.clone(),
+            topic_filters: connector.topic_filters.clone(),
+            record_num: 100,
+        };
+        let stop_send = thread.stop_send.clone();
+        connector_manager.add_connector_thread(&connector.connector_name, thread);
+
+        let result = match connector.connector_type {
+            ConnectorType::LocalFile => match parse_connector_config(&connector) {
+                Ok(config) => {
+                    let mut sink = FileBridgePlugin::new(config);
+                    runtime.run(&mut sink, read_config).await
                 }
-            }
-            ConnectorType::Kafka => {}
-            ConnectorType::GreptimeDB => {}
-            ConnectorType::Pulsar => {
-                let pulsar_config = match serde_json::from_str::<PulsarConnectorConfig>(
-                    &connector.config,
-                ) {
-                    Ok(config) => config,
-                    Err(e) => {
-                        error!("Failed to parse PulsarConnectorConfig file with error message :{}, configuration contents: {}", e, connector.config);
-                        return;
-                    }
-                };
-
-                let bridge = PulsarBridgePlugin::new(
-                    connector_manager.clone(),
-                    message_storage.clone(),
-                    connector.connector_name.clone(),
-                    pulsar_config,
-                    thread.stop_send.clone(),
-                );
-
-                connector_manager.add_connector_thread(&connector.connector_name, thread);
-
-                if let Err(e) = bridge
-                    .exec(BridgePluginReadConfig {
-                        topic_id: connector.topic_id,
-                        record_num: 100,
-                    })
-                    .await
-                {
-                    connector_manager.remove_connector_thread(&connector.connector_name);
-                    error!(
-                        "Failed to start PulsarBridgePlugin with error message: {:?}",
-                        e
-                    );
+                Err(e) => Err(e),
+            },
+            ConnectorType::Kafka => match parse_connector_config(&connector) {
+                Ok(config) => {
+                    let mut sink = KafkaBridgePlugin::new(config);
+                    runtime.run(&mut sink, read_config).await
                 }
-            }
-            ConnectorType::Postgres => {
-                let postgres_config = match serde_json::from_str::<PostgresConnectorConfig>(
-                    &connector.config,
-                ) {
-                    Ok(config) => config,
-                    Err(e) => {
-                        error!("Failed to parse PostgresConnectorConfig with error message: {}, configuration contents: {}", e, connector.config);
-                        return;
+                Err(e) => Err(e),
+            },
+            ConnectorType::GreptimeDB => match parse_connector_config(&connector) {
+                Ok(config) => {
+                    let mut sink = GreptimeDBBridgePlugin::new(config);
+                    runtime.run(&mut sink, read_config).await
+                }
+                Err(e) => Err(e),
+            },
+            ConnectorType::Pulsar => match parse_connector_config(&connector) {
+                Ok(config) => {
+                    let mut sink = PulsarBridgePlugin::new(config);
+                    runtime.run(&mut sink, read_config).await
+                }
+                Err(e) => Err(e),
+            },
+            ConnectorType::Postgres => match parse_connector_config(&connector) {
+                Ok(config) => {
+                    let mut sink = PostgresBridgePlugin::new(config);
+                    runtime.run(&mut sink, read_config).await
+                }
+                Err(e) => Err(e),
+            },
+            ConnectorType::Mqtt => {
+                match parse_connector_config::<MqttBridgeConnectorConfig>(&connector) {
+                    Ok(config) => {
+                        let direction = config.direction.clone();
+                        let mut bridge = MqttBridgePlugin::new(
+                            connector_manager.clone(),
+                            cache_manager.clone(),
+                            client_pool.clone(),
+                            message_storage.clone(),
+                            connector.connector_name.clone(),
+                            config,
+                            stop_send,
+                        );
+                        match direction {
+                            MqttBridgeDirection::Egress => {
+                                runtime.run(&mut bridge, read_config).await
+                            }
+                            MqttBridgeDirection::Ingress => bridge.run_ingress().await,
+                        }
                     }
-                };
-
-                let bridge = PostgresBridgePlugin::new(
-                    connector_manager.clone(),
-                    message_storage.clone(),
-                    connector.connector_name.clone(),
-                    postgres_config,
-                    thread.stop_send.clone(),
-                );
-
-                connector_manager.add_connector_thread(&connector.connector_name, thread);
-
-                if let Err(e) = bridge
-                    .exec(BridgePluginReadConfig {
-                        topic_id: connector.topic_id,
-                        record_num: 100,
-                    })
-                    .await
-                {
-                    connector_manager.remove_connector_thread(&connector.connector_name);
-                    error!(
-                        "Failed to start PostgresBridgePlugin with error message: {:?}",
-                        e
-                    );
+                    Err(e) => Err(e),
                 }
             }
+            ConnectorType::Webhook => match parse_connector_config(&connector) {
+                Ok(config) => {
+                    let mut sink = WebhookBridgePlugin::new(config);
+                    runtime.run(&mut sink, read_config).await
+                }
+                Err(e) => Err(e),
+            },
+        };
+
+        // The thread is restarted by check_connector while the connector is still assigned here
+        connector_manager.remove_connector_thread(&connector.connector_name);
+        if let Err(e) = result {
+            connector_manager.record_failure(&connector.connector_name, &e.to_string());
+            error!(
+                "Connector {} exited with error message: {}",
+                connector.connector_name, e
+            );
         }
     });
 }
 
+// Webhook connectors created before the delivery policy keep their retry settings in the config
+fn delivery_policy(connector: &MQTTConnector) -> ConnectorDeliveryPolicy {
+    let mut policy = connector.delivery_policy.clone();
+    if connector.connector_type == ConnectorType::Webhook {
+        if let Ok(config) = serde_json::from_str::<WebhookConnectorConfig>(&connector.config) {
+            config.apply_retry_settings(&mut policy);
+        }
+    }
+    policy
+}
+
+fn parse_connector_config<T: DeserializeOwned>(
+    connector: &MQTTConnector,
+) -> Result<T, MqttBrokerError> {
+    serde_json::from_str::<T>(&connector.config).map_err(|e| {
+        MqttBrokerError::CommonError(format!(
+            "Failed to parse {} connector configuration with error message: {}, configuration contents: {}",
+            connector.connector_type, e, connector.config
+        ))
+    })
+}
+
 fn stop_thread(thread: BridgePluginThread) -> ResultMqttBrokerError {
     thread.stop_send.send(true)?;
     Ok(())
@@ -258,6 +279,7 @@ fn stop_thread(thread: BridgePluginThread) -> ResultMqttBrokerError {
 mod tests {
     use super::*;
     use crate::bridge::manager::ConnectorManager;
+    use crate::common::tool::test_build_mqtt_cache_manager;
     use common_base::tools::{now_second, unique_id};
     use common_config::{broker::init_broker_conf_by_config, config::BrokerConfig};
     use storage_adapter::storage::{build_memory_storage_driver, ArcStorageAdapter, ShardInfo};
@@ -281,9 +303,11 @@ fn create_test_connector() -> MQTTConnector {
             connector_name: "test_connector".to_string(),
             connector_type: ConnectorType::LocalFile,
             topic_id: "test_topic".to_string(),
+            topic_filters: Vec::new(),
             config: "{}".to_string(),
             status: MQTTStatus::Running,
             broker_id: Some(1),
+            delivery_policy: Default::default(),
             cluster_name: "test_cluster".to_string(),
             create_time: now_second(),
             update_time: now_second(),
@@ -294,10 +318,12 @@ fn create_test_connector() -> MQTTConnector {
     fn test_bridge_plugin_read_config_creation() {
         let config = BridgePluginReadConfig {
             topic_id: "test_topic".to_string(),
+            topic_filters: vec!["factory/+/telemetry".to_string()],
             record_num: 100,
         };
 
         assert_eq!(config.topic_id, "test_topic");
+        assert_eq!(config.topic_filters.len(), 1);
         assert_eq!(config.record_num, 100);
     }
 
@@ -318,8 +344,17 @@ async fn test_start_connector_thread() {
         let (storage_adapter, connector_manager) = setup();
         let (stop_send, _) = broadcast::channel::<bool>(1);
 
+        let cache_manager = test_build_mqtt_cache_manager();
+        let client_pool = cache_manager.client_pool.clone();
         let start_handle = tokio::spawn(async move {
-            start_connector_thread(storage_adapter, connector_manager, stop_send).await;
+            start_connector_thread(
+                storage_adapter,
+                connector_manager,
+                cache_manager,
+                client_pool,
+                stop_send,
+            )
+            .await;
         });
 
         sleep(Duration::from_millis(100)).await;
@@ -347,7 +382,14 @@ async fn test_check_connector() {
             .await
             .unwrap();
 
-        check_connector(&storage_adapter, &connector_manager).await;
+        let cache_manager = test_build_mqtt_cache_manager();
+        check_connector(
+            &storage_adapter,
+            &connector_manager,
+            &cache_manager,
+            &cache_manager.client_pool,
+        )
+        .await;
 
         sleep(Duration::from_millis(100)).await;
 
@@ -12,110 +12,50 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{sync::Arc, time::Duration};
-
-use super::core::{BridgePlugin, BridgePluginReadConfig};
-use super::manager::ConnectorManager;
+use super::runtime::ConnectorSink;
 use crate::common::types::ResultMqttBrokerError;
-use crate::storage::message::MessageStorage;
 use axum::async_trait;
 use metadata_struct::{
     adapter::record::Record, mqtt::bridge::config_local_file::LocalFileConnectorConfig,
 };
-use storage_adapter::storage::ArcStorageAdapter;
 use tokio::fs::File;
+use tokio::fs::OpenOptions;
 use tokio::io::{AsyncWriteExt, BufWriter};
-use tokio::{fs::OpenOptions, select, sync::broadcast, time::sleep};
-use tracing::error;
 
 pub struct FileBridgePlugin {
-    connector_manager: Arc<ConnectorManager>,
-    message_storage: ArcStorageAdapter,
-    connector_name: String,
     config: LocalFileConnectorConfig,
-    stop_send: broadcast::Sender<bool>,
+    writer: Option<BufWriter<File>>,
 }
 
 impl FileBridgePlugin {
-    pub fn new(
-        connector_manager: Arc<ConnectorManager>,
-        message_storage: ArcStorageAdapter,
-        connector_name: String,
-        config: LocalFileConnectorConfig,
-        stop_send: broadcast::Sender<bool>,
-    ) -> Self {
+    pub fn new(config: LocalFileConnectorConfig) -> Self {
         FileBridgePlugin {
-            connector_manager,
-            message_storage,
-            connector_name,
             config,
-            stop_send,
+            writer: None,
         }
     }
-
-    pub async fn append(
-        &self,
-        records: &Vec<Record>,
-        writer: &mut BufWriter<File>,
-    ) -> ResultMqttBrokerError {
-        for record in records {
-            let data = serde_json::to_string(record)?;
-            writer.write_all(data.as_ref()).await?;
-        }
-        writer.flush().await?;
-        Ok(())
-    }
 }
 
 #[async_trait]
-impl BridgePlugin for FileBridgePlugin {
-    async fn exec(&self, config: BridgePluginReadConfig) -> ResultMqttBrokerError {
-        let message_storage = MessageStorage::new(self.message_storage.clone());
-        let group_name = self.connector_name.clone();
-        let mut recv = self.stop_send.subscribe();
+impl ConnectorSink for FileBridgePlugin {
+    async fn init_sink(&mut self) -> ResultMqttBrokerError {
         let file = OpenOptions::new()
             .append(true)
             .open(self.config.local_file_path.clone())
             .await?;
-        let mut writer = tokio::io::BufWriter::new(file);
-
-        loop {
-            let offset = message_storage.get_group_offset(&group_name).await?;
-
-            select! {
-                val = recv.recv() =>{
-                    if let Ok(flag) = val {
-                        if flag {
-                            break;
-                        }
-                    }
-                },
-
-                val = message_storage.read_topic_message(&config.topic_id, offset, config.record_num) => {
-                    match val {
-                        Ok(data) => {
-                            self.connector_manager.report_heartbeat(&self.connector_name);
-                            if data.is_empty() {
-                                sleep(Duration::from_millis(100)).await;
-                                continue;
-                            }
-
-                            if let Err(e) = self.append(&data,&mut writer).await{
-                                error!("Connector {} failed to write data to {}, error message :{}", self.connector_name,self.config.local_file_path, e);
-                                sleep(Duration::from_millis(100)).await;
-                            }
-
-                            // commit offset
-                            message_storage.commit_group_offset(&group_name, &config.topic_id, offset + data.len() as u64).await?;
-                        },
-                        Err(e) => {
-                            error!("Connector {} failed to read Topic {} data with error message :{}", self.connector_name,config.topic_id,e);
-                            sleep(Duration::from_millis(100)).await;
-                        }
-                    }
-                }
-            }
+        self.writer = Some(BufWriter::new(file));
+        Ok(())
+    }
+
+    async fn send_batch(&mut self, records: &[Record]) -> ResultMqttBrokerError {
+        let Some(writer) = self.writer.as_mut() else {
+            return Ok(());
+        };
+        for record in records {
+            let data = serde_json::to_string(record)?;
+            writer.write_all(data.as_ref()).await?;
         }
+        writer.flush().await?;
         Ok(())
     }
 }
@@ -137,10 +77,10 @@ mod tests {
     use tokio::{fs::File, io::AsyncReadExt, sync::broadcast, time::sleep};
 
     use crate::bridge::{
-        core::{BridgePlugin, BridgePluginReadConfig},
-        file::FileBridgePlugin,
-        manager::ConnectorManager,
+        core::BridgePluginReadConfig, file::FileBridgePlugin, manager::ConnectorManager,
+        runtime::ConnectorRuntime,
     };
+    use crate::common::tool::test_build_mqtt_cache_manager;
     use tempfile::tempdir;
 
     #[tokio::test]
@@ -213,22 +153,27 @@ async fn file_bridge_plugin_test() {
 
         let (stop_send, _) = broadcast::channel(1);
 
-        let file_bridge_plugin = FileBridgePlugin::new(
+        let cache_manager = test_build_mqtt_cache_manager();
+        let runtime = ConnectorRuntime::new(
             connector_manager.clone(),
+            cache_manager.clone(),
+            cache_manager.client_pool.clone(),
             storage_adapter.clone(),
             connector_name.clone(),
-            config.clone(),
+            Default::default(),
             stop_send.clone(),
         );
 
         let read_config = BridgePluginReadConfig {
             topic_id: shard_name.clone(),
+            topic_filters: Vec::new(),
             record_num: 100,
         };
 
-        let record_config_clone = read_config.clone();
+        let sink_config = config.clone();
         let handle = tokio::spawn(async move {
-            file_bridge_plugin.exec(record_config_clone).await.unwrap();
+            let mut sink = FileBridgePlugin::new(sink_config);
+            runtime.run(&mut sink, read_config).await.unwrap();
         });
 
         // wait for a while
@@ -237,6 +182,12 @@ async fn file_bridge_plugin_test() {
         stop_send.send(true).unwrap();
 
         handle.await.unwrap();
+        assert_eq!(
+            connector_manager
+                .get_connector_metrics(&connector_name)
+                .success_total,
+            1000
+        );
 
         // read the file and check the data
         let mut file = File::open(config.local_file_path.clone()).await.unwrap();
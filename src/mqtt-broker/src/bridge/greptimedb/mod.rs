@@ -12,105 +12,45 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{sync::Arc, time::Duration};
-
 use axum::async_trait;
 use metadata_struct::{
     adapter::record::Record, mqtt::bridge::config_greptimedb::GreptimeDBConnectorConfig,
 };
 
-use storage_adapter::storage::ArcStorageAdapter;
-use tokio::{select, sync::broadcast, time::sleep};
-use tracing::{error, info};
-
 use crate::common::types::ResultMqttBrokerError;
-use crate::storage::message::MessageStorage;
 
-use super::{
-    core::{BridgePlugin, BridgePluginReadConfig},
-    manager::ConnectorManager,
-};
+use super::runtime::ConnectorSink;
 
 mod sender;
 
 pub struct GreptimeDBBridgePlugin {
-    connector_manager: Arc<ConnectorManager>,
-    message_storage: ArcStorageAdapter,
-    connector_name: String,
     config: GreptimeDBConnectorConfig,
-    stop_send: broadcast::Sender<bool>,
+    sender: Option<sender::Sender>,
 }
 
 impl GreptimeDBBridgePlugin {
-    pub fn new(
-        connector_manager: Arc<ConnectorManager>,
-        message_storage: ArcStorageAdapter,
-        connector_name: String,
-        config: GreptimeDBConnectorConfig,
-        stop_send: broadcast::Sender<bool>,
-    ) -> Self {
+    pub fn new(config: GreptimeDBConnectorConfig) -> Self {
         GreptimeDBBridgePlugin {
-            connector_manager,
-            message_storage,
-            connector_name,
             config,
-            stop_send,
+            sender: None,
         }
     }
-
-    pub async fn append(
-        &self,
-        records: &Vec<Record>,
-        sender: sender::Sender,
-    ) -> ResultMqttBrokerError {
-        for record in records {
-            sender.send(record).await?;
-        }
-        Ok(())
-    }
 }
 
 #[async_trait]
-impl BridgePlugin for GreptimeDBBridgePlugin {
-    async fn exec(&self, config: BridgePluginReadConfig) -> ResultMqttBrokerError {
-        let message_storage = MessageStorage::new(self.message_storage.clone());
-        let group_name = self.connector_name.clone();
-        let offset = message_storage.get_group_offset(&group_name).await?;
-        let mut recv = self.stop_send.subscribe();
-        let sender = sender::Sender::new(&self.config);
-        loop {
-            select! {
-            val = recv.recv() =>{
-                if let Ok(flag) = val {
-                    if flag {
-                        info!("{}","Connector thread exited successfully");
-                        break;
-                    }
-                }
-            }
-
-            val = message_storage.read_topic_message(&config.topic_id, offset, config.record_num) =>
-                match val {
-                    Ok(data) => {
-                        self.connector_manager.report_heartbeat(&self.connector_name);
-                        if data.is_empty() {
-                            tokio::time::sleep(Duration::from_millis(100)).await;
-                            continue;
-                        }
+impl ConnectorSink for GreptimeDBBridgePlugin {
+    async fn init_sink(&mut self) -> ResultMqttBrokerError {
+        self.sender = Some(sender::Sender::new(&self.config));
+        Ok(())
+    }
 
-                        if let Err(e) = self.append(&data, sender.clone()).await{
-                            error!("Connector {} failed to write data to GreptimeDB database {}, error message: {}", self.connector_name, self.config.database, e);
-                            sleep(Duration::from_millis(100)).await;
-                        }
-                    },
-                    Err(e) => {
-                        error!("Connector {} failed to read Topic {} data with error message :{}", self.connector_name,config.topic_id,e);
-                        sleep(Duration::from_millis(100)).await;
-                    }
-                }
-            }
+    async fn send_batch(&mut self, records: &[Record]) -> ResultMqttBrokerError {
+        let Some(sender) = self.sender.as_ref() else {
+            return Ok(());
+        };
+        for record in records {
+            sender.send(record).await?;
         }
-
         Ok(())
     }
 }
@@ -12,60 +12,56 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{sync::Arc, time::Duration};
+use std::time::Duration;
 
 use axum::async_trait;
 use metadata_struct::{adapter::record::Record, mqtt::bridge::config_kafka::KafkaConnectorConfig};
-use rdkafka::producer::{FutureProducer, FutureRecord, Producer};
-use storage_adapter::storage::ArcStorageAdapter;
-use tokio::{select, sync::broadcast, time::sleep};
-use tracing::{error, info};
+use rdkafka::{
+    message::{Header, OwnedHeaders},
+    producer::{FutureProducer, FutureRecord, Producer},
+};
 
 use crate::common::types::ResultMqttBrokerError;
-use crate::storage::message::MessageStorage;
 
-use super::{
-    core::{BridgePlugin, BridgePluginReadConfig},
-    manager::ConnectorManager,
-};
+use super::runtime::ConnectorSink;
 
 pub struct KafkaBridgePlugin {
-    connector_manager: Arc<ConnectorManager>,
-    message_storage: ArcStorageAdapter,
-    connector_name: String,
     config: KafkaConnectorConfig,
-    stop_send: broadcast::Sender<bool>,
+    producer: Option<FutureProducer>,
 }
 
 impl KafkaBridgePlugin {
-    pub fn new(
-        connector_manager: Arc<ConnectorManager>,
-        message_storage: ArcStorageAdapter,
-        connector_name: String,
-        config: KafkaConnectorConfig,
-        stop_send: broadcast::Sender<bool>,
-    ) -> Self {
+    pub fn new(config: KafkaConnectorConfig) -> Self {
         KafkaBridgePlugin {
-            connector_manager,
-            message_storage,
-            connector_name,
             config,
-            stop_send,
+            producer: None,
         }
     }
+}
+
+#[async_trait]
+impl ConnectorSink for KafkaBridgePlugin {
+    async fn init_sink(&mut self) -> ResultMqttBrokerError {
+        let producer: FutureProducer = rdkafka::ClientConfig::new()
+            .set("bootstrap.servers", self.config.bootstrap_servers.as_str())
+            .set("message.timeout.ms", "5000")
+            .create()?;
+        self.producer = Some(producer);
+        Ok(())
+    }
 
-    pub async fn append(
-        &self,
-        records: &Vec<Record>,
-        producer: FutureProducer,
-    ) -> ResultMqttBrokerError {
+    async fn send_batch(&mut self, records: &[Record]) -> ResultMqttBrokerError {
+        let Some(producer) = self.producer.as_ref() else {
+            return Ok(());
+        };
         for record in records {
             let data = serde_json::to_string(record)?;
             producer
                 .send(
                     FutureRecord::to(self.config.topic.as_str())
                         .key(self.config.key.as_str())
-                        .payload(&data),
+                        .payload(&data)
+                        .headers(build_kafka_headers(record)),
                     Duration::from_secs(0),
                 )
                 .await
@@ -77,52 +73,16 @@ pub async fn append(
     }
 }
 
-#[async_trait]
-impl BridgePlugin for KafkaBridgePlugin {
-    async fn exec(&self, config: BridgePluginReadConfig) -> ResultMqttBrokerError {
-        let message_storage = MessageStorage::new(self.message_storage.clone());
-        let group_name = self.connector_name.clone();
-        let offset = message_storage.get_group_offset(&group_name).await?;
-        let mut recv = self.stop_send.subscribe();
-        let producer: FutureProducer = rdkafka::ClientConfig::new()
-            .set("bootstrap.servers", self.config.bootstrap_servers.as_str())
-            .set("message.timeout.ms", "5000")
-            .create()?;
-
-        loop {
-            select! {
-                val = recv.recv() =>{
-                    if let Ok(flag) = val {
-                        if flag {
-                            info!("{}","Connector thread exited successfully");
-                            break;
-                        }
-                    }
-                }
-
-                val = message_storage.read_topic_message(&config.topic_id, offset, config.record_num) => {
-                    match val {
-                        Ok(data) => {
-                            self.connector_manager.report_heartbeat(&self.connector_name);
-                            if data.is_empty() {
-                                tokio::time::sleep(Duration::from_millis(100)).await;
-                                continue;
-                            }
-
-                            if let Err(e) = self.append(&data, producer.clone()).await{
-                                error!("Connector {} failed to write data to kafka topic {}, error message: {}", self.connector_name, self.config.topic, e);
-                                sleep(Duration::from_millis(100)).await;
-                            }
-                        },
-                        Err(e) => {
-                            error!("Connector {} failed to read Topic {} data with error message :{}", self.connector_name,config.topic_id,e);
-                            sleep(Duration::from_millis(100)).await;
-                        }
-                    }
-                }
-            }
-        }
-
-        Ok(())
-    }
+// Record headers, including the trace context, become Kafka headers so that
+// consumers can continue the trace
+fn build_kafka_headers(record: &Record) -> OwnedHeaders {
+    record.header.iter().fold(
+        OwnedHeaders::new_with_capacity(record.header.len()),
+        |headers, h| {
+            headers.insert(Header {
+                key: h.name.as_str(),
+                value: Some(h.value.as_str()),
+            })
+        },
+    )
 }
@@ -12,12 +12,38 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::BTreeMap;
+
 use common_base::tools::now_second;
 use dashmap::DashMap;
-use metadata_struct::mqtt::bridge::connector::MQTTConnector;
+use metadata_struct::mqtt::bridge::{connector::MQTTConnector, status::MQTTStatus};
 
 use super::core::BridgePluginThread;
 
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct ConnectorMetrics {
+    // (topic_id, next offset the connector reads), everything before it has been handled
+    pub committed_offsets: BTreeMap<String, u64>,
+    // (topic_id, records after the committed offset), capped by the lag probe
+    pub topic_lag: BTreeMap<String, u64>,
+    // Records accepted by the target system
+    pub success_total: u64,
+    // Failed send attempts
+    pub failure_total: u64,
+    // Records routed to the dead-letter topic
+    pub dead_letter_total: u64,
+    // Records given up on without a dead-letter topic
+    pub discard_total: u64,
+    pub last_error: Option<String>,
+    pub last_error_time: u64,
+}
+
+impl ConnectorMetrics {
+    pub fn lag(&self) -> u64 {
+        self.topic_lag.values().sum()
+    }
+}
+
 #[derive(Default)]
 pub struct ConnectorManager {
     // (connector_name, Connector)
@@ -28,6 +54,9 @@ pub struct ConnectorManager {
 
     // (connector_name, u64)
     pub connector_heartbeat: DashMap<String, u64>,
+
+    // (connector_name, ConnectorMetrics)
+    pub connector_metrics: DashMap<String, ConnectorMetrics>,
 }
 
 impl ConnectorManager {
@@ -36,6 +65,7 @@ pub fn new() -> Self {
             connector_list: DashMap::with_capacity(8),
             connector_thread: DashMap::with_capacity(8),
             connector_heartbeat: DashMap::with_capacity(8),
+            connector_metrics: DashMap::with_capacity(8),
         }
     }
 
@@ -63,6 +93,13 @@ pub fn get_all_connector(&self) -> Vec<MQTTConnector> {
 
     pub fn remove_connector(&self, connector_name: &str) {
         self.connector_list.remove(connector_name);
+        self.connector_metrics.remove(connector_name);
+    }
+
+    pub fn update_connector_status(&self, connector_name: &str, status: MQTTStatus) {
+        if let Some(mut connector) = self.connector_list.get_mut(connector_name) {
+            connector.status = status;
+        }
     }
 
     // Connector Thread
@@ -96,12 +133,75 @@ pub fn report_heartbeat(&self, connector_name: &str) {
         self.connector_heartbeat
             .insert(connector_name.to_owned(), now_second());
     }
+
+    // Connector Metrics
+    pub fn get_connector_metrics(&self, connector_name: &str) -> ConnectorMetrics {
+        if let Some(metrics) = self.connector_metrics.get(connector_name) {
+            return metrics.clone();
+        }
+        ConnectorMetrics::default()
+    }
+
+    pub fn record_success(&self, connector_name: &str, count: u64) {
+        self.connector_metrics
+            .entry(connector_name.to_owned())
+            .or_default()
+            .success_total += count;
+    }
+
+    pub fn update_committed_offset(&self, connector_name: &str, topic_id: &str, offset: u64) {
+        self.connector_metrics
+            .entry(connector_name.to_owned())
+            .or_default()
+            .committed_offsets
+            .insert(topic_id.to_owned(), offset);
+    }
+
+    pub fn record_failure(&self, connector_name: &str, error: &str) {
+        let mut metrics = self
+            .connector_metrics
+            .entry(connector_name.to_owned())
+            .or_default();
+        metrics.failure_total += 1;
+        metrics.last_error = Some(error.to_owned());
+        metrics.last_error_time = now_second();
+    }
+
+    pub fn record_dead_letter(&self, connector_name: &str, count: u64) {
+        self.connector_metrics
+            .entry(connector_name.to_owned())
+            .or_default()
+            .dead_letter_total += count;
+    }
+
+    pub fn record_discard(&self, connector_name: &str, count: u64) {
+        self.connector_metrics
+            .entry(connector_name.to_owned())
+            .or_default()
+            .discard_total += count;
+    }
+
+    pub fn update_lag(&self, connector_name: &str, topic_id: &str, lag: u64) {
+        self.connector_metrics
+            .entry(connector_name.to_owned())
+            .or_default()
+            .topic_lag
+            .insert(topic_id.to_owned(), lag);
+    }
+
+    // Called when a topic no longer matches the connector or has been deleted
+    pub fn remove_topic_metrics(&self, connector_name: &str, topic_id: &str) {
+        if let Some(mut metrics) = self.connector_metrics.get_mut(connector_name) {
+            metrics.committed_offsets.remove(topic_id);
+            metrics.topic_lag.remove(topic_id);
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use metadata_struct::mqtt::bridge::{connector_type::ConnectorType, status::MQTTStatus};
+    use metadata_struct::mqtt::bridge::connector_type::ConnectorType;
     use tokio::sync::broadcast;
 
     fn create_test_connector() -> MQTTConnector {
@@ -109,9 +209,11 @@ fn create_test_connector() -> MQTTConnector {
             connector_name: "test_connector".to_string(),
             connector_type: ConnectorType::LocalFile,
             topic_id: "test_topic".to_string(),
+            topic_filters: Vec::new(),
             config: "{}".to_string(),
             status: MQTTStatus::Running,
             broker_id: Some(1),
+            delivery_policy: Default::default(),
             cluster_name: "test_cluster".to_string(),
             create_time: now_second(),
             update_time: now_second(),
@@ -155,6 +257,15 @@ fn connector_operations() {
 
         // get all connectors
         assert_eq!(manager.get_all_connector().len(), 2);
+
+        // update status
+        manager.update_connector_status("connector1", MQTTStatus::Idle);
+        assert_eq!(
+            manager.get_connector("connector1").unwrap().status,
+            MQTTStatus::Idle
+        );
+        manager.update_connector_status("non_existent", MQTTStatus::Idle);
+        assert!(manager.get_connector("non_existent").is_none());
     }
 
     #[test]
@@ -196,4 +307,41 @@ fn connector_heartbeat_operations() {
         assert!(heartbeat_time.value() <= &current_time);
         assert!(heartbeat_time.value() > &(current_time - 10));
     }
+
+    #[test]
+    fn connector_metrics_operations() {
+        let manager = ConnectorManager::new();
+        assert_eq!(
+            manager.get_connector_metrics("connector1"),
+            ConnectorMetrics::default()
+        );
+
+        manager.record_success("connector1", 10);
+        manager.record_success("connector1", 5);
+        manager.update_committed_offset("connector1", "topic1", 15);
+        manager.update_committed_offset("connector1", "topic2", 3);
+        manager.record_failure("connector1", "timeout");
+        manager.record_dead_letter("connector1", 2);
+        manager.record_discard("connector1", 1);
+        manager.update_lag("connector1", "topic1", 7);
+        manager.update_lag("connector1", "topic2", 1);
+
+        let metrics = manager.get_connector_metrics("connector1");
+        assert_eq!(metrics.success_total, 15);
+        assert_eq!(metrics.committed_offsets.get("topic1"), Some(&15));
+        assert_eq!(metrics.failure_total, 1);
+        assert_eq!(metrics.dead_letter_total, 2);
+        assert_eq!(metrics.discard_total, 1);
+        assert_eq!(metrics.lag(), 8);
+        assert_eq!(metrics.last_error, Some("timeout".to_string()));
+        assert!(metrics.last_error_time > 0);
+
+        manager.remove_topic_metrics("connector1", "topic2");
+        let metrics = manager.get_connector_metrics("connector1");
+        assert_eq!(metrics.committed_offsets.len(), 1);
+        assert_eq!(metrics.lag(), 7);
+
+        manager.remove_connector("connector1");
+        assert!(!manager.connector_metrics.contains_key("connector1"));
+    }
 }
@@ -18,5 +18,8 @@
 pub mod heartbeat;
 pub mod kafka;
 pub mod manager;
+pub mod mqtt;
 pub mod postgres;
 pub mod pulsar;
+pub mod runtime;
+pub mod webhook;
@@ -0,0 +1,481 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::{sync::Arc, time::Duration};
+
+use axum::async_trait;
+use bytes::Bytes;
+use common_config::broker::broker_config;
+use grpc_clients::pool::ClientPool;
+use metadata_struct::{
+    adapter::record::Record,
+    mqtt::{
+        bridge::{
+            config_mqtt::{MqttBridgeConnectorConfig, MQTT_BRIDGE_ORIGIN_PROPERTY},
+            status::MQTTStatus,
+        },
+        message::MqttMessage,
+    },
+};
+use protocol::mqtt::common::{qos, Publish, PublishProperties};
+use rumqttc::v5::mqttbytes::v5::{
+    Filter, Packet, PublishProperties as RemotePublishProperties, RetainForwardRule,
+};
+use rumqttc::v5::mqttbytes::QoS as RemoteQoS;
+use rumqttc::v5::{AsyncClient, Event, EventLoop, MqttOptions};
+use rumqttc::Outgoing;
+use rumqttc::{TlsConfiguration, Transport};
+use storage_adapter::storage::ArcStorageAdapter;
+use tokio::task::JoinHandle;
+use tokio::{select, sync::broadcast, time::sleep};
+use tracing::{error, info, warn};
+
+use crate::bridge::{manager::ConnectorManager, runtime::ConnectorSink};
+use crate::common::types::ResultMqttBrokerError;
+use crate::handler::cache::MQTTCacheManager;
+use crate::handler::error::MqttBrokerError;
+use crate::handler::message::publish_message_to_topic;
+
+struct EgressConnection {
+    client: AsyncClient,
+    connected: Arc<AtomicBool>,
+    eventloop_handle: JoinHandle<()>,
+}
+
+pub struct MqttBridgePlugin {
+    connector_manager: Arc<ConnectorManager>,
+    cache_manager: Arc<MQTTCacheManager>,
+    client_pool: Arc<ClientPool>,
+    message_storage: ArcStorageAdapter,
+    connector_name: String,
+    config: MqttBridgeConnectorConfig,
+    stop_send: broadcast::Sender<bool>,
+    egress: Option<EgressConnection>,
+}
+
+impl MqttBridgePlugin {
+    pub fn new(
+        connector_manager: Arc<ConnectorManager>,
+        cache_manager: Arc<MQTTCacheManager>,
+        client_pool: Arc<ClientPool>,
+        message_storage: ArcStorageAdapter,
+        connector_name: String,
+        config: MqttBridgeConnectorConfig,
+        stop_send: broadcast::Sender<bool>,
+    ) -> Self {
+        MqttBridgePlugin {
+            connector_manager,
+            cache_manager,
+            client_pool,
+            message_storage,
+            connector_name,
+            config,
+            stop_send,
+            egress: None,
+        }
+    }
+
+    async fn forward(&self, client: &AsyncClient, records: &[Record]) -> ResultMqttBrokerError {
+        let cluster_name = broker_config().cluster_name.clone();
+        for record in records {
+            let message = MqttMessage::decode_record(record.clone())?;
+            if is_loop_message(&message.user_properties, &cluster_name) {
+                continue;
+            }
+
+            let topic = String::from_utf8(message.topic.to_vec())?;
+            let properties = RemotePublishProperties {
+                payload_format_indicator: message.format_indicator,
+                response_topic: message.response_topic.clone(),
+                correlation_data: message.correlation_data.clone(),
+                user_properties: with_origin(&message.user_properties, &cluster_name),
+                content_type: message.content_type.clone(),
+                ..Default::default()
+            };
+            client
+                .publish_with_properties(
+                    self.config.remote_topic(&topic),
+                    remote_qos(self.config.map_qos(message.qos as u8)),
+                    message.retain,
+                    message.payload,
+                    properties,
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    // Subscribes to the remote topic filters and republishes what arrives locally.
+    // Ingress does not read a local topic, so it runs its own loop instead of the connector runtime.
+    pub async fn run_ingress(&self) -> ResultMqttBrokerError {
+        self.connector_manager
+            .update_connector_status(&self.connector_name, MQTTStatus::Running);
+        let result = self.ingress().await;
+        self.connector_manager
+            .update_connector_status(&self.connector_name, MQTTStatus::Idle);
+        result
+    }
+
+    async fn ingress(&self) -> ResultMqttBrokerError {
+        let (client, mut eventloop) =
+            AsyncClient::new(build_mqtt_options(&self.connector_name, &self.config)?, 100);
+        let filters = subscribe_filters(&self.config);
+        let cluster_name = broker_config().cluster_name.clone();
+        let mut recv = self.stop_send.subscribe();
+        let mut attempt = 0;
+
+        loop {
+            select! {
+                val = recv.recv() => {
+                    if let Ok(flag) = val {
+                        if flag {
+                            break;
+                        }
+                    }
+                }
+
+                val = eventloop.poll() => {
+                    match val {
+                        Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                            attempt = 0;
+                            info!("Connector {} connected to remote broker {}", self.connector_name, self.config.server);
+                            // Subscriptions are renewed on every connect in case the remote session is gone
+                            if let Err(e) = client.try_subscribe_many(filters.clone()) {
+                                error!("Connector {} failed to subscribe on remote broker, error message: {}", self.connector_name, e);
+                            }
+                        }
+                        Ok(Event::Incoming(Packet::Publish(publish))) => {
+                            self.connector_manager.report_heartbeat(&self.connector_name);
+                            match self.republish(publish, &cluster_name).await {
+                                Ok(()) => self.connector_manager.record_success(&self.connector_name, 1),
+                                Err(e) => {
+                                    error!("Connector {} failed to republish remote message locally, error message: {}", self.connector_name, e);
+                                    self.connector_manager.record_failure(&self.connector_name, &e.to_string());
+                                }
+                            }
+                        }
+                        Ok(_) => {}
+                        Err(e) => {
+                            self.connector_manager.record_failure(&self.connector_name, &e.to_string());
+                            let interval = self.config.reconnect_interval_ms(attempt);
+                            attempt = attempt.saturating_add(1);
+                            warn!("Connector {} lost connection to remote broker {}, retrying in {}ms, error message: {}", self.connector_name, self.config.server, interval, e);
+                            sleep(Duration::from_millis(interval)).await;
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Err(e) = client.try_disconnect() {
+            warn!(
+                "Connector {} failed to disconnect from remote broker, error message: {}",
+                self.connector_name, e
+            );
+        }
+        info!(
+            "Connector {} thread exited successfully",
+            self.connector_name
+        );
+        Ok(())
+    }
+
+    async fn republish(
+        &self,
+        remote: rumqttc::v5::mqttbytes::v5::Publish,
+        cluster_name: &str,
+    ) -> ResultMqttBrokerError {
+        let remote_properties = remote.properties.unwrap_or_default();
+        if is_loop_message(&remote_properties.user_properties, cluster_name) {
+            return Ok(());
+        }
+
+        let topic = String::from_utf8(remote.topic.to_vec())?;
+        let publish = Publish {
+            dup: false,
+            qos: qos(self.config.map_qos(remote.qos as u8)).unwrap_or_default(),
+            retain: remote.retain,
+            topic: Bytes::from(self.config.local_topic(&topic)),
+            payload: remote.payload,
+            ..Default::default()
+        };
+        let properties = PublishProperties {
+            payload_format_indicator: remote_properties.payload_format_indicator,
+            response_topic: remote_properties.response_topic,
+            correlation_data: remote_properties.correlation_data,
+            user_properties: with_origin(&remote_properties.user_properties, cluster_name),
+            content_type: remote_properties.content_type,
+            ..Default::default()
+        };
+
+        publish_message_to_topic(
+            &self.cache_manager,
+            &self.client_pool,
+            &self.message_storage,
+            &self.config.client_id(&self.connector_name),
+            &publish,
+            &Some(properties),
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl ConnectorSink for MqttBridgePlugin {
+    async fn init_sink(&mut self) -> ResultMqttBrokerError {
+        let (client, eventloop) =
+            AsyncClient::new(build_mqtt_options(&self.connector_name, &self.config)?, 100);
+        let connected = Arc::new(AtomicBool::new(false));
+        let eventloop_handle = tokio::spawn(drive_eventloop(
+            self.connector_name.clone(),
+            self.config.clone(),
+            eventloop,
+            connected.clone(),
+            self.stop_send.clone(),
+        ));
+        self.egress = Some(EgressConnection {
+            client,
+            connected,
+            eventloop_handle,
+        });
+        Ok(())
+    }
+
+    async fn send_batch(&mut self, records: &[Record]) -> ResultMqttBrokerError {
+        let Some(egress) = self.egress.as_ref() else {
+            return Ok(());
+        };
+        if !egress.connected.load(Ordering::Relaxed) {
+            return Err(MqttBrokerError::CommonError(format!(
+                "not connected to remote broker {}",
+                self.config.server
+            )));
+        }
+        self.forward(&egress.client, records).await
+    }
+
+    async fn cleanup_sink(&mut self) -> ResultMqttBrokerError {
+        let Some(egress) = self.egress.take() else {
+            return Ok(());
+        };
+        if egress.connected.load(Ordering::Relaxed) {
+            // The event loop exits once the disconnect has been sent
+            egress.client.try_disconnect()?;
+        } else {
+            egress.eventloop_handle.abort();
+        }
+        Ok(())
+    }
+}
+
+// Keeps the egress connection alive, reconnecting with backoff until the connector stops.
+async fn drive_eventloop(
+    connector_name: String,
+    config: MqttBridgeConnectorConfig,
+    mut eventloop: EventLoop,
+    connected: Arc<AtomicBool>,
+    stop_send: broadcast::Sender<bool>,
+) {
+    let mut recv = stop_send.subscribe();
+    let mut attempt = 0;
+    loop {
+        select! {
+            val = recv.recv() => {
+                if let Ok(true) = val {
+                    break;
+                }
+            }
+
+            val = eventloop.poll() => {
+                match val {
+                    Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                        attempt = 0;
+                        connected.store(true, Ordering::Relaxed);
+                        info!("Connector {} connected to remote broker {}", connector_name, config.server);
+                    }
+                    Ok(Event::Outgoing(Outgoing::Disconnect)) => {
+                        connected.store(false, Ordering::Relaxed);
+                        break;
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        connected.store(false, Ordering::Relaxed);
+                        let interval = config.reconnect_interval_ms(attempt);
+                        attempt = attempt.saturating_add(1);
+                        warn!("Connector {} lost connection to remote broker {}, retrying in {}ms, error message: {}", connector_name, config.server, interval, e);
+                        sleep(Duration::from_millis(interval)).await;
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn build_mqtt_options(
+    connector_name: &str,
+    config: &MqttBridgeConnectorConfig,
+) -> Result<MqttOptions, MqttBrokerError> {
+    let (host, port) = config.host_port()?;
+    let mut options = MqttOptions::new(config.client_id(connector_name), host, port);
+    options.set_keep_alive(Duration::from_secs(config.keep_alive_secs()));
+    if let Some(username) = &config.username {
+        options.set_credentials(username, config.password.clone().unwrap_or_default());
+    }
+    if config.is_tls() {
+        options.set_transport(Transport::Tls(build_tls_config(config)?));
+    }
+    Ok(options)
+}
+
+fn build_tls_config(
+    config: &MqttBridgeConnectorConfig,
+) -> Result<TlsConfiguration, MqttBrokerError> {
+    // Without a CA file the platform trust store is used
+    let Some(ca_path) = &config.ca_path else {
+        return Ok(TlsConfiguration::default());
+    };
+
+    let ca = std::fs::read(ca_path)?;
+    let client_auth = match (&config.client_cert_path, &config.client_key_path) {
+        (Some(cert_path), Some(key_path)) => {
+            Some((std::fs::read(cert_path)?, std::fs::read(key_path)?))
+        }
+        _ => None,
+    };
+    Ok(TlsConfiguration::Simple {
+        ca,
+        alpn: None,
+        client_auth,
+    })
+}
+
+fn subscribe_filters(config: &MqttBridgeConnectorConfig) -> Vec<Filter> {
+    let qos = remote_qos(config.map_qos(2));
+    config
+        .remote_topic_filters
+        .clone()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|path| Filter {
+            path,
+            qos,
+            // Never receive back what this bridge itself publishes on the remote broker
+            nolocal: true,
+            preserve_retain: true,
+            retain_forward_rule: RetainForwardRule::OnEverySubscribe,
+        })
+        .collect()
+}
+
+fn remote_qos(qos: u8) -> RemoteQoS {
+    match qos {
+        0 => RemoteQoS::AtMostOnce,
+        1 => RemoteQoS::AtLeastOnce,
+        _ => RemoteQoS::ExactlyOnce,
+    }
+}
+
+fn is_loop_message(user_properties: &[(String, String)], cluster_name: &str) -> bool {
+    user_properties
+        .iter()
+        .any(|(key, value)| key == MQTT_BRIDGE_ORIGIN_PROPERTY && value == cluster_name)
+}
+
+// Tags a message with the cluster it entered the bridge topology from, keeping an existing tag.
+fn with_origin(user_properties: &[(String, String)], cluster_name: &str) -> Vec<(String, String)> {
+    let mut properties = user_properties.to_vec();
+    if !properties
+        .iter()
+        .any(|(key, _)| key == MQTT_BRIDGE_ORIGIN_PROPERTY)
+    {
+        properties.push((
+            MQTT_BRIDGE_ORIGIN_PROPERTY.to_string(),
+            cluster_name.to_string(),
+        ));
+    }
+    properties
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn origin(cluster_name: &str) -> (String, String) {
+        (
+            MQTT_BRIDGE_ORIGIN_PROPERTY.to_string(),
+            cluster_name.to_string(),
+        )
+    }
+
+    #[test]
+    fn loop_prevention_test() {
+        let plain = vec![("k".to_string(), "v".to_string())];
+        assert!(!is_loop_message(&plain, "edge"));
+
+        let tagged = with_origin(&plain, "edge");
+        assert_eq!(
+            tagged,
+            vec![("k".to_string(), "v".to_string()), origin("edge")]
+        );
+        assert!(is_loop_message(&tagged, "edge"));
+        assert!(!is_loop_message(&tagged, "cloud"));
+
+        // An existing origin is kept when the message is forwarded again
+        assert_eq!(with_origin(&tagged, "cloud"), tagged);
+    }
+
+    #[test]
+    fn subscribe_filters_test() {
+        let config = MqttBridgeConnectorConfig {
+            server: "127.0.0.1:1883".to_string(),
+            direction: MqttBridgeDirection::Ingress,
+            remote_topic_filters: Some(vec!["a/#".to_string(), "b/+".to_string()]),
+            ..Default::default()
+        };
+        let filters = subscribe_filters(&config);
+        assert_eq!(filters.len(), 2);
+        assert_eq!(filters[0].path, "a/#");
+        assert_eq!(filters[0].qos, RemoteQoS::AtLeastOnce);
+        assert!(filters[0].nolocal);
+    }
+
+    #[test]
+    fn build_mqtt_options_test() {
+        let mut config = MqttBridgeConnectorConfig {
+            server: "127.0.0.1:1883".to_string(),
+            username: Some("user".to_string()),
+            password: Some("pass".to_string()),
+            ..Default::default()
+        };
+        let options = build_mqtt_options("bridge", &config).unwrap();
+        assert_eq!(options.client_id(), "robustmq_bridge_bridge");
+        assert_eq!(options.broker_address(), ("127.0.0.1".to_string(), 1883));
+
+        config.server = "127.0.0.1".to_string();
+        assert!(build_mqtt_options("bridge", &config).is_err());
+
+        config.server = "127.0.0.1:8883".to_string();
+        config.enable_tls = Some(true);
+        config.ca_path = Some("/not/exist/ca.pem".to_string());
+        assert!(build_mqtt_options("bridge", &config).is_err());
+    }
+
+    #[test]
+    fn remote_qos_test() {
+        assert_eq!(remote_qos(0), RemoteQoS::AtMostOnce);
+        assert_eq!(remote_qos(1), RemoteQoS::AtLeastOnce);
+        assert_eq!(remote_qos(2), RemoteQoS::ExactlyOnce);
+    }
+}
@@ -12,47 +12,27 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{sync::Arc, time::Duration};
-
 use axum::async_trait;
 use metadata_struct::{
     adapter::record::Record, mqtt::bridge::config_postgres::PostgresConnectorConfig,
 };
-use storage_adapter::storage::ArcStorageAdapter;
-use tokio::{select, sync::broadcast, time::sleep};
 use tokio_postgres::{Client, NoTls};
 use tracing::{error, info};
 
 use crate::common::types::ResultMqttBrokerError;
-use crate::storage::message::MessageStorage;
 
-use super::{
-    core::{BridgePlugin, BridgePluginReadConfig},
-    manager::ConnectorManager,
-};
+use super::runtime::ConnectorSink;
 
 pub struct PostgresBridgePlugin {
-    connector_manager: Arc<ConnectorManager>,
-    message_storage: ArcStorageAdapter,
-    connector_name: String,
     config: PostgresConnectorConfig,
-    stop_send: broadcast::Sender<bool>,
+    client: Option<Client>,
 }
 
 impl PostgresBridgePlugin {
-    pub fn new(
-        connector_manager: Arc<ConnectorManager>,
-        message_storage: ArcStorageAdapter,
-        connector_name: String,
-        config: PostgresConnectorConfig,
-        stop_send: broadcast::Sender<bool>,
-    ) -> Self {
+    pub fn new(config: PostgresConnectorConfig) -> Self {
         PostgresBridgePlugin {
-            connector_manager,
-            message_storage,
-            connector_name,
             config,
-            stop_send,
+            client: None,
         }
     }
 
@@ -70,7 +50,7 @@ pub async fn connect(&self) -> Result<Client, common_base::error::common::Common
         Ok(client)
     }
 
-    pub async fn append(&self, records: &Vec<Record>, client: &Client) -> ResultMqttBrokerError {
+    pub async fn append(&self, records: &[Record], client: &Client) -> ResultMqttBrokerError {
         if records.is_empty() {
             return Ok(());
         }
@@ -84,7 +64,7 @@ pub async fn append(&self, records: &Vec<Record>, client: &Client) -> ResultMqtt
         }
     }
 
-    async fn single_insert(&self, records: &Vec<Record>, client: &Client) -> ResultMqttBrokerError {
+    async fn single_insert(&self, records: &[Record], client: &Client) -> ResultMqttBrokerError {
         let enable_upsert = self.config.enable_upsert.unwrap_or(false);
 
         for record in records {
@@ -127,7 +107,7 @@ async fn single_insert(&self, records: &Vec<Record>, client: &Client) -> ResultM
         Ok(())
     }
 
-    async fn batch_insert(&self, records: &Vec<Record>, client: &Client) -> ResultMqttBrokerError {
+    async fn batch_insert(&self, records: &[Record], client: &Client) -> ResultMqttBrokerError {
         let enable_upsert = self.config.enable_upsert.unwrap_or(false);
         let base_sql = if enable_upsert {
             let conflict_columns = self
@@ -207,81 +187,20 @@ async fn batch_insert(&self, records: &Vec<Record>, client: &Client) -> ResultMq
 }
 
 #[async_trait]
-impl BridgePlugin for PostgresBridgePlugin {
-    async fn exec(&self, config: BridgePluginReadConfig) -> ResultMqttBrokerError {
-        let message_storage = MessageStorage::new(self.message_storage.clone());
-        let group_name = self.connector_name.clone();
-        let mut recv = self.stop_send.subscribe();
-
-        let client = match self.connect().await {
-            Ok(client) => client,
-            Err(e) => {
-                error!(
-                    "Connector {} failed to connect to PostgreSQL: {}",
-                    self.connector_name, e
-                );
-                return Err(e.into());
-            }
-        };
-
+impl ConnectorSink for PostgresBridgePlugin {
+    async fn init_sink(&mut self) -> ResultMqttBrokerError {
+        self.client = Some(self.connect().await?);
         info!(
-            "Connector {} successfully connected to PostgreSQL database: {}",
-            self.connector_name, self.config.database
+            "Successfully connected to PostgreSQL database: {}",
+            self.config.database
         );
-
-        loop {
-            let offset = message_storage.get_group_offset(&group_name).await?;
-
-            select! {
-                val = recv.recv() => {
-                    if let Ok(flag) = val {
-                        if flag {
-                            info!("Connector {} thread exited successfully", self.connector_name);
-                            break;
-                        }
-                    }
-                }
-
-                val = message_storage.read_topic_message(&config.topic_id, offset, config.record_num) => {
-                    match val {
-                        Ok(data) => {
-                            self.connector_manager.report_heartbeat(&self.connector_name);
-
-                            if data.is_empty() {
-                                sleep(Duration::from_millis(100)).await;
-                                continue;
-                            }
-
-                            if let Err(e) = self.append(&data, &client).await {
-                                error!(
-                                    "Connector {} failed to write data to PostgreSQL table {}, error: {}",
-                                    self.connector_name, self.config.table, e
-                                );
-                                sleep(Duration::from_millis(100)).await;
-                            } else {
-                                info!(
-                                    "Connector {} successfully wrote {} records to PostgreSQL table {}",
-                                    self.connector_name, data.len(), self.config.table
-                                );
-                            }
-
-                            // commit offset
-                            message_storage
-                                .commit_group_offset(&group_name, &config.topic_id, offset + data.len() as u64)
-                                .await?;
-                        }
-                        Err(e) => {
-                            error!(
-                                "Connector {} failed to read Topic {} data with error: {}",
-                                self.connector_name, config.topic_id, e
-                            );
-                            sleep(Duration::from_millis(100)).await;
-                        }
-                    }
-                }
-            }
-        }
-
         Ok(())
     }
+
+    async fn send_batch(&mut self, records: &[Record]) -> ResultMqttBrokerError {
+        let Some(client) = self.client.as_ref() else {
+            return Ok(());
+        };
+        self.append(records, client).await
+    }
 }
@@ -0,0 +1,989 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    slice,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use axum::async_trait;
+use common_base::telemetry::trace::{is_trace_enable, TraceSpan};
+use common_base::utils::crc::calc_crc32;
+use futures::future::select_all;
+use grpc_clients::pool::ClientPool;
+use metadata_struct::{
+    adapter::record::{Header, Record},
+    mqtt::bridge::{connector::ConnectorDeliveryPolicy, status::MQTTStatus},
+};
+use storage_adapter::storage::ArcStorageAdapter;
+use tokio::{select, sync::broadcast, time::sleep};
+use tracing::{error, info, warn};
+
+use super::{core::BridgePluginReadConfig, manager::ConnectorManager};
+use crate::common::types::ResultMqttBrokerError;
+use crate::handler::cache::MQTTCacheManager;
+use crate::handler::error::MqttBrokerError;
+use crate::handler::topic::try_init_topic;
+use crate::storage::message::MessageStorage;
+use crate::subscribe::common::is_match_sub_and_topic;
+
+pub const DEAD_LETTER_HEADER_CONNECTOR: &str = "dead_letter_connector";
+pub const DEAD_LETTER_HEADER_REASON: &str = "dead_letter_reason";
+
+// The lag is counted by reading ahead, so it stops at this many records
+const LAG_PROBE_MAX_RECORDS: u64 = 1000;
+const LAG_PROBE_INTERVAL: Duration = Duration::from_secs(5);
+const TOPIC_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+// The target system side of a connector. The runtime reads the topics, hands
+// batches to the sink and commits the offset once every record is handled.
+#[async_trait]
+pub trait ConnectorSink: Send {
+    // Connects to the target system before the first batch
+    async fn init_sink(&mut self) -> ResultMqttBrokerError {
+        Ok(())
+    }
+
+    // Writes a batch. On error none of the records count as delivered, and
+    // ConnectorRecordRejected marks records the target will never accept.
+    async fn send_batch(&mut self, records: &[Record]) -> ResultMqttBrokerError;
+
+    async fn cleanup_sink(&mut self) -> ResultMqttBrokerError {
+        Ok(())
+    }
+
+    // Most records in one batch, defaults to the read size of the connector
+    fn batch_size(&self) -> Option<u64> {
+        None
+    }
+
+    // Longest time a partial batch waits for more records
+    fn batch_interval(&self) -> Duration {
+        Duration::ZERO
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum PollResult {
+    // Records were read or delivered, the topic may have more right away
+    Busy,
+    Idle,
+    Unavailable,
+    Stopped,
+}
+
+// Read position of one topic the connector consumes
+struct TopicCursor {
+    topic_id: String,
+    group_name: String,
+    // Records read but not handled yet, they start at the committed offset
+    pending: Vec<Record>,
+    // Offset the next read starts from
+    read_offset: u64,
+    batch_start: Instant,
+    last_lag_probe: Option<Instant>,
+}
+
+#[derive(Debug, PartialEq)]
+enum DeliveryResult {
+    // Every record was delivered, dead-lettered or discarded, the offset can move on
+    Handled,
+    // The target looks unavailable, the batch has to be sent again
+    Unavailable,
+    Stopped,
+}
+
+enum RecordResult {
+    Delivered,
+    // The target will never accept the record
+    Rejected(String),
+    // The record still failed after every retry
+    Failed(String),
+    Stopped,
+}
+
+pub struct ConnectorRuntime {
+    connector_manager: Arc<ConnectorManager>,
+    cache_manager: Arc<MQTTCacheManager>,
+    client_pool: Arc<ClientPool>,
+    message_storage: ArcStorageAdapter,
+    connector_name: String,
+    policy: ConnectorDeliveryPolicy,
+    stop_send: broadcast::Sender<bool>,
+}
+
+impl ConnectorRuntime {
+    pub fn new(
+        connector_manager: Arc<ConnectorManager>,
+        cache_manager: Arc<MQTTCacheManager>,
+        client_pool: Arc<ClientPool>,
+        message_storage: ArcStorageAdapter,
+        connector_name: String,
+        policy: ConnectorDeliveryPolicy,
+        stop_send: broadcast::Sender<bool>,
+    ) -> Self {
+        ConnectorRuntime {
+            connector_manager,
+            cache_manager,
+            client_pool,
+            message_storage,
+            connector_name,
+            policy,
+            stop_send,
+        }
+    }
+
+    pub async fn run<S: ConnectorSink>(
+        &self,
+        sink: &mut S,
+        config: BridgePluginReadConfig,
+    ) -> ResultMqttBrokerError {
+        self.connector_manager
+            .update_connector_status(&self.connector_name, MQTTStatus::Running);
+
+        let result = self.run_sink(sink, &config).await;
+        if let Err(e) = &result {
+            self.connector_manager
+                .record_failure(&self.connector_name, &e.to_string());
+        }
+        if let Err(e) = sink.cleanup_sink().await {
+            warn!(
+                "Connector {} failed to release the target system, error message: {}",
+                self.connector_name, e
+            );
+        }
+
+        self.connector_manager
+            .update_connector_status(&self.connector_name, MQTTStatus::Idle);
+        info!(
+            "Connector {} thread exited successfully",
+            self.connector_name
+        );
+        result
+    }
+
+    async fn run_sink<S: ConnectorSink>(
+        &self,
+        sink: &mut S,
+        config: &BridgePluginReadConfig,
+    ) -> ResultMqttBrokerError {
+        let mut recv = self.stop_send.subscribe();
+        sink.init_sink().await?;
+
+        let message_storage = MessageStorage::new(self.message_storage.clone());
+        let batch_size = sink.batch_size().unwrap_or(config.record_num).max(1);
+        let batch_interval = sink.batch_interval();
+
+        let mut cursors: Vec<TopicCursor> = Vec::new();
+        let mut last_refresh: Option<Instant> = None;
+
+        loop {
+            if let Ok(true) = recv.try_recv() {
+                break;
+            }
+
+            // Topics matching the filters may be created at any time
+            if last_refresh.is_none_or(|refresh| refresh.elapsed() >= TOPIC_REFRESH_INTERVAL) {
+                last_refresh = Some(Instant::now());
+                self.refresh_cursors(config, &message_storage, &mut cursors)
+                    .await?;
+            }
+
+            let mut busy = false;
+            let mut unavailable = false;
+            for cursor in cursors.iter_mut() {
+                match self
+                    .poll_topic(
+                        sink,
+                        &message_storage,
+                        cursor,
+                        batch_size,
+                        batch_interval,
+                        &mut recv,
+                    )
+                    .await?
+                {
+                    PollResult::Busy => busy = true,
+                    PollResult::Idle => {}
+                    PollResult::Unavailable => {
+                        unavailable = true;
+                        break;
+                    }
+                    PollResult::Stopped => return Ok(()),
+                }
+            }
+            self.connector_manager
+                .report_heartbeat(&self.connector_name);
+
+            let stopped = if unavailable {
+                let wait =
+                    Duration::from_millis(self.policy.retry_interval_ms(self.policy.max_retries));
+                sleep_or_stop(&mut recv, wait).await
+            } else if busy {
+                continue;
+            } else if cursors.iter().any(|cursor| !cursor.pending.is_empty()) {
+                // A partial batch is waiting for the batch interval
+                sleep_or_stop(&mut recv, Duration::from_millis(100)).await
+            } else {
+                // Wake up as soon as a topic gets new records, but still refresh the topics in time
+                let wait = last_refresh
+                    .map(|refresh| TOPIC_REFRESH_INTERVAL.saturating_sub(refresh.elapsed()))
+                    .unwrap_or_default();
+                wait_for_write_or_stop(&mut recv, &message_storage, &cursors, wait).await
+            };
+            if stopped {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    // Topics the connector reads, the bound topic first and then every topic matching a filter
+    fn source_topics(&self, config: &BridgePluginReadConfig) -> Vec<String> {
+        let mut topics = Vec::new();
+        if !config.topic_id.is_empty() {
+            topics.push(config.topic_id.clone());
+        }
+        if config.topic_filters.is_empty() {
+            return topics;
+        }
+
+        let mut matched = Vec::new();
+        for topic in self.cache_manager.topic_info.iter() {
+            // Reading its own dead-letter topic would send the rejected records around forever
+            if self.policy.dead_letter_topic.as_ref() == Some(&topic.topic_name) {
+                continue;
+            }
+            if topics.contains(&topic.topic_id) {
+                continue;
+            }
+            if config
+                .topic_filters
+                .iter()
+                .any(|filter| is_match_sub_and_topic(filter, &topic.topic_name).is_ok())
+            {
+                matched.push(topic.topic_id.clone());
+            }
+        }
+        matched.sort();
+        topics.extend(matched);
+        topics
+    }
+
+    async fn refresh_cursors(
+        &self,
+        config: &BridgePluginReadConfig,
+        message_storage: &MessageStorage,
+        cursors: &mut Vec<TopicCursor>,
+    ) -> ResultMqttBrokerError {
+        let topics = self.source_topics(config);
+
+        cursors.retain(|cursor| {
+            let keep = topics.contains(&cursor.topic_id);
+            if !keep {
+                info!(
+                    "Connector {} stopped reading Topic {}",
+                    self.connector_name, cursor.topic_id
+                );
+                self.connector_manager
+                    .remove_topic_metrics(&self.connector_name, &cursor.topic_id);
+            }
+            keep
+        });
+
+        for topic_id in topics {
+            if cursors.iter().any(|cursor| cursor.topic_id == topic_id) {
+                continue;
+            }
+
+            let group_name = self.offset_group_name(config, &topic_id);
+            let offset = message_storage.get_group_offset(&group_name).await?;
+            self.connector_manager
+                .update_committed_offset(&self.connector_name, &topic_id, offset);
+            info!(
+                "Connector {} started reading Topic {} from offset {}",
+                self.connector_name, topic_id, offset
+            );
+            cursors.push(TopicCursor {
+                topic_id,
+                group_name,
+                pending: Vec::new(),
+                read_offset: offset,
+                batch_start: Instant::now(),
+                last_lag_probe: None,
+            });
+        }
+        Ok(())
+    }
+
+    // Offsets are committed per topic. The bound topic keeps the connector name as its
+    // group, so offsets committed before topic filters existed are still picked up.
+    fn offset_group_name(&self, config: &BridgePluginReadConfig, topic_id: &str) -> String {
+        if topic_id == config.topic_id {
+            self.connector_name.clone()
+        } else {
+            format!("{}_{}", self.connector_name, topic_id)
+        }
+    }
+
+    async fn poll_topic<S: ConnectorSink>(
+        &self,
+        sink: &mut S,
+        message_storage: &MessageStorage,
+        cursor: &mut TopicCursor,
+        batch_size: u64,
+        batch_interval: Duration,
+        recv: &mut broadcast::Receiver<bool>,
+    ) -> Result<PollResult, MqttBrokerError> {
+        let offset = message_storage.get_group_offset(&cursor.group_name).await?;
+        let read_offset = next_offset(&cursor.pending, offset);
+        let read_num = batch_size.saturating_sub(cursor.pending.len() as u64);
+
+        let data = match message_storage
+            .read_topic_records(&cursor.topic_id, read_offset, read_num)
+            .await
+        {
+            Ok(data) => data,
+            Err(e) => {
+                error!(
+                    "Connector {} failed to read Topic {} data with error message :{}",
+                    self.connector_name, cursor.topic_id, e
+                );
+                self.connector_manager
+                    .record_failure(&self.connector_name, &e.to_string());
+                return Ok(PollResult::Idle);
+            }
+        };
+
+        if cursor.pending.is_empty() {
+            cursor.batch_start = Instant::now();
+        }
+        let received = data.len() as u64;
+        cursor.pending.extend(data);
+        cursor.read_offset = next_offset(&cursor.pending, offset);
+
+        if cursor.pending.is_empty() {
+            self.connector_manager
+                .update_lag(&self.connector_name, &cursor.topic_id, 0);
+            return Ok(PollResult::Idle);
+        }
+
+        // Wait for a full batch unless the batch interval has passed
+        if (cursor.pending.len() as u64) < batch_size
+            && cursor.batch_start.elapsed() < batch_interval
+        {
+            return Ok(if received > 0 {
+                PollResult::Busy
+            } else {
+                PollResult::Idle
+            });
+        }
+
+        match self.deliver(sink, &cursor.pending, recv).await? {
+            DeliveryResult::Handled => {}
+            DeliveryResult::Unavailable => return Ok(PollResult::Unavailable),
+            DeliveryResult::Stopped => return Ok(PollResult::Stopped),
+        }
+
+        let committed_offset = next_offset(&cursor.pending, offset);
+        message_storage
+            .commit_group_offset(&cursor.group_name, &cursor.topic_id, committed_offset)
+            .await?;
+        self.connector_manager.update_committed_offset(
+            &self.connector_name,
+            &cursor.topic_id,
+            committed_offset,
+        );
+        cursor.pending.clear();
+        cursor.read_offset = committed_offset;
+
+        // A short read means the connector has caught up on this topic
+        if received < read_num {
+            self.connector_manager
+                .update_lag(&self.connector_name, &cursor.topic_id, 0);
+        } else if cursor
+            .last_lag_probe
+            .is_none_or(|probe| probe.elapsed() >= LAG_PROBE_INTERVAL)
+        {
+            cursor.last_lag_probe = Some(Instant::now());
+            if let Ok(ahead) = message_storage
+                .read_topic_records(&cursor.topic_id, committed_offset, LAG_PROBE_MAX_RECORDS)
+                .await
+            {
+                self.connector_manager.update_lag(
+                    &self.connector_name,
+                    &cursor.topic_id,
+                    ahead.len() as u64,
+                );
+            }
+        }
+        Ok(PollResult::Busy)
+    }
+
+    // Sends the batch with retries. When it keeps failing, the records are sent
+    // one by one so that a single bad record cannot hold back the rest.
+    async fn deliver<S: ConnectorSink>(
+        &self,
+        sink: &mut S,
+        records: &[Record],
+        recv: &mut broadcast::Receiver<bool>,
+    ) -> Result<DeliveryResult, MqttBrokerError> {
+        // Records failing the CRC check can never be delivered
+        let (valid, corrupted): (Vec<&Record>, Vec<&Record>) =
+            records.iter().partition(|record| record.crc32_check());
+        let mut poison: Vec<(Record, String)> = corrupted
+            .into_iter()
+            .map(|record| (record.clone(), "crc check failed".to_string()))
+            .collect();
+
+        if !valid.is_empty() {
+            let mut batch: Vec<Record> = valid.into_iter().cloned().collect();
+            let spans = self.start_deliver_spans(&mut batch);
+            let mut attempt = 0;
+            let delivered_as_batch = loop {
+                match sink.send_batch(&batch).await {
+                    Ok(()) => break true,
+                    Err(e) => {
+                        self.report_send_error(&e);
+                        if is_rejected(&e) || attempt >= self.policy.max_retries {
+                            break false;
+                        }
+                    }
+                }
+
+                let interval = self.policy.retry_interval_ms(attempt);
+                attempt += 1;
+                if sleep_or_stop(recv, Duration::from_millis(interval)).await {
+                    return Ok(DeliveryResult::Stopped);
+                }
+            };
+
+            if delivered_as_batch {
+                self.connector_manager
+                    .record_success(&self.connector_name, batch.len() as u64);
+            } else {
+                let mut delivered = 0;
+                let mut failed = Vec::new();
+                let mut rejected = false;
+                for (i, record) in batch.into_iter().enumerate() {
+                    let error = match self.deliver_record(sink, &record, recv).await {
+                        RecordResult::Delivered => {
+                            delivered += 1;
+                            continue;
+                        }
+                        RecordResult::Rejected(error) => {
+                            rejected = true;
+                            error
+                        }
+                        RecordResult::Failed(error) => {
+                            // Nothing got through and nothing was rejected outright, so the
+                            // target itself is most likely down rather than the records bad
+                            if delivered == 0 && !rejected {
+                                return Ok(DeliveryResult::Unavailable);
+                            }
+                            error
+                        }
+                        RecordResult::Stopped => return Ok(DeliveryResult::Stopped),
+                    };
+                    if let Some(span) = spans.get(i).and_then(Option::as_ref) {
+                        span.set_error(error.clone());
+                    }
+                    failed.push((record, error));
+                }
+
+                self.connector_manager
+                    .record_success(&self.connector_name, delivered);
+                poison.extend(failed);
+            }
+        }
+
+        if let Err(e) = self.dead_letter(poison).await {
+            error!(
+                "Connector {} failed to write records to the dead-letter topic, error message: {}",
+                self.connector_name, e
+            );
+            self.connector_manager
+                .record_failure(&self.connector_name, &e.to_string());
+            return Ok(DeliveryResult::Unavailable);
+        }
+        Ok(DeliveryResult::Handled)
+    }
+
+    // Sends one record with the retries of the policy. Only a record the target
+    // rejects or that still fails after every retry ends up as poison.
+    async fn deliver_record<S: ConnectorSink>(
+        &self,
+        sink: &mut S,
+        record: &Record,
+        recv: &mut broadcast::Receiver<bool>,
+    ) -> RecordResult {
+        let mut attempt = 0;
+        loop {
+            match sink.send_batch(slice::from_ref(record)).await {
+                Ok(()) => return RecordResult::Delivered,
+                Err(e) => {
+                    self.report_send_error(&e);
+                    if is_rejected(&e) {
+                        return RecordResult::Rejected(e.to_string());
+                    }
+                    if attempt >= self.policy.max_retries {
+                        return RecordResult::Failed(e.to_string());
+                    }
+                }
+            }
+
+            let interval = self.policy.retry_interval_ms(attempt);
+            attempt += 1;
+            if sleep_or_stop(recv, Duration::from_millis(interval)).await {
+                return RecordResult::Stopped;
+            }
+        }
+    }
+
+    // One span per traced record so that every publisher's trace shows the delivery,
+    // the records then carry the delivery span on to the target
+    fn start_deliver_spans(&self, batch: &mut [Record]) -> Vec<Option<TraceSpan>> {
+        if !is_trace_enable() {
+            return Vec::new();
+        }
+        batch
+            .iter_mut()
+            .map(|record| {
+                let trace_context = record.trace_context();
+                if trace_context.is_empty() {
+                    return None;
+                }
+                let span = TraceSpan::from_pairs("bridge.deliver", trace_context);
+                span.set_attribute("connector.name", self.connector_name.clone());
+                record.set_trace_context(span.trace_context());
+                Some(span)
+            })
+            .collect()
+    }
+
+    async fn dead_letter(&self, records: Vec<(Record, String)>) -> ResultMqttBrokerError {
+        if records.is_empty() {
+            return Ok(());
+        }
+
+        let Some(topic_name) = &self.policy.dead_letter_topic else {
+            for (record, reason) in records.iter() {
+                error!(
+                    "Connector {} discarded the record at offset {:?}, reason: {}",
+                    self.connector_name, record.offset, reason
+                );
+            }
+            self.connector_manager
+                .record_discard(&self.connector_name, records.len() as u64);
+            return Ok(());
+        };
+
+        let topic = try_init_topic(
+            topic_name,
+            &self.cache_manager,
+            &self.message_storage,
+            &self.client_pool,
+        )
+        .await?;
+
+        let count = records.len() as u64;
+        let records: Vec<Record> = records
+            .into_iter()
+            .map(|(mut record, reason)| {
+                warn!(
+                    "Connector {} moved the record at offset {:?} to dead-letter topic {}, reason: {}",
+                    self.connector_name, record.offset, topic_name, reason
+                );
+                record.offset = None;
+                // Keep the dead-letter topic readable even for records that failed the CRC check
+                record.crc_num = calc_crc32(&record.data);
+                record.header.push(Header {
+                    name: DEAD_LETTER_HEADER_CONNECTOR.to_string(),
+                    value: self.connector_name.clone(),
+                });
+                record.header.push(Header {
+                    name: DEAD_LETTER_HEADER_REASON.to_string(),
+                    value: reason,
+                });
+                record
+            })
+            .collect();
+
+        MessageStorage::new(self.message_storage.clone())
+            .append_topic_message(&topic.topic_id, records)
+            .await?;
+        self.connector_manager
+            .record_dead_letter(&self.connector_name, count);
+        Ok(())
+    }
+
+    fn report_send_error(&self, e: &MqttBrokerError) {
+        error!(
+            "Connector {} failed to write data to the target system, error message: {}",
+            self.connector_name, e
+        );
+        self.connector_manager
+            .record_failure(&self.connector_name, &e.to_string());
+    }
+}
+
+// Offset right after the given records, offsets may have gaps once records are deleted
+fn next_offset(records: &[Record], offset: u64) -> u64 {
+    match records.last().and_then(|record| record.offset) {
+        Some(last) => last + 1,
+        None => offset + records.len() as u64,
+    }
+}
+
+fn is_rejected(e: &MqttBrokerError) -> bool {
+    matches!(
+        e,
+        MqttBrokerError::ConnectorRecordRejected(_)
+            | MqttBrokerError::SerdeJsonError(_)
+            | MqttBrokerError::FromUtf8Error(_)
+    )
+}
+
+// Returns true when the connector was asked to stop while waiting
+async fn sleep_or_stop(recv: &mut broadcast::Receiver<bool>, duration: Duration) -> bool {
+    select! {
+        val = recv.recv() => matches!(val, Ok(true)),
+        _ = sleep(duration) => false,
+    }
+}
+
+// Returns true when the connector was asked to stop while waiting for new records
+async fn wait_for_write_or_stop(
+    recv: &mut broadcast::Receiver<bool>,
+    message_storage: &MessageStorage,
+    cursors: &[TopicCursor],
+    wait_time: Duration,
+) -> bool {
+    if cursors.is_empty() {
+        return sleep_or_stop(recv, wait_time).await;
+    }
+
+    let waits = cursors.iter().map(|cursor| {
+        Box::pin(async move {
+            if message_storage
+                .wait_for_topic_write(&cursor.topic_id, cursor.read_offset, wait_time)
+                .await
+                .is_err()
+            {
+                sleep(wait_time.min(Duration::from_millis(100))).await;
+            }
+        })
+    });
+    select! {
+        val = recv.recv() => matches!(val, Ok(true)),
+        _ = select_all(waits) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::tool::test_build_mqtt_cache_manager;
+    use common_base::tools::unique_id;
+    use common_config::{broker::init_broker_conf_by_config, config::BrokerConfig};
+    use metadata_struct::mqtt::topic::MQTTTopic;
+    use std::collections::HashMap;
+    use storage_adapter::storage::build_memory_storage_driver;
+
+    struct TestSink {
+        delivered: Vec<String>,
+        available: bool,
+        // Values failing with a transient error this many times before they are accepted
+        flaky: HashMap<String, u32>,
+    }
+
+    #[async_trait]
+    impl ConnectorSink for TestSink {
+        async fn send_batch(&mut self, records: &[Record]) -> ResultMqttBrokerError {
+            if !self.available {
+                return Err(MqttBrokerError::CommonError(
+                    "target unavailable".to_string(),
+                ));
+            }
+
+            let mut data = Vec::new();
+            for record in records {
+                let value = String::from_utf8(record.data.clone())?;
+                if value == "bad" {
+                    return Err(MqttBrokerError::ConnectorRecordRejected(value));
+                }
+                if let Some(failures) = self.flaky.get_mut(&value).filter(|n| **n > 0) {
+                    *failures -= 1;
+                    return Err(MqttBrokerError::CommonError("target busy".to_string()));
+                }
+                data.push(value);
+            }
+            self.delivered.extend(data);
+            Ok(())
+        }
+    }
+
+    fn test_policy(dead_letter_topic: Option<String>) -> ConnectorDeliveryPolicy {
+        ConnectorDeliveryPolicy {
+            max_retries: 1,
+            retry_min_interval_ms: 10,
+            retry_max_interval_ms: 20,
+            dead_letter_topic,
+        }
+    }
+
+    // Writes the values of every topic and runs the connector for a while. Without
+    // topic filters the connector is bound to the first topic.
+    async fn run_sink_for_a_while(
+        sink: TestSink,
+        policy: ConnectorDeliveryPolicy,
+        topic_filters: Vec<String>,
+        topics: &[(&str, &[&str])],
+    ) -> (TestSink, Arc<ConnectorManager>, ArcStorageAdapter, String) {
+        init_broker_conf_by_config(BrokerConfig {
+            cluster_name: unique_id(),
+            ..Default::default()
+        });
+
+        let storage_adapter = build_memory_storage_driver();
+        let message_storage = MessageStorage::new(storage_adapter.clone());
+        let cache_manager = test_build_mqtt_cache_manager();
+        for (topic_name, values) in topics {
+            let topic = MQTTTopic::new(
+                test_topic_id(topic_name),
+                "test".to_string(),
+                topic_name.to_string(),
+            );
+            cache_manager.add_topic(topic_name, &topic);
+
+            let records = values
+                .iter()
+                .map(|value| Record::build_byte(value.as_bytes().to_vec()))
+                .collect();
+            message_storage
+                .append_topic_message(&topic.topic_id, records)
+                .await
+                .unwrap();
+        }
+        if let Some(topic_name) = &policy.dead_letter_topic {
+            let topic = MQTTTopic::new(
+                test_topic_id(topic_name),
+                "test".to_string(),
+                topic_name.clone(),
+            );
+            cache_manager.add_topic(topic_name, &topic);
+        }
+
+        let topic_id = if topic_filters.is_empty() {
+            test_topic_id(topics[0].0)
+        } else {
+            String::new()
+        };
+
+        let connector_name = unique_id();
+        let connector_manager = Arc::new(ConnectorManager::new());
+        let (stop_send, _) = broadcast::channel(1);
+        let runtime = ConnectorRuntime::new(
+            connector_manager.clone(),
+            cache_manager.clone(),
+            cache_manager.client_pool.clone(),
+            storage_adapter.clone(),
+            connector_name.clone(),
+            policy,
+            stop_send.clone(),
+        );
+
+        let handle = tokio::spawn(async move {
+            let mut sink = sink;
+            runtime
+                .run(
+                    &mut sink,
+                    BridgePluginReadConfig {
+                        topic_id,
+                        topic_filters,
+                        record_num: 100,
+                    },
+                )
+                .await
+                .unwrap();
+            sink
+        });
+
+        sleep(Duration::from_millis(500)).await;
+        stop_send.send(true).unwrap();
+        let sink = handle.await.unwrap();
+        (sink, connector_manager, storage_adapter, connector_name)
+    }
+
+    fn test_topic_id(topic_name: &str) -> String {
+        topic_name.replace('/', "_")
+    }
+
+    #[test]
+    fn next_offset_test() {
+        let mut first = Record::build_byte(b"a".to_vec());
+        first.offset = Some(3);
+        let mut second = Record::build_byte(b"b".to_vec());
+        second.offset = Some(7);
+
+        assert_eq!(next_offset(&[], 5), 5);
+        assert_eq!(next_offset(&[first.clone(), second], 3), 8);
+        first.offset = None;
+        assert_eq!(next_offset(&[first], 3), 4);
+    }
+
+    #[tokio::test]
+    async fn rejected_record_goes_to_dead_letter_topic() {
+        let dead_letter_topic = format!("dlq/{}", unique_id());
+        let sink = TestSink {
+            delivered: Vec::new(),
+            available: true,
+            flaky: HashMap::new(),
+        };
+        let (sink, connector_manager, storage_adapter, connector_name) = run_sink_for_a_while(
+            sink,
+            test_policy(Some(dead_letter_topic.clone())),
+            Vec::new(),
+            &[("orders", &["a", "bad", "c"])],
+        )
+        .await;
+
+        assert_eq!(sink.delivered, vec!["a".to_string(), "c".to_string()]);
+        let metrics = connector_manager.get_connector_metrics(&connector_name);
+        assert_eq!(metrics.success_total, 2);
+        assert_eq!(metrics.dead_letter_total, 1);
+        assert_eq!(metrics.committed_offsets.get("orders"), Some(&3));
+        assert!(metrics.last_error.is_some());
+
+        let message_storage = MessageStorage::new(storage_adapter);
+        assert_eq!(
+            message_storage
+                .get_group_offset(&connector_name)
+                .await
+                .unwrap(),
+            3
+        );
+
+        let dead_letters = message_storage
+            .read_topic_message(&test_topic_id(&dead_letter_topic), 0, 10)
+            .await
+            .unwrap();
+        assert_eq!(dead_letters.len(), 1);
+        assert_eq!(dead_letters[0].data, b"bad".to_vec());
+        assert!(dead_letters[0].header.iter().any(|header| {
+            header.name == DEAD_LETTER_HEADER_CONNECTOR && header.value == connector_name
+        }));
+    }
+
+    #[tokio::test]
+    async fn transient_record_failure_is_retried_not_dead_lettered() {
+        let dead_letter_topic = format!("dlq/{}", unique_id());
+        // fails both batch attempts and the first attempt on its own
+        let sink = TestSink {
+            delivered: Vec::new(),
+            available: true,
+            flaky: HashMap::from([("b".to_string(), 3)]),
+        };
+        let (sink, connector_manager, _, connector_name) = run_sink_for_a_while(
+            sink,
+            test_policy(Some(dead_letter_topic)),
+            Vec::new(),
+            &[("orders", &["a", "b", "c"])],
+        )
+        .await;
+
+        assert_eq!(sink.delivered, vec!["a", "b", "c"]);
+        let metrics = connector_manager.get_connector_metrics(&connector_name);
+        assert_eq!(metrics.success_total, 3);
+        assert_eq!(metrics.dead_letter_total, 0);
+        assert_eq!(metrics.committed_offsets.get("orders"), Some(&3));
+    }
+
+    #[tokio::test]
+    async fn unavailable_target_keeps_offset() {
+        let sink = TestSink {
+            delivered: Vec::new(),
+            available: false,
+            flaky: HashMap::new(),
+        };
+        let (sink, connector_manager, storage_adapter, connector_name) = run_sink_for_a_while(
+            sink,
+            test_policy(None),
+            Vec::new(),
+            &[("orders", &["a", "b"])],
+        )
+        .await;
+
+        assert!(sink.delivered.is_empty());
+        let metrics = connector_manager.get_connector_metrics(&connector_name);
+        assert_eq!(metrics.success_total, 0);
+        assert_eq!(metrics.discard_total, 0);
+        assert!(metrics.failure_total > 0);
+
+        let message_storage = MessageStorage::new(storage_adapter);
+        assert_eq!(
+            message_storage
+                .get_group_offset(&connector_name)
+                .await
+                .unwrap(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn topic_filters_read_every_matching_topic() {
+        let dead_letter_topic = "factory/dlq/telemetry".to_string();
+        let sink = TestSink {
+            delivered: Vec::new(),
+            available: true,
+            flaky: HashMap::new(),
+        };
+        let (mut sink, connector_manager, storage_adapter, connector_name) = run_sink_for_a_while(
+            sink,
+            test_policy(Some(dead_letter_topic)),
+            vec!["factory/+/telemetry".to_string()],
+            &[
+                ("factory/1/telemetry", &["a", "b"]),
+                ("factory/2/telemetry", &["c"]),
+                ("factory/1/status", &["d"]),
+            ],
+        )
+        .await;
+
+        sink.delivered.sort();
+        assert_eq!(sink.delivered, vec!["a", "b", "c"]);
+
+        let metrics = connector_manager.get_connector_metrics(&connector_name);
+        assert_eq!(metrics.committed_offsets.len(), 2);
+        assert_eq!(
+            metrics.committed_offsets.get("factory_1_telemetry"),
+            Some(&2)
+        );
+        assert_eq!(
+            metrics.committed_offsets.get("factory_2_telemetry"),
+            Some(&1)
+        );
+
+        // Every topic keeps its own offset
+        let message_storage = MessageStorage::new(storage_adapter);
+        assert_eq!(
+            message_storage
+                .get_group_offset(&format!("{connector_name}_factory_2_telemetry"))
+                .await
+                .unwrap(),
+            1
+        );
+    }
+}
@@ -0,0 +1,301 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use axum::async_trait;
+use hmac::{Hmac, Mac};
+use metadata_struct::{
+    adapter::record::Record,
+    mqtt::{
+        bridge::config_webhook::{WebhookConnectorConfig, WebhookHttpMethod, WebhookStatusAction},
+        message::MqttMessage,
+    },
+};
+use reqwest::{header::CONTENT_TYPE, Client, Method};
+use serde_json::{json, Value};
+use sha2::Sha256;
+
+use crate::bridge::runtime::ConnectorSink;
+use crate::common::types::ResultMqttBrokerError;
+use crate::handler::error::MqttBrokerError;
+
+pub struct WebhookBridgePlugin {
+    config: WebhookConnectorConfig,
+    client: Option<Client>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct WebhookRequest {
+    url: String,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl WebhookBridgePlugin {
+    pub fn new(config: WebhookConnectorConfig) -> Self {
+        WebhookBridgePlugin {
+            config,
+            client: None,
+        }
+    }
+
+    async fn send_request(
+        &self,
+        client: &Client,
+        request: &WebhookRequest,
+    ) -> Result<u16, MqttBrokerError> {
+        let method = match self.config.method {
+            WebhookHttpMethod::Post => Method::POST,
+            WebhookHttpMethod::Put => Method::PUT,
+            WebhookHttpMethod::Patch => Method::PATCH,
+        };
+
+        let mut builder = client
+            .request(method, &request.url)
+            .body(request.body.clone());
+        if !request
+            .headers
+            .iter()
+            .any(|(name, _)| name.eq_ignore_ascii_case(CONTENT_TYPE.as_str()))
+        {
+            builder = builder.header(CONTENT_TYPE, "application/json");
+        }
+        for (name, value) in &request.headers {
+            builder = builder.header(name, value);
+        }
+        if let Some(secret) = &self.config.hmac_secret {
+            builder = builder.header(
+                self.config.signature_header(),
+                sign_body(secret, &request.body)?,
+            );
+        }
+
+        let res = builder.send().await?;
+        Ok(res.status().as_u16())
+    }
+}
+
+#[async_trait]
+impl ConnectorSink for WebhookBridgePlugin {
+    async fn init_sink(&mut self) -> ResultMqttBrokerError {
+        self.client = Some(
+            Client::builder()
+                .timeout(Duration::from_millis(self.config.timeout_ms()))
+                .build()?,
+        );
+        Ok(())
+    }
+
+    // Retryable failures are returned as errors so that the runtime retries the
+    // batch, other rejections are reported as rejected records.
+    async fn send_batch(&mut self, records: &[Record]) -> ResultMqttBrokerError {
+        let Some(client) = self.client.as_ref() else {
+            return Ok(());
+        };
+
+        let mut messages = Vec::with_capacity(records.len());
+        for record in records {
+            messages.push(MqttMessage::decode_record(record.clone())?);
+        }
+        let request = build_request(&self.config, &messages)?;
+
+        let status = self.send_request(client, &request).await?;
+        match self.config.status_action(status) {
+            WebhookStatusAction::Success => Ok(()),
+            WebhookStatusAction::Retry => Err(MqttBrokerError::CommonError(format!(
+                "webhook {} responded with status {}",
+                request.url, status
+            ))),
+            WebhookStatusAction::Reject => Err(MqttBrokerError::ConnectorRecordRejected(format!(
+                "webhook {} responded with status {}",
+                request.url, status
+            ))),
+        }
+    }
+
+    fn batch_size(&self) -> Option<u64> {
+        Some(self.config.batch_size())
+    }
+
+    fn batch_interval(&self) -> Duration {
+        Duration::from_millis(self.config.batch_interval_ms())
+    }
+}
+
+// URL and headers are rendered with the first message of the batch.
+fn build_request(
+    config: &WebhookConnectorConfig,
+    messages: &[MqttMessage],
+) -> Result<WebhookRequest, MqttBrokerError> {
+    let first = messages
+        .first()
+        .ok_or_else(|| MqttBrokerError::CommonError("webhook batch is empty".to_string()))?;
+
+    let mut headers: Vec<(String, String)> = config
+        .headers
+        .iter()
+        .map(|(name, value)| (name.clone(), render_template(value, first)))
+        .collect();
+    headers.sort();
+
+    let body = if config.batch_size() > 1 {
+        let items: Vec<Value> = messages
+            .iter()
+            .map(|message| {
+                let body = render_body(config, message);
+                serde_json::from_str(&body).unwrap_or(Value::String(body))
+            })
+            .collect();
+        serde_json::to_string(&items)?
+    } else {
+        render_body(config, first)
+    };
+
+    Ok(WebhookRequest {
+        url: render_template(&config.url, first),
+        headers,
+        body,
+    })
+}
+
+fn render_body(config: &WebhookConnectorConfig, message: &MqttMessage) -> String {
+    if let Some(template) = &config.body_template {
+        return render_template(template, message);
+    }
+
+    json!({
+        "topic": String::from_utf8_lossy(&message.topic),
+        "clientid": message.client_id,
+        "payload": String::from_utf8_lossy(&message.payload),
+        "qos": message.qos as u8,
+        "retain": message.retain,
+        "timestamp": message.create_time,
+    })
+    .to_string()
+}
+
+// Replaces ${topic}, ${clientid}, ${payload}, ${timestamp} and ${qos}. Unknown
+// placeholders are kept as they are, and substituted values are never expanded again.
+fn render_template(template: &str, message: &MqttMessage) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("${") {
+        result.push_str(&rest[..start]);
+        let Some(len) = rest[start..].find('}') else {
+            rest = &rest[start..];
+            break;
+        };
+
+        let placeholder = &rest[start..start + len + 1];
+        match &placeholder[2..len] {
+            "topic" => result.push_str(&String::from_utf8_lossy(&message.topic)),
+            "clientid" => result.push_str(&message.client_id),
+            "payload" => result.push_str(&String::from_utf8_lossy(&message.payload)),
+            "timestamp" => result.push_str(&message.create_time.to_string()),
+            "qos" => result.push_str(&(message.qos as u8).to_string()),
+            _ => result.push_str(placeholder),
+        }
+        rest = &rest[start + len + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+fn sign_body(secret: &str, body: &str) -> Result<String, MqttBrokerError> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .map_err(|e| MqttBrokerError::CommonError(e.to_string()))?;
+    mac.update(body.as_bytes());
+    Ok(hex::encode(mac.finalize().into_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use protocol::mqtt::common::QoS;
+    use std::collections::HashMap;
+
+    fn message(client_id: &str, payload: &str) -> MqttMessage {
+        MqttMessage {
+            client_id: client_id.to_string(),
+            qos: QoS::AtLeastOnce,
+            topic: Bytes::from("sensor/1"),
+            payload: Bytes::from(payload.to_string()),
+            create_time: 1700000000,
+            ..Default::default()
+        }
+    }
+
+    fn config(url: &str) -> WebhookConnectorConfig {
+        WebhookConnectorConfig {
+            url: url.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn render_template_test() {
+        let msg = message("c1", "${topic}");
+        assert_eq!(
+            render_template("/hook/${topic}?c=${clientid}&t=${timestamp}&q=${qos}", &msg),
+            "/hook/sensor/1?c=c1&t=1700000000&q=1"
+        );
+        // Payload is inserted verbatim and not expanded again
+        assert_eq!(render_template("p=${payload}", &msg), "p=${topic}");
+        assert_eq!(
+            render_template("${unknown}-${topic", &msg),
+            "${unknown}-${topic"
+        );
+    }
+
+    #[test]
+    fn build_request_test() {
+        let mut conf = config("http://127.0.0.1/hook/${clientid}");
+        conf.headers = HashMap::from([("X-Topic".to_string(), "${topic}".to_string())]);
+
+        let request = build_request(&conf, &[message("c1", "25.6")]).unwrap();
+        assert_eq!(request.url, "http://127.0.0.1/hook/c1");
+        assert_eq!(
+            request.headers,
+            vec![("X-Topic".to_string(), "sensor/1".to_string())]
+        );
+        let body: Value = serde_json::from_str(&request.body).unwrap();
+        assert_eq!(body["topic"], "sensor/1");
+        assert_eq!(body["payload"], "25.6");
+        assert_eq!(body["timestamp"], 1700000000);
+
+        conf.batch_size = Some(10);
+        conf.body_template = Some(r#"{"client": "${clientid}", "value": ${payload}}"#.to_string());
+        let request =
+            build_request(&conf, &[message("c1", "1"), message("c2", "not json")]).unwrap();
+        let body: Value = serde_json::from_str(&request.body).unwrap();
+        assert_eq!(body[0], json!({"client": "c1", "value": 1}));
+        assert_eq!(
+            body[1],
+            Value::String(r#"{"client": "c2", "value": not json}"#.to_string())
+        );
+
+        assert!(build_request(&conf, &[]).is_err());
+    }
+
+    #[test]
+    fn sign_body_test() {
+        // HMAC-SHA256 test vector from RFC 4231, test case 2
+        assert_eq!(
+            sign_body("Jefe", "what do ya want for nothing?").unwrap(),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+}
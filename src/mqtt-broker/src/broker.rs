@@ -18,17 +18,21 @@
 use crate::common::metrics_cache::{metrics_gc_thread, metrics_record_thread, MetricsCacheManager};
 use crate::common::types::ResultMqttBrokerError;
 use crate::handler::cache::MQTTCacheManager;
+use crate::handler::drain::{start_drain_thread, DrainContext};
 use crate::handler::dynamic_cache::load_metadata_cache;
 use crate::handler::flapping_detect::clean_flapping_detect;
 use crate::handler::keep_alive::ClientKeepAlive;
+use crate::handler::scheduled_publish::{start_scheduled_publish_thread, ScheduledPublishContext};
 use crate::handler::sub_parse_topic::start_parse_subscribe_by_new_topic_thread;
 use crate::handler::system_alarm::SystemAlarm;
 use crate::security::auth::super_user::init_system_user;
 use crate::security::storage::sync::sync_auth_storage_info;
 use crate::security::AuthDriver;
+use crate::server::listener::ListenerManager;
 use crate::server::{Server, TcpServerContext};
 use crate::subscribe::exclusive::ExclusivePush;
 use crate::subscribe::manager::SubscribeManager;
+use crate::subscribe::offline_queue::{start_offline_queue_thread, OfflineQueueManager};
 use crate::subscribe::share::follower::ShareFollowerResub;
 use crate::subscribe::share::leader::ShareLeaderPush;
 use crate::system_topic::SystemTopic;
@@ -37,6 +41,8 @@
 use common_config::broker::broker_config;
 use delay_message::{start_delay_message_manager, DelayMessageManager};
 use grpc_clients::pool::ClientPool;
+use message_expire::expire::mqtt_record_expire_at;
+use message_expire::{start_message_expire_thread, MessageExpireManager};
 use network_server::common::connection_manager::ConnectionManager;
 use schema_register::schema::SchemaRegisterManager;
 use std::sync::Arc;
@@ -58,6 +64,7 @@ pub struct MqttBrokerServerParams {
     pub metrics_cache_manager: Arc<MetricsCacheManager>,
     pub rocksdb_engine_handler: Arc<RocksDBEngine>,
     pub broker_cache: Arc<BrokerCacheManager>,
+    pub listener_manager: Arc<ListenerManager>,
 }
 
 pub struct MqttBrokerServer {
@@ -87,11 +94,13 @@ pub fn new(params: MqttBrokerServerParams, main_stop: broadcast::Sender<bool>) -
             message_storage_adapter: params.message_storage_adapter.clone(),
             delay_message_manager: params.delay_message_manager.clone(),
             schema_manager: params.schema_manager.clone(),
+            connector_manager: params.connector_manager.clone(),
             client_pool: params.client_pool.clone(),
             stop_sx: inner_stop.clone(),
             auth_driver: params.auth_driver.clone(),
             rocksdb_engine_handler: params.rocksdb_engine_handler.clone(),
             broker_cache: params.broker_cache.clone(),
+            listener_manager: params.listener_manager.clone(),
         }));
 
         MqttBrokerServer {
@@ -119,6 +128,8 @@ pub async fn start(&self) {
 
         self.start_delay_message_thread();
 
+        self.start_message_expire_thread();
+
         self.start_connector_thread();
 
         self.start_subscribe_push();
@@ -156,6 +167,18 @@ fn start_daemon_thread(&self) {
             clean_flapping_detect(cache_manager, stop_send).await;
         });
 
+        // drain and rebalance
+        let stop_send = self.inner_stop.clone();
+        let drain_context = DrainContext {
+            cache_manager: self.cache_manager.clone(),
+            client_pool: self.client_pool.clone(),
+            connection_manager: self.connection_manager.clone(),
+            subscribe_manager: self.subscribe_manager.clone(),
+        };
+        tokio::spawn(async move {
+            start_drain_thread(drain_context, stop_send).await;
+        });
+
         // observability
         let raw_stop_send = self.inner_stop.clone();
         let system_topic = SystemTopic::new(
@@ -213,9 +236,18 @@ fn start_server(&self) {
     fn start_connector_thread(&self) {
         let message_storage = self.message_storage_adapter.clone();
         let connector_manager = self.connector_manager.clone();
+        let cache_manager = self.cache_manager.clone();
+        let client_pool = self.client_pool.clone();
         let stop_send = self.inner_stop.clone();
         tokio::spawn(async move {
-            start_connector_thread(message_storage, connector_manager, stop_send).await;
+            start_connector_thread(
+                message_storage,
+                connector_manager,
+                cache_manager,
+                client_pool,
+                stop_send,
+            )
+            .await;
         });
     }
 
@@ -235,6 +267,20 @@ fn start_subscribe_push(&self) {
             .await;
         });
 
+        let offline_queue_manager = Arc::new(OfflineQueueManager::new(
+            self.message_storage_adapter.clone(),
+            self.cache_manager.clone(),
+            self.connection_manager.clone(),
+            self.subscribe_manager.clone(),
+            self.client_pool.clone(),
+        ));
+
+        let raw_offline_queue_manager = offline_queue_manager.clone();
+        let stop_send = self.inner_stop.clone();
+        tokio::spawn(async move {
+            start_offline_queue_thread(raw_offline_queue_manager, stop_send).await;
+        });
+
         let exclusive_sub = ExclusivePush::new(
             self.message_storage_adapter.clone(),
             self.cache_manager.clone(),
@@ -242,6 +288,7 @@ fn start_subscribe_push(&self) {
             self.connection_manager.clone(),
             self.metrics_cache_manager.clone(),
             self.rocksdb_engine_handler.clone(),
+            offline_queue_manager,
         );
 
         tokio::spawn(async move {
@@ -275,6 +322,13 @@ fn start_subscribe_push(&self) {
     fn start_delay_message_thread(&self) {
         let delay_message_manager = self.delay_message_manager.clone();
         let message_storage_adapter = self.message_storage_adapter.clone();
+        let scheduled_publish_context = ScheduledPublishContext {
+            cache_manager: self.cache_manager.clone(),
+            client_pool: self.client_pool.clone(),
+            message_storage_adapter: self.message_storage_adapter.clone(),
+            delay_message_manager: self.delay_message_manager.clone(),
+        };
+        let stop_send = self.inner_stop.clone();
         tokio::spawn(async move {
             let conf = broker_config();
             if let Err(e) = start_delay_message_manager(
@@ -287,9 +341,36 @@ fn start_delay_message_thread(&self) {
             {
                 panic!("{}", e.to_string());
             }
+
+            // Scheduled publishes are driven by the delay message engine
+            start_scheduled_publish_thread(scheduled_publish_context, stop_send).await;
         });
     }
 
+    fn start_message_expire_thread(&self) {
+        let conf = broker_config();
+        let message_expire_manager = Arc::new(MessageExpireManager::new(
+            conf.cluster_name.clone(),
+            self.message_storage_adapter.clone(),
+            mqtt_record_expire_at,
+        ));
+
+        // Each topic is stored in the shard named after its topic id, and is
+        // scanned by the one broker that owns it.
+        let cache_manager = self.cache_manager.clone();
+        let broker_id = conf.broker_id;
+        let shard_list = move || {
+            cache_manager
+                .topic_info
+                .iter()
+                .map(|topic| topic.topic_id.clone())
+                .filter(|topic_id| cache_manager.broker_cache.is_owner(broker_id, topic_id))
+                .collect()
+        };
+
+        start_message_expire_thread(message_expire_manager, shard_list, self.inner_stop.clone());
+    }
+
     async fn start_init(&self) {
         if let Err(e) = init_system_user(&self.cache_manager, &self.client_pool).await {
             panic!("{}", e);
@@ -0,0 +1,610 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::subscribe::common::is_match_sub_and_topic;
+use common_base::tools::now_second;
+use common_config::config::{DetailMetricsEviction, MqttDetailMetrics};
+use common_metrics::mqtt::detail::{
+    record_client_inflight_add, record_client_messages_dropped, record_client_messages_in,
+    record_client_messages_out, record_topic_inflight_add, record_topic_messages_dropped,
+    record_topic_messages_in, record_topic_messages_out, remove_client_metrics,
+    remove_topic_metrics,
+};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
+use std::sync::{Mutex, RwLock};
+
+// Topic names whose allowlist decision is cached, the cache is dropped once it grows past this
+const MAX_TOPIC_MATCH_CACHE_SIZE: usize = 100_000;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum DetailKind {
+    Topic,
+    Client,
+}
+
+#[derive(Default)]
+struct DetailMetricsEntry {
+    messages_in: AtomicU64,
+    messages_out: AtomicU64,
+    bytes_in: AtomicU64,
+    bytes_out: AtomicU64,
+    dropped: AtomicU64,
+    inflight: AtomicI64,
+    latency_count: AtomicU64,
+    latency_sum_ms: AtomicU64,
+    latency_max_ms: AtomicU64,
+    // Traffic seen, plus the score inherited from the entry it replaced under top_n eviction
+    score: AtomicU64,
+    // seconds
+    last_active: AtomicU64,
+}
+
+impl DetailMetricsEntry {
+    fn new(score: u64) -> Self {
+        DetailMetricsEntry {
+            score: AtomicU64::new(score),
+            last_active: AtomicU64::new(now_second()),
+            ..Default::default()
+        }
+    }
+
+    fn touch(&self) {
+        self.score.fetch_add(1, Ordering::Relaxed);
+        self.last_active.store(now_second(), Ordering::Relaxed);
+    }
+
+    fn eviction_key(&self, eviction: DetailMetricsEviction) -> u64 {
+        match eviction {
+            DetailMetricsEviction::Lru => self.last_active.load(Ordering::Relaxed),
+            DetailMetricsEviction::TopN => self.score.load(Ordering::Relaxed),
+        }
+    }
+
+    fn snapshot(&self, name: &str) -> DetailMetricsSnapshot {
+        let latency_count = self.latency_count.load(Ordering::Relaxed);
+        let avg_latency_ms = if latency_count == 0 {
+            0
+        } else {
+            self.latency_sum_ms.load(Ordering::Relaxed) / latency_count
+        };
+        DetailMetricsSnapshot {
+            name: name.to_string(),
+            messages_in: self.messages_in.load(Ordering::Relaxed),
+            messages_out: self.messages_out.load(Ordering::Relaxed),
+            bytes_in: self.bytes_in.load(Ordering::Relaxed),
+            bytes_out: self.bytes_out.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            inflight: self.inflight.load(Ordering::Relaxed),
+            avg_latency_ms,
+            max_latency_ms: self.latency_max_ms.load(Ordering::Relaxed),
+            last_active: self.last_active.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DetailMetricsSnapshot {
+    // topic name or client id
+    pub name: String,
+    pub messages_in: u64,
+    pub messages_out: u64,
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+    pub dropped: u64,
+    pub inflight: i64,
+    pub avg_latency_ms: u64,
+    pub max_latency_ms: u64,
+    pub last_active: u64,
+}
+
+// Tracked names ordered by their eviction key, the smallest on top. Updates on
+// the hot path do not touch the queue: an item whose entry has moved on since it
+// was pushed is pushed again with the current key when it reaches the top.
+#[derive(Default)]
+struct EvictionQueue {
+    heap: BinaryHeap<Reverse<(u64, String)>>,
+}
+
+impl EvictionQueue {
+    fn push(
+        &mut self,
+        map: &DashMap<String, DetailMetricsEntry>,
+        eviction: DetailMetricsEviction,
+        name: &str,
+        key: u64,
+    ) {
+        self.heap.push(Reverse((key, name.to_string())));
+        // Items of removed entries are only dropped when they reach the top
+        if self.heap.len() > map.len() * 2 + 16 {
+            self.rebuild(map, eviction);
+        }
+    }
+
+    // Pops the entry with the smallest key, returning its name and score
+    fn pop(
+        &mut self,
+        map: &DashMap<String, DetailMetricsEntry>,
+        eviction: DetailMetricsEviction,
+    ) -> Option<(String, u64)> {
+        while let Some(Reverse((key, name))) = self.heap.pop() {
+            let Some(entry) = map.get(&name) else {
+                continue;
+            };
+            let current = entry.eviction_key(eviction);
+            if current != key {
+                drop(entry);
+                self.heap.push(Reverse((current, name)));
+                continue;
+            }
+            return Some((name, entry.score.load(Ordering::Relaxed)));
+        }
+        None
+    }
+
+    fn rebuild(
+        &mut self,
+        map: &DashMap<String, DetailMetricsEntry>,
+        eviction: DetailMetricsEviction,
+    ) {
+        self.heap = map
+            .iter()
+            .map(|entry| Reverse((entry.eviction_key(eviction), entry.key().clone())))
+            .collect();
+    }
+}
+
+/// Per-topic and per-client metrics for the topics and clients on the
+/// configured allowlists. The number of tracked topics and clients is capped,
+/// once a cap is reached a new one replaces the least recently active or the
+/// least busy one, and the Prometheus series of the replaced one are removed.
+pub struct DetailMetricsManager {
+    enable: AtomicBool,
+    config: RwLock<MqttDetailMetrics>,
+    topics: DashMap<String, DetailMetricsEntry>,
+    clients: DashMap<String, DetailMetricsEntry>,
+    // New entries are admitted under these locks so that the caps hold
+    topic_queue: Mutex<EvictionQueue>,
+    client_queue: Mutex<EvictionQueue>,
+    topic_matches: DashMap<String, bool>,
+}
+
+impl Default for DetailMetricsManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DetailMetricsManager {
+    pub fn new() -> Self {
+        DetailMetricsManager {
+            enable: AtomicBool::new(false),
+            config: RwLock::new(MqttDetailMetrics::default()),
+            topics: DashMap::with_capacity(8),
+            clients: DashMap::with_capacity(8),
+            topic_queue: Mutex::new(EvictionQueue::default()),
+            client_queue: Mutex::new(EvictionQueue::default()),
+            topic_matches: DashMap::with_capacity(8),
+        }
+    }
+
+    pub fn get_config(&self) -> MqttDetailMetrics {
+        self.config.read().unwrap().clone()
+    }
+
+    // Applies a new config. Topics and clients no longer on the allowlists, or
+    // over the new caps, stop being tracked.
+    pub fn set_config(&self, config: MqttDetailMetrics) {
+        let enable = config.enable;
+        let eviction = config.eviction;
+        *self.config.write().unwrap() = config;
+        self.topic_matches.clear();
+        self.enable.store(enable, Ordering::Relaxed);
+
+        for kind in [DetailKind::Topic, DetailKind::Client] {
+            let map = self.map(kind);
+            let mut queue = self.queue(kind).lock().unwrap();
+            let names: Vec<String> = map.iter().map(|e| e.key().clone()).collect();
+            for name in names {
+                if !enable || !self.is_allowed(kind, &name) {
+                    self.remove(kind, &name);
+                }
+            }
+            // the eviction policy may have changed
+            queue.rebuild(map, eviction);
+            while map.len() > self.max_size(kind) {
+                if self.evict(kind, eviction, &mut queue).is_none() {
+                    break;
+                }
+            }
+        }
+    }
+
+    pub fn is_enable(&self) -> bool {
+        self.enable.load(Ordering::Relaxed)
+    }
+
+    pub fn record_message_in(&self, client_id: &str, topic: &str, bytes: u64) {
+        if !self.is_enable() {
+            return;
+        }
+        if self.with_entry(DetailKind::Topic, topic, |entry| {
+            entry.messages_in.fetch_add(1, Ordering::Relaxed);
+            entry.bytes_in.fetch_add(bytes, Ordering::Relaxed);
+        }) {
+            record_topic_messages_in(topic, bytes);
+        }
+        if self.with_entry(DetailKind::Client, client_id, |entry| {
+            entry.messages_in.fetch_add(1, Ordering::Relaxed);
+            entry.bytes_in.fetch_add(bytes, Ordering::Relaxed);
+        }) {
+            record_client_messages_in(client_id, bytes);
+        }
+    }
+
+    // A message delivered to a subscriber, the latency covers the acknowledgement of QoS 1/2
+    pub fn record_message_out(&self, client_id: &str, topic: &str, bytes: u64, latency_ms: u64) {
+        if !self.is_enable() {
+            return;
+        }
+        let record = |entry: &DetailMetricsEntry| {
+            entry.messages_out.fetch_add(1, Ordering::Relaxed);
+            entry.bytes_out.fetch_add(bytes, Ordering::Relaxed);
+            entry.latency_count.fetch_add(1, Ordering::Relaxed);
+            entry
+                .latency_sum_ms
+                .fetch_add(latency_ms, Ordering::Relaxed);
+            entry
+                .latency_max_ms
+                .fetch_max(latency_ms, Ordering::Relaxed);
+        };
+        if self.with_entry(DetailKind::Topic, topic, record) {
+            record_topic_messages_out(topic, bytes, latency_ms as f64);
+        }
+        if self.with_entry(DetailKind::Client, client_id, record) {
+            record_client_messages_out(client_id, bytes, latency_ms as f64);
+        }
+    }
+
+    pub fn record_dropped(&self, client_id: &str, topic: &str) {
+        if !self.is_enable() {
+            return;
+        }
+        let record = |entry: &DetailMetricsEntry| {
+            entry.dropped.fetch_add(1, Ordering::Relaxed);
+        };
+        if self.with_entry(DetailKind::Topic, topic, record) {
+            record_topic_messages_dropped(topic);
+        }
+        if self.with_entry(DetailKind::Client, client_id, record) {
+            record_client_messages_dropped(client_id);
+        }
+    }
+
+    // value is 1 when a QoS 1/2 message is sent and -1 once it is acknowledged or given up
+    pub fn record_inflight(&self, client_id: &str, topic: &str, value: i64) {
+        if !self.is_enable() {
+            return;
+        }
+        // Only existing entries are updated so that the increment and the
+        // decrement always land on the same series
+        if let Some(entry) = self.topics.get(topic) {
+            entry.inflight.fetch_add(value, Ordering::Relaxed);
+            record_topic_inflight_add(topic, value);
+        } else if value > 0
+            && self.with_entry(DetailKind::Topic, topic, |entry| {
+                entry.inflight.fetch_add(value, Ordering::Relaxed);
+            })
+        {
+            record_topic_inflight_add(topic, value);
+        }
+
+        if let Some(entry) = self.clients.get(client_id) {
+            entry.inflight.fetch_add(value, Ordering::Relaxed);
+            record_client_inflight_add(client_id, value);
+        } else if value > 0
+            && self.with_entry(DetailKind::Client, client_id, |entry| {
+                entry.inflight.fetch_add(value, Ordering::Relaxed);
+            })
+        {
+            record_client_inflight_add(client_id, value);
+        }
+    }
+
+    pub fn list_topics(&self) -> Vec<DetailMetricsSnapshot> {
+        self.topics
+            .iter()
+            .map(|entry| entry.value().snapshot(entry.key()))
+            .collect()
+    }
+
+    pub fn list_clients(&self) -> Vec<DetailMetricsSnapshot> {
+        self.clients
+            .iter()
+            .map(|entry| entry.value().snapshot(entry.key()))
+            .collect()
+    }
+
+    // Runs f on the entry of a tracked topic or client, starting to track it
+    // when it is on the allowlist. Returns false when it is not tracked.
+    fn with_entry(&self, kind: DetailKind, name: &str, f: impl Fn(&DetailMetricsEntry)) -> bool {
+        let map = self.map(kind);
+        if let Some(entry) = map.get(name) {
+            entry.touch();
+            f(&entry);
+            return true;
+        }
+
+        if !self.is_allowed(kind, name) {
+            return false;
+        }
+
+        let eviction = self.config.read().unwrap().eviction;
+        let max_size = self.max_size(kind);
+        let mut queue = self.queue(kind).lock().unwrap();
+        // another caller may have admitted it meanwhile
+        if let Some(entry) = map.get(name) {
+            entry.touch();
+            f(&entry);
+            return true;
+        }
+
+        // Under top_n a new entry inherits the score of the one it replaces, so
+        // that it has to outgrow the busiest ones to stay (space saving)
+        let mut score = 0;
+        if map.len() >= max_size {
+            match self.evict(kind, eviction, &mut queue) {
+                Some(victim_score) if eviction == DetailMetricsEviction::TopN => {
+                    score = victim_score
+                }
+                Some(_) => {}
+                None => return false,
+            }
+        }
+
+        let entry = DetailMetricsEntry::new(score);
+        entry.touch();
+        f(&entry);
+        let key = entry.eviction_key(eviction);
+        map.insert(name.to_string(), entry);
+        queue.push(map, eviction, name, key);
+        true
+    }
+
+    fn map(&self, kind: DetailKind) -> &DashMap<String, DetailMetricsEntry> {
+        match kind {
+            DetailKind::Topic => &self.topics,
+            DetailKind::Client => &self.clients,
+        }
+    }
+
+    fn queue(&self, kind: DetailKind) -> &Mutex<EvictionQueue> {
+        match kind {
+            DetailKind::Topic => &self.topic_queue,
+            DetailKind::Client => &self.client_queue,
+        }
+    }
+
+    fn max_size(&self, kind: DetailKind) -> usize {
+        let config = self.config.read().unwrap();
+        match kind {
+            DetailKind::Topic => config.max_topics,
+            DetailKind::Client => config.max_clients,
+        }
+    }
+
+    fn is_allowed(&self, kind: DetailKind, name: &str) -> bool {
+        match kind {
+            DetailKind::Topic => self.is_topic_allowed(name),
+            DetailKind::Client => {
+                let config = self.config.read().unwrap();
+                config
+                    .client_id_filters
+                    .iter()
+                    .any(|filter| is_match_client_id(filter, name))
+            }
+        }
+    }
+
+    fn is_topic_allowed(&self, topic: &str) -> bool {
+        if let Some(allowed) = self.topic_matches.get(topic) {
+            return *allowed;
+        }
+
+        let allowed = {
+            let config = self.config.read().unwrap();
+            config
+                .topic_filters
+                .iter()
+                .any(|filter| is_match_sub_and_topic(filter, topic).is_ok())
+        };
+        if self.topic_matches.len() >= MAX_TOPIC_MATCH_CACHE_SIZE {
+            self.topic_matches.clear();
+        }
+        self.topic_matches.insert(topic.to_string(), allowed);
+        allowed
+    }
+
+    // Stops tracking the entry with the smallest eviction key and returns its
+    // score, the caller holds the queue lock of the kind
+    fn evict(
+        &self,
+        kind: DetailKind,
+        eviction: DetailMetricsEviction,
+        queue: &mut EvictionQueue,
+    ) -> Option<u64> {
+        let (name, score) = queue.pop(self.map(kind), eviction)?;
+        self.remove(kind, &name);
+        Some(score)
+    }
+
+    fn remove(&self, kind: DetailKind, name: &str) {
+        if self.map(kind).remove(name).is_none() {
+            return;
+        }
+        match kind {
+            DetailKind::Topic => remove_topic_metrics(name),
+            DetailKind::Client => remove_client_metrics(name),
+        }
+    }
+}
+
+fn is_match_client_id(filter: &str, client_id: &str) -> bool {
+    match filter.strip_suffix('*') {
+        Some(prefix) => client_id.starts_with(prefix),
+        None => filter == client_id,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_config(eviction: DetailMetricsEviction) -> MqttDetailMetrics {
+        MqttDetailMetrics {
+            enable: true,
+            topic_filters: vec!["sensor/+/temp".to_string()],
+            client_id_filters: vec!["device-*".to_string(), "gateway".to_string()],
+            max_topics: 2,
+            max_clients: 2,
+            eviction,
+        }
+    }
+
+    #[test]
+    fn is_match_client_id_test() {
+        assert!(is_match_client_id("device-*", "device-1"));
+        assert!(is_match_client_id("*", "anything"));
+        assert!(is_match_client_id("gateway", "gateway"));
+        assert!(!is_match_client_id("gateway", "gateway-1"));
+        assert!(!is_match_client_id("device-*", "sensor-1"));
+    }
+
+    #[test]
+    fn allowlist_test() {
+        let manager = DetailMetricsManager::new();
+        manager.record_message_in("device-1", "sensor/1/temp", 10);
+        assert!(manager.list_topics().is_empty());
+
+        manager.set_config(build_config(DetailMetricsEviction::Lru));
+        manager.record_message_in("device-1", "sensor/1/temp", 10);
+        manager.record_message_in("other", "sensor/1/humidity", 10);
+        manager.record_message_out("gateway", "sensor/1/temp", 10, 8);
+        manager.record_message_out("gateway", "sensor/1/temp", 10, 2);
+
+        let topics = manager.list_topics();
+        assert_eq!(topics.len(), 1);
+        assert_eq!(topics[0].name, "sensor/1/temp");
+        assert_eq!(topics[0].messages_in, 1);
+        assert_eq!(topics[0].messages_out, 2);
+        assert_eq!(topics[0].bytes_out, 20);
+        assert_eq!(topics[0].avg_latency_ms, 5);
+        assert_eq!(topics[0].max_latency_ms, 8);
+
+        let mut clients: Vec<String> = manager.list_clients().into_iter().map(|c| c.name).collect();
+        clients.sort();
+        assert_eq!(clients, vec!["device-1", "gateway"]);
+
+        // Disabling drops every tracked topic and client
+        manager.set_config(MqttDetailMetrics::default());
+        assert!(manager.list_topics().is_empty());
+        assert!(manager.list_clients().is_empty());
+    }
+
+    #[test]
+    fn lru_eviction_test() {
+        let manager = DetailMetricsManager::new();
+        manager.set_config(build_config(DetailMetricsEviction::Lru));
+        manager.record_message_in("device-1", "sensor/1/temp", 1);
+        manager.record_message_in("device-2", "sensor/2/temp", 1);
+        // device-1 was active again later than device-2
+        manager
+            .clients
+            .get("device-1")
+            .unwrap()
+            .last_active
+            .store(now_second() + 10, Ordering::Relaxed);
+
+        manager.record_message_in("device-3", "sensor/3/temp", 1);
+        let mut clients: Vec<String> = manager.list_clients().into_iter().map(|c| c.name).collect();
+        clients.sort();
+        assert_eq!(clients, vec!["device-1", "device-3"]);
+        assert_eq!(manager.list_topics().len(), 2);
+    }
+
+    #[test]
+    fn top_n_eviction_test() {
+        let manager = DetailMetricsManager::new();
+        manager.set_config(build_config(DetailMetricsEviction::TopN));
+        for _ in 0..10 {
+            manager.record_message_in("device-busy", "sensor/1/temp", 1);
+        }
+        manager.record_message_in("device-quiet", "sensor/1/temp", 1);
+
+        // The quiet client makes room, the new one inherits its score
+        manager.record_message_in("device-new", "sensor/1/temp", 1);
+        let mut clients: Vec<String> = manager.list_clients().into_iter().map(|c| c.name).collect();
+        clients.sort();
+        assert_eq!(clients, vec!["device-busy", "device-new"]);
+        assert_eq!(
+            manager
+                .clients
+                .get("device-new")
+                .unwrap()
+                .score
+                .load(Ordering::Relaxed),
+            2
+        );
+    }
+
+    #[test]
+    fn concurrent_admission_test() {
+        let manager = std::sync::Arc::new(DetailMetricsManager::new());
+        manager.set_config(build_config(DetailMetricsEviction::TopN));
+
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let manager = manager.clone();
+                std::thread::spawn(move || {
+                    for j in 0..200 {
+                        manager.record_message_in(&format!("device-{i}-{j}"), "sensor/1/temp", 1);
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(manager.list_clients().len(), 2);
+        assert!(manager.client_queue.lock().unwrap().heap.len() <= 2 * 2 + 16);
+    }
+
+    #[test]
+    fn inflight_test() {
+        let manager = DetailMetricsManager::new();
+        manager.set_config(build_config(DetailMetricsEviction::Lru));
+        manager.record_inflight("device-1", "sensor/1/temp", 1);
+        manager.record_inflight("device-1", "sensor/1/temp", 1);
+        manager.record_inflight("device-1", "sensor/1/temp", -1);
+        assert_eq!(manager.list_clients()[0].inflight, 1);
+        assert_eq!(manager.list_topics()[0].inflight, 1);
+
+        // A decrement never starts tracking a client
+        manager.record_inflight("device-2", "sensor/1/temp", -1);
+        assert_eq!(manager.list_clients().len(), 1);
+    }
+}
@@ -12,7 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod detail_metrics;
 pub mod metrics_cache;
+pub mod packet_trace;
 pub mod pkid_manager;
 pub mod pkid_storage;
 pub mod tool;
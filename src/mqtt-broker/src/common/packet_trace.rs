@@ -0,0 +1,303 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::handler::error::MqttBrokerError;
+use crate::subscribe::common::is_match_sub_and_topic;
+use common_base::tools::{now_mills, unique_id};
+use dashmap::DashMap;
+use protocol::mqtt::common::{mqtt_packet_to_string, MqttPacket};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use tokio::sync::broadcast;
+
+pub const MAX_TRACE_DURATION_SEC: u64 = 600;
+pub const MAX_CONCURRENT_TRACE_NUM: usize = 16;
+// Events a slow trace consumer may fall behind before it starts losing them.
+const TRACE_CHANNEL_CAPACITY: usize = 1024;
+// Payloads are cut to this size in trace events.
+const MAX_TRACE_PAYLOAD_SIZE: usize = 1024;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TraceTarget {
+    ClientId(String),
+    // A topic filter, wildcards are allowed
+    Topic(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TraceDirection {
+    Inbound,
+    Outbound,
+}
+
+impl fmt::Display for TraceDirection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TraceDirection::Inbound => write!(f, "inbound"),
+            TraceDirection::Outbound => write!(f, "outbound"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PacketTraceEvent {
+    pub trace_id: String,
+    // milliseconds
+    pub time: u128,
+    pub client_id: String,
+    pub connect_id: u64,
+    pub direction: TraceDirection,
+    pub packet_type: String,
+    pub topic: Option<String>,
+    pub qos: Option<u8>,
+    pub retain: Option<bool>,
+    pub pkid: Option<u16>,
+    pub payload_size: Option<usize>,
+    pub payload: Option<String>,
+    pub filters: Vec<String>,
+}
+
+#[derive(Clone)]
+struct PacketTrace {
+    target: TraceTarget,
+    // milliseconds
+    expire_at: u128,
+    sender: broadcast::Sender<PacketTraceEvent>,
+}
+
+/// Short lived packet traces started from the admin api. Every traced packet
+/// handled by this broker is matched against the running traces.
+#[derive(Default)]
+pub struct PacketTraceManager {
+    traces: DashMap<String, PacketTrace>,
+}
+
+impl PacketTraceManager {
+    pub fn new() -> Self {
+        PacketTraceManager {
+            traces: DashMap::with_capacity(2),
+        }
+    }
+
+    pub fn start_trace(
+        &self,
+        target: TraceTarget,
+        duration_sec: u64,
+    ) -> Result<(String, broadcast::Receiver<PacketTraceEvent>), MqttBrokerError> {
+        self.remove_expired_trace(now_mills());
+        if self.traces.len() >= MAX_CONCURRENT_TRACE_NUM {
+            return Err(MqttBrokerError::TooManyPacketTraces(
+                MAX_CONCURRENT_TRACE_NUM,
+            ));
+        }
+
+        let duration_sec = duration_sec.clamp(1, MAX_TRACE_DURATION_SEC);
+        let (sender, receiver) = broadcast::channel(TRACE_CHANNEL_CAPACITY);
+        let trace_id = unique_id();
+        self.traces.insert(
+            trace_id.clone(),
+            PacketTrace {
+                target,
+                expire_at: now_mills() + (duration_sec as u128) * 1000,
+                sender,
+            },
+        );
+        Ok((trace_id, receiver))
+    }
+
+    pub fn stop_trace(&self, trace_id: &str) {
+        self.traces.remove(trace_id);
+    }
+
+    pub fn trace_num(&self) -> usize {
+        self.traces.len()
+    }
+
+    pub fn record(
+        &self,
+        client_id: &str,
+        connect_id: u64,
+        direction: TraceDirection,
+        packet: &MqttPacket,
+    ) {
+        if self.traces.is_empty() {
+            return;
+        }
+
+        let now = now_mills();
+        let mut expired = false;
+        for raw in self.traces.iter() {
+            if raw.expire_at <= now {
+                expired = true;
+                continue;
+            }
+
+            if !is_match_trace(&raw.target, client_id, packet) {
+                continue;
+            }
+
+            let mut event = build_trace_event(client_id, connect_id, direction, packet);
+            event.trace_id = raw.key().clone();
+            event.time = now;
+            // No receiver left means the trace is being stopped.
+            let _ = raw.sender.send(event);
+        }
+
+        if expired {
+            self.remove_expired_trace(now);
+        }
+    }
+
+    fn remove_expired_trace(&self, now: u128) {
+        self.traces.retain(|_, trace| trace.expire_at > now);
+    }
+}
+
+fn is_match_trace(target: &TraceTarget, client_id: &str, packet: &MqttPacket) -> bool {
+    match target {
+        TraceTarget::ClientId(id) => id == client_id,
+        TraceTarget::Topic(filter) => match packet {
+            MqttPacket::Publish(publish, _) => {
+                let topic = String::from_utf8_lossy(&publish.topic);
+                is_match_sub_and_topic(filter, &topic).is_ok()
+            }
+            MqttPacket::Subscribe(subscribe, _) => subscribe.filters.iter().any(|sub| {
+                sub.path == *filter || is_match_sub_and_topic(filter, &sub.path).is_ok()
+            }),
+            MqttPacket::Unsubscribe(unsubscribe, _) => unsubscribe
+                .filters
+                .iter()
+                .any(|path| path == filter || is_match_sub_and_topic(filter, path).is_ok()),
+            _ => false,
+        },
+    }
+}
+
+fn build_trace_event(
+    client_id: &str,
+    connect_id: u64,
+    direction: TraceDirection,
+    packet: &MqttPacket,
+) -> PacketTraceEvent {
+    let mut event = PacketTraceEvent {
+        trace_id: String::new(),
+        time: 0,
+        client_id: client_id.to_string(),
+        connect_id,
+        direction,
+        packet_type: mqtt_packet_to_string(packet),
+        topic: None,
+        qos: None,
+        retain: None,
+        pkid: None,
+        payload_size: None,
+        payload: None,
+        filters: Vec::new(),
+    };
+
+    match packet {
+        MqttPacket::Publish(publish, _) => {
+            event.topic = Some(String::from_utf8_lossy(&publish.topic).to_string());
+            event.qos = Some(publish.qos.into());
+            event.retain = Some(publish.retain);
+            event.pkid = Some(publish.p_kid);
+            event.payload_size = Some(publish.payload.len());
+            let end = publish.payload.len().min(MAX_TRACE_PAYLOAD_SIZE);
+            event.payload = Some(String::from_utf8_lossy(&publish.payload[..end]).to_string());
+        }
+        MqttPacket::PubAck(ack, _) => event.pkid = Some(ack.pkid),
+        MqttPacket::PubRec(rec, _) => event.pkid = Some(rec.pkid),
+        MqttPacket::PubRel(rel, _) => event.pkid = Some(rel.pkid),
+        MqttPacket::PubComp(comp, _) => event.pkid = Some(comp.pkid),
+        MqttPacket::Subscribe(subscribe, _) => {
+            event.pkid = Some(subscribe.packet_identifier);
+            event.filters = subscribe.filters.iter().map(|f| f.path.clone()).collect();
+        }
+        MqttPacket::Unsubscribe(unsubscribe, _) => {
+            event.pkid = Some(unsubscribe.pkid);
+            event.filters = unsubscribe.filters.clone();
+        }
+        _ => {}
+    }
+    event
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use protocol::mqtt::common::{PingReq, Publish, QoS};
+
+    fn publish(topic: &str) -> MqttPacket {
+        MqttPacket::Publish(
+            Publish {
+                topic: Bytes::from(topic.to_string()),
+                payload: Bytes::from("hello"),
+                qos: QoS::AtLeastOnce,
+                p_kid: 7,
+                ..Default::default()
+            },
+            None,
+        )
+    }
+
+    #[tokio::test]
+    async fn trace_by_topic_test() {
+        let manager = PacketTraceManager::new();
+        let (trace_id, mut receiver) = manager
+            .start_trace(TraceTarget::Topic("sensor/+".to_string()), 10)
+            .unwrap();
+
+        manager.record("c1", 1, TraceDirection::Inbound, &publish("other/t1"));
+        manager.record("c1", 1, TraceDirection::Inbound, &publish("sensor/t1"));
+        manager.record(
+            "c1",
+            1,
+            TraceDirection::Inbound,
+            &MqttPacket::PingReq(PingReq),
+        );
+
+        let event = receiver.try_recv().unwrap();
+        assert_eq!(event.trace_id, trace_id);
+        assert_eq!(event.topic, Some("sensor/t1".to_string()));
+        assert_eq!(event.pkid, Some(7));
+        assert_eq!(event.payload, Some("hello".to_string()));
+        assert!(receiver.try_recv().is_err());
+
+        manager.stop_trace(&trace_id);
+        assert_eq!(manager.trace_num(), 0);
+    }
+
+    #[tokio::test]
+    async fn trace_by_client_test() {
+        let manager = PacketTraceManager::new();
+        let (_, mut receiver) = manager
+            .start_trace(TraceTarget::ClientId("c1".to_string()), 10)
+            .unwrap();
+
+        manager.record("c2", 2, TraceDirection::Inbound, &publish("t1"));
+        manager.record(
+            "c1",
+            1,
+            TraceDirection::Outbound,
+            &MqttPacket::PingReq(PingReq),
+        );
+
+        let event = receiver.try_recv().unwrap();
+        assert_eq!(event.client_id, "c1");
+        assert_eq!(event.direction, TraceDirection::Outbound);
+        assert!(receiver.try_recv().is_err());
+    }
+}
@@ -141,6 +141,28 @@ pub fn get_client_pkid(&self, client_id: &str, pkid: u16) -> Option<ClientPkidDa
         None
     }
 
+    // Pkids of the QoS 2 publishes received from the client that still wait for their PUBREL.
+    pub fn list_client_pkid(&self, client_id: &str) -> Vec<u16> {
+        let prefix = format!("{client_id}_");
+        self.client_pkid_data
+            .iter()
+            .filter(|raw| raw.client_id == client_id)
+            .filter_map(|raw| raw.key().strip_prefix(&prefix)?.parse::<u16>().ok())
+            .collect()
+    }
+
+    pub fn ack_packet_count(&self, client_id: &str) -> usize {
+        let prefix = format!("{client_id}_");
+        self.qos_ack_packet
+            .iter()
+            .filter(|raw| {
+                raw.key()
+                    .strip_prefix(&prefix)
+                    .is_some_and(|pkid| pkid.parse::<u16>().is_ok())
+            })
+            .count()
+    }
+
     fn key(&self, client_id: &str, pkid: u16) -> String {
         format!("{client_id}_{pkid}")
     }
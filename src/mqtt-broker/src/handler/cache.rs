@@ -12,20 +12,26 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::common::detail_metrics::DetailMetricsManager;
+use crate::common::packet_trace::PacketTraceManager;
 use crate::common::pkid_manager::PkidManager;
+use crate::handler::drain::DrainManager;
 use crate::security::auth::metadata::AclMetadata;
 use broker_core::cache::BrokerCacheManager;
+use common_config::config::MqttListener;
 use dashmap::DashMap;
 use grpc_clients::pool::ClientPool;
 use metadata_struct::acl::mqtt_acl::MqttAcl;
 use metadata_struct::acl::mqtt_blacklist::MqttAclBlackList;
 use metadata_struct::mqtt::auto_subscribe_rule::MqttAutoSubscribeRule;
 use metadata_struct::mqtt::connection::MQTTConnection;
+use metadata_struct::mqtt::scheduled_publish::MqttScheduledPublish;
 use metadata_struct::mqtt::session::MqttSession;
 use metadata_struct::mqtt::topic::MQTTTopic;
 use metadata_struct::mqtt::topic_rewrite_rule::MqttTopicRewriteRule;
 use metadata_struct::mqtt::user::MqttUser;
 use protocol::mqtt::common::{MqttProtocol, PublishProperties};
+use rule_engine::engine::RuleEngine;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::sync::Arc;
@@ -116,11 +122,29 @@ pub struct MQTTCacheManager {
     // pkid manager
     pub pkid_metadata: PkidManager,
 
+    // packet traces started from the admin api
+    pub packet_trace: Arc<PacketTraceManager>,
+
+    // drain and rebalance of the connections on this broker
+    pub drain: Arc<DrainManager>,
+
+    // per-topic and per-client metrics of the allowlisted topics and clients
+    pub detail_metrics: Arc<DetailMetricsManager>,
+
     // All topic rewrite rule
     pub topic_rewrite_rule: DashMap<String, MqttTopicRewriteRule>,
 
     // All auto subscribe rule
     pub auto_subscribe_rule: DashMap<String, MqttAutoSubscribeRule>,
+
+    // (listener_name, Listener)
+    pub listener_info: DashMap<String, MqttListener>,
+
+    // SQL rules applied to published messages
+    pub rule_engine: Arc<RuleEngine>,
+
+    // (name, ScheduledPublish)
+    pub scheduled_publish: DashMap<String, MqttScheduledPublish>,
 }
 
 impl MQTTCacheManager {
@@ -136,8 +160,14 @@ pub fn new(client_pool: Arc<ClientPool>, broker_cache: Arc<BrokerCacheManager>)
             heartbeat_data: DashMap::with_capacity(8),
             acl_metadata: AclMetadata::new(),
             pkid_metadata: PkidManager::new(),
+            packet_trace: Arc::new(PacketTraceManager::new()),
+            drain: Arc::new(DrainManager::new()),
+            detail_metrics: Arc::new(DetailMetricsManager::new()),
             topic_rewrite_rule: DashMap::with_capacity(8),
             auto_subscribe_rule: DashMap::with_capacity(8),
+            listener_info: DashMap::with_capacity(8),
+            rule_engine: Arc::new(RuleEngine::new()),
+            scheduled_publish: DashMap::with_capacity(8),
         }
     }
 
@@ -183,6 +213,22 @@ pub fn retain_users(&self, usernames: HashSet<String>) {
             .retain(|username, _| usernames.contains(username));
     }
 
+    // listener
+    pub fn add_listener(&self, listener: MqttListener) {
+        self.listener_info.insert(listener.name.clone(), listener);
+    }
+
+    pub fn get_listener(&self, name: &str) -> Option<MqttListener> {
+        if let Some(listener) = self.listener_info.get(name) {
+            return Some(listener.clone());
+        }
+        None
+    }
+
+    pub fn del_listener(&self, name: &str) {
+        self.listener_info.remove(name);
+    }
+
     // connection
     pub fn add_connection(&self, connect_id: u64, conn: MQTTConnection) {
         if let Some(mut session) = self.session_info.get_mut(&conn.client_id) {
@@ -382,6 +428,29 @@ pub fn delete_auto_subscribe_rule(&self, cluster: &str, topic: &str) {
         let key = self.auto_subscribe_rule_key(cluster, topic);
         self.auto_subscribe_rule.remove(&key);
     }
+
+    // scheduled publish
+    pub fn set_scheduled_publishes(&self, schedules: Vec<MqttScheduledPublish>) {
+        self.scheduled_publish
+            .retain(|name, _| schedules.iter().any(|schedule| schedule.name == *name));
+        for schedule in schedules {
+            self.scheduled_publish
+                .insert(schedule.name.clone(), schedule);
+        }
+    }
+
+    pub fn get_scheduled_publish(&self, name: &str) -> Option<MqttScheduledPublish> {
+        self.scheduled_publish
+            .get(name)
+            .map(|schedule| schedule.clone())
+    }
+
+    pub fn list_scheduled_publishes(&self) -> Vec<MqttScheduledPublish> {
+        self.scheduled_publish
+            .iter()
+            .map(|schedule| schedule.value().clone())
+            .collect()
+    }
 }
 
 #[cfg(test)]
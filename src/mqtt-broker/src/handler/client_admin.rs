@@ -0,0 +1,398 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::cache::MQTTCacheManager;
+use super::connection::{disconnect_connection, send_disconnect_packet};
+use super::error::MqttBrokerError;
+use super::keep_alive::keep_live_time;
+use super::subscribe::{save_subscribe, SaveSubscribeContext};
+use super::unsubscribe::remove_subscribe;
+use crate::storage::message::MessageStorage;
+use crate::storage::session::SessionStorage;
+use crate::subscribe::exclusive::build_group_name;
+use crate::subscribe::manager::SubscribeManager;
+use common_config::broker::broker_config;
+use grpc_clients::pool::ClientPool;
+use network_server::common::connection_manager::ConnectionManager;
+use protocol::broker::broker_mqtt_admin::{
+    ClearSessionReply, ClearSessionRequest, InflightMessage, InspectClientReply,
+    InspectClientRequest, KickClientReply, KickClientRequest, QueuedSubscription,
+    SubscribeForClientReply, SubscribeForClientRequest, UnsubscribeForClientReply,
+    UnsubscribeForClientRequest,
+};
+use protocol::mqtt::common::{
+    qos, retain_forward_rule, Filter, MqttProtocol, Subscribe, Unsubscribe,
+};
+use protocol::mqtt::mqttv5::disconnect::reason;
+use std::sync::Arc;
+use storage_adapter::storage::ArcStorageAdapter;
+use tracing::info;
+
+// Upper bound of the messages counted as pending for one subscription.
+const MAX_PENDING_MESSAGE_COUNT: u64 = 1000;
+
+pub async fn kick_client_by_req(
+    cache_manager: &Arc<MQTTCacheManager>,
+    client_pool: &Arc<ClientPool>,
+    connection_manager: &Arc<ConnectionManager>,
+    subscribe_manager: &Arc<SubscribeManager>,
+    req: &KickClientRequest,
+) -> Result<KickClientReply, MqttBrokerError> {
+    if req.client_id.is_empty() {
+        return Err(MqttBrokerError::ClientIDIsEmpty);
+    }
+
+    let connect_id = kick_client(
+        cache_manager,
+        client_pool,
+        connection_manager,
+        subscribe_manager,
+        &req.client_id,
+        req.reason_code as u8,
+        &req.reason_string,
+        false,
+    )
+    .await?;
+    Ok(KickClientReply { connect_id })
+}
+
+pub async fn clear_session_by_req(
+    cache_manager: &Arc<MQTTCacheManager>,
+    client_pool: &Arc<ClientPool>,
+    connection_manager: &Arc<ConnectionManager>,
+    subscribe_manager: &Arc<SubscribeManager>,
+    req: &ClearSessionRequest,
+) -> Result<ClearSessionReply, MqttBrokerError> {
+    if req.client_id.is_empty() {
+        return Err(MqttBrokerError::ClientIDIsEmpty);
+    }
+
+    let was_connected = clear_session(
+        cache_manager,
+        client_pool,
+        connection_manager,
+        subscribe_manager,
+        &req.client_id,
+        0x98,
+        "Session cleared by administrator",
+    )
+    .await?;
+
+    info!(
+        "Session of client {} was cleared by the admin api",
+        req.client_id
+    );
+    Ok(ClearSessionReply { was_connected })
+}
+
+// Removes the subscriptions and the session of the client, kicking it with the given
+// reason when it is connected. Returns whether the client was connected.
+pub async fn clear_session(
+    cache_manager: &Arc<MQTTCacheManager>,
+    client_pool: &Arc<ClientPool>,
+    connection_manager: &Arc<ConnectionManager>,
+    subscribe_manager: &Arc<SubscribeManager>,
+    client_id: &str,
+    reason_code: u8,
+    reason_string: &str,
+) -> Result<bool, MqttBrokerError> {
+    let paths: Vec<String> = subscribe_manager
+        .list_subscribe_by_client_id(client_id)
+        .into_iter()
+        .map(|raw| raw.path)
+        .collect();
+    if !paths.is_empty() {
+        let unsubscribe = Unsubscribe {
+            pkid: 0,
+            filters: paths,
+        };
+        remove_subscribe(client_id, &unsubscribe, client_pool, subscribe_manager).await?;
+    }
+
+    let was_connected = cache_manager.get_connect_id(client_id).is_some();
+    if was_connected {
+        kick_client(
+            cache_manager,
+            client_pool,
+            connection_manager,
+            subscribe_manager,
+            client_id,
+            reason_code,
+            reason_string,
+            true,
+        )
+        .await?;
+    } else {
+        let session_storage = SessionStorage::new(client_pool.clone());
+        if session_storage
+            .get_session(client_id.to_owned())
+            .await?
+            .is_none()
+        {
+            return Err(MqttBrokerError::SessionDoesNotExist);
+        }
+        session_storage.delete_session(client_id.to_owned()).await?;
+        cache_manager.remove_session(client_id);
+        subscribe_manager.remove_client_id(client_id);
+    }
+    cache_manager.pkid_metadata.remove_by_client_id(client_id);
+    Ok(was_connected)
+}
+
+pub async fn inspect_client_by_req(
+    cache_manager: &Arc<MQTTCacheManager>,
+    connection_manager: &Arc<ConnectionManager>,
+    subscribe_manager: &Arc<SubscribeManager>,
+    message_storage_adapter: &ArcStorageAdapter,
+    req: &InspectClientRequest,
+) -> Result<InspectClientReply, MqttBrokerError> {
+    if req.client_id.is_empty() {
+        return Err(MqttBrokerError::ClientIDIsEmpty);
+    }
+
+    let mut reply = InspectClientReply {
+        client_id: req.client_id.clone(),
+        broker_id: broker_config().broker_id,
+        ..Default::default()
+    };
+
+    if let Some(connect_id) = cache_manager.get_connect_id(&req.client_id) {
+        reply.connected = true;
+        reply.connect_id = connect_id;
+        if let Some(connection) = cache_manager.get_connection(connect_id) {
+            reply.source_addr = connection.source_ip_addr.clone();
+            reply.keep_alive = connection.keep_alive as u32;
+            reply.receive_maximum = connection.client_max_receive_maximum as u32;
+        }
+        if let Some(protocol) = connection_manager
+            .get_connect(connect_id)
+            .and_then(|network| network.protocol)
+        {
+            reply.protocol = protocol.to_mqtt().into();
+        }
+    } else if cache_manager.get_session_info(&req.client_id).is_none() {
+        return Err(MqttBrokerError::SessionDoesNotExist);
+    }
+
+    if let Some(live_time) = cache_manager.heartbeat_data.get(&req.client_id) {
+        reply.last_heartbeat_time = live_time.heartbeat;
+        reply.keep_alive_deadline =
+            live_time.heartbeat + keep_live_time(live_time.keep_live) as u64;
+    }
+
+    reply.inflight_messages = inflight_messages(cache_manager, &req.client_id);
+
+    let message_storage = MessageStorage::new(message_storage_adapter.clone());
+    for (key, subscriber) in subscribe_manager.exclusive_push.clone() {
+        if subscriber.client_id != req.client_id {
+            continue;
+        }
+
+        let group_name = build_group_name(&subscriber);
+        let committed_offset = message_storage.get_group_offset(&group_name).await?;
+        let pending_messages = message_storage
+            .read_topic_message(
+                &subscriber.topic_id,
+                committed_offset,
+                MAX_PENDING_MESSAGE_COUNT,
+            )
+            .await?
+            .len() as u64;
+
+        let mut queued = QueuedSubscription {
+            sub_path: subscriber.sub_path.clone(),
+            topic_name: subscriber.topic_name.clone(),
+            committed_offset,
+            pending_messages,
+            ..Default::default()
+        };
+        if let Some(thread) = subscribe_manager.exclusive_push_thread.get(&key) {
+            queued.push_success_record_num = thread.push_success_record_num;
+            queued.push_error_record_num = thread.push_error_record_num;
+            queued.last_push_time = thread.last_push_time;
+        }
+        reply.queued_subscriptions.push(queued);
+    }
+
+    Ok(reply)
+}
+
+pub async fn subscribe_for_client_by_req(
+    cache_manager: &Arc<MQTTCacheManager>,
+    client_pool: &Arc<ClientPool>,
+    connection_manager: &Arc<ConnectionManager>,
+    subscribe_manager: &Arc<SubscribeManager>,
+    req: &SubscribeForClientRequest,
+) -> Result<SubscribeForClientReply, MqttBrokerError> {
+    if req.client_id.is_empty() {
+        return Err(MqttBrokerError::ClientIDIsEmpty);
+    }
+
+    if cache_manager.get_session_info(&req.client_id).is_none() {
+        return Err(MqttBrokerError::SessionDoesNotExist);
+    }
+
+    let Some(qos) = qos(req.qos as u8) else {
+        return Err(MqttBrokerError::InvalidSubscribeOption(format!(
+            "qos {}",
+            req.qos
+        )));
+    };
+    let Some(retain_handling) = retain_forward_rule(req.retained_handling as u8) else {
+        return Err(MqttBrokerError::InvalidSubscribeOption(format!(
+            "retained handling {}",
+            req.retained_handling
+        )));
+    };
+
+    let protocol = cache_manager
+        .get_connect_id(&req.client_id)
+        .and_then(|connect_id| connection_manager.get_connect(connect_id))
+        .and_then(|network| network.protocol)
+        .map(|protocol| protocol.to_mqtt())
+        .unwrap_or(MqttProtocol::Mqtt5);
+
+    save_subscribe(SaveSubscribeContext {
+        client_id: req.client_id.clone(),
+        protocol,
+        client_pool: client_pool.clone(),
+        cache_manager: cache_manager.clone(),
+        subscribe_manager: subscribe_manager.clone(),
+        subscribe: Subscribe {
+            packet_identifier: 0,
+            filters: vec![Filter {
+                path: req.path.clone(),
+                qos,
+                nolocal: req.no_local,
+                preserve_retain: req.retain_as_published,
+                retain_handling,
+            }],
+        },
+        subscribe_properties: None,
+    })
+    .await?;
+    Ok(SubscribeForClientReply {})
+}
+
+pub async fn unsubscribe_for_client_by_req(
+    client_pool: &Arc<ClientPool>,
+    subscribe_manager: &Arc<SubscribeManager>,
+    req: &UnsubscribeForClientRequest,
+) -> Result<UnsubscribeForClientReply, MqttBrokerError> {
+    if req.client_id.is_empty() {
+        return Err(MqttBrokerError::ClientIDIsEmpty);
+    }
+
+    if subscribe_manager
+        .get_subscribe(&req.client_id, &req.path)
+        .is_none()
+    {
+        return Err(MqttBrokerError::SubscriptionNotFound(
+            req.client_id.clone(),
+            req.path.clone(),
+        ));
+    }
+
+    let unsubscribe = Unsubscribe {
+        pkid: 0,
+        filters: vec![req.path.clone()],
+    };
+    remove_subscribe(&req.client_id, &unsubscribe, client_pool, subscribe_manager).await?;
+    Ok(UnsubscribeForClientReply {})
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn kick_client(
+    cache_manager: &Arc<MQTTCacheManager>,
+    client_pool: &Arc<ClientPool>,
+    connection_manager: &Arc<ConnectionManager>,
+    subscribe_manager: &Arc<SubscribeManager>,
+    client_id: &str,
+    reason_code: u8,
+    reason_string: &str,
+    delete_session: bool,
+) -> Result<u64, MqttBrokerError> {
+    let Some(connect_id) = cache_manager.get_connect_id(client_id) else {
+        return Err(MqttBrokerError::ClientNoAvailableConnection(
+            client_id.to_string(),
+        ));
+    };
+    let code = reason(reason_code)?;
+
+    send_disconnect_packet(connection_manager, connect_id, code, reason_string, None).await;
+
+    disconnect_connection(
+        client_id,
+        connect_id,
+        cache_manager,
+        client_pool,
+        connection_manager,
+        subscribe_manager,
+        delete_session,
+    )
+    .await?;
+    cache_manager.remove_heartbeat(client_id);
+
+    info!(
+        "Client {} (connection {}) was kicked by the admin api, reason code {:?}",
+        client_id, connect_id, code
+    );
+    Ok(connect_id)
+}
+
+fn inflight_messages(
+    cache_manager: &Arc<MQTTCacheManager>,
+    client_id: &str,
+) -> Vec<InflightMessage> {
+    let prefix = format!("{client_id}_");
+    let mut results = Vec::new();
+    for raw in cache_manager.pkid_metadata.qos_ack_packet.iter() {
+        if let Some(pkid) = parse_pkid(raw.key(), &prefix) {
+            results.push(InflightMessage {
+                pkid,
+                direction: "outbound".to_string(),
+                create_time: (raw.create_time / 1000) as u64,
+            });
+        }
+    }
+    for raw in cache_manager.pkid_metadata.client_pkid_data.iter() {
+        if let Some(pkid) = parse_pkid(raw.key(), &prefix) {
+            results.push(InflightMessage {
+                pkid,
+                direction: "inbound".to_string(),
+                create_time: raw.create_time,
+            });
+        }
+    }
+    results.sort_by_key(|message| message.pkid);
+    results
+}
+
+// Keys of the pkid caches are "{client_id}_{pkid}", a client id may itself contain '_'.
+fn parse_pkid(key: &str, prefix: &str) -> Option<u32> {
+    key.strip_prefix(prefix)?.parse::<u32>().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_pkid;
+
+    #[test]
+    fn parse_pkid_test() {
+        assert_eq!(parse_pkid("c1_12", "c1_"), Some(12));
+        assert_eq!(parse_pkid("c1_x_12", "c1_"), None);
+        assert_eq!(parse_pkid("c1_x_12", "c1_x_"), Some(12));
+        assert_eq!(parse_pkid("c2_12", "c1_"), None);
+    }
+}
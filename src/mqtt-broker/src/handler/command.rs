@@ -14,6 +14,8 @@
 
 use super::flow_control::is_qos_message;
 use super::mqtt::{MqttService, MqttServiceConnectContext, MqttServiceContext};
+use crate::bridge::manager::ConnectorManager;
+use crate::common::packet_trace::TraceDirection;
 use crate::handler::cache::MQTTCacheManager;
 use crate::handler::connection::disconnect_connection;
 use crate::handler::response::{
@@ -72,6 +74,7 @@ pub struct CommandContext {
     pub client_pool: Arc<ClientPool>,
     pub connection_manager: Arc<ConnectionManager>,
     pub schema_manager: Arc<SchemaRegisterManager>,
+    pub connector_manager: Arc<ConnectorManager>,
     pub auth_driver: Arc<AuthDriver>,
     pub rocksdb_engine_handler: Arc<RocksDBEngine>,
     pub broker_cache: Arc<BrokerCacheManager>,
@@ -102,6 +105,8 @@ async fn apply(
             ));
         }
 
+        let trace_client_id = self.trace_client_id(tcp_connection.connection_id);
+
         let resp_package = match packet.clone() {
             MqttPacket::Connect(
                 protocol_version,
@@ -179,6 +184,13 @@ async fn apply(
             }
         };
 
+        self.record_packet_trace(
+            tcp_connection.connection_id,
+            trace_client_id,
+            &packet,
+            &resp_package,
+        );
+
         if let Some(pkg) = resp_package.clone() {
             if let MqttPacket::Disconnect(_, _) = pkg.packet.get_mqtt_packet().unwrap() {
                 if let Some(connection) = self
@@ -211,6 +223,48 @@ async fn apply(
     }
 }
 impl MQTTHandlerCommand {
+    fn trace_client_id(&self, connect_id: u64) -> Option<String> {
+        if self.cache_manager.packet_trace.trace_num() == 0 {
+            return None;
+        }
+        self.cache_manager
+            .get_connection(connect_id)
+            .map(|connection| connection.client_id)
+    }
+
+    fn record_packet_trace(
+        &self,
+        connect_id: u64,
+        client_id: Option<String>,
+        packet: &MqttPacket,
+        resp_package: &Option<ResponsePackage>,
+    ) {
+        let packet_trace = &self.cache_manager.packet_trace;
+        if packet_trace.trace_num() == 0 {
+            return;
+        }
+
+        // The client id of a CONNECT is only known once the connection is registered.
+        let client_id = client_id.or_else(|| {
+            self.cache_manager
+                .get_connection(connect_id)
+                .map(|connection| connection.client_id)
+        });
+        let Some(client_id) = client_id.or_else(|| match packet {
+            MqttPacket::Connect(_, connect, _, _, _, _) => Some(connect.client_id.clone()),
+            _ => None,
+        }) else {
+            return;
+        };
+
+        packet_trace.record(&client_id, connect_id, TraceDirection::Inbound, packet);
+        if let Some(pkg) = resp_package {
+            if let Some(resp) = pkg.packet.get_mqtt_packet() {
+                packet_trace.record(&client_id, connect_id, TraceDirection::Outbound, &resp);
+            }
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub async fn process_connect(
         &self,
@@ -235,6 +289,8 @@ pub async fn process_connect(
                 last_will_properties: last_will_properties.clone(),
                 login: login.clone(),
                 addr: *addr,
+                listener: tcp_connection.listener.clone(),
+                client_cert_verified: tcp_connection.is_client_cert_verified(),
             };
             Some(self.mqtt3_service.connect(connect_context).await)
         } else if is_mqtt4(protocol_version.to_owned()) {
@@ -246,6 +302,8 @@ pub async fn process_connect(
                 last_will_properties: last_will_properties.clone(),
                 login: login.clone(),
                 addr: *addr,
+                listener: tcp_connection.listener.clone(),
+                client_cert_verified: tcp_connection.is_client_cert_verified(),
             };
             Some(self.mqtt4_service.connect(connect_context).await)
         } else if is_mqtt5(protocol_version.to_owned()) {
@@ -257,6 +315,8 @@ pub async fn process_connect(
                 last_will_properties: last_will_properties.clone(),
                 login: login.clone(),
                 addr: *addr,
+                listener: tcp_connection.listener.clone(),
+                client_cert_verified: tcp_connection.is_client_cert_verified(),
             };
             Some(self.mqtt5_service.connect(connect_context).await)
         } else {
@@ -673,6 +733,7 @@ pub fn new(context: CommandContext) -> Self {
             delay_message_manager: context.delay_message_manager.clone(),
             subscribe_manager: context.subscribe_manager.clone(),
             schema_manager: context.schema_manager.clone(),
+            connector_manager: context.connector_manager.clone(),
             client_pool: context.client_pool.clone(),
             auth_driver: context.auth_driver.clone(),
             rocksdb_engine_handler: context.rocksdb_engine_handler.clone(),
@@ -686,6 +747,7 @@ pub fn new(context: CommandContext) -> Self {
             delay_message_manager: context.delay_message_manager.clone(),
             subscribe_manager: context.subscribe_manager.clone(),
             schema_manager: context.schema_manager.clone(),
+            connector_manager: context.connector_manager.clone(),
             client_pool: context.client_pool.clone(),
             auth_driver: context.auth_driver.clone(),
             rocksdb_engine_handler: context.rocksdb_engine_handler.clone(),
@@ -699,6 +761,7 @@ pub fn new(context: CommandContext) -> Self {
             delay_message_manager: context.delay_message_manager.clone(),
             subscribe_manager: context.subscribe_manager.clone(),
             schema_manager: context.schema_manager.clone(),
+            connector_manager: context.connector_manager.clone(),
             client_pool: context.client_pool.clone(),
             auth_driver: context.auth_driver.clone(),
             rocksdb_engine_handler: context.rocksdb_engine_handler.clone(),
@@ -12,18 +12,32 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashSet;
 use std::net::SocketAddr;
 use std::sync::Arc;
 
+use axum::extract::ws::Message;
+use bytes::BytesMut;
 use common_base::tools::{now_second, unique_id};
+use common_config::broker::broker_config;
 use common_config::config::BrokerConfig;
+use grpc_clients::mqtt::inner::call::broker_mqtt_takeover_session;
 use grpc_clients::pool::ClientPool;
 use metadata_struct::mqtt::connection::{ConnectionConfig, MQTTConnection};
+use metadata_struct::mqtt::lastwill::LastWillData;
 use network_server::common::connection_manager::ConnectionManager;
-use protocol::mqtt::common::{Connect, ConnectProperties, DisconnectReasonCode, MqttProtocol};
+use protocol::broker::broker_mqtt_session::{TakeoverSessionReply, TakeoverSessionRequest};
+use protocol::mqtt::common::{
+    Connect, ConnectProperties, DisconnectReasonCode, MqttPacket, MqttProtocol, Subscribe,
+    Unsubscribe,
+};
+use protocol::robust::RobustMQPacketWrapper;
 
 use super::cache::MQTTCacheManager;
+use super::error::MqttBrokerError;
 use super::keep_alive::client_keep_live_time;
+use super::subscribe::{save_subscribe, SaveSubscribeContext};
+use super::unsubscribe::remove_subscribe;
 use crate::common::types::ResultMqttBrokerError;
 use crate::handler::flow_control::is_connection_rate_exceeded;
 use crate::handler::response::response_packet_mqtt_distinct_by_reason;
@@ -34,7 +48,7 @@
 use tokio::io::{AsyncWrite, AsyncWriteExt, WriteHalf};
 use tokio::net::TcpStream;
 use tokio_util::codec::FramedWrite;
-use tracing::{error, warn};
+use tracing::{error, info, warn};
 
 pub const REQUEST_RESPONSE_PREFIX_NAME: &str = "/sys/request_response/";
 pub const DISCONNECT_FLAG_NOT_DELETE_SESSION: &str = "DISCONNECT_FLAG_NOT_DELETE_SESSION";
@@ -152,6 +166,282 @@ pub async fn disconnect_connection(
     Ok(())
 }
 
+// Sends a DISCONNECT with the given reason before the connection is closed, clients that
+// do not support it (MQTT 3) only see the connection closed.
+pub async fn send_disconnect_packet(
+    connection_manager: &Arc<ConnectionManager>,
+    connect_id: u64,
+    code: DisconnectReasonCode,
+    reason_string: &str,
+    server_reference: Option<String>,
+) {
+    let Some(network) = connection_manager.get_connect(connect_id) else {
+        return;
+    };
+    let Some(protocol) = network.protocol.clone() else {
+        return;
+    };
+
+    let mut packet = response_packet_mqtt_distinct_by_reason(&protocol.to_mqtt(), Some(code));
+    if let MqttPacket::Disconnect(_, Some(properties)) = &mut packet {
+        if !reason_string.is_empty() {
+            properties.reason_string = Some(reason_string.to_string());
+        }
+        properties.server_reference = server_reference;
+    }
+    let wrap = MqttPacketWrapper {
+        protocol_version: protocol.to_u8(),
+        packet,
+    };
+
+    // The connection is closed right after, a failed write does not stop the disconnect.
+    if network.is_tcp() {
+        let _ = connection_manager
+            .write_tcp_frame(connect_id, RobustMQPacketWrapper::from_mqtt(wrap))
+            .await;
+    } else if network.is_quic() {
+        let _ = connection_manager
+            .write_quic_frame(connect_id, RobustMQPacketWrapper::from_mqtt(wrap))
+            .await;
+    } else {
+        let mut codec = MqttCodec::new(Some(protocol.to_u8()));
+        let mut buff = BytesMut::new();
+        if codec.encode_data(wrap.clone(), &mut buff).is_ok() {
+            let _ = connection_manager
+                .write_websocket_frame(
+                    connect_id,
+                    RobustMQPacketWrapper::from_mqtt(wrap),
+                    Message::Binary(buff.to_vec()),
+                )
+                .await;
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct TakeoverSessionContext {
+    pub client_id: String,
+    pub connect_id: u64,
+    pub resume_session: bool,
+    pub has_last_will: bool,
+    pub cache_manager: Arc<MQTTCacheManager>,
+    pub client_pool: Arc<ClientPool>,
+    pub connection_manager: Arc<ConnectionManager>,
+    pub subscribe_manager: Arc<SubscribeManager>,
+}
+
+// Called when a client connects, before its session is saved. An older connection with the
+// same client id is kicked with "Session taken over", on this broker directly and on any
+// other broker through its session service. A resumed session keeps its subscriptions, which
+// are now pushed from this broker, and the inbound QoS 2 state; a discarded one loses its
+// subscriptions. The will of the old connection is replaced by the one of the new connection.
+pub async fn takeover_session(context: &TakeoverSessionContext) -> ResultMqttBrokerError {
+    // The session cache is shared by all brokers, it tells where the client was connected.
+    let Some(session) = context.cache_manager.get_session_info(&context.client_id) else {
+        return Ok(());
+    };
+    let conf = broker_config();
+
+    if let Some(connect_id) = close_taken_over_connection(
+        &context.cache_manager,
+        &context.connection_manager,
+        &context.client_id,
+    )
+    .await
+    {
+        info!(
+            "Connection {} of client {} was taken over by connection {}",
+            connect_id, context.client_id, context.connect_id
+        );
+        if !context.resume_session {
+            context
+                .cache_manager
+                .pkid_metadata
+                .remove_by_client_id(&context.client_id);
+        }
+    }
+
+    // The broker of a connected session, plus the brokers still pushing to its subscriptions
+    // after the client went offline there.
+    let mut broker_ids: HashSet<u64> = context
+        .subscribe_manager
+        .list_subscribe_by_client_id(&context.client_id)
+        .iter()
+        .map(|raw| raw.broker_id)
+        .collect();
+    broker_ids.extend(session.broker_id);
+    broker_ids.remove(&conf.broker_id);
+    for broker_id in broker_ids {
+        takeover_remote_session(context, broker_id).await;
+    }
+
+    let subscribes = context
+        .subscribe_manager
+        .list_subscribe_by_client_id(&context.client_id);
+    if context.resume_session {
+        for subscribe in subscribes {
+            if subscribe.broker_id == conf.broker_id {
+                continue;
+            }
+            save_subscribe(SaveSubscribeContext {
+                client_id: context.client_id.clone(),
+                protocol: subscribe.protocol.clone(),
+                client_pool: context.client_pool.clone(),
+                cache_manager: context.cache_manager.clone(),
+                subscribe_manager: context.subscribe_manager.clone(),
+                subscribe: Subscribe {
+                    packet_identifier: subscribe.pkid,
+                    filters: vec![subscribe.filter.clone()],
+                },
+                subscribe_properties: subscribe.subscribe_properties.clone(),
+            })
+            .await?;
+        }
+
+        if !context.has_last_will {
+            let lastwill = LastWillData {
+                client_id: context.client_id.clone(),
+                last_will: None,
+                last_will_properties: None,
+            };
+            SessionStorage::new(context.client_pool.clone())
+                .save_last_will_message(context.client_id.clone(), lastwill.encode())
+                .await?;
+        }
+    } else {
+        let paths: Vec<String> = subscribes.into_iter().map(|raw| raw.path).collect();
+        if !paths.is_empty() {
+            let unsubscribe = Unsubscribe {
+                pkid: 0,
+                filters: paths,
+            };
+            remove_subscribe(
+                &context.client_id,
+                &unsubscribe,
+                &context.client_pool,
+                &context.subscribe_manager,
+            )
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+// The broker that held the connection may be gone, its connection then went with it and the
+// takeover goes on without its inbound QoS 2 state.
+async fn takeover_remote_session(context: &TakeoverSessionContext, broker_id: u64) {
+    let Some(addr) = context
+        .cache_manager
+        .broker_cache
+        .node_lists
+        .get(&broker_id)
+        .map(|node| node.node_inner_addr.clone())
+    else {
+        warn!(
+            "Broker {} holding the session of client {} is not in the cluster, the session is taken over without it",
+            broker_id, context.client_id
+        );
+        return;
+    };
+
+    let request = TakeoverSessionRequest {
+        client_id: context.client_id.clone(),
+        broker_id: broker_config().broker_id,
+        connect_id: context.connect_id,
+    };
+    match broker_mqtt_takeover_session(&context.client_pool, &[addr], request).await {
+        Ok(reply) => {
+            info!(
+                "Session of client {} was taken over from broker {} (connection {}), {} inbound and {} outbound messages in flight",
+                context.client_id,
+                broker_id,
+                reply.connect_id,
+                reply.inbound_pkids.len(),
+                reply.outbound_inflight
+            );
+            if context.resume_session {
+                for pkid in reply.inbound_pkids {
+                    context
+                        .cache_manager
+                        .pkid_metadata
+                        .add_client_pkid(&context.client_id, pkid as u16);
+                }
+            }
+        }
+        Err(e) => {
+            warn!(
+                "Failed to take over the session of client {} from broker {}, error message: {}",
+                context.client_id, broker_id, e
+            );
+        }
+    }
+}
+
+// Served by the broker that held the session: the old connection is kicked and this broker
+// stops pushing to the client, the inbound QoS 2 state is handed to the new broker.
+pub async fn takeover_session_by_req(
+    cache_manager: &Arc<MQTTCacheManager>,
+    connection_manager: &Arc<ConnectionManager>,
+    subscribe_manager: &Arc<SubscribeManager>,
+    req: &TakeoverSessionRequest,
+) -> Result<TakeoverSessionReply, MqttBrokerError> {
+    if req.client_id.is_empty() {
+        return Err(MqttBrokerError::ClientIDIsEmpty);
+    }
+
+    let connect_id = close_taken_over_connection(cache_manager, connection_manager, &req.client_id)
+        .await
+        .unwrap_or_default();
+    subscribe_manager.remove_push_by_client_id(&req.client_id);
+
+    let inbound_pkids = cache_manager.pkid_metadata.list_client_pkid(&req.client_id);
+    for pkid in inbound_pkids.iter() {
+        cache_manager
+            .pkid_metadata
+            .delete_client_pkid(&req.client_id, *pkid);
+    }
+    let outbound_inflight = cache_manager.pkid_metadata.ack_packet_count(&req.client_id);
+
+    info!(
+        "Session of client {} was taken over by broker {} (connection {})",
+        req.client_id, req.broker_id, req.connect_id
+    );
+    Ok(TakeoverSessionReply {
+        connect_id,
+        inbound_pkids: inbound_pkids.into_iter().map(|pkid| pkid as u32).collect(),
+        outbound_inflight: outbound_inflight as u32,
+    })
+}
+
+// Closes the connection of the client held by this broker without touching the stored
+// session, which now belongs to the new connection. Returns the closed connection.
+async fn close_taken_over_connection(
+    cache_manager: &Arc<MQTTCacheManager>,
+    connection_manager: &Arc<ConnectionManager>,
+    client_id: &str,
+) -> Option<u64> {
+    let connect_id = cache_manager.get_connect_id(client_id)?;
+    // The connection id may come from the session of another broker.
+    let connection = cache_manager.get_connection(connect_id)?;
+    if connection.client_id != client_id {
+        return None;
+    }
+
+    send_disconnect_packet(
+        connection_manager,
+        connect_id,
+        DisconnectReasonCode::SessionTakenOver,
+        "Session taken over",
+        None,
+    )
+    .await;
+    connection_manager.close_connect(connect_id).await;
+    cache_manager.remove_connection(connect_id);
+    cache_manager.remove_heartbeat(client_id);
+    cache_manager.update_session_connect_id(client_id, None);
+    Some(connect_id)
+}
+
 pub async fn tcp_establish_connection_check(
     addr: &SocketAddr,
     connection_manager: &Arc<ConnectionManager>,
@@ -243,11 +533,16 @@ async fn handle_connection_rate_exceeded<T>(
 #[cfg(test)]
 mod test {
     use super::{
-        build_connection, get_client_id, response_information, MQTTConnection,
-        REQUEST_RESPONSE_PREFIX_NAME,
+        build_connection, get_client_id, response_information, takeover_session_by_req,
+        MQTTConnection, REQUEST_RESPONSE_PREFIX_NAME,
     };
+    use crate::common::tool::test_build_mqtt_cache_manager;
+    use crate::subscribe::manager::SubscribeManager;
     use common_config::broker::default_broker_config;
+    use network_server::common::connection_manager::ConnectionManager;
+    use protocol::broker::broker_mqtt_session::TakeoverSessionRequest;
     use protocol::mqtt::common::{Connect, ConnectProperties};
+    use std::sync::Arc;
 
     #[tokio::test]
     pub async fn build_connection_test() {
@@ -345,4 +640,50 @@ pub async fn send_qos_message_num_test() {
         conn.send_qos_message_decr();
         assert_eq!(conn.get_send_qos_message(), 0);
     }
+
+    #[tokio::test]
+    pub async fn takeover_session_by_req_test() {
+        let cache_manager = test_build_mqtt_cache_manager();
+        let connection_manager = Arc::new(ConnectionManager::new(3, 1000));
+        let subscribe_manager = Arc::new(SubscribeManager::new());
+        let mut req = TakeoverSessionRequest {
+            client_id: "".to_string(),
+            broker_id: 2,
+            connect_id: 10,
+        };
+        assert!(takeover_session_by_req(
+            &cache_manager,
+            &connection_manager,
+            &subscribe_manager,
+            &req
+        )
+        .await
+        .is_err());
+
+        req.client_id = "c1".to_string();
+        cache_manager.pkid_metadata.add_client_pkid("c1", 3);
+        cache_manager.pkid_metadata.add_client_pkid("c1", 5);
+        cache_manager.pkid_metadata.add_client_pkid("c1_x", 7);
+        let reply = takeover_session_by_req(
+            &cache_manager,
+            &connection_manager,
+            &subscribe_manager,
+            &req,
+        )
+        .await
+        .unwrap();
+        assert_eq!(reply.connect_id, 0);
+        let mut pkids = reply.inbound_pkids.clone();
+        pkids.sort();
+        assert_eq!(pkids, vec![3, 5]);
+        assert_eq!(reply.outbound_inflight, 0);
+        assert!(cache_manager
+            .pkid_metadata
+            .get_client_pkid("c1", 3)
+            .is_none());
+        assert!(cache_manager
+            .pkid_metadata
+            .get_client_pkid("c1_x", 7)
+            .is_some());
+    }
 }
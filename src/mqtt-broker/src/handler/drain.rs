@@ -0,0 +1,435 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+
+use common_base::error::ResultCommonError;
+use common_base::tools::{loop_select, now_second};
+use common_config::broker::broker_config;
+use grpc_clients::pool::ClientPool;
+use metadata_struct::mqtt::node_extend::NodeExtend;
+use network_server::common::connection_manager::ConnectionManager;
+use protocol::broker::broker_mqtt_admin::{
+    DrainNodeReply, DrainNodeRequest, DrainNodeStatusReply, RebalanceConnectionsReply,
+    RebalanceConnectionsRequest,
+};
+use protocol::mqtt::common::{
+    ConnectProperties, ConnectReturnCode, DisconnectReasonCode, MqttPacket, MqttProtocol,
+};
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+
+use super::cache::MQTTCacheManager;
+use super::connection::{disconnect_connection, send_disconnect_packet};
+use super::error::MqttBrokerError;
+use super::response::response_packet_mqtt_connect_fail;
+use crate::bridge::manager::ConnectorManager;
+use crate::subscribe::manager::SubscribeManager;
+
+pub const DEFAULT_DRAIN_DISCONNECT_RATE: u32 = 100;
+
+#[derive(Clone, Debug, Default)]
+pub struct DrainTask {
+    // seconds
+    pub start_time: u64,
+    // connections disconnected per second
+    pub disconnect_rate: u32,
+    pub disconnected_count: u64,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct RebalanceTask {
+    // connections still to move
+    pub remaining: u64,
+    // connections disconnected per second
+    pub disconnect_rate: u32,
+    pub server_references: Vec<String>,
+}
+
+// Moves connections away from this broker, either all of them while the broker is
+// drained or a given number of them to rebalance the cluster. Clients are disconnected
+// a few at a time so they do not all reconnect to the other brokers at once.
+#[derive(Default)]
+pub struct DrainManager {
+    drain: RwLock<Option<DrainTask>>,
+    rebalance: RwLock<Option<RebalanceTask>>,
+    next_reference: AtomicUsize,
+}
+
+impl DrainManager {
+    pub fn new() -> Self {
+        DrainManager::default()
+    }
+
+    pub fn start_drain(&self, disconnect_rate: u32) {
+        let mut drain = self.drain.write().unwrap();
+        if let Some(task) = drain.as_mut() {
+            task.disconnect_rate = disconnect_rate;
+            return;
+        }
+        *drain = Some(DrainTask {
+            start_time: now_second(),
+            disconnect_rate,
+            disconnected_count: 0,
+        });
+        // A drain moves every connection, a rebalance on top of it has nothing left to do.
+        *self.rebalance.write().unwrap() = None;
+    }
+
+    pub fn stop_drain(&self) {
+        *self.drain.write().unwrap() = None;
+    }
+
+    pub fn get_drain(&self) -> Option<DrainTask> {
+        self.drain.read().unwrap().clone()
+    }
+
+    pub fn start_rebalance(
+        &self,
+        count: u64,
+        disconnect_rate: u32,
+        server_references: Vec<String>,
+    ) {
+        *self.rebalance.write().unwrap() = if count > 0 {
+            Some(RebalanceTask {
+                remaining: count,
+                disconnect_rate,
+                server_references,
+            })
+        } else {
+            None
+        };
+    }
+
+    pub fn get_rebalance(&self) -> Option<RebalanceTask> {
+        self.rebalance.read().unwrap().clone()
+    }
+
+    // Number of connections to move in this round.
+    fn batch_size(&self) -> u64 {
+        if let Some(task) = self.get_drain() {
+            return task.disconnect_rate as u64;
+        }
+        if let Some(task) = self.get_rebalance() {
+            return task.remaining.min(task.disconnect_rate as u64);
+        }
+        0
+    }
+
+    fn record_moved(&self, num: u64) {
+        if let Some(task) = self.drain.write().unwrap().as_mut() {
+            task.disconnected_count += num;
+            return;
+        }
+
+        let mut rebalance = self.rebalance.write().unwrap();
+        if let Some(task) = rebalance.as_mut() {
+            task.remaining = task.remaining.saturating_sub(num);
+            if task.remaining == 0 {
+                *rebalance = None;
+            }
+        }
+    }
+
+    fn next_server_reference(&self, server_references: &[String]) -> Option<String> {
+        if server_references.is_empty() {
+            return None;
+        }
+        let index = self.next_reference.fetch_add(1, Ordering::Relaxed);
+        Some(server_references[index % server_references.len()].clone())
+    }
+}
+
+// MQTT addresses of the other brokers that still take connections.
+pub fn available_server_references(cache_manager: &Arc<MQTTCacheManager>) -> Vec<String> {
+    let conf = broker_config();
+    let mut references: Vec<String> = cache_manager
+        .broker_cache
+        .node_list()
+        .iter()
+        .filter(|node| node.node_id != conf.broker_id && !node.is_draining())
+        .filter_map(|node| NodeExtend::decode(&node.extend).ok())
+        .map(|extend| extend.mqtt.mqtt_addr)
+        .filter(|addr| !addr.is_empty())
+        .collect();
+    references.sort();
+    references
+}
+
+// CONNACK returned while the broker is drained, MQTT 5 clients are pointed to another broker.
+pub fn response_packet_mqtt_connect_draining(
+    cache_manager: &Arc<MQTTCacheManager>,
+    protocol: &MqttProtocol,
+    connect_properties: &Option<ConnectProperties>,
+) -> MqttPacket {
+    let mut packet = response_packet_mqtt_connect_fail(
+        protocol,
+        ConnectReturnCode::UseAnotherServer,
+        connect_properties,
+        Some("Server is draining".to_string()),
+    );
+    if let MqttPacket::ConnAck(_, Some(properties)) = &mut packet {
+        properties.server_reference = cache_manager
+            .drain
+            .next_server_reference(&available_server_references(cache_manager));
+    }
+    packet
+}
+
+pub fn drain_node_by_req(
+    cache_manager: &Arc<MQTTCacheManager>,
+    req: &DrainNodeRequest,
+) -> Result<DrainNodeReply, MqttBrokerError> {
+    if req.cancel {
+        cache_manager.drain.stop_drain();
+        cache_manager.broker_cache.set_draining(false);
+        info!("Node drain cancelled, the broker takes new connections again");
+        return Ok(DrainNodeReply {});
+    }
+
+    let disconnect_rate = if req.disconnect_rate == 0 {
+        DEFAULT_DRAIN_DISCONNECT_RATE
+    } else {
+        req.disconnect_rate
+    };
+    cache_manager.drain.start_drain(disconnect_rate);
+    cache_manager.broker_cache.set_draining(true);
+    info!(
+        "Node drain started, connections disconnected per second: {}",
+        disconnect_rate
+    );
+    Ok(DrainNodeReply {})
+}
+
+pub fn drain_node_status_by_req(
+    cache_manager: &Arc<MQTTCacheManager>,
+    subscribe_manager: &Arc<SubscribeManager>,
+    connector_manager: &Arc<ConnectorManager>,
+) -> DrainNodeStatusReply {
+    let conf = broker_config();
+    let task = cache_manager.drain.get_drain();
+    let connection_count = cache_manager.get_connection_count() as u64;
+    let share_leader_count = subscribe_manager.share_leader_push.len() as u64;
+    let connector_count = connector_manager.connector_thread.len() as u64;
+
+    DrainNodeStatusReply {
+        broker_id: conf.broker_id,
+        draining: task.is_some(),
+        start_time: task.as_ref().map(|task| task.start_time).unwrap_or(0),
+        disconnect_rate: task.as_ref().map(|task| task.disconnect_rate).unwrap_or(0),
+        connection_count,
+        disconnected_count: task
+            .as_ref()
+            .map(|task| task.disconnected_count)
+            .unwrap_or(0),
+        share_leader_count,
+        connector_count,
+        drained: task.is_some()
+            && connection_count == 0
+            && share_leader_count == 0
+            && connector_count == 0,
+    }
+}
+
+pub fn rebalance_connections_by_req(
+    cache_manager: &Arc<MQTTCacheManager>,
+    req: &RebalanceConnectionsRequest,
+) -> Result<RebalanceConnectionsReply, MqttBrokerError> {
+    if cache_manager.drain.get_drain().is_some() {
+        return Err(MqttBrokerError::CommonError(
+            "The broker is draining, its connections are already being moved".to_string(),
+        ));
+    }
+
+    let count = req.count.min(cache_manager.get_connection_count() as u64);
+    let disconnect_rate = if req.disconnect_rate == 0 {
+        DEFAULT_DRAIN_DISCONNECT_RATE
+    } else {
+        req.disconnect_rate
+    };
+    cache_manager
+        .drain
+        .start_rebalance(count, disconnect_rate, req.server_references.clone());
+    info!(
+        "Connection rebalance started, connections to move: {}, per second: {}",
+        count, disconnect_rate
+    );
+    Ok(RebalanceConnectionsReply { count })
+}
+
+#[derive(Clone)]
+pub struct DrainContext {
+    pub cache_manager: Arc<MQTTCacheManager>,
+    pub client_pool: Arc<ClientPool>,
+    pub connection_manager: Arc<ConnectionManager>,
+    pub subscribe_manager: Arc<SubscribeManager>,
+}
+
+pub async fn start_drain_thread(context: DrainContext, stop_send: broadcast::Sender<bool>) {
+    let ac_fn = async || -> ResultCommonError {
+        move_connections(&context).await;
+        release_share_leader(&context);
+        Ok(())
+    };
+
+    loop_select(ac_fn, 1, &stop_send).await;
+}
+
+async fn move_connections(context: &DrainContext) {
+    let drain = &context.cache_manager.drain;
+    let batch_size = drain.batch_size();
+    if batch_size == 0 {
+        return;
+    }
+
+    let server_references = if let Some(task) = drain.get_rebalance() {
+        task.server_references
+    } else {
+        available_server_references(&context.cache_manager)
+    };
+    if server_references.is_empty() {
+        warn!("No other broker takes connections, clients are moved without a server reference");
+    }
+
+    let connections: Vec<(u64, String)> = context
+        .cache_manager
+        .connection_info
+        .iter()
+        .take(batch_size as usize)
+        .map(|raw| (raw.connect_id, raw.client_id.clone()))
+        .collect();
+
+    let mut moved = 0;
+    for (connect_id, client_id) in connections {
+        send_disconnect_packet(
+            &context.connection_manager,
+            connect_id,
+            DisconnectReasonCode::ServerMoved,
+            "Server moved",
+            drain.next_server_reference(&server_references),
+        )
+        .await;
+
+        if let Err(e) = disconnect_connection(
+            &client_id,
+            connect_id,
+            &context.cache_manager,
+            &context.client_pool,
+            &context.connection_manager,
+            &context.subscribe_manager,
+            false,
+        )
+        .await
+        {
+            warn!(
+                "Failed to move client {} (connection {}) to another broker, error message: {}",
+                client_id, connect_id, e
+            );
+            continue;
+        }
+        context.cache_manager.remove_heartbeat(&client_id);
+        moved += 1;
+    }
+
+    if moved > 0 {
+        drain.record_moved(moved);
+        info!("{} connections moved to other brokers", moved);
+    }
+}
+
+// Once the clients are gone the shared subscription groups led by this broker are
+// dropped, meta service hands them to another broker and the followers subscribe there.
+fn release_share_leader(context: &DrainContext) {
+    if context.cache_manager.drain.get_drain().is_none()
+        || context.cache_manager.get_connection_count() > 0
+    {
+        return;
+    }
+
+    let keys: Vec<String> = context
+        .subscribe_manager
+        .share_leader_push
+        .iter()
+        .map(|raw| raw.key().clone())
+        .collect();
+    for key in keys {
+        context.subscribe_manager.share_leader_push.remove(&key);
+        info!(
+            "Shared subscription {} released by the draining broker",
+            key
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DrainManager;
+
+    #[test]
+    fn drain_task_test() {
+        let drain = DrainManager::new();
+        assert!(drain.get_drain().is_none());
+        assert_eq!(drain.batch_size(), 0);
+
+        drain.start_drain(10);
+        assert_eq!(drain.batch_size(), 10);
+        drain.record_moved(4);
+        drain.record_moved(6);
+        let task = drain.get_drain().unwrap();
+        assert_eq!(task.disconnected_count, 10);
+        assert!(task.start_time > 0);
+
+        // starting again only changes the rate
+        drain.start_drain(20);
+        let task = drain.get_drain().unwrap();
+        assert_eq!(task.disconnect_rate, 20);
+        assert_eq!(task.disconnected_count, 10);
+
+        drain.stop_drain();
+        assert!(drain.get_drain().is_none());
+    }
+
+    #[test]
+    fn rebalance_task_test() {
+        let drain = DrainManager::new();
+        drain.start_rebalance(15, 10, vec!["a:1883".to_string(), "b:1883".to_string()]);
+        assert_eq!(drain.batch_size(), 10);
+
+        drain.record_moved(10);
+        assert_eq!(drain.get_rebalance().unwrap().remaining, 5);
+        assert_eq!(drain.batch_size(), 5);
+
+        drain.record_moved(5);
+        assert!(drain.get_rebalance().is_none());
+        assert_eq!(drain.batch_size(), 0);
+
+        // a drain replaces the rebalance
+        drain.start_rebalance(15, 10, Vec::new());
+        drain.start_drain(10);
+        assert!(drain.get_rebalance().is_none());
+    }
+
+    #[test]
+    fn next_server_reference_test() {
+        let drain = DrainManager::new();
+        assert!(drain.next_server_reference(&[]).is_none());
+
+        let references = vec!["a:1883".to_string(), "b:1883".to_string()];
+        let first = drain.next_server_reference(&references).unwrap();
+        let second = drain.next_server_reference(&references).unwrap();
+        assert_ne!(first, second);
+        assert_eq!(drain.next_server_reference(&references).unwrap(), first);
+    }
+}
@@ -16,9 +16,12 @@
 use super::dynamic_config::build_cluster_config;
 use crate::bridge::manager::ConnectorManager;
 use crate::common::types::ResultMqttBrokerError;
-use crate::handler::dynamic_config::{update_cluster_dynamic_config, ClusterDynamicConfig};
+use crate::handler::dynamic_config::{
+    get_scheduled_publishes, update_cluster_dynamic_config, ClusterDynamicConfig,
+};
 use crate::storage::auto_subscribe::AutoSubscribeStorage;
 use crate::storage::connector::ConnectorStorage;
+use crate::storage::rule::RuleStorage;
 use crate::storage::schema::SchemaStorage;
 use crate::storage::topic::TopicStorage;
 use crate::{security::AuthDriver, subscribe::manager::SubscribeManager};
@@ -47,6 +50,9 @@ pub async fn load_metadata_cache(
 ) -> ResultMqttBrokerError {
     // load cluster config
     let cluster = build_cluster_config(client_pool).await?;
+    cache_manager
+        .detail_metrics
+        .set_config(cluster.mqtt_detail_metrics.clone());
     cache_manager.broker_cache.set_cluster_config(cluster);
 
     // load all topic
@@ -102,8 +108,19 @@ pub async fn load_metadata_cache(
         cache_manager.add_auto_subscribe_rule(auto_subscribe_rule.clone());
     }
 
+    // load all sql rules
+    let rule_storage = RuleStorage::new(client_pool.clone());
+    let rules = rule_storage.list_rule().await?;
+    let rule_num = rules.len();
+    cache_manager.rule_engine.set_rules(rules);
+
+    // load all scheduled publishes
+    let schedules = get_scheduled_publishes(client_pool).await?;
+    let schedule_num = schedules.len();
+    cache_manager.set_scheduled_publishes(schedules);
+
     info!(
-        "Cache loading successful.topic:{},user:{},acl:{},blacklist:{},topic_rewrite_rule:{},connectors:{},schemas:{},auto_subscribe_rules:{}",
+        "Cache loading successful.topic:{},user:{},acl:{},blacklist:{},topic_rewrite_rule:{},connectors:{},schemas:{},auto_subscribe_rules:{},rules:{},scheduled_publishes:{}",
         topic_list.len(),
         user_list.len(),
         acl_list.len(),
@@ -112,6 +129,8 @@ pub async fn load_metadata_cache(
         connectors.len(),
         schemas.len(),
         auto_subscribe_rules.len(),
+        rule_num,
+        schedule_num,
     );
 
     Ok(())
@@ -219,7 +238,9 @@ pub async fn update_cache_metadata(
         MqttBrokerUpdateCacheResourceType::ClusterResourceConfig => match request.action_type() {
             MqttBrokerUpdateCacheActionType::Set => {
                 let data = serde_json::from_str::<ClusterResourceConfig>(&request.data)?;
-                let config = data.resource.parse::<ClusterDynamicConfig>()?;
+                // The resource is the full "cluster/{cluster_name}/{config}" path
+                let resource = data.resource.rsplit('/').next().unwrap_or_default();
+                let config = resource.parse::<ClusterDynamicConfig>()?;
                 update_cluster_dynamic_config(cache_manager, config, data.config).await?;
             }
             MqttBrokerUpdateCacheActionType::Delete => {}
@@ -18,10 +18,12 @@
 use broker_core::cluster::ClusterStorage;
 use common_config::broker::broker_config;
 use common_config::config::{
-    BrokerConfig, MqttFlappingDetect, MqttOfflineMessage, MqttProtocolConfig, MqttSchema,
-    MqttSecurity, MqttSlowSubscribeConfig, MqttSystemMonitor,
+    BrokerConfig, MqttDetailMetrics, MqttFlappingDetect, MqttOfflineMessage, MqttProtocolConfig,
+    MqttSchema, MqttSecurity, MqttSlowSubscribeConfig, MqttSystemMonitor,
 };
 use grpc_clients::pool::ClientPool;
+use metadata_struct::mqtt::rule::MqttRuleChange;
+use metadata_struct::mqtt::scheduled_publish::MqttScheduledPublish;
 use std::sync::Arc;
 use strum_macros::{Display, EnumString};
 
@@ -35,6 +37,9 @@ pub enum ClusterDynamicConfig {
     MqttSecurity,
     MqttSystemMonitor,
     MqttSchema,
+    MqttRuleEngine,
+    MqttScheduledPublish,
+    MqttDetailMetrics,
 }
 
 impl MQTTCacheManager {
@@ -144,6 +149,22 @@ pub fn update_security_config(&self, security: MqttSecurity) {
     pub fn get_security_config(&self) -> MqttSecurity {
         self.broker_cache.get_cluster_config().mqtt_security
     }
+
+    // detail metrics
+    pub fn update_detail_metrics_config(&self, detail_metrics: MqttDetailMetrics) {
+        if let Some(mut config) = self
+            .broker_cache
+            .cluster_info
+            .get_mut(&self.broker_cache.cluster_name)
+        {
+            config.mqtt_detail_metrics = detail_metrics.clone();
+        }
+        self.detail_metrics.set_config(detail_metrics);
+    }
+
+    pub fn get_detail_metrics_config(&self) -> MqttDetailMetrics {
+        self.broker_cache.get_cluster_config().mqtt_detail_metrics
+    }
 }
 
 pub async fn build_cluster_config(
@@ -178,6 +199,10 @@ pub async fn build_cluster_config(
         conf.mqtt_system_monitor = data;
     }
 
+    if let Some(data) = get_detail_metrics(client_pool).await? {
+        conf.mqtt_detail_metrics = data;
+    }
+
     Ok(conf)
 }
 
@@ -216,6 +241,22 @@ pub async fn update_cluster_dynamic_config(
             let security_config = serde_json::from_slice(&config)?;
             cache_manager.update_security_config(security_config);
         }
+        ClusterDynamicConfig::MqttRuleEngine => {
+            match serde_json::from_slice::<MqttRuleChange>(&config)? {
+                MqttRuleChange::Set { rule } => cache_manager.rule_engine.set_rule(rule)?,
+                MqttRuleChange::Delete { rule_name } => {
+                    cache_manager.rule_engine.remove_rule(&rule_name)
+                }
+            }
+        }
+        ClusterDynamicConfig::MqttScheduledPublish => {
+            let schedules = serde_json::from_slice::<Vec<MqttScheduledPublish>>(&config)?;
+            cache_manager.set_scheduled_publishes(schedules);
+        }
+        ClusterDynamicConfig::MqttDetailMetrics => {
+            let detail_metrics = serde_json::from_slice(&config)?;
+            cache_manager.update_detail_metrics_config(detail_metrics);
+        }
     }
     Ok(())
 }
@@ -359,3 +400,41 @@ async fn get_system_monitor(
 
     Ok(None)
 }
+
+async fn get_detail_metrics(
+    client_pool: &Arc<ClientPool>,
+) -> Result<Option<MqttDetailMetrics>, MqttBrokerError> {
+    let conf = broker_config();
+    let cluster_storage = ClusterStorage::new(client_pool.clone());
+    let data = cluster_storage
+        .get_dynamic_config(
+            &conf.cluster_name,
+            &ClusterDynamicConfig::MqttDetailMetrics.to_string(),
+        )
+        .await?;
+
+    if !data.is_empty() {
+        return Ok(Some(serde_json::from_slice::<MqttDetailMetrics>(&data)?));
+    }
+
+    Ok(None)
+}
+
+pub async fn get_scheduled_publishes(
+    client_pool: &Arc<ClientPool>,
+) -> Result<Vec<MqttScheduledPublish>, MqttBrokerError> {
+    let conf = broker_config();
+    let cluster_storage = ClusterStorage::new(client_pool.clone());
+    let data = cluster_storage
+        .get_dynamic_config(
+            &conf.cluster_name,
+            &ClusterDynamicConfig::MqttScheduledPublish.to_string(),
+        )
+        .await?;
+
+    if !data.is_empty() {
+        return Ok(serde_json::from_slice::<Vec<MqttScheduledPublish>>(&data)?);
+    }
+
+    Ok(Vec::new())
+}
@@ -20,6 +20,7 @@
 use r2d2;
 use rdkafka::error::KafkaError;
 use reqwest::Error as RequestError;
+use rule_engine::error::RuleEngineError;
 use thiserror::Error;
 use tonic::Status;
 
@@ -91,6 +92,12 @@ pub enum MqttBrokerError {
     #[error("{0}")]
     FromProtocolMQTTCommonError(#[from] MQTTProtocolError),
 
+    #[error("{0}")]
+    RuleEngineError(#[from] RuleEngineError),
+
+    #[error("{0}")]
+    MqttClientError(#[from] rumqttc::v5::ClientError),
+
     #[error("Topic alias is too long. alias is {0}")]
     TopicAliasTooLong(u16),
 
@@ -224,6 +231,54 @@ pub enum MqttBrokerError {
 
     #[error("Pulsar error: {0}")]
     PulsarError(#[from] PulsarError),
+
+    #[error("Listener {0} not found")]
+    ListenerNotFound(String),
+
+    #[error("Listener {0} already exists")]
+    ListenerAlreadyExist(String),
+
+    #[error("Listener {0} is already running")]
+    ListenerAlreadyRunning(String),
+
+    #[error("Listener {0} is not running")]
+    ListenerNotRunning(String),
+
+    #[error("Unsupported listener protocol: {0}")]
+    UnsupportedListenerProtocol(String),
+
+    #[error("Invalid subscribe option: {0}")]
+    InvalidSubscribeOption(String),
+
+    #[error("Client {0} has no subscription on {1}")]
+    SubscriptionNotFound(String, String),
+
+    #[error("At most {0} packet traces can run at the same time")]
+    TooManyPacketTraces(usize),
+
+    #[error("Connector {0} not found")]
+    ConnectorNotFound(String),
+
+    #[error("Connector {0} is not bound to a topic")]
+    ConnectorNotBoundToTopic(String),
+
+    #[error("Connector target rejected the record: {0}")]
+    ConnectorRecordRejected(String),
+
+    #[error("Rule {0} not found")]
+    RuleNotFound(String),
+
+    #[error("Scheduled publish {0} not found")]
+    ScheduledPublishNotFound(String),
+
+    #[error("Scheduled publish {0} already exists")]
+    ScheduledPublishAlreadyExist(String),
+
+    #[error("Invalid scheduled publish: {0}")]
+    InvalidScheduledPublish(String),
+
+    #[error("Invalid detail metrics config: {0}")]
+    InvalidDetailMetricsConfig(String),
 }
 
 impl From<MqttBrokerError> for Status {
@@ -14,16 +14,12 @@
 
 use super::cache::MQTTCacheManager;
 use super::error::MqttBrokerError;
-use super::message::build_message_expire;
-use super::retain::save_retain_message;
-use super::topic::try_init_topic;
+use super::message::publish_message_to_topic;
 use crate::common::types::ResultMqttBrokerError;
-use crate::storage::message::MessageStorage;
 use crate::storage::session::SessionStorage;
 use bytes::Bytes;
 use grpc_clients::pool::ClientPool;
 use metadata_struct::mqtt::lastwill::LastWillData;
-use metadata_struct::mqtt::message::MqttMessage;
 use protocol::mqtt::common::{LastWill, LastWillProperties, Publish, PublishProperties};
 use std::sync::Arc;
 use storage_adapter::storage::ArcStorageAdapter;
@@ -45,37 +41,15 @@ pub async fn send_last_will_message(
     }
 
     let publish = publish_res.unwrap();
-
-    let topic = try_init_topic(
-        &topic_name,
+    publish_message_to_topic(
         cache_manager,
-        &message_storage_adapter,
         client_pool,
-    )
-    .await?;
-
-    save_retain_message(
-        cache_manager,
-        client_pool,
-        topic_name,
+        &message_storage_adapter,
         client_id,
         &publish,
         &publish_properties,
     )
-    .await?;
-
-    // Persisting stores message data
-    let message_storage = MessageStorage::new(message_storage_adapter.clone());
-
-    let message_expire = build_message_expire(cache_manager, &publish_properties);
-    if let Some(record) =
-        MqttMessage::build_record(client_id, &publish, &publish_properties, message_expire)
-    {
-        message_storage
-            .append_topic_message(&topic.topic_id, vec![record])
-            .await?;
-    }
-    Ok(())
+    .await
 }
 
 async fn build_publish_message_by_lastwill(
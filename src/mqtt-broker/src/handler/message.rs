@@ -15,13 +15,26 @@
 use std::sync::Arc;
 
 use common_base::tools::now_second;
+use grpc_clients::pool::ClientPool;
+use message_expire::expire::{is_expired, remaining_expiry_interval};
 use metadata_struct::mqtt::message::MqttMessage;
-use protocol::mqtt::common::PublishProperties;
+use protocol::mqtt::common::{Publish, PublishProperties};
+use storage_adapter::storage::ArcStorageAdapter;
 
 use super::cache::MQTTCacheManager;
+use super::retain::save_retain_message;
+use super::topic::try_init_topic;
+use crate::common::types::ResultMqttBrokerError;
+use crate::storage::message::MessageStorage;
 
 pub fn is_message_expire(message: &MqttMessage) -> bool {
-    message.expiry_interval < now_second()
+    is_expired(message.expiry_interval, now_second())
+}
+
+/// The Message Expiry Interval forwarded to subscribers is the lifetime the
+/// message has left, as required by MQTT 5.
+pub fn message_remaining_expiry_interval(message: &MqttMessage) -> Option<u32> {
+    remaining_expiry_interval(message.expiry_interval, now_second())
 }
 
 pub fn build_message_expire(
@@ -40,10 +53,55 @@ pub fn build_message_expire(
     now_second() + cluster.mqtt_protocol_config.max_message_expiry_interval
 }
 
+/// Publish a message that does not come from a client connection, such as a
+/// last will or a message sent from the admin api.
+pub async fn publish_message_to_topic(
+    cache_manager: &Arc<MQTTCacheManager>,
+    client_pool: &Arc<ClientPool>,
+    message_storage_adapter: &ArcStorageAdapter,
+    client_id: &str,
+    publish: &Publish,
+    publish_properties: &Option<PublishProperties>,
+) -> ResultMqttBrokerError {
+    let topic_name = String::from_utf8(publish.topic.to_vec())?;
+    let topic = try_init_topic(
+        &topic_name,
+        cache_manager,
+        message_storage_adapter,
+        client_pool,
+    )
+    .await?;
+
+    save_retain_message(
+        cache_manager,
+        client_pool,
+        topic_name,
+        client_id,
+        publish,
+        publish_properties,
+    )
+    .await?;
+
+    // Persisting stores message data
+    let message_storage = MessageStorage::new(message_storage_adapter.clone());
+
+    let message_expire = build_message_expire(cache_manager, publish_properties);
+    if let Some(record) =
+        MqttMessage::build_record(client_id, publish, publish_properties, message_expire)
+    {
+        message_storage
+            .append_topic_message(&topic.topic_id, vec![record])
+            .await?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use crate::common::tool::test_build_mqtt_cache_manager;
-    use crate::handler::message::{build_message_expire, is_message_expire};
+    use crate::handler::message::{
+        build_message_expire, is_message_expire, message_remaining_expiry_interval,
+    };
     use common_base::tools::now_second;
     use common_config::config::{BrokerConfig, MqttProtocolConfig};
     use metadata_struct::mqtt::message::MqttMessage;
@@ -89,4 +147,19 @@ fn is_message_expire_test() {
 
         assert!(!is_message_expire(&message));
     }
+
+    #[test]
+    fn message_remaining_expiry_interval_test() {
+        let message = MqttMessage {
+            expiry_interval: now_second() + 30,
+            ..Default::default()
+        };
+        let remaining = message_remaining_expiry_interval(&message).unwrap();
+        assert!(remaining <= 30 && remaining >= 29);
+
+        // messages without an expiry, such as system topic messages, never expire
+        let message = MqttMessage::default();
+        assert!(!is_message_expire(&message));
+        assert!(message_remaining_expiry_interval(&message).is_none());
+    }
 }
@@ -13,11 +13,13 @@
 // limitations under the License.
 
 pub mod cache;
+pub mod client_admin;
 pub mod command;
 pub mod connection;
 pub mod constant;
 pub mod content_type;
 pub mod delay_message;
+pub mod drain;
 pub mod dynamic_cache;
 pub mod dynamic_config;
 pub mod error;
@@ -27,10 +29,13 @@
 pub mod keep_alive;
 pub mod last_will;
 pub mod message;
+pub mod mountpoint;
 pub mod mqtt;
 pub mod offline_message;
 pub mod response;
 pub mod retain;
+pub mod rule_engine;
+pub mod scheduled_publish;
 pub mod session;
 pub mod slow_subscribe;
 pub mod sub_auto;
@@ -0,0 +1,118 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use protocol::mqtt::common::{Login, Subscribe, Unsubscribe};
+
+const SHARE_SUB_PREFIX: &str = "$share/";
+const QUEUE_SUB_PREFIX: &str = "$queue/";
+
+// Expand the ${clientid} and ${username} placeholders of the listener mountpoint.
+pub fn build_mountpoint(mountpoint: &str, client_id: &str, login: &Option<Login>) -> String {
+    let username = login
+        .as_ref()
+        .map(|login| login.username.as_str())
+        .unwrap_or_default();
+    mountpoint
+        .replace("${clientid}", client_id)
+        .replace("${username}", username)
+}
+
+pub fn mount_topic(mountpoint: &Option<String>, topic_name: &str) -> String {
+    if let Some(mountpoint) = mountpoint {
+        return format!("{mountpoint}{topic_name}");
+    }
+    topic_name.to_string()
+}
+
+// The shared subscription prefix must stay in front of the mountpoint, e.g.
+// "$share/g1/a/b" with mountpoint "tenant1/" becomes "$share/g1/tenant1/a/b".
+pub fn mount_filter(mountpoint: &Option<String>, filter: &str) -> String {
+    let Some(mountpoint) = mountpoint else {
+        return filter.to_string();
+    };
+
+    if let Some(rest) = filter.strip_prefix(SHARE_SUB_PREFIX) {
+        if let Some((group, topic)) = rest.split_once('/') {
+            return format!("{SHARE_SUB_PREFIX}{group}/{mountpoint}{topic}");
+        }
+    }
+
+    if let Some(topic) = filter.strip_prefix(QUEUE_SUB_PREFIX) {
+        return format!("{QUEUE_SUB_PREFIX}{mountpoint}{topic}");
+    }
+
+    format!("{mountpoint}{filter}")
+}
+
+pub fn unmount_topic(mountpoint: &Option<String>, topic_name: &str) -> String {
+    if let Some(mountpoint) = mountpoint {
+        if let Some(topic) = topic_name.strip_prefix(mountpoint.as_str()) {
+            return topic.to_string();
+        }
+    }
+    topic_name.to_string()
+}
+
+pub fn mount_subscribe(mountpoint: &Option<String>, subscribe: &Subscribe) -> Subscribe {
+    let mut subscribe = subscribe.clone();
+    for filter in subscribe.filters.iter_mut() {
+        filter.path = mount_filter(mountpoint, &filter.path);
+    }
+    subscribe
+}
+
+pub fn mount_un_subscribe(mountpoint: &Option<String>, un_subscribe: &Unsubscribe) -> Unsubscribe {
+    let mut un_subscribe = un_subscribe.clone();
+    for filter in un_subscribe.filters.iter_mut() {
+        *filter = mount_filter(mountpoint, filter);
+    }
+    un_subscribe
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_mountpoint_test() {
+        let login = Some(Login {
+            username: "user1".to_string(),
+            password: "pwd".to_string(),
+        });
+        assert_eq!(
+            build_mountpoint("tenant/${username}/${clientid}/", "c1", &login),
+            "tenant/user1/c1/"
+        );
+        assert_eq!(build_mountpoint("${username}/", "c1", &None), "/");
+    }
+
+    #[test]
+    fn mount_and_unmount_test() {
+        let mountpoint = Some("tenant1/".to_string());
+        assert_eq!(mount_topic(&mountpoint, "a/b"), "tenant1/a/b");
+        assert_eq!(mount_topic(&None, "a/b"), "a/b");
+        assert_eq!(unmount_topic(&mountpoint, "tenant1/a/b"), "a/b");
+        assert_eq!(unmount_topic(&mountpoint, "other/a/b"), "other/a/b");
+        assert_eq!(mount_filter(&mountpoint, "a/#"), "tenant1/a/#");
+        assert_eq!(
+            mount_filter(&mountpoint, "$share/g1/a/+"),
+            "$share/g1/tenant1/a/+"
+        );
+        assert_eq!(
+            mount_filter(&mountpoint, "$queue/a/+"),
+            "$queue/tenant1/a/+"
+        );
+        assert_eq!(mount_filter(&None, "$share/g1/a"), "$share/g1/a");
+    }
+}
@@ -16,6 +16,7 @@
 use std::sync::Arc;
 
 use broker_core::rocksdb::RocksDBEngine;
+use common_base::telemetry::trace::TraceSpan;
 use common_base::tools::{now_mills, now_second};
 use common_metrics::mqtt::auth::{record_mqtt_auth_failed, record_mqtt_auth_success};
 use common_metrics::mqtt::publish::{
@@ -39,19 +40,27 @@
 
 use super::connection::{disconnect_connection, is_delete_session};
 use super::delay_message::{decode_delay_topic, is_delay_topic};
+use super::drain::response_packet_mqtt_connect_draining;
 use super::offline_message::{save_message, SaveMessageContext};
 use super::response::build_pub_ack_fail;
 use super::retain::{is_new_sub, try_send_retain_message, TrySendRetainMessageContext};
+use super::rule_engine::{apply_message_rules, ApplyMessageRulesContext};
 use super::sub_auto::try_auto_subscribe;
 use super::subscribe::{save_subscribe, SaveSubscribeContext};
 use super::unsubscribe::remove_subscribe;
+use crate::bridge::manager::ConnectorManager;
 use crate::common::pkid_storage::{pkid_delete, pkid_exists, pkid_save};
 use crate::handler::cache::{
     ConnectionLiveTime, MQTTCacheManager, QosAckPackageData, QosAckPackageType,
 };
-use crate::handler::connection::{build_connection, get_client_id};
+use crate::handler::connection::{
+    build_connection, get_client_id, takeover_session, TakeoverSessionContext,
+};
 use crate::handler::flapping_detect::check_flapping_detect;
 use crate::handler::last_will::save_last_will_message;
+use crate::handler::mountpoint::{
+    build_mountpoint, mount_subscribe, mount_topic, mount_un_subscribe,
+};
 use crate::handler::response::{
     build_puback, build_pubrec, response_packet_mqtt_connect_fail,
     response_packet_mqtt_connect_success, response_packet_mqtt_distinct_by_reason,
@@ -82,6 +91,7 @@ pub struct MqttService {
     delay_message_manager: Arc<DelayMessageManager>,
     subscribe_manager: Arc<SubscribeManager>,
     schema_manager: Arc<SchemaRegisterManager>,
+    connector_manager: Arc<ConnectorManager>,
     client_pool: Arc<ClientPool>,
     auth_driver: Arc<AuthDriver>,
     rocksdb_engine_handler: Arc<RocksDBEngine>,
@@ -96,6 +106,7 @@ pub struct MqttServiceContext {
     pub delay_message_manager: Arc<DelayMessageManager>,
     pub subscribe_manager: Arc<SubscribeManager>,
     pub schema_manager: Arc<SchemaRegisterManager>,
+    pub connector_manager: Arc<ConnectorManager>,
     pub client_pool: Arc<ClientPool>,
     pub auth_driver: Arc<AuthDriver>,
     pub rocksdb_engine_handler: Arc<RocksDBEngine>,
@@ -110,6 +121,8 @@ pub struct MqttServiceConnectContext {
     pub last_will_properties: Option<LastWillProperties>,
     pub login: Option<Login>,
     pub addr: SocketAddr,
+    pub listener: Option<String>,
+    pub client_cert_verified: bool,
 }
 
 impl MqttService {
@@ -124,11 +137,35 @@ pub fn new(context: MqttServiceContext) -> Self {
             client_pool: context.client_pool,
             auth_driver: context.auth_driver,
             schema_manager: context.schema_manager,
+            connector_manager: context.connector_manager,
             rocksdb_engine_handler: context.rocksdb_engine_handler,
         }
     }
 
     pub async fn connect(&self, context: MqttServiceConnectContext) -> MqttPacket {
+        let span = TraceSpan::from_user_properties(
+            "mqtt.connect",
+            context
+                .connect_properties
+                .as_ref()
+                .map(|properties| properties.user_properties.as_slice())
+                .unwrap_or(&[]),
+        );
+        if span.is_recording() {
+            span.set_attribute("mqtt.client_id", context.connect.client_id.clone());
+            span.set_attribute("net.peer.addr", context.addr.to_string());
+        }
+
+        let packet = self.process_connect(context).await;
+        if let MqttPacket::ConnAck(ack, _) = &packet {
+            if ack.code != ConnectReturnCode::Success {
+                span.set_error(format!("{:?}", ack.code));
+            }
+        }
+        packet
+    }
+
+    async fn process_connect(&self, context: MqttServiceConnectContext) -> MqttPacket {
         let cluster = self.cache_manager.broker_cache.get_cluster_config();
 
         // connect params validator
@@ -144,9 +181,23 @@ pub async fn connect(&self, context: MqttServiceConnectContext) -> MqttPacket {
             return res;
         }
 
+        // A draining broker takes no new connections
+        if self.cache_manager.broker_cache.is_draining() {
+            return response_packet_mqtt_connect_draining(
+                &self.cache_manager,
+                &self.protocol,
+                &context.connect_properties,
+            );
+        }
+
+        let listener = context
+            .listener
+            .as_ref()
+            .and_then(|name| self.cache_manager.get_listener(name));
+
         // blacklist check
         let (client_id, new_client_id) = get_client_id(&context.connect.client_id);
-        let connection = build_connection(
+        let mut connection = build_connection(
             context.connect_id,
             client_id.clone(),
             &cluster,
@@ -154,6 +205,14 @@ pub async fn connect(&self, context: MqttServiceConnectContext) -> MqttPacket {
             &context.connect_properties,
             &context.addr,
         );
+        if let Some(listener) = &listener {
+            connection.set_mountpoint(
+                listener
+                    .mountpoint
+                    .as_ref()
+                    .map(|mountpoint| build_mountpoint(mountpoint, &client_id, &context.login)),
+            );
+        }
 
         if self.auth_driver.auth_connect_check(&connection).await {
             return response_packet_mqtt_connect_fail(
@@ -164,12 +223,26 @@ pub async fn connect(&self, context: MqttServiceConnectContext) -> MqttPacket {
             );
         }
 
-        // login check
-        match self
-            .auth_driver
-            .auth_login_check(&context.login, &context.connect_properties, &context.addr)
-            .await
-        {
+        // login check, the listener authentication chain takes precedence over the global one
+        let login_result = match &listener {
+            Some(listener) if !listener.authentication.is_empty() => {
+                self.auth_driver
+                    .auth_listener_login_check(
+                        &listener.authentication,
+                        &context.login,
+                        &context.connect_properties,
+                        &context.addr,
+                        context.client_cert_verified,
+                    )
+                    .await
+            }
+            _ => {
+                self.auth_driver
+                    .auth_login_check(&context.login, &context.connect_properties, &context.addr)
+                    .await
+            }
+        };
+        match login_result {
             Ok(flag) => {
                 if !flag {
                     record_mqtt_auth_failed();
@@ -233,6 +306,26 @@ pub async fn connect(&self, context: MqttServiceConnectContext) -> MqttPacket {
             }
         };
 
+        if let Err(e) = takeover_session(&TakeoverSessionContext {
+            client_id: client_id.clone(),
+            connect_id: context.connect_id,
+            resume_session: !new_session,
+            has_last_will: context.last_will.is_some(),
+            cache_manager: self.cache_manager.clone(),
+            client_pool: self.client_pool.clone(),
+            connection_manager: self.connection_manager.clone(),
+            subscribe_manager: self.subscribe_manager.clone(),
+        })
+        .await
+        {
+            return response_packet_mqtt_connect_fail(
+                &self.protocol,
+                ConnectReturnCode::UnspecifiedError,
+                &context.connect_properties,
+                Some(e.to_string()),
+            );
+        }
+
         if let Err(e) = save_session(
             context.connect_id,
             session.clone(),
@@ -351,6 +444,15 @@ pub async fn publish(
 
         let is_pub_ack = publish.qos != QoS::ExactlyOnce;
 
+        // The span continues the trace of the publisher when it carries a traceparent
+        let span = TraceSpan::from_user_properties(
+            "mqtt.publish",
+            publish_properties
+                .as_ref()
+                .map(|properties| properties.user_properties.as_slice())
+                .unwrap_or(&[]),
+        );
+
         let mut topic_name = match get_topic_name(
             &self.cache_manager,
             connect_id,
@@ -392,6 +494,14 @@ pub async fn publish(
             None
         };
 
+        // Prefix the topic with the mountpoint of the listener the client connected to
+        if connection.mountpoint.is_some() {
+            topic_name = mount_topic(&connection.mountpoint, &topic_name);
+            if let Some(info) = delay_info.as_mut() {
+                info.target_topic_name = topic_name.clone();
+            }
+        }
+
         if !self
             .auth_driver
             .auth_publish_check(&connection, &topic_name, publish.retain, publish.qos)
@@ -444,8 +554,20 @@ pub async fn publish(
             delay_info = Some(new_delay_info);
         }
 
+        if span.is_recording() {
+            span.set_attribute("mqtt.client_id", connection.client_id.clone());
+            span.set_attribute("mqtt.topic", topic_name.clone());
+            span.set_attribute("mqtt.qos", format!("{:?}", publish.qos));
+        }
+
         if self.schema_manager.is_check_schema(&topic_name) {
+            let schema_span = span.child("mqtt.schema.validate");
             if let Err(e) = self.schema_manager.validate(&topic_name, &publish.payload) {
+                schema_span.set_error(e.to_string());
+                span.set_error(e.to_string());
+                self.cache_manager
+                    .detail_metrics
+                    .record_dropped(&connection.client_id, &topic_name);
                 return Some(build_pub_ack_fail(
                     &self.protocol,
                     &connection,
@@ -458,37 +580,81 @@ pub async fn publish(
 
         let client_id = connection.client_id.clone();
 
-        // Persisting stores message data
-        let offset = match save_message(SaveMessageContext {
-            message_storage_adapter: self.message_storage_adapter.clone(),
-            delay_message_manager: self.delay_message_manager.clone(),
+        // A rule with a drop action consumes the message, it is acknowledged but not stored
+        let dropped_by_rule = apply_message_rules(ApplyMessageRulesContext {
             cache_manager: self.cache_manager.clone(),
             client_pool: self.client_pool.clone(),
-            publish: publish.clone(),
-            publish_properties: publish_properties.clone(),
-            subscribe_manager: self.subscribe_manager.clone(),
+            message_storage_adapter: self.message_storage_adapter.clone(),
+            connector_manager: self.connector_manager.clone(),
             client_id: client_id.clone(),
-            topic: topic.clone(),
-            delay_info,
+            username: connection.login_user.clone(),
+            peerhost: connection.source_ip_addr.clone(),
+            topic_name: topic_name.clone(),
+            publish: publish.clone(),
         })
-        .await
-        {
-            Ok(da) => {
-                format!("{da:?}")
+        .await;
+
+        // Persisting stores message data
+        let offset = if dropped_by_rule {
+            span.set_attribute("mqtt.dropped_by_rule", "true");
+            self.cache_manager
+                .detail_metrics
+                .record_dropped(&client_id, &topic_name);
+            "".to_string()
+        } else {
+            // The stored message carries the ingest span, so the push to subscribers and
+            // the delivery by connectors continue the same trace
+            let mut stored_properties = publish_properties.clone();
+            if span.is_recording() {
+                span.inject_user_properties(
+                    &mut stored_properties
+                        .get_or_insert_with(PublishProperties::default)
+                        .user_properties,
+                );
             }
-            Err(e) => {
-                return Some(build_pub_ack_fail(
-                    &self.protocol,
-                    &connection,
-                    publish.p_kid,
-                    Some(e.to_string()),
-                    is_pub_ack,
-                ))
+
+            let storage_span = span.child("mqtt.storage.write");
+            match save_message(SaveMessageContext {
+                message_storage_adapter: self.message_storage_adapter.clone(),
+                delay_message_manager: self.delay_message_manager.clone(),
+                cache_manager: self.cache_manager.clone(),
+                client_pool: self.client_pool.clone(),
+                publish: publish.clone(),
+                publish_properties: stored_properties,
+                subscribe_manager: self.subscribe_manager.clone(),
+                client_id: client_id.clone(),
+                topic: topic.clone(),
+                delay_info,
+            })
+            .await
+            {
+                Ok(da) => {
+                    format!("{da:?}")
+                }
+                Err(e) => {
+                    storage_span.set_error(e.to_string());
+                    span.set_error(e.to_string());
+                    self.cache_manager
+                        .detail_metrics
+                        .record_dropped(&client_id, &topic_name);
+                    return Some(build_pub_ack_fail(
+                        &self.protocol,
+                        &connection,
+                        publish.p_kid,
+                        Some(e.to_string()),
+                        is_pub_ack,
+                    ));
+                }
             }
         };
 
         record_mqtt_messages_received_inc(topic_name.clone());
         record_mqtt_message_bytes_received(topic_name.clone(), publish.payload.len() as u64);
+        self.cache_manager.detail_metrics.record_message_in(
+            &client_id,
+            &topic_name,
+            publish.payload.len() as u64,
+        );
         let user_properties: Vec<(String, String)> = vec![("offset".to_string(), offset)];
 
         self.cache_manager
@@ -707,6 +873,7 @@ pub async fn subscribe(
             );
         };
 
+        let subscribe = &mount_subscribe(&connection.mountpoint, subscribe);
         if let Some(packet) = subscribe_validator(
             &self.protocol,
             &self.auth_driver,
@@ -828,6 +995,7 @@ pub async fn un_subscribe(
             );
         };
 
+        let un_subscribe = &mount_un_subscribe(&connection.mountpoint, un_subscribe);
         if let Some(packet) = un_subscribe_validator(
             &connection.client_id,
             &self.subscribe_manager,
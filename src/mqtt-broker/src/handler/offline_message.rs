@@ -24,6 +24,7 @@
     retain::save_retain_message,
 };
 use crate::{storage::message::MessageStorage, subscribe::manager::SubscribeManager};
+use common_base::telemetry::trace::is_trace_context_key;
 use common_base::tools::now_second;
 use common_metrics::mqtt::packets::record_messages_dropped_no_subscribers_metrics;
 use delay_message::DelayMessageManager;
@@ -61,6 +62,10 @@ pub async fn save_message(context: SaveMessageContext) -> Result<Option<String>,
         !is_exist_subscribe(&context.subscribe_manager, &context.topic.topic_name);
     if offline_message_disabled && not_exist_subscribe {
         record_messages_dropped_no_subscribers_metrics(context.publish.qos);
+        context
+            .cache_manager
+            .detail_metrics
+            .record_dropped(&context.client_id, &context.topic.topic_name);
         return Ok(None);
     }
 
@@ -109,6 +114,12 @@ async fn save_delay_message(
     delay_info: &DelayPublishTopic,
 ) -> Result<Option<String>, MqttBrokerError> {
     let new_publish_properties = if let Some(mut properties) = publish_properties.clone() {
+        // Keep the trace context so the delayed delivery stays in the publisher's trace
+        let trace_context: Vec<(String, String)> = properties
+            .user_properties
+            .into_iter()
+            .filter(|(k, _)| is_trace_context_key(k))
+            .collect();
         properties.user_properties = vec![
             (DELAY_MESSAGE_FLAG.to_string(), "true".to_string()),
             (DELAY_MESSAGE_RECV_MS.to_string(), now_second().to_string()),
@@ -117,6 +128,7 @@ async fn save_delay_message(
                 (now_second() + delay_info.delay_timestamp).to_string(),
             ),
         ];
+        properties.user_properties.extend(trace_context);
         properties
     } else {
         PublishProperties {
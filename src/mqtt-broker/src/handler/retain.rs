@@ -14,8 +14,9 @@
 
 use super::cache::MQTTCacheManager;
 use super::constant::{SUB_RETAIN_MESSAGE_PUSH_FLAG, SUB_RETAIN_MESSAGE_PUSH_FLAG_VALUE};
-use super::message::build_message_expire;
+use super::message::{build_message_expire, is_message_expire, message_remaining_expiry_interval};
 use crate::common::types::ResultMqttBrokerError;
+use crate::handler::mountpoint::unmount_topic;
 use crate::handler::sub_option::{
     get_retain_flag_by_retain_as_published, is_send_msg_by_bo_local,
     is_send_retain_msg_by_retain_handling,
@@ -157,6 +158,12 @@ async fn send_retain_message(context: SendRetainMessageContext) -> ResultMqttBro
         }
     }
 
+    let mountpoint = context
+        .cache_manager
+        .get_connect_id(&context.client_id)
+        .and_then(|connect_id| context.cache_manager.get_connection(connect_id))
+        .and_then(|conn| conn.mountpoint);
+
     for filter in context.subscribe.filters.iter() {
         if !is_send_retain_msg_by_retain_handling(
             &filter.path,
@@ -185,6 +192,11 @@ async fn send_retain_message(context: SendRetainMessageContext) -> ResultMqttBro
                 continue;
             };
 
+            if is_message_expire(&msg) {
+                info!("retain messages: The retained message of topic {} has expired and is not sent. Client ID: {}", topic_name, context.client_id);
+                continue;
+            }
+
             if !is_send_msg_by_bo_local(filter.nolocal, &context.client_id, &msg.client_id) {
                 info!("retain messages: Determine whether to send retained messages based on the no local strategy. Client ID: {}", context.client_id);
                 continue;
@@ -204,7 +216,7 @@ async fn send_retain_message(context: SendRetainMessageContext) -> ResultMqttBro
 
             let properties = PublishProperties {
                 payload_format_indicator: msg.format_indicator,
-                message_expiry_interval: Some(msg.expiry_interval as u32),
+                message_expiry_interval: message_remaining_expiry_interval(&msg),
                 topic_alias: None,
                 response_topic: msg.response_topic,
                 correlation_data: msg.correlation_data,
@@ -224,7 +236,7 @@ async fn send_retain_message(context: SendRetainMessageContext) -> ResultMqttBro
                 qos,
                 p_kid: pkid,
                 retain,
-                topic: Bytes::from(topic_name.clone()),
+                topic: Bytes::from(unmount_topic(&mountpoint, &topic_name)),
                 payload: msg.payload,
             };
 
@@ -0,0 +1,137 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::cache::MQTTCacheManager;
+use super::error::MqttBrokerError;
+use super::message::{build_message_expire, publish_message_to_topic};
+use crate::bridge::manager::ConnectorManager;
+use crate::common::types::ResultMqttBrokerError;
+use crate::storage::message::MessageStorage;
+use bytes::Bytes;
+use common_base::tools::now_second;
+use grpc_clients::pool::ClientPool;
+use metadata_struct::mqtt::message::MqttMessage;
+use metadata_struct::mqtt::rule::MqttRuleAction;
+use protocol::mqtt::common::Publish;
+use rule_engine::engine::{RuleMessage, RuleOutput};
+use std::sync::Arc;
+use storage_adapter::storage::ArcStorageAdapter;
+use tracing::warn;
+
+#[derive(Clone)]
+pub struct ApplyMessageRulesContext {
+    pub cache_manager: Arc<MQTTCacheManager>,
+    pub client_pool: Arc<ClientPool>,
+    pub message_storage_adapter: ArcStorageAdapter,
+    pub connector_manager: Arc<ConnectorManager>,
+    pub client_id: String,
+    pub username: String,
+    pub peerhost: String,
+    pub topic_name: String,
+    pub publish: Publish,
+}
+
+// Runs the rule engine against a published message and executes the actions of every
+// matching rule. Returns true when a rule asked for the original message to be dropped.
+pub async fn apply_message_rules(context: ApplyMessageRulesContext) -> bool {
+    let rule_engine = &context.cache_manager.rule_engine;
+    if rule_engine.is_empty() {
+        return false;
+    }
+
+    let outputs = rule_engine.evaluate(&RuleMessage {
+        client_id: &context.client_id,
+        username: &context.username,
+        peerhost: &context.peerhost,
+        topic: &context.topic_name,
+        qos: context.publish.qos as u8,
+        retain: context.publish.retain,
+        payload: &context.publish.payload,
+        timestamp: now_second(),
+    });
+
+    let mut dropped = false;
+    for output in outputs.iter() {
+        for action in output.actions.iter() {
+            if *action == MqttRuleAction::Drop {
+                dropped = true;
+                continue;
+            }
+            if let Err(e) = run_rule_action(&context, output, action).await {
+                warn!(
+                    "Rule {} failed to execute action {:?}, error message: {}",
+                    output.rule_name, action, e
+                );
+            }
+        }
+    }
+    dropped
+}
+
+async fn run_rule_action(
+    context: &ApplyMessageRulesContext,
+    output: &RuleOutput,
+    action: &MqttRuleAction,
+) -> ResultMqttBrokerError {
+    let payload = Bytes::from(serde_json::to_vec(&output.output)?);
+    match action {
+        MqttRuleAction::Republish { topic, qos, retain } => {
+            let publish = Publish {
+                dup: false,
+                qos: *qos,
+                retain: *retain,
+                topic: Bytes::from(topic.clone()),
+                payload,
+                ..Default::default()
+            };
+            publish_message_to_topic(
+                &context.cache_manager,
+                &context.client_pool,
+                &context.message_storage_adapter,
+                &context.client_id,
+                &publish,
+                &None,
+            )
+            .await
+        }
+        MqttRuleAction::Connector { connector_name } => {
+            let Some(connector) = context.connector_manager.get_connector(connector_name) else {
+                return Err(MqttBrokerError::ConnectorNotFound(connector_name.clone()));
+            };
+            if connector.topic_id.is_empty() {
+                return Err(MqttBrokerError::ConnectorNotBoundToTopic(
+                    connector_name.clone(),
+                ));
+            }
+
+            // The connector consumes the shard of the topic it is bound to
+            let publish = Publish {
+                payload,
+                ..context.publish.clone()
+            };
+            let message_expire = build_message_expire(&context.cache_manager, &None);
+            let Some(record) =
+                MqttMessage::build_record(&context.client_id, &publish, &None, message_expire)
+            else {
+                return Err(MqttBrokerError::FailedToBuildMessage);
+            };
+            let message_storage = MessageStorage::new(context.message_storage_adapter.clone());
+            message_storage
+                .append_topic_message(&connector.topic_id, vec![record])
+                .await?;
+            Ok(())
+        }
+        MqttRuleAction::Drop => Ok(()),
+    }
+}
@@ -0,0 +1,501 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::cache::MQTTCacheManager;
+use super::error::MqttBrokerError;
+use super::message::publish_message_to_topic;
+use super::topic::topic_name_validator;
+use crate::common::types::ResultMqttBrokerError;
+use crate::storage::scheduled_publish::{
+    ScheduledPublishFire, ScheduledPublishStorage, ScheduledPublishTrigger,
+    SCHEDULED_PUBLISH_TRIGGER_SHARD_NAME,
+};
+use bytes::Bytes;
+use common_base::error::ResultCommonError;
+use common_base::tools::{loop_select, now_second};
+use common_base::utils::cron::CronExpression;
+use common_config::broker::broker_config;
+use dashmap::DashMap;
+use delay_message::DelayMessageManager;
+use grpc_clients::pool::ClientPool;
+use metadata_struct::mqtt::scheduled_publish::{MqttPublishSchedule, MqttScheduledPublish};
+use protocol::mqtt::common::Publish;
+use std::sync::Arc;
+use storage_adapter::storage::ArcStorageAdapter;
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+
+// Firings older than this are removed from the history
+const SCHEDULED_PUBLISH_HISTORY_RETENTION_SECONDS: u64 = 7 * 24 * 3600;
+const SCHEDULED_PUBLISH_HISTORY_CLEAN_INTERVAL_SECONDS: u64 = 3600;
+// Triggers are read within seconds, an hour leaves room for a broker restart
+const SCHEDULED_PUBLISH_TRIGGER_RETENTION_SECONDS: u64 = 3600;
+const TRIGGER_READ_BATCH_NUM: u64 = 100;
+const TRIGGER_CHECK_INTERVAL_SECONDS: u64 = 30;
+// How long a trigger may be late before its chain is taken as lost
+const TRIGGER_LOST_GRACE_SECONDS: u64 = 60;
+
+// The fire time of the latest trigger read for each schedule, together with
+// the create time of the schedule it belongs to.
+type SeenTriggers = Arc<DashMap<String, (u64, u64)>>;
+
+#[derive(Clone)]
+pub struct ScheduledPublishContext {
+    pub cache_manager: Arc<MQTTCacheManager>,
+    pub client_pool: Arc<ClientPool>,
+    pub message_storage_adapter: ArcStorageAdapter,
+    pub delay_message_manager: Arc<DelayMessageManager>,
+}
+
+pub fn validate_scheduled_publish(schedule: &MqttScheduledPublish) -> ResultMqttBrokerError {
+    if schedule.name.is_empty() {
+        return Err(MqttBrokerError::InvalidScheduledPublish(
+            "name must not be empty".to_string(),
+        ));
+    }
+
+    topic_name_validator(&schedule.topic_name)?;
+    if schedule.topic_name.contains('+') || schedule.topic_name.contains('#') {
+        return Err(MqttBrokerError::InvalidScheduledPublish(format!(
+            "topic name {} must not contain wildcards",
+            schedule.topic_name
+        )));
+    }
+
+    // Also checks the cron expression and the timezone
+    if next_fire_time(schedule, now_second())?.is_none() {
+        return Err(MqttBrokerError::InvalidScheduledPublish(format!(
+            "schedule {} never fires",
+            schedule.name
+        )));
+    }
+    Ok(())
+}
+
+// The first firing strictly after `after`. Intervals count from the create
+// time of the schedule, nothing fires after the end time.
+pub fn next_fire_time(
+    schedule: &MqttScheduledPublish,
+    after: u64,
+) -> Result<Option<u64>, MqttBrokerError> {
+    let fire_time = match &schedule.schedule {
+        MqttPublishSchedule::Cron { expression } => {
+            CronExpression::parse(expression)?.next_after(after, &schedule.timezone)?
+        }
+        MqttPublishSchedule::Interval { seconds } => {
+            if *seconds == 0 {
+                return Err(MqttBrokerError::InvalidScheduledPublish(
+                    "interval must be greater than 0".to_string(),
+                ));
+            }
+            let elapsed = after.saturating_sub(schedule.create_time);
+            (elapsed / seconds + 1)
+                .checked_mul(*seconds)
+                .and_then(|offset| schedule.create_time.checked_add(offset))
+        }
+    };
+
+    Ok(fire_time.filter(|fire_time| match schedule.end_time {
+        Some(end_time) => *fire_time <= end_time,
+        None => true,
+    }))
+}
+
+// Queues the next firing of a schedule in the delay message engine, the
+// trigger comes back through the trigger shard once it is due.
+pub async fn schedule_next_fire(
+    delay_message_manager: &Arc<DelayMessageManager>,
+    schedule: &MqttScheduledPublish,
+    after: u64,
+) -> Result<Option<u64>, MqttBrokerError> {
+    let Some(fire_time) = next_fire_time(schedule, after)? else {
+        return Ok(None);
+    };
+
+    let trigger = ScheduledPublishTrigger {
+        name: schedule.name.clone(),
+        create_time: schedule.create_time,
+        fire_time,
+    };
+    delay_message_manager
+        .send(
+            SCHEDULED_PUBLISH_TRIGGER_SHARD_NAME,
+            fire_time.saturating_sub(now_second()),
+            trigger.encode()?,
+        )
+        .await?;
+    Ok(Some(fire_time))
+}
+
+pub async fn start_scheduled_publish_thread(
+    context: ScheduledPublishContext,
+    stop_send: broadcast::Sender<bool>,
+) {
+    let storage = ScheduledPublishStorage::new(context.message_storage_adapter.clone());
+    if let Err(e) = storage.init_shards().await {
+        warn!(
+            "Failed to create the scheduled publish shards, error message: {}",
+            e
+        );
+    }
+
+    // The triggers queued before a restart stay in the delay message engine,
+    // so starting does not queue anything. Chains that did get lost, e.g.
+    // when the owner of a schedule went down while handling its trigger, are
+    // queued again by the new owner once their firing is overdue.
+    info!(
+        "Scheduled publish thread started, schedules: {}",
+        context.cache_manager.list_scheduled_publishes().len()
+    );
+    let seen_triggers: SeenTriggers = Arc::new(DashMap::new());
+    let start_time = now_second();
+
+    let clean_storage = storage.clone();
+    let clean_stop_send = stop_send.clone();
+    tokio::spawn(async move {
+        let ac_fn = async || -> ResultCommonError {
+            let now = now_second();
+            let before = now.saturating_sub(SCHEDULED_PUBLISH_HISTORY_RETENTION_SECONDS);
+            if let Err(e) = clean_storage.delete_fires_before(before).await {
+                warn!(
+                    "Failed to clean the scheduled publish history, error message: {}",
+                    e
+                );
+            }
+            let before = now.saturating_sub(SCHEDULED_PUBLISH_TRIGGER_RETENTION_SECONDS);
+            if let Err(e) = clean_storage.delete_triggers_before(before).await {
+                warn!(
+                    "Failed to clean the scheduled publish triggers, error message: {}",
+                    e
+                );
+            }
+            Ok(())
+        };
+        loop_select(
+            ac_fn,
+            SCHEDULED_PUBLISH_HISTORY_CLEAN_INTERVAL_SECONDS,
+            &clean_stop_send,
+        )
+        .await;
+    });
+
+    let check_context = context.clone();
+    let check_seen_triggers = seen_triggers.clone();
+    let check_stop_send = stop_send.clone();
+    tokio::spawn(async move {
+        let ac_fn = async || -> ResultCommonError {
+            requeue_lost_triggers(&check_context, &check_seen_triggers, start_time).await;
+            Ok(())
+        };
+        loop_select(ac_fn, TRIGGER_CHECK_INTERVAL_SECONDS, &check_stop_send).await;
+    });
+
+    let ac_fn = async || -> ResultCommonError {
+        if let Err(e) = fire_scheduled_publishes(&context, &storage, &seen_triggers).await {
+            warn!("Failed to fire scheduled publishes, error message: {}", e);
+        }
+        Ok(())
+    };
+    loop_select(ac_fn, 1, &stop_send).await;
+}
+
+// Every broker reads every trigger, only the owner of the schedule fires it.
+// Owners are picked by hashing the schedule name over the broker list, so a
+// firing is published by one broker even when all of them read its trigger.
+async fn fire_scheduled_publishes(
+    context: &ScheduledPublishContext,
+    storage: &ScheduledPublishStorage,
+    seen_triggers: &SeenTriggers,
+) -> ResultMqttBrokerError {
+    let group_name = trigger_group_name();
+    let offset = storage.get_trigger_offset(&group_name).await?;
+    let triggers = storage
+        .read_triggers(offset, TRIGGER_READ_BATCH_NUM)
+        .await?;
+    let Some((last_offset, _)) = triggers.last() else {
+        return Ok(());
+    };
+    let next_offset = last_offset + 1;
+
+    let broker_id = broker_config().broker_id;
+    for (_, trigger) in triggers {
+        record_seen_trigger(seen_triggers, &trigger);
+        if !context
+            .cache_manager
+            .broker_cache
+            .is_owner(broker_id, &trigger.name)
+        {
+            continue;
+        }
+        fire_scheduled_publish(context, storage, trigger).await?;
+    }
+    storage
+        .commit_trigger_offset(&group_name, next_offset)
+        .await
+}
+
+async fn fire_scheduled_publish(
+    context: &ScheduledPublishContext,
+    storage: &ScheduledPublishStorage,
+    trigger: ScheduledPublishTrigger,
+) -> ResultMqttBrokerError {
+    // The schedule was deleted, or deleted and created again
+    let Some(schedule) = context.cache_manager.get_scheduled_publish(&trigger.name) else {
+        return Ok(());
+    };
+    if schedule.create_time != trigger.create_time {
+        return Ok(());
+    }
+
+    // Another trigger of the same firing got here first and already queued
+    // the next one, dropping this one merges the two chains. Only the owner
+    // fires, so the check and the save below do not race with other brokers.
+    if storage
+        .get_fire(&trigger.name, trigger.create_time, trigger.fire_time)
+        .await?
+        .is_some()
+    {
+        return Ok(());
+    }
+
+    let publish_time = now_second();
+    let result = publish_scheduled_message(context, &schedule).await;
+    if let Err(e) = &result {
+        warn!(
+            "Scheduled publish {} failed to publish to topic {}, error message: {}",
+            schedule.name, schedule.topic_name, e
+        );
+    }
+
+    // The next firing is queued before the firing is recorded, a failure in
+    // between publishes again rather than ending the schedule.
+    schedule_next_fire(
+        &context.delay_message_manager,
+        &schedule,
+        trigger.fire_time.max(publish_time),
+    )
+    .await?;
+
+    storage
+        .save_fire(&ScheduledPublishFire {
+            name: trigger.name,
+            create_time: trigger.create_time,
+            fire_time: trigger.fire_time,
+            publish_time,
+            success: result.is_ok(),
+            error: result.err().map(|e| e.to_string()),
+        })
+        .await?;
+    Ok(())
+}
+
+fn record_seen_trigger(seen_triggers: &SeenTriggers, trigger: &ScheduledPublishTrigger) {
+    let mut entry = seen_triggers
+        .entry(trigger.name.clone())
+        .or_insert((trigger.create_time, trigger.fire_time));
+    if trigger.create_time > entry.0
+        || (trigger.create_time == entry.0 && trigger.fire_time > entry.1)
+    {
+        *entry = (trigger.create_time, trigger.fire_time);
+    }
+}
+
+// Queues the next firing again for owned schedules whose trigger is overdue.
+// The chain is expected to continue from the latest trigger read, or from
+// the start of this broker when none was read since.
+async fn requeue_lost_triggers(
+    context: &ScheduledPublishContext,
+    seen_triggers: &SeenTriggers,
+    start_time: u64,
+) {
+    let broker_id = broker_config().broker_id;
+    let now = now_second();
+    let schedules = context.cache_manager.list_scheduled_publishes();
+    seen_triggers.retain(|name, _| schedules.iter().any(|schedule| schedule.name == *name));
+
+    for schedule in schedules.iter() {
+        if !context
+            .cache_manager
+            .broker_cache
+            .is_owner(broker_id, &schedule.name)
+        {
+            continue;
+        }
+
+        let after = match seen_triggers.get(&schedule.name) {
+            Some(entry) if entry.0 == schedule.create_time => entry.1,
+            _ => start_time.max(schedule.create_time),
+        };
+        let expected = match next_fire_time(schedule, after) {
+            Ok(Some(expected)) => expected,
+            Ok(None) => continue,
+            Err(e) => {
+                warn!(
+                    "Failed to compute the next firing of scheduled publish {}, error message: {}",
+                    schedule.name, e
+                );
+                continue;
+            }
+        };
+        if expected.saturating_add(TRIGGER_LOST_GRACE_SECONDS) > now {
+            continue;
+        }
+
+        warn!(
+            "Trigger of scheduled publish {} for {} did not arrive, queueing the next firing again",
+            schedule.name, expected
+        );
+        match schedule_next_fire(&context.delay_message_manager, schedule, now).await {
+            Ok(_) => {
+                seen_triggers.insert(schedule.name.clone(), (schedule.create_time, now));
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to schedule the next firing of scheduled publish {}, error message: {}",
+                    schedule.name, e
+                );
+            }
+        }
+    }
+}
+
+async fn publish_scheduled_message(
+    context: &ScheduledPublishContext,
+    schedule: &MqttScheduledPublish,
+) -> ResultMqttBrokerError {
+    let publish = Publish {
+        qos: schedule.qos,
+        retain: schedule.retain,
+        topic: Bytes::from(schedule.topic_name.clone()),
+        payload: Bytes::from(schedule.payload.clone()),
+        ..Default::default()
+    };
+    publish_message_to_topic(
+        &context.cache_manager,
+        &context.client_pool,
+        &context.message_storage_adapter,
+        &scheduled_publish_client_id(&schedule.name),
+        &publish,
+        &None,
+    )
+    .await
+}
+
+pub fn scheduled_publish_client_id(name: &str) -> String {
+    format!("scheduler:{name}")
+}
+
+// Every broker reads the whole trigger shard with its own group, see
+// fire_scheduled_publishes
+fn trigger_group_name() -> String {
+    let conf = broker_config();
+    format!(
+        "$scheduled-publish-{}-{}",
+        conf.cluster_name, conf.broker_id
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protocol::mqtt::common::QoS;
+
+    fn build_schedule(schedule: MqttPublishSchedule) -> MqttScheduledPublish {
+        MqttScheduledPublish {
+            name: "s1".to_string(),
+            topic_name: "device/cmd".to_string(),
+            payload: "reboot".to_string(),
+            qos: QoS::AtLeastOnce,
+            retain: false,
+            schedule,
+            timezone: "UTC".to_string(),
+            end_time: None,
+            create_time: 1000,
+        }
+    }
+
+    #[test]
+    fn next_fire_time_test() {
+        let mut schedule = build_schedule(MqttPublishSchedule::Interval { seconds: 60 });
+        assert_eq!(next_fire_time(&schedule, 0).unwrap(), Some(1060));
+        assert_eq!(next_fire_time(&schedule, 1000).unwrap(), Some(1060));
+        assert_eq!(next_fire_time(&schedule, 1059).unwrap(), Some(1060));
+        assert_eq!(next_fire_time(&schedule, 1060).unwrap(), Some(1120));
+
+        schedule.end_time = Some(1100);
+        assert_eq!(next_fire_time(&schedule, 1000).unwrap(), Some(1060));
+        assert_eq!(next_fire_time(&schedule, 1060).unwrap(), None);
+
+        // 2024-01-01 00:00:00 UTC
+        let mut schedule = build_schedule(MqttPublishSchedule::Cron {
+            expression: "30 8 * * *".to_string(),
+        });
+        assert_eq!(
+            next_fire_time(&schedule, 1704067200).unwrap(),
+            Some(1704067200 + 8 * 3600 + 30 * 60)
+        );
+
+        schedule.timezone = "Asia/Shanghai".to_string();
+        assert_eq!(
+            next_fire_time(&schedule, 1704067200).unwrap(),
+            Some(1704067200 + 30 * 60)
+        );
+
+        schedule.timezone = "Mars/Olympus".to_string();
+        assert!(next_fire_time(&schedule, 1704067200).is_err());
+    }
+
+    #[test]
+    fn record_seen_trigger_test() {
+        let seen_triggers: SeenTriggers = Arc::new(DashMap::new());
+        let trigger = |create_time, fire_time| ScheduledPublishTrigger {
+            name: "s1".to_string(),
+            create_time,
+            fire_time,
+        };
+
+        record_seen_trigger(&seen_triggers, &trigger(1000, 1120));
+        record_seen_trigger(&seen_triggers, &trigger(1000, 1060));
+        assert_eq!(*seen_triggers.get("s1").unwrap(), (1000, 1120));
+
+        // A trigger of the schedule before it was created again is ignored
+        record_seen_trigger(&seen_triggers, &trigger(2000, 2060));
+        record_seen_trigger(&seen_triggers, &trigger(1000, 1180));
+        assert_eq!(*seen_triggers.get("s1").unwrap(), (2000, 2060));
+    }
+
+    #[test]
+    fn validate_scheduled_publish_test() {
+        let mut schedule = build_schedule(MqttPublishSchedule::Cron {
+            expression: "*/5 * * * *".to_string(),
+        });
+        assert!(validate_scheduled_publish(&schedule).is_ok());
+
+        schedule.topic_name = "device/+".to_string();
+        assert!(validate_scheduled_publish(&schedule).is_err());
+
+        schedule.topic_name = "device/cmd".to_string();
+        schedule.schedule = MqttPublishSchedule::Cron {
+            expression: "61 * * * *".to_string(),
+        };
+        assert!(validate_scheduled_publish(&schedule).is_err());
+
+        schedule.schedule = MqttPublishSchedule::Interval { seconds: 0 };
+        assert!(validate_scheduled_publish(&schedule).is_err());
+
+        schedule.schedule = MqttPublishSchedule::Interval { seconds: 10 };
+        schedule.end_time = Some(now_second().saturating_sub(1));
+        assert!(validate_scheduled_publish(&schedule).is_err());
+    }
+}
@@ -160,6 +160,36 @@ pub async fn auth_login_check(
         }
     }
 
+    // Try every authenticator of the listener chain in order, the first one that passes wins.
+    pub async fn auth_listener_login_check(
+        &self,
+        chain: &[String],
+        login: &Option<Login>,
+        connect_properties: &Option<ConnectProperties>,
+        socket_addr: &SocketAddr,
+        client_cert_verified: bool,
+    ) -> Result<bool, MqttBrokerError> {
+        for authenticator in chain {
+            let flag = match authenticator.as_str() {
+                "anonymous" => true,
+                "x509" => client_cert_verified,
+                "password" => {
+                    self.auth_login_check(login, connect_properties, socket_addr)
+                        .await?
+                }
+                _ => {
+                    return Err(MqttBrokerError::UnsupportedAuthType(
+                        authenticator.to_string(),
+                    ))
+                }
+            };
+            if flag {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
     pub async fn auth_connect_check(&self, connection: &MQTTConnection) -> bool {
         // default true if blacklist check fails
         is_blacklist(&self.cache_manager, connection).unwrap_or(true)
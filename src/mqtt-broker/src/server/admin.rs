@@ -0,0 +1,179 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::bridge::manager::ConnectorManager;
+use crate::handler::cache::MQTTCacheManager;
+use crate::handler::client_admin::{
+    clear_session_by_req, inspect_client_by_req, kick_client_by_req, subscribe_for_client_by_req,
+    unsubscribe_for_client_by_req,
+};
+use crate::handler::drain::{
+    drain_node_by_req, drain_node_status_by_req, rebalance_connections_by_req,
+};
+use crate::subscribe::manager::SubscribeManager;
+use grpc_clients::pool::ClientPool;
+use network_server::common::connection_manager::ConnectionManager;
+use protocol::broker::broker_mqtt_admin::mqtt_broker_admin_service_server::MqttBrokerAdminService;
+use protocol::broker::broker_mqtt_admin::{
+    ClearSessionReply, ClearSessionRequest, DrainNodeReply, DrainNodeRequest, DrainNodeStatusReply,
+    DrainNodeStatusRequest, InspectClientReply, InspectClientRequest, KickClientReply,
+    KickClientRequest, RebalanceConnectionsReply, RebalanceConnectionsRequest,
+    SubscribeForClientReply, SubscribeForClientRequest, UnsubscribeForClientReply,
+    UnsubscribeForClientRequest,
+};
+use std::sync::Arc;
+use storage_adapter::storage::ArcStorageAdapter;
+use tonic::{Request, Response, Status};
+
+pub struct GrpcAdminServices {
+    cache_manager: Arc<MQTTCacheManager>,
+    subscribe_manager: Arc<SubscribeManager>,
+    connection_manager: Arc<ConnectionManager>,
+    client_pool: Arc<ClientPool>,
+    message_storage_adapter: ArcStorageAdapter,
+    connector_manager: Arc<ConnectorManager>,
+}
+
+impl GrpcAdminServices {
+    pub fn new(
+        cache_manager: Arc<MQTTCacheManager>,
+        subscribe_manager: Arc<SubscribeManager>,
+        connection_manager: Arc<ConnectionManager>,
+        client_pool: Arc<ClientPool>,
+        message_storage_adapter: ArcStorageAdapter,
+        connector_manager: Arc<ConnectorManager>,
+    ) -> Self {
+        GrpcAdminServices {
+            cache_manager,
+            subscribe_manager,
+            connection_manager,
+            client_pool,
+            message_storage_adapter,
+            connector_manager,
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl MqttBrokerAdminService for GrpcAdminServices {
+    async fn kick_client(
+        &self,
+        request: Request<KickClientRequest>,
+    ) -> Result<Response<KickClientReply>, Status> {
+        let req = request.into_inner();
+        kick_client_by_req(
+            &self.cache_manager,
+            &self.client_pool,
+            &self.connection_manager,
+            &self.subscribe_manager,
+            &req,
+        )
+        .await
+        .map_err(|e| Status::internal(e.to_string()))
+        .map(Response::new)
+    }
+
+    async fn clear_session(
+        &self,
+        request: Request<ClearSessionRequest>,
+    ) -> Result<Response<ClearSessionReply>, Status> {
+        let req = request.into_inner();
+        clear_session_by_req(
+            &self.cache_manager,
+            &self.client_pool,
+            &self.connection_manager,
+            &self.subscribe_manager,
+            &req,
+        )
+        .await
+        .map_err(|e| Status::internal(e.to_string()))
+        .map(Response::new)
+    }
+
+    async fn inspect_client(
+        &self,
+        request: Request<InspectClientRequest>,
+    ) -> Result<Response<InspectClientReply>, Status> {
+        let req = request.into_inner();
+        inspect_client_by_req(
+            &self.cache_manager,
+            &self.connection_manager,
+            &self.subscribe_manager,
+            &self.message_storage_adapter,
+            &req,
+        )
+        .await
+        .map_err(|e| Status::internal(e.to_string()))
+        .map(Response::new)
+    }
+
+    async fn subscribe_for_client(
+        &self,
+        request: Request<SubscribeForClientRequest>,
+    ) -> Result<Response<SubscribeForClientReply>, Status> {
+        let req = request.into_inner();
+        subscribe_for_client_by_req(
+            &self.cache_manager,
+            &self.client_pool,
+            &self.connection_manager,
+            &self.subscribe_manager,
+            &req,
+        )
+        .await
+        .map_err(|e| Status::internal(e.to_string()))
+        .map(Response::new)
+    }
+
+    async fn unsubscribe_for_client(
+        &self,
+        request: Request<UnsubscribeForClientRequest>,
+    ) -> Result<Response<UnsubscribeForClientReply>, Status> {
+        let req = request.into_inner();
+        unsubscribe_for_client_by_req(&self.client_pool, &self.subscribe_manager, &req)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))
+            .map(Response::new)
+    }
+
+    async fn drain_node(
+        &self,
+        request: Request<DrainNodeRequest>,
+    ) -> Result<Response<DrainNodeReply>, Status> {
+        let req = request.into_inner();
+        drain_node_by_req(&self.cache_manager, &req)
+            .map_err(|e| Status::internal(e.to_string()))
+            .map(Response::new)
+    }
+
+    async fn drain_node_status(
+        &self,
+        _: Request<DrainNodeStatusRequest>,
+    ) -> Result<Response<DrainNodeStatusReply>, Status> {
+        Ok(Response::new(drain_node_status_by_req(
+            &self.cache_manager,
+            &self.subscribe_manager,
+            &self.connector_manager,
+        )))
+    }
+
+    async fn rebalance_connections(
+        &self,
+        request: Request<RebalanceConnectionsRequest>,
+    ) -> Result<Response<RebalanceConnectionsReply>, Status> {
+        let req = request.into_inner();
+        rebalance_connections_by_req(&self.cache_manager, &req)
+            .map_err(|e| Status::internal(e.to_string()))
+            .map(Response::new)
+    }
+}
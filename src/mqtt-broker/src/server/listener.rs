@@ -0,0 +1,344 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::types::ResultMqttBrokerError;
+use crate::handler::cache::MQTTCacheManager;
+use crate::handler::error::MqttBrokerError;
+use crate::storage::listener::ListenerStorage;
+use broker_core::cache::BrokerCacheManager;
+use common_config::broker::broker_config;
+use common_config::config::MqttListener;
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use grpc_clients::pool::ClientPool;
+use metadata_struct::connection::NetworkConnectionType;
+use network_server::command::ArcCommandAdapter;
+use network_server::common::connection_manager::ConnectionManager;
+use network_server::context::{ProcessorConfig, ServerContext};
+use network_server::quic::server::QuicServer;
+use network_server::tcp::server::TcpServer;
+use network_server::websocket::server::{WebSocketServer, WebSocketServerState};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, OnceLock};
+use tokio::sync::broadcast;
+use tracing::{error, info, warn};
+
+#[derive(Clone)]
+enum ListenerServer {
+    Tcp(Arc<TcpServer>),
+    WebSocket(WebSocketServer),
+    Quic(Arc<QuicServer>),
+}
+
+#[derive(Clone)]
+struct RunningListener {
+    server: ListenerServer,
+    stop_sx: broadcast::Sender<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ListenerStatus {
+    pub listener: MqttListener,
+    pub running: bool,
+    pub connection_num: u64,
+}
+
+/// Owns every MQTT listener of this broker. Each listener gets its own server
+/// instance and stop channel, so it can be started and stopped independently
+/// at runtime without affecting the other listeners. Listeners added at runtime
+/// are stored in meta-service and loaded again when the broker restarts.
+pub struct ListenerManager {
+    cache_manager: Arc<MQTTCacheManager>,
+    connection_manager: Arc<ConnectionManager>,
+    client_pool: Arc<ClientPool>,
+    broker_cache: Arc<BrokerCacheManager>,
+    command: OnceLock<ArcCommandAdapter>,
+    stop_sx: OnceLock<broadcast::Sender<bool>>,
+    running: DashMap<String, RunningListener>,
+}
+
+impl ListenerManager {
+    pub fn new(
+        cache_manager: Arc<MQTTCacheManager>,
+        connection_manager: Arc<ConnectionManager>,
+        client_pool: Arc<ClientPool>,
+        broker_cache: Arc<BrokerCacheManager>,
+    ) -> Self {
+        for listener in broker_config().mqtt_server.get_listeners() {
+            cache_manager.add_listener(listener);
+        }
+        ListenerManager {
+            cache_manager,
+            connection_manager,
+            client_pool,
+            broker_cache,
+            command: OnceLock::new(),
+            stop_sx: OnceLock::new(),
+            running: DashMap::with_capacity(8),
+        }
+    }
+
+    pub fn init(&self, command: ArcCommandAdapter, stop_sx: broadcast::Sender<bool>) {
+        let _ = self.command.set(command);
+        let _ = self.stop_sx.set(stop_sx);
+    }
+
+    pub fn list_listeners(&self) -> Vec<ListenerStatus> {
+        let mut results: Vec<ListenerStatus> = self
+            .cache_manager
+            .listener_info
+            .iter()
+            .map(|raw| ListenerStatus {
+                listener: raw.value().clone(),
+                running: self.running.contains_key(raw.key()),
+                connection_num: self
+                    .connection_manager
+                    .get_listener_connection_num(raw.key()),
+            })
+            .collect();
+        results.sort_by(|a, b| a.listener.name.cmp(&b.listener.name));
+        results
+    }
+
+    pub fn is_running(&self, name: &str) -> bool {
+        self.running.contains_key(name)
+    }
+
+    pub async fn add_listener(&self, listener: MqttListener) -> ResultMqttBrokerError {
+        if self.cache_manager.get_listener(&listener.name).is_some() {
+            return Err(MqttBrokerError::ListenerAlreadyExist(listener.name));
+        }
+        ListenerStorage::new(self.client_pool.clone())
+            .save_listener(&listener)
+            .await?;
+        self.cache_manager.add_listener(listener);
+        Ok(())
+    }
+
+    pub async fn delete_listener(&self, name: &str) -> ResultMqttBrokerError {
+        if self.cache_manager.get_listener(name).is_none() {
+            return Err(MqttBrokerError::ListenerNotFound(name.to_string()));
+        }
+        if self.is_running(name) {
+            self.stop_listener(name).await?;
+        }
+        ListenerStorage::new(self.client_pool.clone())
+            .delete_listener(name)
+            .await?;
+        self.cache_manager.del_listener(name);
+        Ok(())
+    }
+
+    /// Loads the listeners added at runtime, a listener of the config file takes
+    /// precedence over a stored one with the same name.
+    pub async fn load_listeners(&self) -> ResultMqttBrokerError {
+        let listeners = ListenerStorage::new(self.client_pool.clone())
+            .list_listener()
+            .await?;
+        for listener in listeners {
+            if self.cache_manager.get_listener(&listener.name).is_some() {
+                warn!(
+                    "Listener [{}] is defined in the config file, the stored one is ignored",
+                    listener.name
+                );
+                continue;
+            }
+            self.cache_manager.add_listener(listener);
+        }
+        Ok(())
+    }
+
+    pub async fn start_all(&self) -> ResultMqttBrokerError {
+        let mut names: Vec<String> = self
+            .cache_manager
+            .listener_info
+            .iter()
+            .map(|raw| raw.key().clone())
+            .collect();
+        names.sort();
+        for name in names {
+            self.start_listener(&name).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn stop_all(&self) {
+        let names: Vec<String> = self.running.iter().map(|raw| raw.key().clone()).collect();
+        for name in names {
+            if let Err(e) = self.stop_listener(&name).await {
+                error!("Failed to stop listener {}, error message: {}", name, e);
+            }
+        }
+    }
+
+    pub async fn start_listener(&self, name: &str) -> ResultMqttBrokerError {
+        let listener = if let Some(listener) = self.cache_manager.get_listener(name) {
+            listener
+        } else {
+            return Err(MqttBrokerError::ListenerNotFound(name.to_string()));
+        };
+
+        if self.is_running(name) {
+            return Err(MqttBrokerError::ListenerAlreadyRunning(name.to_string()));
+        }
+
+        let (command, global_stop_sx) = match (self.command.get(), self.stop_sx.get()) {
+            (Some(command), Some(stop_sx)) => (command.clone(), stop_sx.clone()),
+            _ => {
+                return Err(MqttBrokerError::CommonError(
+                    "Listener manager has not been initialized".to_string(),
+                ))
+            }
+        };
+
+        let network_type = match listener.protocol.as_str() {
+            "tcp" => NetworkConnectionType::Tcp,
+            "tls" => NetworkConnectionType::Tls,
+            "websocket" => NetworkConnectionType::WebSocket,
+            "websockets" => NetworkConnectionType::WebSockets,
+            "quic" => NetworkConnectionType::QUIC,
+            protocol => {
+                return Err(MqttBrokerError::UnsupportedListenerProtocol(
+                    protocol.to_string(),
+                ))
+            }
+        };
+
+        // Every listener has its own stop channel so that it can be stopped alone.
+        // The broker level stop signal is relayed to it.
+        let (stop_sx, _) = broadcast::channel(2);
+        relay_stop_signal(global_stop_sx, stop_sx.clone());
+
+        let server = match self
+            .start_server(&listener, network_type, command, stop_sx.clone())
+            .await
+        {
+            Ok(server) => server,
+            Err(e) => {
+                // stops the relay and whatever the server had started before failing
+                let _ = stop_sx.send(true);
+                return Err(e);
+            }
+        };
+
+        let running = RunningListener { server, stop_sx };
+        match self.running.entry(name.to_string()) {
+            Entry::Occupied(_) => {
+                // started concurrently by another call
+                stop_server(name, &running).await;
+                return Err(MqttBrokerError::ListenerAlreadyRunning(name.to_string()));
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(running);
+            }
+        }
+        info!(
+            "Listener [{}] started, protocol: {}, bind addr: {}",
+            listener.name, listener.protocol, listener.bind_addr
+        );
+        Ok(())
+    }
+
+    async fn start_server(
+        &self,
+        listener: &MqttListener,
+        network_type: NetworkConnectionType,
+        command: ArcCommandAdapter,
+        stop_sx: broadcast::Sender<bool>,
+    ) -> Result<ListenerServer, MqttBrokerError> {
+        let conf = broker_config();
+        let context = ServerContext {
+            connection_manager: self.connection_manager.clone(),
+            client_pool: self.client_pool.clone(),
+            command: command.clone(),
+            network_type: network_type.clone(),
+            proc_config: ProcessorConfig {
+                accept_thread_num: conf.network.accept_thread_num,
+                handler_process_num: conf.network.handler_thread_num,
+                response_process_num: conf.network.response_thread_num,
+                channel_size: conf.network.queue_size,
+            },
+            stop_sx: stop_sx.clone(),
+            broker_cache: self.broker_cache.clone(),
+            listener: listener.clone(),
+        };
+
+        let server = match network_type {
+            NetworkConnectionType::Tcp | NetworkConnectionType::Tls => {
+                let server = Arc::new(TcpServer::new(context));
+                server.start().await?;
+                ListenerServer::Tcp(server)
+            }
+            NetworkConnectionType::WebSocket | NetworkConnectionType::WebSockets => {
+                let server = WebSocketServer::new(WebSocketServerState::new(
+                    listener.clone(),
+                    command,
+                    self.connection_manager.clone(),
+                    stop_sx,
+                ));
+                server.start().await?;
+                ListenerServer::WebSocket(server)
+            }
+            NetworkConnectionType::QUIC => {
+                let server = Arc::new(QuicServer::new(context));
+                server.start().await?;
+                ListenerServer::Quic(server)
+            }
+        };
+        Ok(server)
+    }
+
+    pub async fn stop_listener(&self, name: &str) -> ResultMqttBrokerError {
+        let running = if let Some((_, running)) = self.running.remove(name) {
+            running
+        } else {
+            return Err(MqttBrokerError::ListenerNotRunning(name.to_string()));
+        };
+
+        stop_server(name, &running).await;
+        self.connection_manager.close_listener_connect(name).await;
+        info!("Listener [{}] stopped", name);
+        Ok(())
+    }
+}
+
+async fn stop_server(name: &str, running: &RunningListener) {
+    match &running.server {
+        ListenerServer::Tcp(server) => server.stop().await,
+        ListenerServer::WebSocket(server) => server.stop(),
+        ListenerServer::Quic(server) => server.stop().await,
+    }
+
+    if let Err(e) = running.stop_sx.send(true) {
+        error!(
+            "Failed to send stop signal to listener {}, error message: {}",
+            name, e
+        );
+    }
+}
+
+fn relay_stop_signal(global_stop_sx: broadcast::Sender<bool>, stop_sx: broadcast::Sender<bool>) {
+    let mut global_stop_rx = global_stop_sx.subscribe();
+    let mut stop_rx = stop_sx.subscribe();
+    tokio::spawn(async move {
+        tokio::select! {
+            val = global_stop_rx.recv() => {
+                if let Ok(flag) = val {
+                    let _ = stop_sx.send(flag);
+                }
+            }
+            _ = stop_rx.recv() => {}
+        }
+    });
+}
@@ -0,0 +1,61 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::handler::cache::MQTTCacheManager;
+use crate::handler::connection::takeover_session_by_req;
+use crate::subscribe::manager::SubscribeManager;
+use network_server::common::connection_manager::ConnectionManager;
+use protocol::broker::broker_mqtt_session::mqtt_broker_session_service_server::MqttBrokerSessionService;
+use protocol::broker::broker_mqtt_session::{TakeoverSessionReply, TakeoverSessionRequest};
+use std::sync::Arc;
+use tonic::{Request, Response, Status};
+
+pub struct GrpcSessionServices {
+    cache_manager: Arc<MQTTCacheManager>,
+    subscribe_manager: Arc<SubscribeManager>,
+    connection_manager: Arc<ConnectionManager>,
+}
+
+impl GrpcSessionServices {
+    pub fn new(
+        cache_manager: Arc<MQTTCacheManager>,
+        subscribe_manager: Arc<SubscribeManager>,
+        connection_manager: Arc<ConnectionManager>,
+    ) -> Self {
+        GrpcSessionServices {
+            cache_manager,
+            subscribe_manager,
+            connection_manager,
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl MqttBrokerSessionService for GrpcSessionServices {
+    async fn takeover_session(
+        &self,
+        request: Request<TakeoverSessionRequest>,
+    ) -> Result<Response<TakeoverSessionReply>, Status> {
+        let req = request.into_inner();
+        takeover_session_by_req(
+            &self.cache_manager,
+            &self.connection_manager,
+            &self.subscribe_manager,
+            &req,
+        )
+        .await
+        .map_err(|e| Status::internal(e.to_string()))
+        .map(Response::new)
+    }
+}
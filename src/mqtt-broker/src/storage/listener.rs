@@ -0,0 +1,91 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use common_config::broker::broker_config;
+use common_config::config::MqttListener;
+use grpc_clients::meta::kv::call::{placement_delete, placement_get_prefix, placement_set};
+use grpc_clients::pool::ClientPool;
+use protocol::meta::meta_service_kv::{DeleteRequest, GetPrefixRequest, SetRequest};
+
+use crate::common::types::ResultMqttBrokerError;
+use crate::handler::error::MqttBrokerError;
+
+/// Listeners created through the admin API. They belong to one broker, so they
+/// are kept in meta-service under the broker id and loaded again on restart.
+pub struct ListenerStorage {
+    client_pool: Arc<ClientPool>,
+}
+
+impl ListenerStorage {
+    pub fn new(client_pool: Arc<ClientPool>) -> Self {
+        ListenerStorage { client_pool }
+    }
+
+    pub async fn list_listener(&self) -> Result<Vec<MqttListener>, MqttBrokerError> {
+        let config = broker_config();
+        let request = GetPrefixRequest {
+            prefix: listener_prefix_key(&config.cluster_name, config.broker_id),
+        };
+        let reply =
+            placement_get_prefix(&self.client_pool, &config.get_meta_service_addr(), request)
+                .await?;
+        let mut results = Vec::with_capacity(reply.values.len());
+        for value in reply.values {
+            results.push(serde_json::from_str::<MqttListener>(&value)?);
+        }
+        Ok(results)
+    }
+
+    pub async fn save_listener(&self, listener: &MqttListener) -> ResultMqttBrokerError {
+        let config = broker_config();
+        let request = SetRequest {
+            key: listener_key(&config.cluster_name, config.broker_id, &listener.name),
+            value: serde_json::to_string(listener)?,
+        };
+        placement_set(&self.client_pool, &config.get_meta_service_addr(), request).await?;
+        Ok(())
+    }
+
+    pub async fn delete_listener(&self, name: &str) -> ResultMqttBrokerError {
+        let config = broker_config();
+        let request = DeleteRequest {
+            key: listener_key(&config.cluster_name, config.broker_id, name),
+        };
+        placement_delete(&self.client_pool, &config.get_meta_service_addr(), request).await?;
+        Ok(())
+    }
+}
+
+fn listener_prefix_key(cluster_name: &str, broker_id: u64) -> String {
+    format!("/mqtt/listener/{cluster_name}/{broker_id}/")
+}
+
+fn listener_key(cluster_name: &str, broker_id: u64, name: &str) -> String {
+    format!("{}{}", listener_prefix_key(cluster_name, broker_id), name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn listener_key_test() {
+        assert_eq!(listener_prefix_key("c1", 1), "/mqtt/listener/c1/1/");
+        assert_eq!(listener_key("c1", 1, "ws"), "/mqtt/listener/c1/1/ws");
+        // the prefix of one broker must not match the listeners of another
+        assert!(!listener_key("c1", 10, "ws").starts_with(&listener_prefix_key("c1", 1)));
+    }
+}
@@ -15,16 +15,20 @@
 use crate::handler::error::MqttBrokerError;
 use common_base::error::common::CommonError;
 use common_config::broker::broker_config;
+use futures::stream::{BoxStream, StreamExt};
 use metadata_struct::adapter::read_config::ReadConfig;
 use metadata_struct::adapter::record::Record;
 use std::collections::HashMap;
 use std::str::FromStr;
+use std::time::Duration;
 use storage_adapter::memory::MemoryStorageAdapter;
 use storage_adapter::mysql::MySQLStorageAdapter;
+use storage_adapter::postgres::PostgresStorageAdapter;
 use storage_adapter::rocksdb::RocksDBStorageAdapter;
 use storage_adapter::storage::{ArcStorageAdapter, StorageAdapter};
 use storage_adapter::StorageType;
 use third_driver::mysql::build_mysql_conn_pool;
+use third_driver::postgresql::build_postgresql_conn_pool;
 
 pub fn cluster_name() -> String {
     let conf = broker_config();
@@ -61,14 +65,8 @@ pub async fn read_topic_message(
         offset: u64,
         record_num: u64,
     ) -> Result<Vec<Record>, CommonError> {
-        let shard_name = topic_id;
-        let namespace = cluster_name();
-        let mut read_config = ReadConfig::new();
-        read_config.max_record_num = record_num;
-
         let records = self
-            .storage_adapter
-            .read_by_offset(namespace, shard_name.to_owned(), offset, read_config)
+            .read_topic_records(topic_id, offset, record_num)
             .await?;
         for raw in records.iter() {
             if !raw.crc32_check() {
@@ -78,6 +76,58 @@ pub async fn read_topic_message(
         Ok(records)
     }
 
+    // Reads records without the CRC check, callers decide what to do with corrupted records
+    pub async fn read_topic_records(
+        &self,
+        topic_id: &str,
+        offset: u64,
+        record_num: u64,
+    ) -> Result<Vec<Record>, CommonError> {
+        let shard_name = topic_id;
+        let namespace = cluster_name();
+        let mut read_config = ReadConfig::new();
+        read_config.max_record_num = record_num;
+
+        self.storage_adapter
+            .read_by_offset(namespace, shard_name.to_owned(), offset, read_config)
+            .await
+    }
+
+    // Streams the records of a topic from the offset on and waits for new writes once it
+    // has caught up. Records failing the CRC check come out as errors and are skipped.
+    pub fn read_topic_stream(
+        &self,
+        topic_id: &str,
+        offset: u64,
+        record_num: u64,
+    ) -> BoxStream<'_, Result<Record, CommonError>> {
+        let mut read_config = ReadConfig::new();
+        read_config.max_record_num = record_num;
+
+        self.storage_adapter
+            .read_stream(cluster_name(), topic_id.to_owned(), offset, read_config)
+            .map(|record| {
+                let record = record?;
+                if !record.crc32_check() {
+                    return Err(CommonError::CrcCheckByMessage);
+                }
+                Ok(record)
+            })
+            .boxed()
+    }
+
+    // Returns once the topic may have records at or after the offset, or the wait time has passed
+    pub async fn wait_for_topic_write(
+        &self,
+        topic_id: &str,
+        offset: u64,
+        wait_time: Duration,
+    ) -> Result<(), CommonError> {
+        self.storage_adapter
+            .wait_for_write(cluster_name(), topic_id.to_owned(), offset, wait_time)
+            .await
+    }
+
     pub async fn get_group_offset(&self, group_id: &str) -> Result<u64, CommonError> {
         let offset_data = self
             .storage_adapter
@@ -122,6 +172,11 @@ pub fn build_message_storage_driver(
             Box::new(MySQLStorageAdapter::new(pool.clone())?)
         }
 
+        StorageType::Postgres => {
+            let pool = build_postgresql_conn_pool(&conf.mqtt_message_storage.postgres_addr)?;
+            Box::new(PostgresStorageAdapter::new(pool)?)
+        }
+
         StorageType::RocksDB => Box::new(RocksDBStorageAdapter::new(
             conf.mqtt_message_storage.rocksdb_data_path.as_str(),
             conf.mqtt_message_storage
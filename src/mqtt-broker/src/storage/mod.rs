@@ -17,8 +17,12 @@
 pub mod blacklist;
 pub mod connector;
 pub mod keys;
+pub mod listener;
 pub mod local;
 pub mod message;
+pub mod offline_queue;
+pub mod rule;
+pub mod scheduled_publish;
 pub mod schema;
 pub mod session;
 pub mod topic;
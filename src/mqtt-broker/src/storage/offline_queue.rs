@@ -0,0 +1,154 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::message::cluster_name;
+use crate::handler::error::MqttBrokerError;
+use common_base::error::common::CommonError;
+use metadata_struct::adapter::read_config::ReadConfig;
+use metadata_struct::adapter::record::Record;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use storage_adapter::storage::{ArcStorageAdapter, DeletionSupport};
+
+// A message waiting in the offline queue of a session.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OfflineQueueMessage {
+    // Key of the exclusive subscription the message was matched by
+    pub exclusive_key: String,
+    pub record: Record,
+}
+
+impl OfflineQueueMessage {
+    pub fn size(&self) -> u64 {
+        self.record.data.len() as u64
+    }
+
+    pub fn encode(&self) -> Result<Record, MqttBrokerError> {
+        Ok(Record::build_byte(serde_json::to_vec(self)?))
+    }
+
+    pub fn decode(record: &Record) -> Result<Self, MqttBrokerError> {
+        Ok(serde_json::from_slice(&record.data)?)
+    }
+}
+
+#[derive(Clone)]
+pub struct OfflineQueueStorage {
+    storage_adapter: ArcStorageAdapter,
+}
+
+impl OfflineQueueStorage {
+    pub fn new(storage_adapter: ArcStorageAdapter) -> Self {
+        OfflineQueueStorage { storage_adapter }
+    }
+
+    pub async fn append(
+        &self,
+        client_id: &str,
+        message: &OfflineQueueMessage,
+    ) -> Result<u64, MqttBrokerError> {
+        let offsets = self
+            .storage_adapter
+            .write(
+                cluster_name(),
+                offline_queue_shard_name(client_id),
+                message.encode()?,
+            )
+            .await?;
+        Ok(offsets)
+    }
+
+    pub async fn read(
+        &self,
+        client_id: &str,
+        offset: u64,
+        record_num: u64,
+    ) -> Result<Vec<(u64, OfflineQueueMessage)>, MqttBrokerError> {
+        let mut read_config = ReadConfig::new();
+        read_config.max_record_num = record_num;
+
+        let records = self
+            .storage_adapter
+            .read_by_offset(
+                cluster_name(),
+                offline_queue_shard_name(client_id),
+                offset,
+                read_config,
+            )
+            .await?;
+
+        let mut results = Vec::with_capacity(records.len());
+        for record in records.iter() {
+            let Some(offset) = record.offset else {
+                continue;
+            };
+            results.push((offset, OfflineQueueMessage::decode(record)?));
+        }
+        Ok(results)
+    }
+
+    // The queue is read from its committed cursor, so delivered and evicted
+    // messages never come back, whether or not the adapter removed them yet.
+    pub async fn read_cursor(&self, client_id: &str) -> Result<u64, MqttBrokerError> {
+        let shard_name = offline_queue_shard_name(client_id);
+        let offsets = self
+            .storage_adapter
+            .get_offset_by_group(offline_queue_group_name(client_id))
+            .await?;
+        Ok(offsets
+            .into_iter()
+            .find(|offset| offset.shard_name == shard_name)
+            .map(|offset| offset.offset)
+            .unwrap_or(0))
+    }
+
+    pub async fn commit_cursor(&self, client_id: &str, offset: u64) -> Result<(), MqttBrokerError> {
+        let mut offset_data = HashMap::new();
+        offset_data.insert(offline_queue_shard_name(client_id), offset);
+        self.storage_adapter
+            .commit_offset(
+                offline_queue_group_name(client_id),
+                cluster_name(),
+                offset_data,
+            )
+            .await?;
+        Ok(())
+    }
+
+    // Reclaims the space of the messages below the cursor. Adapters that drop
+    // whole segments keep some of them for a while, which is fine as they are
+    // never read again.
+    pub async fn trim(&self, client_id: &str, offset: u64) -> Result<(), MqttBrokerError> {
+        match self
+            .storage_adapter
+            .delete_before_offset(cluster_name(), offline_queue_shard_name(client_id), offset)
+            .await
+        {
+            Ok(()) | Err(CommonError::PartialDeletion(_, _)) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn support_trim(&self) -> bool {
+        self.storage_adapter.deletion_support() != DeletionSupport::None
+    }
+}
+
+pub fn offline_queue_shard_name(client_id: &str) -> String {
+    format!("offline_queue_{client_id}")
+}
+
+pub fn offline_queue_group_name(client_id: &str) -> String {
+    format!("offline_queue_{client_id}")
+}
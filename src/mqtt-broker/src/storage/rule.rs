@@ -0,0 +1,90 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use common_config::broker::broker_config;
+use grpc_clients::meta::kv::call::{placement_delete, placement_get_prefix, placement_set};
+use grpc_clients::pool::ClientPool;
+use metadata_struct::mqtt::rule::MqttRule;
+use protocol::meta::meta_service_kv::{DeleteRequest, GetPrefixRequest, SetRequest};
+
+use crate::common::types::ResultMqttBrokerError;
+use crate::handler::error::MqttBrokerError;
+
+/// SQL rules of the cluster. Every rule has its own key in meta-service, so
+/// creating or deleting one rule never rewrites the others.
+pub struct RuleStorage {
+    client_pool: Arc<ClientPool>,
+}
+
+impl RuleStorage {
+    pub fn new(client_pool: Arc<ClientPool>) -> Self {
+        RuleStorage { client_pool }
+    }
+
+    pub async fn list_rule(&self) -> Result<Vec<MqttRule>, MqttBrokerError> {
+        let config = broker_config();
+        let request = GetPrefixRequest {
+            prefix: rule_prefix_key(&config.cluster_name),
+        };
+        let reply =
+            placement_get_prefix(&self.client_pool, &config.get_meta_service_addr(), request)
+                .await?;
+        let mut results = Vec::with_capacity(reply.values.len());
+        for value in reply.values {
+            results.push(serde_json::from_str::<MqttRule>(&value)?);
+        }
+        Ok(results)
+    }
+
+    pub async fn save_rule(&self, rule: &MqttRule) -> ResultMqttBrokerError {
+        let config = broker_config();
+        let request = SetRequest {
+            key: rule_key(&config.cluster_name, &rule.rule_name),
+            value: serde_json::to_string(rule)?,
+        };
+        placement_set(&self.client_pool, &config.get_meta_service_addr(), request).await?;
+        Ok(())
+    }
+
+    pub async fn delete_rule(&self, rule_name: &str) -> ResultMqttBrokerError {
+        let config = broker_config();
+        let request = DeleteRequest {
+            key: rule_key(&config.cluster_name, rule_name),
+        };
+        placement_delete(&self.client_pool, &config.get_meta_service_addr(), request).await?;
+        Ok(())
+    }
+}
+
+fn rule_prefix_key(cluster_name: &str) -> String {
+    format!("/mqtt/rule/{cluster_name}/")
+}
+
+fn rule_key(cluster_name: &str, rule_name: &str) -> String {
+    format!("{}{}", rule_prefix_key(cluster_name), rule_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rule_key_test() {
+        assert_eq!(rule_prefix_key("c1"), "/mqtt/rule/c1/");
+        assert_eq!(rule_key("c1", "r1"), "/mqtt/rule/c1/r1");
+        assert!(!rule_key("c10", "r1").starts_with(&rule_prefix_key("c1")));
+    }
+}
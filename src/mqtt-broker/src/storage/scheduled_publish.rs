@@ -0,0 +1,344 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::message::cluster_name;
+use crate::handler::error::MqttBrokerError;
+use common_base::error::common::CommonError;
+use metadata_struct::adapter::read_config::ReadConfig;
+use metadata_struct::adapter::record::Record;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use storage_adapter::storage::{ArcStorageAdapter, ShardInfo};
+
+// Firings of every schedule, kept for auditing
+pub const SCHEDULED_PUBLISH_HISTORY_SHARD_NAME: &str = "$scheduled-publish-history";
+// Triggers handed back by the delay message engine when a firing is due
+pub const SCHEDULED_PUBLISH_TRIGGER_SHARD_NAME: &str = "$scheduled-publish-trigger";
+
+const HISTORY_READ_BATCH_NUM: u64 = 1000;
+
+// Asks for one firing of a schedule. The create time tells a schedule apart
+// from one that was deleted and created again under the same name.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ScheduledPublishTrigger {
+    pub name: String,
+    pub create_time: u64,
+    pub fire_time: u64,
+}
+
+impl ScheduledPublishTrigger {
+    pub fn encode(&self) -> Result<Record, MqttBrokerError> {
+        Ok(Record::build_byte(serde_json::to_vec(self)?))
+    }
+
+    pub fn decode(record: &Record) -> Result<Self, MqttBrokerError> {
+        Ok(serde_json::from_slice(&record.data)?)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ScheduledPublishFire {
+    pub name: String,
+    pub create_time: u64,
+    pub fire_time: u64,
+    pub publish_time: u64,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+impl ScheduledPublishFire {
+    pub fn encode(&self) -> Result<Record, MqttBrokerError> {
+        let mut record = Record::build_byte(serde_json::to_vec(self)?);
+        record.set_key(fire_key(&self.name, self.create_time, self.fire_time));
+        record.set_tags(vec![self.name.clone()]);
+        Ok(record)
+    }
+
+    pub fn decode(record: &Record) -> Result<Self, MqttBrokerError> {
+        Ok(serde_json::from_slice(&record.data)?)
+    }
+}
+
+#[derive(Clone)]
+pub struct ScheduledPublishStorage {
+    storage_adapter: ArcStorageAdapter,
+}
+
+impl ScheduledPublishStorage {
+    pub fn new(storage_adapter: ArcStorageAdapter) -> Self {
+        ScheduledPublishStorage { storage_adapter }
+    }
+
+    pub async fn init_shards(&self) -> Result<(), MqttBrokerError> {
+        for shard_name in [
+            SCHEDULED_PUBLISH_HISTORY_SHARD_NAME,
+            SCHEDULED_PUBLISH_TRIGGER_SHARD_NAME,
+        ] {
+            let list = self
+                .storage_adapter
+                .list_shard(cluster_name(), shard_name.to_owned())
+                .await?;
+            if list.is_empty() {
+                let shard = ShardInfo {
+                    namespace: cluster_name(),
+                    shard_name: shard_name.to_owned(),
+                    replica_num: 1,
+                };
+                self.storage_adapter.create_shard(shard).await?;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn save_fire(&self, fire: &ScheduledPublishFire) -> Result<u64, MqttBrokerError> {
+        let offset = self
+            .storage_adapter
+            .write(
+                cluster_name(),
+                SCHEDULED_PUBLISH_HISTORY_SHARD_NAME.to_owned(),
+                fire.encode()?,
+            )
+            .await?;
+        Ok(offset)
+    }
+
+    pub async fn get_fire(
+        &self,
+        name: &str,
+        create_time: u64,
+        fire_time: u64,
+    ) -> Result<Option<ScheduledPublishFire>, MqttBrokerError> {
+        let mut read_config = ReadConfig::new();
+        read_config.max_record_num = 1;
+
+        let records = self
+            .storage_adapter
+            .read_by_key(
+                cluster_name(),
+                SCHEDULED_PUBLISH_HISTORY_SHARD_NAME.to_owned(),
+                0,
+                fire_key(name, create_time, fire_time),
+                read_config,
+            )
+            .await?;
+
+        match records.first() {
+            Some(record) => Ok(Some(ScheduledPublishFire::decode(record)?)),
+            None => Ok(None),
+        }
+    }
+
+    // Every recorded firing of the schedule, oldest first
+    pub async fn list_fires(
+        &self,
+        name: &str,
+    ) -> Result<Vec<ScheduledPublishFire>, MqttBrokerError> {
+        let mut results = Vec::new();
+        let mut offset = 0;
+        loop {
+            let mut read_config = ReadConfig::new();
+            read_config.max_record_num = HISTORY_READ_BATCH_NUM;
+
+            let records = self
+                .storage_adapter
+                .read_by_tag(
+                    cluster_name(),
+                    SCHEDULED_PUBLISH_HISTORY_SHARD_NAME.to_owned(),
+                    offset,
+                    name.to_owned(),
+                    read_config,
+                )
+                .await?;
+
+            for record in records.iter() {
+                results.push(ScheduledPublishFire::decode(record)?);
+            }
+
+            match records.last().and_then(|record| record.offset) {
+                Some(last_offset) if records.len() as u64 == HISTORY_READ_BATCH_NUM => {
+                    offset = last_offset + 1;
+                }
+                _ => break,
+            }
+        }
+        Ok(results)
+    }
+
+    // Adapters that drop whole segments keep some of the older firings until
+    // their segment goes, which only makes the history a little longer.
+    pub async fn delete_fires_before(&self, timestamp: u64) -> Result<(), MqttBrokerError> {
+        match self
+            .storage_adapter
+            .delete_before_timestamp(
+                cluster_name(),
+                SCHEDULED_PUBLISH_HISTORY_SHARD_NAME.to_owned(),
+                timestamp,
+            )
+            .await
+        {
+            Ok(()) | Err(CommonError::PartialDeletion(_, _)) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub async fn read_triggers(
+        &self,
+        offset: u64,
+        record_num: u64,
+    ) -> Result<Vec<(u64, ScheduledPublishTrigger)>, MqttBrokerError> {
+        let mut read_config = ReadConfig::new();
+        read_config.max_record_num = record_num;
+
+        let records = self
+            .storage_adapter
+            .read_by_offset(
+                cluster_name(),
+                SCHEDULED_PUBLISH_TRIGGER_SHARD_NAME.to_owned(),
+                offset,
+                read_config,
+            )
+            .await?;
+
+        let mut results = Vec::with_capacity(records.len());
+        for record in records.iter() {
+            let Some(offset) = record.offset else {
+                continue;
+            };
+            results.push((offset, ScheduledPublishTrigger::decode(record)?));
+        }
+        Ok(results)
+    }
+
+    pub async fn get_trigger_offset(&self, group_name: &str) -> Result<u64, MqttBrokerError> {
+        let offsets = self
+            .storage_adapter
+            .get_offset_by_group(group_name.to_owned())
+            .await?;
+        Ok(offsets.first().map(|offset| offset.offset).unwrap_or(0))
+    }
+
+    // Commits the offset of the next trigger to read
+    pub async fn commit_trigger_offset(
+        &self,
+        group_name: &str,
+        offset: u64,
+    ) -> Result<(), MqttBrokerError> {
+        let mut offset_data = HashMap::new();
+        offset_data.insert(SCHEDULED_PUBLISH_TRIGGER_SHARD_NAME.to_owned(), offset);
+        self.storage_adapter
+            .commit_offset(group_name.to_owned(), cluster_name(), offset_data)
+            .await?;
+        Ok(())
+    }
+
+    // Every broker reads the trigger shard with its own group, so triggers
+    // are removed by age once all brokers are long past them rather than
+    // below the offset of one group.
+    pub async fn delete_triggers_before(&self, timestamp: u64) -> Result<(), MqttBrokerError> {
+        match self
+            .storage_adapter
+            .delete_before_timestamp(
+                cluster_name(),
+                SCHEDULED_PUBLISH_TRIGGER_SHARD_NAME.to_owned(),
+                timestamp,
+            )
+            .await
+        {
+            Ok(()) | Err(CommonError::PartialDeletion(_, _)) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+fn fire_key(name: &str, create_time: u64, fire_time: u64) -> String {
+    format!("{name}/{create_time}/{fire_time}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common_config::broker::{default_broker_config, init_broker_conf_by_config};
+    use storage_adapter::storage::build_memory_storage_driver;
+
+    fn build_fire(name: &str, fire_time: u64) -> ScheduledPublishFire {
+        ScheduledPublishFire {
+            name: name.to_string(),
+            create_time: 1,
+            fire_time,
+            publish_time: fire_time,
+            success: true,
+            error: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn fire_history_test() {
+        init_broker_conf_by_config(default_broker_config());
+        let storage = ScheduledPublishStorage::new(build_memory_storage_driver());
+
+        storage.save_fire(&build_fire("s1", 60)).await.unwrap();
+        storage.save_fire(&build_fire("s2", 60)).await.unwrap();
+        storage.save_fire(&build_fire("s1", 120)).await.unwrap();
+
+        let fire = storage.get_fire("s1", 1, 120).await.unwrap().unwrap();
+        assert_eq!(fire, build_fire("s1", 120));
+        assert!(storage.get_fire("s1", 2, 120).await.unwrap().is_none());
+        assert!(storage.get_fire("s2", 1, 120).await.unwrap().is_none());
+
+        let fires = storage.list_fires("s1").await.unwrap();
+        assert_eq!(fires, vec![build_fire("s1", 60), build_fire("s1", 120)]);
+    }
+
+    #[tokio::test]
+    async fn trigger_offset_test() {
+        init_broker_conf_by_config(default_broker_config());
+        let adapter = build_memory_storage_driver();
+        let storage = ScheduledPublishStorage::new(adapter.clone());
+        let group_name = "scheduled-publish-test";
+
+        for fire_time in [60, 120] {
+            let trigger = ScheduledPublishTrigger {
+                name: "s1".to_string(),
+                create_time: 1,
+                fire_time,
+            };
+            adapter
+                .write(
+                    cluster_name(),
+                    SCHEDULED_PUBLISH_TRIGGER_SHARD_NAME.to_owned(),
+                    trigger.encode().unwrap(),
+                )
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(storage.get_trigger_offset(group_name).await.unwrap(), 0);
+        let triggers = storage.read_triggers(0, 10).await.unwrap();
+        assert_eq!(triggers.len(), 2);
+        assert_eq!(triggers[1].1.fire_time, 120);
+
+        storage
+            .commit_trigger_offset(group_name, triggers[0].0 + 1)
+            .await
+            .unwrap();
+        let offset = storage.get_trigger_offset(group_name).await.unwrap();
+        assert_eq!(offset, triggers[1].0);
+        let triggers = storage.read_triggers(offset, 10).await.unwrap();
+        assert_eq!(triggers.len(), 1);
+        assert_eq!(triggers[0].1.fire_time, 120);
+
+        // Committing leaves the triggers for the groups of the other brokers
+        assert_eq!(storage.read_triggers(0, 10).await.unwrap().len(), 2);
+    }
+}
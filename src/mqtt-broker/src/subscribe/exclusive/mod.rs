@@ -15,13 +15,13 @@
 use super::common::loop_commit_offset;
 use super::common::Subscriber;
 use super::manager::SubscribeManager;
+use super::offline_queue::OfflineQueueManager;
 use super::push::{
     build_publish_message, send_publish_packet_to_client, BuildPublishMessageContext,
 };
 use crate::common::metrics_cache::MetricsCacheManager;
 use crate::common::types::ResultMqttBrokerError;
 use crate::handler::cache::MQTTCacheManager;
-use crate::handler::error::MqttBrokerError;
 use crate::handler::slow_subscribe::record_slow_subscribe_data;
 use crate::storage::message::MessageStorage;
 use crate::subscribe::common::is_ignore_push_error;
@@ -29,6 +29,7 @@
 use crate::subscribe::push::{build_pub_qos, build_sub_ids};
 use broker_core::rocksdb::RocksDBEngine;
 use common_base::tools::now_second;
+use futures::StreamExt;
 use metadata_struct::adapter::record::Record;
 use network_server::common::connection_manager::ConnectionManager;
 use protocol::mqtt::common::QoS;
@@ -42,6 +43,9 @@
 use tracing::error;
 use tracing::warn;
 
+// Most records read from storage in one go
+const PUSH_READ_RECORD_NUM: u64 = 5;
+
 pub struct ExclusivePush {
     cache_manager: Arc<MQTTCacheManager>,
     subscribe_manager: Arc<SubscribeManager>,
@@ -49,6 +53,7 @@ pub struct ExclusivePush {
     message_storage: ArcStorageAdapter,
     metrics_cache_manager: Arc<MetricsCacheManager>,
     rocksdb_engine_handler: Arc<RocksDBEngine>,
+    offline_queue_manager: Arc<OfflineQueueManager>,
 }
 
 impl ExclusivePush {
@@ -59,6 +64,7 @@ pub fn new(
         connection_manager: Arc<ConnectionManager>,
         metrics_cache_manager: Arc<MetricsCacheManager>,
         rocksdb_engine_handler: Arc<RocksDBEngine>,
+        offline_queue_manager: Arc<OfflineQueueManager>,
     ) -> Self {
         ExclusivePush {
             message_storage,
@@ -67,6 +73,7 @@ pub fn new(
             connection_manager,
             metrics_cache_manager,
             rocksdb_engine_handler,
+            offline_queue_manager,
         }
     }
 
@@ -120,6 +127,7 @@ async fn start_push_thread(&self) {
             let subscribe_manager = self.subscribe_manager.clone();
             let metrics_cache_manager = self.metrics_cache_manager.clone();
             let rocksdb_engine_handler = self.rocksdb_engine_handler.clone();
+            let offline_queue_manager = self.offline_queue_manager.clone();
 
             // Subscribe to the data push thread
             self.subscribe_manager.exclusive_push_thread.insert(
@@ -142,7 +150,7 @@ async fn start_push_thread(&self) {
                 let qos = build_pub_qos(&cache_manager, &subscriber);
                 let sub_ids = build_sub_ids(&subscriber);
 
-                let mut offset = match message_storage.get_group_offset(&group_id).await {
+                let offset = match message_storage.get_group_offset(&group_id).await {
                     Ok(offset) => offset,
                     Err(e) => {
                         error!("{}", e);
@@ -153,6 +161,12 @@ async fn start_push_thread(&self) {
                     }
                 };
 
+                // Records that are already stored come out right away, after that the
+                // stream waits for new writes to the topic
+                let mut records = message_storage
+                    .read_topic_stream(&subscriber.topic_id, offset, PUSH_READ_RECORD_NUM)
+                    .ready_chunks(PUSH_READ_RECORD_NUM as usize);
+
                 loop {
                     select! {
                         val = sub_thread_stop_rx.recv() =>{
@@ -170,31 +184,17 @@ async fn start_push_thread(&self) {
                                 }
                             }
                         },
-                        val = pub_message(
-                            ExclusivePushContext {
-                                subscribe_manager: subscribe_manager.clone(),
-                                connection_manager: connection_manager.clone(),
-                                message_storage: message_storage.clone(),
-                                cache_manager: cache_manager.clone(),
-                                metrics_cache_manager: metrics_cache_manager.clone(),
-                                subscriber: subscriber.clone(),
-                                group_id: group_id.clone(),
-                                rocksdb_engine_handler: rocksdb_engine_handler.clone(),
-                                qos,
-                                sub_ids: sub_ids.clone(),
-                                offset,
-                                exclusive_key: exclusive_key.clone(),
-                                sub_thread_stop_sx: sub_thread_stop_sx.clone(),
-                            }
-                            ) => {
-                                match val{
-                                    Ok(offset_op) => {
-                                        if let Some(off) = offset_op{
-                                            offset = off + 1;
-                                        } else {
-                                            sleep(Duration::from_millis(100)).await;
-                                        }
-                                    }
+                        val = records.next() => {
+                            let Some(items) = val else {
+                                subscribe_manager.exclusive_push_thread.remove(&exclusive_key);
+                                break;
+                            };
+
+                            let mut results = Vec::with_capacity(items.len());
+                            let mut read_failed = false;
+                            for item in items {
+                                match item {
+                                    Ok(record) => results.push(record),
                                     Err(e) => {
                                         error!(
                                             "Push message to client failed, failure message: {}, topic:{}, group:{}",
@@ -202,10 +202,35 @@ async fn start_push_thread(&self) {
                                             subscriber.topic_id.clone(),
                                             group_id.clone()
                                         );
-                                        sleep(Duration::from_millis(100)).await;
+                                        read_failed = true;
                                     }
                                 }
                             }
+
+                            pub_message(
+                                ExclusivePushContext {
+                                    subscribe_manager: subscribe_manager.clone(),
+                                    connection_manager: connection_manager.clone(),
+                                    message_storage: message_storage.clone(),
+                                    cache_manager: cache_manager.clone(),
+                                    metrics_cache_manager: metrics_cache_manager.clone(),
+                                    subscriber: subscriber.clone(),
+                                    group_id: group_id.clone(),
+                                    rocksdb_engine_handler: rocksdb_engine_handler.clone(),
+                                    offline_queue_manager: offline_queue_manager.clone(),
+                                    qos,
+                                    sub_ids: sub_ids.clone(),
+                                    exclusive_key: exclusive_key.clone(),
+                                    sub_thread_stop_sx: sub_thread_stop_sx.clone(),
+                                },
+                                results,
+                            )
+                            .await;
+
+                            if read_failed {
+                                sleep(Duration::from_millis(100)).await;
+                            }
+                        }
                     }
                 }
             });
@@ -221,22 +246,16 @@ pub struct ExclusivePushContext {
     pub cache_manager: Arc<MQTTCacheManager>,
     pub metrics_cache_manager: Arc<MetricsCacheManager>,
     pub rocksdb_engine_handler: Arc<RocksDBEngine>,
+    pub offline_queue_manager: Arc<OfflineQueueManager>,
     pub subscriber: Subscriber,
     pub group_id: String,
     pub qos: QoS,
     pub sub_ids: Vec<usize>,
-    pub offset: u64,
     pub exclusive_key: String,
     pub sub_thread_stop_sx: broadcast::Sender<bool>,
 }
 
-async fn pub_message(context: ExclusivePushContext) -> Result<Option<u64>, MqttBrokerError> {
-    let record_num = 5;
-    let results = context
-        .message_storage
-        .read_topic_message(&context.subscriber.topic_id, context.offset, record_num)
-        .await?;
-
+async fn pub_message(context: ExclusivePushContext, results: Vec<Record>) {
     let push_fn = async |record: &Record| -> ResultMqttBrokerError {
         let record_offset = if let Some(offset) = record.offset {
             offset
@@ -244,6 +263,32 @@ async fn pub_message(context: ExclusivePushContext) -> Result<Option<u64>, MqttB
             return Ok(());
         };
 
+        // The client is offline, or still has earlier messages waiting in its offline queue
+        if context
+            .offline_queue_manager
+            .should_enqueue(&context.subscriber.client_id)
+            .await?
+        {
+            context
+                .offline_queue_manager
+                .enqueue(
+                    &context.exclusive_key,
+                    &context.subscriber,
+                    context.qos,
+                    record,
+                )
+                .await?;
+
+            loop_commit_offset(
+                &context.message_storage,
+                &context.subscriber.topic_id,
+                &context.group_id,
+                record_offset,
+            )
+            .await?;
+            return Ok(());
+        }
+
         // build publish params
         let sub_pub_param = if let Some(params) =
             build_publish_message(BuildPublishMessageContext {
@@ -320,15 +365,9 @@ async fn pub_message(context: ExclusivePushContext) -> Result<Option<u64>, MqttB
         success_num as u64,
         error_num as u64,
     );
-
-    if results.is_empty() {
-        return Ok(None);
-    }
-
-    Ok(Some(results.last().unwrap().offset.unwrap()))
 }
 
-fn build_group_name(subscriber: &Subscriber) -> String {
+pub(crate) fn build_group_name(subscriber: &Subscriber) -> String {
     format!(
         "system_sub_{}_{}_{}",
         subscriber.client_id, subscriber.sub_path, subscriber.topic_id
@@ -18,7 +18,7 @@
 use metadata_struct::mqtt::subscribe_data::MqttSubscribe;
 use protocol::mqtt::common::{Filter, MqttProtocol};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use tokio::sync::broadcast::Sender;
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -78,6 +78,9 @@ pub struct SubscribeManager {
     //(client_id_path: MqttSubscribe)
     pub subscribe_list: DashMap<String, MqttSubscribe>,
 
+    //(client_id, paths), the subscriptions of one client without scanning subscribe_list
+    client_subscribe_paths: DashMap<String, HashSet<String>>,
+
     // (client_id_sub_name_topic_id, Subscriber)
     pub exclusive_push: DashMap<String, Subscriber>,
 
@@ -113,6 +116,7 @@ impl SubscribeManager {
     pub fn new() -> Self {
         SubscribeManager {
             subscribe_list: DashMap::with_capacity(8),
+            client_subscribe_paths: DashMap::with_capacity(8),
             exclusive_push: DashMap::with_capacity(8),
             share_leader_push: DashMap::with_capacity(8),
             share_follower_resub: DashMap::with_capacity(8),
@@ -127,6 +131,10 @@ pub fn new() -> Self {
     // subscribe info
     pub fn add_subscribe(&self, subscribe: MqttSubscribe) {
         let key = self.subscribe_key(&subscribe.client_id, &subscribe.path);
+        self.client_subscribe_paths
+            .entry(subscribe.client_id.clone())
+            .or_default()
+            .insert(subscribe.path.clone());
         self.subscribe_list.insert(key, subscribe);
     }
 
@@ -146,15 +154,32 @@ pub fn list_subscribe(&self) -> Vec<String> {
         list
     }
 
+    pub fn list_subscribe_by_client_id(&self, client_id: &str) -> Vec<MqttSubscribe> {
+        let paths: Vec<String> = match self.client_subscribe_paths.get(client_id) {
+            Some(paths) => paths.iter().cloned().collect(),
+            None => return Vec::new(),
+        };
+        paths
+            .iter()
+            .filter_map(|path| self.get_subscribe(client_id, path))
+            .collect()
+    }
+
     pub fn remove_subscribe(&self, client_id: &str, path: &str) {
         let key = self.subscribe_key(client_id, path);
         self.subscribe_list.remove(&key);
+        if let Some(mut paths) = self.client_subscribe_paths.get_mut(client_id) {
+            paths.remove(path);
+        }
+        self.client_subscribe_paths
+            .remove_if(client_id, |_, paths| paths.is_empty());
     }
 
     pub fn remove_subscriber_by_client_id(&self, client_id: &str) {
-        for (key, subscribe) in self.subscribe_list.clone() {
-            if subscribe.client_id == *client_id {
-                self.subscribe_list.remove(&key);
+        if let Some((_, paths)) = self.client_subscribe_paths.remove(client_id) {
+            for path in paths {
+                self.subscribe_list
+                    .remove(&self.subscribe_key(client_id, &path));
             }
         }
     }
@@ -357,10 +382,16 @@ pub fn remove_topic_subscribe_by_path(&self, topic_name: &str, client_id: &str,
     }
 
     pub fn remove_client_id(&self, client_id: &str) {
+        self.remove_push_by_client_id(client_id);
+        self.remove_subscriber_by_client_id(client_id);
+    }
+
+    // Stops pushing to the client from this broker but keeps its subscriptions, used when
+    // the session is taken over by another broker.
+    pub fn remove_push_by_client_id(&self, client_id: &str) {
         self.remove_exclusive_push_by_client_id(client_id);
         self.remove_share_subscribe_leader_by_client_id(client_id);
         self.remove_share_subscribe_follower_by_client_id(client_id);
-        self.remove_subscriber_by_client_id(client_id);
         self.remove_not_push_client(client_id);
     }
 
@@ -447,6 +478,7 @@ mod tests {
     use std::sync::Arc;
 
     use common_base::tools::{now_second, unique_id};
+    use metadata_struct::mqtt::subscribe_data::MqttSubscribe;
     use protocol::mqtt::common::{Filter, MqttProtocol, QoS, RetainHandling};
 
     use crate::subscribe::{
@@ -480,6 +512,43 @@ fn topic_subscribe_test() {
         assert!(!subscribe_manager.is_exclusive_subscribe(topic_name));
     }
 
+    #[test]
+    fn subscribe_by_client_id_test() {
+        let subscribe_manager = SubscribeManager::new();
+        let build = |client_id: &str, path: &str| MqttSubscribe {
+            client_id: client_id.to_string(),
+            path: path.to_string(),
+            cluster_name: "c1".to_string(),
+            broker_id: 1,
+            protocol: MqttProtocol::Mqtt5,
+            filter: Filter::default(),
+            pkid: 1,
+            subscribe_properties: None,
+            create_time: now_second(),
+        };
+        subscribe_manager.add_subscribe(build("c1", "/a"));
+        subscribe_manager.add_subscribe(build("c1", "/b"));
+        subscribe_manager.add_subscribe(build("c2", "/a"));
+
+        let mut paths: Vec<String> = subscribe_manager
+            .list_subscribe_by_client_id("c1")
+            .into_iter()
+            .map(|sub| sub.path)
+            .collect();
+        paths.sort();
+        assert_eq!(paths, vec!["/a", "/b"]);
+
+        subscribe_manager.remove_subscribe("c1", "/a");
+        assert_eq!(subscribe_manager.list_subscribe_by_client_id("c1").len(), 1);
+
+        subscribe_manager.remove_subscriber_by_client_id("c1");
+        assert!(subscribe_manager
+            .list_subscribe_by_client_id("c1")
+            .is_empty());
+        assert_eq!(subscribe_manager.list_subscribe_by_client_id("c2").len(), 1);
+        assert_eq!(subscribe_manager.subscribe_list.len(), 1);
+    }
+
     #[test]
     fn share_subscribe_leader_test() {
         let subscribe_manager = Arc::new(SubscribeManager::new());
@@ -528,4 +597,50 @@ fn share_subscribe_followe_test() {
         subscribe_manager.remove_share_subscribe_follower_by_client_id(&share_sub.client_id);
         assert_eq!(subscribe_manager.share_follower_resub.len(), 0);
     }
+
+    #[test]
+    fn remove_push_by_client_id_test() {
+        let subscribe_manager = Arc::new(SubscribeManager::new());
+        let client_id = "client_id_1";
+        subscribe_manager.add_subscribe(MqttSubscribe {
+            client_id: client_id.to_string(),
+            path: "/var/111".to_string(),
+            cluster_name: "c1".to_string(),
+            broker_id: 1,
+            protocol: MqttProtocol::Mqtt5,
+            filter: Filter::default(),
+            pkid: 1,
+            subscribe_properties: None,
+            create_time: now_second(),
+        });
+        let sub = Subscriber {
+            protocol: MqttProtocol::Mqtt5,
+            client_id: client_id.to_string(),
+            topic_name: "t_name_1".to_string(),
+            group_name: None,
+            topic_id: "t_id_1".to_string(),
+            qos: QoS::AtLeastOnce,
+            nolocal: false,
+            preserve_retain: false,
+            retain_forward_rule: RetainHandling::Never,
+            subscription_identifier: None,
+            sub_path: "/var/111".to_string(),
+            rewrite_sub_path: None,
+            create_time: now_second(),
+        };
+        subscribe_manager.add_exclusive_push(client_id, "/var/111", "t_id_1", sub);
+        subscribe_manager.add_topic_subscribe("t_name_1", client_id, "/var/111");
+
+        subscribe_manager.remove_push_by_client_id(client_id);
+        assert!(subscribe_manager.exclusive_push.is_empty());
+        assert!(!subscribe_manager.contain_topic_subscribe("t_name_1"));
+        assert!(subscribe_manager
+            .get_subscribe(client_id, "/var/111")
+            .is_some());
+
+        subscribe_manager.remove_client_id(client_id);
+        assert!(subscribe_manager
+            .get_subscribe(client_id, "/var/111")
+            .is_none());
+    }
 }
@@ -15,5 +15,6 @@
 pub mod common;
 pub mod exclusive;
 pub mod manager;
+pub mod offline_queue;
 pub mod push;
 pub mod share;
@@ -0,0 +1,573 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::common::Subscriber;
+use super::exclusive::build_group_name;
+use super::manager::SubscribeManager;
+use super::push::{
+    build_pub_qos, build_publish_message, build_sub_ids, send_publish_packet_to_client,
+    BuildPublishMessageContext,
+};
+use crate::common::types::ResultMqttBrokerError;
+use crate::handler::cache::MQTTCacheManager;
+use crate::handler::client_admin::clear_session;
+use crate::handler::error::MqttBrokerError;
+use crate::storage::offline_queue::{OfflineQueueMessage, OfflineQueueStorage};
+use common_base::error::ResultCommonError;
+use common_base::tools::loop_select;
+use common_config::config::{MqttOfflineMessage, OfflineMessageOverflowPolicy};
+use common_metrics::mqtt::session::{
+    record_offline_queue_depth, record_offline_queue_dropped, remove_offline_queue_depth,
+};
+use dashmap::DashMap;
+use grpc_clients::pool::ClientPool;
+use metadata_struct::adapter::record::Record;
+use network_server::common::connection_manager::ConnectionManager;
+use protocol::mqtt::common::QoS;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use storage_adapter::storage::ArcStorageAdapter;
+use tokio::sync::{broadcast, Mutex};
+use tracing::{info, warn};
+
+// Number of queued messages read from storage at a time.
+const OFFLINE_QUEUE_READ_BATCH: u64 = 100;
+
+// Reason code sent when a client is disconnected because its queue overflowed (Quota exceeded).
+const OFFLINE_QUEUE_OVERFLOW_REASON_CODE: u8 = 0x97;
+
+#[derive(Default, Debug)]
+pub struct OfflineQueue {
+    // (offset, bytes) of the queued messages, in delivery order
+    entries: VecDeque<(u64, u64)>,
+    bytes: u64,
+}
+
+impl OfflineQueue {
+    pub fn len(&self) -> u64 {
+        self.entries.len() as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    fn push(&mut self, offset: u64, bytes: u64) {
+        self.entries.push_back((offset, bytes));
+        self.bytes += bytes;
+    }
+
+    fn pop_front(&mut self) -> Option<u64> {
+        let (offset, bytes) = self.entries.pop_front()?;
+        self.bytes -= bytes;
+        Some(offset)
+    }
+
+    // Offset after the last queued message
+    fn end_offset(&self) -> Option<u64> {
+        self.entries.back().map(|(offset, _)| offset + 1)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum EnqueueAction {
+    // Append the message after evicting the given number of messages from the head
+    Append(usize),
+    DropIncoming,
+    Disconnect,
+}
+
+// Decides how a message of `bytes` bytes enters the queue under the configured bounds.
+pub fn enqueue_action(
+    queue: &OfflineQueue,
+    config: &MqttOfflineMessage,
+    bytes: u64,
+) -> EnqueueAction {
+    let max_num = config.max_messages_num as u64;
+    let max_bytes = config.max_messages_bytes;
+    let fits = |num: u64, total: u64| {
+        (max_num == 0 || num < max_num) && (max_bytes == 0 || total + bytes <= max_bytes)
+    };
+
+    if fits(queue.len(), queue.bytes()) {
+        return EnqueueAction::Append(0);
+    }
+
+    match config.overflow_policy {
+        OfflineMessageOverflowPolicy::DropNewest => EnqueueAction::DropIncoming,
+        OfflineMessageOverflowPolicy::Disconnect => EnqueueAction::Disconnect,
+        OfflineMessageOverflowPolicy::DropOldest => {
+            // A message larger than the whole queue never fits
+            if max_bytes > 0 && bytes > max_bytes {
+                return EnqueueAction::DropIncoming;
+            }
+
+            let mut num = queue.len();
+            let mut total = queue.bytes();
+            let mut evict = 0;
+            for (_, size) in queue.entries.iter() {
+                if fits(num, total) {
+                    break;
+                }
+                num -= 1;
+                total -= size;
+                evict += 1;
+            }
+            EnqueueAction::Append(evict)
+        }
+    }
+}
+
+fn overflow_policy_name(policy: OfflineMessageOverflowPolicy) -> &'static str {
+    match policy {
+        OfflineMessageOverflowPolicy::DropOldest => "drop_oldest",
+        OfflineMessageOverflowPolicy::DropNewest => "drop_newest",
+        OfflineMessageOverflowPolicy::Disconnect => "disconnect",
+    }
+}
+
+// Keeps the messages of persistent sessions whose client is offline, and delivers
+// them in order once the client is back.
+pub struct OfflineQueueManager {
+    storage: OfflineQueueStorage,
+    cache_manager: Arc<MQTTCacheManager>,
+    connection_manager: Arc<ConnectionManager>,
+    subscribe_manager: Arc<SubscribeManager>,
+    client_pool: Arc<ClientPool>,
+    // (client_id, OfflineQueue)
+    queues: DashMap<String, Arc<Mutex<OfflineQueue>>>,
+    // Queues are only kept when the storage can reclaim delivered messages
+    support_trim: bool,
+}
+
+impl OfflineQueueManager {
+    pub fn new(
+        message_storage_adapter: ArcStorageAdapter,
+        cache_manager: Arc<MQTTCacheManager>,
+        connection_manager: Arc<ConnectionManager>,
+        subscribe_manager: Arc<SubscribeManager>,
+        client_pool: Arc<ClientPool>,
+    ) -> Self {
+        let storage = OfflineQueueStorage::new(message_storage_adapter);
+        OfflineQueueManager {
+            support_trim: storage.support_trim(),
+            storage,
+            cache_manager,
+            connection_manager,
+            subscribe_manager,
+            client_pool,
+            queues: DashMap::with_capacity(8),
+        }
+    }
+
+    fn config(&self) -> MqttOfflineMessage {
+        self.cache_manager
+            .broker_cache
+            .get_cluster_config()
+            .mqtt_offline_message
+    }
+
+    // Messages go to the queue while the client of a persistent session is offline,
+    // and until everything queued before has been delivered.
+    pub async fn should_enqueue(&self, client_id: &str) -> Result<bool, MqttBrokerError> {
+        if !self.config().enable
+            || !self.support_trim
+            || self.cache_manager.get_session_info(client_id).is_none()
+        {
+            return Ok(false);
+        }
+
+        let queue = self.load_queue(client_id).await?;
+        if self.cache_manager.get_connect_id(client_id).is_none() {
+            return Ok(true);
+        }
+        let is_empty = queue.lock().await.is_empty();
+        Ok(!is_empty)
+    }
+
+    pub async fn enqueue(
+        &self,
+        exclusive_key: &str,
+        subscriber: &Subscriber,
+        qos: QoS,
+        record: &Record,
+    ) -> ResultMqttBrokerError {
+        let config = self.config();
+        if config.exclude_qos0 && qos == QoS::AtMostOnce {
+            return Ok(());
+        }
+
+        let client_id = &subscriber.client_id;
+        let message = OfflineQueueMessage {
+            exclusive_key: exclusive_key.to_string(),
+            record: record.clone(),
+        };
+
+        let queue = self.load_queue(client_id).await?;
+        let mut queue = queue.lock().await;
+        let policy = overflow_policy_name(config.overflow_policy);
+        match enqueue_action(&queue, &config, message.size()) {
+            EnqueueAction::Append(evict) => {
+                if evict > 0 {
+                    let last = (0..evict).filter_map(|_| queue.pop_front()).last();
+                    if let Some(offset) = last {
+                        self.advance_cursor(client_id, offset + 1).await?;
+                    }
+                    record_offline_queue_dropped(policy, evict as u64);
+                }
+                let offset = self.storage.append(client_id, &message).await?;
+                queue.push(offset, message.size());
+            }
+            EnqueueAction::DropIncoming => {
+                record_offline_queue_dropped(policy, 1);
+            }
+            EnqueueAction::Disconnect => {
+                record_offline_queue_dropped(policy, queue.len() + 1);
+                drop(queue);
+                return self.disconnect(client_id).await;
+            }
+        }
+
+        record_offline_queue_depth(client_id, queue.len(), queue.bytes());
+        Ok(())
+    }
+
+    async fn load_queue(
+        &self,
+        client_id: &str,
+    ) -> Result<Arc<Mutex<OfflineQueue>>, MqttBrokerError> {
+        if let Some(queue) = self.queues.get(client_id) {
+            return Ok(queue.clone());
+        }
+
+        // Rebuild the queue from what a previous run left undelivered
+        let mut queue = OfflineQueue::default();
+        let mut offset = self.storage.read_cursor(client_id).await?;
+        loop {
+            let messages = self
+                .storage
+                .read(client_id, offset, OFFLINE_QUEUE_READ_BATCH)
+                .await?;
+            let Some((last_offset, _)) = messages.last() else {
+                break;
+            };
+            offset = last_offset + 1;
+            for (offset, message) in messages.iter() {
+                queue.push(*offset, message.size());
+            }
+        }
+
+        if !queue.is_empty() {
+            record_offline_queue_depth(client_id, queue.len(), queue.bytes());
+        }
+
+        Ok(self
+            .queues
+            .entry(client_id.to_string())
+            .or_insert_with(|| Arc::new(Mutex::new(queue)))
+            .clone())
+    }
+
+    async fn remove_queue(&self, client_id: &str) -> ResultMqttBrokerError {
+        if let Some((_, queue)) = self.queues.remove(client_id) {
+            let end_offset = queue.lock().await.end_offset();
+            if let Some(offset) = end_offset {
+                self.advance_cursor(client_id, offset).await?;
+            }
+        }
+        remove_offline_queue_depth(client_id);
+        Ok(())
+    }
+
+    // Moves the read cursor to `offset`, everything below it is done with
+    async fn advance_cursor(&self, client_id: &str, offset: u64) -> ResultMqttBrokerError {
+        self.storage.commit_cursor(client_id, offset).await?;
+        self.storage.trim(client_id, offset).await
+    }
+
+    async fn disconnect(&self, client_id: &str) -> ResultMqttBrokerError {
+        self.remove_queue(client_id).await?;
+        clear_session(
+            &self.cache_manager,
+            &self.client_pool,
+            &self.connection_manager,
+            &self.subscribe_manager,
+            client_id,
+            OFFLINE_QUEUE_OVERFLOW_REASON_CODE,
+            "Offline message queue overflow",
+        )
+        .await?;
+        info!(
+            "Session of client {} was discarded because its offline message queue overflowed",
+            client_id
+        );
+        Ok(())
+    }
+
+    async fn drain(
+        &self,
+        client_id: &str,
+        queue: &mut OfflineQueue,
+        stop_sx: &broadcast::Sender<bool>,
+    ) -> ResultMqttBrokerError {
+        while let Some(&(head, _)) = queue.entries.front() {
+            let messages = self
+                .storage
+                .read(client_id, head, OFFLINE_QUEUE_READ_BATCH)
+                .await?;
+            if messages.is_empty() {
+                // Nothing left in storage, the queue is out of date
+                *queue = OfflineQueue::default();
+                break;
+            }
+
+            let mut next_offset = head;
+            for (offset, message) in messages {
+                self.deliver(client_id, &message, stop_sx).await?;
+                next_offset = offset + 1;
+                self.storage.commit_cursor(client_id, next_offset).await?;
+                while queue
+                    .entries
+                    .front()
+                    .is_some_and(|(head, _)| *head <= offset)
+                {
+                    queue.pop_front();
+                }
+                record_offline_queue_depth(client_id, queue.len(), queue.bytes());
+            }
+            self.storage.trim(client_id, next_offset).await?;
+        }
+        Ok(())
+    }
+
+    async fn deliver(
+        &self,
+        client_id: &str,
+        message: &OfflineQueueMessage,
+        stop_sx: &broadcast::Sender<bool>,
+    ) -> ResultMqttBrokerError {
+        // The subscription was removed after the message was queued
+        let Some(subscriber) = self
+            .subscribe_manager
+            .exclusive_push
+            .get(&message.exclusive_key)
+            .map(|raw| raw.clone())
+        else {
+            return Ok(());
+        };
+
+        let qos = build_pub_qos(&self.cache_manager, &subscriber);
+        let Some(sub_pub_param) = build_publish_message(BuildPublishMessageContext {
+            cache_manager: self.cache_manager.clone(),
+            connection_manager: self.connection_manager.clone(),
+            client_id: client_id.to_string(),
+            record: message.record.clone(),
+            group_id: build_group_name(&subscriber),
+            qos,
+            subscriber: subscriber.clone(),
+            sub_ids: build_sub_ids(&subscriber),
+        })
+        .await?
+        else {
+            return Ok(());
+        };
+
+        send_publish_packet_to_client(
+            &self.connection_manager,
+            &self.cache_manager,
+            &sub_pub_param,
+            &qos,
+            stop_sx,
+        )
+        .await
+    }
+}
+
+async fn drain_offline_queues(
+    manager: &Arc<OfflineQueueManager>,
+    stop_sx: &broadcast::Sender<bool>,
+) -> ResultMqttBrokerError {
+    for (client_id, queue) in manager.queues.clone() {
+        if manager.cache_manager.get_session_info(&client_id).is_none() {
+            manager.remove_queue(&client_id).await?;
+            continue;
+        }
+
+        if manager.cache_manager.get_connect_id(&client_id).is_none() {
+            continue;
+        }
+
+        // A drain task already holds the queue
+        let Ok(mut queue) = queue.try_lock_owned() else {
+            continue;
+        };
+        if queue.is_empty() {
+            continue;
+        }
+
+        let manager = manager.clone();
+        let stop_sx = stop_sx.clone();
+        tokio::spawn(async move {
+            if let Err(e) = manager.drain(&client_id, &mut queue, &stop_sx).await {
+                warn!(
+                    "Failed to deliver the offline messages of client {}, error message: {}",
+                    client_id, e
+                );
+            }
+        });
+    }
+    Ok(())
+}
+
+pub async fn start_offline_queue_thread(
+    manager: Arc<OfflineQueueManager>,
+    stop_send: broadcast::Sender<bool>,
+) {
+    if manager.config().enable && !manager.support_trim {
+        warn!("Offline message queues are enabled, but the message storage cannot delete delivered messages, so no messages will be queued");
+    }
+
+    let ac_fn = async || -> ResultCommonError {
+        if let Err(e) = drain_offline_queues(&manager, &stop_send).await {
+            warn!(
+                "Failed to drain offline message queues, error message: {}",
+                e
+            );
+        }
+        Ok(())
+    };
+
+    loop_select(ac_fn, 1, &stop_send).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_config(
+        max_messages_num: u32,
+        max_messages_bytes: u64,
+        overflow_policy: OfflineMessageOverflowPolicy,
+    ) -> MqttOfflineMessage {
+        MqttOfflineMessage {
+            enable: true,
+            max_messages_num,
+            max_messages_bytes,
+            overflow_policy,
+            ..Default::default()
+        }
+    }
+
+    fn build_queue(sizes: &[u64]) -> OfflineQueue {
+        let mut queue = OfflineQueue::default();
+        for (offset, size) in sizes.iter().enumerate() {
+            queue.push(offset as u64, *size);
+        }
+        queue
+    }
+
+    #[test]
+    fn enqueue_action_unlimited_test() {
+        let queue = build_queue(&[10; 100]);
+        let config = build_config(0, 0, OfflineMessageOverflowPolicy::Disconnect);
+        assert_eq!(
+            enqueue_action(&queue, &config, 1000),
+            EnqueueAction::Append(0)
+        );
+    }
+
+    #[test]
+    fn enqueue_action_by_num_test() {
+        let queue = build_queue(&[10, 10, 10]);
+
+        let config = build_config(3, 0, OfflineMessageOverflowPolicy::DropOldest);
+        assert_eq!(
+            enqueue_action(&queue, &config, 10),
+            EnqueueAction::Append(1)
+        );
+
+        let config = build_config(4, 0, OfflineMessageOverflowPolicy::DropOldest);
+        assert_eq!(
+            enqueue_action(&queue, &config, 10),
+            EnqueueAction::Append(0)
+        );
+
+        let config = build_config(3, 0, OfflineMessageOverflowPolicy::DropNewest);
+        assert_eq!(
+            enqueue_action(&queue, &config, 10),
+            EnqueueAction::DropIncoming
+        );
+
+        let config = build_config(3, 0, OfflineMessageOverflowPolicy::Disconnect);
+        assert_eq!(
+            enqueue_action(&queue, &config, 10),
+            EnqueueAction::Disconnect
+        );
+    }
+
+    #[test]
+    fn enqueue_action_by_bytes_test() {
+        let queue = build_queue(&[10, 20, 30]);
+
+        let config = build_config(0, 70, OfflineMessageOverflowPolicy::DropOldest);
+        assert_eq!(
+            enqueue_action(&queue, &config, 10),
+            EnqueueAction::Append(0)
+        );
+        assert_eq!(
+            enqueue_action(&queue, &config, 15),
+            EnqueueAction::Append(1)
+        );
+        assert_eq!(
+            enqueue_action(&queue, &config, 45),
+            EnqueueAction::Append(2)
+        );
+        assert_eq!(
+            enqueue_action(&queue, &config, 70),
+            EnqueueAction::Append(3)
+        );
+        assert_eq!(
+            enqueue_action(&queue, &config, 71),
+            EnqueueAction::DropIncoming
+        );
+
+        let config = build_config(0, 70, OfflineMessageOverflowPolicy::DropNewest);
+        assert_eq!(
+            enqueue_action(&queue, &config, 15),
+            EnqueueAction::DropIncoming
+        );
+    }
+
+    #[test]
+    fn offline_queue_test() {
+        let mut queue = build_queue(&[10, 20]);
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.bytes(), 30);
+
+        assert_eq!(queue.end_offset(), Some(2));
+        assert_eq!(queue.pop_front(), Some(0));
+        assert_eq!(queue.bytes(), 20);
+        assert_eq!(queue.end_offset(), Some(2));
+
+        assert_eq!(queue.pop_front(), Some(1));
+        assert_eq!(queue.pop_front(), None);
+        assert_eq!(queue.end_offset(), None);
+        assert!(queue.is_empty());
+        assert_eq!(queue.bytes(), 0);
+    }
+}
@@ -14,17 +14,20 @@
 
 use super::common::min_qos;
 use super::common::Subscriber;
+use crate::common::packet_trace::TraceDirection;
 use crate::common::types::ResultMqttBrokerError;
 use crate::handler::cache::{
     MQTTCacheManager, QosAckPackageData, QosAckPackageType, QosAckPacketInfo,
 };
 use crate::handler::error::MqttBrokerError;
-use crate::handler::message::is_message_expire;
+use crate::handler::message::{is_message_expire, message_remaining_expiry_interval};
+use crate::handler::mountpoint::unmount_topic;
 use crate::handler::sub_option::{get_retain_flag_by_retain_as_published, is_send_msg_by_bo_local};
 use crate::subscribe::common::{is_ignore_push_error, SubPublishParam};
 use axum::extract::ws::Message;
 use bytes::{Bytes, BytesMut};
 use common_base::network::broker_not_available;
+use common_base::telemetry::trace::TraceSpan;
 use common_base::tools::now_mills;
 use common_metrics::mqtt::packets::record_sent_metrics;
 use common_metrics::mqtt::publish::record_mqtt_message_bytes_sent;
@@ -69,6 +72,10 @@ pub async fn build_publish_message(
 
     if is_message_expire(&msg) {
         debug!("Message dropping: message expires, is not pushed to the client, and is discarded");
+        context
+            .cache_manager
+            .detail_metrics
+            .record_dropped(&context.client_id, &context.subscriber.topic_name);
         return Ok(None);
     }
 
@@ -92,7 +99,9 @@ pub async fn build_publish_message(
         ));
     };
 
+    let mut mountpoint = None;
     if let Some(conn) = context.cache_manager.get_connection(connect_id) {
+        mountpoint = conn.mountpoint.clone();
         if msg.payload.len() > (conn.max_packet_size as usize) {
             debug!(
                 "{:?}",
@@ -102,6 +111,10 @@ pub async fn build_publish_message(
                     conn.max_packet_size
                 )
             );
+            context
+                .cache_manager
+                .detail_metrics
+                .record_dropped(&context.client_id, &context.subscriber.topic_name);
             return Ok(None);
         }
     }
@@ -127,14 +140,14 @@ pub async fn build_publish_message(
         qos: context.qos,
         p_kid: pkid,
         retain,
-        topic: Bytes::from(context.subscriber.topic_name.clone()),
+        topic: Bytes::from(unmount_topic(&mountpoint, &context.subscriber.topic_name)),
         payload: msg.payload,
     };
 
     let properties = if contain_properties {
         Some(PublishProperties {
             payload_format_indicator: msg.format_indicator,
-            message_expiry_interval: Some(msg.expiry_interval as u32),
+            message_expiry_interval: message_remaining_expiry_interval(&msg),
             topic_alias: None,
             response_topic: msg.response_topic,
             correlation_data: msg.correlation_data,
@@ -163,6 +176,77 @@ pub async fn send_publish_packet_to_client(
     sub_pub_param: &SubPublishParam,
     qos: &QoS,
     stop_sx: &Sender<bool>,
+) -> ResultMqttBrokerError {
+    // Only MQTT 5 packets carry user properties, so only they continue the trace
+    let span = match &sub_pub_param.packet {
+        MqttPacket::Publish(_, Some(properties)) => {
+            TraceSpan::from_user_properties("mqtt.subscriber.push", &properties.user_properties)
+        }
+        _ => TraceSpan::default(),
+    };
+
+    let detail_metrics = &cache_manager.detail_metrics;
+    let client_id = &sub_pub_param.subscribe.client_id;
+    let topic_name = &sub_pub_param.subscribe.topic_name;
+    let start = now_mills();
+    if *qos != QoS::AtMostOnce {
+        detail_metrics.record_inflight(client_id, topic_name, 1);
+    }
+
+    let result = if span.is_recording() {
+        span.set_attribute("mqtt.client_id", client_id.clone());
+        span.set_attribute("mqtt.topic", topic_name.clone());
+        let mut traced_param = sub_pub_param.clone();
+        if let MqttPacket::Publish(_, Some(properties)) = &mut traced_param.packet {
+            span.inject_user_properties(&mut properties.user_properties);
+        }
+        push_publish_packet_by_qos(
+            connection_manager,
+            cache_manager,
+            &traced_param,
+            qos,
+            stop_sx,
+        )
+        .await
+    } else {
+        push_publish_packet_by_qos(
+            connection_manager,
+            cache_manager,
+            sub_pub_param,
+            qos,
+            stop_sx,
+        )
+        .await
+    };
+
+    if *qos != QoS::AtMostOnce {
+        detail_metrics.record_inflight(client_id, topic_name, -1);
+    }
+    match &result {
+        Ok(()) => {
+            if let MqttPacket::Publish(publish, _) = &sub_pub_param.packet {
+                detail_metrics.record_message_out(
+                    client_id,
+                    topic_name,
+                    publish.payload.len() as u64,
+                    (now_mills() - start) as u64,
+                );
+            }
+        }
+        Err(e) => {
+            span.set_error(e.to_string());
+            detail_metrics.record_dropped(client_id, topic_name);
+        }
+    }
+    result
+}
+
+async fn push_publish_packet_by_qos(
+    connection_manager: &Arc<ConnectionManager>,
+    cache_manager: &Arc<MQTTCacheManager>,
+    sub_pub_param: &SubPublishParam,
+    qos: &QoS,
+    stop_sx: &Sender<bool>,
 ) -> ResultMqttBrokerError {
     match qos {
         QoS::AtMostOnce => {
@@ -268,7 +352,14 @@ pub async fn push_packet_to_client(
         let packet = RobustMQPacket::MQTT(sub_pub_param.packet.clone());
         let resp = ResponsePackage::new(connect_id, packet, 0, 0, 0, "Subsceibe".to_string());
 
-        send_message_to_client(resp, connection_manager).await
+        send_message_to_client(resp, connection_manager).await?;
+        cache_manager.packet_trace.record(
+            &client_id,
+            connect_id,
+            TraceDirection::Outbound,
+            &sub_pub_param.packet,
+        );
+        Ok(())
     };
 
     retry_tool_fn_timeout(action_fn, stop_sx, "push_packet_to_client").await
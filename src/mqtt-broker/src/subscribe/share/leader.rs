@@ -14,7 +14,6 @@
 
 use crate::common::types::ResultMqttBrokerError;
 use crate::handler::cache::MQTTCacheManager;
-use crate::handler::error::MqttBrokerError;
 use crate::handler::slow_subscribe::record_slow_subscribe_data;
 use crate::storage::message::MessageStorage;
 use crate::subscribe::common::is_ignore_push_error;
@@ -29,6 +28,7 @@
 use broker_core::rocksdb::RocksDBEngine;
 use common_base::network::broker_not_available;
 use common_base::tools::now_second;
+use futures::StreamExt;
 use metadata_struct::adapter::record::Record;
 use network_server::common::connection_manager::ConnectionManager;
 use std::sync::Arc;
@@ -41,6 +41,9 @@
 use tracing::warn;
 use tracing::{error, info};
 
+// Most records read from storage in one go
+const SHARE_READ_RECORD_NUM: u64 = 100;
+
 #[derive(Clone)]
 pub struct ShareLeaderPush {
     pub subscribe_manager: Arc<SubscribeManager>,
@@ -148,7 +151,7 @@ async fn push_by_round_robin(
 
         // get current offset by group
         let message_storage = MessageStorage::new(self.message_storage.clone());
-        let mut offset = message_storage.get_group_offset(&group_id).await?;
+        let offset = message_storage.get_group_offset(&group_id).await?;
 
         // save push thread
         self.subscribe_manager.share_leader_push_thread.insert(
@@ -175,6 +178,9 @@ async fn push_by_round_robin(
             );
 
             let mut seq = 1;
+            let mut records = message_storage
+                .read_topic_stream(&sub_data.topic_id, offset, SHARE_READ_RECORD_NUM)
+                .ready_chunks(SHARE_READ_RECORD_NUM as usize);
             loop {
                 select! {
                     val = sub_thread_stop_rx.recv() =>{
@@ -188,43 +194,46 @@ async fn push_by_round_robin(
                             }
                         }
                     }
-                    res = read_message_process(
-                        ShareLeaderPushContext {
-                            connection_manager: connection_manager.clone(),
-                            cache_manager: cache_manager.clone(),
-                            message_storage: message_storage.clone(),
-                            subscribe_manager: subscribe_manager.clone(),
-                            share_leader_key: share_leader_key.clone(),
-                            rocksdb_engine_handler: rocksdb_engine_handler.clone(),
-                            sub_data: sub_data.clone(),
-                            group_id: group_id.clone(),
-                            offset,
-                            seq,
-                            stop_sx: sub_thread_stop_sx.clone(),
+                    val = records.next() =>{
+                        let Some(items) = val else {
+                            break;
+                        };
+
+                        let mut results = Vec::with_capacity(items.len());
+                        let mut read_error = None;
+                        for item in items {
+                            match item {
+                                Ok(record) => results.push(record),
+                                Err(e) => read_error = Some(e),
+                            }
                         }
-                    ) =>{
-                        match res {
-                            Ok((data,seq_num)) => {
-                                if let Some(offset_cur) = data{
-                                    offset = offset_cur + 1;
-                                    seq = seq_num;
-                                }else{
-                                    sleep(Duration::from_millis(100)).await;
-                                }
-                            },
 
-                            Err(e) => {
-                                if !is_ignore_push_error(&e){
-                                    error!(
-                                        "Failed to read message from storage, failure message: {},topic:{},group{}",
-                                        e.to_string(),
-                                        &sub_data.topic_id,
-                                        group_id
-                                    );
-                                    sleep(Duration::from_millis(100)).await;
-                                }
-                                break;
-                            }
+                        seq = read_message_process(
+                            ShareLeaderPushContext {
+                                connection_manager: connection_manager.clone(),
+                                cache_manager: cache_manager.clone(),
+                                message_storage: message_storage.clone(),
+                                subscribe_manager: subscribe_manager.clone(),
+                                share_leader_key: share_leader_key.clone(),
+                                rocksdb_engine_handler: rocksdb_engine_handler.clone(),
+                                sub_data: sub_data.clone(),
+                                group_id: group_id.clone(),
+                                seq,
+                                stop_sx: sub_thread_stop_sx.clone(),
+                            },
+                            results,
+                        )
+                        .await;
+
+                        if let Some(e) = read_error {
+                            error!(
+                                "Failed to read message from storage, failure message: {},topic:{},group{}",
+                                e.to_string(),
+                                &sub_data.topic_id,
+                                group_id
+                            );
+                            sleep(Duration::from_millis(100)).await;
+                            break;
                         }
                     }
                 }
@@ -248,19 +257,11 @@ pub struct ShareLeaderPushContext {
     pub share_leader_key: String,
     pub sub_data: ShareLeaderSubscribeData,
     pub group_id: String,
-    pub offset: u64,
     pub seq: u64,
     pub stop_sx: Sender<bool>,
 }
 
-async fn read_message_process(
-    mut context: ShareLeaderPushContext,
-) -> Result<(Option<u64>, u64), MqttBrokerError> {
-    let results = context
-        .message_storage
-        .read_topic_message(&context.sub_data.topic_id, context.offset, 100)
-        .await?;
-
+async fn read_message_process(mut context: ShareLeaderPushContext, results: Vec<Record>) -> u64 {
     let mut push_fn = async |record: &Record| -> ResultMqttBrokerError {
         let record_offset = if let Some(offset) = record.offset {
             offset
@@ -399,11 +400,7 @@ async fn read_message_process(
             error_num as u64,
         );
 
-    if results.is_empty() {
-        return Ok((None, context.seq));
-    }
-
-    Ok((results.last().unwrap().offset, context.seq))
+    context.seq
 }
 
 fn get_subscribe_by_random(
@@ -14,5 +14,16 @@
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     robustmq_proto_build::setup()?;
+
+    // Services that are private to the broker and live in this repository.
+    tonic_build::configure().compile_protos(
+        &[
+            "proto/broker/mqtt_admin.proto",
+            "proto/broker/mqtt_session.proto",
+            "proto/journal/segment_admin.proto",
+            "proto/meta/consumer_group.proto",
+        ],
+        &["proto"],
+    )?;
     Ok(())
 }
@@ -17,3 +17,11 @@
 pub mod broker_mqtt_inner {
     tonic::include_proto!("broker.mqtt.inner");
 }
+
+pub mod broker_mqtt_admin {
+    tonic::include_proto!("broker.mqtt.admin");
+}
+
+pub mod broker_mqtt_session {
+    tonic::include_proto!("broker.mqtt.session");
+}
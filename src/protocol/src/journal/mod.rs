@@ -33,7 +33,12 @@ pub mod journal_record {
     tonic::include_proto!("journal.record");
 }
 
+pub mod journal_segment_admin {
+    tonic::include_proto!("journal.segment.admin");
+}
+
 pub mod codec;
+pub mod producer_batch;
 
 /// Error during serialization and deserialization
 #[derive(Debug, thiserror::Error)]
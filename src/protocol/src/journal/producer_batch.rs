@@ -0,0 +1,303 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Wire format of the batches written by the journal producer.
+//!
+//! A producer batch travels as a single `WriteReqMessages` whose tags contain
+//! [`PRODUCER_BATCH_TAG`] and whose value is an encoded [`ProducerBatch`]. The
+//! journal server expands it back into one record per entry, so readers never
+//! see the envelope.
+
+use common_base::error::common::CommonError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::Read;
+use std::str::FromStr;
+
+pub const PRODUCER_BATCH_TAG: &str = "$producer_batch";
+
+const PRODUCER_BATCH_MAGIC: u32 = 0x524d_5142;
+const PRODUCER_BATCH_VERSION: u8 = 1;
+const ZSTD_COMPRESSION_LEVEL: i32 = 3;
+
+// Largest size a compressed payload may expand to. LZ4 and Snappy carry the
+// size in the payload, it is checked before anything is allocated.
+pub const MAX_DECOMPRESSED_SIZE: usize = 64 * 1024 * 1024;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompressionType {
+    #[default]
+    None,
+    Lz4,
+    Zstd,
+    Snappy,
+}
+
+impl fmt::Display for CompressionType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            CompressionType::None => "none",
+            CompressionType::Lz4 => "lz4",
+            CompressionType::Zstd => "zstd",
+            CompressionType::Snappy => "snappy",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for CompressionType {
+    type Err = CommonError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(CompressionType::None),
+            "lz4" => Ok(CompressionType::Lz4),
+            "zstd" => Ok(CompressionType::Zstd),
+            "snappy" => Ok(CompressionType::Snappy),
+            _ => Err(CommonError::CommonError(format!(
+                "Unsupported compression type {s}"
+            ))),
+        }
+    }
+}
+
+pub fn compress(compression: CompressionType, data: &[u8]) -> Result<Vec<u8>, CommonError> {
+    match compression {
+        CompressionType::None => Ok(data.to_vec()),
+        CompressionType::Lz4 => Ok(lz4::block::compress(data, None, true)?),
+        CompressionType::Zstd => Ok(zstd::bulk::compress(data, ZSTD_COMPRESSION_LEVEL)?),
+        CompressionType::Snappy => snap::raw::Encoder::new()
+            .compress_vec(data)
+            .map_err(|e| CommonError::CommonError(e.to_string())),
+    }
+}
+
+pub fn decompress(compression: CompressionType, data: &[u8]) -> Result<Vec<u8>, CommonError> {
+    match compression {
+        CompressionType::None => Ok(data.to_vec()),
+        CompressionType::Lz4 => {
+            // lz4 writes the size as a little endian i32 in front of the block
+            let Some(prefix) = data.get(..4) else {
+                return Err(CommonError::CommonError(
+                    "Invalid lz4 payload, missing the size prefix".to_string(),
+                ));
+            };
+            let size = i32::from_le_bytes([prefix[0], prefix[1], prefix[2], prefix[3]]);
+            check_decompressed_size(usize::try_from(size).unwrap_or(usize::MAX))?;
+            Ok(lz4::block::decompress(data, None)?)
+        }
+        CompressionType::Zstd => {
+            let mut results = Vec::new();
+            zstd::stream::read::Decoder::new(data)?
+                .take(MAX_DECOMPRESSED_SIZE as u64 + 1)
+                .read_to_end(&mut results)?;
+            check_decompressed_size(results.len())?;
+            Ok(results)
+        }
+        CompressionType::Snappy => {
+            let size = snap::raw::decompress_len(data)
+                .map_err(|e| CommonError::CommonError(e.to_string()))?;
+            check_decompressed_size(size)?;
+            snap::raw::Decoder::new()
+                .decompress_vec(data)
+                .map_err(|e| CommonError::CommonError(e.to_string()))
+        }
+    }
+}
+
+fn check_decompressed_size(size: usize) -> Result<(), CommonError> {
+    if size > MAX_DECOMPRESSED_SIZE {
+        return Err(CommonError::CommonError(format!(
+            "Decompressed size {size} exceeds the limit of {MAX_DECOMPRESSED_SIZE} bytes"
+        )));
+    }
+    Ok(())
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProducerBatchRecord {
+    pub key: String,
+    pub value: Vec<u8>,
+    pub tags: Vec<String>,
+}
+
+impl ProducerBatchRecord {
+    pub fn size(&self) -> usize {
+        self.key.len() + self.value.len() + self.tags.iter().map(|tag| tag.len()).sum::<usize>()
+    }
+}
+
+/// A batch of records from one producer to one shard.
+///
+/// `producer_id` and `base_sequence` drive the server side dedupe: the first
+/// record of the batch has sequence `base_sequence`, the following records are
+/// numbered consecutively. An empty `producer_id` disables dedupe.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProducerBatch {
+    pub producer_id: String,
+    pub base_sequence: u64,
+    pub compression: CompressionType,
+    pub records: Vec<ProducerBatchRecord>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ProducerBatchEnvelope {
+    magic: u32,
+    version: u8,
+    producer_id: String,
+    base_sequence: u64,
+    record_count: u32,
+    compression: CompressionType,
+    payload: Vec<u8>,
+}
+
+impl ProducerBatch {
+    pub fn is_idempotent(&self) -> bool {
+        !self.producer_id.is_empty()
+    }
+
+    pub fn last_sequence(&self) -> u64 {
+        self.base_sequence + (self.records.len() as u64).saturating_sub(1)
+    }
+
+    pub fn encode(&self) -> Result<Vec<u8>, CommonError> {
+        let raw = bincode::serialize(&self.records)?;
+        let envelope = ProducerBatchEnvelope {
+            magic: PRODUCER_BATCH_MAGIC,
+            version: PRODUCER_BATCH_VERSION,
+            producer_id: self.producer_id.clone(),
+            base_sequence: self.base_sequence,
+            record_count: self.records.len() as u32,
+            compression: self.compression,
+            payload: compress(self.compression, &raw)?,
+        };
+        Ok(bincode::serialize(&envelope)?)
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, CommonError> {
+        let envelope: ProducerBatchEnvelope = bincode::deserialize(data)?;
+        if envelope.magic != PRODUCER_BATCH_MAGIC {
+            return Err(CommonError::CommonError(
+                "Invalid producer batch, magic mismatch".to_string(),
+            ));
+        }
+        if envelope.version != PRODUCER_BATCH_VERSION {
+            return Err(CommonError::CommonError(format!(
+                "Unsupported producer batch version {}",
+                envelope.version
+            )));
+        }
+
+        let raw = decompress(envelope.compression, &envelope.payload)?;
+        let records: Vec<ProducerBatchRecord> = bincode::deserialize(&raw)?;
+        if records.len() != envelope.record_count as usize {
+            return Err(CommonError::CommonError(format!(
+                "Invalid producer batch, expected {} records, got {}",
+                envelope.record_count,
+                records.len()
+            )));
+        }
+
+        Ok(ProducerBatch {
+            producer_id: envelope.producer_id,
+            base_sequence: envelope.base_sequence,
+            compression: envelope.compression,
+            records,
+        })
+    }
+}
+
+pub fn is_producer_batch(tags: &[String]) -> bool {
+    tags.iter().any(|tag| tag == PRODUCER_BATCH_TAG)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_batch(compression: CompressionType) -> ProducerBatch {
+        let records = (0..100)
+            .map(|i| ProducerBatchRecord {
+                key: format!("key-{i}"),
+                value: format!("value-value-value-{i}").into_bytes(),
+                tags: vec!["t1".to_string()],
+            })
+            .collect();
+        ProducerBatch {
+            producer_id: "p1".to_string(),
+            base_sequence: 10,
+            compression,
+            records,
+        }
+    }
+
+    #[test]
+    fn encode_decode_test() {
+        for compression in [
+            CompressionType::None,
+            CompressionType::Lz4,
+            CompressionType::Zstd,
+            CompressionType::Snappy,
+        ] {
+            let batch = build_batch(compression);
+            let data = batch.encode().unwrap();
+            let decoded = ProducerBatch::decode(&data).unwrap();
+            assert_eq!(decoded, batch);
+            assert_eq!(decoded.last_sequence(), 109);
+        }
+    }
+
+    #[test]
+    fn compression_test() {
+        let data = vec![7u8; 4096];
+        for compression in [
+            CompressionType::Lz4,
+            CompressionType::Zstd,
+            CompressionType::Snappy,
+        ] {
+            let compressed = compress(compression, &data).unwrap();
+            assert!(compressed.len() < data.len());
+            assert_eq!(decompress(compression, &compressed).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn decompress_limit_test() {
+        // A declared size above the limit is refused before allocating
+        let mut lz4 = compress(CompressionType::Lz4, b"data").unwrap();
+        lz4[..4].copy_from_slice(&(MAX_DECOMPRESSED_SIZE as i32 + 1).to_le_bytes());
+        assert!(decompress(CompressionType::Lz4, &lz4).is_err());
+
+        lz4[..4].copy_from_slice(&(-1i32).to_le_bytes());
+        assert!(decompress(CompressionType::Lz4, &lz4).is_err());
+        assert!(decompress(CompressionType::Lz4, &[1, 2]).is_err());
+
+        let data = vec![0u8; MAX_DECOMPRESSED_SIZE + 1];
+        for compression in [CompressionType::Zstd, CompressionType::Snappy] {
+            let compressed = compress(compression, &data).unwrap();
+            assert!(decompress(compression, &compressed).is_err());
+        }
+    }
+
+    #[test]
+    fn compression_type_test() {
+        assert_eq!(
+            CompressionType::from_str("ZSTD").unwrap(),
+            CompressionType::Zstd
+        );
+        assert!(CompressionType::from_str("gzip").is_err());
+        assert!(is_producer_batch(&[PRODUCER_BATCH_TAG.to_string()]));
+        assert!(!is_producer_batch(&["a".to_string()]));
+    }
+}
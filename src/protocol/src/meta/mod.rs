@@ -23,6 +23,10 @@ pub mod meta_service_inner {
     tonic::include_proto!("meta.service.inner");
 }
 
+pub mod meta_service_consumer_group {
+    tonic::include_proto!("meta.service.consumer_group");
+}
+
 pub mod meta_service_kv {
     tonic::include_proto!("meta.service.kv");
 }
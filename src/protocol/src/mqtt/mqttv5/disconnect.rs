@@ -212,7 +212,7 @@ pub fn read(bytes: &mut Bytes) -> Result<Option<DisconnectProperties>, MQTTProto
     }
 }
 
-fn code(reason: DisconnectReasonCode) -> u8 {
+pub fn code(reason: DisconnectReasonCode) -> u8 {
     match reason {
         DisconnectReasonCode::NormalDisconnection => 0x00,
         DisconnectReasonCode::DisconnectWithWillMessage => 0x04,
@@ -246,7 +246,7 @@ fn code(reason: DisconnectReasonCode) -> u8 {
     }
 }
 
-fn reason(code: u8) -> Result<DisconnectReasonCode, MQTTProtocolError> {
+pub fn reason(code: u8) -> Result<DisconnectReasonCode, MQTTProtocolError> {
     let v = match code {
         0x00 => DisconnectReasonCode::NormalDisconnection,
         0x04 => DisconnectReasonCode::DisconnectWithWillMessage,
@@ -0,0 +1,328 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::error::RuleEngineError;
+use crate::eval::{evaluate, is_true};
+use crate::payload::decode_payload;
+use crate::sql::{parse_rule_sql, RuleSql, SelectFields};
+use dashmap::DashMap;
+use metadata_struct::mqtt::rule::{MqttRule, MqttRuleAction};
+use serde_json::{Map, Value};
+use tracing::warn;
+
+// The message attributes a rule can select and filter on.
+pub struct RuleMessage<'a> {
+    pub client_id: &'a str,
+    pub username: &'a str,
+    pub peerhost: &'a str,
+    pub topic: &'a str,
+    pub qos: u8,
+    pub retain: bool,
+    pub payload: &'a [u8],
+    pub timestamp: u64,
+}
+
+impl RuleMessage<'_> {
+    pub fn columns(&self) -> Value {
+        let mut columns = Map::new();
+        columns.insert("clientid".to_string(), Value::from(self.client_id));
+        columns.insert("username".to_string(), Value::from(self.username));
+        columns.insert("peerhost".to_string(), Value::from(self.peerhost));
+        columns.insert("topic".to_string(), Value::from(self.topic));
+        columns.insert("qos".to_string(), Value::from(self.qos));
+        columns.insert("retain".to_string(), Value::from(self.retain));
+        columns.insert("payload".to_string(), decode_payload(self.payload));
+        columns.insert("timestamp".to_string(), Value::from(self.timestamp));
+        Value::Object(columns)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RuleOutput {
+    pub rule_name: String,
+    pub output: Value,
+    pub actions: Vec<MqttRuleAction>,
+}
+
+struct CompiledRule {
+    rule: MqttRule,
+    sql: RuleSql,
+}
+
+#[derive(Default)]
+pub struct RuleEngine {
+    // (RuleName, CompiledRule)
+    rules: DashMap<String, CompiledRule>,
+}
+
+impl RuleEngine {
+    pub fn new() -> Self {
+        RuleEngine {
+            rules: DashMap::with_capacity(2),
+        }
+    }
+
+    pub fn add_rule(&self, rule: MqttRule) -> Result<(), RuleEngineError> {
+        if self.rules.contains_key(&rule.rule_name) {
+            return Err(RuleEngineError::RuleAlreadyExist(rule.rule_name));
+        }
+        let sql = parse_rule_sql(&rule.sql)?;
+        self.rules
+            .insert(rule.rule_name.clone(), CompiledRule { rule, sql });
+        Ok(())
+    }
+
+    // Adds the rule or replaces the one with the same name
+    pub fn set_rule(&self, rule: MqttRule) -> Result<(), RuleEngineError> {
+        let sql = parse_rule_sql(&rule.sql)?;
+        self.rules
+            .insert(rule.rule_name.clone(), CompiledRule { rule, sql });
+        Ok(())
+    }
+
+    pub fn remove_rule(&self, rule_name: &str) {
+        self.rules.remove(rule_name);
+    }
+
+    pub fn get_rule(&self, rule_name: &str) -> Option<MqttRule> {
+        self.rules
+            .get(rule_name)
+            .map(|compiled| compiled.rule.clone())
+    }
+
+    pub fn list_rules(&self) -> Vec<MqttRule> {
+        self.rules
+            .iter()
+            .map(|compiled| compiled.rule.clone())
+            .collect()
+    }
+
+    // Replaces all rules. Rules whose SQL no longer parses are skipped and logged.
+    pub fn set_rules(&self, rules: Vec<MqttRule>) {
+        self.rules.clear();
+        for rule in rules {
+            let rule_name = rule.rule_name.clone();
+            if let Err(e) = self.add_rule(rule) {
+                warn!("Failed to load rule {}, error message: {}", rule_name, e);
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    // Runs every enabled rule whose FROM clause matches the topic and returns the
+    // outputs of those whose WHERE clause holds.
+    pub fn evaluate(&self, message: &RuleMessage) -> Vec<RuleOutput> {
+        let mut results = Vec::new();
+        let mut columns = None;
+
+        for compiled in self.rules.iter() {
+            if !compiled.rule.enable
+                || !compiled
+                    .sql
+                    .topic_filters
+                    .iter()
+                    .any(|filter| topic_filter_match(filter, message.topic))
+            {
+                continue;
+            }
+
+            // Payload decoding is deferred until at least one rule matches the topic
+            let columns = columns.get_or_insert_with(|| message.columns());
+            match run_rule(&compiled.sql, columns) {
+                Ok(Some(output)) => results.push(RuleOutput {
+                    rule_name: compiled.rule.rule_name.clone(),
+                    output,
+                    actions: compiled.rule.actions.clone(),
+                }),
+                Ok(None) => {}
+                Err(e) => {
+                    warn!(
+                        "Rule {} failed to process message on topic {}, error message: {}",
+                        compiled.rule.rule_name, message.topic, e
+                    );
+                }
+            }
+        }
+        results
+    }
+}
+
+fn run_rule(sql: &RuleSql, columns: &Value) -> Result<Option<Value>, RuleEngineError> {
+    if let Some(condition) = &sql.condition {
+        if !is_true(&evaluate(condition, columns)?) {
+            return Ok(None);
+        }
+    }
+
+    let output = match &sql.fields {
+        SelectFields::All => columns.clone(),
+        SelectFields::List(fields) => {
+            let mut output = Map::new();
+            for field in fields {
+                output.insert(field.name.clone(), evaluate(&field.expr, columns)?);
+            }
+            Value::Object(output)
+        }
+    };
+    Ok(Some(output))
+}
+
+// MQTT topic filter matching with + and # wildcards.
+pub fn topic_filter_match(filter: &str, topic: &str) -> bool {
+    // Wildcards at the first level never match system topics
+    if topic.starts_with('$') && (filter.starts_with('+') || filter.starts_with('#')) {
+        return false;
+    }
+
+    let mut topic_levels = topic.split('/');
+    for filter_level in filter.split('/') {
+        if filter_level == "#" {
+            return true;
+        }
+        match topic_levels.next() {
+            Some(level) if filter_level == "+" || filter_level == level => {}
+            _ => return false,
+        }
+    }
+    topic_levels.next().is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rule(name: &str, sql: &str) -> MqttRule {
+        MqttRule {
+            rule_name: name.to_string(),
+            sql: sql.to_string(),
+            actions: vec![MqttRuleAction::Drop],
+            enable: true,
+            description: "".to_string(),
+            create_time: 0,
+        }
+    }
+
+    fn message<'a>(topic: &'a str, payload: &'a [u8]) -> RuleMessage<'a> {
+        RuleMessage {
+            client_id: "c1",
+            username: "u1",
+            peerhost: "127.0.0.1:1883",
+            topic,
+            qos: 1,
+            retain: false,
+            payload,
+            timestamp: 100,
+        }
+    }
+
+    #[test]
+    fn topic_filter_match_test() {
+        assert!(topic_filter_match("a/b", "a/b"));
+        assert!(topic_filter_match("a/+", "a/b"));
+        assert!(topic_filter_match("a/#", "a"));
+        assert!(topic_filter_match("a/#", "a/b/c"));
+        assert!(topic_filter_match("+/+/c", "a/b/c"));
+        assert!(topic_filter_match("#", "a/b"));
+        assert!(!topic_filter_match("a/+", "a/b/c"));
+        assert!(!topic_filter_match("a/b/c", "a/b"));
+        assert!(!topic_filter_match("#", "$SYS/broker"));
+        assert!(topic_filter_match("$SYS/#", "$SYS/broker"));
+    }
+
+    #[test]
+    fn rule_management_test() {
+        let engine = RuleEngine::new();
+        assert!(engine.is_empty());
+
+        engine.add_rule(rule("r1", "SELECT * FROM 't/#'")).unwrap();
+        assert!(matches!(
+            engine.add_rule(rule("r1", "SELECT * FROM 't/#'")),
+            Err(RuleEngineError::RuleAlreadyExist(_))
+        ));
+        assert!(engine.add_rule(rule("r2", "SELECT FROM")).is_err());
+        assert_eq!(engine.list_rules().len(), 1);
+        assert!(engine.get_rule("r1").is_some());
+
+        engine.set_rules(vec![rule("r3", "SELECT * FROM 'x'"), rule("r4", "broken")]);
+        assert!(engine.get_rule("r1").is_none());
+        assert!(engine.get_rule("r3").is_some());
+        assert!(engine.get_rule("r4").is_none());
+
+        engine.set_rule(rule("r3", "SELECT qos FROM 'x'")).unwrap();
+        assert_eq!(engine.get_rule("r3").unwrap().sql, "SELECT qos FROM 'x'");
+        assert!(engine.set_rule(rule("r3", "broken")).is_err());
+        assert_eq!(engine.list_rules().len(), 1);
+
+        engine.remove_rule("r3");
+        assert!(engine.is_empty());
+    }
+
+    #[test]
+    fn evaluate_test() {
+        let engine = RuleEngine::new();
+        engine
+            .add_rule(rule(
+                "hot",
+                "SELECT payload.temp AS temp, clientid, upper(payload.room) AS room \
+                 FROM 'sensors/+' WHERE payload.temp > 80",
+            ))
+            .unwrap();
+        engine
+            .add_rule(rule("all", "SELECT * FROM 'sensors/#'"))
+            .unwrap();
+        let mut disabled = rule("disabled", "SELECT * FROM '#'");
+        disabled.enable = false;
+        engine.add_rule(disabled).unwrap();
+
+        let payload = br#"{"temp": 85, "room": "a"}"#;
+        let mut results = engine.evaluate(&message("sensors/1", payload));
+        results.sort_by(|a, b| a.rule_name.cmp(&b.rule_name));
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].rule_name, "all");
+        assert_eq!(results[0].output["payload"]["temp"], json!(85));
+        assert_eq!(results[0].output["username"], json!("u1"));
+        assert_eq!(results[1].rule_name, "hot");
+        assert_eq!(
+            results[1].output,
+            json!({"temp": 85, "clientid": "c1", "room": "A"})
+        );
+        assert_eq!(results[1].actions, vec![MqttRuleAction::Drop]);
+
+        let results = engine.evaluate(&message("sensors/1", br#"{"temp": 20}"#));
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].rule_name, "all");
+
+        assert!(engine.evaluate(&message("other", payload)).is_empty());
+    }
+
+    #[test]
+    fn evaluate_error_test() {
+        let engine = RuleEngine::new();
+        engine
+            .add_rule(rule("bad", "SELECT payload * 2 AS x FROM 't'"))
+            .unwrap();
+        engine
+            .add_rule(rule("good", "SELECT qos FROM 't'"))
+            .unwrap();
+
+        // A failing rule does not prevent the others from producing output
+        let results = engine.evaluate(&message("t", b"text"));
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].output, json!({"qos": 1}));
+    }
+}
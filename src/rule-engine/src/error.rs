@@ -0,0 +1,39 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum RuleEngineError {
+    #[error("{0}")]
+    SerdeJsonError(#[from] serde_json::Error),
+
+    #[error("Rule SQL syntax error at position {0}: {1}")]
+    SqlSyntaxError(usize, String),
+
+    #[error("Unknown function {0}")]
+    UnknownFunction(String),
+
+    #[error("Function {0} expects {1} arguments, but {2} were given")]
+    FunctionArgumentCount(String, String, usize),
+
+    #[error("Function {0} received an invalid argument: {1}")]
+    InvalidFunctionArgument(String, String),
+
+    #[error("Operator {0} cannot be applied to {1} and {2}")]
+    InvalidOperand(String, String, String),
+
+    #[error("Rule {0} already exists")]
+    RuleAlreadyExist(String),
+}
@@ -0,0 +1,242 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::error::RuleEngineError;
+use crate::functions::{as_f64, as_string, call_function, to_number};
+use crate::sql::{BinaryOp, Expr};
+use serde_json::Value;
+use std::cmp::Ordering;
+
+pub fn evaluate(expr: &Expr, columns: &Value) -> Result<Value, RuleEngineError> {
+    match expr {
+        Expr::Literal(value) => Ok(value.clone()),
+        Expr::Field(path) => Ok(lookup_field(columns, path)),
+        Expr::Not(inner) => Ok(Value::Bool(!is_true(&evaluate(inner, columns)?))),
+        Expr::Neg(inner) => {
+            let value = evaluate(inner, columns)?;
+            match as_f64(&value) {
+                Some(n) if !value.is_string() => Ok(to_number(-n)),
+                _ => Err(invalid_operand("-", &value, &Value::Null)),
+            }
+        }
+        Expr::Binary(op, left, right) => evaluate_binary(*op, left, right, columns),
+        Expr::Call(name, args) => {
+            let args = args
+                .iter()
+                .map(|arg| evaluate(arg, columns))
+                .collect::<Result<Vec<Value>, RuleEngineError>>()?;
+            call_function(name, &args)
+        }
+    }
+}
+
+// Only a boolean true satisfies a condition; null, numbers and strings do not.
+pub fn is_true(value: &Value) -> bool {
+    matches!(value, Value::Bool(true))
+}
+
+// Missing fields resolve to null instead of failing the rule.
+fn lookup_field(columns: &Value, path: &[String]) -> Value {
+    let mut current = columns;
+    for segment in path {
+        let next = match current {
+            Value::Object(map) => map.get(segment),
+            Value::Array(list) => segment.parse::<usize>().ok().and_then(|i| list.get(i)),
+            _ => None,
+        };
+        match next {
+            Some(value) => current = value,
+            None => return Value::Null,
+        }
+    }
+    current.clone()
+}
+
+fn evaluate_binary(
+    op: BinaryOp,
+    left: &Expr,
+    right: &Expr,
+    columns: &Value,
+) -> Result<Value, RuleEngineError> {
+    // AND and OR short-circuit so the right side is only evaluated when needed
+    match op {
+        BinaryOp::And => {
+            if !is_true(&evaluate(left, columns)?) {
+                return Ok(Value::Bool(false));
+            }
+            return Ok(Value::Bool(is_true(&evaluate(right, columns)?)));
+        }
+        BinaryOp::Or => {
+            if is_true(&evaluate(left, columns)?) {
+                return Ok(Value::Bool(true));
+            }
+            return Ok(Value::Bool(is_true(&evaluate(right, columns)?)));
+        }
+        _ => {}
+    }
+
+    let left = evaluate(left, columns)?;
+    let right = evaluate(right, columns)?;
+
+    match op {
+        BinaryOp::Eq => Ok(Value::Bool(values_equal(&left, &right))),
+        BinaryOp::Ne => Ok(Value::Bool(!values_equal(&left, &right))),
+        BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge => {
+            // Comparing against a missing field is simply false
+            let Some(ordering) = compare(&left, &right) else {
+                return Ok(Value::Bool(false));
+            };
+            let result = match op {
+                BinaryOp::Lt => ordering == Ordering::Less,
+                BinaryOp::Le => ordering != Ordering::Greater,
+                BinaryOp::Gt => ordering == Ordering::Greater,
+                _ => ordering != Ordering::Less,
+            };
+            Ok(Value::Bool(result))
+        }
+        BinaryOp::Add => {
+            if left.is_string() || right.is_string() {
+                return Ok(Value::from(format!(
+                    "{}{}",
+                    as_string(&left),
+                    as_string(&right)
+                )));
+            }
+            arithmetic(op, &left, &right, |a, b| Some(a + b))
+        }
+        BinaryOp::Sub => arithmetic(op, &left, &right, |a, b| Some(a - b)),
+        BinaryOp::Mul => arithmetic(op, &left, &right, |a, b| Some(a * b)),
+        BinaryOp::Div => arithmetic(op, &left, &right, |a, b| (b != 0.0).then(|| a / b)),
+        BinaryOp::Mod => arithmetic(op, &left, &right, |a, b| (b != 0.0).then(|| a % b)),
+        BinaryOp::And | BinaryOp::Or => unreachable!(),
+    }
+}
+
+fn arithmetic(
+    op: BinaryOp,
+    left: &Value,
+    right: &Value,
+    f: fn(f64, f64) -> Option<f64>,
+) -> Result<Value, RuleEngineError> {
+    if left.is_null() || right.is_null() {
+        return Ok(Value::Null);
+    }
+    match (numeric(left), numeric(right)) {
+        (Some(a), Some(b)) => Ok(f(a, b).map(to_number).unwrap_or(Value::Null)),
+        _ => Err(invalid_operand(op.symbol(), left, right)),
+    }
+}
+
+fn numeric(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        _ => None,
+    }
+}
+
+fn values_equal(left: &Value, right: &Value) -> bool {
+    if let (Some(a), Some(b)) = (numeric(left), numeric(right)) {
+        return a == b;
+    }
+    // Allow comparing numeric strings from text payloads with numbers
+    if left.is_number() || right.is_number() {
+        if let (Some(a), Some(b)) = (as_f64(left), as_f64(right)) {
+            if !left.is_boolean() && !right.is_boolean() {
+                return a == b;
+            }
+        }
+    }
+    left == right
+}
+
+fn compare(left: &Value, right: &Value) -> Option<Ordering> {
+    match (left, right) {
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        (Value::Null, _) | (_, Value::Null) => None,
+        _ => as_f64(left)?.partial_cmp(&as_f64(right)?),
+    }
+}
+
+fn invalid_operand(op: &str, left: &Value, right: &Value) -> RuleEngineError {
+    RuleEngineError::InvalidOperand(op.to_string(), left.to_string(), right.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql::{parse_rule_sql, SelectFields};
+    use serde_json::json;
+
+    fn eval_where(condition: &str, columns: &Value) -> Value {
+        let rule = parse_rule_sql(&format!("SELECT * FROM 't' WHERE {condition}")).unwrap();
+        evaluate(&rule.condition.unwrap(), columns).unwrap()
+    }
+
+    fn eval_select(field: &str, columns: &Value) -> Result<Value, RuleEngineError> {
+        let rule = parse_rule_sql(&format!("SELECT {field} FROM 't'")).unwrap();
+        let SelectFields::List(fields) = rule.fields else {
+            panic!("expected a field list");
+        };
+        evaluate(&fields[0].expr, columns)
+    }
+
+    #[test]
+    fn condition_test() {
+        let columns = json!({
+            "clientid": "c1",
+            "qos": 1,
+            "payload": {"temp": 85.5, "unit": "c", "tags": ["a", "b"]}
+        });
+
+        assert_eq!(eval_where("payload.temp > 80", &columns), json!(true));
+        assert_eq!(eval_where("payload.temp <= 80", &columns), json!(false));
+        assert_eq!(
+            eval_where("clientid = 'c1' AND qos >= 1", &columns),
+            json!(true)
+        );
+        assert_eq!(
+            eval_where("clientid != 'c1' OR qos = 2", &columns),
+            json!(false)
+        );
+        assert_eq!(eval_where("NOT qos = 2", &columns), json!(true));
+        assert_eq!(eval_where("payload.tags.1 = 'b'", &columns), json!(true));
+        assert_eq!(
+            eval_where("upper(payload.unit) = 'C'", &columns),
+            json!(true)
+        );
+    }
+
+    #[test]
+    fn missing_field_test() {
+        let columns = json!({"payload": "plain text"});
+        assert_eq!(eval_where("payload.temp > 80", &columns), json!(false));
+        assert_eq!(eval_where("payload.temp = null", &columns), json!(true));
+        assert_eq!(eval_select("missing + 1", &columns).unwrap(), Value::Null);
+        assert_eq!(eval_where("missing", &columns), Value::Null);
+    }
+
+    #[test]
+    fn arithmetic_test() {
+        let columns = json!({"a": 7, "b": 2, "s": "10"});
+        assert_eq!(eval_select("a + b * 3", &columns).unwrap(), json!(13));
+        assert_eq!(eval_select("(a + b) * 3", &columns).unwrap(), json!(27));
+        assert_eq!(eval_select("a / b", &columns).unwrap(), json!(3.5));
+        assert_eq!(eval_select("a % b", &columns).unwrap(), json!(1));
+        assert_eq!(eval_select("-a", &columns).unwrap(), json!(-7));
+        assert_eq!(eval_select("a / 0", &columns).unwrap(), Value::Null);
+        assert_eq!(eval_select("'x' + a", &columns).unwrap(), json!("x7"));
+        assert_eq!(eval_where("s = 10", &columns), json!(true));
+        assert!(eval_select("s * 2", &columns).is_err());
+    }
+}
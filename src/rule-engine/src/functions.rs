@@ -0,0 +1,371 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::error::RuleEngineError;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{TimeZone, Utc};
+use common_base::tools::{now_mills, now_second};
+use serde_json::Value;
+
+// Name, minimum and maximum number of arguments of every built-in function.
+const FUNCTIONS: &[(&str, usize, usize)] = &[
+    // string
+    ("lower", 1, 1),
+    ("upper", 1, 1),
+    ("trim", 1, 1),
+    ("ltrim", 1, 1),
+    ("rtrim", 1, 1),
+    ("concat", 1, usize::MAX),
+    ("substr", 2, 3),
+    ("replace", 3, 3),
+    ("strlen", 1, 1),
+    ("split", 2, 2),
+    ("str", 1, 1),
+    // math
+    ("abs", 1, 1),
+    ("ceil", 1, 1),
+    ("floor", 1, 1),
+    ("round", 1, 1),
+    ("sqrt", 1, 1),
+    ("power", 2, 2),
+    ("int", 1, 1),
+    ("float", 1, 1),
+    // time
+    ("now_timestamp", 0, 0),
+    ("now_timestamp_ms", 0, 0),
+    ("format_date", 2, 2),
+    // encoding
+    ("base64_encode", 1, 1),
+    ("base64_decode", 1, 1),
+    ("json_decode", 1, 1),
+    ("json_encode", 1, 1),
+    ("json_path", 2, 2),
+];
+
+pub fn check_function(name: &str, arg_num: usize) -> Result<(), RuleEngineError> {
+    let Some((_, min, max)) = FUNCTIONS.iter().find(|(fn_name, _, _)| *fn_name == name) else {
+        return Err(RuleEngineError::UnknownFunction(name.to_string()));
+    };
+
+    if arg_num < *min || arg_num > *max {
+        let expected = if min == max {
+            min.to_string()
+        } else if *max == usize::MAX {
+            format!("at least {min}")
+        } else {
+            format!("{min} to {max}")
+        };
+        return Err(RuleEngineError::FunctionArgumentCount(
+            name.to_string(),
+            expected,
+            arg_num,
+        ));
+    }
+    Ok(())
+}
+
+pub fn call_function(name: &str, args: &[Value]) -> Result<Value, RuleEngineError> {
+    check_function(name, args.len())?;
+
+    let value = match name {
+        "lower" => Value::from(as_string(&args[0]).to_lowercase()),
+        "upper" => Value::from(as_string(&args[0]).to_uppercase()),
+        "trim" => Value::from(as_string(&args[0]).trim()),
+        "ltrim" => Value::from(as_string(&args[0]).trim_start()),
+        "rtrim" => Value::from(as_string(&args[0]).trim_end()),
+        "concat" => Value::from(args.iter().map(as_string).collect::<String>()),
+        "substr" => {
+            let chars: Vec<char> = as_string(&args[0]).chars().collect();
+            let start = (as_integer(name, &args[1])?.max(0) as usize).min(chars.len());
+            let end = match args.get(2) {
+                Some(len) => start.saturating_add(as_integer(name, len)?.max(0) as usize),
+                None => chars.len(),
+            }
+            .min(chars.len());
+            Value::from(chars[start..end].iter().collect::<String>())
+        }
+        "replace" => {
+            Value::from(as_string(&args[0]).replace(&as_string(&args[1]), &as_string(&args[2])))
+        }
+        "strlen" => Value::from(as_string(&args[0]).chars().count()),
+        "split" => {
+            let value = as_string(&args[0]);
+            let separator = as_string(&args[1]);
+            Value::from(
+                value
+                    .split(separator.as_str())
+                    .filter(|s| !s.is_empty())
+                    .collect::<Vec<&str>>(),
+            )
+        }
+        "str" => Value::from(as_string(&args[0])),
+        "abs" => number_fn(name, &args[0], f64::abs)?,
+        "ceil" => number_fn(name, &args[0], f64::ceil)?,
+        "floor" => number_fn(name, &args[0], f64::floor)?,
+        "round" => number_fn(name, &args[0], f64::round)?,
+        "sqrt" => number_fn(name, &args[0], f64::sqrt)?,
+        "power" => to_number(as_number(name, &args[0])?.powf(as_number(name, &args[1])?)),
+        "int" => Value::from(as_integer(name, &args[0])?),
+        "float" => Value::from(as_number(name, &args[0])?),
+        "now_timestamp" => Value::from(now_second()),
+        "now_timestamp_ms" => Value::from(now_mills() as u64),
+        "format_date" => {
+            let format = as_string(&args[0]);
+            let Some(time) = Utc
+                .timestamp_millis_opt(as_integer(name, &args[1])?)
+                .single()
+            else {
+                return Err(invalid_argument(name, "timestamp out of range"));
+            };
+            Value::from(time.format(&format).to_string())
+        }
+        "base64_encode" => Value::from(STANDARD.encode(as_string(&args[0]))),
+        "base64_decode" => {
+            let data = STANDARD
+                .decode(as_string(&args[0]))
+                .map_err(|e| invalid_argument(name, &e.to_string()))?;
+            Value::from(String::from_utf8_lossy(&data).to_string())
+        }
+        "json_decode" => match &args[0] {
+            Value::String(data) => serde_json::from_str(data)?,
+            other => other.clone(),
+        },
+        "json_encode" => Value::from(serde_json::to_string(&args[0])?),
+        "json_path" => json_path(&args[0], &as_string(&args[1]))
+            .map_err(|e| invalid_argument(name, &e))?
+            .unwrap_or(Value::Null),
+        _ => return Err(RuleEngineError::UnknownFunction(name.to_string())),
+    };
+    Ok(value)
+}
+
+// Renders a value as text. Strings are returned without quotes, null as an empty string.
+pub fn as_string(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+pub fn as_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse::<f64>().ok(),
+        Value::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+        _ => None,
+    }
+}
+
+// Converts a float result back into JSON, keeping whole numbers integral.
+pub fn to_number(value: f64) -> Value {
+    if value.fract() == 0.0 && value.abs() < i64::MAX as f64 {
+        return Value::from(value as i64);
+    }
+    serde_json::Number::from_f64(value)
+        .map(Value::Number)
+        .unwrap_or(Value::Null)
+}
+
+fn as_number(name: &str, value: &Value) -> Result<f64, RuleEngineError> {
+    as_f64(value).ok_or_else(|| invalid_argument(name, &format!("{value} is not a number")))
+}
+
+fn as_integer(name: &str, value: &Value) -> Result<i64, RuleEngineError> {
+    if let Value::Number(n) = value {
+        if let Some(i) = n.as_i64() {
+            return Ok(i);
+        }
+    }
+    Ok(as_number(name, value)?.trunc() as i64)
+}
+
+fn number_fn(name: &str, value: &Value, f: fn(f64) -> f64) -> Result<Value, RuleEngineError> {
+    Ok(to_number(f(as_number(name, value)?)))
+}
+
+fn invalid_argument(name: &str, reason: &str) -> RuleEngineError {
+    RuleEngineError::InvalidFunctionArgument(name.to_string(), reason.to_string())
+}
+
+// Resolves a path like $.a.b[0]['c d'] against a JSON value.
+fn json_path(value: &Value, path: &str) -> Result<Option<Value>, String> {
+    let path = path.trim();
+    let Some(mut rest) = path.strip_prefix('$') else {
+        return Err(format!("path {path} must start with $"));
+    };
+
+    let mut current = value;
+    while !rest.is_empty() {
+        let next = if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            if end == 0 {
+                return Err(format!("empty key in path {path}"));
+            }
+            let key = &after[..end];
+            rest = &after[end..];
+            current.get(key)
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let Some(end) = after.find(']') else {
+                return Err(format!("unclosed [ in path {path}"));
+            };
+            let segment = after[..end].trim();
+            rest = &after[end + 1..];
+            if let Some(key) = segment
+                .strip_prefix('\'')
+                .and_then(|s| s.strip_suffix('\''))
+            {
+                current.get(key)
+            } else {
+                let index = segment
+                    .parse::<usize>()
+                    .map_err(|_| format!("invalid index {segment} in path {path}"))?;
+                current.get(index)
+            }
+        } else {
+            return Err(format!("unexpected character in path {path}"));
+        };
+
+        match next {
+            Some(value) => current = value,
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(current.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn call(name: &str, args: Vec<Value>) -> Value {
+        call_function(name, &args).unwrap()
+    }
+
+    #[test]
+    fn check_function_test() {
+        assert!(check_function("upper", 1).is_ok());
+        assert!(check_function("concat", 5).is_ok());
+        assert!(check_function("substr", 3).is_ok());
+        assert!(matches!(
+            check_function("nope", 1),
+            Err(RuleEngineError::UnknownFunction(_))
+        ));
+        assert!(matches!(
+            check_function("substr", 4),
+            Err(RuleEngineError::FunctionArgumentCount(_, _, 4))
+        ));
+    }
+
+    #[test]
+    fn string_function_test() {
+        assert_eq!(call("lower", vec![json!("AbC")]), json!("abc"));
+        assert_eq!(call("upper", vec![json!("AbC")]), json!("ABC"));
+        assert_eq!(call("trim", vec![json!("  a ")]), json!("a"));
+        assert_eq!(call("ltrim", vec![json!("  a ")]), json!("a "));
+        assert_eq!(call("rtrim", vec![json!("  a ")]), json!("  a"));
+        assert_eq!(
+            call("concat", vec![json!("a"), json!(1), json!(true)]),
+            json!("a1true")
+        );
+        assert_eq!(
+            call("substr", vec![json!("hello"), json!(1)]),
+            json!("ello")
+        );
+        assert_eq!(
+            call("substr", vec![json!("hello"), json!(1), json!(3)]),
+            json!("ell")
+        );
+        assert_eq!(
+            call("substr", vec![json!("hello"), json!(10), json!(3)]),
+            json!("")
+        );
+        assert_eq!(
+            call("replace", vec![json!("a-b-c"), json!("-"), json!("/")]),
+            json!("a/b/c")
+        );
+        assert_eq!(call("strlen", vec![json!("héllo")]), json!(5));
+        assert_eq!(
+            call("split", vec![json!("a/b//c"), json!("/")]),
+            json!(["a", "b", "c"])
+        );
+        assert_eq!(call("str", vec![json!(1.5)]), json!("1.5"));
+    }
+
+    #[test]
+    fn math_function_test() {
+        assert_eq!(call("abs", vec![json!(-3)]), json!(3));
+        assert_eq!(call("ceil", vec![json!(1.2)]), json!(2));
+        assert_eq!(call("floor", vec![json!(1.8)]), json!(1));
+        assert_eq!(call("round", vec![json!(1.5)]), json!(2));
+        assert_eq!(call("sqrt", vec![json!(16)]), json!(4));
+        assert_eq!(call("power", vec![json!(2), json!(10)]), json!(1024));
+        assert_eq!(call("int", vec![json!("42.9")]), json!(42));
+        assert_eq!(call("float", vec![json!("1.5")]), json!(1.5));
+        assert!(call_function("abs", &[json!("x")]).is_err());
+    }
+
+    #[test]
+    fn time_function_test() {
+        assert!(call("now_timestamp", vec![]).as_u64().unwrap() > 0);
+        assert!(call("now_timestamp_ms", vec![]).as_u64().unwrap() > 0);
+        assert_eq!(
+            call("format_date", vec![json!("%Y-%m-%d %H:%M:%S"), json!(0)]),
+            json!("1970-01-01 00:00:00")
+        );
+    }
+
+    #[test]
+    fn encoding_function_test() {
+        assert_eq!(
+            call("base64_encode", vec![json!("hello")]),
+            json!("aGVsbG8=")
+        );
+        assert_eq!(
+            call("base64_decode", vec![json!("aGVsbG8=")]),
+            json!("hello")
+        );
+        assert!(call_function("base64_decode", &[json!("!!")]).is_err());
+
+        assert_eq!(
+            call("json_decode", vec![json!(r#"{"a":1}"#)]),
+            json!({"a": 1})
+        );
+        assert_eq!(
+            call("json_encode", vec![json!({"a": 1})]),
+            json!(r#"{"a":1}"#)
+        );
+    }
+
+    #[test]
+    fn json_path_test() {
+        let data = json!({"a": {"b": [1, {"c d": "x"}]}});
+        assert_eq!(
+            call("json_path", vec![data.clone(), json!("$.a.b[0]")]),
+            json!(1)
+        );
+        assert_eq!(
+            call("json_path", vec![data.clone(), json!("$.a.b[1]['c d']")]),
+            json!("x")
+        );
+        assert_eq!(call("json_path", vec![data.clone(), json!("$")]), data);
+        assert_eq!(
+            call("json_path", vec![data.clone(), json!("$.z")]),
+            Value::Null
+        );
+        assert!(call_function("json_path", &[data, json!("a.b")]).is_err());
+    }
+}
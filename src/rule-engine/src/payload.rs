@@ -0,0 +1,90 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde_json::Value;
+
+// Decodes a message payload so rule SQL can address its fields. JSON is tried first,
+// then Avro object container files (which carry their own schema). Anything else is
+// exposed as a plain string.
+pub fn decode_payload(payload: &[u8]) -> Value {
+    if let Ok(value) = serde_json::from_slice::<Value>(payload) {
+        return value;
+    }
+
+    if let Some(value) = decode_avro(payload) {
+        return value;
+    }
+
+    Value::String(String::from_utf8_lossy(payload).to_string())
+}
+
+fn decode_avro(payload: &[u8]) -> Option<Value> {
+    let reader = apache_avro::Reader::new(payload).ok()?;
+    let mut records = Vec::new();
+    for record in reader {
+        records.push(Value::try_from(record.ok()?).ok()?);
+    }
+
+    if records.len() == 1 {
+        return records.pop();
+    }
+    Some(Value::Array(records))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use apache_avro::types::Record;
+    use apache_avro::{Schema, Writer};
+    use serde_json::json;
+
+    #[test]
+    fn decode_json_test() {
+        assert_eq!(
+            decode_payload(br#"{"temp": 21.5, "room": "a"}"#),
+            json!({"temp": 21.5, "room": "a"})
+        );
+        assert_eq!(decode_payload(b"42"), json!(42));
+    }
+
+    #[test]
+    fn decode_text_test() {
+        assert_eq!(decode_payload(b"hello world"), json!("hello world"));
+        assert_eq!(decode_payload(b""), json!(""));
+    }
+
+    #[test]
+    fn decode_avro_test() {
+        let schema = Schema::parse_str(
+            r#"{
+                "type": "record",
+                "name": "reading",
+                "fields": [
+                    {"name": "temp", "type": "long"},
+                    {"name": "room", "type": "string"}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let mut writer = Writer::new(&schema, Vec::new());
+        let mut record = Record::new(writer.schema()).unwrap();
+        record.put("temp", 21i64);
+        record.put("room", "a");
+        writer.append(record).unwrap();
+        let data = writer.into_inner().unwrap();
+
+        assert_eq!(decode_payload(&data), json!({"temp": 21, "room": "a"}));
+    }
+}
@@ -0,0 +1,593 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::error::RuleEngineError;
+use crate::functions::check_function;
+use serde_json::{Number, Value};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Literal(Value),
+    // Path into the message columns, e.g. payload.temp
+    Field(Vec<String>),
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BinaryOp {
+    Or,
+    And,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+}
+
+impl BinaryOp {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            BinaryOp::Or => "OR",
+            BinaryOp::And => "AND",
+            BinaryOp::Eq => "=",
+            BinaryOp::Ne => "!=",
+            BinaryOp::Lt => "<",
+            BinaryOp::Le => "<=",
+            BinaryOp::Gt => ">",
+            BinaryOp::Ge => ">=",
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Mod => "%",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SelectField {
+    pub expr: Expr,
+    // The alias, or the expression text when there is none
+    pub name: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SelectFields {
+    All,
+    List(Vec<SelectField>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RuleSql {
+    pub fields: SelectFields,
+    pub topic_filters: Vec<String>,
+    pub condition: Option<Expr>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum TokenKind {
+    Ident(String),
+    Str(String),
+    Number(Number),
+    Comma,
+    Dot,
+    LParen,
+    RParen,
+    Star,
+    Plus,
+    Minus,
+    Slash,
+    Percent,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eof,
+}
+
+#[derive(Clone, Debug)]
+struct Token {
+    kind: TokenKind,
+    start: usize,
+    end: usize,
+}
+
+fn tokenize(sql: &str) -> Result<Vec<Token>, RuleEngineError> {
+    let chars: Vec<(usize, char)> = sql.char_indices().collect();
+    let end_of = |i: usize| chars.get(i).map(|(pos, _)| *pos).unwrap_or(sql.len());
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let (start, c) = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let (kind, next) = match c {
+            ',' => (TokenKind::Comma, i + 1),
+            '.' => (TokenKind::Dot, i + 1),
+            '(' => (TokenKind::LParen, i + 1),
+            ')' => (TokenKind::RParen, i + 1),
+            '*' => (TokenKind::Star, i + 1),
+            '+' => (TokenKind::Plus, i + 1),
+            '-' => (TokenKind::Minus, i + 1),
+            '/' => (TokenKind::Slash, i + 1),
+            '%' => (TokenKind::Percent, i + 1),
+            '=' => {
+                if matches!(chars.get(i + 1), Some((_, '='))) {
+                    (TokenKind::Eq, i + 2)
+                } else {
+                    (TokenKind::Eq, i + 1)
+                }
+            }
+            '!' => {
+                if !matches!(chars.get(i + 1), Some((_, '='))) {
+                    return Err(RuleEngineError::SqlSyntaxError(
+                        start,
+                        "expected != ".to_string(),
+                    ));
+                }
+                (TokenKind::Ne, i + 2)
+            }
+            '<' => match chars.get(i + 1) {
+                Some((_, '=')) => (TokenKind::Le, i + 2),
+                Some((_, '>')) => (TokenKind::Ne, i + 2),
+                _ => (TokenKind::Lt, i + 1),
+            },
+            '>' => match chars.get(i + 1) {
+                Some((_, '=')) => (TokenKind::Ge, i + 2),
+                _ => (TokenKind::Gt, i + 1),
+            },
+            '\'' | '"' => {
+                let mut value = String::new();
+                let mut j = i + 1;
+                loop {
+                    match chars.get(j) {
+                        None => {
+                            return Err(RuleEngineError::SqlSyntaxError(
+                                start,
+                                "unterminated string".to_string(),
+                            ));
+                        }
+                        // A doubled quote stands for the quote itself
+                        Some((_, q)) if *q == c => {
+                            if matches!(chars.get(j + 1), Some((_, q2)) if *q2 == c) {
+                                value.push(c);
+                                j += 2;
+                            } else {
+                                break;
+                            }
+                        }
+                        Some((_, ch)) => {
+                            value.push(*ch);
+                            j += 1;
+                        }
+                    }
+                }
+                (TokenKind::Str(value), j + 1)
+            }
+            c if c.is_ascii_digit() => {
+                let mut j = i;
+                while matches!(chars.get(j), Some((_, d)) if d.is_ascii_digit()) {
+                    j += 1;
+                }
+                if matches!(chars.get(j), Some((_, '.')))
+                    && matches!(chars.get(j + 1), Some((_, d)) if d.is_ascii_digit())
+                {
+                    j += 1;
+                    while matches!(chars.get(j), Some((_, d)) if d.is_ascii_digit()) {
+                        j += 1;
+                    }
+                }
+                let text = &sql[start..end_of(j)];
+                let number = serde_json::from_str::<Number>(text).map_err(|_| {
+                    RuleEngineError::SqlSyntaxError(start, format!("invalid number {text}"))
+                })?;
+                (TokenKind::Number(number), j)
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut j = i;
+                while matches!(chars.get(j), Some((_, ch)) if ch.is_alphanumeric() || *ch == '_') {
+                    j += 1;
+                }
+                (TokenKind::Ident(sql[start..end_of(j)].to_string()), j)
+            }
+            _ => {
+                return Err(RuleEngineError::SqlSyntaxError(
+                    start,
+                    format!("unexpected character {c}"),
+                ));
+            }
+        };
+
+        tokens.push(Token {
+            kind,
+            start,
+            end: end_of(next),
+        });
+        i = next;
+    }
+
+    tokens.push(Token {
+        kind: TokenKind::Eof,
+        start: sql.len(),
+        end: sql.len(),
+    });
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    sql: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos]
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.pos].clone();
+        if token.kind != TokenKind::Eof {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn error<T>(&self, message: &str) -> Result<T, RuleEngineError> {
+        Err(RuleEngineError::SqlSyntaxError(
+            self.peek().start,
+            message.to_string(),
+        ))
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(&self.peek().kind, TokenKind::Ident(name) if name.eq_ignore_ascii_case(keyword))
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if self.is_keyword(keyword) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), RuleEngineError> {
+        if !self.eat_keyword(keyword) {
+            return self.error(&format!("expected {keyword}"));
+        }
+        Ok(())
+    }
+
+    fn eat(&mut self, kind: &TokenKind) -> bool {
+        if &self.peek().kind == kind {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn parse_rule(&mut self) -> Result<RuleSql, RuleEngineError> {
+        self.expect_keyword("SELECT")?;
+        let fields = self.parse_select_fields()?;
+
+        self.expect_keyword("FROM")?;
+        let mut topic_filters = Vec::new();
+        loop {
+            match self.next().kind {
+                TokenKind::Str(topic) if !topic.is_empty() => topic_filters.push(topic),
+                _ => return self.error("expected a quoted topic filter"),
+            }
+            if !self.eat(&TokenKind::Comma) {
+                break;
+            }
+        }
+
+        let condition = if self.eat_keyword("WHERE") {
+            Some(self.parse_expr()?)
+        } else {
+            None
+        };
+
+        if self.peek().kind != TokenKind::Eof {
+            return self.error("unexpected trailing input");
+        }
+
+        Ok(RuleSql {
+            fields,
+            topic_filters,
+            condition,
+        })
+    }
+
+    fn parse_select_fields(&mut self) -> Result<SelectFields, RuleEngineError> {
+        if self.peek().kind == TokenKind::Star {
+            self.pos += 1;
+            return Ok(SelectFields::All);
+        }
+
+        let mut fields = Vec::new();
+        loop {
+            let start = self.peek().start;
+            let expr = self.parse_expr()?;
+            let end = self.tokens[self.pos - 1].end;
+            let name = if self.eat_keyword("AS") {
+                match self.next().kind {
+                    TokenKind::Ident(alias) | TokenKind::Str(alias) => alias,
+                    _ => return self.error("expected an alias after AS"),
+                }
+            } else {
+                self.sql[start..end].trim().to_string()
+            };
+            fields.push(SelectField { expr, name });
+
+            if !self.eat(&TokenKind::Comma) {
+                break;
+            }
+        }
+        Ok(SelectFields::List(fields))
+    }
+
+    fn parse_expr(&mut self) -> Result<Expr, RuleEngineError> {
+        let mut left = self.parse_and()?;
+        while self.eat_keyword("OR") {
+            let right = self.parse_and()?;
+            left = Expr::Binary(BinaryOp::Or, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, RuleEngineError> {
+        let mut left = self.parse_not()?;
+        while self.eat_keyword("AND") {
+            let right = self.parse_not()?;
+            left = Expr::Binary(BinaryOp::And, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr, RuleEngineError> {
+        if self.eat_keyword("NOT") {
+            return Ok(Expr::Not(Box::new(self.parse_not()?)));
+        }
+        self.parse_comparison()
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr, RuleEngineError> {
+        let left = self.parse_additive()?;
+        let op = match self.peek().kind {
+            TokenKind::Eq => BinaryOp::Eq,
+            TokenKind::Ne => BinaryOp::Ne,
+            TokenKind::Lt => BinaryOp::Lt,
+            TokenKind::Le => BinaryOp::Le,
+            TokenKind::Gt => BinaryOp::Gt,
+            TokenKind::Ge => BinaryOp::Ge,
+            _ => return Ok(left),
+        };
+        self.pos += 1;
+        let right = self.parse_additive()?;
+        Ok(Expr::Binary(op, Box::new(left), Box::new(right)))
+    }
+
+    fn parse_additive(&mut self) -> Result<Expr, RuleEngineError> {
+        let mut left = self.parse_multiplicative()?;
+        loop {
+            let op = match self.peek().kind {
+                TokenKind::Plus => BinaryOp::Add,
+                TokenKind::Minus => BinaryOp::Sub,
+                _ => return Ok(left),
+            };
+            self.pos += 1;
+            let right = self.parse_multiplicative()?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<Expr, RuleEngineError> {
+        let mut left = self.parse_unary()?;
+        loop {
+            let op = match self.peek().kind {
+                TokenKind::Star => BinaryOp::Mul,
+                TokenKind::Slash => BinaryOp::Div,
+                TokenKind::Percent => BinaryOp::Mod,
+                _ => return Ok(left),
+            };
+            self.pos += 1;
+            let right = self.parse_unary()?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, RuleEngineError> {
+        if self.eat(&TokenKind::Minus) {
+            return Ok(Expr::Neg(Box::new(self.parse_unary()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, RuleEngineError> {
+        let token = self.next();
+        match token.kind {
+            TokenKind::Number(number) => Ok(Expr::Literal(Value::Number(number))),
+            TokenKind::Str(value) => Ok(Expr::Literal(Value::String(value))),
+            TokenKind::LParen => {
+                let expr = self.parse_expr()?;
+                if !self.eat(&TokenKind::RParen) {
+                    return self.error("expected )");
+                }
+                Ok(expr)
+            }
+            TokenKind::Ident(name) => {
+                if name.eq_ignore_ascii_case("true") {
+                    return Ok(Expr::Literal(Value::Bool(true)));
+                }
+                if name.eq_ignore_ascii_case("false") {
+                    return Ok(Expr::Literal(Value::Bool(false)));
+                }
+                if name.eq_ignore_ascii_case("null") {
+                    return Ok(Expr::Literal(Value::Null));
+                }
+
+                if self.eat(&TokenKind::LParen) {
+                    let args = self.parse_args()?;
+                    let name = name.to_lowercase();
+                    check_function(&name, args.len())?;
+                    return Ok(Expr::Call(name, args));
+                }
+
+                let mut path = vec![name];
+                while self.eat(&TokenKind::Dot) {
+                    match self.next().kind {
+                        TokenKind::Ident(segment) => path.push(segment),
+                        TokenKind::Number(index) if index.is_u64() => path.push(index.to_string()),
+                        _ => return self.error("expected a field name after ."),
+                    }
+                }
+                Ok(Expr::Field(path))
+            }
+            _ => Err(RuleEngineError::SqlSyntaxError(
+                token.start,
+                "expected an expression".to_string(),
+            )),
+        }
+    }
+
+    fn parse_args(&mut self) -> Result<Vec<Expr>, RuleEngineError> {
+        let mut args = Vec::new();
+        if self.eat(&TokenKind::RParen) {
+            return Ok(args);
+        }
+        loop {
+            args.push(self.parse_expr()?);
+            if self.eat(&TokenKind::RParen) {
+                return Ok(args);
+            }
+            if !self.eat(&TokenKind::Comma) {
+                return self.error("expected , or )");
+            }
+        }
+    }
+}
+
+pub fn parse_rule_sql(sql: &str) -> Result<RuleSql, RuleEngineError> {
+    let mut parser = Parser {
+        sql,
+        tokens: tokenize(sql)?,
+        pos: 0,
+    };
+    parser.parse_rule()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(path: &[&str]) -> Expr {
+        Expr::Field(path.iter().map(|s| s.to_string()).collect())
+    }
+
+    #[test]
+    fn parse_rule_sql_test() {
+        let sql = r#"SELECT payload.temp AS t, clientid FROM "sensors/#" WHERE payload.temp > 80"#;
+        let rule = parse_rule_sql(sql).unwrap();
+
+        assert_eq!(rule.topic_filters, vec!["sensors/#".to_string()]);
+        assert_eq!(
+            rule.fields,
+            SelectFields::List(vec![
+                SelectField {
+                    expr: field(&["payload", "temp"]),
+                    name: "t".to_string(),
+                },
+                SelectField {
+                    expr: field(&["clientid"]),
+                    name: "clientid".to_string(),
+                },
+            ])
+        );
+        assert_eq!(
+            rule.condition,
+            Some(Expr::Binary(
+                BinaryOp::Gt,
+                Box::new(field(&["payload", "temp"])),
+                Box::new(Expr::Literal(Value::from(80))),
+            ))
+        );
+    }
+
+    #[test]
+    fn parse_precedence_test() {
+        let rule =
+            parse_rule_sql("select * from 't/1', 't/2' where a = 1 or b = 2 and not c").unwrap();
+        assert_eq!(rule.fields, SelectFields::All);
+        assert_eq!(rule.topic_filters.len(), 2);
+
+        let Some(Expr::Binary(BinaryOp::Or, _, right)) = rule.condition else {
+            panic!("expected OR at the top");
+        };
+        assert!(matches!(*right, Expr::Binary(BinaryOp::And, _, _)));
+
+        let rule = parse_rule_sql("SELECT 1 + 2 * 3 FROM 't'").unwrap();
+        let SelectFields::List(fields) = rule.fields else {
+            panic!("expected a field list");
+        };
+        assert_eq!(fields[0].name, "1 + 2 * 3");
+        assert!(matches!(
+            &fields[0].expr,
+            Expr::Binary(BinaryOp::Add, _, right) if matches!(**right, Expr::Binary(BinaryOp::Mul, _, _))
+        ));
+    }
+
+    #[test]
+    fn parse_function_test() {
+        let rule = parse_rule_sql("SELECT upper(clientid) AS c, now_timestamp() FROM 't'").unwrap();
+        let SelectFields::List(fields) = rule.fields else {
+            panic!("expected a field list");
+        };
+        assert_eq!(
+            fields[0].expr,
+            Expr::Call("upper".to_string(), vec![field(&["clientid"])])
+        );
+        assert_eq!(fields[1].name, "now_timestamp()");
+
+        assert!(parse_rule_sql("SELECT unknown_fn(a) FROM 't'").is_err());
+        assert!(parse_rule_sql("SELECT upper(a, b) FROM 't'").is_err());
+    }
+
+    #[test]
+    fn parse_error_test() {
+        assert!(parse_rule_sql("").is_err());
+        assert!(parse_rule_sql("SELECT a").is_err());
+        assert!(parse_rule_sql("SELECT a FROM t").is_err());
+        assert!(parse_rule_sql("SELECT a FROM 't' WHERE").is_err());
+        assert!(parse_rule_sql("SELECT a FROM 't' extra").is_err());
+        assert!(parse_rule_sql("SELECT 'abc FROM 't'").is_err());
+        assert!(parse_rule_sql("SELECT a FROM 't' WHERE (a > 1").is_err());
+    }
+}
@@ -0,0 +1,568 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Behaviour every StorageAdapter has to share. Each adapter runs the cases
+// through storage_conformance_tests!, so a new adapter gets the same coverage
+// by adding one line to its test module.
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use common_base::{error::common::CommonError, tools::unique_id};
+use futures::StreamExt;
+use metadata_struct::adapter::{
+    read_config::ReadConfig,
+    record::{Header, Record},
+};
+use tokio::time::{sleep, timeout};
+
+use crate::storage::{ShardInfo, StorageAdapter};
+
+// Expands to one test per conformance case. `$build` names an async fn of the
+// calling test module that returns a fresh adapter.
+macro_rules! storage_conformance_tests {
+    ($(#[$attr:meta])* $build:ident) => {
+        mod conformance {
+            use std::sync::Arc;
+
+            use crate::conformance;
+
+            #[tokio::test]
+            $(#[$attr])*
+            async fn ordering_and_offsets() {
+                conformance::ordering_and_offsets(&super::$build().await).await;
+            }
+
+            #[tokio::test]
+            $(#[$attr])*
+            async fn read_by_tag() {
+                conformance::read_by_tag(&super::$build().await).await;
+            }
+
+            #[tokio::test]
+            $(#[$attr])*
+            async fn read_by_key() {
+                conformance::read_by_key(&super::$build().await).await;
+            }
+
+            #[tokio::test]
+            $(#[$attr])*
+            async fn offset_by_timestamp() {
+                conformance::offset_by_timestamp(&super::$build().await).await;
+            }
+
+            #[tokio::test]
+            $(#[$attr])*
+            async fn group_offsets() {
+                conformance::group_offsets(&super::$build().await).await;
+            }
+
+            #[tokio::test]
+            $(#[$attr])*
+            async fn concurrent_writes() {
+                conformance::concurrent_writes(Arc::new(super::$build().await)).await;
+            }
+
+            #[tokio::test]
+            $(#[$attr])*
+            async fn read_stream() {
+                conformance::read_stream(Arc::new(super::$build().await)).await;
+            }
+
+            #[tokio::test]
+            $(#[$attr])*
+            async fn close() {
+                conformance::close(&super::$build().await).await;
+            }
+        }
+    };
+}
+
+// Record deletion cases. Adapters that only drop whole segments may keep
+// records they were asked to delete, as long as they report it.
+macro_rules! storage_deletion_tests {
+    ($(#[$attr:meta])* $build:ident) => {
+        mod deletion {
+            use crate::conformance;
+
+            #[tokio::test]
+            $(#[$attr])*
+            async fn delete_before_offset() {
+                conformance::delete_before_offset(&super::$build().await).await;
+            }
+
+            #[tokio::test]
+            $(#[$attr])*
+            async fn delete_before_timestamp() {
+                conformance::delete_before_timestamp(&super::$build().await).await;
+            }
+
+            #[tokio::test]
+            $(#[$attr])*
+            async fn compact_by_key() {
+                conformance::compact_by_key(&super::$build().await).await;
+            }
+        }
+    };
+}
+
+pub(crate) use storage_conformance_tests;
+pub(crate) use storage_deletion_tests;
+
+const SHARD_NAME: &str = "conformance";
+
+async fn create_test_shard<A: StorageAdapter + Sync>(adapter: &A) -> String {
+    let namespace = unique_id();
+    adapter
+        .create_shard(ShardInfo {
+            namespace: namespace.clone(),
+            shard_name: SHARD_NAME.to_string(),
+            replica_num: 1,
+        })
+        .await
+        .unwrap();
+    namespace
+}
+
+fn build_record(i: u64) -> Record {
+    let mut record = Record::build_byte(format!("record-{i}").into_bytes());
+    record.set_header(vec![Header {
+        name: "index".to_string(),
+        value: i.to_string(),
+    }]);
+    record
+}
+
+async fn read<A: StorageAdapter + Sync>(
+    adapter: &A,
+    namespace: &str,
+    offset: u64,
+    max_record_num: u64,
+) -> Vec<Record> {
+    let mut read_config = ReadConfig::new();
+    read_config.max_record_num = max_record_num;
+    adapter
+        .read_by_offset(
+            namespace.to_string(),
+            SHARD_NAME.to_string(),
+            offset,
+            read_config,
+        )
+        .await
+        .unwrap()
+}
+
+// A delete either removes everything it was asked to, or says it did not
+fn deleted_all(result: Result<(), CommonError>) -> bool {
+    match result {
+        Ok(()) => true,
+        Err(CommonError::PartialDeletion(_, _)) => false,
+        Err(e) => panic!("{e}"),
+    }
+}
+
+fn offsets_of(records: &[Record]) -> Vec<u64> {
+    records
+        .iter()
+        .map(|record| record.offset.unwrap())
+        .collect()
+}
+
+// Offsets start at 0, grow by one per record and come back in write order
+pub async fn ordering_and_offsets<A: StorageAdapter + Sync>(adapter: &A) {
+    let namespace = create_test_shard(adapter).await;
+
+    let offsets = adapter
+        .batch_write(
+            namespace.clone(),
+            SHARD_NAME.to_string(),
+            (0..5).map(build_record).collect(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(offsets, vec![0, 1, 2, 3, 4]);
+
+    let offset = adapter
+        .write(namespace.clone(), SHARD_NAME.to_string(), build_record(5))
+        .await
+        .unwrap();
+    assert_eq!(offset, 5);
+
+    let records = read(adapter, &namespace, 0, 100).await;
+    assert_eq!(offsets_of(&records), vec![0, 1, 2, 3, 4, 5]);
+    for record in records.iter() {
+        let offset = record.offset.unwrap();
+        assert_eq!(record.data, format!("record-{offset}").into_bytes());
+        assert_eq!(record.header[0].value, offset.to_string());
+        assert!(record.crc32_check());
+    }
+
+    let records = read(adapter, &namespace, 2, 2).await;
+    assert_eq!(offsets_of(&records), vec![2, 3]);
+
+    let records = read(adapter, &namespace, 6, 100).await;
+    assert!(records.is_empty());
+}
+
+// Only records carrying the tag are returned, from the offset on
+pub async fn read_by_tag<A: StorageAdapter + Sync>(adapter: &A) {
+    let namespace = create_test_shard(adapter).await;
+
+    let records = (0..6)
+        .map(|i| {
+            let mut record = build_record(i);
+            let tag = if i % 2 == 0 { "even" } else { "odd" };
+            record.set_tags(vec![tag.to_string()]);
+            record
+        })
+        .collect();
+    adapter
+        .batch_write(namespace.clone(), SHARD_NAME.to_string(), records)
+        .await
+        .unwrap();
+
+    let read_by_tag = |offset: u64, tag: &str, max_record_num: u64| {
+        let mut read_config = ReadConfig::new();
+        read_config.max_record_num = max_record_num;
+        adapter.read_by_tag(
+            namespace.clone(),
+            SHARD_NAME.to_string(),
+            offset,
+            tag.to_string(),
+            read_config,
+        )
+    };
+
+    let records = read_by_tag(0, "even", 10).await.unwrap();
+    assert_eq!(offsets_of(&records), vec![0, 2, 4]);
+    assert!(records.iter().all(|record| record.tags == vec!["even"]));
+
+    let records = read_by_tag(1, "odd", 10).await.unwrap();
+    assert_eq!(offsets_of(&records), vec![1, 3, 5]);
+
+    let records = read_by_tag(1, "even", 1).await.unwrap();
+    assert_eq!(offsets_of(&records), vec![2]);
+}
+
+// A keyed record can be found by its key
+pub async fn read_by_key<A: StorageAdapter + Sync>(adapter: &A) {
+    let namespace = create_test_shard(adapter).await;
+
+    let records = (0..4)
+        .map(|i| {
+            let mut record = build_record(i);
+            record.set_key(format!("key-{i}"));
+            record
+        })
+        .collect();
+    adapter
+        .batch_write(namespace.clone(), SHARD_NAME.to_string(), records)
+        .await
+        .unwrap();
+
+    let mut read_config = ReadConfig::new();
+    read_config.max_record_num = 10;
+    let records = adapter
+        .read_by_key(
+            namespace.clone(),
+            SHARD_NAME.to_string(),
+            0,
+            "key-2".to_string(),
+            read_config,
+        )
+        .await
+        .unwrap();
+    assert_eq!(offsets_of(&records), vec![2]);
+    assert_eq!(records[0].key, "key-2");
+    assert_eq!(records[0].data, b"record-2".to_vec());
+}
+
+// The lookup returns the first record written at or after the timestamp
+pub async fn offset_by_timestamp<A: StorageAdapter + Sync>(adapter: &A) {
+    let namespace = create_test_shard(adapter).await;
+
+    let records = (0..5)
+        .map(|i| {
+            let mut record = build_record(i);
+            record.timestamp = 1000 + i * 10;
+            record
+        })
+        .collect();
+    adapter
+        .batch_write(namespace.clone(), SHARD_NAME.to_string(), records)
+        .await
+        .unwrap();
+
+    let offset_by_timestamp = |timestamp: u64| {
+        adapter.get_offset_by_timestamp(namespace.clone(), SHARD_NAME.to_string(), timestamp)
+    };
+
+    let offset = offset_by_timestamp(0).await.unwrap().unwrap();
+    assert_eq!(offset.offset, 0);
+
+    let offset = offset_by_timestamp(1020).await.unwrap().unwrap();
+    assert_eq!(offset.offset, 2);
+
+    let offset = offset_by_timestamp(1015).await.unwrap().unwrap();
+    assert_eq!(offset.offset, 2);
+
+    assert!(offset_by_timestamp(2000).await.unwrap().is_none());
+}
+
+// A committed offset is returned for its group, and a new commit replaces it
+pub async fn group_offsets<A: StorageAdapter + Sync>(adapter: &A) {
+    let namespace = create_test_shard(adapter).await;
+    let group_name = unique_id();
+
+    assert!(adapter
+        .get_offset_by_group(group_name.clone())
+        .await
+        .unwrap()
+        .is_empty());
+
+    for committed in [3, 5] {
+        let mut offsets = HashMap::new();
+        offsets.insert(SHARD_NAME.to_string(), committed);
+        adapter
+            .commit_offset(group_name.clone(), namespace.clone(), offsets)
+            .await
+            .unwrap();
+
+        let offsets = adapter
+            .get_offset_by_group(group_name.clone())
+            .await
+            .unwrap();
+        assert_eq!(offsets.len(), 1);
+        assert_eq!(offsets[0].offset, committed);
+    }
+}
+
+// Writers racing on one shard never get the same offset, and no record is lost
+pub async fn concurrent_writes<A: StorageAdapter + Send + Sync + 'static>(adapter: Arc<A>) {
+    let namespace = create_test_shard(adapter.as_ref()).await;
+    let writers = 8;
+    let batches = 5;
+    let batch_size = 5;
+
+    let mut tasks = Vec::new();
+    for writer in 0..writers {
+        let adapter = adapter.clone();
+        let namespace = namespace.clone();
+        tasks.push(tokio::spawn(async move {
+            let mut offsets = Vec::new();
+            for batch in 0..batches {
+                let start = (writer * batches + batch) * batch_size;
+                let records = (start..start + batch_size).map(build_record).collect();
+                let written = adapter
+                    .batch_write(namespace.clone(), SHARD_NAME.to_string(), records)
+                    .await
+                    .unwrap();
+                assert_eq!(written.len() as u64, batch_size);
+                offsets.extend(written);
+            }
+            offsets
+        }));
+    }
+
+    let mut offsets = Vec::new();
+    for task in tasks {
+        offsets.extend(task.await.unwrap());
+    }
+    offsets.sort();
+    let total = writers * batches * batch_size;
+    assert_eq!(offsets, (0..total).collect::<Vec<u64>>());
+
+    let records = read(adapter.as_ref(), &namespace, 0, total * 2).await;
+    assert_eq!(offsets_of(&records), (0..total).collect::<Vec<u64>>());
+}
+
+// The stream returns what is stored, then wakes up for records written later
+pub async fn read_stream<A: StorageAdapter + Send + Sync + 'static>(adapter: Arc<A>) {
+    let namespace = create_test_shard(adapter.as_ref()).await;
+    adapter
+        .batch_write(
+            namespace.clone(),
+            SHARD_NAME.to_string(),
+            (0..3).map(build_record).collect(),
+        )
+        .await
+        .unwrap();
+
+    let mut read_config = ReadConfig::new();
+    read_config.max_record_num = 2;
+    let mut stream = adapter.read_stream(namespace.clone(), SHARD_NAME.to_string(), 1, read_config);
+
+    for expected in [1, 2] {
+        let record = timeout(Duration::from_secs(5), stream.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(record.offset, Some(expected));
+    }
+
+    let writer = adapter.clone();
+    let writer_namespace = namespace.clone();
+    tokio::spawn(async move {
+        sleep(Duration::from_millis(200)).await;
+        writer
+            .write(writer_namespace, SHARD_NAME.to_string(), build_record(3))
+            .await
+            .unwrap();
+    });
+
+    let record = timeout(Duration::from_secs(5), stream.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert_eq!(record.offset, Some(3));
+    assert_eq!(record.data, b"record-3".to_vec());
+}
+
+// Records below the offset are gone, and truncating does not reuse offsets
+pub async fn delete_before_offset<A: StorageAdapter + Sync>(adapter: &A) {
+    let namespace = create_test_shard(adapter).await;
+    adapter
+        .batch_write(
+            namespace.clone(),
+            SHARD_NAME.to_string(),
+            (0..5).map(build_record).collect(),
+        )
+        .await
+        .unwrap();
+
+    let deleted = deleted_all(
+        adapter
+            .delete_before_offset(namespace.clone(), SHARD_NAME.to_string(), 3)
+            .await,
+    );
+    let offsets = offsets_of(&read(adapter, &namespace, 0, 100).await);
+    if deleted {
+        assert_eq!(offsets, vec![3, 4]);
+    } else {
+        assert!(offsets[0] < 3);
+        assert!(offsets.ends_with(&[3, 4]));
+    }
+
+    let deleted = deleted_all(
+        adapter
+            .delete_before_offset(namespace.clone(), SHARD_NAME.to_string(), 100)
+            .await,
+    );
+    assert_eq!(deleted, read(adapter, &namespace, 0, 100).await.is_empty());
+
+    let offset = adapter
+        .write(namespace.clone(), SHARD_NAME.to_string(), build_record(5))
+        .await
+        .unwrap();
+    assert_eq!(offset, 5);
+}
+
+pub async fn delete_before_timestamp<A: StorageAdapter + Sync>(adapter: &A) {
+    let namespace = create_test_shard(adapter).await;
+    let records = (0..5)
+        .map(|i| {
+            let mut record = build_record(i);
+            record.timestamp = 1000 + i * 10;
+            record
+        })
+        .collect();
+    adapter
+        .batch_write(namespace.clone(), SHARD_NAME.to_string(), records)
+        .await
+        .unwrap();
+
+    let deleted = deleted_all(
+        adapter
+            .delete_before_timestamp(namespace.clone(), SHARD_NAME.to_string(), 1015)
+            .await,
+    );
+    let offsets = offsets_of(&read(adapter, &namespace, 0, 100).await);
+    if deleted {
+        assert_eq!(offsets, vec![2, 3, 4]);
+    } else {
+        assert!(offsets[0] < 2);
+        assert!(offsets.ends_with(&[2, 3, 4]));
+    }
+}
+
+// The latest record of each key survives, and so do records without a key
+pub async fn compact_by_key<A: StorageAdapter + Sync>(adapter: &A) {
+    let namespace = create_test_shard(adapter).await;
+    let keys = ["a", "b", "", "a", "b", "a", ""];
+    let records = keys
+        .iter()
+        .enumerate()
+        .map(|(i, key)| {
+            let mut record = build_record(i as u64);
+            record.set_key(key.to_string());
+            record.set_tags(vec!["compact".to_string()]);
+            record
+        })
+        .collect();
+    adapter
+        .batch_write(namespace.clone(), SHARD_NAME.to_string(), records)
+        .await
+        .unwrap();
+
+    let deleted = deleted_all(
+        adapter
+            .compact_by_key(namespace.clone(), SHARD_NAME.to_string())
+            .await,
+    );
+    let offsets = offsets_of(&read(adapter, &namespace, 0, 100).await);
+    if !deleted {
+        // At least one superseded record is still there
+        assert!([0, 1, 3].iter().any(|offset| offsets.contains(offset)));
+        assert!([2, 4, 5, 6].iter().all(|offset| offsets.contains(offset)));
+        return;
+    }
+    assert_eq!(offsets, vec![2, 4, 5, 6]);
+
+    // The tag and key lookups no longer return the removed records
+    let mut read_config = ReadConfig::new();
+    read_config.max_record_num = 100;
+    let records = adapter
+        .read_by_tag(
+            namespace.clone(),
+            SHARD_NAME.to_string(),
+            0,
+            "compact".to_string(),
+            read_config.clone(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(offsets_of(&records), vec![2, 4, 5, 6]);
+
+    let records = adapter
+        .read_by_key(
+            namespace.clone(),
+            SHARD_NAME.to_string(),
+            0,
+            "a".to_string(),
+            read_config,
+        )
+        .await
+        .unwrap();
+    assert_eq!(offsets_of(&records), vec![5]);
+}
+
+pub async fn close<A: StorageAdapter + Sync>(adapter: &A) {
+    let namespace = create_test_shard(adapter).await;
+    adapter
+        .write(namespace, SHARD_NAME.to_string(), build_record(0))
+        .await
+        .unwrap();
+    adapter.close().await.unwrap();
+}
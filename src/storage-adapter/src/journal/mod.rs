@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 use axum::async_trait;
@@ -22,15 +22,21 @@
 use metadata_struct::adapter::read_config::ReadConfig;
 use metadata_struct::adapter::record::Record;
 use offset::PlaceOffsetManager;
+use segment::{deletable_segments, remaining_offsets, segments_before_offset, PlaceSegmentManager};
 
-use crate::storage::{ShardInfo, ShardOffset, StorageAdapter};
+use crate::storage::{superseded_offsets, DeletionSupport, ShardInfo, ShardOffset, StorageAdapter};
 
 pub mod offset;
+pub mod segment;
+
+// Records read from the shard at a time while looking for superseded keys
+const COMPACTION_READ_RECORD_NUM: u64 = 1000;
 
 pub struct JournalStorageAdapter {
     cluster_name: String,
     client: JournalClient,
     offset_manager: PlaceOffsetManager,
+    segment_manager: PlaceSegmentManager,
 }
 
 impl JournalStorageAdapter {
@@ -40,18 +46,48 @@ pub async fn new(
         journal_addrs: Vec<String>,
         place_addrs: Vec<String>,
     ) -> Result<JournalStorageAdapter, CommonError> {
-        let offset_manager = PlaceOffsetManager::new(client_pool, place_addrs.clone());
+        let offset_manager = PlaceOffsetManager::new(client_pool.clone(), place_addrs.clone());
+        let segment_manager = PlaceSegmentManager::new(client_pool, place_addrs.clone());
         let client = match JournalClient::new(journal_addrs.clone()).await {
             Ok(client) => client,
             Err(e) => return Err(CommonError::CommonError(e.to_string())),
         };
         let adapter = JournalStorageAdapter {
             offset_manager,
+            segment_manager,
             cluster_name,
             client,
         };
         Ok(adapter)
     }
+
+    async fn delete_segments(
+        &self,
+        namespace: &str,
+        shard_name: &str,
+        segment_seqs: &[u32],
+    ) -> Result<(), CommonError> {
+        for segment_seq in segment_seqs {
+            self.segment_manager
+                .delete_segment(&self.cluster_name, namespace, shard_name, *segment_seq)
+                .await?;
+        }
+        Ok(())
+    }
+
+    // Offset of the oldest record the shard still stores
+    async fn first_offset(
+        &self,
+        namespace: &str,
+        shard_name: &str,
+    ) -> Result<Option<u64>, CommonError> {
+        let mut read_config = ReadConfig::new();
+        read_config.max_record_num = 1;
+        let records = self
+            .read_by_offset(namespace.to_owned(), shard_name.to_owned(), 0, read_config)
+            .await?;
+        Ok(records.first().and_then(|record| record.offset))
+    }
 }
 
 #[async_trait]
@@ -258,10 +294,155 @@ async fn commit_offset(
             .await
     }
 
+    async fn delete_by_offsets(
+        &self,
+        namespace: String,
+        shard_name: String,
+        offsets: Vec<u64>,
+    ) -> Result<(), CommonError> {
+        let segments = self
+            .segment_manager
+            .list_sealed_segments(&self.cluster_name, &namespace, &shard_name)
+            .await?;
+
+        let offsets: HashSet<u64> = offsets.into_iter().collect();
+        let deleted = deletable_segments(&segments, &offsets);
+        self.delete_segments(&namespace, &shard_name, &deleted)
+            .await?;
+
+        let first_offset = self.first_offset(&namespace, &shard_name).await?;
+        let remaining = remaining_offsets(offsets, &segments, &deleted, first_offset);
+        if !remaining.is_empty() {
+            return Err(CommonError::PartialDeletion(
+                shard_name,
+                format!(
+                    "{} records share a segment with records that are kept",
+                    remaining.len()
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    // Records go away a whole sealed segment at a time, so records sharing a
+    // segment with newer ones, or in the active segment, stay stored
+    async fn delete_before_offset(
+        &self,
+        namespace: String,
+        shard_name: String,
+        offset: u64,
+    ) -> Result<(), CommonError> {
+        let segments = self
+            .segment_manager
+            .list_sealed_segments(&self.cluster_name, &namespace, &shard_name)
+            .await?;
+
+        let deleted = segments_before_offset(&segments, offset);
+        self.delete_segments(&namespace, &shard_name, &deleted)
+            .await?;
+
+        if let Some(first_offset) = self.first_offset(&namespace, &shard_name).await? {
+            if first_offset < offset {
+                return Err(CommonError::PartialDeletion(
+                    shard_name,
+                    format!("records from offset {first_offset} to {offset} are still stored"),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    async fn delete_before_timestamp(
+        &self,
+        namespace: String,
+        shard_name: String,
+        timestamp: u64,
+    ) -> Result<(), CommonError> {
+        let Some(shard_offset) = self
+            .get_offset_by_timestamp(namespace.clone(), shard_name.clone(), timestamp)
+            .await?
+        else {
+            return Ok(());
+        };
+
+        self.delete_before_offset(namespace, shard_name, shard_offset.offset)
+            .await
+    }
+
+    async fn compact_by_key(
+        &self,
+        namespace: String,
+        shard_name: String,
+    ) -> Result<(), CommonError> {
+        let mut read_config = ReadConfig::new();
+        read_config.max_record_num = COMPACTION_READ_RECORD_NUM;
+
+        let mut entries = Vec::new();
+        let mut offset = 0;
+        loop {
+            let records = self
+                .read_by_offset(
+                    namespace.clone(),
+                    shard_name.clone(),
+                    offset,
+                    read_config.clone(),
+                )
+                .await?;
+            let Some(last_offset) = records.last().and_then(|record| record.offset) else {
+                break;
+            };
+
+            entries.extend(
+                records
+                    .into_iter()
+                    .filter_map(|record| Some((record.key, record.offset?))),
+            );
+            offset = last_offset + 1;
+        }
+
+        self.delete_by_offsets(namespace, shard_name, superseded_offsets(entries))
+            .await
+    }
+
     async fn close(&self) -> Result<(), CommonError> {
         if let Err(e) = self.client.close().await {
             return Err(CommonError::CommonError(e.to_string()));
         }
         Ok(())
     }
+
+    fn deletion_support(&self) -> DeletionSupport {
+        DeletionSupport::Segment
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use grpc_clients::pool::ClientPool;
+
+    use super::JournalStorageAdapter;
+
+    async fn build_adapter() -> JournalStorageAdapter {
+        let client_pool = Arc::new(ClientPool::new(100));
+        JournalStorageAdapter::new(
+            client_pool,
+            "test-cluster".to_string(),
+            vec!["127.0.0.1:3110".to_string()],
+            vec!["127.0.0.1:1228".to_string()],
+        )
+        .await
+        .unwrap()
+    }
+
+    crate::conformance::storage_conformance_tests!(
+        #[ignore]
+        build_adapter
+    );
+
+    crate::conformance::storage_deletion_tests!(
+        #[ignore]
+        build_adapter
+    );
 }
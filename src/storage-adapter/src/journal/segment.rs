@@ -0,0 +1,272 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use common_base::error::common::CommonError;
+use grpc_clients::meta::journal::call::{delete_segment, list_segment, list_segment_meta};
+use grpc_clients::pool::ClientPool;
+use metadata_struct::journal::segment::{JournalSegment, SegmentStatus};
+use metadata_struct::journal::segment_meta::JournalSegmentMetadata;
+use protocol::meta::meta_service_journal::{
+    DeleteSegmentRequest, ListSegmentMetaRequest, ListSegmentRequest,
+};
+
+/// Offset range of a sealed segment, both ends included.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SealedSegment {
+    pub segment_seq: u32,
+    pub start_offset: u64,
+    pub end_offset: u64,
+}
+
+#[derive(Clone)]
+pub(crate) struct PlaceSegmentManager {
+    client_pool: Arc<ClientPool>,
+    addrs: Vec<String>,
+}
+
+impl PlaceSegmentManager {
+    pub fn new(client_pool: Arc<ClientPool>, addrs: Vec<String>) -> Self {
+        PlaceSegmentManager { client_pool, addrs }
+    }
+
+    pub async fn list_sealed_segments(
+        &self,
+        cluster_name: &str,
+        namespace: &str,
+        shard_name: &str,
+    ) -> Result<Vec<SealedSegment>, CommonError> {
+        let request = ListSegmentRequest {
+            cluster_name: cluster_name.to_owned(),
+            namespace: namespace.to_owned(),
+            shard_name: shard_name.to_owned(),
+            segment_no: -1,
+        };
+        let reply = list_segment(&self.client_pool, &self.addrs, request).await?;
+        let segments = serde_json::from_slice::<Vec<JournalSegment>>(&reply.segments)?;
+
+        let request = ListSegmentMetaRequest {
+            cluster_name: cluster_name.to_owned(),
+            namespace: namespace.to_owned(),
+            shard_name: shard_name.to_owned(),
+            segment_no: -1,
+        };
+        let reply = list_segment_meta(&self.client_pool, &self.addrs, request).await?;
+        let metas = serde_json::from_slice::<Vec<JournalSegmentMetadata>>(&reply.segments)?;
+
+        Ok(build_sealed_segments(&segments, &metas))
+    }
+
+    pub async fn delete_segment(
+        &self,
+        cluster_name: &str,
+        namespace: &str,
+        shard_name: &str,
+        segment_seq: u32,
+    ) -> Result<(), CommonError> {
+        let request = DeleteSegmentRequest {
+            cluster_name: cluster_name.to_owned(),
+            namespace: namespace.to_owned(),
+            shard_name: shard_name.to_owned(),
+            segment_seq,
+        };
+        delete_segment(&self.client_pool, &self.addrs, request).await?;
+        Ok(())
+    }
+}
+
+fn build_sealed_segments(
+    segments: &[JournalSegment],
+    metas: &[JournalSegmentMetadata],
+) -> Vec<SealedSegment> {
+    segments
+        .iter()
+        .filter(|segment| segment.status == SegmentStatus::SealUp)
+        .filter_map(|segment| {
+            let meta = metas
+                .iter()
+                .find(|meta| meta.segment_seq == segment.segment_seq)?;
+            if meta.start_offset < 0 || meta.end_offset < meta.start_offset {
+                return None;
+            }
+            Some(SealedSegment {
+                segment_seq: segment.segment_seq,
+                start_offset: meta.start_offset as u64,
+                end_offset: meta.end_offset as u64,
+            })
+        })
+        .collect()
+}
+
+/// Journal segments are append only, so records can only be reclaimed by
+/// dropping a whole sealed segment once every record in it is to be deleted.
+pub(crate) fn deletable_segments(segments: &[SealedSegment], offsets: &HashSet<u64>) -> Vec<u32> {
+    segments
+        .iter()
+        .filter(|segment| {
+            (segment.start_offset..=segment.end_offset).all(|offset| offsets.contains(&offset))
+        })
+        .map(|segment| segment.segment_seq)
+        .collect()
+}
+
+/// Sealed segments whose every record is below `offset`.
+pub(crate) fn segments_before_offset(segments: &[SealedSegment], offset: u64) -> Vec<u32> {
+    segments
+        .iter()
+        .filter(|segment| segment.end_offset < offset)
+        .map(|segment| segment.segment_seq)
+        .collect()
+}
+
+/// Offsets still stored after the `deleted` segments were dropped, where
+/// `first_offset` is the oldest record left in the shard. Records below it,
+/// or inside a dropped segment, are gone.
+pub(crate) fn remaining_offsets(
+    offsets: impl IntoIterator<Item = u64>,
+    segments: &[SealedSegment],
+    deleted: &[u32],
+    first_offset: Option<u64>,
+) -> Vec<u64> {
+    let Some(first_offset) = first_offset else {
+        return Vec::new();
+    };
+    let mut remaining: Vec<u64> = offsets
+        .into_iter()
+        .filter(|offset| *offset >= first_offset)
+        .filter(|offset| {
+            !segments.iter().any(|segment| {
+                deleted.contains(&segment.segment_seq)
+                    && (segment.start_offset..=segment.end_offset).contains(offset)
+            })
+        })
+        .collect();
+    remaining.sort_unstable();
+    remaining
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_sealed_segments_test() {
+        let segments = vec![
+            JournalSegment {
+                segment_seq: 0,
+                status: SegmentStatus::SealUp,
+                ..Default::default()
+            },
+            JournalSegment {
+                segment_seq: 1,
+                status: SegmentStatus::Write,
+                ..Default::default()
+            },
+        ];
+        let metas = vec![
+            JournalSegmentMetadata {
+                segment_seq: 0,
+                start_offset: 0,
+                end_offset: 9,
+                ..Default::default()
+            },
+            JournalSegmentMetadata {
+                segment_seq: 1,
+                start_offset: 10,
+                end_offset: -1,
+                ..Default::default()
+            },
+        ];
+
+        let sealed = build_sealed_segments(&segments, &metas);
+        assert_eq!(
+            sealed,
+            vec![SealedSegment {
+                segment_seq: 0,
+                start_offset: 0,
+                end_offset: 9,
+            }]
+        );
+    }
+
+    #[test]
+    fn deletable_segments_test() {
+        let segments = vec![
+            SealedSegment {
+                segment_seq: 0,
+                start_offset: 0,
+                end_offset: 2,
+            },
+            SealedSegment {
+                segment_seq: 1,
+                start_offset: 3,
+                end_offset: 5,
+            },
+        ];
+
+        let offsets: HashSet<u64> = vec![0, 1, 2, 3, 5].into_iter().collect();
+        assert_eq!(deletable_segments(&segments, &offsets), vec![0]);
+
+        let offsets: HashSet<u64> = HashSet::new();
+        assert!(deletable_segments(&segments, &offsets).is_empty());
+    }
+
+    #[test]
+    fn segments_before_offset_test() {
+        let segments = vec![
+            SealedSegment {
+                segment_seq: 0,
+                start_offset: 0,
+                end_offset: 2,
+            },
+            SealedSegment {
+                segment_seq: 1,
+                start_offset: 3,
+                end_offset: 5,
+            },
+        ];
+
+        assert!(segments_before_offset(&segments, 2).is_empty());
+        assert_eq!(segments_before_offset(&segments, 3), vec![0]);
+        assert_eq!(segments_before_offset(&segments, 100), vec![0, 1]);
+    }
+
+    #[test]
+    fn remaining_offsets_test() {
+        let segments = vec![
+            SealedSegment {
+                segment_seq: 0,
+                start_offset: 0,
+                end_offset: 2,
+            },
+            SealedSegment {
+                segment_seq: 1,
+                start_offset: 3,
+                end_offset: 5,
+            },
+        ];
+
+        assert_eq!(
+            remaining_offsets(vec![0, 1, 2, 4, 7], &segments, &[0], Some(3)),
+            vec![4, 7]
+        );
+        assert_eq!(
+            remaining_offsets(vec![4, 7], &segments, &[0, 1], Some(6)),
+            vec![7]
+        );
+        assert!(remaining_offsets(vec![0, 1], &segments, &[], None).is_empty());
+    }
+}
@@ -16,11 +16,14 @@
 
 use std::str::FromStr;
 
+#[cfg(test)]
+mod conformance;
 pub mod journal;
 pub mod memory;
 pub mod meta;
 pub mod minio;
 pub mod mysql;
+pub mod postgres;
 pub mod rocksdb;
 pub mod s3;
 pub mod storage;
@@ -30,6 +33,7 @@ pub enum StorageType {
     Journal,
     Memory,
     Mysql,
+    Postgres,
     Placement,
     RocksDB,
     MinIO,
@@ -43,6 +47,7 @@ fn from_str(s: &str) -> Result<Self, Self::Err> {
             "journal" => Ok(StorageType::Journal),
             "memory" => Ok(StorageType::Memory),
             "mysql" => Ok(StorageType::Mysql),
+            "postgres" => Ok(StorageType::Postgres),
             "placement" => Ok(StorageType::Placement),
             "rocksdb" => Ok(StorageType::RocksDB),
             "minio" => Ok(StorageType::MinIO),
@@ -68,6 +73,10 @@ fn storage_type_from_str() {
             StorageType::Memory
         );
         assert_eq!(StorageType::from_str("mysql").unwrap(), StorageType::Mysql);
+        assert_eq!(
+            StorageType::from_str("postgres").unwrap(),
+            StorageType::Postgres
+        );
         assert_eq!(
             StorageType::from_str("placement").unwrap(),
             StorageType::Placement
@@ -12,22 +12,27 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
 
 use axum::async_trait;
 use common_base::error::common::CommonError;
 use dashmap::DashMap;
 use metadata_struct::adapter::read_config::ReadConfig;
 use metadata_struct::adapter::record::Record;
+use tokio::sync::Notify;
+use tokio::time::timeout;
 
-use crate::storage::{ShardInfo, ShardOffset, StorageAdapter};
+use crate::storage::{superseded_offsets, ShardInfo, ShardOffset, StorageAdapter};
 
 #[derive(Clone)]
 pub struct MemoryStorageAdapter {
     pub shard_info: DashMap<String, ShardInfo>,
-    pub shard_data: DashMap<String, Vec<Record>>,
-    //group, (namespace_shard_name,offset)
+    pub shard_data: DashMap<String, BTreeMap<u64, Record>>,
+    pub shard_offset: DashMap<String, u64>,
     pub group_data: DashMap<String, DashMap<String, u64>>,
+    write_notifiers: DashMap<String, Arc<Notify>>,
 }
 
 impl Default for MemoryStorageAdapter {
@@ -40,23 +45,45 @@ impl MemoryStorageAdapter {
     pub fn new() -> Self {
         MemoryStorageAdapter {
             shard_data: DashMap::with_capacity(256),
+            shard_offset: DashMap::with_capacity(256),
             group_data: DashMap::with_capacity(256),
             shard_info: DashMap::with_capacity(2),
+            write_notifiers: DashMap::with_capacity(2),
         }
     }
 
     pub fn shard_key(&self, namespace: &str, shard_name: &str) -> String {
         format!("{namespace}_{shard_name}")
     }
-}
 
-impl MemoryStorageAdapter {}
+    // Offsets keep growing even after records are deleted, so that a deleted
+    // offset is never handed out twice.
+    fn append(&self, shard_key: &str, messages: Vec<Record>) -> Vec<u64> {
+        let mut next_offset = self.shard_offset.entry(shard_key.to_owned()).or_insert(0);
+        let mut data_list = self.shard_data.entry(shard_key.to_owned()).or_default();
+
+        let mut offset_res = Vec::new();
+        for mut msg in messages {
+            let offset = *next_offset;
+            offset_res.push(offset);
+            msg.offset = Some(offset);
+            data_list.insert(offset, msg);
+            *next_offset += 1;
+        }
+
+        if let Some(notify) = self.write_notifiers.get(shard_key) {
+            notify.notify_waiters();
+        }
+        offset_res
+    }
+}
 
 #[async_trait]
 impl StorageAdapter for MemoryStorageAdapter {
     async fn create_shard(&self, shard: ShardInfo) -> Result<(), CommonError> {
         let key = self.shard_key(&shard.namespace, &shard.shard_name);
-        self.shard_data.insert(key.clone(), Vec::new());
+        self.shard_data.insert(key.clone(), BTreeMap::new());
+        self.shard_offset.insert(key.clone(), 0);
         self.shard_info.insert(key.clone(), shard);
         return Ok(());
     }
@@ -77,8 +104,9 @@ async fn list_shard(
     }
 
     async fn delete_shard(&self, namespace: String, shard_name: String) -> Result<(), CommonError> {
-        self.shard_data
-            .remove(&self.shard_key(&namespace, &shard_name));
+        let shard_key = self.shard_key(&namespace, &shard_name);
+        self.shard_data.remove(&shard_key);
+        self.shard_offset.remove(&shard_key);
         return Ok(());
     }
 
@@ -89,52 +117,18 @@ async fn batch_write(
         messages: Vec<Record>,
     ) -> Result<Vec<u64>, CommonError> {
         let shard_key = self.shard_key(&namespace, &shard_name);
-        let mut offset_res = Vec::new();
-
-        if let Some(mut data_list) = self.shard_data.get_mut(&shard_key) {
-            let mut start_offset = data_list.len();
-            for mut msg in messages {
-                offset_res.push(start_offset as u64);
-                msg.offset = Some(start_offset as u64);
-                data_list.push(msg);
-                start_offset += 1;
-            }
-        } else {
-            let mut data_list = Vec::new();
-            for (offset, mut msg) in messages.into_iter().enumerate() {
-                offset_res.push(offset as u64);
-
-                msg.offset = Some(offset as u64);
-                data_list.push(msg);
-            }
-            self.shard_data.insert(shard_key, data_list);
-        }
-
-        return Ok(offset_res);
+        return Ok(self.append(&shard_key, messages));
     }
 
     async fn write(
         &self,
         namespace: String,
         shard_name: String,
-        mut data: Record,
+        data: Record,
     ) -> Result<u64, CommonError> {
         let shard_key = self.shard_key(&namespace, &shard_name);
-
-        let offset = if let Some(mut data_list) = self.shard_data.get_mut(&shard_key) {
-            let start_offset = data_list.len();
-
-            data.offset = Some(start_offset as u64);
-            data_list.push(data);
-
-            start_offset
-        } else {
-            data.offset = Some(0);
-            self.shard_data.insert(shard_key, vec![data]);
-            0
-        };
-
-        return Ok(offset as u64);
+        let offsets = self.append(&shard_key, vec![data]);
+        return Ok(offsets[0]);
     }
 
     async fn read_by_offset(
@@ -147,19 +141,11 @@ async fn read_by_offset(
         let shard_key = self.shard_key(&namespace, &shard_name);
 
         if let Some(data_list) = self.shard_data.get(&shard_key) {
-            if data_list.len() < offset as usize {
-                return Ok(Vec::new());
-            }
-
-            let mut result = Vec::new();
-            for i in offset..(offset + read_config.max_record_num) {
-                if let Some(value) = data_list.get(i as usize) {
-                    result.push(value.clone());
-                } else {
-                    break;
-                }
-            }
-            return Ok(result);
+            return Ok(data_list
+                .range(offset..)
+                .take(read_config.max_record_num as usize)
+                .map(|(_, record)| record.clone())
+                .collect());
         }
 
         Ok(Vec::new())
@@ -176,21 +162,12 @@ async fn read_by_tag(
         let shard_key = self.shard_key(&namespace, &shard_name);
 
         if let Some(record_list) = self.shard_data.get(&shard_key) {
-            if record_list.len() < offset as usize {
-                return Ok(Vec::new());
-            }
-            let mut result = Vec::new();
-
-            for i in offset..(offset + read_config.max_record_num) {
-                if let Some(value) = record_list.get(i as usize) {
-                    if value.tags.contains(&tag) {
-                        result.push(value.clone());
-                    }
-                } else {
-                    break;
-                }
-            }
-            return Ok(result);
+            return Ok(record_list
+                .range(offset..)
+                .filter(|(_, record)| record.tags.contains(&tag))
+                .take(read_config.max_record_num as usize)
+                .map(|(_, record)| record.clone())
+                .collect());
         }
 
         Ok(Vec::new())
@@ -207,21 +184,12 @@ async fn read_by_key(
         let shard_key = self.shard_key(&namespace, &shard_name);
 
         if let Some(record_list) = self.shard_data.get(&shard_key) {
-            if record_list.len() < offset as usize {
-                return Ok(Vec::new());
-            }
-            let mut result = Vec::new();
-
-            for i in offset..(offset + read_config.max_record_num) {
-                if let Some(value) = record_list.get(i as usize) {
-                    if value.key == key {
-                        result.push(value.clone());
-                    }
-                } else {
-                    break;
-                }
-            }
-            return Ok(result);
+            return Ok(record_list
+                .range(offset..)
+                .filter(|(_, record)| record.key == key)
+                .take(read_config.max_record_num as usize)
+                .map(|(_, record)| record.clone())
+                .collect());
         }
 
         Ok(Vec::new())
@@ -236,7 +204,7 @@ async fn get_offset_by_timestamp(
         let shard_key = self.shard_key(&namespace, &shard_name);
 
         if let Some(record_list) = self.shard_data.get(&shard_key) {
-            for record in record_list.iter() {
+            for record in record_list.values() {
                 if record.timestamp >= timestamp {
                     if record.offset.is_none() {
                         return Ok(None);
@@ -291,9 +259,105 @@ async fn commit_offset(
         Ok(())
     }
 
+    async fn delete_by_offsets(
+        &self,
+        namespace: String,
+        shard_name: String,
+        offsets: Vec<u64>,
+    ) -> Result<(), CommonError> {
+        let shard_key = self.shard_key(&namespace, &shard_name);
+
+        if let Some(mut record_list) = self.shard_data.get_mut(&shard_key) {
+            for offset in offsets {
+                record_list.remove(&offset);
+            }
+        }
+        Ok(())
+    }
+
+    async fn delete_before_offset(
+        &self,
+        namespace: String,
+        shard_name: String,
+        offset: u64,
+    ) -> Result<(), CommonError> {
+        let shard_key = self.shard_key(&namespace, &shard_name);
+
+        if let Some(mut record_list) = self.shard_data.get_mut(&shard_key) {
+            let kept = record_list.split_off(&offset);
+            *record_list = kept;
+        }
+        Ok(())
+    }
+
+    async fn delete_before_timestamp(
+        &self,
+        namespace: String,
+        shard_name: String,
+        timestamp: u64,
+    ) -> Result<(), CommonError> {
+        let shard_key = self.shard_key(&namespace, &shard_name);
+
+        if let Some(mut record_list) = self.shard_data.get_mut(&shard_key) {
+            record_list.retain(|_, record| record.timestamp >= timestamp);
+        }
+        Ok(())
+    }
+
+    async fn compact_by_key(
+        &self,
+        namespace: String,
+        shard_name: String,
+    ) -> Result<(), CommonError> {
+        let shard_key = self.shard_key(&namespace, &shard_name);
+
+        if let Some(mut record_list) = self.shard_data.get_mut(&shard_key) {
+            let superseded = superseded_offsets(
+                record_list
+                    .iter()
+                    .map(|(offset, record)| (record.key.as_str(), *offset)),
+            );
+            for offset in superseded {
+                record_list.remove(&offset);
+            }
+        }
+        Ok(())
+    }
+
     async fn close(&self) -> Result<(), CommonError> {
         Ok(())
     }
+
+    async fn wait_for_write(
+        &self,
+        namespace: String,
+        shard_name: String,
+        offset: u64,
+        wait_time: Duration,
+    ) -> Result<(), CommonError> {
+        let shard_key = self.shard_key(&namespace, &shard_name);
+        let notify = self
+            .write_notifiers
+            .entry(shard_key.clone())
+            .or_insert_with(|| Arc::new(Notify::new()))
+            .clone();
+
+        // Register before checking the offset so that a write in between still wakes us
+        let notified = notify.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+
+        if self
+            .shard_offset
+            .get(&shard_key)
+            .is_some_and(|next_offset| *next_offset > offset)
+        {
+            return Ok(());
+        }
+
+        let _ = timeout(wait_time, notified).await;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -464,4 +528,51 @@ async fn stream_read_write() {
             .await
             .unwrap();
     }
+
+    #[tokio::test]
+    async fn delete_by_offsets_test() {
+        let storage_adapter = MemoryStorageAdapter::new();
+        let namespace = unique_id();
+        let shard_name = "test-delete".to_string();
+        let data = (0..4)
+            .map(|i| Record::build_byte(format!("test{i}").as_bytes().to_vec()))
+            .collect();
+
+        storage_adapter
+            .batch_write(namespace.clone(), shard_name.clone(), data)
+            .await
+            .unwrap();
+
+        storage_adapter
+            .delete_by_offsets(namespace.clone(), shard_name.clone(), vec![0, 2, 10])
+            .await
+            .unwrap();
+
+        let mut read_config = ReadConfig::new();
+        read_config.max_record_num = 10;
+        let res = storage_adapter
+            .read_by_offset(namespace.clone(), shard_name.clone(), 0, read_config)
+            .await
+            .unwrap();
+        let offsets: Vec<u64> = res.iter().map(|r| r.offset.unwrap()).collect();
+        assert_eq!(offsets, vec![1, 3]);
+
+        // deleted offsets are not reused
+        let offset = storage_adapter
+            .write(
+                namespace,
+                shard_name,
+                Record::build_byte("test4".as_bytes().to_vec()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(offset, 4);
+    }
+
+    async fn build_adapter() -> MemoryStorageAdapter {
+        MemoryStorageAdapter::new()
+    }
+
+    crate::conformance::storage_conformance_tests!(build_adapter);
+    crate::conformance::storage_deletion_tests!(build_adapter);
 }
@@ -16,7 +16,7 @@
 
 use axum::async_trait;
 use common_base::error::common::CommonError;
-use dashmap::DashMap;
+use dashmap::{mapref::entry::Entry, DashMap};
 use grpc_clients::{
     meta::kv::call::{placement_delete, placement_get, placement_get_prefix, placement_set},
     pool::ClientPool,
@@ -33,7 +33,7 @@
     time::{sleep, timeout},
 };
 
-use crate::storage::{ShardInfo, ShardOffset, StorageAdapter};
+use crate::storage::{DeletionSupport, ShardInfo, ShardOffset, StorageAdapter};
 
 pub struct PlacementStorageAdapter {
     client_pool: Arc<ClientPool>,
@@ -189,7 +189,16 @@ async fn register_write_handle(
         handle: ThreadWriteHandle,
     ) {
         let handle_key = Self::write_handle_key(namespace, shard_name);
-        self.write_handles.insert(handle_key, handle);
+
+        // Concurrent first writes to a shard may each start a write thread, keep only one
+        match self.write_handles.entry(handle_key) {
+            Entry::Occupied(_) => {
+                let _ = handle.stop_sender.send(true);
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(handle);
+            }
+        }
     }
 
     async fn create_write_thread(&self, namespace: impl AsRef<str>, shard_name: impl AsRef<str>) {
@@ -678,6 +687,53 @@ async fn commit_offset(
         Ok(())
     }
 
+    async fn delete_by_offsets(
+        &self,
+        _namespace: String,
+        _shard_name: String,
+        _offsets: Vec<u64>,
+    ) -> Result<(), CommonError> {
+        Err(CommonError::NotSupportFeature(
+            "PlacementStorageAdapter".to_string(),
+            "delete_by_offsets".to_string(),
+        ))
+    }
+
+    async fn delete_before_offset(
+        &self,
+        _namespace: String,
+        _shard_name: String,
+        _offset: u64,
+    ) -> Result<(), CommonError> {
+        Err(CommonError::NotSupportFeature(
+            "PlacementStorageAdapter".to_string(),
+            "delete_before_offset".to_string(),
+        ))
+    }
+
+    async fn delete_before_timestamp(
+        &self,
+        _namespace: String,
+        _shard_name: String,
+        _timestamp: u64,
+    ) -> Result<(), CommonError> {
+        Err(CommonError::NotSupportFeature(
+            "PlacementStorageAdapter".to_string(),
+            "delete_before_timestamp".to_string(),
+        ))
+    }
+
+    async fn compact_by_key(
+        &self,
+        _namespace: String,
+        _shard_name: String,
+    ) -> Result<(), CommonError> {
+        Err(CommonError::NotSupportFeature(
+            "PlacementStorageAdapter".to_string(),
+            "compact_by_key".to_string(),
+        ))
+    }
+
     async fn close(&self) -> Result<(), CommonError> {
         let write_handles = self.get_all_write_handles().await;
 
@@ -690,6 +746,10 @@ async fn close(&self) -> Result<(), CommonError> {
 
         Ok(())
     }
+
+    fn deletion_support(&self) -> DeletionSupport {
+        DeletionSupport::None
+    }
 }
 
 #[cfg(test)]
@@ -1109,4 +1169,11 @@ async fn concurrency_test() {
 
         assert_eq!(list_res.len(), 0);
     }
+
+    async fn build_adapter() -> PlacementStorageAdapter {
+        let client_pool = Arc::new(ClientPool::new(100));
+        PlacementStorageAdapter::new(client_pool, vec![get_placement_addr()])
+    }
+
+    crate::conformance::storage_conformance_tests!(build_adapter);
 }
@@ -16,7 +16,7 @@
 
 use axum::async_trait;
 use common_base::error::common::CommonError;
-use dashmap::DashMap;
+use dashmap::{mapref::entry::Entry, DashMap};
 use futures::TryStreamExt;
 use metadata_struct::adapter::{read_config::ReadConfig, record::Record};
 use opendal::{services::S3, EntryMode, Operator};
@@ -30,7 +30,7 @@
     time::{sleep, timeout},
 };
 
-use crate::storage::{ShardInfo, ShardOffset, StorageAdapter};
+use crate::storage::{DeletionSupport, ShardInfo, ShardOffset, StorageAdapter};
 
 #[derive(Debug)]
 #[allow(dead_code)]
@@ -237,7 +237,16 @@ async fn register_write_handle(
         handle: ThreadWriteHandle,
     ) {
         let handle_key = Self::write_handle_key(namespace, shard_name);
-        self.write_handles.insert(handle_key, handle);
+
+        // Concurrent first writes to a shard may each start a write thread, keep only one
+        match self.write_handles.entry(handle_key) {
+            Entry::Occupied(_) => {
+                let _ = handle.stop_sender.send(true);
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(handle);
+            }
+        }
     }
 
     async fn create_write_thread(&self, namespace: impl AsRef<str>, shard_name: impl AsRef<str>) {
@@ -487,7 +496,7 @@ async fn read_by_key(
             .await?
             .to_vec();
 
-        if record_bytes.len() < read_config.max_size as usize {
+        if record_bytes.len() > read_config.max_size as usize {
             return Ok(vec![]);
         }
 
@@ -502,11 +511,28 @@ async fn read_by_key(
 
     async fn get_offset_by_timestamp(
         &self,
-        _namespace: String,
-        _shard_name: String,
-        _timestamp: u64,
+        namespace: String,
+        shard_name: String,
+        timestamp: u64,
     ) -> Result<Option<ShardOffset>, CommonError> {
-        Ok(None)
+        // Records are never deleted, so the offsets have no gaps
+        let mut offset = 0;
+        loop {
+            let path = Self::records_path(&namespace, &shard_name, offset);
+            if !self.op.exists(&path).await? {
+                return Ok(None);
+            }
+
+            let record_bytes = self.op.read(&path).await?.to_vec();
+            let record = serde_json::from_slice::<Record>(&record_bytes)?;
+            if record.timestamp >= timestamp {
+                return Ok(Some(ShardOffset {
+                    offset,
+                    ..Default::default()
+                }));
+            }
+            offset += 1;
+        }
     }
 
     async fn get_offset_by_group(
@@ -564,6 +590,53 @@ async fn commit_offset(
         Ok(())
     }
 
+    async fn delete_by_offsets(
+        &self,
+        _namespace: String,
+        _shard_name: String,
+        _offsets: Vec<u64>,
+    ) -> Result<(), CommonError> {
+        Err(CommonError::NotSupportFeature(
+            "MinIoStorageAdapter".to_string(),
+            "delete_by_offsets".to_string(),
+        ))
+    }
+
+    async fn delete_before_offset(
+        &self,
+        _namespace: String,
+        _shard_name: String,
+        _offset: u64,
+    ) -> Result<(), CommonError> {
+        Err(CommonError::NotSupportFeature(
+            "MinIoStorageAdapter".to_string(),
+            "delete_before_offset".to_string(),
+        ))
+    }
+
+    async fn delete_before_timestamp(
+        &self,
+        _namespace: String,
+        _shard_name: String,
+        _timestamp: u64,
+    ) -> Result<(), CommonError> {
+        Err(CommonError::NotSupportFeature(
+            "MinIoStorageAdapter".to_string(),
+            "delete_before_timestamp".to_string(),
+        ))
+    }
+
+    async fn compact_by_key(
+        &self,
+        _namespace: String,
+        _shard_name: String,
+    ) -> Result<(), CommonError> {
+        Err(CommonError::NotSupportFeature(
+            "MinIoStorageAdapter".to_string(),
+            "compact_by_key".to_string(),
+        ))
+    }
+
     async fn close(&self) -> Result<(), CommonError> {
         let write_handles = self.get_all_write_handles().await;
 
@@ -576,6 +649,10 @@ async fn close(&self) -> Result<(), CommonError> {
 
         Ok(())
     }
+
+    fn deletion_support(&self) -> DeletionSupport {
+        DeletionSupport::None
+    }
 }
 
 #[cfg(test)]
@@ -941,4 +1018,13 @@ async fn concurrency_test() {
             assert_eq!(len, (100 / shards.len()) * 100);
         }
     }
+
+    async fn build_adapter() -> MinIoStorageAdapter {
+        MinIoStorageAdapter::new("/tmp/minio", "test").unwrap()
+    }
+
+    crate::conformance::storage_conformance_tests!(
+        #[ignore]
+        build_adapter
+    );
 }
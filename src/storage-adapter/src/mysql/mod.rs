@@ -451,9 +451,9 @@ async fn read_by_tag(
         let mut conn = self.pool.get()?;
 
         let sql = format!(
-            "SELECT `r.offset`,`r.key`,`r.data`,`r.header`,`r.tags`,`r.ts`
+            "SELECT r.`offset`, r.`key`, r.`data`, r.`header`, r.`tags`, r.`ts`
             FROM
-                `{}` l LEFT JOIN `{}` r on l.m_offset = r.offset
+                `{}` l LEFT JOIN `{}` r on l.m_offset = r.`offset`
             WHERE l.tag = :tag and l.m_offset >= :offset and l.namespace = :namespace and l.shard = :shard
             ORDER BY l.m_offset
             LIMIT :limit",
@@ -631,6 +631,135 @@ async fn commit_offset(
         Ok(())
     }
 
+    async fn delete_by_offsets(
+        &self,
+        namespace: String,
+        shard_name: String,
+        offsets: Vec<u64>,
+    ) -> Result<(), CommonError> {
+        let mut conn = self.pool.get()?;
+
+        let delete_record_sql = format!(
+            "DELETE FROM `{}` WHERE `offset` = :offset",
+            Self::record_table_name(&namespace, &shard_name)
+        );
+
+        conn.exec_batch(
+            delete_record_sql,
+            offsets.iter().map(|offset| {
+                params! {
+                    "offset" => offset,
+                }
+            }),
+        )?;
+
+        let delete_tags_sql = format!(
+            "DELETE FROM `{}` WHERE `namespace` = :namespace AND `shard` = :shard AND `m_offset` = :m_offset",
+            Self::tags_table_name()
+        );
+
+        conn.exec_batch(
+            delete_tags_sql,
+            offsets.iter().map(|offset| {
+                params! {
+                    "namespace" => namespace.clone(),
+                    "shard" => shard_name.clone(),
+                    "m_offset" => offset,
+                }
+            }),
+        )?;
+
+        Ok(())
+    }
+
+    async fn delete_before_offset(
+        &self,
+        namespace: String,
+        shard_name: String,
+        offset: u64,
+    ) -> Result<(), CommonError> {
+        let mut conn = self.pool.get()?;
+
+        let delete_record_sql = format!(
+            "DELETE FROM `{}` WHERE `offset` < :offset",
+            Self::record_table_name(&namespace, &shard_name)
+        );
+
+        conn.exec_drop(
+            delete_record_sql,
+            params! {
+                "offset" => offset,
+            },
+        )?;
+
+        let delete_tags_sql = format!(
+            "DELETE FROM `{}` WHERE `namespace` = :namespace AND `shard` = :shard AND `m_offset` < :m_offset",
+            Self::tags_table_name()
+        );
+
+        conn.exec_drop(
+            delete_tags_sql,
+            params! {
+                "namespace" => namespace,
+                "shard" => shard_name,
+                "m_offset" => offset,
+            },
+        )?;
+
+        Ok(())
+    }
+
+    async fn delete_before_timestamp(
+        &self,
+        namespace: String,
+        shard_name: String,
+        timestamp: u64,
+    ) -> Result<(), CommonError> {
+        let offsets: Vec<u64> = {
+            let mut conn = self.pool.get()?;
+
+            let sql = format!(
+                "SELECT `offset` FROM `{}` WHERE `ts` < :ts",
+                Self::record_table_name(&namespace, &shard_name)
+            );
+
+            conn.exec(
+                sql,
+                params! {
+                    "ts" => timestamp,
+                },
+            )?
+        };
+
+        self.delete_by_offsets(namespace, shard_name, offsets).await
+    }
+
+    async fn compact_by_key(
+        &self,
+        namespace: String,
+        shard_name: String,
+    ) -> Result<(), CommonError> {
+        let offsets: Vec<u64> = {
+            let mut conn = self.pool.get()?;
+
+            let table_name = Self::record_table_name(&namespace, &shard_name);
+            let sql = format!(
+                "SELECT r.`offset`
+                FROM `{table_name}` r
+                JOIN (
+                    SELECT `key`, MAX(`offset`) AS max_offset
+                    FROM `{table_name}`
+                    WHERE `key` IS NOT NULL AND `key` <> ''
+                    GROUP BY `key`
+                ) l ON r.`key` = l.`key` AND r.`offset` < l.max_offset"
+            );
+
+            conn.query(sql)?
+        };
+
+        self.delete_by_offsets(namespace, shard_name, offsets).await
+    }
+
     async fn close(&self) -> Result<(), CommonError> {
         self.stop_send.send(true).await.map_err(|err| {
             CommonError::CommonError(format!("Failed to send stop signal: {err}"))
@@ -1103,4 +1232,18 @@ async fn mysql_concurrent_batch_write_test() {
 
         clean_resources(pool).await;
     }
+
+    async fn build_adapter() -> MySQLStorageAdapter {
+        let pool = build_mysql_conn_pool("mysql://root@127.0.0.1:3306/mqtt").unwrap();
+        MySQLStorageAdapter::new(pool).unwrap()
+    }
+
+    crate::conformance::storage_conformance_tests!(
+        #[ignore]
+        build_adapter
+    );
+    crate::conformance::storage_deletion_tests!(
+        #[ignore]
+        build_adapter
+    );
 }
@@ -0,0 +1,958 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::storage::{ShardInfo, ShardOffset, StorageAdapter};
+use axum::async_trait;
+use common_base::{error::common::CommonError, tools::unique_id, utils::crc::calc_crc32};
+use dashmap::DashMap;
+use metadata_struct::adapter::{read_config::ReadConfig, record::Record};
+use r2d2_postgres::postgres::{fallible_iterator::FallibleIterator, Client, Row};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Weak,
+    },
+    thread,
+    time::Duration,
+};
+use third_driver::postgresql::PostgresPool;
+use tokio::{sync::Notify, task::spawn_blocking, time::timeout};
+use tracing::warn;
+
+// Channel the writers notify after a commit, the payload is the shard id
+const WRITE_CHANNEL: &str = "mqtt_record_write";
+
+// Records of every shard live in one table partitioned by shard id. Each partition
+// gets its own BIGSERIAL-style sequence, so offsets start at 0 in every shard.
+#[derive(Clone)]
+pub struct PostgresStorageAdapter {
+    pool: PostgresPool,
+    // (namespace/shard_name, shard id)
+    shard_ids: Arc<DashMap<String, String>>,
+    // (shard id, readers waiting for a write)
+    write_notifiers: Arc<DashMap<String, Arc<Notify>>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl PostgresStorageAdapter {
+    pub fn new(pool: PostgresPool) -> Result<Self, CommonError> {
+        let mut conn = pool.get()?;
+        conn.batch_execute(&format!(
+            "CREATE TABLE IF NOT EXISTS {record} (
+                shard_id TEXT NOT NULL,
+                \"offset\" BIGINT NOT NULL,
+                key TEXT NOT NULL DEFAULT '',
+                data BYTEA,
+                header BYTEA,
+                tags TEXT[] NOT NULL DEFAULT '{{}}',
+                ts BIGINT NOT NULL,
+                PRIMARY KEY (shard_id, \"offset\")
+            ) PARTITION BY LIST (shard_id);
+            CREATE INDEX IF NOT EXISTS {record}_tags_idx ON {record} USING GIN (tags);
+            CREATE INDEX IF NOT EXISTS {record}_key_idx ON {record} (shard_id, key, \"offset\");
+            CREATE INDEX IF NOT EXISTS {record}_ts_idx ON {record} (shard_id, ts, \"offset\");
+
+            CREATE TABLE IF NOT EXISTS {shard} (
+                namespace TEXT NOT NULL,
+                shard TEXT NOT NULL,
+                shard_id TEXT NOT NULL,
+                info BYTEA NOT NULL,
+                PRIMARY KEY (namespace, shard)
+            );
+
+            CREATE TABLE IF NOT EXISTS {groups} (
+                group_name TEXT NOT NULL,
+                namespace TEXT NOT NULL,
+                shard TEXT NOT NULL,
+                \"offset\" BIGINT NOT NULL,
+                PRIMARY KEY (group_name, namespace, shard)
+            );",
+            record = Self::record_table_name(),
+            shard = Self::shard_table_name(),
+            groups = Self::groups_table_name(),
+        ))?;
+
+        let adapter = PostgresStorageAdapter {
+            pool,
+            shard_ids: Arc::new(DashMap::with_capacity(8)),
+            write_notifiers: Arc::new(DashMap::with_capacity(8)),
+            stop: Arc::new(AtomicBool::new(false)),
+        };
+        adapter.spawn_listen_thread();
+        Ok(adapter)
+    }
+
+    #[inline(always)]
+    pub fn record_table_name() -> String {
+        "mqtt_record".to_string()
+    }
+
+    #[inline(always)]
+    pub fn shard_table_name() -> String {
+        "mqtt_shard".to_string()
+    }
+
+    #[inline(always)]
+    pub fn groups_table_name() -> String {
+        "mqtt_group_offset".to_string()
+    }
+
+    #[inline(always)]
+    pub fn partition_table_name(shard_id: &str) -> String {
+        format!("mqtt_record_{shard_id}")
+    }
+
+    #[inline(always)]
+    fn shard_key(namespace: &str, shard_name: &str) -> String {
+        format!("{namespace}/{shard_name}")
+    }
+
+    // The postgres client blocks on a runtime of its own, so it must not run on a tokio worker
+    async fn with_conn<T, F>(&self, f: F) -> Result<T, CommonError>
+    where
+        F: FnOnce(&mut Client) -> Result<T, CommonError> + Send + 'static,
+        T: Send + 'static,
+    {
+        let pool = self.pool.clone();
+        spawn_blocking(move || {
+            let mut conn = pool.get()?;
+            f(&mut conn)
+        })
+        .await
+        .map_err(|e| CommonError::CommonError(format!("Postgres task failed: {e}")))?
+    }
+
+    async fn shard_id(&self, namespace: &str, shard_name: &str) -> Result<String, CommonError> {
+        let shard_key = Self::shard_key(namespace, shard_name);
+        if let Some(shard_id) = self.shard_ids.get(&shard_key) {
+            return Ok(shard_id.clone());
+        }
+
+        let sql = format!(
+            "SELECT shard_id FROM {} WHERE namespace = $1 AND shard = $2",
+            Self::shard_table_name()
+        );
+        let (ns, shard) = (namespace.to_owned(), shard_name.to_owned());
+        let row = self
+            .with_conn(move |conn| Ok(conn.query_opt(&sql, &[&ns, &shard])?))
+            .await?;
+
+        let Some(row) = row else {
+            return Err(CommonError::CommonError(format!(
+                "shard {shard_name} under namespace {namespace} does not exist"
+            )));
+        };
+        let shard_id: String = row.get(0);
+        self.shard_ids.insert(shard_key, shard_id.clone());
+        Ok(shard_id)
+    }
+
+    async fn handle_write_request(
+        &self,
+        namespace: String,
+        shard_name: String,
+        messages: Vec<Record>,
+    ) -> Result<Vec<u64>, CommonError> {
+        let shard_id = self.shard_id(&namespace, &shard_name).await?;
+        self.with_conn(move |conn| {
+            let sql = format!(
+                "INSERT INTO {} (shard_id, key, data, header, tags, ts) VALUES ($1, $2, $3, $4, $5, $6) RETURNING \"offset\"",
+                Self::partition_table_name(&shard_id)
+            );
+
+            let mut tx = conn.transaction()?;
+            // Offsets come from a sequence, so two writers could commit them out of order and a
+            // reader would move past the lower one before it is visible. Holding a per-shard lock
+            // until commit makes offsets become visible in order, across brokers as well.
+            tx.execute("SELECT pg_advisory_xact_lock(hashtext($1))", &[&shard_id])?;
+            let statement = tx.prepare(&sql)?;
+            let mut offsets = Vec::with_capacity(messages.len());
+            for message in messages {
+                let row = tx.query_one(
+                    &statement,
+                    &[
+                        &shard_id,
+                        &message.key,
+                        &message.data,
+                        &serde_json::to_vec(&message.header)?,
+                        &message.tags,
+                        &(message.timestamp as i64),
+                    ],
+                )?;
+                let offset: i64 = row.get(0);
+                offsets.push(offset as u64);
+            }
+
+            // Delivered to listeners once the transaction commits
+            tx.execute("SELECT pg_notify($1, $2)", &[&WRITE_CHANNEL, &shard_id])?;
+            tx.commit()?;
+            Ok(offsets)
+        })
+        .await
+    }
+
+    async fn read_records(
+        &self,
+        sql: String,
+        params: ReadParams,
+    ) -> Result<Vec<Record>, CommonError> {
+        self.with_conn(move |conn| {
+            let limit = params.limit as i64;
+            let offset = params.offset as i64;
+            let rows = match &params.filter {
+                Some(filter) => conn.query(&sql, &[&params.shard_id, &offset, filter, &limit])?,
+                None => conn.query(&sql, &[&params.shard_id, &offset, &limit])?,
+            };
+            rows.iter().map(row_to_record).collect()
+        })
+        .await
+    }
+
+    // Wakes the readers of a shard whenever a writer on any broker commits to it.
+    // The thread only holds a weak stop flag, so it also ends once the adapter is dropped.
+    fn spawn_listen_thread(&self) {
+        let pool = self.pool.clone();
+        let notifiers = self.write_notifiers.clone();
+        let stop = Arc::downgrade(&self.stop);
+
+        thread::spawn(move || {
+            while !is_stopped(&stop) {
+                if let Err(e) = listen_writes(&pool, &notifiers, &stop) {
+                    warn!(
+                        "Postgres storage adapter stopped listening for writes, error message: {}",
+                        e
+                    );
+                    // Readers may have missed a write while the listener was down
+                    for notify in notifiers.iter() {
+                        notify.notify_waiters();
+                    }
+                    thread::sleep(Duration::from_secs(1));
+                }
+            }
+        });
+    }
+}
+
+struct ReadParams {
+    shard_id: String,
+    offset: u64,
+    filter: Option<String>,
+    limit: u64,
+}
+
+fn is_stopped(stop: &Weak<AtomicBool>) -> bool {
+    stop.upgrade()
+        .is_none_or(|stop| stop.load(Ordering::Relaxed))
+}
+
+fn listen_writes(
+    pool: &PostgresPool,
+    notifiers: &DashMap<String, Arc<Notify>>,
+    stop: &Weak<AtomicBool>,
+) -> Result<(), CommonError> {
+    let mut conn = pool.get()?;
+    conn.batch_execute(&format!("LISTEN {WRITE_CHANNEL}"))?;
+
+    while !is_stopped(stop) {
+        let mut notifications = conn.notifications();
+        let mut iter = notifications.timeout_iter(Duration::from_secs(1));
+        while let Some(notification) = iter.next()? {
+            if let Some(notify) = notifiers.get(notification.payload()) {
+                notify.notify_waiters();
+            }
+        }
+    }
+    Ok(())
+}
+
+fn row_to_record(row: &Row) -> Result<Record, CommonError> {
+    let offset: i64 = row.get(0);
+    let data: Vec<u8> = row.get(2);
+    let header: Vec<u8> = row.get(3);
+    let ts: i64 = row.get(5);
+    Ok(Record {
+        offset: Some(offset as u64),
+        key: row.get(1),
+        crc_num: calc_crc32(&data),
+        data,
+        header: serde_json::from_slice(&header)?,
+        tags: row.get(4),
+        timestamp: ts as u64,
+    })
+}
+
+#[async_trait]
+impl StorageAdapter for PostgresStorageAdapter {
+    async fn create_shard(&self, shard: ShardInfo) -> Result<(), CommonError> {
+        let shard_id = unique_id();
+        let shard_key = Self::shard_key(&shard.namespace, &shard.shard_name);
+
+        let created_id = shard_id.clone();
+        self.with_conn(move |conn| {
+            let mut tx = conn.transaction()?;
+            let inserted = tx.execute(
+                &format!(
+                    "INSERT INTO {} (namespace, shard, shard_id, info) VALUES ($1, $2, $3, $4) ON CONFLICT DO NOTHING",
+                    Self::shard_table_name()
+                ),
+                &[
+                    &shard.namespace,
+                    &shard.shard_name,
+                    &shard_id,
+                    &serde_json::to_vec(&shard)?,
+                ],
+            )?;
+            if inserted == 0 {
+                return Err(CommonError::CommonError(format!(
+                    "shard {} under namespace {} already exists",
+                    shard.shard_name, shard.namespace
+                )));
+            }
+
+            // This is what BIGSERIAL expands to, set up on the partition so that it counts per shard
+            let partition = Self::partition_table_name(&shard_id);
+            tx.batch_execute(&format!(
+                "CREATE TABLE {partition} PARTITION OF {record} FOR VALUES IN ('{shard_id}');
+                CREATE SEQUENCE {partition}_offset_seq MINVALUE 0 START WITH 0 OWNED BY {partition}.\"offset\";
+                ALTER TABLE {partition} ALTER COLUMN \"offset\" SET DEFAULT nextval('{partition}_offset_seq');",
+                record = Self::record_table_name(),
+            ))?;
+            tx.commit()?;
+            Ok(())
+        })
+        .await?;
+
+        self.shard_ids.insert(shard_key, created_id);
+        Ok(())
+    }
+
+    async fn list_shard(
+        &self,
+        namespace: String,
+        shard_name: String,
+    ) -> Result<Vec<ShardInfo>, CommonError> {
+        self.with_conn(move |conn| {
+            let table = Self::shard_table_name();
+            let rows = if namespace.is_empty() {
+                conn.query(&format!("SELECT info FROM {table}"), &[])?
+            } else if shard_name.is_empty() {
+                conn.query(
+                    &format!("SELECT info FROM {table} WHERE namespace = $1"),
+                    &[&namespace],
+                )?
+            } else {
+                conn.query(
+                    &format!("SELECT info FROM {table} WHERE namespace = $1 AND shard = $2"),
+                    &[&namespace, &shard_name],
+                )?
+            };
+
+            let mut results = Vec::with_capacity(rows.len());
+            for row in rows {
+                let info: Vec<u8> = row.get(0);
+                results.push(serde_json::from_slice::<ShardInfo>(&info)?);
+            }
+            Ok(results)
+        })
+        .await
+    }
+
+    async fn delete_shard(&self, namespace: String, shard_name: String) -> Result<(), CommonError> {
+        let shard_id = self.shard_id(&namespace, &shard_name).await?;
+        self.shard_ids
+            .remove(&Self::shard_key(&namespace, &shard_name));
+        self.write_notifiers.remove(&shard_id);
+
+        self.with_conn(move |conn| {
+            let mut tx = conn.transaction()?;
+            // Dropping the partition drops the sequence it owns as well
+            tx.batch_execute(&format!(
+                "DROP TABLE IF EXISTS {}",
+                Self::partition_table_name(&shard_id)
+            ))?;
+            tx.execute(
+                &format!(
+                    "DELETE FROM {} WHERE namespace = $1 AND shard = $2",
+                    Self::shard_table_name()
+                ),
+                &[&namespace, &shard_name],
+            )?;
+            tx.commit()?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn write(
+        &self,
+        namespace: String,
+        shard_name: String,
+        data: Record,
+    ) -> Result<u64, CommonError> {
+        let offsets = self
+            .handle_write_request(namespace, shard_name, vec![data])
+            .await?;
+
+        offsets.first().cloned().ok_or(CommonError::CommonError(
+            "Failed to get offset. The vector is empty!".to_string(),
+        ))
+    }
+
+    async fn batch_write(
+        &self,
+        namespace: String,
+        shard_name: String,
+        data: Vec<Record>,
+    ) -> Result<Vec<u64>, CommonError> {
+        self.handle_write_request(namespace, shard_name, data).await
+    }
+
+    async fn read_by_offset(
+        &self,
+        namespace: String,
+        shard_name: String,
+        offset: u64,
+        read_config: ReadConfig,
+    ) -> Result<Vec<Record>, CommonError> {
+        let shard_id = self.shard_id(&namespace, &shard_name).await?;
+        let sql = format!(
+            "SELECT \"offset\", key, data, header, tags, ts FROM {}
+            WHERE shard_id = $1 AND \"offset\" >= $2
+            ORDER BY \"offset\"
+            LIMIT $3",
+            Self::record_table_name()
+        );
+        self.read_records(
+            sql,
+            ReadParams {
+                shard_id,
+                offset,
+                filter: None,
+                limit: read_config.max_record_num,
+            },
+        )
+        .await
+    }
+
+    async fn read_by_tag(
+        &self,
+        namespace: String,
+        shard_name: String,
+        offset: u64,
+        tag: String,
+        read_config: ReadConfig,
+    ) -> Result<Vec<Record>, CommonError> {
+        let shard_id = self.shard_id(&namespace, &shard_name).await?;
+        // The containment operator can use the GIN index on tags
+        let sql = format!(
+            "SELECT \"offset\", key, data, header, tags, ts FROM {}
+            WHERE shard_id = $1 AND \"offset\" >= $2 AND tags @> ARRAY[$3::TEXT]
+            ORDER BY \"offset\"
+            LIMIT $4",
+            Self::record_table_name()
+        );
+        self.read_records(
+            sql,
+            ReadParams {
+                shard_id,
+                offset,
+                filter: Some(tag),
+                limit: read_config.max_record_num,
+            },
+        )
+        .await
+    }
+
+    async fn read_by_key(
+        &self,
+        namespace: String,
+        shard_name: String,
+        offset: u64,
+        key: String,
+        read_config: ReadConfig,
+    ) -> Result<Vec<Record>, CommonError> {
+        let shard_id = self.shard_id(&namespace, &shard_name).await?;
+        let sql = format!(
+            "SELECT \"offset\", key, data, header, tags, ts FROM {}
+            WHERE shard_id = $1 AND \"offset\" >= $2 AND key = $3
+            ORDER BY \"offset\"
+            LIMIT $4",
+            Self::record_table_name()
+        );
+        self.read_records(
+            sql,
+            ReadParams {
+                shard_id,
+                offset,
+                filter: Some(key),
+                limit: read_config.max_record_num,
+            },
+        )
+        .await
+    }
+
+    async fn get_offset_by_timestamp(
+        &self,
+        namespace: String,
+        shard_name: String,
+        timestamp: u64,
+    ) -> Result<Option<ShardOffset>, CommonError> {
+        let shard_id = self.shard_id(&namespace, &shard_name).await?;
+        self.with_conn(move |conn| {
+            let sql = format!(
+                "SELECT \"offset\" FROM {}
+                WHERE shard_id = $1 AND ts >= $2
+                ORDER BY ts, \"offset\"
+                LIMIT 1",
+                Self::record_table_name()
+            );
+            let row = conn.query_opt(&sql, &[&shard_id, &(timestamp as i64)])?;
+            Ok(row.map(|row| {
+                let offset: i64 = row.get(0);
+                ShardOffset {
+                    namespace,
+                    shard_name,
+                    offset: offset as u64,
+                    ..Default::default()
+                }
+            }))
+        })
+        .await
+    }
+
+    async fn get_offset_by_group(
+        &self,
+        group_name: String,
+    ) -> Result<Vec<ShardOffset>, CommonError> {
+        self.with_conn(move |conn| {
+            let sql = format!(
+                "SELECT namespace, shard, \"offset\" FROM {} WHERE group_name = $1",
+                Self::groups_table_name()
+            );
+            let rows = conn.query(&sql, &[&group_name])?;
+            Ok(rows
+                .iter()
+                .map(|row| {
+                    let offset: i64 = row.get(2);
+                    ShardOffset {
+                        namespace: row.get(0),
+                        shard_name: row.get(1),
+                        offset: offset as u64,
+                        ..Default::default()
+                    }
+                })
+                .collect())
+        })
+        .await
+    }
+
+    async fn commit_offset(
+        &self,
+        group_name: String,
+        namespace: String,
+        offset: HashMap<String, u64>,
+    ) -> Result<(), CommonError> {
+        self.with_conn(move |conn| {
+            let sql = format!(
+                "INSERT INTO {} (group_name, namespace, shard, \"offset\") VALUES ($1, $2, $3, $4)
+                ON CONFLICT (group_name, namespace, shard) DO UPDATE SET \"offset\" = EXCLUDED.\"offset\"",
+                Self::groups_table_name()
+            );
+
+            let mut tx = conn.transaction()?;
+            let statement = tx.prepare(&sql)?;
+            for (shard, offset) in offset {
+                tx.execute(
+                    &statement,
+                    &[&group_name, &namespace, &shard, &(offset as i64)],
+                )?;
+            }
+            tx.commit()?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn delete_by_offsets(
+        &self,
+        namespace: String,
+        shard_name: String,
+        offsets: Vec<u64>,
+    ) -> Result<(), CommonError> {
+        let shard_id = self.shard_id(&namespace, &shard_name).await?;
+        self.with_conn(move |conn| {
+            let sql = format!(
+                "DELETE FROM {} WHERE shard_id = $1 AND \"offset\" = ANY($2)",
+                Self::record_table_name()
+            );
+            let offsets: Vec<i64> = offsets.into_iter().map(|offset| offset as i64).collect();
+            conn.execute(&sql, &[&shard_id, &offsets])?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn delete_before_offset(
+        &self,
+        namespace: String,
+        shard_name: String,
+        offset: u64,
+    ) -> Result<(), CommonError> {
+        let shard_id = self.shard_id(&namespace, &shard_name).await?;
+        self.with_conn(move |conn| {
+            let sql = format!(
+                "DELETE FROM {} WHERE shard_id = $1 AND \"offset\" < $2",
+                Self::record_table_name()
+            );
+            let offset = i64::try_from(offset).unwrap_or(i64::MAX);
+            conn.execute(&sql, &[&shard_id, &offset])?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn delete_before_timestamp(
+        &self,
+        namespace: String,
+        shard_name: String,
+        timestamp: u64,
+    ) -> Result<(), CommonError> {
+        let shard_id = self.shard_id(&namespace, &shard_name).await?;
+        self.with_conn(move |conn| {
+            let sql = format!(
+                "DELETE FROM {} WHERE shard_id = $1 AND ts < $2",
+                Self::record_table_name()
+            );
+            let timestamp = i64::try_from(timestamp).unwrap_or(i64::MAX);
+            conn.execute(&sql, &[&shard_id, &timestamp])?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn compact_by_key(
+        &self,
+        namespace: String,
+        shard_name: String,
+    ) -> Result<(), CommonError> {
+        let shard_id = self.shard_id(&namespace, &shard_name).await?;
+        self.with_conn(move |conn| {
+            let sql = format!(
+                "DELETE FROM {record} r
+                USING (
+                    SELECT key, MAX(\"offset\") AS max_offset FROM {record}
+                    WHERE shard_id = $1 AND key <> ''
+                    GROUP BY key
+                ) l
+                WHERE r.shard_id = $1 AND r.key = l.key AND r.\"offset\" < l.max_offset",
+                record = Self::record_table_name()
+            );
+            conn.execute(&sql, &[&shard_id])?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn close(&self) -> Result<(), CommonError> {
+        self.stop.store(true, Ordering::Relaxed);
+        for notify in self.write_notifiers.iter() {
+            notify.notify_waiters();
+        }
+        Ok(())
+    }
+
+    async fn wait_for_write(
+        &self,
+        namespace: String,
+        shard_name: String,
+        offset: u64,
+        wait_time: Duration,
+    ) -> Result<(), CommonError> {
+        let shard_id = self.shard_id(&namespace, &shard_name).await?;
+        let notify = self
+            .write_notifiers
+            .entry(shard_id.clone())
+            .or_insert_with(|| Arc::new(Notify::new()))
+            .clone();
+
+        // Register before checking the offset so that a write in between still wakes us
+        let notified = notify.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+
+        let written = self
+            .with_conn(move |conn| {
+                let sql = format!(
+                    "SELECT 1 FROM {} WHERE \"offset\" >= $1 LIMIT 1",
+                    Self::partition_table_name(&shard_id)
+                );
+                Ok(conn.query_opt(&sql, &[&(offset as i64)])?.is_some())
+            })
+            .await?;
+        if written {
+            return Ok(());
+        }
+
+        let _ = timeout(wait_time, notified).await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use common_base::tools::unique_id;
+    use metadata_struct::adapter::{
+        read_config::ReadConfig,
+        record::{Header, Record},
+    };
+    use third_driver::postgresql::build_postgresql_conn_pool;
+
+    use super::PostgresStorageAdapter;
+    use crate::storage::{ShardInfo, StorageAdapter};
+
+    const ADDR: &str = "host=127.0.0.1 port=5432 user=postgres password=postgres dbname=mqtt";
+
+    // The blocking client cannot connect from inside the test runtime
+    async fn build_adapter() -> PostgresStorageAdapter {
+        tokio::task::spawn_blocking(|| {
+            let pool = build_postgresql_conn_pool(ADDR).unwrap();
+            PostgresStorageAdapter::new(pool).unwrap()
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_create_shard() {
+        let adapter = build_adapter().await;
+        let namespace = unique_id();
+        let shard_name = "test/shard".to_string();
+        let shard = ShardInfo {
+            namespace: namespace.clone(),
+            shard_name: shard_name.clone(),
+            replica_num: 1,
+        };
+
+        adapter.create_shard(shard.clone()).await.unwrap();
+        assert!(adapter.create_shard(shard).await.is_err());
+
+        let shards = adapter
+            .list_shard(namespace.clone(), shard_name.clone())
+            .await
+            .unwrap();
+        assert_eq!(shards.len(), 1);
+        assert_eq!(shards[0].shard_name, shard_name);
+
+        adapter
+            .delete_shard(namespace.clone(), shard_name.clone())
+            .await
+            .unwrap();
+        assert!(adapter
+            .list_shard(namespace, shard_name)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_read_write() {
+        let adapter = build_adapter().await;
+        let namespace = unique_id();
+        let shard_name = "test".to_string();
+        adapter
+            .create_shard(ShardInfo {
+                namespace: namespace.clone(),
+                shard_name: shard_name.clone(),
+                replica_num: 1,
+            })
+            .await
+            .unwrap();
+
+        let mut data = Vec::new();
+        for i in 0..4 {
+            let mut record = Record::build_byte(format!("test{i}").into_bytes());
+            record.set_key(format!("k{}", i % 2));
+            record.set_tags(vec![format!("t{i}")]);
+            record.set_header(vec![Header {
+                name: "n1".to_string(),
+                value: "v1".to_string(),
+            }]);
+            record.timestamp = 1737600096 + i;
+            data.push(record);
+        }
+
+        let offsets = adapter
+            .batch_write(namespace.clone(), shard_name.clone(), data)
+            .await
+            .unwrap();
+        assert_eq!(offsets, vec![0, 1, 2, 3]);
+
+        let read_config = ReadConfig {
+            max_record_num: 10,
+            ..Default::default()
+        };
+        let records = adapter
+            .read_by_offset(
+                namespace.clone(),
+                shard_name.clone(),
+                1,
+                read_config.clone(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].offset, Some(1));
+        assert_eq!(records[0].data, b"test1".to_vec());
+        assert_eq!(records[0].header[0].value, "v1");
+        assert!(records[0].crc32_check());
+
+        let records = adapter
+            .read_by_tag(
+                namespace.clone(),
+                shard_name.clone(),
+                0,
+                "t2".to_string(),
+                read_config.clone(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].offset, Some(2));
+
+        let records = adapter
+            .read_by_key(
+                namespace.clone(),
+                shard_name.clone(),
+                0,
+                "k1".to_string(),
+                read_config.clone(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(records.len(), 2);
+
+        let offset = adapter
+            .get_offset_by_timestamp(namespace.clone(), shard_name.clone(), 1737600098)
+            .await
+            .unwrap();
+        assert_eq!(offset.unwrap().offset, 2);
+
+        let group = unique_id();
+        let mut offset_data = HashMap::new();
+        offset_data.insert(shard_name.clone(), 2);
+        adapter
+            .commit_offset(group.clone(), namespace.clone(), offset_data.clone())
+            .await
+            .unwrap();
+        offset_data.insert(shard_name.clone(), 3);
+        adapter
+            .commit_offset(group.clone(), namespace.clone(), offset_data)
+            .await
+            .unwrap();
+        let offsets = adapter.get_offset_by_group(group).await.unwrap();
+        assert_eq!(offsets.len(), 1);
+        assert_eq!(offsets[0].offset, 3);
+        assert_eq!(offsets[0].shard_name, shard_name);
+
+        adapter
+            .delete_by_offsets(namespace.clone(), shard_name.clone(), vec![0, 1])
+            .await
+            .unwrap();
+        let records = adapter
+            .read_by_offset(namespace.clone(), shard_name.clone(), 0, read_config)
+            .await
+            .unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].offset, Some(2));
+
+        adapter.delete_shard(namespace, shard_name).await.unwrap();
+        adapter.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn postgres_concurrent_write_read() {
+        let adapter = build_adapter().await;
+        let namespace = unique_id();
+        let shard_name = "test".to_string();
+        adapter
+            .create_shard(ShardInfo {
+                namespace: namespace.clone(),
+                shard_name: shard_name.clone(),
+                replica_num: 1,
+            })
+            .await
+            .unwrap();
+
+        let writer_num = 8;
+        let record_num = 50;
+        let mut writers = Vec::new();
+        for w in 0..writer_num {
+            let adapter = adapter.clone();
+            let (namespace, shard_name) = (namespace.clone(), shard_name.clone());
+            writers.push(tokio::spawn(async move {
+                for i in 0..record_num {
+                    let record = Record::build_byte(format!("w{w}-{i}").into_bytes());
+                    adapter
+                        .write(namespace.clone(), shard_name.clone(), record)
+                        .await
+                        .unwrap();
+                }
+            }));
+        }
+
+        // A reader that follows the writers must see every offset, in order
+        let total = (writer_num * record_num) as u64;
+        let read_config = ReadConfig {
+            max_record_num: 10,
+            ..Default::default()
+        };
+        let mut next_offset = 0;
+        while next_offset < total {
+            let records = adapter
+                .read_by_offset(
+                    namespace.clone(),
+                    shard_name.clone(),
+                    next_offset,
+                    read_config.clone(),
+                )
+                .await
+                .unwrap();
+            for record in records {
+                assert_eq!(record.offset, Some(next_offset));
+                next_offset += 1;
+            }
+        }
+
+        for writer in writers {
+            writer.await.unwrap();
+        }
+        adapter.delete_shard(namespace, shard_name).await.unwrap();
+        adapter.close().await.unwrap();
+    }
+
+    crate::conformance::storage_conformance_tests!(
+        #[ignore]
+        build_adapter
+    );
+    crate::conformance::storage_deletion_tests!(
+        #[ignore]
+        build_adapter
+    );
+}
@@ -16,7 +16,7 @@
 
 use axum::async_trait;
 use common_base::error::common::CommonError;
-use dashmap::DashMap;
+use dashmap::{mapref::entry::Entry, DashMap};
 use metadata_struct::adapter::{read_config::ReadConfig, record::Record};
 use rocksdb_engine::RocksDBEngine;
 use tokio::{
@@ -24,12 +24,12 @@
     sync::{
         broadcast,
         mpsc::{self, Receiver},
-        oneshot,
+        oneshot, Notify,
     },
     time::{sleep, timeout},
 };
 
-use crate::storage::{ShardInfo, ShardOffset, StorageAdapter};
+use crate::storage::{superseded_offsets, ShardInfo, ShardOffset, StorageAdapter};
 
 const DB_COLUMN_FAMILY: &str = "db";
 
@@ -41,6 +41,7 @@ fn column_family_list() -> Vec<String> {
 pub struct RocksDBStorageAdapter {
     pub db: Arc<RocksDBEngine>,
     write_handles: DashMap<String, ThreadWriteHandle>,
+    write_notifiers: DashMap<String, Arc<Notify>>,
 }
 
 struct WriteThreadData {
@@ -81,6 +82,7 @@ pub fn new(db_path: impl AsRef<str>, max_open_files: i32) -> Self {
                 column_family_list(),
             )),
             write_handles: DashMap::with_capacity(2),
+            write_notifiers: DashMap::with_capacity(2),
         }
     }
 
@@ -171,6 +173,7 @@ async fn handle_write_request(
         messages: Vec<Record>,
     ) -> Result<Vec<u64>, CommonError> {
         let write_handle = self.get_write_handle(&namespace, &shard_name).await;
+        let handle_key = Self::write_handle_key(&namespace, &shard_name);
 
         let (resp_sx, resp_rx) = oneshot::channel();
 
@@ -180,12 +183,19 @@ async fn handle_write_request(
             CommonError::CommonError(format!("Failed to send data to write thread: {err}"))
         })?;
 
-        timeout(Duration::from_secs(30), resp_rx)
+        let offsets = timeout(Duration::from_secs(30), resp_rx)
             .await
             .map_err(|err| {
                 CommonError::CommonError(format!("Timeout while waiting for response: {err}"))
             })?
-            .map_err(|err| CommonError::CommonError(format!("Failed to receive response: {err}")))?
+            .map_err(|err| {
+                CommonError::CommonError(format!("Failed to receive response: {err}"))
+            })??;
+
+        if let Some(notify) = self.write_notifiers.get(&handle_key) {
+            notify.notify_waiters();
+        }
+        Ok(offsets)
     }
 
     async fn get_write_handle(
@@ -217,7 +227,16 @@ async fn register_write_handle(
         handle: ThreadWriteHandle,
     ) {
         let handle_key = Self::write_handle_key(namespace, shard_name);
-        self.write_handles.insert(handle_key, handle);
+
+        // Concurrent first writes to a shard may each start a write thread, keep only one
+        match self.write_handles.entry(handle_key) {
+            Entry::Occupied(_) => {
+                let _ = handle.stop_sender.send(true);
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(handle);
+            }
+        }
     }
 
     async fn create_write_thread(&self, namespace: impl AsRef<str>, shard_name: impl AsRef<str>) {
@@ -437,22 +456,27 @@ async fn read_by_offset(
 
         let mut total_size = 0;
 
-        for i in offset..offset.saturating_add(read_config.max_record_num) {
-            let shard_record_key = Self::shard_record_key(&namespace, &shard_name, i);
-            let record = self.db.read::<Record>(cf.clone(), &shard_record_key)?;
-
-            if record.is_none() {
-                break;
-            }
+        // Records may have been deleted, so scan forward from the offset instead
+        // of stopping at the first missing one.
+        let shard_record_key_prefix = Self::shard_record_key_prefix(&namespace, &shard_name);
+        let start_record_key = Self::shard_record_key(&namespace, &shard_name, offset);
+        let raw_records = self.db.read_prefix_from(
+            cf,
+            &shard_record_key_prefix,
+            &start_record_key,
+            read_config.max_record_num as usize,
+        )?;
 
-            let record_bytes = record.as_ref().unwrap().data.len() as u64;
+        for (_, v) in raw_records {
+            let record = serde_json::from_slice::<Record>(&v)?;
+            let record_bytes = record.data.len() as u64;
 
             if total_size + record_bytes > read_config.max_size {
                 break;
             }
 
             total_size += record_bytes;
-            records.push(record.unwrap());
+            records.push(record);
         }
 
         Ok(records)
@@ -522,7 +546,7 @@ async fn read_by_key(
 
         match self.db.read::<u64>(cf.clone(), &key_offset_key)? {
             Some(key_offset) if key_offset >= offset && read_config.max_record_num >= 1 => {
-                let shard_record_key = Self::shard_record_key(&namespace, &shard_name, offset);
+                let shard_record_key = Self::shard_record_key(&namespace, &shard_name, key_offset);
                 let record = self
                     .db
                     .read::<Record>(cf.clone(), &shard_record_key)?
@@ -613,6 +637,112 @@ async fn commit_offset(
         Ok(())
     }
 
+    async fn delete_by_offsets(
+        &self,
+        namespace: String,
+        shard_name: String,
+        offsets: Vec<u64>,
+    ) -> Result<(), CommonError> {
+        self.ensure_shard_exists(&namespace, &shard_name)?;
+
+        let cf = self.db.cf_handle(DB_COLUMN_FAMILY).unwrap();
+
+        for offset in offsets {
+            let shard_record_key = Self::shard_record_key(&namespace, &shard_name, offset);
+            let Some(record) = self.db.read::<Record>(cf.clone(), &shard_record_key)? else {
+                continue;
+            };
+
+            // drop the tag and key indexes that point at this record
+            for tag in record.tags.iter() {
+                let tag_offsets_key = Self::tag_offsets_key(&namespace, &shard_name, tag, offset);
+                self.db.delete(cf.clone(), &tag_offsets_key)?;
+            }
+
+            if !record.key.is_empty() {
+                let key_offset_key = Self::key_offset_key(&namespace, &shard_name, &record.key);
+                if self.db.read::<u64>(cf.clone(), &key_offset_key)? == Some(offset) {
+                    self.db.delete(cf.clone(), &key_offset_key)?;
+                }
+            }
+
+            self.db.delete(cf.clone(), &shard_record_key)?;
+        }
+
+        Ok(())
+    }
+
+    async fn delete_before_offset(
+        &self,
+        namespace: String,
+        shard_name: String,
+        offset: u64,
+    ) -> Result<(), CommonError> {
+        self.ensure_shard_exists(&namespace, &shard_name)?;
+
+        let cf = self.db.cf_handle(DB_COLUMN_FAMILY).unwrap();
+
+        let shard_record_key_prefix = Self::shard_record_key_prefix(&namespace, &shard_name);
+        let end_record_key = Self::shard_record_key(&namespace, &shard_name, offset);
+
+        let mut offsets = Vec::new();
+        for (k, v) in self.db.read_prefix(cf, &shard_record_key_prefix)? {
+            if k >= end_record_key {
+                break;
+            }
+            let record = serde_json::from_slice::<Record>(&v)?;
+            offsets.extend(record.offset);
+        }
+
+        self.delete_by_offsets(namespace, shard_name, offsets).await
+    }
+
+    async fn delete_before_timestamp(
+        &self,
+        namespace: String,
+        shard_name: String,
+        timestamp: u64,
+    ) -> Result<(), CommonError> {
+        self.ensure_shard_exists(&namespace, &shard_name)?;
+
+        let cf = self.db.cf_handle(DB_COLUMN_FAMILY).unwrap();
+
+        let shard_record_key_prefix = Self::shard_record_key_prefix(&namespace, &shard_name);
+
+        let mut offsets = Vec::new();
+        for (_, v) in self.db.read_prefix(cf, &shard_record_key_prefix)? {
+            let record = serde_json::from_slice::<Record>(&v)?;
+            if record.timestamp < timestamp {
+                offsets.extend(record.offset);
+            }
+        }
+
+        self.delete_by_offsets(namespace, shard_name, offsets).await
+    }
+
+    async fn compact_by_key(
+        &self,
+        namespace: String,
+        shard_name: String,
+    ) -> Result<(), CommonError> {
+        self.ensure_shard_exists(&namespace, &shard_name)?;
+
+        let cf = self.db.cf_handle(DB_COLUMN_FAMILY).unwrap();
+
+        let shard_record_key_prefix = Self::shard_record_key_prefix(&namespace, &shard_name);
+
+        let mut entries = Vec::new();
+        for (_, v) in self.db.read_prefix(cf, &shard_record_key_prefix)? {
+            let record = serde_json::from_slice::<Record>(&v)?;
+            if let Some(offset) = record.offset {
+                entries.push((record.key, offset));
+            }
+        }
+
+        self.delete_by_offsets(namespace, shard_name, superseded_offsets(entries))
+            .await
+    }
+
     async fn close(&self) -> Result<(), CommonError> {
         let write_handles = self.get_all_write_handles().await;
 
@@ -625,6 +755,38 @@ async fn close(&self) -> Result<(), CommonError> {
 
         Ok(())
     }
+
+    async fn wait_for_write(
+        &self,
+        namespace: String,
+        shard_name: String,
+        offset: u64,
+        wait_time: Duration,
+    ) -> Result<(), CommonError> {
+        let notify = self
+            .write_notifiers
+            .entry(Self::write_handle_key(&namespace, &shard_name))
+            .or_insert_with(|| Arc::new(Notify::new()))
+            .clone();
+
+        // Register before checking the offset so that a write in between still wakes us
+        let notified = notify.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+
+        let cf = self.db.cf_handle(DB_COLUMN_FAMILY).unwrap();
+        let shard_offset_key = Self::shard_offset_key(&namespace, &shard_name);
+        if self
+            .db
+            .read::<u64>(cf, shard_offset_key.as_str())?
+            .is_some_and(|next_offset| next_offset > offset)
+        {
+            return Ok(());
+        }
+
+        let _ = timeout(wait_time, notified).await;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -1042,4 +1204,79 @@ async fn concurrency_test() {
 
         let _ = std::fs::remove_dir_all(&db_path);
     }
+
+    #[tokio::test]
+    async fn delete_by_offsets_test() {
+        let db_path = format!("/tmp/robustmq_{}", unique_id());
+
+        let storage_adapter = RocksDBStorageAdapter::new(db_path.as_str(), 100);
+        let namespace = unique_id();
+        let shard_name = "test-delete".to_string();
+
+        storage_adapter
+            .create_shard(ShardInfo {
+                namespace: namespace.clone(),
+                shard_name: shard_name.clone(),
+                replica_num: 1,
+            })
+            .await
+            .unwrap();
+
+        let records = (0..4)
+            .map(|i| {
+                let mut record = Record::build_byte(format!("test{i}").as_bytes().to_vec());
+                record.set_tags(vec!["t1".to_string()]);
+                record
+            })
+            .collect();
+        storage_adapter
+            .batch_write(namespace.clone(), shard_name.clone(), records)
+            .await
+            .unwrap();
+
+        storage_adapter
+            .delete_by_offsets(namespace.clone(), shard_name.clone(), vec![0, 1, 9])
+            .await
+            .unwrap();
+
+        let read_config = ReadConfig {
+            max_record_num: 10,
+            max_size: u64::MAX,
+        };
+        let res = storage_adapter
+            .read_by_offset(
+                namespace.clone(),
+                shard_name.clone(),
+                0,
+                read_config.clone(),
+            )
+            .await
+            .unwrap();
+        let offsets: Vec<u64> = res.iter().map(|r| r.offset.unwrap()).collect();
+        assert_eq!(offsets, vec![2, 3]);
+
+        let res = storage_adapter
+            .read_by_tag(
+                namespace.clone(),
+                shard_name.clone(),
+                0,
+                "t1".to_string(),
+                read_config,
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.len(), 2);
+
+        storage_adapter.close().await.unwrap();
+
+        let _ = std::fs::remove_dir_all(&db_path);
+    }
+
+    async fn build_adapter() -> RocksDBStorageAdapter {
+        let db_path = format!("/tmp/robustmq_{}", unique_id());
+        RocksDBStorageAdapter::new(db_path.as_str(), 100)
+    }
+
+    crate::conformance::storage_conformance_tests!(build_adapter);
+    crate::conformance::storage_deletion_tests!(build_adapter);
 }
@@ -14,15 +14,27 @@
 
 use axum::async_trait;
 use common_base::error::common::CommonError;
+use futures::stream::{self, BoxStream, StreamExt};
 use metadata_struct::adapter::read_config::ReadConfig;
 use metadata_struct::adapter::record::Record;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::time::sleep;
 
 use crate::memory::MemoryStorageAdapter;
 
 pub type ArcStorageAdapter = Arc<Box<dyn StorageAdapter + Send + Sync>>;
 
+// Adapters that cannot tell when a shard is written are polled at this interval
+const WRITE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// Longest single wait of read_stream, it reads the shard again afterwards
+const READ_STREAM_WAIT_TIME: Duration = Duration::from_secs(5);
+
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub struct ShardInfo {
     pub namespace: String,
@@ -38,6 +50,18 @@ pub struct ShardOffset {
     pub offset: u64,
 }
 
+/// How finely an adapter can delete records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeletionSupport {
+    /// Records are never deleted, the delete calls return NotSupportFeature
+    None,
+    /// Records go away a whole segment at a time, so some of the records a
+    /// delete asks for may stay stored
+    Segment,
+    /// Every record asked for is deleted
+    Record,
+}
+
 #[async_trait]
 pub trait StorageAdapter {
     async fn create_shard(&self, shard: ShardInfo) -> Result<(), CommonError>;
@@ -109,9 +133,161 @@ async fn commit_offset(
         offset: HashMap<String, u64>,
     ) -> Result<(), CommonError>;
 
+    /// Delete the records stored at the given offsets of a shard. Offsets that
+    /// no longer exist are ignored, and the offsets of the remaining records
+    /// do not change. An adapter that cannot delete all of them returns
+    /// PartialDeletion instead of Ok, this also holds for the other deletes.
+    async fn delete_by_offsets(
+        &self,
+        namespace: String,
+        shard_name: String,
+        offsets: Vec<u64>,
+    ) -> Result<(), CommonError>;
+
+    /// Delete every record of a shard stored below `offset`. The shard keeps
+    /// handing out offsets after its last record, so truncating it does not
+    /// reuse offsets.
+    async fn delete_before_offset(
+        &self,
+        namespace: String,
+        shard_name: String,
+        offset: u64,
+    ) -> Result<(), CommonError>;
+
+    /// Delete every record of a shard whose timestamp is below `timestamp`.
+    async fn delete_before_timestamp(
+        &self,
+        namespace: String,
+        shard_name: String,
+        timestamp: u64,
+    ) -> Result<(), CommonError>;
+
+    /// Keep only the latest record of every key in a shard. Records without a
+    /// key are left alone.
+    async fn compact_by_key(
+        &self,
+        namespace: String,
+        shard_name: String,
+    ) -> Result<(), CommonError>;
+
     async fn close(&self) -> Result<(), CommonError>;
+
+    /// Features that depend on deleted records being gone check this before
+    /// they start, rather than finding out from failed deletes later.
+    fn deletion_support(&self) -> DeletionSupport {
+        DeletionSupport::Record
+    }
+
+    /// Wait until the shard may have a record at or after `offset`, or until
+    /// `wait_time` has passed. Returning does not promise a record, callers
+    /// read the shard again either way. Adapters without write notifications
+    /// only sleep for a short poll interval.
+    async fn wait_for_write(
+        &self,
+        _namespace: String,
+        _shard_name: String,
+        _offset: u64,
+        wait_time: Duration,
+    ) -> Result<(), CommonError> {
+        sleep(wait_time.min(WRITE_POLL_INTERVAL)).await;
+        Ok(())
+    }
+
+    /// Stream the records of a shard from `offset` on. Once the stream has
+    /// caught up it waits for new writes instead of ending. A failed read is
+    /// yielded as an error and the next poll reads from the same offset again.
+    fn read_stream(
+        &self,
+        namespace: String,
+        shard_name: String,
+        offset: u64,
+        read_config: ReadConfig,
+    ) -> BoxStream<'_, Result<Record, CommonError>>
+    where
+        Self: Sync,
+    {
+        let state = ReadStreamState {
+            namespace,
+            shard_name,
+            next_offset: offset,
+            read_config,
+            buffer: VecDeque::new(),
+        };
+
+        stream::unfold((self, state), |(adapter, mut state)| async move {
+            loop {
+                if let Some(record) = state.buffer.pop_front() {
+                    return Some((Ok(record), (adapter, state)));
+                }
+
+                let records = match adapter
+                    .read_by_offset(
+                        state.namespace.clone(),
+                        state.shard_name.clone(),
+                        state.next_offset,
+                        state.read_config.clone(),
+                    )
+                    .await
+                {
+                    Ok(records) => records,
+                    Err(e) => return Some((Err(e), (adapter, state))),
+                };
+
+                if records.is_empty() {
+                    if let Err(e) = adapter
+                        .wait_for_write(
+                            state.namespace.clone(),
+                            state.shard_name.clone(),
+                            state.next_offset,
+                            READ_STREAM_WAIT_TIME,
+                        )
+                        .await
+                    {
+                        return Some((Err(e), (adapter, state)));
+                    }
+                    continue;
+                }
+
+                state.next_offset = match records.last().and_then(|record| record.offset) {
+                    Some(offset) => offset + 1,
+                    None => state.next_offset + records.len() as u64,
+                };
+                state.buffer.extend(records);
+            }
+        })
+        .boxed()
+    }
+}
+
+struct ReadStreamState {
+    namespace: String,
+    shard_name: String,
+    next_offset: u64,
+    read_config: ReadConfig,
+    buffer: VecDeque<Record>,
 }
 
 pub fn build_memory_storage_driver() -> ArcStorageAdapter {
     Arc::new(Box::new(MemoryStorageAdapter::new()))
 }
+
+// Offsets of the records that key compaction removes. `entries` pairs each
+// record key with its offset and has to come in offset order. Records
+// without a key are never superseded.
+pub(crate) fn superseded_offsets<K: AsRef<str>>(
+    entries: impl IntoIterator<Item = (K, u64)>,
+) -> Vec<u64> {
+    let mut latest: HashMap<String, u64> = HashMap::new();
+    let mut superseded = Vec::new();
+    for (key, offset) in entries {
+        let key = key.as_ref();
+        if key.is_empty() {
+            continue;
+        }
+        if let Some(previous) = latest.insert(key.to_owned(), offset) {
+            superseded.push(previous);
+        }
+    }
+    superseded.sort_unstable();
+    superseded
+}